@@ -0,0 +1,52 @@
+// Benchmarks of recursive permission walks
+//
+// Run with `cargo bench --bench walk`; the tree is built once in a temporary directory.
+use alhalo::audit::context::AuditContext;
+use alhalo::{Importance, PermissionRules, ScanControl};
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::collections::HashSet;
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+use std::sync::Arc;
+
+// 20 directories of 10 subdirectories with 25 files each: 5,000 files, 221 directories
+fn build_tree(root: &Path) {
+    for a in 0..20 {
+        for b in 0..10 {
+            let dir = root.join(format!("d{}/s{}", a, b));
+            fs::create_dir_all(&dir).unwrap();
+            for f in 0..25 {
+                fs::write(dir.join(format!("f{}", f)), "").unwrap();
+            }
+        }
+    }
+}
+
+fn walk(c: &mut Criterion) {
+    let tree = tempfile::tempdir().unwrap();
+    build_tree(tree.path());
+    let (rule, _) = PermissionRules::new(tree.path().to_path_buf(), 0o644, Importance::Low);
+    let rule = PermissionRules { recursive: true, ..rule };
+
+    let mut group = c.benchmark_group("walk");
+    group.sample_size(20);
+    group.bench_function("check", |b| b.iter(|| black_box(rule.check(&mut HashSet::new()))));
+    group.bench_function("check_iter", |b| b.iter(|| black_box(rule.check_iter().count())));
+    group.bench_function("check_with_cache", |b| {
+        b.iter(|| {
+            let mut control = ScanControl::default().with_context(Arc::new(AuditContext::cached()));
+            black_box(rule.check_with(&mut HashSet::new(), &mut control))
+        })
+    });
+    group.bench_function("check_parallel", |b| {
+        b.iter(|| {
+            let mut control = ScanControl::default().with_jobs(4);
+            black_box(rule.check_with(&mut HashSet::new(), &mut control))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, walk);
+criterion_main!(benches);
@@ -0,0 +1,33 @@
+/*
+ * C ABI of the alhalo library (feature `ffi`), see the `alhalo::ffi` docs.
+ *
+ * Build: cargo rustc --release --lib --features ffi --crate-type cdylib
+ * Link:  -L target/release -lalhalo
+ *
+ * Every function returns a JSON string: the audit report, or
+ * {"error": "<message>", "code": "<code>"} with a stable code such as "E_PATH_NOT_FOUND".
+ * Release it with halo_string_free.
+ */
+#ifndef HALO_H
+#define HALO_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Audits `path` (recursively for a directory) against `expected_mode`, octal ("640") or
+ * symbolic ("rw-r-----"), with `importance` "high", "medium" or "low". A missing or
+ * unreadable path is an error with code E_PATH_NOT_FOUND or E_PERM_DENIED. */
+char *halo_audit_path(const char *path, const char *expected_mode, const char *importance);
+
+/* Runs a built-in target ("user", "all", ...) or benchmark profile ("cis-l1", "cis-l2"). */
+char *halo_audit_profile(const char *profile);
+
+/* Releases a string returned by the functions above; NULL is ignored. */
+void halo_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* HALO_H */
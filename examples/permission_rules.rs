@@ -13,7 +13,6 @@ fn main() {
     match status {
         PathStatus::NotFound => {
             eprintln!("Warning: Path {} not found", rule.path.display());
-            return;
         }
         _ => {
             // Print the results using trait-based rendering
@@ -0,0 +1,154 @@
+//! Acknowledgment of findings.
+//!
+//! Some findings are accepted exceptions: a service that must run with a login shell, a
+//! sysctl that differs for a good reason, a world-readable file that must stay so.
+//! Acknowledging a finding records who accepted it and why, marks it in later reports and
+//! excludes it from the failure count. Findings, permission results and ownership results can
+//! be acknowledged, see [`Acknowledgeable`].
+//!
+//! An acknowledgment is tied to the finding's state when it was made (its
+//! [fingerprint](Acknowledgeable::fingerprint)). If the finding later changes — a different
+//! severity or message, for example a sysctl taking another wrong value — the acknowledgment
+//! no longer applies and the finding is reported as failing again.
+//!
+//! Acknowledgments are stored in the state directory, see
+//! [`StateStore::acknowledge`](crate::state::StateStore::acknowledge).
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::ack::{Acknowledgment, apply_acknowledgments};
+//! use alhalo::{Finding, Severity};
+//! let mut findings = vec![Finding::fail("sysctl", "/proc/sys/net/ipv4/ip_forward", Severity::Medium, "ip_forward = 1")];
+//! let ack = Acknowledgment::new(&findings[0], "router, ticket #123");
+//! assert_eq!(apply_acknowledgments(&mut findings, &[ack]), 1);
+//! assert!(findings[0].acknowledged.is_some());
+//! ```
+use crate::audit::finding::Finding;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A result that can be acknowledged: a [`Finding`], a permission or an ownership result.
+pub trait Acknowledgeable {
+    /// Stable id of the result, shown in reports and passed to `halo ack`.
+    fn ack_id(&self) -> String;
+    /// Hash of the result's state; acknowledgments only apply while it is unchanged.
+    fn fingerprint(&self) -> String;
+    /// Returns true if the result is a failure.
+    fn is_failure(&self) -> bool;
+    /// Acknowledgment covering the result, set by [`apply_acknowledgments`].
+    fn acknowledgment_mut(&mut self) -> &mut Option<Acknowledgment>;
+}
+
+impl Acknowledgeable for Finding {
+    fn ack_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn fingerprint(&self) -> String {
+        Finding::fingerprint(self)
+    }
+
+    fn is_failure(&self) -> bool {
+        Finding::is_failure(self)
+    }
+
+    fn acknowledgment_mut(&mut self) -> &mut Option<Acknowledgment> {
+        &mut self.acknowledged
+    }
+}
+
+/// An accepted exception for a single finding.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Acknowledgment {
+    /// Id of the acknowledged finding
+    pub id: String,
+    /// Fingerprint of the finding's state when it was acknowledged
+    pub fingerprint: String,
+    /// Why the finding is accepted
+    pub comment: String,
+    /// Unix timestamp (seconds) when the acknowledgment was made
+    pub timestamp: u64,
+}
+
+impl Acknowledgment {
+    /// Acknowledge a finding in its current state.
+    pub fn new(finding: &impl Acknowledgeable, comment: &str) -> Self {
+        Self {
+            id: finding.ack_id(),
+            fingerprint: finding.fingerprint(),
+            comment: comment.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns true if this acknowledgment covers the finding in its current state.
+    pub fn applies_to(&self, finding: &impl Acknowledgeable) -> bool {
+        self.id == finding.ack_id() && self.fingerprint == finding.fingerprint()
+    }
+}
+
+/// Marks failing findings that have a matching acknowledgment.
+///
+/// # Returns
+/// The number of findings marked as acknowledged.
+pub fn apply_acknowledgments<T: Acknowledgeable>(findings: &mut [T], acks: &[Acknowledgment]) -> usize {
+    let mut count = 0;
+    for finding in findings.iter_mut().filter(|f| f.is_failure()) {
+        let ack = acks.iter().find(|a| a.applies_to(&*finding)).cloned();
+        count += usize::from(ack.is_some());
+        *finding.acknowledgment_mut() = ack;
+    }
+    count
+}
+
+/// Short, stable hash of the given parts (64-bit FNV-1a, 12 hex digits).
+///
+/// Unlike `std`'s `DefaultHasher`, the result does not change between Rust releases, so ids
+/// stored in the state directory stay valid across upgrades.
+pub fn stable_id(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{:012x}", hash >> 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Severity;
+
+    #[test]
+    fn test_stable_id() {
+        assert_eq!(stable_id(&["a", "b"]), stable_id(&["a", "b"]));
+        assert_ne!(stable_id(&["ab", ""]), stable_id(&["a", "b"]));
+        assert_eq!(stable_id(&["x"]).len(), 12);
+    }
+
+    #[test]
+    fn test_acknowledgment_expires_when_state_changes() {
+        let finding = Finding::fail("sysctl", "/proc/sys/kernel/sysrq", Severity::Low, "sysrq = 1")
+            .with_subject("kernel.sysrq");
+        let ack = Acknowledgment::new(&finding, "needed for kiosk recovery");
+
+        let mut same = vec![
+            Finding::fail("sysctl", "/proc/sys/kernel/sysrq", Severity::Low, "sysrq = 1")
+                .with_subject("kernel.sysrq"),
+        ];
+        assert_eq!(apply_acknowledgments(&mut same, std::slice::from_ref(&ack)), 1);
+
+        let mut changed = vec![
+            Finding::fail("sysctl", "/proc/sys/kernel/sysrq", Severity::Low, "sysrq = 438")
+                .with_subject("kernel.sysrq"),
+        ];
+        assert_eq!(changed[0].id, finding.id);
+        assert_eq!(apply_acknowledgments(&mut changed, &[ack]), 0);
+        assert!(changed[0].acknowledged.is_none());
+    }
+}
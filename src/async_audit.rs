@@ -0,0 +1,194 @@
+//! Asynchronous audits for tokio applications (feature `async`).
+//!
+//! Server-mode and remote-audit code often runs many audits at once: one per request, or one
+//! per host of a fleet. Calling [`Audit::run`] from async code blocks the executor, and
+//! `spawn_blocking` per audit ties up one thread per host. An [`AsyncAuditor`] instead runs
+//! local audits on a fixed pool of worker threads and hands each report back through a
+//! channel, so any number of audits can be awaited concurrently while at most `workers` of
+//! them use a thread. Remote hosts are audited over SSH with `tokio::process`, without a
+//! thread each.
+//!
+//! The SSH client needs a runtime with the I/O driver enabled (`enable_all`, or the default
+//! `#[tokio::main]` runtime).
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::async_audit::AsyncAuditor;
+//! use alhalo::{Audit, Target};
+//! let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+//! let auditor = AsyncAuditor::new(2);
+//! let reports = runtime.block_on(async {
+//!     let user = auditor.run(Audit::new().target(Target::User));
+//!     let net = auditor.run(Audit::new().target(Target::Net));
+//!     tokio::join!(user, net)
+//! });
+//! assert!(reports.0.is_ok() && reports.1.is_ok());
+//! ```
+use crate::Audit;
+use crate::fleet::{FleetReport, Host};
+use crate::report::AuditReport;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::task::JoinSet;
+
+/// Error of an asynchronous audit; unlike the errors of [`Audit::run`] it can be sent
+/// between tasks.
+pub type AsyncAuditError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Runs audits on a fixed pool of worker threads for async callers.
+///
+/// Cloning is cheap; clones share the workers.
+#[derive(Clone)]
+pub struct AsyncAuditor {
+    pool: Arc<rayon::ThreadPool>,
+}
+
+impl Default for AsyncAuditor {
+    /// One worker per CPU.
+    fn default() -> Self {
+        Self::new(
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+        )
+    }
+}
+
+impl AsyncAuditor {
+    /// An auditor running at most `workers` audits at a time; more are queued.
+    pub fn new(workers: usize) -> Self {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(workers.max(1))
+            .thread_name(|i| format!("halo-audit-{}", i))
+            .build()
+            .expect("failed to start audit workers");
+        AsyncAuditor {
+            pool: Arc::new(pool),
+        }
+    }
+
+    /// Runs `audit` on a worker and waits for its report without blocking the executor.
+    ///
+    /// # Returns
+    /// * `Ok(AuditReport)` as returned by [`Audit::run`].
+    /// * `Err` if the audit failed or panicked.
+    pub async fn run(&self, audit: Audit) -> Result<AuditReport, AsyncAuditError> {
+        self.spawn(move || audit.run().map_err(Into::into)).await
+    }
+
+    /// Collects the report of `host` like [`Host::collect`]: read from a file or run on a
+    /// worker, or fetched over SSH without occupying a worker.
+    pub async fn collect(&self, host: &Host) -> Result<AuditReport, AsyncAuditError> {
+        let Some(address) = host.address.as_deref().filter(|_| host.report.is_none()) else {
+            let host = host.clone();
+            return self
+                .spawn(move || {
+                    host.collect().map_err(|e| e.to_string().into())
+                })
+                .await;
+        };
+        let output = tokio::process::Command::new("ssh")
+            .args(host.ssh_args(address))
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run ssh: {}", e))?;
+        Host::read_ssh_output(&output).map_err(|e| e.to_string().into())
+    }
+
+    /// Collects the reports of `hosts` concurrently into a fleet report, like
+    /// [`Inventory::collect`](crate::fleet::Inventory::collect).
+    pub async fn collect_fleet(&self, hosts: &[&Host]) -> FleetReport {
+        let mut tasks = JoinSet::new();
+        for (i, host) in hosts.iter().enumerate() {
+            let (auditor, host) = (self.clone(), (*host).clone());
+            tasks
+                .spawn(async move { (i, auditor.collect(&host).await.map_err(|e| e.to_string())) });
+        }
+        let mut results: Vec<Result<AuditReport, String>> = hosts
+            .iter()
+            .map(|_| Err("audit panicked".to_string()))
+            .collect();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((i, result)) = joined {
+                results[i] = result;
+            }
+        }
+        FleetReport::new(
+            hosts
+                .iter()
+                .zip(results)
+                .map(|(host, result)| (*host, result))
+                .collect(),
+        )
+    }
+
+    // Runs `job` on a worker
+    async fn spawn<F>(&self, job: F) -> Result<AuditReport, AsyncAuditError>
+    where
+        F: FnOnce() -> Result<AuditReport, AsyncAuditError> + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        self.pool.spawn(move || {
+            // A dropped receiver means the caller stopped waiting
+            let _ = sender.send(job());
+        });
+        receiver
+            .await
+            .map_err(|_| "audit panicked".into())
+            .and_then(|result| result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Importance, PermissionRules};
+    use std::fs;
+
+    #[test]
+    fn test_concurrent_audits_and_fleet() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file"), "").unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let auditor = AsyncAuditor::new(2);
+
+        let audit = |path: &str| {
+            let (rule, _) = PermissionRules::new(dir.path().join(path), 0o644, Importance::Low);
+            Audit::new().rule(rule)
+        };
+        // Audits of an alternate root are tested in tests/async_audit.rs
+        let (file, again, missing) = runtime.block_on(async {
+            tokio::join!(
+                auditor.run(audit("file")),
+                auditor.run(audit("file")),
+                auditor.run(Audit::new().config_file(dir.path().join("missing.toml"))),
+            )
+        });
+        assert_eq!(file.unwrap().permissions.len(), 1);
+        assert_eq!(again.unwrap().permissions.len(), 1);
+        assert!(missing.is_err());
+
+        let stored = dir.path().join("web-1.json");
+        fs::write(
+            &stored,
+            serde_json::to_string(&AuditReport::default()).unwrap(),
+        )
+        .unwrap();
+        let host = |name: &str, report| Host {
+            name: name.to_string(),
+            report: Some(report),
+            ..Host::default()
+        };
+        let hosts = [
+            host("web-1", stored),
+            host("gone", dir.path().join("gone.json")),
+        ];
+        let fleet = runtime.block_on(auditor.collect_fleet(&hosts.iter().collect::<Vec<_>>()));
+        assert_eq!(fleet.summary.hosts, 2);
+        assert_eq!(fleet.summary.unreachable, 1);
+    }
+}
@@ -0,0 +1,163 @@
+//! File-absence rules for HALO.
+//!
+//! Some files are a risk by merely existing: DSA host keys, `.rhosts` and `hosts.equiv`
+//! trust files, leftover installer credentials. An absence rule names a path, optionally with
+//! `*` and `?` wildcards, and fails for every existing match. Symlinks count as present even
+//! if they are broken.
+//!
+//! # Checks
+//! - `path-must-not-exist`: nothing exists at the path or matches the pattern
+//!
+//! # Example TOML
+//! ```toml
+//! [[absent_rules]]
+//! path = "/etc/ssh/ssh_host_dsa_key"
+//! severity = "High"
+//! reason = "DSA host keys are obsolete"
+//!
+//! [[absent_rules]]
+//! path = "/home/*/.rhosts"
+//! severity = "High"
+//! ```
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AbsenceAudit, AbsenceRule, AuditFindings, Severity};
+//! let rule = AbsenceRule::new("/etc/hosts.equiv", Severity::High);
+//! for finding in AbsenceAudit::new(vec![rule]).run_audit() {
+//!     println!("{:?} {}", finding.status, finding.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::audit::glob;
+use crate::Severity;
+use crate::error::HaloError;
+use crate::audit::sysroot;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// A path that must not exist.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AbsenceRule {
+    /// Forbidden path; may contain `*` and `?` wildcards
+    pub path: String,
+    /// Severity reported for each existing match
+    pub severity: Severity,
+    /// Why the path must not exist, added to failure messages
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Rule id reported as the finding subject
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+impl AbsenceRule {
+    pub fn new(path: &str, severity: Severity) -> Self {
+        Self {
+            path: path.to_string(),
+            severity,
+            reason: None,
+            id: None,
+        }
+    }
+
+    /// Explain why the path must not exist.
+    pub fn reason(mut self, reason: &str) -> Self {
+        self.reason = Some(reason.to_string());
+        self
+    }
+
+    /// Checks that the rule names a path.
+    pub fn validate(&self) -> Result<(), HaloError> {
+        if self.path.trim().is_empty() {
+            return Err(HaloError::InvalidConfig("Absence rule has empty or invalid path.".to_string()));
+        }
+        Ok(())
+    }
+
+    /// One failure per existing match, or a single pass if nothing exists.
+    pub fn check(&self) -> Vec<Finding> {
+        let present = if glob::is_pattern(&self.path) {
+            glob::expand(&self.path)
+        } else {
+            let path = PathBuf::from(&self.path);
+            if sysroot::symlink_metadata(&path).is_ok() { vec![path] } else { Vec::new() }
+        };
+        let with_subject = |finding: Finding| match &self.id {
+            Some(id) => finding.with_subject(id),
+            None => finding,
+        };
+        if present.is_empty() {
+            return vec![with_subject(Finding::pass(
+                "path-must-not-exist",
+                &self.path,
+                "Path does not exist",
+            ))];
+        }
+        let message = match &self.reason {
+            Some(reason) => format!("Path must not exist: {}", reason),
+            None => "Path must not exist".to_string(),
+        };
+        present
+            .into_iter()
+            .map(|path| {
+                with_subject(Finding::fail(
+                    "path-must-not-exist",
+                    path,
+                    self.severity.clone(),
+                    message.clone(),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Runs absence rules.
+#[derive(Debug, Clone, Default)]
+pub struct AbsenceAudit {
+    pub rules: Vec<AbsenceRule>,
+}
+
+impl AbsenceAudit {
+    pub fn new(rules: Vec<AbsenceRule>) -> Self {
+        Self { rules }
+    }
+}
+
+impl AuditFindings for AbsenceAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        self.rules.iter().flat_map(|rule| rule.check()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::audit::permissions::audit_permissions::Status;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_absent_and_present() {
+        let dir = tempdir().unwrap();
+        for user in ["alice", "bob"] {
+            fs::create_dir(dir.path().join(user)).unwrap();
+        }
+        fs::write(dir.path().join("bob/.rhosts"), "+ +\n").unwrap();
+        std::os::unix::fs::symlink("/nonexistent", dir.path().join("alice/.netrc")).unwrap();
+
+        let rhosts = AbsenceRule::new(&format!("{}/*/.rhosts", dir.path().display()), Severity::High)
+            .reason("trusts remote hosts");
+        let findings = rhosts.check();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].path, dir.path().join("bob/.rhosts"));
+        assert_eq!(findings[0].message, "Path must not exist: trusts remote hosts");
+
+        // A broken symlink is still present
+        let netrc = AbsenceRule::new(&format!("{}/alice/.netrc", dir.path().display()), Severity::Medium);
+        assert_eq!(netrc.check()[0].status, Status::Fail);
+
+        let dsa = AbsenceRule::new(&format!("{}/ssh_host_dsa_key", dir.path().display()), Severity::High);
+        assert_eq!(dsa.check()[0].status, Status::Pass);
+    }
+}
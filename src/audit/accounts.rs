@@ -0,0 +1,229 @@
+//! Password aging and account policy audit for HALO.
+//!
+//! Parses `/etc/login.defs` for the system-wide password aging defaults and `/etc/shadow`
+//! (when readable, usually as root) for per-account password state.
+//!
+//! # Checks
+//! - `login-defs-pass-max-days`: `PASS_MAX_DAYS` missing or above the policy maximum
+//! - `login-defs-pass-min-days`: `PASS_MIN_DAYS` missing or below the policy minimum
+//! - `login-defs-pass-warn-age`: `PASS_WARN_AGE` missing or below the policy minimum
+//! - `account-empty-password`: account with an empty password field (login without password)
+//! - `account-locked`: account is locked (reported as passing)
+//! - `account-password-aging`: account password never expires or exceeds the maximum age
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AccountsAudit, AuditFindings};
+//! let findings = AccountsAudit::default().run_audit();
+//! println!("{} account findings", findings.len());
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::Severity;
+use crate::audit::sysroot;
+use std::path::{Path, PathBuf};
+
+/// Password aging policy and the files it is checked against.
+///
+/// Defaults follow common hardening guidance: passwords expire within 365 days, cannot be
+/// changed more than once a day, and users are warned 7 days before expiry.
+pub struct AccountsAudit {
+    pub login_defs: PathBuf,
+    pub shadow: PathBuf,
+    /// Maximum allowed `PASS_MAX_DAYS` / per-account maximum password age
+    pub max_days: u32,
+    /// Minimum required `PASS_MIN_DAYS`
+    pub min_days: u32,
+    /// Minimum required `PASS_WARN_AGE`
+    pub warn_age: u32,
+}
+
+/// Provides default paths and policy values for the accounts audit.
+impl Default for AccountsAudit {
+    fn default() -> Self {
+        Self {
+            login_defs: "/etc/login.defs".into(),
+            shadow: "/etc/shadow".into(),
+            max_days: 365,
+            min_days: 1,
+            warn_age: 7,
+        }
+    }
+}
+
+impl AuditFindings for AccountsAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        match sysroot::read_to_string(&self.login_defs) {
+            Ok(content) => findings.extend(self.check_login_defs(&self.login_defs, &content)),
+            Err(e) => findings.push(Finding::fail(
+                "login-defs-unreadable",
+                &self.login_defs,
+                Severity::Info,
+                format!("Cannot read login.defs: {}", e),
+            )),
+        }
+        match sysroot::read_to_string(&self.shadow) {
+            Ok(content) => findings.extend(self.check_shadow(&self.shadow, &content)),
+            Err(e) => findings.push(Finding::fail(
+                "shadow-unreadable",
+                &self.shadow,
+                Severity::Info,
+                format!("Cannot read shadow file (run as root for account checks): {}", e),
+            )),
+        }
+        findings
+    }
+}
+
+impl AccountsAudit {
+    /// Checks `login.defs` content against the password aging policy.
+    ///
+    /// # Arguments
+    /// * `path` - Path reported in the findings
+    /// * `content` - Contents of `login.defs`
+    ///
+    /// # Returns
+    /// One finding per checked setting.
+    pub fn check_login_defs(&self, path: &Path, content: &str) -> Vec<Finding> {
+        let value = |key: &str| -> Option<u32> {
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.starts_with('#'))
+                .filter_map(|l| l.split_once(char::is_whitespace))
+                .filter(|(k, _)| *k == key)
+                .filter_map(|(_, v)| v.trim().parse().ok())
+                .next_back()
+        };
+
+        let mut findings = Vec::new();
+        // (setting, check id, severity, policy limit, whether the limit is an upper bound)
+        let checks = [
+            ("PASS_MAX_DAYS", "login-defs-pass-max-days", Severity::Medium, self.max_days, true),
+            ("PASS_MIN_DAYS", "login-defs-pass-min-days", Severity::Low, self.min_days, false),
+            ("PASS_WARN_AGE", "login-defs-pass-warn-age", Severity::Low, self.warn_age, false),
+        ];
+        for (key, check, severity, limit, upper_bound) in checks {
+            let finding = match value(key) {
+                None => Finding::fail(check, path, severity, format!("{} is not set", key)),
+                Some(v) if (upper_bound && v <= limit) || (!upper_bound && v >= limit) => {
+                    Finding::pass(check, path, format!("{} is {}", key, v))
+                }
+                Some(v) => Finding::fail(
+                    check,
+                    path,
+                    severity,
+                    format!("{} is {} (policy: {})", key, v, limit),
+                ),
+            };
+            findings.push(finding.with_subject(key));
+        }
+        findings
+    }
+
+    /// Checks `/etc/shadow` content and emits one finding per account.
+    ///
+    /// # Arguments
+    /// * `path` - Path reported in the findings
+    /// * `content` - Contents of the shadow file
+    ///
+    /// # Returns
+    /// One finding per account: empty password, locked, or password aging result.
+    pub fn check_shadow(&self, path: &Path, content: &str) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            // name:password:lastchg:min:max:warn:inactive:expire:reserved
+            let fields: Vec<&str> = line.split(':').collect();
+            if fields.len() < 2 {
+                continue;
+            }
+            let name = fields[0];
+            let password = fields[1];
+            let max_days = fields.get(4).and_then(|v| v.parse::<u32>().ok());
+
+            let finding = if password.is_empty() {
+                Finding::fail(
+                    "account-empty-password",
+                    path,
+                    Severity::Critical,
+                    "Account has an empty password and can log in without one",
+                )
+            } else if password.starts_with('!') || password.starts_with('*') {
+                Finding::pass("account-locked", path, "Account is locked")
+            } else {
+                match max_days {
+                    Some(days) if days <= self.max_days => Finding::pass(
+                        "account-password-aging",
+                        path,
+                        format!("Password expires after {} days", days),
+                    ),
+                    Some(days) => Finding::fail(
+                        "account-password-aging",
+                        path,
+                        Severity::Medium,
+                        format!(
+                            "Password expires after {} days (policy: {})",
+                            days, self.max_days
+                        ),
+                    ),
+                    None => Finding::fail(
+                        "account-password-aging",
+                        path,
+                        Severity::Medium,
+                        "Password never expires",
+                    ),
+                }
+            };
+            findings.push(finding.with_subject(name));
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Status;
+
+    #[test]
+    fn test_login_defs_policy() {
+        let audit = AccountsAudit::default();
+        let content = "# comment\nPASS_MAX_DAYS\t99999\nPASS_MIN_DAYS 0\nPASS_WARN_AGE 7\n";
+        let findings = audit.check_login_defs(Path::new("/etc/login.defs"), content);
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].status, Status::Fail);
+        assert_eq!(findings[0].severity, Severity::Medium);
+        assert_eq!(findings[1].status, Status::Fail);
+        assert_eq!(findings[2].status, Status::Pass);
+    }
+
+    #[test]
+    fn test_login_defs_missing_setting() {
+        let audit = AccountsAudit::default();
+        let findings = audit.check_login_defs(Path::new("/etc/login.defs"), "UMASK 022\n");
+        assert!(findings.iter().all(|f| f.status == Status::Fail));
+    }
+
+    #[test]
+    fn test_shadow_accounts() {
+        let audit = AccountsAudit::default();
+        let content = "root:$6$abc:19000:0:99999:7:::\n\
+                       daemon:*:19000:0:99999:7:::\n\
+                       guest::19000:0:99999:7:::\n\
+                       alice:$6$def:19000:1:90:7:::\n\
+                       bob:$6$ghi:19000:0::7:::\n";
+        let findings = audit.check_shadow(Path::new("/etc/shadow"), content);
+        let by_account = |name: &str| {
+            findings
+                .iter()
+                .find(|f| f.subject.as_deref() == Some(name))
+                .unwrap()
+        };
+        assert_eq!(by_account("root").check, "account-password-aging");
+        assert_eq!(by_account("root").status, Status::Fail);
+        assert_eq!(by_account("daemon").check, "account-locked");
+        assert_eq!(by_account("guest").severity, Severity::Critical);
+        assert_eq!(by_account("alice").status, Status::Pass);
+        assert_eq!(by_account("bob").message, "Password never expires");
+    }
+}
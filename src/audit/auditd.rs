@@ -0,0 +1,253 @@
+//! Linux audit daemon (auditd) audit for HALO.
+//!
+//! Checks that auditd is installed and running and that the loaded ruleset watches the files
+//! an attacker would change to gain or keep privileges. The permissions of the auditd
+//! configuration itself are covered by [`AuditdConfig`](crate::AuditdConfig).
+//!
+//! The loaded ruleset is read with `auditctl -l`, which requires root. When that is not
+//! possible the rules in `/etc/audit/rules.d` are checked instead, and the findings say so.
+//!
+//! # Checks
+//! - `auditd-installed`: the `auditd` binary exists
+//! - `auditd-running`: an `auditd` process is running
+//! - `auditd-missing-watch`: a required file has no write watch in the ruleset
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditdAudit, AuditFindings};
+//! let findings = AuditdAudit::default().run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{}: {}", f.path.display(), f.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::Severity;
+use crate::audit::sysroot;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// auditd installation, process and required watch rules.
+pub struct AuditdAudit {
+    /// Candidate locations of the auditd binary
+    pub binaries: Vec<PathBuf>,
+    /// Rule directory read when the loaded ruleset is unavailable
+    pub rules_d: PathBuf,
+    /// Files that must be watched for writes
+    pub required_watches: Vec<PathBuf>,
+}
+
+/// Provides default paths and the identity/privilege files that must be watched.
+impl Default for AuditdAudit {
+    fn default() -> Self {
+        Self {
+            binaries: vec!["/usr/sbin/auditd".into(), "/sbin/auditd".into()],
+            rules_d: "/etc/audit/rules.d".into(),
+            required_watches: vec![
+                "/etc/passwd".into(),
+                "/etc/shadow".into(),
+                "/etc/group".into(),
+                "/etc/gshadow".into(),
+                "/etc/sudoers".into(),
+                "/etc/sudoers.d".into(),
+            ],
+        }
+    }
+}
+
+impl AuditFindings for AuditdAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        let installed = self.binaries.iter().find(|b| sysroot::exists(b));
+        findings.push(match installed {
+            Some(binary) => Finding::pass("auditd-installed", binary, "auditd is installed"),
+            None => Finding::fail(
+                "auditd-installed",
+                &self.binaries[0],
+                Severity::Medium,
+                "auditd is not installed; security-relevant changes are not recorded",
+            ),
+        });
+        if installed.is_none() {
+            return findings;
+        }
+
+        findings.push(if auditd_running() {
+            Finding::pass("auditd-running", "/proc", "auditd is running")
+        } else {
+            Finding::fail(
+                "auditd-running",
+                "/proc",
+                Severity::High,
+                "auditd is installed but not running",
+            )
+        });
+
+        let (source, rules) = match loaded_rules() {
+            Some(rules) => (PathBuf::from("auditctl -l"), rules),
+            None => (self.rules_d.clone(), self.rules_d_content()),
+        };
+        findings.extend(check_watch_rules(&source, &rules, &self.required_watches));
+        findings
+    }
+}
+
+impl AuditdAudit {
+    /// Concatenated content of the `.rules` files in `rules_d`.
+    fn rules_d_content(&self) -> String {
+        let Ok(entries) = sysroot::read_dir(&self.rules_d) else {
+            return String::new();
+        };
+        let mut files: Vec<PathBuf> = entries
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|e| e == "rules"))
+            .collect();
+        files.sort();
+        files
+            .iter()
+            .filter_map(|f| sysroot::read_to_string(f).ok())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Returns true if a process named `auditd` is running.
+fn auditd_running() -> bool {
+    let Ok(entries) = sysroot::read_dir(Path::new("/proc")) else {
+        return false;
+    };
+    entries
+        .iter()
+        .any(|dir| sysroot::read_to_string(&dir.join("comm")).is_ok_and(|comm| comm.trim() == "auditd"))
+}
+
+/// The ruleset loaded in the kernel, if `auditctl -l` can be run; never for an alternate root,
+/// whose kernel is not the running one.
+fn loaded_rules() -> Option<String> {
+    if sysroot::root().is_some() {
+        return None;
+    }
+    let output = Command::new("auditctl").arg("-l").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Paths watched for writes by an audit ruleset.
+///
+/// Understands file watches (`-w /etc/passwd -p wa`) and syscall rules filtering on a path
+/// (`-a always,exit -F path=/etc/passwd -F perm=wa`, also `dir=`). Watches without a
+/// permission filter cover writes.
+pub fn watched_paths(rules: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for line in rules.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let mut path = None;
+        let mut perms = None;
+        let mut i = 0;
+        while i < tokens.len() {
+            let value = tokens.get(i + 1).copied();
+            match tokens[i] {
+                "-w" => path = value,
+                "-p" => perms = value,
+                "-F" => {
+                    if let Some((key, v)) = value.and_then(|f| f.split_once('=')) {
+                        match key {
+                            "path" | "dir" => path = Some(v),
+                            "perm" => perms = Some(v),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            }
+            i += 2;
+        }
+        if let Some(path) = path
+            && perms.is_none_or(|p| p.contains('w'))
+        {
+            paths.push(PathBuf::from(path.trim_end_matches('/')));
+        }
+    }
+    paths
+}
+
+/// Reports required files without a write watch in `rules`.
+///
+/// # Arguments
+/// * `source` - Where the rules came from, reported as the finding path
+/// * `rules` - Audit rules, one per line
+/// * `required` - Files that must be watched
+///
+/// # Returns
+/// One finding per required file.
+pub fn check_watch_rules(source: &Path, rules: &str, required: &[PathBuf]) -> Vec<Finding> {
+    let watched = watched_paths(rules);
+    required
+        .iter()
+        .map(|path| {
+            let finding = if watched.iter().any(|w| w == path) {
+                Finding::pass("auditd-missing-watch", source, "Writes are audited")
+            } else {
+                Finding::fail(
+                    "auditd-missing-watch",
+                    source,
+                    Severity::Medium,
+                    "No audit rule watches this file for writes",
+                )
+            };
+            finding.with_subject(path.display().to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watched_paths() {
+        let rules = "-w /etc/passwd -p wa -k identity\n\
+                     # -w /etc/group -p wa\n\
+                     -w /etc/shadow -p r -k read\n\
+                     -a always,exit -F arch=b64 -F path=/etc/sudoers -F perm=wa -k scope\n\
+                     -a always,exit -F dir=/etc/sudoers.d/ -F perm=w\n\
+                     -w /etc/gshadow\n";
+        let paths = watched_paths(rules);
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/etc/passwd"),
+                PathBuf::from("/etc/sudoers"),
+                PathBuf::from("/etc/sudoers.d"),
+                PathBuf::from("/etc/gshadow"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_watches() {
+        let required = AuditdAudit::default().required_watches;
+        let findings = check_watch_rules(
+            Path::new("auditctl -l"),
+            "-w /etc/passwd -p wa\n-w /etc/shadow -p wa\n",
+            &required,
+        );
+        let missing: Vec<&str> = findings
+            .iter()
+            .filter(|f| f.is_failure())
+            .filter_map(|f| f.subject.as_deref())
+            .collect();
+        assert_eq!(
+            missing,
+            vec!["/etc/group", "/etc/gshadow", "/etc/sudoers", "/etc/sudoers.d"]
+        );
+    }
+}
@@ -0,0 +1,906 @@
+//! Builder API for composing audits.
+//!
+//! [`Audit`] combines built-in targets, individual rules and TOML config files into a single
+//! run and returns one [`AuditReport`], so library users do not need to track visited
+//! directories, call the separate `toml_*` loaders or render each result type on its own.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{Audit, Importance, PermissionRules, Renderable, Severity, Target};
+//! let (rule, _status) = PermissionRules::new("/etc/shadow".into(), 0o640, Importance::High);
+//! let report = Audit::new()
+//!     .target(Target::User)
+//!     .rule(rule)
+//!     .min_severity(Severity::High)
+//!     .run()
+//!     .unwrap();
+//! report.render_and_print(Some("pretty"));
+//! ```
+use crate::audit::{
+    accounts::AccountsAudit,
+    auditd::AuditdAudit,
+    cis::{CisAudit, CisLevel},
+    context::AuditContext,
+    docker::DockerAudit,
+    finding::AuditFindings,
+    footprint::Footprint,
+    hardening::HardeningAudit,
+    immutable::{ImmutableAudit, ImmutableDistro},
+    logrotate::LogrotateAudit,
+    mounts::MountPolicy,
+    ownership::ownership::OwnershipRule,
+    passwd::PasswdAudit,
+    permissions::{
+        audit_permissions::{AuditPermissions, PermissionRules, Severity},
+        severity_policy::SeverityPolicy,
+        default_permissions::{
+            AuditdConfig, EmbeddedConfig, K8sNodeConfig, Log, NetConf, SysConfig, UserConfig,
+        },
+    },
+    polkit::PolkitAudit,
+    rule::AuditRule,
+    scan::{CancelToken, ScanControl, ScanLimits},
+    sysroot,
+    embedded::EmbeddedAudit,
+    environ::EnvironAudit,
+    firewall::FirewallAudit,
+    sudoers::SudoersAudit,
+    sysctl::SysctlAudit,
+    tls::TlsAudit,
+    toml_config::AuditConfig,
+    udev::UdevAudit,
+};
+use crate::error::HaloError;
+use crate::observer::{AuditObserver, ResultRef};
+use crate::report::{AuditReport, ReportMark, RunMetadata, TargetTiming};
+use crate::risk::UNATTRIBUTED;
+use crate::stream::ResultStream;
+use crate::waiver::Waiver;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, info};
+
+/// Built-in groups of audits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    /// User account files and `/etc/passwd` consistency
+    User,
+    /// System configuration files
+    Sys,
+    /// Network configuration files
+    Net,
+    /// Log files, logrotate configuration and coverage, oversized logs
+    Log,
+    /// Kernel parameters in `/proc/sys`
+    Sysctl,
+    /// Sudoers policy
+    Sudoers,
+    /// Password aging and account state
+    Accounts,
+    /// auditd installation, configuration and watch rules
+    Auditd,
+    /// polkit rules and D-Bus system policies
+    Polkit,
+    /// Udev rules
+    Udev,
+    /// Docker socket, daemon configuration and `docker` group
+    Docker,
+    /// Secrets in the environment of running processes
+    Environ,
+    /// Active firewall, default-deny inbound policy and SSH rate limiting
+    Firewall,
+    /// TLS certificate expiry and private key permissions
+    Tls,
+    /// Core dump restrictions and `/proc` hidepid
+    Hardening,
+    /// Raspberry Pi / embedded device preset
+    Embedded,
+    /// NixOS and ostree specific checks
+    Immutable,
+    /// Kubernetes node files per the CIS Kubernetes Benchmark
+    #[serde(rename = "k8s-node")]
+    K8sNode,
+    /// Every target that applies to a general-purpose host
+    All,
+}
+
+// Names and help of `--target` come from `name` and `description`, so the library does
+// not depend on clap
+#[cfg(feature = "cli")]
+impl clap::ValueEnum for Target {
+    fn value_variants<'a>() -> &'a [Self] {
+        &Target::ALL
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(clap::builder::PossibleValue::new(self.name()).help(self.description()))
+    }
+}
+
+impl Target {
+    /// Every target, in the order `halo list targets` shows them.
+    pub const ALL: [Target; 19] = [
+        Target::User,
+        Target::Sys,
+        Target::Net,
+        Target::Log,
+        Target::Sysctl,
+        Target::Sudoers,
+        Target::Accounts,
+        Target::Auditd,
+        Target::Polkit,
+        Target::Udev,
+        Target::Docker,
+        Target::Environ,
+        Target::Firewall,
+        Target::Tls,
+        Target::Hardening,
+        Target::Embedded,
+        Target::Immutable,
+        Target::K8sNode,
+        Target::All,
+    ];
+
+    /// Name used by `check --target` and in reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Target::User => "user",
+            Target::Sys => "sys",
+            Target::Net => "net",
+            Target::Log => "log",
+            Target::Sysctl => "sysctl",
+            Target::Sudoers => "sudoers",
+            Target::Accounts => "accounts",
+            Target::Auditd => "auditd",
+            Target::Polkit => "polkit",
+            Target::Udev => "udev",
+            Target::Docker => "docker",
+            Target::Environ => "environ",
+            Target::Firewall => "firewall",
+            Target::Tls => "tls",
+            Target::Hardening => "hardening",
+            Target::Embedded => "embedded",
+            Target::Immutable => "immutable",
+            Target::K8sNode => "k8s-node",
+            Target::All => "all",
+        }
+    }
+
+    /// One-line description listed by `halo list targets`.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Target::User => "User account files and `/etc/passwd` consistency",
+            Target::Sys => "System configuration files",
+            Target::Net => "Network configuration files",
+            Target::Log => "Log files, logrotate configuration and coverage, oversized logs",
+            Target::Sysctl => "Kernel parameters in `/proc/sys`",
+            Target::Sudoers => "Sudoers policy",
+            Target::Accounts => "Password aging and account state",
+            Target::Auditd => "auditd installation, configuration and watch rules",
+            Target::Polkit => "polkit rules and D-Bus system policies",
+            Target::Udev => "Udev rules",
+            Target::Docker => "Docker socket, daemon configuration and `docker` group",
+            Target::Environ => "Secrets in the environment of running processes",
+            Target::Firewall => "Active firewall, default-deny inbound policy and SSH rate limiting",
+            Target::Tls => "TLS certificate expiry and private key permissions",
+            Target::Hardening => "Core dump restrictions and `/proc` hidepid",
+            Target::Embedded => "Raspberry Pi / embedded device preset",
+            Target::Immutable => "NixOS and ostree specific checks",
+            Target::K8sNode => "Kubernetes node files per the CIS Kubernetes Benchmark",
+            Target::All => "Every target that applies to a general-purpose host",
+        }
+    }
+
+    /// The target called `name`, as returned by [`name`](Self::name).
+    pub fn from_name(name: &str) -> Option<Target> {
+        Target::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    /// The targets `self` stands for; `All` expands to the general-purpose targets.
+    ///
+    /// `Embedded` is never part of `All`, `Immutable` only when an immutable distribution is
+    /// detected and `K8sNode` only on Kubernetes nodes.
+    pub fn expand(&self) -> Vec<Target> {
+        match self {
+            Target::All => {
+                let mut targets = vec![
+                    Target::User,
+                    Target::Sys,
+                    Target::Net,
+                    Target::Log,
+                    Target::Sysctl,
+                    Target::Sudoers,
+                    Target::Accounts,
+                    Target::Auditd,
+                    Target::Polkit,
+                    Target::Udev,
+                    Target::Docker,
+                    Target::Environ,
+                    Target::Firewall,
+                    Target::Tls,
+                    Target::Hardening,
+                ];
+                if ImmutableDistro::detect().is_some() {
+                    targets.push(Target::Immutable);
+                }
+                if K8sNodeConfig::detect() {
+                    targets.push(Target::K8sNode);
+                }
+                targets
+            }
+            t => vec![*t],
+        }
+    }
+
+    /// The permission rules the target checks; empty for targets that only produce
+    /// findings, such as `Sysctl`.
+    pub fn permission_rules(&self) -> Vec<PermissionRules> {
+        match self {
+            Target::User => UserConfig::default().rules(),
+            Target::Sys => SysConfig::default().rules(),
+            Target::Net => NetConf::default().rules(),
+            Target::Log => Log::default().rules(),
+            Target::Auditd => AuditdConfig::default().rules(),
+            Target::Embedded => EmbeddedConfig::default().rules(),
+            Target::K8sNode => K8sNodeConfig::default().rules(),
+            Target::All => self.expand().iter().flat_map(Target::permission_rules).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Runs the audits for a single (already expanded) target into `report`.
+    fn run(&self, footprint: &Footprint, control: &mut ScanControl, report: &mut AuditReport) {
+        match self {
+            Target::User => {
+                report.permissions.extend(UserConfig::default().run_audit_perms_controlled(footprint, control));
+                report.findings.extend(PasswdAudit::default().run_audit());
+            }
+            Target::Sys => {
+                report.permissions.extend(SysConfig::default().run_audit_perms_controlled(footprint, control));
+            }
+            Target::Net => {
+                report.permissions.extend(NetConf::default().run_audit_perms_controlled(footprint, control));
+            }
+            Target::Log => {
+                report.permissions.extend(Log::default().run_audit_perms_controlled(footprint, control));
+                report.findings.extend(LogrotateAudit::default().run_audit());
+            }
+            Target::Sysctl => report.findings.extend(SysctlAudit::default().run_audit()),
+            Target::Sudoers => report.findings.extend(SudoersAudit::default().run_audit()),
+            Target::Accounts => report.findings.extend(AccountsAudit::default().run_audit()),
+            Target::Auditd => {
+                report
+                    .permissions
+                    .extend(AuditdConfig::default().run_audit_perms_controlled(footprint, control));
+                report.findings.extend(AuditdAudit::default().run_audit());
+            }
+            Target::Polkit => report.findings.extend(PolkitAudit::default().run_audit()),
+            Target::Udev => report.findings.extend(UdevAudit::default().run_audit()),
+            Target::Docker => report.findings.extend(DockerAudit::default().run_audit()),
+            Target::Environ => report.findings.extend(EnvironAudit::default().run_audit()),
+            Target::Firewall => report.findings.extend(FirewallAudit::default().run_audit()),
+            Target::Tls => report.findings.extend(TlsAudit::default().run_audit()),
+            Target::Hardening => report.findings.extend(HardeningAudit::default().run_audit()),
+            Target::Embedded => {
+                report
+                    .permissions
+                    .extend(EmbeddedConfig::default().run_audit_perms_controlled(footprint, control));
+                report.findings.extend(EmbeddedAudit::default().run_audit());
+            }
+            Target::Immutable => report.findings.extend(ImmutableAudit::default().run_audit()),
+            Target::K8sNode => {
+                report
+                    .permissions
+                    .extend(K8sNodeConfig::default().run_audit_perms_controlled(footprint, control));
+            }
+            Target::All => {
+                for target in self.expand() {
+                    target.run(footprint, control, report);
+                }
+            }
+        }
+    }
+}
+
+/// Fluent builder for a complete audit run.
+#[derive(Debug, Default)]
+pub struct Audit {
+    targets: Vec<Target>,
+    plugins: Vec<String>,
+    rules: Vec<PermissionRules>,
+    ownership_rules: Vec<OwnershipRule>,
+    audit_rules: Vec<Box<dyn AuditRule>>,
+    config_files: Vec<PathBuf>,
+    configs: Vec<AuditConfig>,
+    min_severity: Option<Severity>,
+    footprint: Footprint,
+    io_rate: Option<u32>,
+    jobs: Option<usize>,
+    mounts: Option<MountPolicy>,
+    limits: Option<ScanLimits>,
+    checkpoint: Option<PathBuf>,
+    severity_policy: Option<SeverityPolicy>,
+    observers: Vec<Arc<dyn AuditObserver>>,
+    cancel: Option<CancelToken>,
+    waivers: Vec<Waiver>,
+    cis: Option<CisLevel>,
+    profile: Option<String>,
+    root: Option<PathBuf>,
+}
+
+impl Audit {
+    /// Create an empty audit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a built-in target.
+    pub fn target(mut self, target: Target) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    /// Run the [plugin](crate::plugin) called `name`, registered or found on `PATH`, as a target.
+    pub fn plugin(mut self, name: &str) -> Self {
+        self.plugins.push(name.to_string());
+        self
+    }
+
+    /// Run the controls of a CIS benchmark level; findings carry the control id.
+    pub fn cis(mut self, level: CisLevel) -> Self {
+        self.cis = Some(level);
+        self
+    }
+
+    /// Add a permission rule.
+    pub fn rule(mut self, rule: PermissionRules) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Add an ownership rule.
+    pub fn ownership_rule(mut self, rule: OwnershipRule) -> Self {
+        self.ownership_rules.push(rule);
+        self
+    }
+
+    /// Add a rule of any kind, e.g. a custom check; its findings are reported with the rules'.
+    pub fn audit_rule(mut self, rule: impl AuditRule + 'static) -> Self {
+        self.audit_rules.push(Box::new(rule));
+        self
+    }
+
+    /// Load permission, ownership, sysctl, firewall, TLS and logrotate rules from a TOML config file.
+    pub fn config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_files.push(path.into());
+        self
+    }
+
+    /// Only run the rules of the named `[profiles.<name>]` from each config file.
+    ///
+    /// Every config file must define the profile; see [`AuditConfig::profile`].
+    pub fn profile(mut self, name: &str) -> Self {
+        self.profile = Some(name.to_string());
+        self
+    }
+
+    /// Add rules from an already loaded config, e.g. the last good config of a
+    /// [`ConfigWatcher`](crate::reload::ConfigWatcher).
+    pub fn config(mut self, config: AuditConfig) -> Self {
+        self.configs.push(config);
+        self
+    }
+
+    /// Waive matching failures until the waiver expires; config files can add more with
+    /// `[[waivers]]`.
+    pub fn waiver(mut self, waiver: Waiver) -> Self {
+        self.waivers.push(waiver);
+        self
+    }
+
+    /// Only report failures at least this severe; passing results are kept.
+    pub fn min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    /// Set the resource profile; [`Footprint::Low`] keeps only non-passing results.
+    pub fn footprint(mut self, footprint: Footprint) -> Self {
+        self.footprint = footprint;
+        self
+    }
+
+    /// Limit permission walks to `files_per_sec` files per second.
+    pub fn io_rate(mut self, files_per_sec: u32) -> Self {
+        self.io_rate = Some(files_per_sec);
+        self
+    }
+
+    /// Walk directories with `jobs` threads; defaults to the footprint's
+    /// [`jobs`](Footprint::jobs), one per CPU unless [`Footprint::Low`]. Results are in the
+    /// same order as with a single thread. Throttled and checkpointed walks use one thread.
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    /// Enter the mounts below recursive rules' paths according to `policy`, unless a rule
+    /// sets its own; by default pseudo and network filesystems are skipped.
+    pub fn mount_policy(mut self, policy: MountPolicy) -> Self {
+        self.mounts = Some(policy);
+        self
+    }
+
+    /// Stop each rule's walk at `limits`; the walks stopped are listed in the report summary.
+    ///
+    /// Takes precedence over `[limits]` in the config files.
+    pub fn limits(mut self, limits: ScanLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Checkpoint permission results to `path` and resume from it after an interruption.
+    ///
+    /// The checkpoint is removed when the run completes.
+    pub fn checkpoint(mut self, path: impl Into<PathBuf>) -> Self {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
+    /// Map permission mismatches to severities with `policy`.
+    ///
+    /// Takes precedence over a `[severity_policy]` in the config files.
+    pub fn severity_policy(mut self, policy: SeverityPolicy) -> Self {
+        self.severity_policy = Some(policy);
+        self
+    }
+
+    /// Tell `observer` about the progress and results of the run; see
+    /// [`observer`](crate::observer).
+    pub fn observer(mut self, observer: impl AuditObserver + 'static) -> Self {
+        self.observers.push(Arc::new(observer));
+        self
+    }
+
+    /// Stop the run once `token` is cancelled, e.g. from a Ctrl-C handler; the report then
+    /// holds the results found so far and its metadata is marked `cancelled`.
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Audit the filesystem mounted at `root`, e.g. a container image or chroot, instead of the
+    /// running system; see [`sysroot`](crate::audit::sysroot). Reported paths stay as they
+    /// are inside the root.
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Identifies the configured scan, so a checkpoint is only resumed by the same audit.
+    fn scan_key(&self) -> String {
+        let rules = format!(
+            "{:?}{:?}{:?}{:?}{:?}",
+            self.targets, self.rules, self.config_files, self.profile, self.mounts
+        );
+        crate::ack::stable_id(&[&rules])
+    }
+
+    /// Run every configured audit.
+    ///
+    /// Targets run first (each at most once, even if also covered by `Target::All`), then
+    /// plugins, the CIS profile, rules, and configs and config files. The severity policy, from
+    /// [`severity_policy`](Self::severity_policy) or the first config that has one, applies to
+    /// all permission results; so do the [`limits`](Self::limits). Waivers are applied last, before the severity filter.
+    ///
+    /// # Returns
+    /// * `Ok(AuditReport)` with the combined results.
+    /// * `Err` if a config file cannot be read or contains an invalid rule, a plugin is not
+    ///   found, the root is not a directory, or the checkpoint cannot be written.
+    pub fn run(&self) -> Result<AuditReport, HaloError> {
+        self.run_with(None)
+    }
+
+    /// Run every configured audit like [`run`](Self::run), writing each result to `stream` as
+    /// it is produced.
+    ///
+    /// The stream applies the audit's waivers, severity threshold and footprint to every line.
+    ///
+    /// # Returns
+    /// * `Ok(AuditReport)` with the combined results.
+    /// * `Err` as for [`run`](Self::run), or if writing to the stream failed.
+    pub fn run_streaming(&self, stream: ResultStream) -> Result<AuditReport, HaloError> {
+        self.run_with(Some(stream))
+    }
+
+    fn run_with(&self, stream: Option<ResultStream>) -> Result<AuditReport, HaloError> {
+        let mut plugins = Vec::new();
+        for name in &self.plugins {
+            let plugin = crate::plugin::find(name).ok_or_else(|| {
+                HaloError::InvalidArgument(format!("Unknown target or plugin '{}'; see `halo list targets`", name))
+            })?;
+            plugins.push(plugin);
+        }
+        let mut configs = Vec::new();
+        for path in &self.config_files {
+            let path = path.to_str().ok_or_else(|| {
+                HaloError::InvalidConfig(format!("Config path is not valid UTF-8: {}", path.display()))
+            })?;
+            let config = AuditConfig::from_file(path)?;
+            configs.push(match &self.profile {
+                Some(name) => config
+                    .profile(name)
+                    .map_err(|e| HaloError::InvalidConfig(format!("{}: {}", path, e)))?,
+                None => config,
+            });
+        }
+        let policy = self.severity_policy.clone().or_else(|| {
+            self.configs
+                .iter()
+                .chain(&configs)
+                .find_map(|c| c.severity_policy.clone())
+        });
+        let limits = self
+            .limits
+            .or_else(|| self.configs.iter().chain(&configs).find_map(|c| c.limits));
+
+        let waivers: Vec<Waiver> = self
+            .waivers
+            .iter()
+            .chain(self.configs.iter().chain(&configs).flat_map(|c| &c.waivers))
+            .cloned()
+            .collect();
+
+        // Config files are read from the running system, everything audited from the root,
+        // entered on this thread only and carried to walk workers by the context
+        let _root = match &self.root {
+            Some(root) if !root.is_dir() => {
+                return Err(HaloError::InvalidArgument(format!("Root {} is not a directory", root.display())));
+            }
+            Some(root) => Some(sysroot::enter(Some(root.clone()))),
+            None => None,
+        };
+        let started = Instant::now();
+        let mut metadata = RunMetadata::capture();
+        let mut report = AuditReport::default();
+        let mut control = ScanControl::new();
+        if let Some(policy) = policy {
+            control = control.with_severity_policy(policy);
+        }
+        if let Some(mut stream) = stream {
+            stream.add_waivers(waivers.clone());
+            if let Some(min) = &self.min_severity {
+                stream = stream.min_severity(min.clone());
+            }
+            if !self.footprint.keep_passing() {
+                stream.set_keep_passing(false);
+            }
+            control = control.with_stream(stream);
+        }
+        let context = Arc::new(if self.footprint.cache_metadata() {
+            AuditContext::cached()
+        } else {
+            AuditContext::new()
+        });
+        control = control.with_jobs(self.jobs.unwrap_or_else(|| self.footprint.jobs()));
+        control = control.with_context(context.clone());
+        let cancel = self.cancel.clone().unwrap_or_default();
+        control = control.with_cancel(cancel.clone());
+        for observer in &self.observers {
+            control = control.with_observer(observer.clone());
+        }
+        if let Some(policy) = self.mounts {
+            control = control.with_mount_policy(policy);
+        }
+        if let Some(limits) = limits {
+            control = control.with_limits(limits);
+        }
+        if !self.footprint.keep_passing() {
+            control = control.without_passing();
+        }
+        if let Some(rate) = self.io_rate {
+            control = control.with_rate(rate);
+        }
+        if let Some(path) = &self.checkpoint {
+            control = control.with_checkpoint(path, &self.scan_key()).map_err(|e| HaloError::io(path, e))?;
+            report.permissions = control.take_restored();
+            report.permissions.iter().for_each(|r| control.publish(ResultRef::Permission(r)));
+        }
+
+        let mut seen = HashSet::new();
+        for target in self.targets.iter().flat_map(Target::expand) {
+            if cancel.is_cancelled() {
+                break;
+            }
+            if seen.insert(target) {
+                let name = target.name();
+                let (mark, stage) = (report.mark(), Stage::start(&report, &control));
+                metadata.targets.push(name.to_string());
+                set_stream_target(&mut control, name);
+                target.run(&self.footprint, &mut control, &mut report);
+                stream_added(&mut control, &report, mark);
+                report.attribute(mark, name);
+                stage.finish(name, &report, &control, &mut metadata);
+            }
+        }
+
+        for plugin in plugins.iter().take_while(|_| !cancel.is_cancelled()) {
+            let (mark, stage) = (report.mark(), Stage::start(&report, &control));
+            metadata.targets.push(plugin.name().to_string());
+            report.findings.extend(plugin.evaluate(&context));
+            set_stream_target(&mut control, plugin.name());
+            stream_added(&mut control, &report, mark);
+            report.attribute(mark, plugin.name());
+            stage.finish(plugin.name(), &report, &control, &mut metadata);
+        }
+
+        if let Some(level) = self.cis.filter(|_| !cancel.is_cancelled()) {
+            let (mark, stage) = (report.mark(), Stage::start(&report, &control));
+            metadata.targets.push(level.name().to_string());
+            report.findings.extend(CisAudit::new(level).run_audit());
+            set_stream_target(&mut control, level.name());
+            stream_added(&mut control, &report, mark);
+            report.attribute(mark, level.name());
+            stage.finish(level.name(), &report, &control, &mut metadata);
+        }
+
+        if !self.rules.is_empty() || !self.ownership_rules.is_empty() || !self.audit_rules.is_empty() {
+            metadata.targets.push("rules".to_string());
+        }
+        if !self.configs.is_empty() || !configs.is_empty() {
+            metadata.targets.push("config".to_string());
+        }
+        let (mark, stage) = (report.mark(), Stage::start(&report, &control));
+        set_stream_target(&mut control, "rules");
+        let mut visited = HashSet::new();
+        for rule in &self.rules {
+            report.permissions.extend(rule.check_with(&mut visited, &mut control));
+        }
+        for rule in self.ownership_rules.iter().take_while(|_| !cancel.is_cancelled()) {
+            report.ownership.push(rule.check_ownership_with(&context));
+        }
+        for rule in self.audit_rules.iter().take_while(|_| !cancel.is_cancelled()) {
+            report.findings.extend(rule.evaluate(&context));
+        }
+        stream_added(&mut control, &report, mark);
+        report.attribute(mark, "rules");
+        stage.finish("rules", &report, &control, &mut metadata);
+
+        let (mark, stage) = (report.mark(), Stage::start(&report, &control));
+        set_stream_target(&mut control, "config");
+        for config in self.configs.iter().chain(&configs) {
+            if cancel.is_cancelled() {
+                break;
+            }
+            report.permissions.extend(config.permission_results_with(&mut control)?);
+            report.ownership.extend(config.ownership_results_with(&context)?);
+            report.findings.extend(config.sysctl_findings());
+            report.findings.extend(config.content_findings());
+            report.findings.extend(config.absence_findings());
+            report.findings.extend(config.firewall_findings());
+            report.findings.extend(config.tls_findings());
+            report.findings.extend(config.logrotate_findings());
+        }
+        stream_added(&mut control, &report, mark);
+        report.attribute(mark, "config");
+        stage.finish("config", &report, &control, &mut metadata);
+
+        let conflicts = report.dedup();
+        set_stream_target(&mut control, UNATTRIBUTED);
+        report.findings[report.findings.len() - conflicts..]
+            .iter()
+            .for_each(|f| control.publish(ResultRef::Finding(f)));
+
+        metadata.truncated = control.truncations().to_vec();
+        metadata.cancelled = cancel.is_cancelled();
+        if metadata.cancelled {
+            info!("Audit cancelled: {} results so far", report.len());
+        }
+        control.finish().map_err(HaloError::Output)?;
+        let cache = context.stats();
+        debug!("Metadata cache: {} hits, {} misses", cache.hits, cache.misses);
+
+        report.apply_waivers(&waivers, crate::waiver::today());
+
+        if !self.footprint.keep_passing() {
+            report.retain_failures();
+        }
+        if let Some(min) = &self.min_severity {
+            report.retain_min_severity(min);
+        }
+        metadata.duration_ms = started.elapsed().as_millis() as u64;
+        info!("Audit finished: {} results in {} ms", report.len(), metadata.duration_ms);
+        report.metadata = Some(metadata);
+        Ok(report)
+    }
+}
+
+// Sets the target reported for the following streamed results
+fn set_stream_target(control: &mut ScanControl, target: &str) {
+    if let Some(stream) = control.stream_mut() {
+        stream.set_target(target);
+    }
+}
+
+// Publishes the ownership results and findings added since `mark`; permission results are
+// published by the walk itself
+fn stream_added(control: &mut ScanControl, report: &AuditReport, mark: ReportMark) {
+    report.ownership[mark.ownership..].iter().for_each(|r| control.publish(ResultRef::Ownership(r)));
+    report.findings[mark.findings..].iter().for_each(|f| control.publish(ResultRef::Finding(f)));
+}
+
+// Timing of one target or rule set, logged at info level and recorded in the metadata of
+// targets that ran
+struct Stage {
+    started: Instant,
+    results: usize,
+    files: u64,
+}
+
+impl Stage {
+    fn start(report: &AuditReport, control: &ScanControl) -> Self {
+        Self {
+            started: Instant::now(),
+            results: report.len(),
+            files: Self::files(report, control),
+        }
+    }
+
+    // Paths checked by permission walks and ownership rules
+    fn files(report: &AuditReport, control: &ScanControl) -> u64 {
+        control.files_scanned() + report.ownership.len() as u64
+    }
+
+    fn finish(self, name: &str, report: &AuditReport, control: &ScanControl, metadata: &mut RunMetadata) {
+        let elapsed = self.started.elapsed();
+        let added = report.len().saturating_sub(self.results);
+        if added > 0 {
+            info!("{}: {} results in {} ms", name, added, elapsed.as_millis());
+        } else {
+            debug!("{}: no results", name);
+        }
+        if metadata.targets.iter().any(|t| t == name) {
+            let files = Self::files(report, control) - self.files;
+            let timing = TargetTiming::new(name, elapsed, added, files);
+            control.observers().iter().for_each(|o| o.on_target_complete(&timing));
+            metadata.timings.push(timing);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Importance;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_all_expands_without_embedded() {
+        let targets = Target::All.expand();
+        assert!(targets.contains(&Target::User));
+        assert!(!targets.contains(&Target::Embedded));
+        assert!(!targets.contains(&Target::All));
+    }
+
+    #[test]
+    fn test_k8s_node_names() {
+        assert_eq!(Target::K8sNode.name(), "k8s-node");
+        assert_eq!(Target::from_name("k8s-node"), Some(Target::K8sNode));
+        assert_eq!(serde_json::to_string(&Target::K8sNode).unwrap(), "\"k8s-node\"");
+    }
+
+    #[test]
+    fn test_rules_and_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("secret");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+        let config = dir.path().join("config.toml");
+        fs::write(
+            &config,
+            format!(
+                "[[perm_rules]]\npath = \"{}\"\nexpected_mode = 644\nimportance = \"Low\"\n",
+                file.display()
+            ),
+        )
+        .unwrap();
+
+        let (rule, _) = PermissionRules::new(file.clone(), 0o600, Importance::High);
+        let report = Audit::new().rule(rule).config_file(&config).run().unwrap();
+        // Both cover the file; the stricter expectation is kept
+        assert_eq!(report.permissions.len(), 1);
+        assert_eq!(report.permissions[0].expected_mode, 0o600);
+        assert_eq!(report.failure_count(), 1);
+        let metadata = report.metadata.as_ref().unwrap();
+        assert_eq!(metadata.targets, ["rules", "config"]);
+        assert_eq!(metadata.halo_version, env!("CARGO_PKG_VERSION"));
+        let timed: Vec<_> = metadata.timings.iter().map(|t| (t.target.as_str(), t.results, t.files)).collect();
+        assert_eq!(timed, [("rules", 1, 1), ("config", 1, 1)]);
+
+        let (rule, _) = PermissionRules::new(file, 0o600, Importance::High);
+        let report = Audit::new()
+            .rule(rule)
+            .config_file(&config)
+            .footprint(Footprint::Low)
+            .run()
+            .unwrap();
+        assert_eq!(report.permissions.len(), 1);
+        // The passing result is dropped but was scanned
+        assert_eq!(report.summary().timings[1].files, 1);
+    }
+
+    #[test]
+    fn test_run_streaming() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a", "b"] {
+            fs::write(dir.path().join(name), "").unwrap();
+            fs::set_permissions(dir.path().join(name), fs::Permissions::from_mode(0o644)).unwrap();
+        }
+        let out_dir = tempfile::tempdir().unwrap();
+        let out = out_dir.path().join("out.jsonl");
+        let (rule, _) = PermissionRules::new(dir.path().to_path_buf(), 0o600, Importance::Low);
+        let stream = ResultStream::new(fs::File::create(&out).unwrap());
+        let report = Audit::new().rule(rule).run_streaming(stream).unwrap();
+        assert_eq!(report.failure_count(), 2);
+
+        let lines: Vec<serde_json::Value> = fs::read_to_string(&out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), report.permissions.len());
+        assert!(lines.iter().all(|l| l["kind"] == "permission" && l["target"] == "rules"));
+    }
+
+    #[test]
+    fn test_cancel_keeps_partial_results() {
+        struct CancelAfter(CancelToken, usize, std::sync::atomic::AtomicUsize);
+        impl AuditObserver for CancelAfter {
+            fn on_finding(&self, _result: ResultRef<'_>) {
+                if self.2.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1 == self.1 {
+                    self.0.cancel();
+                }
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..10 {
+            fs::write(dir.path().join(i.to_string()), "").unwrap();
+        }
+        let rule = || PermissionRules::new(dir.path().to_path_buf(), 0o600, Importance::Low).0;
+        let (owner, _) = OwnershipRule::new(dir.path().to_path_buf(), 0, 0, true);
+        let token = CancelToken::new();
+        let report = Audit::new()
+            .rule(rule())
+            .ownership_rule(owner)
+            .jobs(1)
+            .cancel_token(token.clone())
+            .observer(CancelAfter(token, 3, Default::default()))
+            .run()
+            .unwrap();
+        assert_eq!(report.permissions.len(), 3);
+        assert!(report.ownership.is_empty());
+        assert!(report.summary().cancelled);
+
+        // A parallel walk cancelled up front finds nothing
+        let token = CancelToken::new();
+        token.cancel();
+        let report = Audit::new().rule(rule()).jobs(4).cancel_token(token).run().unwrap();
+        assert!(report.is_empty());
+        assert!(report.metadata.unwrap().cancelled);
+        let complete = Audit::new().rule(rule()).run().unwrap();
+        assert!(!complete.summary().cancelled);
+        assert!(serde_json::to_value(complete.summary()).unwrap().get("cancelled").is_none());
+    }
+
+    #[test]
+    fn test_missing_config_file_is_an_error() {
+        let result = Audit::new().config_file("/nonexistent/halo.toml").run();
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,378 @@
+//! CIS benchmark profiles for HALO.
+//!
+//! Maps HALO's checks to controls of the CIS Distribution Independent Linux Benchmark so a
+//! run can be read as a compliance report. `check --profile cis-l1` runs the Level 1
+//! controls, `cis-l2` adds the Level 2 ones. Every finding carries the control id in its
+//! `benchmark` field.
+//!
+//! Only controls HALO can check automatically are included: file permissions and ownership,
+//! kernel parameters, and results of the account, hardening and auditd audits. Controls
+//! about partitioning, package installation or services are not covered, so a clean run is
+//! not a full benchmark pass.
+//!
+//! # Checks
+//! - `cis-file-permissions`: a file or directory has no permission bits beyond the allowed
+//!   mode and is owned by root (group root where required); missing files are skipped
+//! - `sysctl`: a kernel parameter has the benchmark value; parameters the kernel does not
+//!   provide are skipped
+//! - `hardening-core-limit`, `account-empty-password`, `passwd-duplicate-uid0`,
+//!   `auditd-installed`, `auditd-running`, `auditd-missing-watch`: as reported by their audits
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, CisAudit, CisLevel};
+//! let findings = CisAudit::new(CisLevel::L1).run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{} {}: {}", f.benchmark.as_deref().unwrap_or_default(), f.path.display(), f.message);
+//! }
+//! ```
+use crate::audit::{
+    accounts::AccountsAudit,
+    auditd::AuditdAudit,
+    finding::{AuditFindings, Finding},
+    hardening::HardeningAudit,
+    passwd::PasswdAudit,
+    permissions::{audit_permissions::Importance, severity_policy::SeverityPolicy},
+    sysctl::{SysctlRule, check_sysctl_value},
+};
+use crate::Severity;
+use crate::error::HaloError;
+use crate::audit::sysroot;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Benchmark level; Level 2 includes the Level 1 controls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CisLevel {
+    /// Level 1: practical hardening with little impact on functionality
+    L1,
+    /// Level 2: defense in depth for high-security environments
+    L2,
+}
+
+impl CisLevel {
+    /// All levels, in order.
+    pub const ALL: [CisLevel; 2] = [CisLevel::L1, CisLevel::L2];
+
+    /// Profile name used by `check --profile`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CisLevel::L1 => "cis-l1",
+            CisLevel::L2 => "cis-l2",
+        }
+    }
+
+    /// One-line description of the profile.
+    pub fn description(&self) -> &'static str {
+        match self {
+            CisLevel::L1 => "CIS Distribution Independent Linux, Level 1 controls HALO can check",
+            CisLevel::L2 => "CIS Distribution Independent Linux, Level 1 and Level 2 controls",
+        }
+    }
+
+    /// Level for a profile name, e.g. `cis-l1`.
+    pub fn from_name(name: &str) -> Option<CisLevel> {
+        CisLevel::ALL.into_iter().find(|l| l.name() == name)
+    }
+
+    /// Level named by a `--profile`, or `None` for no profile or a `[profiles.<name>]` of the
+    /// config.
+    ///
+    /// # Returns
+    /// * `Err` if `profile` is no level and there is no config to take a subset of.
+    pub fn resolve(profile: Option<&str>, has_config: bool) -> Result<Option<CisLevel>, HaloError> {
+        let Some(name) = profile else {
+            return Ok(None);
+        };
+        match CisLevel::from_name(name) {
+            Some(level) => Ok(Some(level)),
+            None if has_config => Ok(None),
+            None => Err(HaloError::InvalidArgument(format!(
+                "Unknown profile '{}'; see `halo list profiles`",
+                name
+            ))),
+        }
+    }
+}
+
+/// How a control is checked.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CisCheck {
+    /// No permission bits beyond `mode`, owner root and, if `root_group`, group root
+    Permissions {
+        path: &'static str,
+        mode: u32,
+        root_group: bool,
+    },
+    /// Kernel parameter value, see [`SysctlRule`]
+    Sysctl {
+        key: &'static str,
+        expected: &'static str,
+        severity: Severity,
+    },
+    /// Findings of an existing HALO check with this id
+    Check(&'static str),
+}
+
+/// A benchmark control and how HALO checks it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CisControl {
+    /// Control number, e.g. `6.1.2`
+    pub id: &'static str,
+    pub title: &'static str,
+    pub level: CisLevel,
+    pub check: CisCheck,
+}
+
+const fn perms(id: &'static str, title: &'static str, path: &'static str, mode: u32) -> CisControl {
+    CisControl {
+        id,
+        title,
+        level: CisLevel::L1,
+        check: CisCheck::Permissions {
+            path,
+            mode,
+            root_group: true,
+        },
+    }
+}
+
+// Shadow files may belong to group shadow
+const fn shadow_perms(id: &'static str, title: &'static str, path: &'static str) -> CisControl {
+    CisControl {
+        id,
+        title,
+        level: CisLevel::L1,
+        check: CisCheck::Permissions {
+            path,
+            mode: 0o640,
+            root_group: false,
+        },
+    }
+}
+
+const fn sysctl(id: &'static str, title: &'static str, key: &'static str, expected: &'static str) -> CisControl {
+    CisControl {
+        id,
+        title,
+        level: CisLevel::L1,
+        check: CisCheck::Sysctl {
+            key,
+            expected,
+            severity: Severity::Medium,
+        },
+    }
+}
+
+const fn check(id: &'static str, title: &'static str, level: CisLevel, check: &'static str) -> CisControl {
+    CisControl {
+        id,
+        title,
+        level,
+        check: CisCheck::Check(check),
+    }
+}
+
+/// Controls of the CIS Distribution Independent Linux Benchmark checked by HALO.
+pub const CONTROLS: &[CisControl] = &[
+    perms("1.4.1", "Ensure permissions on bootloader config are configured", "/boot/grub/grub.cfg", 0o600),
+    perms("1.4.1", "Ensure permissions on bootloader config are configured", "/boot/grub2/grub.cfg", 0o600),
+    check("1.5.1", "Ensure core dumps are restricted", CisLevel::L1, "hardening-core-limit"),
+    sysctl("1.5.1", "Ensure core dumps are restricted", "fs.suid_dumpable", "0"),
+    CisControl {
+        id: "1.5.3",
+        title: "Ensure address space layout randomization (ASLR) is enabled",
+        level: CisLevel::L1,
+        check: CisCheck::Sysctl {
+            key: "kernel.randomize_va_space",
+            expected: "2",
+            severity: Severity::High,
+        },
+    },
+    perms("1.7.1.4", "Ensure permissions on /etc/motd are configured", "/etc/motd", 0o644),
+    perms("1.7.1.5", "Ensure permissions on /etc/issue are configured", "/etc/issue", 0o644),
+    perms("1.7.1.6", "Ensure permissions on /etc/issue.net are configured", "/etc/issue.net", 0o644),
+    sysctl("3.1.1", "Ensure IP forwarding is disabled", "net.ipv4.ip_forward", "0"),
+    sysctl("3.1.2", "Ensure packet redirect sending is disabled", "net.ipv4.conf.all.send_redirects", "0"),
+    sysctl("3.1.2", "Ensure packet redirect sending is disabled", "net.ipv4.conf.default.send_redirects", "0"),
+    sysctl("3.2.1", "Ensure source routed packets are not accepted", "net.ipv4.conf.all.accept_source_route", "0"),
+    sysctl("3.2.1", "Ensure source routed packets are not accepted", "net.ipv4.conf.default.accept_source_route", "0"),
+    sysctl("3.2.2", "Ensure ICMP redirects are not accepted", "net.ipv4.conf.all.accept_redirects", "0"),
+    sysctl("3.2.2", "Ensure ICMP redirects are not accepted", "net.ipv4.conf.default.accept_redirects", "0"),
+    sysctl("3.2.3", "Ensure secure ICMP redirects are not accepted", "net.ipv4.conf.all.secure_redirects", "0"),
+    sysctl("3.2.3", "Ensure secure ICMP redirects are not accepted", "net.ipv4.conf.default.secure_redirects", "0"),
+    sysctl("3.2.4", "Ensure suspicious packets are logged", "net.ipv4.conf.all.log_martians", "1"),
+    sysctl("3.2.4", "Ensure suspicious packets are logged", "net.ipv4.conf.default.log_martians", "1"),
+    sysctl("3.2.5", "Ensure broadcast ICMP requests are ignored", "net.ipv4.icmp_echo_ignore_broadcasts", "1"),
+    sysctl("3.2.6", "Ensure bogus ICMP responses are ignored", "net.ipv4.icmp_ignore_bogus_error_responses", "1"),
+    sysctl("3.2.7", "Ensure Reverse Path Filtering is enabled", "net.ipv4.conf.all.rp_filter", "1"),
+    sysctl("3.2.7", "Ensure Reverse Path Filtering is enabled", "net.ipv4.conf.default.rp_filter", "1"),
+    sysctl("3.2.8", "Ensure TCP SYN Cookies is enabled", "net.ipv4.tcp_syncookies", "1"),
+    check("4.1.1.1", "Ensure auditd is installed", CisLevel::L2, "auditd-installed"),
+    check("4.1.1.2", "Ensure auditd service is enabled", CisLevel::L2, "auditd-running"),
+    check("4.1.4", "Ensure events that modify user/group information are collected", CisLevel::L2, "auditd-missing-watch"),
+    perms("5.1.2", "Ensure permissions on /etc/crontab are configured", "/etc/crontab", 0o600),
+    perms("5.1.3", "Ensure permissions on /etc/cron.hourly are configured", "/etc/cron.hourly", 0o700),
+    perms("5.1.4", "Ensure permissions on /etc/cron.daily are configured", "/etc/cron.daily", 0o700),
+    perms("5.1.5", "Ensure permissions on /etc/cron.weekly are configured", "/etc/cron.weekly", 0o700),
+    perms("5.1.6", "Ensure permissions on /etc/cron.monthly are configured", "/etc/cron.monthly", 0o700),
+    perms("5.1.7", "Ensure permissions on /etc/cron.d are configured", "/etc/cron.d", 0o700),
+    perms("5.2.1", "Ensure permissions on /etc/ssh/sshd_config are configured", "/etc/ssh/sshd_config", 0o600),
+    perms("6.1.2", "Ensure permissions on /etc/passwd are configured", "/etc/passwd", 0o644),
+    shadow_perms("6.1.3", "Ensure permissions on /etc/shadow are configured", "/etc/shadow"),
+    perms("6.1.4", "Ensure permissions on /etc/group are configured", "/etc/group", 0o644),
+    shadow_perms("6.1.5", "Ensure permissions on /etc/gshadow are configured", "/etc/gshadow"),
+    perms("6.1.6", "Ensure permissions on /etc/passwd- are configured", "/etc/passwd-", 0o644),
+    shadow_perms("6.1.7", "Ensure permissions on /etc/shadow- are configured", "/etc/shadow-"),
+    perms("6.1.8", "Ensure permissions on /etc/group- are configured", "/etc/group-", 0o644),
+    shadow_perms("6.1.9", "Ensure permissions on /etc/gshadow- are configured", "/etc/gshadow-"),
+    check("6.2.1", "Ensure password fields are not empty", CisLevel::L1, "account-empty-password"),
+    check("6.2.6", "Ensure root is the only UID 0 account", CisLevel::L1, "passwd-duplicate-uid0"),
+];
+
+// Runs one of the audits behind `CisCheck::Check` controls
+type RunAudit = fn() -> Vec<Finding>;
+
+/// Runs the CIS controls of one level.
+pub struct CisAudit {
+    pub level: CisLevel,
+    /// Root of the sysctl tree, normally `/proc/sys`
+    pub proc_sys: PathBuf,
+}
+
+impl CisAudit {
+    /// Audit of the controls of `level` on this host.
+    pub fn new(level: CisLevel) -> Self {
+        Self {
+            level,
+            proc_sys: "/proc/sys".into(),
+        }
+    }
+
+    /// Controls of this audit's level, including lower levels.
+    pub fn controls(&self) -> impl Iterator<Item = &'static CisControl> + '_ {
+        CONTROLS
+            .iter()
+            .filter(|c| c.level == CisLevel::L1 || self.level == CisLevel::L2)
+    }
+}
+
+impl AuditFindings for CisAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let controls: Vec<&CisControl> = self.controls().collect();
+        let mut findings = Vec::new();
+        for control in &controls {
+            match control.check {
+                CisCheck::Permissions { path, mode, root_group } => {
+                    if let Ok(meta) = sysroot::metadata(Path::new(path)) {
+                        let found = meta.mode() & 0o7777;
+                        findings.push(
+                            check_file(Path::new(path), mode, root_group, found, meta.uid(), meta.gid())
+                                .with_benchmark(benchmark_id(control)),
+                        );
+                    }
+                }
+                CisCheck::Sysctl { key, expected, ref severity } => {
+                    let path = self.proc_sys.join(key.replace('.', "/"));
+                    if let Ok(actual) = sysroot::read_to_string(&path) {
+                        let rule = SysctlRule::new(key, expected, severity.clone());
+                        findings.push(check_sysctl_value(&path, &rule, &actual).with_benchmark(benchmark_id(control)));
+                    }
+                }
+                CisCheck::Check(_) => {}
+            }
+        }
+
+        // Run each audit behind a mapped check once and keep the mapped findings
+        let audits: [(&str, RunAudit); 4] = [
+            ("hardening-", || HardeningAudit::default().run_audit()),
+            ("account-", || AccountsAudit::default().run_audit()),
+            ("passwd-", || PasswdAudit::default().run_audit()),
+            ("auditd-", || AuditdAudit::default().run_audit()),
+        ];
+        let mapped = |check: &str| {
+            controls
+                .iter()
+                .find(|c| matches!(c.check, CisCheck::Check(id) if id == check))
+        };
+        for (prefix, run) in audits {
+            if !controls.iter().any(|c| matches!(c.check, CisCheck::Check(id) if id.starts_with(prefix))) {
+                continue;
+            }
+            for finding in run() {
+                if let Some(control) = mapped(&finding.check) {
+                    let id = benchmark_id(control);
+                    findings.push(finding.with_benchmark(id));
+                }
+            }
+        }
+        findings
+    }
+}
+
+/// Benchmark reference stored in findings, e.g. `CIS 6.1.2`.
+pub fn benchmark_id(control: &CisControl) -> String {
+    format!("CIS {}", control.id)
+}
+
+/// Checks a file's mode and owner against a permissions control.
+///
+/// # Arguments
+/// * `mode` - Allowed permission bits; any other bit fails
+/// * `root_group` - Whether the group must be root as well as the owner
+/// * `found`, `uid`, `gid` - Mode and owner of the file
+pub fn check_file(path: &Path, mode: u32, root_group: bool, found: u32, uid: u32, gid: u32) -> Finding {
+    if found & !mode != 0 {
+        let severity = SeverityPolicy::default().severity(mode, found, &Importance::High);
+        return Finding::fail(
+            "cis-file-permissions",
+            path,
+            severity,
+            format!("Mode is {:o}, allowed at most {:o}", found, mode),
+        );
+    }
+    if uid != 0 || (root_group && gid != 0) {
+        return Finding::fail(
+            "cis-file-permissions",
+            path,
+            Severity::High,
+            format!("Owned by {}:{}, expected root{}", uid, gid, if root_group { ":root" } else { "" }),
+        );
+    }
+    Finding::pass("cis-file-permissions", path, format!("Mode {:o}, owned by root", found))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_and_profile_names() {
+        assert_eq!(CisLevel::from_name("cis-l2"), Some(CisLevel::L2));
+        assert_eq!(CisLevel::from_name("cis-l3"), None);
+        assert_eq!(CisLevel::resolve(Some("cis-l1"), false).unwrap(), Some(CisLevel::L1));
+        assert_eq!(CisLevel::resolve(Some("web"), true).unwrap(), None);
+        assert!(CisLevel::resolve(Some("web"), false).is_err());
+        let l1 = CisAudit::new(CisLevel::L1).controls().count();
+        let l2 = CisAudit::new(CisLevel::L2).controls().count();
+        assert!(l1 > 0 && l2 > l1);
+        assert!(CisAudit::new(CisLevel::L1).controls().all(|c| c.level == CisLevel::L1));
+    }
+
+    #[test]
+    fn test_check_file() {
+        let shadow = Path::new("/etc/shadow");
+        assert!(!check_file(shadow, 0o640, false, 0o640, 0, 42).is_failure());
+        assert!(!check_file(shadow, 0o640, false, 0o600, 0, 42).is_failure());
+        let world = check_file(shadow, 0o640, false, 0o644, 0, 42);
+        assert_eq!(world.severity, Severity::High);
+        assert_eq!(check_file(shadow, 0o640, false, 0o666, 0, 0).severity, Severity::Critical);
+
+        let passwd = Path::new("/etc/passwd");
+        assert!(check_file(passwd, 0o644, true, 0o644, 0, 42).is_failure());
+        assert_eq!(
+            check_file(passwd, 0o644, true, 0o644, 0, 0).with_benchmark("CIS 6.1.2").benchmark.as_deref(),
+            Some("CIS 6.1.2")
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Content rules for HALO.
+//!
+//! A content rule names a file and a regular expression the file must match, must not match,
+//! or both. Policies like "sshd_config must contain `PermitRootLogin no`" or "no `nullok` in
+//! the PAM stack" can then be expressed in the config without a dedicated parser for every
+//! file type. Expressions are matched in multi-line mode, so `^` and `$` anchor at line
+//! boundaries. Paths may contain `*` and `?` wildcards like other config rules.
+//!
+//! # Checks
+//! - `content-must-match`: the file contains a match for `must_match`
+//! - `content-must-not-match`: the file contains no match for `must_not_match`; a failure
+//!   names the first matching line
+//! - `content-unreadable`: the file could not be read
+//!
+//! # Example TOML
+//! ```toml
+//! [[content_rules]]
+//! id = "SITE-SSH-0001" # optional, reported as the subject
+//! path = "/etc/ssh/sshd_config"
+//! must_match = '^\s*PermitRootLogin\s+no\b'
+//! severity = "High"
+//!
+//! [[content_rules]]
+//! path = "/etc/pam.d/*"
+//! must_not_match = '\bnullok\b'
+//! severity = "Medium"
+//! ```
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, ContentAudit, ContentRule, Severity};
+//! let rule = ContentRule::new("/etc/ssh/sshd_config", Severity::High).must_match(r"^\s*PermitRootLogin\s+no\b");
+//! for finding in ContentAudit::new(vec![rule]).run_audit() {
+//!     println!("{:?} {}", finding.status, finding.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::audit::glob;
+use crate::Severity;
+use crate::error::HaloError;
+use crate::audit::sysroot;
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Regular expressions a file must or must not match.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ContentRule {
+    /// File to read; may contain `*` and `?` wildcards
+    pub path: String,
+    /// Expression the file must contain a match for
+    #[serde(default)]
+    pub must_match: Option<String>,
+    /// Expression the file must not contain a match for
+    #[serde(default)]
+    pub must_not_match: Option<String>,
+    /// Severity of a failed expectation
+    pub severity: Severity,
+    /// Rule id reported as the finding subject; the expression is used if unset
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+impl ContentRule {
+    /// Rule for `path` with no expressions yet.
+    pub fn new(path: &str, severity: Severity) -> Self {
+        Self {
+            path: path.to_string(),
+            must_match: None,
+            must_not_match: None,
+            severity,
+            id: None,
+        }
+    }
+
+    /// Require a match for `pattern`.
+    pub fn must_match(mut self, pattern: &str) -> Self {
+        self.must_match = Some(pattern.to_string());
+        self
+    }
+
+    /// Forbid a match for `pattern`.
+    pub fn must_not_match(mut self, pattern: &str) -> Self {
+        self.must_not_match = Some(pattern.to_string());
+        self
+    }
+
+    /// Checks that the rule has a path, at least one expression, and that the expressions
+    /// compile.
+    pub fn validate(&self) -> Result<(), HaloError> {
+        if self.path.trim().is_empty() {
+            return Err(HaloError::InvalidConfig("Content rule has empty or invalid path.".to_string()));
+        }
+        if self.must_match.is_none() && self.must_not_match.is_none() {
+            return Err(HaloError::InvalidConfig(format!(
+                "Content rule for '{}' needs must_match or must_not_match.",
+                self.path
+            )));
+        }
+        for pattern in self.must_match.iter().chain(&self.must_not_match) {
+            compile(pattern).map_err(|e| {
+                HaloError::InvalidConfig(format!("Invalid regex '{}' for path '{}': {}", pattern, self.path, e))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Checks `content` of the file at `path` against the rule.
+    ///
+    /// Expressions that do not compile are skipped; see [`validate`](Self::validate).
+    pub fn check_content(&self, path: &Path, content: &str) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        if let Some(pattern) = &self.must_match
+            && let Ok(re) = compile(pattern)
+        {
+            let finding = if re.is_match(content) {
+                Finding::pass("content-must-match", path, format!("Contains a match for `{}`", pattern))
+            } else {
+                Finding::fail(
+                    "content-must-match",
+                    path,
+                    self.severity.clone(),
+                    format!("No match for required `{}`", pattern),
+                )
+            };
+            findings.push(finding.with_subject(self.subject(pattern)));
+        }
+        if let Some(pattern) = &self.must_not_match
+            && let Ok(re) = compile(pattern)
+        {
+            let finding = match re.find(content) {
+                Some(m) => {
+                    let line_no = content[..m.start()].matches('\n').count() + 1;
+                    let line = content.lines().nth(line_no - 1).unwrap_or_default().trim();
+                    Finding::fail(
+                        "content-must-not-match",
+                        path,
+                        self.severity.clone(),
+                        format!("Line {} matches forbidden `{}`: {}", line_no, pattern, line),
+                    )
+                }
+                None => Finding::pass(
+                    "content-must-not-match",
+                    path,
+                    format!("No match for forbidden `{}`", pattern),
+                ),
+            };
+            findings.push(finding.with_subject(self.subject(pattern)));
+        }
+        findings
+    }
+
+    fn subject(&self, pattern: &str) -> String {
+        self.id.clone().unwrap_or_else(|| pattern.to_string())
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).multi_line(true).build()
+}
+
+/// Runs content rules against the files they name.
+#[derive(Debug, Clone, Default)]
+pub struct ContentAudit {
+    pub rules: Vec<ContentRule>,
+}
+
+impl ContentAudit {
+    pub fn new(rules: Vec<ContentRule>) -> Self {
+        Self { rules }
+    }
+}
+
+impl AuditFindings for ContentAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for rule in &self.rules {
+            let paths = if glob::is_pattern(&rule.path) {
+                glob::expand(&rule.path)
+            } else {
+                vec![PathBuf::from(&rule.path)]
+            };
+            for path in paths {
+                match sysroot::read_to_string(&path) {
+                    Ok(content) => findings.extend(rule.check_content(&path, &content)),
+                    Err(e) => findings.push(
+                        Finding::fail(
+                            "content-unreadable",
+                            &path,
+                            Severity::Info,
+                            format!("Cannot read file: {}", e),
+                        )
+                        .with_subject(rule.id.clone().unwrap_or_else(|| rule.path.clone())),
+                    ),
+                }
+            }
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::permissions::audit_permissions::Status;
+
+    const SSHD: &str = "Port 22\n#PermitRootLogin yes\nPermitRootLogin prohibit-password\n";
+
+    #[test]
+    fn test_must_match_and_must_not_match() {
+        let path = Path::new("/etc/ssh/sshd_config");
+        let rule = ContentRule::new("/etc/ssh/sshd_config", Severity::High)
+            .must_match(r"^\s*PermitRootLogin\s+no\b")
+            .must_not_match(r"^\s*PermitRootLogin\s+(yes|prohibit-password)");
+        let findings = rule.check_content(path, SSHD);
+        assert_eq!(findings.len(), 2);
+        assert!(findings.iter().all(|f| f.status == Status::Fail));
+        assert_eq!(findings[1].message, "Line 3 matches forbidden `^\\s*PermitRootLogin\\s+(yes|prohibit-password)`: PermitRootLogin prohibit-password");
+
+        let fixed = SSHD.replace("prohibit-password", "no");
+        let findings = rule.check_content(path, &fixed);
+        assert!(findings.iter().all(|f| f.status == Status::Pass));
+    }
+
+    #[test]
+    fn test_validate_and_unreadable() {
+        assert!(ContentRule::new("/etc/x", Severity::Low).validate().is_err());
+        assert!(ContentRule::new("/etc/x", Severity::Low).must_match("(").validate().is_err());
+        let rule = ContentRule::new("/nonexistent/halo", Severity::Low).must_match("x");
+        assert!(rule.validate().is_ok());
+        let findings = ContentAudit::new(vec![rule]).run_audit();
+        assert_eq!(findings[0].check, "content-unreadable");
+    }
+}
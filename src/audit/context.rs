@@ -0,0 +1,206 @@
+//! Shared state of one audit run.
+//!
+//! Large configs often cover a path with several rules, e.g. a permission rule on `/etc`
+//! and an ownership rule on `/etc/shadow`. An [`AuditContext`] is created once per
+//! [`Audit`](crate::Audit) run and handed to every rule type, so such a path is read with one
+//! `stat` instead of one per rule: its metadata cache keeps the result of each lookup by path,
+//! failures included. A `symlink_metadata` result that is not a symlink also answers the
+//! following `metadata` lookup.
+//!
+//! The cache holds at most [`DEFAULT_CAPACITY`] paths; lookups beyond that are not stored, so
+//! a million-file walk does not keep every file's metadata. Results are not invalidated, so a
+//! context should not outlive its run. With [`Footprint::Low`](crate::Footprint::Low) runs
+//! use an uncached context.
+//!
+//! The context also holds the mount table, read once per run when a walk first reaches a mount
+//! point, see [`mounts`](crate::audit::mounts), and the alternate root of the run: the
+//! [`sysroot::root`] of the thread that created it, entered again by the workers of a
+//! parallel walk.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::audit::context::AuditContext;
+//! use std::path::Path;
+//! let context = AuditContext::cached();
+//! let first = context.symlink_metadata(Path::new("/etc")).unwrap();
+//! let again = context.metadata(Path::new("/etc")).unwrap();
+//! assert_eq!(first.len(), again.len());
+//! assert_eq!(context.stats().hits, 1);
+//! ```
+use crate::audit::mounts::MountTable;
+use crate::audit::sysroot;
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Paths kept by [`AuditContext::cached`].
+pub const DEFAULT_CAPACITY: usize = 100_000;
+
+// A lookup result; errors are kept as kind and message since io::Error is not Clone
+type Lookup = Result<Metadata, (io::ErrorKind, String)>;
+
+/// Lookups served from the cache and from the filesystem.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Per-run state shared by all rules of an audit.
+#[derive(Debug)]
+pub struct AuditContext {
+    // (path, follows symlinks) -> result; `None` when caching is off
+    cache: Option<Mutex<HashMap<(PathBuf, bool), Lookup>>>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    mounts: OnceLock<MountTable>,
+    root: Option<PathBuf>,
+}
+
+impl Default for AuditContext {
+    fn default() -> Self {
+        Self {
+            cache: None,
+            capacity: 0,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            mounts: OnceLock::new(),
+            root: sysroot::root(),
+        }
+    }
+}
+
+impl AuditContext {
+    /// A context that does not cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A context caching up to [`DEFAULT_CAPACITY`] paths.
+    pub fn cached() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// A context caching up to `capacity` paths.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            cache: Some(Mutex::new(HashMap::new())),
+            capacity,
+            ..Self::default()
+        }
+    }
+
+    /// Like [`sysroot::metadata`], from the cache if the path was looked up before.
+    pub fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.lookup(path, true)
+    }
+
+    /// Like [`sysroot::symlink_metadata`], from the cache if the path was looked up before.
+    pub fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.lookup(path, false)
+    }
+
+    /// Like [`sysroot::is_file`].
+    pub fn is_file(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok_and(|m| m.is_file())
+    }
+
+    /// Like [`sysroot::is_dir`].
+    pub fn is_dir(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok_and(|m| m.is_dir())
+    }
+
+    /// The mount table of this machine, read on first use.
+    pub fn mount_table(&self) -> &MountTable {
+        self.mounts.get_or_init(MountTable::load)
+    }
+
+    /// The alternate root of the run, if any.
+    pub fn root(&self) -> Option<&Path> {
+        self.root.as_deref()
+    }
+
+    /// Lookups so far.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn lookup(&self, path: &Path, follow: bool) -> io::Result<Metadata> {
+        let Some(cache) = &self.cache else {
+            return stat(path, follow);
+        };
+        let cached = {
+            let cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+            cache.get(&(path.to_path_buf(), follow)).cloned().or_else(|| {
+                // Metadata of a path that is not a symlink is the same either way
+                cache
+                    .get(&(path.to_path_buf(), false))
+                    .filter(|lookup| follow && lookup.as_ref().is_ok_and(|m| !m.file_type().is_symlink()))
+                    .cloned()
+            })
+        };
+        if let Some(lookup) = cached {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return lookup.map_err(|(kind, message)| io::Error::new(kind, message));
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let result = stat(path, follow);
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.len() < self.capacity {
+            let lookup = match &result {
+                Ok(meta) => Ok(meta.clone()),
+                Err(e) => Err((e.kind(), e.to_string())),
+            };
+            cache.insert((path.to_path_buf(), follow), lookup);
+        }
+        result
+    }
+}
+
+fn stat(path: &Path, follow: bool) -> io::Result<Metadata> {
+    if follow {
+        sysroot::metadata(path)
+    } else {
+        sysroot::symlink_metadata(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_cache_hits_and_capacity() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        fs::write(&file, "x").unwrap();
+        std::os::unix::fs::symlink(&file, dir.path().join("link")).unwrap();
+
+        let context = AuditContext::with_capacity(3);
+        assert!(context.is_file(&file));
+        fs::remove_file(&file).unwrap();
+        // Served from the cache although the file is gone
+        assert!(context.metadata(&file).is_ok());
+        // A symlink's own metadata does not answer a lookup that follows it
+        assert!(context.symlink_metadata(&dir.path().join("link")).unwrap().file_type().is_symlink());
+        assert!(context.metadata(&dir.path().join("link")).is_err());
+        assert_eq!(context.stats(), CacheStats { hits: 1, misses: 3 });
+        // Errors are cached too, until the cache is full
+        assert_eq!(context.metadata(&dir.path().join("link")).unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert!(context.metadata(&dir.path().join("missing")).is_err());
+        assert!(context.metadata(&dir.path().join("missing")).is_err());
+        assert_eq!(context.stats(), CacheStats { hits: 2, misses: 5 });
+
+        let uncached = AuditContext::new();
+        assert!(uncached.metadata(dir.path()).is_ok());
+        assert_eq!(uncached.stats(), CacheStats::default());
+    }
+}
@@ -0,0 +1,439 @@
+//! Docker host audit for HALO.
+//!
+//! Access to the Docker daemon is equivalent to root on the host: anyone who can talk to the
+//! socket can start a privileged container with the host filesystem mounted. This audit checks
+//! who can reach the daemon and how the daemon is configured.
+//!
+//! # Checks
+//! - `docker-socket-owner`: `/var/run/docker.sock` is owned by root and group `root` or `docker`
+//! - `docker-socket-mode`: the socket is not accessible to other users
+//! - `docker-userns-remap`: `daemon.json` enables user namespace remapping
+//! - `docker-live-restore`: `daemon.json` enables live restore
+//! - `docker-tcp-without-tls`: the daemon does not listen on TCP without `tlsverify`
+//! - `docker-group-member`: one finding per member of the `docker` group
+//! - `docker-container`: one finding per running container, failing if it runs privileged,
+//!   shares the host PID or network namespace, or bind-mounts a sensitive host path
+//! - `docker-api`: the Docker API could not be queried (usually: not run as root)
+//!
+//! Running containers are listed through the Docker API on the socket, so no `docker` CLI is
+//! needed.
+//!
+//! Hosts without a Docker socket or `daemon.json` get a single passing `docker` finding.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, DockerAudit};
+//! let findings = DockerAudit::default().run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{}: {}", f.check, f.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::audit::passwd::parse_passwd;
+use crate::Severity;
+use crate::audit::sysroot;
+use serde_json::Value;
+use std::io::{self, Read, Write};
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Host paths that give a container control over the host when bind-mounted.
+const SENSITIVE_MOUNTS: &[&str] = &[
+    "/", "/etc", "/root", "/boot", "/dev", "/proc", "/sys", "/var/lib/docker", "/run/docker.sock",
+    "/var/run/docker.sock",
+];
+
+/// Docker socket, daemon configuration and `docker` group membership.
+pub struct DockerAudit {
+    /// Docker daemon socket
+    pub socket: PathBuf,
+    /// Docker daemon configuration file
+    pub daemon_json: PathBuf,
+    /// Group database used to resolve the socket group and `docker` members
+    pub group_file: PathBuf,
+    /// Account database used to find users whose primary group is `docker`
+    pub passwd_file: PathBuf,
+}
+
+/// Provides the default Docker paths.
+impl Default for DockerAudit {
+    fn default() -> Self {
+        Self {
+            socket: "/var/run/docker.sock".into(),
+            daemon_json: "/etc/docker/daemon.json".into(),
+            group_file: "/etc/group".into(),
+            passwd_file: "/etc/passwd".into(),
+        }
+    }
+}
+
+impl AuditFindings for DockerAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let socket = sysroot::metadata(&self.socket).ok();
+        let daemon_json = sysroot::read_to_string(&self.daemon_json).ok();
+        if socket.is_none() && daemon_json.is_none() {
+            return vec![Finding::pass("docker", &self.socket, "Docker is not installed")];
+        }
+
+        let groups = sysroot::read_to_string(&self.group_file).unwrap_or_default();
+        let mut findings = Vec::new();
+        if let Some(meta) = socket {
+            let group = group_name(&groups, meta.gid());
+            findings.extend(check_socket(
+                &self.socket,
+                meta.mode(),
+                meta.uid(),
+                group.as_deref(),
+            ));
+            findings.extend(self.container_findings());
+        }
+        match daemon_json {
+            Some(content) => findings.extend(check_daemon_json(&self.daemon_json, &content)),
+            None => findings.push(Finding::fail(
+                "docker-userns-remap",
+                &self.daemon_json,
+                Severity::Medium,
+                "No daemon.json; containers run without user namespace remapping",
+            )),
+        }
+        let passwd = sysroot::read_to_string(&self.passwd_file).unwrap_or_default();
+        let members = docker_group_members(&groups, &passwd);
+        findings.extend(check_group_members(&self.group_file, &members));
+        findings
+    }
+}
+
+impl DockerAudit {
+    /// One finding per running container, from the Docker API.
+    fn container_findings(&self) -> Vec<Finding> {
+        let inspected = docker_get(&self.socket, "/containers/json").and_then(|list| {
+            list.as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|c| c.get("Id")?.as_str())
+                .map(|id| docker_get(&self.socket, &format!("/containers/{}/json", id)))
+                .collect::<io::Result<Vec<Value>>>()
+        });
+        match inspected {
+            Ok(containers) => containers
+                .iter()
+                .map(|c| check_container(&self.socket, c))
+                .collect(),
+            Err(e) => vec![Finding::fail(
+                "docker-api",
+                &self.socket,
+                Severity::Info,
+                format!("Running containers not inspected: {}", e),
+            )],
+        }
+    }
+}
+
+/// Sends a GET request to the Docker API on `socket` and parses the JSON response.
+fn docker_get(socket: &Path, endpoint: &str) -> io::Result<Value> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    // HTTP/1.0 makes the daemon close the connection after the response, without chunking
+    write!(stream, "GET {} HTTP/1.0\r\nHost: docker\r\n\r\n", endpoint)?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed Docker API response"))?;
+    let status = head.lines().next().unwrap_or_default();
+    if !status.split_whitespace().nth(1).is_some_and(|code| code.starts_with('2')) {
+        return Err(io::Error::other(format!("Docker API {}: {}", endpoint, status)));
+    }
+    serde_json::from_str(body).map_err(io::Error::other)
+}
+
+/// Checks a container from its `GET /containers/{id}/json` inspect data.
+///
+/// # Returns
+/// One finding for the container: passing, or failing with the worst problem's severity and
+/// every problem listed in the message.
+pub fn check_container(socket: &Path, inspect: &Value) -> Finding {
+    let name = inspect
+        .get("Name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim_start_matches('/');
+    let host_config = &inspect["HostConfig"];
+    let mut problems: Vec<(Severity, String)> = Vec::new();
+
+    if host_config["Privileged"].as_bool() == Some(true) {
+        problems.push((Severity::Critical, "runs --privileged".to_string()));
+    }
+    if host_config["PidMode"].as_str() == Some("host") {
+        problems.push((Severity::High, "shares the host PID namespace".to_string()));
+    }
+    if host_config["NetworkMode"].as_str() == Some("host") {
+        problems.push((Severity::Medium, "shares the host network namespace".to_string()));
+    }
+    let bind_sources = inspect["Mounts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|m| m["Type"].as_str() == Some("bind"))
+        .filter_map(|m| m["Source"].as_str());
+    for source in bind_sources {
+        let source = source.trim_end_matches('/');
+        let source = if source.is_empty() { "/" } else { source };
+        if SENSITIVE_MOUNTS.contains(&source) {
+            let severity = if source.ends_with("docker.sock") || source == "/" {
+                Severity::Critical
+            } else {
+                Severity::High
+            };
+            problems.push((severity, format!("bind-mounts host {}", source)));
+        }
+    }
+
+    let Some(worst) = problems.iter().map(|(s, _)| s).max() else {
+        return Finding::pass("docker-container", socket, format!("Container {} is isolated", name))
+            .with_subject(name);
+    };
+    let reasons: Vec<&str> = problems.iter().map(|(_, r)| r.as_str()).collect();
+    Finding::fail(
+        "docker-container",
+        socket,
+        worst.clone(),
+        format!("Container {} {}", name, reasons.join(", ")),
+    )
+    .with_subject(name)
+}
+
+/// Checks ownership and mode of the Docker socket.
+///
+/// # Arguments
+/// * `path` - Socket path, used in the findings
+/// * `mode` - Mode bits of the socket
+/// * `uid` - Owner UID of the socket
+/// * `group` - Name of the owning group, if it resolves
+pub fn check_socket(path: &Path, mode: u32, uid: u32, group: Option<&str>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let group_ok = matches!(group, Some("root") | Some("docker"));
+    findings.push(if uid == 0 && group_ok {
+        Finding::pass("docker-socket-owner", path, "Docker socket is owned by root")
+    } else {
+        Finding::fail(
+            "docker-socket-owner",
+            path,
+            Severity::High,
+            format!(
+                "Docker socket is owned by uid {} group {}; members can control the daemon as root",
+                uid,
+                group.unwrap_or("unknown")
+            ),
+        )
+    });
+    let other = mode & 0o007;
+    findings.push(if other == 0 {
+        Finding::pass("docker-socket-mode", path, format!("Socket mode {:o}", mode & 0o777))
+    } else {
+        Finding::fail(
+            "docker-socket-mode",
+            path,
+            Severity::Critical,
+            format!(
+                "Socket mode {:o} lets every local user control the Docker daemon",
+                mode & 0o777
+            ),
+        )
+    });
+    findings
+}
+
+/// Checks Docker daemon settings in `daemon.json` content.
+pub fn check_daemon_json(path: &Path, content: &str) -> Vec<Finding> {
+    let config: serde_json::Value = match serde_json::from_str(content) {
+        Ok(config) => config,
+        Err(e) => {
+            return vec![Finding::fail(
+                "docker-daemon-json",
+                path,
+                Severity::Info,
+                format!("daemon.json could not be parsed: {}", e),
+            )];
+        }
+    };
+    let mut findings = Vec::new();
+
+    let remap = config
+        .get("userns-remap")
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.is_empty());
+    findings.push(match remap {
+        Some(remap) => Finding::pass(
+            "docker-userns-remap",
+            path,
+            format!("userns-remap is set to {}", remap),
+        ),
+        None => Finding::fail(
+            "docker-userns-remap",
+            path,
+            Severity::Medium,
+            "userns-remap is not set; root in a container is root on the host",
+        ),
+    });
+
+    findings.push(if config.get("live-restore").and_then(|v| v.as_bool()) == Some(true) {
+        Finding::pass("docker-live-restore", path, "live-restore is enabled")
+    } else {
+        Finding::fail(
+            "docker-live-restore",
+            path,
+            Severity::Low,
+            "live-restore is not enabled; containers stop when the daemon restarts",
+        )
+    });
+
+    let tls_verify = config.get("tlsverify").and_then(|v| v.as_bool()) == Some(true);
+    let tcp_hosts: Vec<&str> = config
+        .get("hosts")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|h| h.as_str())
+        .filter(|h| h.starts_with("tcp://"))
+        .collect();
+    if !tls_verify {
+        for host in tcp_hosts {
+            findings.push(
+                Finding::fail(
+                    "docker-tcp-without-tls",
+                    path,
+                    Severity::Critical,
+                    "Daemon listens on TCP without tlsverify; anyone who can connect controls the host",
+                )
+                .with_subject(host),
+            );
+        }
+    }
+    findings
+}
+
+/// Users in the `docker` group, as supplementary or primary group, in file order.
+pub fn docker_group_members(group_content: &str, passwd_content: &str) -> Vec<String> {
+    let Some(fields) = group_content
+        .lines()
+        .map(|l| l.split(':').collect::<Vec<_>>())
+        .find(|f| f.len() >= 4 && f[0] == "docker")
+    else {
+        return Vec::new();
+    };
+    let mut members: Vec<String> = fields[3]
+        .split(',')
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .map(String::from)
+        .collect();
+    if let Ok(gid) = fields[2].parse::<u32>() {
+        for entry in parse_passwd(passwd_content) {
+            if entry.gid == gid && !members.contains(&entry.name) {
+                members.push(entry.name);
+            }
+        }
+    }
+    members
+}
+
+/// One failing finding per `docker` group member, or a pass if the group is empty.
+pub fn check_group_members(path: &Path, members: &[String]) -> Vec<Finding> {
+    if members.is_empty() {
+        return vec![Finding::pass(
+            "docker-group-member",
+            path,
+            "The docker group has no members",
+        )];
+    }
+    members
+        .iter()
+        .map(|member| {
+            Finding::fail(
+                "docker-group-member",
+                path,
+                Severity::Medium,
+                format!("{} is in the docker group, which is equivalent to root", member),
+            )
+            .with_subject(member.as_str())
+        })
+        .collect()
+}
+
+/// Name of the group with `gid` in `/etc/group` content.
+fn group_name(content: &str, gid: u32) -> Option<String> {
+    content.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.len() >= 3 && fields[2].parse() == Ok(gid)).then(|| fields[0].to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_socket_checks() {
+        let path = Path::new("/var/run/docker.sock");
+        let ok = check_socket(path, 0o140660, 0, Some("docker"));
+        assert!(ok.iter().all(|f| !f.is_failure()));
+        let bad = check_socket(path, 0o140666, 1000, Some("users"));
+        assert_eq!(bad.iter().filter(|f| f.is_failure()).count(), 2);
+        assert_eq!(bad[1].severity, Severity::Critical);
+    }
+
+    #[test]
+    fn test_daemon_json() {
+        let path = Path::new("/etc/docker/daemon.json");
+        let hardened = r#"{"userns-remap": "default", "live-restore": true}"#;
+        assert!(check_daemon_json(path, hardened).iter().all(|f| !f.is_failure()));
+
+        let exposed = r#"{"hosts": ["unix:///var/run/docker.sock", "tcp://0.0.0.0:2375"]}"#;
+        let findings = check_daemon_json(path, exposed);
+        let checks: Vec<&str> = findings
+            .iter()
+            .filter(|f| f.is_failure())
+            .map(|f| f.check.as_str())
+            .collect();
+        assert_eq!(
+            checks,
+            vec!["docker-userns-remap", "docker-live-restore", "docker-tcp-without-tls"]
+        );
+    }
+
+    #[test]
+    fn test_container_inspect() {
+        let socket = Path::new("/var/run/docker.sock");
+        let isolated: Value = serde_json::from_str(
+            r#"{"Name": "/web", "HostConfig": {"Privileged": false, "NetworkMode": "bridge"}, "Mounts": []}"#,
+        )
+        .unwrap();
+        assert!(!check_container(socket, &isolated).is_failure());
+
+        let exposed: Value = serde_json::from_str(
+            r#"{"Name": "/agent", "HostConfig": {"PidMode": "host", "NetworkMode": "host"},
+                "Mounts": [{"Type": "bind", "Source": "/var/run/docker.sock"},
+                           {"Type": "bind", "Source": "/srv/data"}]}"#,
+        )
+        .unwrap();
+        let finding = check_container(socket, &exposed);
+        assert_eq!(finding.severity, Severity::Critical);
+        assert_eq!(finding.subject.as_deref(), Some("agent"));
+        assert_eq!(
+            finding.message,
+            "Container agent shares the host PID namespace, shares the host network namespace, \
+             bind-mounts host /var/run/docker.sock"
+        );
+    }
+
+    #[test]
+    fn test_group_members() {
+        let group = "root:x:0:\ndocker:x:998:alice,bob\n";
+        let passwd = "carol:x:1002:998::/home/carol:/bin/bash\nbob:x:1001:1001::/home/bob:/bin/bash\n";
+        assert_eq!(docker_group_members(group, passwd), vec!["alice", "bob", "carol"]);
+        assert!(docker_group_members("root:x:0:\n", passwd).is_empty());
+    }
+}
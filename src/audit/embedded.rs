@@ -0,0 +1,167 @@
+//! Raspberry Pi and embedded device checks for HALO.
+//!
+//! Permission rules for common single-board computer files live in
+//! [`EmbeddedConfig`](crate::EmbeddedConfig); this module adds the account checks that
+//! go with them.
+//!
+//! # Checks
+//! - `embedded-default-pi-user`: the historical default `pi` account exists and is not locked
+//! - `sudoers-*`: policy checks from the sudoers audit applied to the `010_pi-nopasswd` drop-in
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, EmbeddedAudit};
+//! let findings = EmbeddedAudit::default().run_audit();
+//! println!("{} embedded findings", findings.len());
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::audit::passwd::parse_passwd;
+use crate::audit::sudoers::check_sudoers_content;
+use crate::Severity;
+use crate::audit::sysroot;
+use std::path::PathBuf;
+
+/// Account files checked for Raspberry Pi OS defaults.
+pub struct EmbeddedAudit {
+    pub passwd: PathBuf,
+    pub shadow: PathBuf,
+    pub pi_sudoers: PathBuf,
+    /// Name of the distribution's default account
+    pub default_user: String,
+}
+
+/// Provides default paths for Raspberry Pi OS.
+impl Default for EmbeddedAudit {
+    fn default() -> Self {
+        Self {
+            passwd: "/etc/passwd".into(),
+            shadow: "/etc/shadow".into(),
+            pi_sudoers: "/etc/sudoers.d/010_pi-nopasswd".into(),
+            default_user: "pi".to_string(),
+        }
+    }
+}
+
+impl AuditFindings for EmbeddedAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        let passwd = sysroot::read_to_string(&self.passwd).unwrap_or_default();
+        let has_default_user = parse_passwd(&passwd)
+            .iter()
+            .any(|e| e.name == self.default_user);
+        if has_default_user {
+            // Locked accounts have a password field starting with '!' or '*'
+            let locked = sysroot::read_to_string(&self.shadow).ok().and_then(|shadow| {
+                shadow
+                    .lines()
+                    .filter_map(|l| l.split_once(':'))
+                    .find(|(name, _)| *name == self.default_user)
+                    .map(|(_, rest)| rest.starts_with('!') || rest.starts_with('*'))
+            });
+            let finding = match locked {
+                Some(true) => Finding::pass(
+                    "embedded-default-pi-user",
+                    &self.passwd,
+                    "Default account is locked",
+                ),
+                Some(false) => Finding::fail(
+                    "embedded-default-pi-user",
+                    &self.passwd,
+                    Severity::High,
+                    "Default account can log in; rename it or lock it",
+                ),
+                None => Finding::fail(
+                    "embedded-default-pi-user",
+                    &self.passwd,
+                    Severity::Medium,
+                    "Default account exists (run as root to check whether it is locked)",
+                ),
+            };
+            findings.push(finding.with_subject(&self.default_user));
+        } else {
+            findings.push(
+                Finding::pass(
+                    "embedded-default-pi-user",
+                    &self.passwd,
+                    "Default account does not exist",
+                )
+                .with_subject(&self.default_user),
+            );
+        }
+
+        if let Ok(content) = sysroot::read_to_string(&self.pi_sudoers) {
+            findings.extend(check_sudoers_content(&self.pi_sudoers, &content));
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // Audit of a passwd file with a `pi` account and `shadow` as the shadow file
+    fn audit(dir: &std::path::Path, shadow: Option<&str>) -> Finding {
+        let passwd = dir.join("passwd");
+        fs::write(&passwd, "root:x:0:0:root:/root:/bin/bash\npi:x:1000:1000::/home/pi:/bin/bash\n").unwrap();
+        let shadow_path = dir.join("shadow");
+        match shadow {
+            Some(content) => fs::write(&shadow_path, content).unwrap(),
+            // A directory cannot be read as a file, even by root
+            None => fs::create_dir(&shadow_path).unwrap(),
+        }
+        let audit = EmbeddedAudit {
+            passwd,
+            shadow: shadow_path,
+            pi_sudoers: dir.join("010_pi-nopasswd"),
+            ..Default::default()
+        };
+        let findings = audit.run_audit();
+        assert_eq!(findings.len(), 1);
+        findings.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_default_user_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let finding = audit(dir.path(), Some("root:*:19000::::::\npi:!$6$salt$hash:19000::::::\n"));
+        assert_eq!(finding.check, "embedded-default-pi-user");
+        assert!(!finding.is_failure());
+        assert_eq!(finding.subject.as_deref(), Some("pi"));
+    }
+
+    #[test]
+    fn test_default_user_unlocked() {
+        let dir = tempfile::tempdir().unwrap();
+        let finding = audit(dir.path(), Some("pi:$6$salt$hash:19000::::::\n"));
+        assert!(finding.is_failure());
+        assert_eq!(finding.severity, Severity::High);
+    }
+
+    #[test]
+    fn test_default_user_shadow_unreadable() {
+        let dir = tempfile::tempdir().unwrap();
+        let finding = audit(dir.path(), None);
+        assert!(finding.is_failure());
+        assert_eq!(finding.severity, Severity::Medium);
+        assert!(finding.message.contains("run as root"));
+    }
+
+    #[test]
+    fn test_pi_sudoers_and_absent_user() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("passwd"), "root:x:0:0:root:/root:/bin/bash\n").unwrap();
+        fs::write(dir.path().join("010_pi-nopasswd"), "pi ALL=(ALL) NOPASSWD: ALL\n").unwrap();
+        let audit = EmbeddedAudit {
+            passwd: dir.path().join("passwd"),
+            shadow: dir.path().join("shadow"),
+            pi_sudoers: dir.path().join("010_pi-nopasswd"),
+            ..Default::default()
+        };
+        let findings = audit.run_audit();
+        assert!(!findings[0].is_failure());
+        assert!(findings[1..].iter().any(|f| f.check.starts_with("sudoers-") && f.is_failure()));
+    }
+}
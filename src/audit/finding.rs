@@ -0,0 +1,184 @@
+//! Generic findings for content and policy audits.
+//!
+//! Permission and ownership audits compare a single file attribute against an expected value and
+//! have dedicated result types. Content and policy audits (for example parsing `/etc/sudoers`)
+//! report arbitrary problems, so they share the [`Finding`] type defined here.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{Finding, Severity};
+//! let finding = Finding::fail(
+//!     "sudoers-nopasswd-all",
+//!     "/etc/sudoers",
+//!     Severity::High,
+//!     "NOPASSWD grants password-less root for all commands",
+//! )
+//! .with_subject("alice ALL=(ALL) NOPASSWD: ALL");
+//! assert!(finding.is_failure());
+//! ```
+use crate::ack::{Acknowledgment, stable_id};
+use crate::audit::permissions::audit_permissions::{Severity, Status};
+use crate::render_output::{DataList, Renderable, Style, paint};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A single result produced by a content or policy audit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// Stable id derived from the check, path and subject, used to acknowledge the finding
+    pub id: String,
+    /// Identifier of the check that produced the finding (e.g. `sudoers-nopasswd-all`)
+    pub check: String,
+    /// File the finding relates to
+    pub path: PathBuf,
+    /// What the finding is about within the file: a line, an account, a parameter...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Status of the check (Pass or Fail)
+    pub status: Status,
+    /// Severity of the finding
+    pub severity: Severity,
+    /// Human-readable explanation
+    pub message: String,
+    /// Acknowledgment covering this finding in its current state
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledged: Option<Acknowledgment>,
+    /// Benchmark control the finding maps to (e.g. `CIS 6.1.2`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub benchmark: Option<String>,
+}
+
+impl Finding {
+    /// Create a passing finding with `Severity::None`.
+    pub fn pass(check: &str, path: impl Into<PathBuf>, message: impl Into<String>) -> Self {
+        Finding::new(check, path.into(), Status::Pass, Severity::None, message.into())
+    }
+
+    /// Create a failing finding with the given severity.
+    pub fn fail(
+        check: &str,
+        path: impl Into<PathBuf>,
+        severity: Severity,
+        message: impl Into<String>,
+    ) -> Self {
+        Finding::new(check, path.into(), Status::Fail, severity, message.into())
+    }
+
+    fn new(check: &str, path: PathBuf, status: Status, severity: Severity, message: String) -> Self {
+        let mut finding = Finding {
+            id: String::new(),
+            check: check.to_string(),
+            path,
+            subject: None,
+            status,
+            severity,
+            message,
+            acknowledged: None,
+            benchmark: None,
+        };
+        finding.id = finding.compute_id();
+        finding
+    }
+
+    /// Attach the subject (line, account, parameter) the finding is about.
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self.id = self.compute_id();
+        self
+    }
+
+    /// Attach the benchmark control the finding maps to; does not change the id.
+    pub fn with_benchmark(mut self, benchmark: impl Into<String>) -> Self {
+        self.benchmark = Some(benchmark.into());
+        self
+    }
+
+    fn compute_id(&self) -> String {
+        let path = self.path.to_string_lossy();
+        stable_id(&[&self.check, &path, self.subject.as_deref().unwrap_or_default()])
+    }
+
+    /// Hash of the finding's state (status, severity and message).
+    ///
+    /// Acknowledgments only apply while the fingerprint is unchanged.
+    pub fn fingerprint(&self) -> String {
+        stable_id(&[
+            &format!("{:?}", self.status),
+            &format!("{:?}", self.severity),
+            &self.message,
+        ])
+    }
+
+    /// Returns true if the finding is a failure.
+    pub fn is_failure(&self) -> bool {
+        self.status == Status::Fail
+    }
+
+    /// Returns true if the finding is a failure that has not been acknowledged.
+    pub fn is_open_failure(&self) -> bool {
+        self.is_failure() && self.acknowledged.is_none()
+    }
+}
+
+impl Renderable for Finding {
+    fn to_datalist(&self) -> DataList {
+        let mut map = IndexMap::new();
+        map.insert("id".to_string(), self.id.clone());
+        map.insert("check".to_string(), self.check.clone());
+        map.insert("path".to_string(), self.path.display().to_string());
+        map.insert(
+            "subject".to_string(),
+            self.subject.clone().unwrap_or_default(),
+        );
+        map.insert("status".to_string(), format!("{:?}", self.status));
+        map.insert("severity".to_string(), format!("{:?}", self.severity));
+        map.insert("message".to_string(), self.message.clone());
+        map.insert(
+            "acknowledged".to_string(),
+            self.acknowledged
+                .as_ref()
+                .map(|a| a.comment.clone())
+                .unwrap_or_default(),
+        );
+        if let Some(ref benchmark) = self.benchmark {
+            map.insert("benchmark".to_string(), benchmark.clone());
+        }
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        let status_symbol = match self.acknowledged {
+            Some(_) => paint("~", Style::Dim),
+            None => self.status.symbol(),
+        };
+        let mut result = format!(
+            "{} {} {} [{}] {} ({})",
+            status_symbol,
+            self.severity.column(),
+            self.path.display(),
+            self.check,
+            self.message,
+            self.id
+        );
+        if let Some(ref subject) = self.subject {
+            result.push_str(&format!("\n      > {}", subject));
+        }
+        if let Some(ref benchmark) = self.benchmark {
+            result.push_str(&format!("\n      > benchmark: {}", benchmark));
+        }
+        if let Some(ref ack) = self.acknowledged {
+            result.push_str(&format!("\n      > acknowledged: {}", ack.comment));
+        }
+        result
+    }
+}
+
+/// Trait for content and policy audits that report [`Finding`]s.
+///
+/// Implement this trait for audits that inspect file contents or system state rather than
+/// a single file attribute.
+pub trait AuditFindings {
+    /// Runs the audit and returns all findings, passing and failing.
+    fn run_audit(&self) -> Vec<Finding>;
+}
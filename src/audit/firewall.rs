@@ -0,0 +1,453 @@
+//! Host firewall audit for HALO.
+//!
+//! Detects which firewall is active (ufw, nftables or iptables), captures its ruleset and
+//! checks it against a [`FirewallPolicy`]: inbound traffic is denied by default and new SSH
+//! connections are rate limited. The expectations can be changed with a `[firewall]` table in
+//! a TOML config, e.g. for a host that runs SSH on another port.
+//!
+//! The ruleset is read with `ufw status verbose`, `nft list ruleset` and `iptables-save`, in
+//! that order; the first active one is checked. All of them require root. When none can be
+//! run a `firewall-unavailable` finding says so: a failure naming the installed tool, or a pass
+//! naming the searched `PATH` if no tool is installed. Alternate roots have no loaded firewall
+//! and also pass.
+//!
+//! Default deny means a drop policy on the inbound base chain (`policy drop` on an nftables
+//! `hook input` chain, `:INPUT DROP` or a final unconditional `-A INPUT -j DROP` for iptables,
+//! `deny (incoming)` for ufw).
+//!
+//! # Checks
+//! - `firewall-active`: a firewall with rules or a restrictive policy is loaded
+//! - `firewall-backend`: the active firewall is the one the policy requires
+//! - `firewall-default-deny`: inbound traffic is dropped unless allowed
+//! - `firewall-ssh-rate-limit`: new connections to the SSH port are rate limited
+//! - `firewall-unavailable`: an installed firewall tool could not be run
+//!
+//! # Example TOML
+//! ```toml
+//! [firewall]
+//! backend = "nftables"
+//! ssh_port = 2222
+//! ssh_rate_limit = true
+//! capture = "/var/lib/halo/firewall.rules"
+//! ```
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, FirewallAudit};
+//! let findings = FirewallAudit::default().run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{}: {}", f.path.display(), f.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::Severity;
+use crate::audit::sysroot;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Firewall front ends HALO can read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FirewallBackend {
+    Ufw,
+    Nftables,
+    Iptables,
+}
+
+impl FirewallBackend {
+    /// Name as used in TOML configs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FirewallBackend::Ufw => "ufw",
+            FirewallBackend::Nftables => "nftables",
+            FirewallBackend::Iptables => "iptables",
+        }
+    }
+
+    /// Command that prints the loaded ruleset.
+    pub fn command(&self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            FirewallBackend::Ufw => ("ufw", &["status", "verbose"]),
+            FirewallBackend::Nftables => ("nft", &["list", "ruleset"]),
+            FirewallBackend::Iptables => ("iptables-save", &[]),
+        }
+    }
+}
+
+/// Expected firewall configuration, the `[firewall]` table of a TOML config.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FirewallPolicy {
+    /// Firewall that must be active; any if unset
+    pub backend: Option<FirewallBackend>,
+    /// Inbound traffic must be dropped unless allowed
+    pub default_deny_inbound: bool,
+    /// New connections to `ssh_port` must be rate limited
+    pub ssh_rate_limit: bool,
+    pub ssh_port: u16,
+    /// File the captured ruleset is written to
+    pub capture: Option<PathBuf>,
+}
+
+/// Requires default deny inbound and an SSH rate limit on port 22.
+impl Default for FirewallPolicy {
+    fn default() -> Self {
+        Self {
+            backend: None,
+            default_deny_inbound: true,
+            ssh_rate_limit: true,
+            ssh_port: 22,
+            capture: None,
+        }
+    }
+}
+
+/// A ruleset as printed by one of the firewall tools.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FirewallRuleset {
+    pub backend: FirewallBackend,
+    pub ruleset: String,
+}
+
+impl FirewallRuleset {
+    pub fn new(backend: FirewallBackend, ruleset: impl Into<String>) -> Self {
+        Self {
+            backend,
+            ruleset: ruleset.into(),
+        }
+    }
+
+    /// Reads the loaded ruleset of every firewall tool that can be run.
+    ///
+    /// # Returns
+    /// * `Some` with the first active ruleset, or the first one read if none is active.
+    /// * `None` if no firewall tool is installed or none could be run (usually not root), or
+    ///   an [alternate root](crate::audit::sysroot) is audited.
+    pub fn capture() -> Option<Self> {
+        if sysroot::root().is_some() {
+            return None;
+        }
+        let mut first = None;
+        for backend in [FirewallBackend::Ufw, FirewallBackend::Nftables, FirewallBackend::Iptables] {
+            let (program, args) = backend.command();
+            let Ok(output) = Command::new(program).args(args).output() else {
+                continue;
+            };
+            if !output.status.success() {
+                continue;
+            }
+            let ruleset = Self::new(backend, String::from_utf8_lossy(&output.stdout));
+            if ruleset.is_active() {
+                return Some(ruleset);
+            }
+            first.get_or_insert(ruleset);
+        }
+        first
+    }
+
+    /// Returns true if the ruleset filters anything.
+    pub fn is_active(&self) -> bool {
+        match self.backend {
+            FirewallBackend::Ufw => self.ruleset.lines().any(|l| l.trim() == "Status: active"),
+            FirewallBackend::Nftables => self.ruleset.lines().any(|l| l.trim_start().starts_with("chain ")),
+            FirewallBackend::Iptables => self.ruleset.lines().any(|l| {
+                l.starts_with("-A ")
+                    || (l.starts_with(':') && !l.split_whitespace().nth(1).is_some_and(|p| p == "ACCEPT" || p == "-"))
+            }),
+        }
+    }
+
+    /// Returns true if inbound traffic is dropped unless a rule allows it.
+    pub fn default_deny_inbound(&self) -> bool {
+        match self.backend {
+            FirewallBackend::Ufw => self.ruleset.lines().any(|l| {
+                l.trim_start().starts_with("Default:")
+                    && (l.contains("deny (incoming)") || l.contains("reject (incoming)"))
+            }),
+            FirewallBackend::Nftables => self
+                .ruleset
+                .lines()
+                .any(|l| l.contains("hook input") && l.contains("policy drop")),
+            FirewallBackend::Iptables => {
+                let drop_policy = self.ruleset.lines().any(|l| l.starts_with(":INPUT DROP"));
+                let last_input = self.ruleset.lines().rfind(|l| l.starts_with("-A INPUT "));
+                drop_policy
+                    || last_input.is_some_and(|l| {
+                        matches!(l.trim(), "-A INPUT -j DROP" | "-A INPUT -j REJECT")
+                            || l.trim().starts_with("-A INPUT -j REJECT --reject-with")
+                    })
+            }
+        }
+    }
+
+    /// Returns true if a rule limits the rate of connections to `port`.
+    pub fn rate_limits_port(&self, port: u16) -> bool {
+        let port = port.to_string();
+        match self.backend {
+            FirewallBackend::Ufw => self.ruleset.lines().any(|l| {
+                let mut fields = l.split_whitespace();
+                let to = fields.next().unwrap_or_default();
+                let service = to.split('/').next().unwrap_or_default();
+                (service == port || (port == "22" && to == "OpenSSH")) && fields.any(|f| f == "LIMIT")
+            }),
+            FirewallBackend::Nftables => self.ruleset.lines().any(|l| {
+                matches_port(l, "dport", &port)
+                    && (l.contains("limit rate") || l.contains("meter ") || l.contains("ct count"))
+            }),
+            FirewallBackend::Iptables => self.ruleset.lines().any(|l| {
+                (matches_port(l, "--dport", &port) || matches_port(l, "--dports", &port))
+                    && ["-m limit", "-m hashlimit", "-m recent", "-m connlimit"]
+                        .iter()
+                        .any(|m| l.contains(m))
+            }),
+        }
+    }
+}
+
+/// Returns true if the value after `keyword` on `line` names `port`, alone, in a
+/// comma-separated list or in an nftables set (`{ 22, 443 }`).
+fn matches_port(line: &str, keyword: &str, port: &str) -> bool {
+    let service = if port == "22" { Some("ssh") } else { None };
+    let mut tokens = line.split_whitespace();
+    while tokens.any(|t| t == keyword) {
+        let mut values = Vec::new();
+        match tokens.next() {
+            Some("{") => {
+                for t in tokens.by_ref() {
+                    if t == "}" {
+                        break;
+                    }
+                    values.push(t.trim_end_matches(','));
+                }
+            }
+            Some(value) => values.extend(value.split(',')),
+            None => {}
+        }
+        if values.iter().any(|v| *v == port || Some(*v) == service) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Checks the active firewall against a policy.
+#[derive(Default)]
+pub struct FirewallAudit {
+    pub policy: FirewallPolicy,
+}
+
+impl AuditFindings for FirewallAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        if let Some(root) = sysroot::root() {
+            return vec![Finding::pass(
+                "firewall-unavailable",
+                root,
+                "No firewall is loaded for an alternate root",
+            )];
+        }
+        let Some(ruleset) = FirewallRuleset::capture() else {
+            return vec![unavailable(std::env::var_os("PATH").unwrap_or_default())];
+        };
+        let mut findings = Vec::new();
+        if let Some(path) = &self.policy.capture
+            && let Err(e) = fs::write(path, &ruleset.ruleset)
+        {
+            findings.push(Finding::fail(
+                "firewall-capture",
+                path,
+                Severity::Info,
+                format!("Failed to save the firewall ruleset: {}", e),
+            ));
+        }
+        findings.extend(check_ruleset(&ruleset, &self.policy));
+        findings
+    }
+}
+
+/// Reports why no ruleset could be read, given the `PATH` the tools were looked up in.
+fn unavailable(search_path: OsString) -> Finding {
+    let installed = [FirewallBackend::Ufw, FirewallBackend::Nftables, FirewallBackend::Iptables]
+        .iter()
+        .find_map(|backend| {
+            let (program, _) = backend.command();
+            std::env::split_paths(&search_path)
+                .map(|dir| dir.join(program))
+                .find(|path| path.is_file())
+        });
+    match installed {
+        Some(program) => Finding::fail(
+            "firewall-unavailable",
+            program,
+            Severity::Info,
+            "The firewall ruleset could not be read; HALO must run as root",
+        ),
+        None => Finding::pass(
+            "firewall-unavailable",
+            search_path,
+            "No firewall tool is installed; ufw, nft and iptables-save were not found",
+        ),
+    }
+}
+
+/// Checks a captured ruleset against a policy.
+///
+/// # Arguments
+/// * `ruleset` - Ruleset printed by the firewall tool
+/// * `policy` - Expected configuration
+///
+/// # Returns
+/// One finding per check; only `firewall-active` if the firewall is not active.
+pub fn check_ruleset(ruleset: &FirewallRuleset, policy: &FirewallPolicy) -> Vec<Finding> {
+    let (program, args) = ruleset.backend.command();
+    let source = PathBuf::from(format!("{} {}", program, args.join(" ")).trim_end());
+    let name = ruleset.backend.name();
+    let mut findings = Vec::new();
+
+    if let Some(required) = policy.backend {
+        findings.push(if required == ruleset.backend {
+            Finding::pass("firewall-backend", &source, format!("{} is the active firewall", name))
+        } else {
+            Finding::fail(
+                "firewall-backend",
+                &source,
+                Severity::Medium,
+                format!("Expected {} to be the active firewall, found {}", required.name(), name),
+            )
+        });
+    }
+
+    if !ruleset.is_active() {
+        findings.push(Finding::fail(
+            "firewall-active",
+            &source,
+            Severity::High,
+            "No firewall is active; all inbound traffic is accepted",
+        ));
+        return findings;
+    }
+    findings.push(Finding::pass("firewall-active", &source, format!("{} is active", name)));
+
+    if policy.default_deny_inbound {
+        findings.push(if ruleset.default_deny_inbound() {
+            Finding::pass("firewall-default-deny", &source, "Inbound traffic is denied by default")
+        } else {
+            Finding::fail(
+                "firewall-default-deny",
+                &source,
+                Severity::High,
+                "Inbound traffic is accepted unless a rule drops it; set the input policy to drop",
+            )
+        });
+    }
+
+    if policy.ssh_rate_limit {
+        let subject = format!("port {}", policy.ssh_port);
+        findings.push(
+            if ruleset.rate_limits_port(policy.ssh_port) {
+                Finding::pass("firewall-ssh-rate-limit", &source, "SSH connections are rate limited")
+            } else {
+                Finding::fail(
+                    "firewall-ssh-rate-limit",
+                    &source,
+                    Severity::Medium,
+                    format!(
+                        "New SSH connections to port {} are not rate limited; brute-force attempts are not slowed down",
+                        policy.ssh_port
+                    ),
+                )
+            }
+            .with_subject(subject),
+        );
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failing(findings: Vec<Finding>) -> Vec<String> {
+        findings.into_iter().filter(|f| f.is_failure()).map(|f| f.check).collect()
+    }
+
+    #[test]
+    fn test_nftables_ruleset() {
+        let nft = "table inet filter {
+	chain input {
+		type filter hook input priority filter; policy drop;
+		ct state established,related accept
+		tcp dport { 22, 443 } ct state new limit rate 4/minute accept
+	}
+}
+";
+        let ruleset = FirewallRuleset::new(FirewallBackend::Nftables, nft);
+        assert!(failing(check_ruleset(&ruleset, &FirewallPolicy::default())).is_empty());
+
+        let policy = FirewallPolicy {
+            ssh_port: 2222,
+            backend: Some(FirewallBackend::Iptables),
+            ..Default::default()
+        };
+        let failed = failing(check_ruleset(&ruleset, &policy));
+        assert_eq!(failed, ["firewall-backend", "firewall-ssh-rate-limit"]);
+    }
+
+    #[test]
+    fn test_iptables_ruleset() {
+        let open = "*filter\n:INPUT ACCEPT [0:0]\n:FORWARD ACCEPT [0:0]\n:OUTPUT ACCEPT [0:0]\nCOMMIT\n";
+        let ruleset = FirewallRuleset::new(FirewallBackend::Iptables, open);
+        assert_eq!(failing(check_ruleset(&ruleset, &FirewallPolicy::default())), ["firewall-active"]);
+
+        let hardened = "*filter
+:INPUT ACCEPT [0:0]
+-A INPUT -p tcp -m tcp --dport 22 -m state --state NEW -m recent --update --seconds 60 --hitcount 4 -j DROP
+-A INPUT -p tcp -m tcp --dport 22 -j ACCEPT
+-A INPUT -j DROP
+COMMIT
+";
+        let ruleset = FirewallRuleset::new(FirewallBackend::Iptables, hardened);
+        assert!(failing(check_ruleset(&ruleset, &FirewallPolicy::default())).is_empty());
+    }
+
+    #[test]
+    fn test_ufw_status() {
+        let status = "Status: active
+Logging: on (low)
+Default: allow (incoming), allow (outgoing), disabled (routed)
+
+To                         Action      From
+--                         ------      ----
+22/tcp                     ALLOW IN    Anywhere
+";
+        let ruleset = FirewallRuleset::new(FirewallBackend::Ufw, status);
+        let failed = failing(check_ruleset(&ruleset, &FirewallPolicy::default()));
+        assert_eq!(failed, ["firewall-default-deny", "firewall-ssh-rate-limit"]);
+
+        let limited = status
+            .replace("allow (incoming)", "deny (incoming)")
+            .replace("ALLOW IN", "LIMIT IN");
+        let ruleset = FirewallRuleset::new(FirewallBackend::Ufw, limited);
+        assert!(failing(check_ruleset(&ruleset, &FirewallPolicy::default())).is_empty());
+    }
+
+    #[test]
+    fn test_unavailable() {
+        let dir = tempfile::tempdir().unwrap();
+        let (empty, sbin) = (dir.path().join("bin"), dir.path().join("sbin"));
+        fs::create_dir(&empty).unwrap();
+        fs::create_dir(&sbin).unwrap();
+        let search_path = std::env::join_paths([&empty, &sbin]).unwrap();
+
+        let missing = unavailable(search_path.clone());
+        assert!(!missing.is_failure());
+        assert_eq!(missing.path.as_os_str(), search_path);
+
+        fs::write(sbin.join("nft"), "").unwrap();
+        let installed = unavailable(search_path);
+        assert!(installed.is_failure());
+        assert_eq!(installed.path, sbin.join("nft"));
+    }
+}
@@ -0,0 +1,101 @@
+//! Resource usage profiles for audit runs.
+//!
+//! HALO is often run on single-board computers with little memory. [`Footprint::Low`]
+//! trades completeness of the output and speed for a small, predictable resource use:
+//! - a single worker thread for directory traversal
+//! - no metadata caching between rules
+//! - passing results are dropped as each rule completes, so only failures are retained
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditPermissions, Footprint, UserConfig};
+//! let failures = UserConfig::default().run_audit_perms_with(&Footprint::Low);
+//! println!("{} non-passing results", failures.len());
+//! ```
+
+/// Resource usage profile for an audit run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Footprint {
+    /// Default behavior: parallel traversal, caching, full results
+    #[default]
+    Standard,
+    /// Minimal memory and CPU use for embedded devices
+    Low,
+}
+
+impl Footprint {
+    /// Short name of the profile.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Footprint::Standard => "standard",
+            Footprint::Low => "low",
+        }
+    }
+
+    /// One-line description of the profile.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Footprint::Standard => "Parallel traversal, metadata caching and full results (default)",
+            Footprint::Low => "Single thread, no caching, only failures kept (check --low-footprint)",
+        }
+    }
+
+    /// Number of worker threads to use for directory traversal.
+    pub fn jobs(&self) -> usize {
+        match self {
+            Footprint::Standard => std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            Footprint::Low => 1,
+        }
+    }
+
+    /// Whether file metadata may be cached and shared between rules.
+    pub fn cache_metadata(&self) -> bool {
+        matches!(self, Footprint::Standard)
+    }
+
+    /// Whether passing results are kept in the output.
+    pub fn keep_passing(&self) -> bool {
+        matches!(self, Footprint::Standard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AuditPermissions, Importance, PermissionRules, Status};
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    struct Rules(Vec<PermissionRules>);
+
+    impl AuditPermissions for Rules {
+        fn rules(&self) -> Vec<PermissionRules> {
+            self.0.clone()
+        }
+    }
+
+    #[test]
+    fn test_low_footprint_keeps_only_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, mode) in [("ok", 0o644), ("open", 0o666), ("sub/ok", 0o644), ("sub/open", 0o666)] {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+        let (rule, _) = PermissionRules::new(dir.path().to_path_buf(), 0o644, Importance::High);
+        let rules = Rules(vec![rule]);
+
+        let standard = rules.run_audit_perms_with(&Footprint::Standard);
+        let low = rules.run_audit_perms_with(&Footprint::Low);
+        assert_eq!(standard.len(), 4);
+        assert_eq!(low.len(), 2);
+        assert!(low.iter().all(|r| r.status == Status::Fail));
+        let failed: Vec<_> = standard.iter().filter(|r| r.status != Status::Pass).collect();
+        assert_eq!(low.iter().collect::<Vec<_>>(), failed);
+        assert_eq!(Footprint::Low.jobs(), 1);
+        assert!(!Footprint::Low.cache_metadata());
+    }
+}
@@ -0,0 +1,127 @@
+//! Shell-style wildcards in rule paths.
+//!
+//! Config rules may use `*` and `?` in any path component, e.g. `/etc/cron.d/*` or
+//! `/home/*/.ssh`. Patterns are expanded against the filesystem when the rules run, one path
+//! per match, so a single rule covers every matching file. As in the shell, wildcards do not
+//! match a leading `.` unless the pattern component starts with one, and `*` never crosses a
+//! `/`.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::audit::glob::{expand, glob_match};
+//! assert!(glob_match("*.log", "auth.log"));
+//! for path in expand("/etc/cron.d/*") {
+//!     println!("{}", path.display());
+//! }
+//! ```
+use crate::audit::sysroot;
+use std::path::{Component, Path, PathBuf};
+
+/// Returns true if `path` contains a wildcard.
+pub fn is_pattern(path: &str) -> bool {
+    path.contains(['*', '?'])
+}
+
+/// Shell-style match supporting `*` and `?`.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star_pi, star_ti)) = star {
+            pi = star_pi + 1;
+            ti = star_ti + 1;
+            star = Some((star_pi, star_ti + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+/// Existing paths matching `pattern`, sorted.
+///
+/// A pattern without wildcards yields the path itself if it exists. Unreadable directories
+/// are skipped.
+pub fn expand(pattern: &str) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::new()];
+    for component in Path::new(pattern).components() {
+        let name = match component {
+            Component::Normal(name) => name.to_string_lossy(),
+            other => {
+                paths.iter_mut().for_each(|p| p.push(other));
+                continue;
+            }
+        };
+        if !is_pattern(&name) {
+            paths.iter_mut().for_each(|p| p.push(name.as_ref()));
+            continue;
+        }
+        let mut matched = Vec::new();
+        for dir in &paths {
+            let read_from = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+            let Ok(entries) = sysroot::read_dir(read_from) else {
+                continue;
+            };
+            for entry in entries {
+                let file_name = entry.file_name().unwrap_or_default().to_string_lossy();
+                if file_name.starts_with('.') && !name.starts_with('.') {
+                    continue;
+                }
+                if glob_match(&name, &file_name) {
+                    matched.push(dir.join(file_name.as_ref()));
+                }
+            }
+        }
+        paths = matched;
+    }
+    let mut paths: Vec<PathBuf> = paths
+        .into_iter()
+        .filter(|p| !p.as_os_str().is_empty() && sysroot::symlink_metadata(p).is_ok())
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{File, create_dir_all};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.log", "auth.log"));
+        assert!(glob_match("wtmp?", "wtmp1"));
+        assert!(!glob_match("*.log", "auth.log.1"));
+    }
+
+    #[test]
+    fn test_expand_components() {
+        let dir = tempdir().unwrap();
+        for user in ["alice", "bob", "carol"] {
+            create_dir_all(dir.path().join("home").join(user)).unwrap();
+        }
+        create_dir_all(dir.path().join("home/alice/.ssh")).unwrap();
+        create_dir_all(dir.path().join("home/bob/.ssh")).unwrap();
+        File::create(dir.path().join("home/.hidden")).unwrap();
+
+        let root = dir.path().display();
+        let found = expand(&format!("{}/home/*/.ssh", root));
+        assert_eq!(
+            found,
+            vec![dir.path().join("home/alice/.ssh"), dir.path().join("home/bob/.ssh")]
+        );
+        // Hidden entries only match patterns that start with a dot
+        assert_eq!(expand(&format!("{}/home/*", root)).len(), 3);
+        assert_eq!(expand(&format!("{}/home/.h*", root)).len(), 1);
+        assert!(expand(&format!("{}/nothing/*", root)).is_empty());
+        assert_eq!(expand(&format!("{}/home", root)), vec![dir.path().join("home")]);
+    }
+}
@@ -0,0 +1,234 @@
+//! Core dump and `/proc` hardening audit for HALO.
+//!
+//! Core dumps contain the memory of the crashed process, including passwords and keys it
+//! held. Dumps of setuid programs and dumps written to shared directories leak that memory to
+//! other users. `/proc` mounted without `hidepid` lets every user see the command lines and
+//! owners of all processes.
+//!
+//! # Checks
+//! - `hardening-core-pattern`: `kernel.core_pattern` pipes dumps to a handler or writes them
+//!   to a private directory; a world-writable directory fails (High), the working directory of
+//!   the crashing process is Low
+//! - `hardening-suid-dumpable`: `fs.suid_dumpable` is 0; 2 is accepted at Low when dumps are
+//!   piped to a handler
+//! - `hardening-core-limit`: `limits.conf` or `limits.d` sets `hard core 0` for all users
+//! - `hardening-proc-hidepid`: `/proc` is mounted with `hidepid=2` (`invisible`) or
+//!   `hidepid=1` (`noaccess`)
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, HardeningAudit};
+//! let findings = HardeningAudit::default().run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{}: {}", f.path.display(), f.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::Severity;
+use crate::audit::mounts::parse_fstab;
+use crate::audit::sysroot;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// Core dump and procfs hardening settings.
+pub struct HardeningAudit {
+    /// Root of the sysctl tree, normally `/proc/sys`
+    pub proc_sys: PathBuf,
+    pub limits_conf: PathBuf,
+    pub limits_d: PathBuf,
+    /// Mount table listing the `/proc` mount options
+    pub mounts: PathBuf,
+}
+
+/// Provides the default procfs, limits and mount table paths.
+impl Default for HardeningAudit {
+    fn default() -> Self {
+        Self {
+            proc_sys: "/proc/sys".into(),
+            limits_conf: "/etc/security/limits.conf".into(),
+            limits_d: "/etc/security/limits.d".into(),
+            mounts: "/proc/mounts".into(),
+        }
+    }
+}
+
+impl AuditFindings for HardeningAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        let pattern_path = self.proc_sys.join("kernel/core_pattern");
+        let pattern = sysroot::read_to_string(&pattern_path).ok();
+        if let Some(pattern) = &pattern {
+            findings.push(check_core_pattern(&pattern_path, pattern));
+        }
+
+        let dumpable_path = self.proc_sys.join("fs/suid_dumpable");
+        if let Ok(value) = sysroot::read_to_string(&dumpable_path) {
+            let piped = pattern.as_deref().is_some_and(|p| p.trim_start().starts_with('|'));
+            findings.push(check_suid_dumpable(&dumpable_path, &value, piped));
+        }
+
+        let mut limits = vec![(self.limits_conf.clone(), sysroot::read_to_string(&self.limits_conf).unwrap_or_default())];
+        if let Ok(entries) = sysroot::read_dir(&self.limits_d) {
+            let mut files: Vec<PathBuf> = entries
+                .into_iter()
+                .filter(|p| p.extension().is_some_and(|e| e == "conf"))
+                .collect();
+            files.sort();
+            limits.extend(files.into_iter().filter_map(|f| sysroot::read_to_string(&f).ok().map(|c| (f, c))));
+        }
+        findings.push(check_core_limit(&self.limits_conf, &limits));
+
+        if let Ok(mounts) = sysroot::read_to_string(&self.mounts) {
+            findings.push(check_proc_hidepid(&self.mounts, &mounts));
+        }
+        findings
+    }
+}
+
+/// Checks where `kernel.core_pattern` sends core dumps.
+pub fn check_core_pattern(path: &Path, pattern: &str) -> Finding {
+    let pattern = pattern.trim();
+    let finding = if let Some(handler) = pattern.strip_prefix('|') {
+        let program = handler.split_whitespace().next().unwrap_or_default();
+        Finding::pass(
+            "hardening-core-pattern",
+            path,
+            format!("Core dumps are piped to {}", program),
+        )
+    } else if pattern.starts_with('/') {
+        let dir = Path::new(pattern).parent().unwrap_or(Path::new("/"));
+        let world_writable = sysroot::metadata(dir).is_ok_and(|m| m.permissions().mode() & 0o002 != 0);
+        if world_writable {
+            Finding::fail(
+                "hardening-core-pattern",
+                path,
+                Severity::High,
+                format!("Core dumps are written to world-writable {}", dir.display()),
+            )
+        } else {
+            Finding::pass(
+                "hardening-core-pattern",
+                path,
+                format!("Core dumps are written to {}", dir.display()),
+            )
+        }
+    } else {
+        Finding::fail(
+            "hardening-core-pattern",
+            path,
+            Severity::Low,
+            format!(
+                "Core dumps ({}) are written to the working directory of the crashing process",
+                pattern
+            ),
+        )
+    };
+    finding.with_subject("kernel.core_pattern")
+}
+
+/// Checks `fs.suid_dumpable`; `piped` is true if core dumps go to a handler program.
+pub fn check_suid_dumpable(path: &Path, value: &str, piped: bool) -> Finding {
+    let finding = match value.trim() {
+        "0" => Finding::pass("hardening-suid-dumpable", path, "setuid programs do not dump core"),
+        "2" if piped => Finding::fail(
+            "hardening-suid-dumpable",
+            path,
+            Severity::Low,
+            "setuid programs dump core (readable by root only) to the core_pattern handler",
+        ),
+        "2" => Finding::fail(
+            "hardening-suid-dumpable",
+            path,
+            Severity::Medium,
+            "setuid programs dump core; set fs.suid_dumpable = 0",
+        ),
+        other => Finding::fail(
+            "hardening-suid-dumpable",
+            path,
+            Severity::High,
+            format!("fs.suid_dumpable = {}; setuid programs dump core as the invoking user", other),
+        ),
+    };
+    finding.with_subject("fs.suid_dumpable")
+}
+
+/// Checks that one of the limits files sets `* hard core 0`.
+///
+/// # Arguments
+/// * `path` - Path reported when no file sets the limit
+/// * `files` - (path, content) of `limits.conf` and the `limits.d` files
+pub fn check_core_limit(path: &Path, files: &[(PathBuf, String)]) -> Finding {
+    for (file, content) in files {
+        let found = content.lines().any(|line| {
+            let fields: Vec<&str> = line.split('#').next().unwrap_or_default().split_whitespace().collect();
+            matches!(fields.as_slice(), ["*", "hard", "core", "0"])
+        });
+        if found {
+            return Finding::pass("hardening-core-limit", file, "Core dumps are limited to 0 for all users");
+        }
+    }
+    Finding::fail(
+        "hardening-core-limit",
+        path,
+        Severity::Medium,
+        "No '* hard core 0' limit; users can enable core dumps",
+    )
+}
+
+/// Checks the `/proc` entry of a mount table for `hidepid`.
+pub fn check_proc_hidepid(path: &Path, mounts: &str) -> Finding {
+    let hidepid = parse_fstab(mounts)
+        .iter()
+        .find(|m| m.mount_point == Path::new("/proc") && m.fs_type == "proc")
+        .and_then(|m| m.option_value("hidepid").map(str::to_string));
+    match hidepid.as_deref() {
+        Some("1" | "2" | "noaccess" | "invisible") => Finding::pass(
+            "hardening-proc-hidepid",
+            path,
+            format!("/proc is mounted with hidepid={}", hidepid.unwrap_or_default()),
+        ),
+        _ => Finding::fail(
+            "hardening-proc-hidepid",
+            path,
+            Severity::Low,
+            "/proc is mounted without hidepid; every user can see all processes",
+        ),
+    }
+    .with_subject("/proc")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_dump_settings() {
+        let path = Path::new("/proc/sys/kernel/core_pattern");
+        assert!(!check_core_pattern(path, "|/usr/lib/systemd/systemd-coredump %P %u %g\n").is_failure());
+        assert_eq!(check_core_pattern(path, "/tmp/core.%e.%p").severity, Severity::High);
+        assert_eq!(check_core_pattern(path, "core").severity, Severity::Low);
+
+        let path = Path::new("/proc/sys/fs/suid_dumpable");
+        assert!(!check_suid_dumpable(path, "0\n", false).is_failure());
+        assert_eq!(check_suid_dumpable(path, "2", true).severity, Severity::Low);
+        assert_eq!(check_suid_dumpable(path, "1", true).severity, Severity::High);
+    }
+
+    #[test]
+    fn test_limits_and_hidepid() {
+        let conf = PathBuf::from("/etc/security/limits.conf");
+        let files = vec![
+            (conf.clone(), "# *   soft core 0\n@staff hard nproc 50\n".to_string()),
+            (PathBuf::from("/etc/security/limits.d/10-core.conf"), "*  hard  core  0\n".to_string()),
+        ];
+        let finding = check_core_limit(&conf, &files);
+        assert!(!finding.is_failure());
+        assert!(finding.path.ends_with("10-core.conf"));
+        assert!(check_core_limit(&conf, &files[..1]).is_failure());
+
+        let mounts = Path::new("/proc/mounts");
+        assert!(check_proc_hidepid(mounts, "proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0\n").is_failure());
+        assert!(!check_proc_hidepid(mounts, "proc /proc proc rw,relatime,hidepid=invisible 0 0\n").is_failure());
+    }
+}
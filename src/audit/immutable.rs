@@ -0,0 +1,279 @@
+//! NixOS and immutable distribution awareness for HALO.
+//!
+//! On NixOS most of `/etc` is a symlink farm into the read-only `/nix/store`, and on
+//! ostree-based systems (Fedora Silverblue/Kinoite, CoreOS) `/usr` is a read-only deployment.
+//! `chmod` suggestions for those paths are wrong: the change either fails or is undone on the
+//! next rebuild. This module detects such systems, tells callers which paths are read-only,
+//! and provides checks that fit them.
+//!
+//! # Checks
+//! - `nixos-etc-static`: `/etc/static` is a symlink into `/nix/store`
+//! - `nixos-etc-symlink-target`: symlinks in `/etc` point into `/etc/static` or `/nix/store`
+//! - `nixos-store-writable`: `/nix/store` is not world-writable
+//! - `ostree-gpg-verify`: ostree remotes do not disable GPG verification
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::ImmutableDistro;
+//! if let Some(distro) = ImmutableDistro::detect() {
+//!     println!("{} detected; /nix/store read-only: {}", distro.name, distro.is_read_only("/nix/store".as_ref()));
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::Severity;
+use crate::audit::sysroot;
+use crate::parsers::parse_key_values;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Kind of immutable system layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImmutableKind {
+    /// NixOS: configuration is built into `/nix/store`
+    NixOs,
+    /// ostree deployment: `/usr` is read-only (Silverblue, Kinoite, CoreOS...)
+    Ostree,
+}
+
+/// A detected immutable or declaratively managed distribution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImmutableDistro {
+    pub kind: ImmutableKind,
+    /// Human-readable name from os-release, e.g. "NixOS" or "Fedora Linux (Silverblue)"
+    pub name: String,
+}
+
+impl ImmutableDistro {
+    /// Detect the running system from `/etc/os-release` and `/run/ostree-booted`.
+    pub fn detect() -> Option<Self> {
+        let os_release = sysroot::read_to_string(Path::new("/etc/os-release")).unwrap_or_default();
+        let ostree_booted = sysroot::exists(Path::new("/run/ostree-booted"));
+        Self::from_os_release(&os_release, ostree_booted)
+    }
+
+    /// Classify a system from os-release content and whether an ostree deployment is booted.
+    pub fn from_os_release(os_release: &str, ostree_booted: bool) -> Option<Self> {
+        let fields = parse_key_values(os_release);
+        let field = |key: &str| fields.get(key).cloned();
+        let name = field("PRETTY_NAME")
+            .or_else(|| field("NAME"))
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        if field("ID").as_deref() == Some("nixos") {
+            return Some(ImmutableDistro {
+                kind: ImmutableKind::NixOs,
+                name,
+            });
+        }
+        if ostree_booted {
+            return Some(ImmutableDistro {
+                kind: ImmutableKind::Ostree,
+                name,
+            });
+        }
+        None
+    }
+
+    /// Returns true if `path` resolves into a read-only, system-managed location.
+    ///
+    /// Symlinks are resolved first, so `/etc/passwd` on NixOS counts as read-only when it
+    /// points into `/nix/store`.
+    pub fn is_read_only(&self, path: &Path) -> bool {
+        let resolved = sysroot::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let prefixes: &[&str] = match self.kind {
+            ImmutableKind::NixOs => &["/nix/store"],
+            ImmutableKind::Ostree => &["/usr", "/sysroot", "/ostree"],
+        };
+        prefixes.iter().any(|p| resolved.starts_with(p))
+    }
+
+    /// Advice shown instead of a `chmod` suggestion for read-only paths.
+    pub fn fix_hint(&self) -> &'static str {
+        match self.kind {
+            ImmutableKind::NixOs => "managed by NixOS; change it in configuration.nix and rebuild",
+            ImmutableKind::Ostree => "read-only ostree deployment; change it with an overlay or layered package",
+        }
+    }
+}
+
+/// Checks specific to immutable distributions.
+pub struct ImmutableAudit {
+    pub distro: Option<ImmutableDistro>,
+    pub etc: PathBuf,
+    pub nix_store: PathBuf,
+    pub ostree_remotes: PathBuf,
+}
+
+/// Detects the running distribution and uses the standard paths.
+impl Default for ImmutableAudit {
+    fn default() -> Self {
+        Self {
+            distro: ImmutableDistro::detect(),
+            etc: "/etc".into(),
+            nix_store: "/nix/store".into(),
+            ostree_remotes: "/etc/ostree/remotes.d".into(),
+        }
+    }
+}
+
+impl AuditFindings for ImmutableAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        match self.distro.as_ref().map(|d| d.kind) {
+            Some(ImmutableKind::NixOs) => self.check_nixos(),
+            Some(ImmutableKind::Ostree) => self.check_ostree(),
+            None => vec![Finding::pass(
+                "immutable-distro",
+                "/etc/os-release",
+                "Not an immutable distribution; no checks apply",
+            )],
+        }
+    }
+}
+
+impl ImmutableAudit {
+    fn check_nixos(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        let static_link = self.etc.join("static");
+        let finding = match sysroot::read_link(&static_link) {
+            Ok(target) if target.starts_with(&self.nix_store) => {
+                Finding::pass("nixos-etc-static", &static_link, "Points into the Nix store")
+            }
+            Ok(target) => Finding::fail(
+                "nixos-etc-static",
+                &static_link,
+                Severity::High,
+                format!("Points outside the Nix store: {}", target.display()),
+            ),
+            Err(e) => Finding::fail(
+                "nixos-etc-static",
+                &static_link,
+                Severity::Medium,
+                format!("Expected a symlink into the Nix store: {}", e),
+            ),
+        };
+        findings.push(finding);
+
+        // Symlinks in /etc managed by NixOS point to /etc/static/... or directly into the store
+        let mut unexpected = 0;
+        if let Ok(entries) = sysroot::read_dir(&self.etc) {
+            for path in entries {
+                let Ok(target) = sysroot::read_link(&path) else {
+                    continue;
+                };
+                let absolute = if target.is_relative() {
+                    self.etc.join(&target)
+                } else {
+                    target.clone()
+                };
+                if path == static_link
+                    || absolute.starts_with(&static_link)
+                    || absolute.starts_with(&self.nix_store)
+                    || absolute.starts_with("/run")
+                {
+                    continue;
+                }
+                unexpected += 1;
+                findings.push(Finding::fail(
+                    "nixos-etc-symlink-target",
+                    &path,
+                    Severity::Medium,
+                    format!(
+                        "Symlink points outside the NixOS-managed tree: {}",
+                        target.display()
+                    ),
+                ));
+            }
+        }
+        if unexpected == 0 {
+            findings.push(Finding::pass(
+                "nixos-etc-symlink-target",
+                &self.etc,
+                "All /etc symlinks point into the NixOS-managed tree",
+            ));
+        }
+
+        let finding = match sysroot::metadata(&self.nix_store) {
+            Ok(meta) if meta.mode() & 0o002 != 0 => Finding::fail(
+                "nixos-store-writable",
+                &self.nix_store,
+                Severity::Critical,
+                "Nix store is world-writable",
+            ),
+            Ok(_) => Finding::pass(
+                "nixos-store-writable",
+                &self.nix_store,
+                "Nix store is not world-writable",
+            ),
+            Err(e) => Finding::fail(
+                "nixos-store-writable",
+                &self.nix_store,
+                Severity::Info,
+                format!("Cannot read Nix store metadata: {}", e),
+            ),
+        };
+        findings.push(finding);
+        findings
+    }
+
+    fn check_ostree(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let Ok(entries) = sysroot::read_dir(&self.ostree_remotes) else {
+            return findings;
+        };
+        let mut files: Vec<PathBuf> = entries
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|e| e == "conf"))
+            .collect();
+        files.sort();
+        for path in files {
+            let content = sysroot::read_to_string(&path).unwrap_or_default();
+            let disabled = content
+                .lines()
+                .filter_map(|l| l.split_once('='))
+                .any(|(k, v)| k.trim() == "gpg-verify" && v.trim() == "false");
+            findings.push(if disabled {
+                Finding::fail(
+                    "ostree-gpg-verify",
+                    &path,
+                    Severity::High,
+                    "Remote disables GPG verification of deployments",
+                )
+            } else {
+                Finding::pass("ostree-gpg-verify", &path, "GPG verification enabled")
+            });
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_nixos() {
+        let os_release = "NAME=NixOS\nID=nixos\nPRETTY_NAME=\"NixOS 24.05 (Uakari)\"\n";
+        let distro = ImmutableDistro::from_os_release(os_release, false).unwrap();
+        assert_eq!(distro.kind, ImmutableKind::NixOs);
+        assert_eq!(distro.name, "NixOS 24.05 (Uakari)");
+    }
+
+    #[test]
+    fn test_detect_ostree() {
+        let os_release = "NAME=\"Fedora Linux\"\nID=fedora\nVARIANT_ID=silverblue\n";
+        let distro = ImmutableDistro::from_os_release(os_release, true).unwrap();
+        assert_eq!(distro.kind, ImmutableKind::Ostree);
+        assert!(ImmutableDistro::from_os_release(os_release, false).is_none());
+    }
+
+    #[test]
+    fn test_read_only_paths() {
+        let nixos = ImmutableDistro {
+            kind: ImmutableKind::NixOs,
+            name: "NixOS".to_string(),
+        };
+        assert!(nixos.is_read_only(Path::new("/nix/store/abc-etc/etc/hosts")));
+        assert!(!nixos.is_read_only(Path::new("/var/lib/halo")));
+    }
+}
@@ -0,0 +1,250 @@
+//! Log rotation and retention audit for HALO.
+//!
+//! Logs that are never rotated fill the disk and, once full, stop recording the events an
+//! investigation needs. This audit checks that logrotate is configured, that its
+//! configuration covers the logs that matter for security, and flags log files whose size
+//! suggests rotation is not happening. The permissions of the logrotate configuration are
+//! checked by the [`Log`](crate::Log) rules.
+//!
+//! Coverage is determined from the log path patterns in `/etc/logrotate.conf` and
+//! `/etc/logrotate.d/*`; only key logs that exist on the host are checked. `/var/log/journal`
+//! is skipped by the size check since journald enforces its own limits.
+//!
+//! # Checks
+//! - `logrotate-config`: `/etc/logrotate.conf` exists
+//! - `logrotate-coverage`: one finding per existing key log, failing if no logrotate entry
+//!   matches it
+//! - `logrotate-oversized-log`: a log file is larger than `max_log_size`
+//!
+//! # Example TOML
+//! ```toml
+//! [logrotate]
+//! max_log_size = 524288000 # 500 MiB
+//! key_logs = ["/var/log/auth.log", "/var/log/nginx/access.log"]
+//! ```
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, LogrotateAudit};
+//! let findings = LogrotateAudit::default().run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{}: {}", f.path.display(), f.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::audit::glob::glob_match;
+use crate::Severity;
+use crate::audit::sysroot;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// logrotate configuration, key logs and the size limit for log files.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LogrotateAudit {
+    /// Main logrotate configuration
+    pub config: PathBuf,
+    /// Directory of per-package logrotate configurations
+    pub config_d: PathBuf,
+    /// Logs that must be rotated if they exist
+    pub key_logs: Vec<PathBuf>,
+    /// Directory searched for oversized logs
+    pub log_dir: PathBuf,
+    /// Log files larger than this many bytes are reported
+    pub max_log_size: u64,
+}
+
+/// Uses the standard logrotate paths, the common syslog and login logs and a 100 MiB limit.
+impl Default for LogrotateAudit {
+    fn default() -> Self {
+        Self {
+            config: "/etc/logrotate.conf".into(),
+            config_d: "/etc/logrotate.d".into(),
+            key_logs: [
+                "/var/log/syslog",
+                "/var/log/messages",
+                "/var/log/auth.log",
+                "/var/log/secure",
+                "/var/log/kern.log",
+                "/var/log/wtmp",
+                "/var/log/btmp",
+            ]
+            .iter()
+            .map(PathBuf::from)
+            .collect(),
+            log_dir: "/var/log".into(),
+            max_log_size: 100 * 1024 * 1024,
+        }
+    }
+}
+
+impl AuditFindings for LogrotateAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut content = String::new();
+        match sysroot::read_to_string(&self.config) {
+            Ok(main) => {
+                findings.push(Finding::pass("logrotate-config", &self.config, "logrotate is configured"));
+                content.push_str(&main);
+            }
+            Err(e) => findings.push(Finding::fail(
+                "logrotate-config",
+                &self.config,
+                Severity::Medium,
+                format!("Cannot read logrotate configuration: {}", e),
+            )),
+        }
+        if let Ok(mut files) = sysroot::read_dir(&self.config_d) {
+            files.sort();
+            for file in files {
+                if let Ok(part) = sysroot::read_to_string(&file) {
+                    content.push('\n');
+                    content.push_str(&part);
+                }
+            }
+        }
+
+        let patterns = rotated_patterns(&content);
+        for log in self.key_logs.iter().filter(|l| sysroot::exists(l)) {
+            findings.push(check_coverage(log, &patterns));
+        }
+
+        let mut oversized = Vec::new();
+        self.find_oversized(&self.log_dir, &mut oversized);
+        for (path, size) in oversized {
+            findings.push(Finding::fail(
+                "logrotate-oversized-log",
+                &path,
+                Severity::Medium,
+                format!(
+                    "Log is {} MiB (limit {} MiB); rotation may be broken",
+                    size / (1024 * 1024),
+                    self.max_log_size / (1024 * 1024)
+                ),
+            ));
+        }
+        findings
+    }
+}
+
+impl LogrotateAudit {
+    fn find_oversized(&self, dir: &Path, found: &mut Vec<(PathBuf, u64)>) {
+        let Ok(entries) = sysroot::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<PathBuf> = entries.into_iter().collect();
+        paths.sort();
+        for path in paths {
+            let Ok(meta) = sysroot::symlink_metadata(&path) else {
+                continue;
+            };
+            if meta.is_dir() && !path.ends_with("journal") {
+                self.find_oversized(&path, found);
+            } else if meta.is_file() && meta.len() > self.max_log_size {
+                found.push((path, meta.len()));
+            }
+        }
+    }
+}
+
+/// Log path patterns of the logrotate entries in `content`.
+///
+/// Patterns are the words outside `{ ... }` blocks that start with `/`, optionally quoted.
+pub fn rotated_patterns(content: &str) -> Vec<String> {
+    let mut patterns = Vec::new();
+    let mut depth = 0usize;
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        for word in line.split_whitespace() {
+            match word {
+                "{" => depth += 1,
+                "}" => depth = depth.saturating_sub(1),
+                w if depth == 0 => {
+                    let w = w.trim_end_matches('{').trim_matches('"').trim_matches('\'');
+                    if w.starts_with('/') {
+                        patterns.push(w.to_string());
+                    }
+                    if word.ends_with('{') {
+                        depth += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    patterns
+}
+
+/// Checks that one of `patterns` matches `log`.
+pub fn check_coverage(log: &Path, patterns: &[String]) -> Finding {
+    let text = log.to_string_lossy();
+    match patterns.iter().find(|p| glob_match(p, &text)) {
+        Some(pattern) => Finding::pass(
+            "logrotate-coverage",
+            log,
+            format!("Rotated by logrotate entry {}", pattern),
+        ),
+        None => Finding::fail(
+            "logrotate-coverage",
+            log,
+            Severity::Medium,
+            "No logrotate entry covers this log",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_rotated_patterns() {
+        let content = r#"
+weekly
+include /etc/logrotate.d
+/var/log/wtmp {
+    missingok
+    monthly
+}
+/var/log/syslog
+/var/log/mail.log
+{
+    rotate 4
+    postrotate
+        /usr/lib/rsyslog/rsyslog-rotate
+    endscript
+}
+"/var/log/nginx/*.log" {
+    daily
+}
+"#;
+        let patterns = rotated_patterns(content);
+        assert_eq!(
+            patterns,
+            ["/etc/logrotate.d", "/var/log/wtmp", "/var/log/syslog", "/var/log/mail.log", "/var/log/nginx/*.log"]
+        );
+        assert!(!check_coverage(Path::new("/var/log/nginx/access.log"), &patterns).is_failure());
+        assert!(check_coverage(Path::new("/var/log/auth.log"), &patterns).is_failure());
+    }
+
+    #[test]
+    fn test_oversized_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("small.log"), vec![0u8; 10]).unwrap();
+        fs::write(dir.path().join("big.log"), vec![0u8; 100]).unwrap();
+        fs::create_dir(dir.path().join("journal")).unwrap();
+        fs::write(dir.path().join("journal/system.journal"), vec![0u8; 100]).unwrap();
+        let audit = LogrotateAudit {
+            config: dir.path().join("logrotate.conf"),
+            config_d: dir.path().join("logrotate.d"),
+            key_logs: Vec::new(),
+            log_dir: dir.path().to_path_buf(),
+            max_log_size: 50,
+        };
+        let failed: Vec<_> = audit.run_audit().into_iter().filter(|f| f.is_failure()).collect();
+        assert_eq!(failed.len(), 2);
+        assert_eq!(failed[0].check, "logrotate-config");
+        assert!(failed[1].path.ends_with("big.log"));
+    }
+}
@@ -1,5 +1,34 @@
+pub mod absence;
+pub mod accounts;
+pub mod auditd;
+pub mod builder;
+pub mod cis;
+pub mod context;
+pub mod content;
+pub mod docker;
+pub mod embedded;
+pub mod environ;
+pub mod finding;
+pub mod firewall;
+pub mod footprint;
+pub mod glob;
+pub mod hardening;
+pub mod immutable;
 pub mod ownership;
+pub mod passwd;
 pub mod permissions;
+pub mod polkit;
+pub mod rule;
+pub mod scan;
+pub mod logrotate;
+#[cfg(feature = "watch")]
+pub mod monitor;
+pub mod mounts;
 pub mod networking;
+pub mod sudoers;
 pub mod symlink;
-pub mod toml_config;
\ No newline at end of file
+pub mod sysroot;
+pub mod sysctl;
+pub mod tls;
+pub mod toml_config;
+pub mod udev;
\ No newline at end of file
@@ -0,0 +1,392 @@
+//! Real-time monitoring of rule paths.
+//!
+//! Periodic audits miss a permission that is wrong for only a few minutes. A [`Monitor`]
+//! watches the directories of its permission and ownership rules with inotify and re-audits
+//! a path as soon as its mode or owner changes (`chmod`, `chown`, `setfacl`) or a file is
+//! created or moved into place, so the window is reported while it is open. Rules on
+//! recursive directories watch every subdirectory, including ones created later.
+//!
+//! Files are watched through their parent directory, so a file replaced by an editor or
+//! package manager stays watched. Each event re-reads the path; a mode that was changed and
+//! restored before the event is handled is reported as restored. If the kernel event queue
+//! overflows, every rule is re-audited.
+//!
+//! From the CLI: `halo monitor --target user --config rules.toml`.
+//!
+//! # Example Usage
+//! ```rust,no_run
+//! use alhalo::audit::monitor::Monitor;
+//! use alhalo::{Renderable, Target};
+//! use std::ops::ControlFlow;
+//! let mut monitor = Monitor::new().target(Target::User);
+//! monitor
+//!     .run(|report| {
+//!         report.render_and_print(Some("pretty"));
+//!         ControlFlow::Continue(())
+//!     })
+//!     .unwrap();
+//! ```
+use crate::audit::builder::Target;
+use crate::audit::ownership::ownership::OwnershipRule;
+use crate::audit::permissions::audit_permissions::PermissionRules;
+use crate::audit::permissions::severity_policy::SeverityPolicy;
+use crate::audit::scan::ScanControl;
+use crate::audit::sysroot;
+use crate::audit::toml_config::AuditConfig;
+use crate::error::HaloError;
+use crate::report::AuditReport;
+use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Events that can change the mode or owner of a path.
+const EVENTS: WatchMask = WatchMask::ATTRIB
+    .union(WatchMask::CREATE)
+    .union(WatchMask::MOVED_TO)
+    .union(WatchMask::ONLYDIR);
+
+/// Watches rule paths and re-audits them when they change.
+#[derive(Default)]
+pub struct Monitor {
+    rules: Vec<PermissionRules>,
+    ownership_rules: Vec<OwnershipRule>,
+    severity_policy: Option<SeverityPolicy>,
+    inotify: Option<Inotify>,
+    dirs: HashMap<WatchDescriptor, PathBuf>,
+}
+
+impl Monitor {
+    /// A monitor without rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Monitor the permission rules of a built-in target.
+    pub fn target(mut self, target: Target) -> Self {
+        self.rules.extend(target.permission_rules());
+        self
+    }
+
+    /// Monitor a single permission rule.
+    pub fn rule(mut self, rule: PermissionRules) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Monitor a single ownership rule.
+    pub fn ownership_rule(mut self, rule: OwnershipRule) -> Self {
+        self.ownership_rules.push(rule);
+        self
+    }
+
+    /// Monitor the permission and ownership rules of a config, with its severity policy.
+    ///
+    /// # Returns
+    /// * `Err` if a rule has an invalid mode.
+    pub fn config(mut self, config: &AuditConfig) -> Result<Self, HaloError> {
+        self.rules.extend(config.permission_rules()?);
+        self.ownership_rules.extend(config.ownership_rules());
+        if let Some(policy) = &config.severity_policy {
+            self.severity_policy = Some(policy.clone());
+        }
+        Ok(self)
+    }
+
+    /// Rate permission results with `policy` instead of the built-in severities.
+    pub fn severity_policy(mut self, policy: SeverityPolicy) -> Self {
+        self.severity_policy = Some(policy);
+        self
+    }
+
+    /// Starts watching the rule paths; called by [`poll`](Self::poll) and
+    /// [`wait`](Self::wait) if needed.
+    ///
+    /// Directories that cannot be watched, e.g. for lack of permission, are skipped with a
+    /// warning.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` with the number of watched directories.
+    /// * `Err` if inotify is unavailable or no directory could be watched.
+    pub fn start(&mut self) -> io::Result<usize> {
+        if self.inotify.is_some() {
+            return Ok(self.dirs.len());
+        }
+        self.inotify = Some(Inotify::init()?);
+        let roots: Vec<(PathBuf, bool)> = self
+            .rules
+            .iter()
+            .map(|r| (r.path.clone(), r.recursive))
+            .chain(self.ownership_rules.iter().map(|r| (r.path.clone(), r.recursive)))
+            .collect();
+        for (path, recursive) in roots {
+            match sysroot::symlink_metadata(&path) {
+                Ok(meta) if meta.is_dir() && recursive => self.watch_tree(&path),
+                Ok(meta) if meta.is_dir() => self.watch_dir(&path),
+                // Files, symlinks and missing paths are watched through their parent
+                _ => match path.parent() {
+                    Some(parent) => self.watch_dir(parent),
+                    None => warn!("Cannot monitor {}: no parent directory", path.display()),
+                },
+            }
+        }
+        if self.dirs.is_empty() {
+            self.inotify = None;
+            return Err(io::Error::other("No rule path could be monitored"));
+        }
+        info!("Monitoring {} directories", self.dirs.len());
+        Ok(self.dirs.len())
+    }
+
+    fn watch_dir(&mut self, dir: &Path) {
+        let Some(inotify) = &self.inotify else {
+            return;
+        };
+        match inotify.watches().add(sysroot::host_path_of(dir), EVENTS) {
+            Ok(wd) => {
+                debug!("Watching {}", dir.display());
+                self.dirs.insert(wd, dir.to_path_buf());
+            }
+            Err(e) => warn!("Cannot monitor {}: {}", dir.display(), e),
+        }
+    }
+
+    // Watches `dir` and every directory below it, without following symlinks
+    fn watch_tree(&mut self, dir: &Path) {
+        let mut stack = vec![dir.to_path_buf()];
+        while let Some(dir) = stack.pop() {
+            self.watch_dir(&dir);
+            if let Ok(entries) = sysroot::read_dir(&dir) {
+                stack.extend(
+                    entries
+                        .into_iter()
+                        .filter(|p| sysroot::symlink_metadata(p).is_ok_and(|m| m.is_dir())),
+                );
+            }
+        }
+    }
+
+    /// Re-audits the paths changed since the last call, without blocking.
+    ///
+    /// # Returns
+    /// * `Ok(Some(report))` with the results for every changed rule path.
+    /// * `Ok(None)` if no rule path changed.
+    pub fn poll(&mut self) -> io::Result<Option<AuditReport>> {
+        self.start()?;
+        self.read(false)
+    }
+
+    /// Blocks until a rule path changes and returns its re-audit.
+    pub fn wait(&mut self) -> io::Result<AuditReport> {
+        self.start()?;
+        loop {
+            if let Some(report) = self.read(true)? {
+                return Ok(report);
+            }
+        }
+    }
+
+    /// Reports every change until `on_change` breaks or an error occurs.
+    pub fn run(&mut self, mut on_change: impl FnMut(AuditReport) -> ControlFlow<()>) -> io::Result<()> {
+        loop {
+            let report = self.wait()?;
+            if on_change(report).is_break() {
+                return Ok(());
+            }
+        }
+    }
+
+    // Reads one buffer of events and re-audits the affected paths
+    fn read(&mut self, blocking: bool) -> io::Result<Option<AuditReport>> {
+        let Some(inotify) = self.inotify.as_mut() else {
+            return Ok(None);
+        };
+        let mut buffer = [0u8; 4096];
+        let read = if blocking {
+            inotify.read_events_blocking(&mut buffer)
+        } else {
+            inotify.read_events(&mut buffer)
+        };
+        let events = match read {
+            Ok(events) => events,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut changed = Vec::new();
+        let mut new_dirs = Vec::new();
+        let mut overflow = false;
+        for event in events {
+            if event.mask.contains(EventMask::Q_OVERFLOW) {
+                overflow = true;
+                continue;
+            }
+            if event.mask.contains(EventMask::IGNORED) {
+                self.dirs.remove(&event.wd);
+                continue;
+            }
+            let Some(dir) = self.dirs.get(&event.wd) else {
+                continue;
+            };
+            let path = match event.name {
+                Some(name) => dir.join(name),
+                None => dir.clone(),
+            };
+            if event.mask.contains(EventMask::ISDIR)
+                && event.mask.intersects(EventMask::CREATE | EventMask::MOVED_TO)
+                && self.recursive_roots().any(|root| path.starts_with(root))
+            {
+                new_dirs.push(path.clone());
+            }
+            if !changed.contains(&path) {
+                changed.push(path);
+            }
+        }
+        for dir in new_dirs {
+            self.watch_tree(&dir);
+        }
+
+        let mut control = ScanControl::new();
+        if let Some(policy) = &self.severity_policy {
+            control = control.with_severity_policy(policy.clone());
+        }
+        let mut report = AuditReport::default();
+        if overflow {
+            warn!("inotify event queue overflowed; re-auditing every rule");
+            let mut visited = HashSet::new();
+            for rule in &self.rules {
+                report.permissions.extend(rule.check_with(&mut visited, &mut control));
+            }
+            report
+                .ownership
+                .extend(self.ownership_rules.iter().map(OwnershipRule::check_ownership));
+        } else {
+            for path in &changed {
+                self.audit_path(path, &mut control, &mut report);
+            }
+        }
+        Ok(if report.is_empty() { None } else { Some(report) })
+    }
+
+    // Paths of the recursive rules
+    fn recursive_roots(&self) -> impl Iterator<Item = &PathBuf> {
+        let rules = self.rules.iter().filter(|r| r.recursive).map(|r| &r.path);
+        rules.chain(self.ownership_rules.iter().filter(|r| r.recursive).map(|r| &r.path))
+    }
+
+    // Re-audits `path` against every rule that covers it
+    fn audit_path(&self, path: &Path, control: &mut ScanControl, report: &mut AuditReport) {
+        let covers = |root: &Path, recursive: bool| path == root || (recursive && path.starts_with(root));
+        for rule in self.rules.iter().filter(|r| covers(&r.path, r.recursive)) {
+            let rule = PermissionRules {
+                path: path.to_path_buf(),
+                recursive: false,
+                ..rule.clone()
+            };
+            report.permissions.extend(rule.check_with(&mut HashSet::new(), control));
+        }
+        for rule in self.ownership_rules.iter().filter(|r| covers(&r.path, r.recursive)) {
+            let rule = OwnershipRule {
+                path: path.to_path_buf(),
+                recursive: false,
+                ..rule.clone()
+            };
+            if sysroot::symlink_metadata(path).is_ok() {
+                report.ownership.push(rule.check_ownership());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::permissions::audit_permissions::{Importance, Status};
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::thread;
+    use std::time::Duration;
+
+    fn chmod(path: &Path, mode: u32) {
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    // Polls until the monitor reports a change, for up to two seconds
+    fn next_report(monitor: &mut Monitor) -> Option<AuditReport> {
+        for _ in 0..200 {
+            if let Some(report) = monitor.poll().unwrap() {
+                return Some(report);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        None
+    }
+
+    #[test]
+    fn test_reports_mode_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let secret = dir.path().join("secret");
+        let other = dir.path().join("other");
+        let conf = dir.path().join("conf.d");
+        fs::write(&secret, "").unwrap();
+        fs::write(&other, "").unwrap();
+        fs::create_dir(&conf).unwrap();
+        chmod(&secret, 0o600);
+
+        let (rule, _) = PermissionRules::new(secret.clone(), 0o600, Importance::High);
+        let (tree, _) = PermissionRules::new(conf.clone(), 0o644, Importance::Medium);
+        let mut monitor = Monitor::new().rule(rule).rule(tree);
+        assert_eq!(monitor.start().unwrap(), 2);
+        assert!(monitor.poll().unwrap().is_none());
+
+        // Unrelated files in a watched directory are ignored
+        chmod(&other, 0o666);
+        thread::sleep(Duration::from_millis(50));
+        assert!(monitor.poll().unwrap().is_none());
+
+        chmod(&secret, 0o644);
+        let report = next_report(&mut monitor).unwrap();
+        assert_eq!(report.permissions.len(), 1);
+        assert_eq!(report.permissions[0].path, secret);
+        assert_eq!(report.permissions[0].status, Status::Fail);
+
+        // New subdirectories of a recursive rule are watched as well
+        fs::create_dir(conf.join("sub")).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        monitor.poll().unwrap();
+        let file = conf.join("sub").join("app.conf");
+        fs::write(&file, "").unwrap();
+        chmod(&file, 0o666);
+        let report = next_report(&mut monitor).unwrap();
+        assert!(report.permissions.iter().all(|r| r.path == file));
+        assert_eq!(report.permissions.last().unwrap().found_mode, 0o666);
+    }
+
+    #[test]
+    fn test_config_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("app.key");
+        fs::write(&file, "").unwrap();
+        chmod(&file, 0o600);
+        let uid = fs::metadata(&file).map(|m| std::os::unix::fs::MetadataExt::uid(&m)).unwrap();
+        let config = AuditConfig::parse(
+            &format!(
+                "[[perm_rules]]\npath = \"{0}\"\nexpected_mode = 600\nimportance = \"High\"\n\n[[owner_rules]]\npath = \"{0}\"\nexpected_uid = {1}\nexpected_gid = 0\n",
+                file.display(),
+                uid
+            ),
+            crate::audit::toml_config::ConfigFormat::Toml,
+        )
+        .unwrap();
+        let mut monitor = Monitor::new().config(&config).unwrap();
+        monitor.start().unwrap();
+        chmod(&file, 0o640);
+        let report = next_report(&mut monitor).unwrap();
+        assert_eq!(report.permissions[0].found_mode, 0o640);
+        assert_eq!(report.ownership.len(), 1);
+        assert_eq!(report.ownership[0].found_uid, Some(uid));
+
+        assert!(Monitor::new().start().is_err());
+    }
+}
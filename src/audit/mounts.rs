@@ -0,0 +1,276 @@
+//! Mount boundaries of recursive permission walks.
+//!
+//! A recursive rule on `/` or `/var` would otherwise descend into `/proc`, `/sys`, `/dev` and
+//! network shares: pseudo filesystems produce thousands of meaningless results, and an NFS or
+//! CIFS mount can stall the walk or audit another machine's files. A walk notices a mount
+//! point when a directory's device differs from its parent's and then applies the rule's
+//! [`MountPolicy`]:
+//! - [`SkipVirtual`](MountPolicy::SkipVirtual) (the default) skips mounts of pseudo and network
+//!   filesystem types, looked up in `/proc/self/mountinfo`
+//! - [`OneFileSystem`](MountPolicy::OneFileSystem) stays on the filesystem of the rule's path,
+//!   like `find -xdev` (`halo check --one-file-system`)
+//! - [`All`](MountPolicy::All) descends into every mount
+//!
+//! The rule's own path is always checked, so an explicit rule on `/proc` still walks it.
+//!
+//! [`parse_fstab`] and [`parse_mountinfo`] read `/etc/fstab`, `/proc/mounts` and
+//! `/proc/self/mountinfo` into [`MountEntry`] records with the options split into a list; the
+//! mount table, the `/proc` hardening check and `halo parse --parser fstab` share them.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::audit::mounts::{MountPolicy, MountTable};
+//! use std::path::Path;
+//! let table = MountTable::parse("22 1 0:21 / /proc rw,nosuid - proc proc rw\n");
+//! assert_eq!(table.fs_type(Path::new("/proc")), Some("proc"));
+//! assert!(MountPolicy::SkipVirtual.skips(Some("proc")));
+//! assert!(!MountPolicy::SkipVirtual.skips(Some("ext4")));
+//!
+//! let entries = alhalo::audit::mounts::parse_fstab("tmpfs /tmp tmpfs nodev,nosuid 0 0\n");
+//! assert!(entries[0].has_option("nosuid"));
+//! ```
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Kernel and pseudo filesystems: their contents are not files on disk.
+pub const PSEUDO_FS_TYPES: &[&str] = &[
+    "proc", "sysfs", "devtmpfs", "devpts", "cgroup", "cgroup2", "securityfs", "debugfs", "tracefs",
+    "configfs", "fusectl", "mqueue", "hugetlbfs", "bpf", "pstore", "efivarfs", "binfmt_misc", "autofs",
+    "rpc_pipefs", "nsfs", "selinuxfs",
+];
+
+/// Network filesystems: walking them audits, and loads, another machine.
+pub const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "ncpfs", "afs", "9p", "ceph", "glusterfs", "lustre", "davfs",
+    "fuse.sshfs", "fuse.glusterfs", "fuse.s3fs", "fuse.rclone",
+];
+
+/// Which mounts below a rule's path a recursive walk descends into.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum MountPolicy {
+    /// Skip pseudo and network filesystems
+    #[default]
+    SkipVirtual,
+    /// Stay on the filesystem of the rule's path
+    OneFileSystem,
+    /// Descend into every mount
+    All,
+}
+
+impl MountPolicy {
+    /// Whether a walk skips a mount point of filesystem type `fs_type` (`None` if unknown).
+    pub fn skips(&self, fs_type: Option<&str>) -> bool {
+        match self {
+            MountPolicy::SkipVirtual => fs_type.is_some_and(is_virtual),
+            MountPolicy::OneFileSystem => true,
+            MountPolicy::All => false,
+        }
+    }
+}
+
+/// Whether `fs_type` is a pseudo or network filesystem.
+pub fn is_virtual(fs_type: &str) -> bool {
+    PSEUDO_FS_TYPES.contains(&fs_type) || NETWORK_FS_TYPES.contains(&fs_type)
+}
+
+/// One mount of an fstab or mountinfo table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MountEntry {
+    /// Mounted device or source, e.g. `/dev/sda1`, `UUID=...` or `proc`
+    pub device: String,
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    /// Mount options in table order; for mountinfo, the per-mount options followed by the
+    /// superblock options not already listed
+    pub options: Vec<String>,
+}
+
+impl MountEntry {
+    /// Whether the mount has the flag `name`, e.g. `nodev`.
+    pub fn has_option(&self, name: &str) -> bool {
+        self.options.iter().any(|o| o == name)
+    }
+
+    /// Value of the option `key=value`, e.g. `hidepid`; the last one wins.
+    pub fn option_value(&self, key: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find_map(|o| o.strip_prefix(key)?.strip_prefix('='))
+    }
+}
+
+/// Parses the fstab format of `/etc/fstab` and `/proc/mounts`: device, mount point, type and
+/// options. Blank lines, `#` comments and lines with fewer than three fields are skipped; a
+/// missing options field is `defaults`.
+pub fn parse_fstab(content: &str) -> Vec<MountEntry> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (device, point, fs_type) = (fields.next()?, fields.next()?, fields.next()?);
+            Some(MountEntry {
+                device: unescape(device),
+                mount_point: PathBuf::from(unescape(point)),
+                fs_type: fs_type.to_string(),
+                options: split_options(fields.next().unwrap_or("defaults")),
+            })
+        })
+        .collect()
+}
+
+/// Parses the `mountinfo` format: mount point in the fifth field and options in the sixth,
+/// then type, source and superblock options after the ` - ` separator.
+pub fn parse_mountinfo(content: &str) -> Vec<MountEntry> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (fields, rest) = line.split_once(" - ")?;
+            let mut fields = fields.split_whitespace().skip(4);
+            let (point, mount_options) = (fields.next()?, fields.next().unwrap_or_default());
+            let mut rest = rest.split_whitespace();
+            let (fs_type, source) = (rest.next()?, rest.next().unwrap_or_default());
+            let mut options = split_options(mount_options);
+            for option in split_options(rest.next().unwrap_or_default()) {
+                if !options.contains(&option) {
+                    options.push(option);
+                }
+            }
+            Some(MountEntry {
+                device: unescape(source),
+                mount_point: PathBuf::from(unescape(point)),
+                fs_type: fs_type.to_string(),
+                options,
+            })
+        })
+        .collect()
+}
+
+fn split_options(options: &str) -> Vec<String> {
+    options.split(',').filter(|o| !o.is_empty()).map(str::to_string).collect()
+}
+
+/// Filesystem types by mount point, as seen by this process.
+#[derive(Debug, Clone, Default)]
+pub struct MountTable {
+    types: HashMap<PathBuf, String>,
+}
+
+impl MountTable {
+    /// Reads `/proc/self/mountinfo`, or `/proc/mounts` on older kernels; empty if neither can
+    /// be read.
+    ///
+    /// Mount points are paths on this machine, also when auditing an alternate root.
+    pub fn load() -> Self {
+        if let Ok(text) = fs::read_to_string("/proc/self/mountinfo") {
+            return Self::parse(&text);
+        }
+        fs::read_to_string("/proc/mounts")
+            .map(|text| Self::parse_mounts(&text))
+            .unwrap_or_default()
+    }
+
+    /// Parses the `mountinfo` format: mount point in the fifth field, type after the ` - `
+    /// separator. Later mounts over the same point win, as they hide the earlier ones.
+    pub fn parse(mountinfo: &str) -> Self {
+        Self::from_entries(parse_mountinfo(mountinfo))
+    }
+
+    /// Parses the `/proc/mounts` (fstab) format: device, mount point, type, options.
+    pub fn parse_mounts(mounts: &str) -> Self {
+        Self::from_entries(parse_fstab(mounts))
+    }
+
+    fn from_entries(entries: Vec<MountEntry>) -> Self {
+        let types = entries.into_iter().map(|e| (e.mount_point, e.fs_type)).collect();
+        MountTable { types }
+    }
+
+    /// Filesystem type mounted at `mount_point`, if it is one.
+    pub fn fs_type(&self, mount_point: &Path) -> Option<&str> {
+        self.types.get(mount_point).map(String::as_str)
+    }
+}
+
+// The kernel writes space, tab, newline and backslash in paths as octal escapes, e.g. `\040`
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = match bytes.get(i..i + 4) {
+            Some([b'\\', digits @ ..]) if digits.iter().all(|d| (b'0'..=b'7').contains(d)) => {
+                Some(digits.iter().fold(0u32, |n, d| n * 8 + u32::from(d - b'0')))
+            }
+            _ => None,
+        };
+        match octal.and_then(|n| u8::try_from(n).ok()) {
+            Some(byte) => {
+                out.push(byte);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mount_tables() {
+        let mountinfo = "\
+25 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw
+22 25 0:21 / /proc rw,nosuid,nodev,noexec,relatime shared:12 - proc proc rw
+40 25 0:35 / /mnt/my\\040share rw,relatime shared:20 - cifs //nas/share rw
+41 25 0:36 / /mnt/data rw - ext4 /dev/sdb1 rw
+42 25 0:37 / /mnt/data rw - nfs4 nas:/data rw
+";
+        let table = MountTable::parse(mountinfo);
+        assert_eq!(table.fs_type(Path::new("/proc")), Some("proc"));
+        assert_eq!(table.fs_type(Path::new("/mnt/my share")), Some("cifs"));
+        // The later mount hides the earlier one
+        assert_eq!(table.fs_type(Path::new("/mnt/data")), Some("nfs4"));
+        assert_eq!(table.fs_type(Path::new("/etc")), None);
+
+        let table = MountTable::parse_mounts("sysfs /sys sysfs rw,nosuid 0 0\n/dev/sda1 /boot ext4 rw 0 0\n");
+        assert_eq!(table.fs_type(Path::new("/sys")), Some("sysfs"));
+        assert_eq!(table.fs_type(Path::new("/boot")), Some("ext4"));
+
+        let mounts = parse_mountinfo(mountinfo);
+        assert_eq!(mounts[1].device, "proc");
+        assert_eq!(mounts[1].options, ["rw", "nosuid", "nodev", "noexec", "relatime"]);
+        assert_eq!(mounts[2].device, "//nas/share");
+
+        let fstab = "\
+# /etc/fstab
+UUID=1234 /boot ext4 defaults,nodev 0 2
+
+tmpfs /tmp tmpfs rw,nosuid,size=2G,size=4G 0 0
+/dev/sr0 /media/cd\\040rom iso9660
+";
+        let entries = parse_fstab(fstab);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].device, "UUID=1234");
+        assert!(entries[0].has_option("nodev") && !entries[0].has_option("nosuid"));
+        assert_eq!(entries[1].option_value("size"), Some("4G"));
+        assert_eq!(entries[2].mount_point, Path::new("/media/cd rom"));
+        assert_eq!(entries[2].options, ["defaults"]);
+
+        assert!(MountPolicy::SkipVirtual.skips(Some("nfs4")));
+        assert!(!MountPolicy::SkipVirtual.skips(None));
+        assert!(MountPolicy::OneFileSystem.skips(Some("ext4")));
+        assert!(!MountPolicy::All.skips(Some("proc")));
+    }
+}
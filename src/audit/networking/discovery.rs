@@ -3,6 +3,7 @@
 use std::net::IpAddr;
 use serde::{Deserialize, Serialize};
 use std::process::Command;
+use crate::error::HaloError;
 use crate::render_output::{Renderable, DataList};
 use indexmap::IndexMap;
 
@@ -40,28 +41,29 @@ pub struct ScanResults {
 }
 
 /// Discovers network devices by parsing the system's ARP table.
-/// Returns a vector of devices or an error message if the operation fails.
-pub fn get_arp_devices() -> Result<Vec<Devices>, String> {
-    let output = Command::new("arp")
-        .arg("-a")
-        .output()
-        .map_err(|e| format!("Failed to locate devices: {}", e))?;
+/// Returns a vector of devices, or a [`HaloError::Command`] if `arp` cannot run or fails.
+pub fn get_arp_devices() -> Result<Vec<Devices>, HaloError> {
+    let command_error = |message: String| HaloError::Command {
+        command: "arp -a".to_string(),
+        message,
+    };
+    let output = Command::new("arp").arg("-a").output().map_err(|e| command_error(e.to_string()))?;
 
     if !output.status.success() {
-        return Err(format!("Output failed with status: {}", output.status));
+        return Err(command_error(format!("exited with {}", output.status)));
     }
 
     let devices = String::from_utf8(output.stdout);
 
     match devices {
-        Ok(arp_data) => { return parse_arp(arp_data) },
-        Err(e) => { return Err(format!("Cannot process data: {}", e)) },
+        Ok(arp_data) => parse_arp(arp_data),
+        Err(e) => Err(HaloError::parse("ARP table", e)),
     }
 }
 
 /// Parses ARP table output to extract device information.
 /// Expects format: `hostname (192.168.1.1) at aa:bb:cc:dd:ee:ff [ether] on eth0`
-fn parse_arp(arp_data: String) -> Result<Vec<Devices>, String> {
+fn parse_arp(arp_data: String) -> Result<Vec<Devices>, HaloError> {
     let mut devices = Vec::new();
 
     for r in arp_data.lines() {
@@ -1 +1,2 @@
-pub mod discovery;
\ No newline at end of file
+pub mod discovery;
+pub mod sockets;
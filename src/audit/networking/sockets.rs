@@ -0,0 +1,266 @@
+//! Listening socket discovery using `/proc/net`.
+//!
+//! Parses `/proc/net/tcp`, `tcp6`, `udp` and `udp6` for sockets accepting connections, maps
+//! each socket inode to the owning process through `/proc/<pid>/fd`, and optionally compares
+//! the ports against an allowlist of expected services.
+//!
+//! Mapping sockets of other users' processes requires root; without it `pid` and `process` are
+//! only filled in for the caller's own processes.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::audit::networking::sockets::{apply_allowlist, get_listening_sockets};
+//! use alhalo::Renderable;
+//! let mut sockets = get_listening_sockets().unwrap();
+//! apply_allowlist(&mut sockets, &[22, 443]);
+//! sockets.render_and_print(Some("pretty"));
+//! ```
+use crate::audit::passwd::parse_passwd;
+use crate::error::HaloError;
+use crate::render_output::{DataList, Renderable};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+/// TCP state of a listening socket in `/proc/net/tcp`.
+const TCP_LISTEN: &str = "0A";
+/// State of a bound, unconnected UDP socket in `/proc/net/udp`.
+const UDP_UNCONNECTED: &str = "07";
+
+/// A socket accepting connections or datagrams.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ListeningSocket {
+    /// `tcp`, `tcp6`, `udp` or `udp6`
+    pub protocol: String,
+    /// Local address the socket is bound to
+    pub address: IpAddr,
+    /// Local port
+    pub port: u16,
+    /// UID owning the socket
+    pub uid: u32,
+    /// User name for `uid`, if known
+    pub user: Option<String>,
+    /// Socket inode
+    pub inode: u64,
+    /// Process holding the socket, if it could be determined
+    pub pid: Option<u32>,
+    /// Name of that process
+    pub process: Option<String>,
+    /// Whether the port is on the allowlist; `None` if no allowlist was applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<bool>,
+}
+
+impl Renderable for ListeningSocket {
+    fn to_datalist(&self) -> DataList {
+        let mut map = IndexMap::new();
+        map.insert("protocol".to_string(), self.protocol.clone());
+        map.insert("address".to_string(), self.address.to_string());
+        map.insert("port".to_string(), self.port.to_string());
+        map.insert(
+            "user".to_string(),
+            self.user.clone().unwrap_or_else(|| self.uid.to_string()),
+        );
+        map.insert(
+            "pid".to_string(),
+            self.pid.map(|p| p.to_string()).unwrap_or_default(),
+        );
+        map.insert("process".to_string(), self.process.clone().unwrap_or_default());
+        map.insert(
+            "expected".to_string(),
+            self.expected.map(|e| e.to_string()).unwrap_or_default(),
+        );
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        let marker = match self.expected {
+            Some(false) => "[!] ",
+            _ => "",
+        };
+        let process = match (&self.process, self.pid) {
+            (Some(name), Some(pid)) => format!("{} ({})", name, pid),
+            _ => "Unknown".to_string(),
+        };
+        let user = self.user.clone().unwrap_or_else(|| self.uid.to_string());
+        let address = match self.address {
+            IpAddr::V6(v6) => format!("[{}]", v6),
+            v4 => v4.to_string(),
+        };
+        format!(
+            "{}{} {}:{} - {} as {}",
+            marker, self.protocol, address, self.port, process, user
+        )
+    }
+}
+
+/// Discovers listening TCP and bound UDP sockets from `/proc/net`.
+/// Returns the sockets sorted by protocol and port, or the error reading `/proc/net/tcp` if
+/// no socket table can be read.
+pub fn get_listening_sockets() -> Result<Vec<ListeningSocket>, HaloError> {
+    let mut sockets = Vec::new();
+    let mut first_error = None;
+    let mut found_any = false;
+    for protocol in ["tcp", "tcp6", "udp", "udp6"] {
+        // tcp6/udp6 are missing when IPv6 is disabled
+        let path = Path::new("/proc/net").join(protocol);
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                found_any = true;
+                sockets.extend(parse_proc_net(&content, protocol));
+            }
+            Err(e) => {
+                first_error.get_or_insert(HaloError::io(&path, e));
+            }
+        }
+    }
+    if let Some(error) = first_error.filter(|_| !found_any) {
+        return Err(error);
+    }
+
+    let users: HashMap<u32, String> = fs::read_to_string("/etc/passwd")
+        .map(|c| parse_passwd(&c).into_iter().map(|e| (e.uid, e.name)).collect())
+        .unwrap_or_default();
+    let owners = socket_owners(Path::new("/proc"));
+    for socket in &mut sockets {
+        socket.user = users.get(&socket.uid).cloned();
+        if let Some((pid, name)) = owners.get(&socket.inode) {
+            socket.pid = Some(*pid);
+            socket.process = Some(name.clone());
+        }
+    }
+    sockets.sort_by(|a, b| (&a.protocol, a.port).cmp(&(&b.protocol, b.port)));
+    Ok(sockets)
+}
+
+/// Parses a `/proc/net/{tcp,tcp6,udp,udp6}` table and keeps the listening sockets.
+pub fn parse_proc_net(content: &str, protocol: &str) -> Vec<ListeningSocket> {
+    let listening = if protocol.starts_with("tcp") {
+        TCP_LISTEN
+    } else {
+        UDP_UNCONNECTED
+    };
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != listening {
+                return None;
+            }
+            let (address, port) = parse_address(fields[1])?;
+            Some(ListeningSocket {
+                protocol: protocol.to_string(),
+                address,
+                port,
+                uid: fields[7].parse().ok()?,
+                user: None,
+                inode: fields[9].parse().ok()?,
+                pid: None,
+                process: None,
+                expected: None,
+            })
+        })
+        .collect()
+}
+
+/// Marks each socket as expected or not by its port.
+pub fn apply_allowlist(sockets: &mut [ListeningSocket], allowed_ports: &[u16]) {
+    for socket in sockets {
+        socket.expected = Some(allowed_ports.contains(&socket.port));
+    }
+}
+
+/// Parses `0100007F:0016` (IPv4) or a 32 digit IPv6 address with port.
+///
+/// The kernel prints each 32-bit word of the address in host byte order.
+fn parse_address(field: &str) -> Option<(IpAddr, u16)> {
+    let (addr, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut bytes = Vec::with_capacity(16);
+    for i in (0..addr.len()).step_by(8) {
+        let word = u32::from_str_radix(addr.get(i..i + 8)?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_ne_bytes());
+    }
+    let address = match bytes.len() {
+        4 => IpAddr::V4(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])),
+        16 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => return None,
+    };
+    Some((address, port))
+}
+
+/// Maps socket inodes to the (pid, process name) holding them.
+fn socket_owners(proc_root: &Path) -> HashMap<u64, (u32, String)> {
+    let mut owners = HashMap::new();
+    let Ok(entries) = fs::read_dir(proc_root) else {
+        return owners;
+    };
+    for entry in entries.flatten() {
+        let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let name = fs::read_to_string(entry.path().join("comm"))
+            .map(|c| c.trim().to_string())
+            .unwrap_or_default();
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            if let Some(inode) = target
+                .strip_prefix("socket:[")
+                .and_then(|t| t.strip_suffix(']'))
+                .and_then(|i| i.parse().ok())
+            {
+                owners.entry(inode).or_insert_with(|| (pid, name.clone()));
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0277 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 21457 1 0000000000000000 100 0 0 10 0
+   1: 0F02000A:0016 0202000A:C3A2 01 00000000:00000000 02:000A7F7A 00000000     0        0 33012 4 0000000000000000 20 4 30 10 -1
+   2: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 19000 1 0000000000000000 100 0 0 10 0
+";
+
+    #[test]
+    fn test_parse_tcp_listeners() {
+        let sockets = parse_proc_net(TCP, "tcp");
+        assert_eq!(sockets.len(), 2);
+        if cfg!(target_endian = "little") {
+            assert_eq!(sockets[0].address, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        }
+        assert_eq!(sockets[0].port, 631);
+        assert_eq!(sockets[1].port, 22);
+        assert_eq!(sockets[1].inode, 19000);
+    }
+
+    #[test]
+    fn test_parse_ipv6_and_allowlist() {
+        let udp6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  10: 00000000000000000000000001000000:0035 00000000000000000000000000000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 41000 2 0000000000000000 0
+";
+        let mut sockets = parse_proc_net(udp6, "udp6");
+        assert_eq!(sockets.len(), 1);
+        if cfg!(target_endian = "little") {
+            assert_eq!(sockets[0].address, IpAddr::V6(Ipv6Addr::LOCALHOST));
+        }
+        assert_eq!((sockets[0].port, sockets[0].uid), (53, 101));
+        apply_allowlist(&mut sockets, &[22]);
+        assert_eq!(sockets[0].expected, Some(false));
+        assert!(sockets[0].pretty_print().starts_with("[!] udp6 "));
+    }
+}
@@ -16,24 +16,30 @@
 //!     expected_gid: 42,
 //!     follow_symlinks: false,
 //!     recursive: false,
+//!     id: None,
+//!     symlink_severity: None,
 //! };
 //! let result = rule.check_ownership();
 //! println!("UID: {:?}, GID: {:?}, Pass: {}", result.found_uid, result.found_gid, result.pass);
 //! ```
 
-use crate::{PathStatus, Severity, SymRule, check_symlink};
-use serde::{Deserialize, Serialize};
-use std::fs;
+use crate::{PathStatus, Severity, Status, SymRule, check_symlink};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 use std::io;
 use std::os::unix::fs::MetadataExt;
 use std::path::PathBuf;
-use crate::render_output::{Renderable, DataList as RenderDataList, DataMap};
+use crate::ack::{Acknowledgeable, Acknowledgment, stable_id};
+use crate::render_output::{Renderable, DataList as RenderDataList, DataMap, Style, paint};
+use crate::audit::context::AuditContext;
+use crate::audit::sysroot;
+use crate::error::HaloError;
 use indexmap::IndexMap;
 
 /// Result of an ownership audit.
 ///
 /// Contains the actual and expected UID/GID, pass/fail status, and error info.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct OwnershipResult {
     pub path: PathBuf,
     pub expected_uid: Option<u32>,
@@ -42,13 +48,99 @@ pub struct OwnershipResult {
     pub found_gid: Option<u32>,
     pub pass: bool,
     pub severity: Severity,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Id of the rule that produced the result
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    /// Acknowledgment covering this failure in its current state
+    #[serde(default)]
+    pub acknowledged: Option<Acknowledgment>,
     pub error: Option<String>,
 }
 
+impl OwnershipResult {
+    /// Informational failure for a rule whose path does not exist.
+    pub fn not_found(path: PathBuf, expected_uid: u32, expected_gid: u32, rule_id: Option<String>) -> Self {
+        let error = Some(HaloError::NotFound(path.clone()).to_string());
+        OwnershipResult {
+            path,
+            expected_uid: Some(expected_uid),
+            expected_gid: Some(expected_gid),
+            found_uid: None,
+            found_gid: None,
+            pass: false,
+            severity: Severity::Info,
+            rule_id,
+            acknowledged: None,
+            error,
+        }
+    }
+
+    /// Stable id of the result, derived from its path; used to acknowledge a failure.
+    pub fn id(&self) -> String {
+        stable_id(&["ownership", &self.path.to_string_lossy()])
+    }
+}
+
+impl Acknowledgeable for OwnershipResult {
+    fn ack_id(&self) -> String {
+        self.id()
+    }
+
+    fn fingerprint(&self) -> String {
+        let id = |id: Option<u32>| id.map_or("?".to_string(), |id| id.to_string());
+        stable_id(&[
+            &format!("{:?}", self.severity),
+            &id(self.found_uid),
+            &id(self.found_gid),
+            &id(self.expected_uid),
+            &id(self.expected_gid),
+        ])
+    }
+
+    fn is_failure(&self) -> bool {
+        !self.pass
+    }
+
+    fn acknowledgment_mut(&mut self) -> &mut Option<Acknowledgment> {
+        &mut self.acknowledged
+    }
+}
+
+// Written by hand to include the computed id
+impl Serialize for OwnershipResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut result = serializer.serialize_struct("OwnershipResult", 11)?;
+        result.serialize_field("id", &self.id())?;
+        result.serialize_field("path", &self.path)?;
+        result.serialize_field("expected_uid", &self.expected_uid)?;
+        result.serialize_field("expected_gid", &self.expected_gid)?;
+        result.serialize_field("found_uid", &self.found_uid)?;
+        result.serialize_field("found_gid", &self.found_gid)?;
+        result.serialize_field("pass", &self.pass)?;
+        result.serialize_field("severity", &self.severity)?;
+        match &self.rule_id {
+            Some(id) => result.serialize_field("rule_id", id)?,
+            None => result.skip_field("rule_id")?,
+        }
+        match &self.acknowledged {
+            Some(ack) => result.serialize_field("acknowledged", ack)?,
+            None => result.skip_field("acknowledged")?,
+        }
+        match &self.error {
+            Some(error) => result.serialize_field("error", error)?,
+            None => result.skip_field("error")?,
+        }
+        result.end()
+    }
+}
+
 impl Renderable for OwnershipResult {
     fn to_datalist(&self) -> RenderDataList {
         let mut map = IndexMap::new();
+        map.insert("id".to_string(), self.id());
+        if let Some(ref id) = self.rule_id {
+            map.insert("rule_id".to_string(), id.clone());
+        }
         map.insert("path".to_string(), self.path.display().to_string());
         map.insert("expected_uid".to_string(), 
             self.expected_uid.map_or("N/A".to_string(), |uid| uid.to_string()));
@@ -63,26 +155,42 @@ impl Renderable for OwnershipResult {
         if let Some(ref err) = self.error {
             map.insert("error".to_string(), err.clone());
         }
+        if let Some(ref ack) = self.acknowledged {
+            map.insert("acknowledged".to_string(), ack.comment.clone());
+        }
         vec![map]
     }
     
     fn pretty_print(&self) -> String {
-        let status_symbol = if self.pass { "✓" } else { "✗" };
-        
+        let status = match (self.pass, &self.acknowledged) {
+            (true, _) => Status::Pass.symbol(),
+            (false, Some(_)) => paint("~", Style::Dim),
+            (false, None) => Status::Fail.symbol(),
+        };
+
         let mut result = format!(
-            "{} {} (UID: {}/{}, GID: {}/{}) - {:?}",
-            status_symbol,
+            "{} {} {} (UID: {}/{}, GID: {}/{})",
+            status,
+            self.severity.column(),
             self.path.display(),
             self.found_uid.map_or("?".to_string(), |uid| uid.to_string()),
             self.expected_uid.map_or("?".to_string(), |uid| uid.to_string()),
             self.found_gid.map_or("?".to_string(), |gid| gid.to_string()),
-            self.expected_gid.map_or("?".to_string(), |gid| gid.to_string()),
-            self.severity
+            self.expected_gid.map_or("?".to_string(), |gid| gid.to_string())
         );
+        if let Some(ref id) = self.rule_id {
+            result.push_str(&format!(" [{}]", id));
+        }
         
         if let Some(ref err) = self.error {
             result.push_str(&format!(" [Error: {}]", err));
         }
+        if !self.pass {
+            result.push_str(&format!(" ({})", self.id()));
+        }
+        if let Some(ref ack) = self.acknowledged {
+            result.push_str(&format!("\n      > acknowledged: {}", ack.comment));
+        }
         
         result
     }
@@ -99,6 +207,11 @@ pub struct OwnershipRule {
     /// If true, follow symlinks
     pub follow_symlinks: bool,
     pub recursive: bool,
+    /// Stable rule id carried by the result
+    pub id: Option<String>,
+    /// Severity of a failed check if the path is a symlink, overriding the per-issue default,
+    /// see [`SymRule::severity_on_mismatch`]
+    pub symlink_severity: Option<Severity>,
 }
 
 impl OwnershipRule {
@@ -108,7 +221,7 @@ impl OwnershipRule {
         expected_gid: u32,
         follow_symlinks: bool,
     ) -> (Self, PathStatus) {
-        if !path.exists() {
+        if !sysroot::exists(&path) {
             return (
                 OwnershipRule {
                     path,
@@ -116,12 +229,14 @@ impl OwnershipRule {
                     expected_gid,
                     follow_symlinks,
                     recursive: false,
+                    id: None,
+                    symlink_severity: None,
                 },
                 PathStatus::NotFound,
             );
         }
 
-        match fs::metadata(&path) {
+        match sysroot::metadata(&path) {
             Ok(meta) => {
                 if meta.is_file() {
                     (
@@ -131,6 +246,8 @@ impl OwnershipRule {
                             expected_gid,
                             follow_symlinks,
                             recursive: false,
+                            id: None,
+                            symlink_severity: None,
                         },
                         PathStatus::ValidFile,
                     )
@@ -142,6 +259,8 @@ impl OwnershipRule {
                             expected_gid,
                             follow_symlinks,
                             recursive: true,
+                            id: None,
+                            symlink_severity: None,
                         },
                         PathStatus::ValidDirectory,
                     )
@@ -153,6 +272,8 @@ impl OwnershipRule {
                             expected_gid,
                             follow_symlinks,
                             recursive: false,
+                            id: None,
+                            symlink_severity: None,
                         },
                         PathStatus::NotFound,
                     )
@@ -167,6 +288,8 @@ impl OwnershipRule {
                             expected_gid,
                             follow_symlinks,
                             recursive: false,
+                            id: None,
+                            symlink_severity: None,
                         },
                         PathStatus::PermissionDenied,
                     )
@@ -178,6 +301,8 @@ impl OwnershipRule {
                             expected_gid,
                             follow_symlinks,
                             recursive: false,
+                            id: None,
+                            symlink_severity: None,
                         },
                         PathStatus::NotFound,
                     )
@@ -186,6 +311,12 @@ impl OwnershipRule {
         }
     }
 
+    /// Set the rule id reported with the result.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
     /// Determine ownership audit severity
     pub fn owner_severity(&self, uid: u32, gid: u32) -> Severity {
         // If audit passes, no severity
@@ -215,35 +346,40 @@ impl OwnershipRule {
     /// Checks ownership of the given path against expected UID and GID.
     /// Uses symlink audit module for symlink paths.
     pub fn check_ownership(&self) -> OwnershipResult {
+        self.check_ownership_with(&AuditContext::new())
+    }
+
+    /// Like [`check_ownership`](Self::check_ownership), reading metadata through `context`
+    /// so a path already read by another rule of the run is not read again.
+    pub fn check_ownership_with(&self, context: &AuditContext) -> OwnershipResult {
         // Symlink handling: delegate to symlink audit module
-        if let Ok(meta) = fs::symlink_metadata(&self.path) {
-            if meta.file_type().is_symlink() {
-                let sym_rule = SymRule {
-                    path: self.path.clone(),
-                    target_link: None, // Optionally pass expected target
-                };
-                let sym_result = check_symlink(&sym_rule);
-                return OwnershipResult {
-                    path: sym_result.path.clone(),
-                    expected_uid: Some(self.expected_uid),
-                    expected_gid: Some(self.expected_gid),
-                    found_uid: None,
-                    found_gid: None,
-                    pass: sym_result.pass,
-                    severity: if sym_result.pass {
-                        Severity::None
-                    } else {
-                        Severity::Critical
-                    },
-                    error: sym_result.error,
-                };
-            }
+        if let Ok(meta) = context.symlink_metadata(&self.path)
+            && meta.file_type().is_symlink()
+        {
+            let sym_rule = SymRule {
+                path: self.path.clone(),
+                target_link: None, // Optionally pass expected target
+                severity_on_mismatch: self.symlink_severity.clone(),
+            };
+            let sym_result = check_symlink(&sym_rule);
+            return OwnershipResult {
+                path: sym_result.path.clone(),
+                expected_uid: Some(self.expected_uid),
+                expected_gid: Some(self.expected_gid),
+                found_uid: None,
+                found_gid: None,
+                pass: sym_result.pass,
+                severity: sym_result.severity,
+                rule_id: self.id.clone(),
+                acknowledged: None,
+                error: sym_result.error,
+            };
         }
         // Non-symlink: regular ownership check
         let meta_result = if self.follow_symlinks {
-            fs::metadata(&self.path)
+            context.metadata(&self.path)
         } else {
-            fs::symlink_metadata(&self.path)
+            context.symlink_metadata(&self.path)
         };
         match meta_result {
             Ok(meta) => {
@@ -258,6 +394,8 @@ impl OwnershipRule {
                     found_gid: Some(found_gid),
                     pass,
                     severity: self.owner_severity(found_uid, found_gid),
+                    rule_id: self.id.clone(),
+                    acknowledged: None,
                     error: None,
                 }
             }
@@ -269,6 +407,8 @@ impl OwnershipRule {
                 found_gid: None,
                 pass: false,
                 severity: Severity::Critical,
+                rule_id: self.id.clone(),
+                acknowledged: None,
                 error: Some(format!("Failed to read metadata: {}", e)),
             },
         }
@@ -0,0 +1,328 @@
+//! `/etc/passwd` consistency audit for HALO.
+//!
+//! Complements the `UserConfig` permission rules with checks on the account database itself.
+//!
+//! # Checks
+//! - `passwd-duplicate-uid0`: accounts other than `root` with UID 0
+//! - `passwd-system-login-shell`: system accounts (UID below 1000) with an interactive login shell
+//! - `passwd-missing-group`: primary GIDs in `/etc/passwd` with no entry in `/etc/group`
+//! - `passwd-orphaned-uid`: files under `/home` owned by UIDs with no `/etc/passwd` entry
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, PasswdAudit};
+//! let findings = PasswdAudit::default().run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{}: {}", f.check, f.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::Severity;
+use crate::audit::sysroot;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// First UID assigned to regular users on most distributions.
+const FIRST_USER_UID: u32 = 1000;
+
+/// Shells that deny interactive logins.
+const NOLOGIN_SHELLS: &[&str] = &[
+    "/usr/sbin/nologin",
+    "/sbin/nologin",
+    "/usr/bin/nologin",
+    "/bin/false",
+    "/usr/bin/false",
+];
+
+/// System accounts whose "shell" is a single command by convention.
+const COMMAND_ACCOUNTS: &[&str] = &["sync", "shutdown", "halt"];
+
+/// A single `/etc/passwd` entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswdEntry {
+    pub name: String,
+    pub uid: u32,
+    pub gid: u32,
+    pub home: PathBuf,
+    pub shell: String,
+}
+
+/// Account database files checked for consistency.
+pub struct PasswdAudit {
+    pub passwd: PathBuf,
+    pub group: PathBuf,
+    pub home: PathBuf,
+    /// How many directory levels below `home` are scanned for orphaned owners
+    pub home_depth: usize,
+}
+
+/// Provides default paths for the passwd consistency audit.
+impl Default for PasswdAudit {
+    fn default() -> Self {
+        Self {
+            passwd: "/etc/passwd".into(),
+            group: "/etc/group".into(),
+            home: "/home".into(),
+            home_depth: 2,
+        }
+    }
+}
+
+impl AuditFindings for PasswdAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let content = match sysroot::read_to_string(&self.passwd) {
+            Ok(content) => content,
+            Err(e) => {
+                return vec![Finding::fail(
+                    "passwd-unreadable",
+                    &self.passwd,
+                    Severity::Info,
+                    format!("Cannot read passwd file: {}", e),
+                )];
+            }
+        };
+        let entries = parse_passwd(&content);
+
+        let mut findings = check_uid0(&self.passwd, &entries);
+        findings.extend(check_login_shells(&self.passwd, &entries));
+        match sysroot::read_to_string(&self.group) {
+            Ok(groups) => findings.extend(check_missing_groups(
+                &self.passwd,
+                &entries,
+                &parse_group_ids(&groups),
+            )),
+            Err(e) => findings.push(Finding::fail(
+                "group-unreadable",
+                &self.group,
+                Severity::Info,
+                format!("Cannot read group file: {}", e),
+            )),
+        }
+        findings.extend(self.check_orphaned_owners(&entries));
+        findings
+    }
+}
+
+impl PasswdAudit {
+    /// Scans `home` for files owned by UIDs that have no passwd entry.
+    ///
+    /// Emits one finding per orphaned UID, with an example path and the number of files found.
+    pub fn check_orphaned_owners(&self, entries: &[PasswdEntry]) -> Vec<Finding> {
+        let known: HashSet<u32> = entries.iter().map(|e| e.uid).collect();
+        let mut orphans: BTreeMap<u32, (PathBuf, usize)> = BTreeMap::new();
+        let mut stack = vec![(self.home.clone(), 0)];
+        while let Some((dir, depth)) = stack.pop() {
+            let Ok(entries) = sysroot::read_dir(&dir) else {
+                continue;
+            };
+            for path in entries {
+                let Ok(meta) = sysroot::symlink_metadata(&path) else {
+                    continue;
+                };
+                if !known.contains(&meta.uid()) {
+                    orphans
+                        .entry(meta.uid())
+                        .and_modify(|(_, count)| *count += 1)
+                        .or_insert((path.clone(), 1));
+                }
+                if meta.is_dir() && depth + 1 < self.home_depth {
+                    stack.push((path, depth + 1));
+                }
+            }
+        }
+
+        if orphans.is_empty() {
+            return vec![Finding::pass(
+                "passwd-orphaned-uid",
+                &self.home,
+                "All files are owned by known accounts",
+            )];
+        }
+        orphans
+            .into_iter()
+            .map(|(uid, (example, count))| {
+                Finding::fail(
+                    "passwd-orphaned-uid",
+                    example,
+                    Severity::Medium,
+                    format!(
+                        "UID {} owns {} file(s) but has no passwd entry; a new account reusing it would inherit them",
+                        uid, count
+                    ),
+                )
+                .with_subject(format!("uid {}", uid))
+            })
+            .collect()
+    }
+}
+
+/// Parses `/etc/passwd` content, skipping malformed lines.
+pub fn parse_passwd(content: &str) -> Vec<PasswdEntry> {
+    content
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+        .filter_map(|line| {
+            // name:password:uid:gid:gecos:home:shell
+            let fields: Vec<&str> = line.split(':').collect();
+            if fields.len() < 7 {
+                return None;
+            }
+            Some(PasswdEntry {
+                name: fields[0].to_string(),
+                uid: fields[2].parse().ok()?,
+                gid: fields[3].parse().ok()?,
+                home: PathBuf::from(fields[5]),
+                shell: fields[6].to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses the GIDs defined in `/etc/group` content.
+pub fn parse_group_ids(content: &str) -> HashSet<u32> {
+    content
+        .lines()
+        .filter_map(|line| line.split(':').nth(2)?.parse().ok())
+        .collect()
+}
+
+/// Maps the GIDs in `/etc/group` content to group names; the first name wins.
+pub fn parse_group_names(content: &str) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    for line in content.lines() {
+        let mut fields = line.split(':');
+        if let (Some(name), Some(gid)) = (fields.next(), fields.nth(1).and_then(|g| g.parse().ok())) {
+            names.entry(gid).or_insert_with(|| name.to_string());
+        }
+    }
+    names
+}
+
+/// Flags every account other than `root` that has UID 0.
+pub fn check_uid0(path: &Path, entries: &[PasswdEntry]) -> Vec<Finding> {
+    let findings: Vec<Finding> = entries
+        .iter()
+        .filter(|e| e.uid == 0 && e.name != "root")
+        .map(|e| {
+            Finding::fail(
+                "passwd-duplicate-uid0",
+                path,
+                Severity::Critical,
+                "Account has UID 0 and full root privileges",
+            )
+            .with_subject(&e.name)
+        })
+        .collect();
+    if findings.is_empty() {
+        return vec![Finding::pass(
+            "passwd-duplicate-uid0",
+            path,
+            "Only root has UID 0",
+        )];
+    }
+    findings
+}
+
+/// Flags system accounts that have an interactive login shell.
+pub fn check_login_shells(path: &Path, entries: &[PasswdEntry]) -> Vec<Finding> {
+    let findings: Vec<Finding> = entries
+        .iter()
+        .filter(|e| e.uid != 0 && e.uid < FIRST_USER_UID)
+        .filter(|e| !COMMAND_ACCOUNTS.contains(&e.name.as_str()))
+        .filter(|e| !e.shell.is_empty() && !NOLOGIN_SHELLS.contains(&e.shell.as_str()))
+        .map(|e| {
+            Finding::fail(
+                "passwd-system-login-shell",
+                path,
+                Severity::Medium,
+                format!("System account has login shell {}", e.shell),
+            )
+            .with_subject(&e.name)
+        })
+        .collect();
+    if findings.is_empty() {
+        return vec![Finding::pass(
+            "passwd-system-login-shell",
+            path,
+            "System accounts cannot log in interactively",
+        )];
+    }
+    findings
+}
+
+/// Flags accounts whose primary GID is not defined in `/etc/group`.
+pub fn check_missing_groups(
+    path: &Path,
+    entries: &[PasswdEntry],
+    group_ids: &HashSet<u32>,
+) -> Vec<Finding> {
+    let findings: Vec<Finding> = entries
+        .iter()
+        .filter(|e| !group_ids.contains(&e.gid))
+        .map(|e| {
+            Finding::fail(
+                "passwd-missing-group",
+                path,
+                Severity::Medium,
+                format!("Primary GID {} does not exist in /etc/group", e.gid),
+            )
+            .with_subject(&e.name)
+        })
+        .collect();
+    if findings.is_empty() {
+        return vec![Finding::pass(
+            "passwd-missing-group",
+            path,
+            "All primary groups exist",
+        )];
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Status;
+
+    const PASSWD: &str = "root:x:0:0:root:/root:/bin/bash\n\
+                          toor:x:0:0::/root:/bin/sh\n\
+                          daemon:x:1:1::/usr/sbin:/usr/sbin/nologin\n\
+                          sync:x:4:65534::/bin:/bin/sync\n\
+                          www:x:33:33::/var/www:/bin/bash\n\
+                          alice:x:1000:1000::/home/alice:/bin/bash\n";
+
+    #[test]
+    fn test_parse_passwd() {
+        let entries = parse_passwd(PASSWD);
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries[5].home, PathBuf::from("/home/alice"));
+    }
+
+    #[test]
+    fn test_duplicate_uid0() {
+        let findings = check_uid0(Path::new("/etc/passwd"), &parse_passwd(PASSWD));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].subject.as_deref(), Some("toor"));
+        assert_eq!(findings[0].severity, Severity::Critical);
+    }
+
+    #[test]
+    fn test_system_login_shell() {
+        let findings = check_login_shells(Path::new("/etc/passwd"), &parse_passwd(PASSWD));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].subject.as_deref(), Some("www"));
+    }
+
+    #[test]
+    fn test_missing_groups() {
+        let content = "root:x:0:\ndaemon:x:1:\nwww-data:x:33:\nalice:x:1000:\n";
+        let groups = parse_group_ids(content);
+        assert_eq!(parse_group_names(content)[&33], "www-data");
+        let findings =
+            check_missing_groups(Path::new("/etc/passwd"), &parse_passwd(PASSWD), &groups);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].subject.as_deref(), Some("sync"));
+        assert_eq!(findings[0].status, Status::Fail);
+    }
+}
@@ -22,6 +22,10 @@
 //!     expected_mode: 0o644,
 //!     recursive: false,
 //!     importance: Importance::High,
+//!     id: None,
+//!     expected_dir_mode: None,
+//!     mounts: None,
+//!     symlink_severity: None,
 //! };
 //! let mut visited = std::collections::HashSet::new();
 //! let results = rule.check(&mut visited);
@@ -38,6 +42,11 @@
 //!     expected_mode: 0o640,
 //!     recursive: true,
 //!     importance: Importance::Medium,
+//!     id: None,
+//!     // Directories in the tree are checked against their own mode
+//!     expected_dir_mode: Some(0o755),
+//!     mounts: None,
+//!     symlink_severity: None,
 //! };
 //! let mut visited = std::collections::HashSet::new();
 //! let results = rule.check(&mut visited);
@@ -62,31 +71,38 @@
 //! assert_eq!(parse_mode("rw-r-----"), Ok(0o640));
 //! assert_eq!(parse_mode("u=rw,g=r,o="), Ok(0o640));
 //! ```
-use serde::{Deserialize, Serialize, Serializer};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
 use std::collections::HashSet;
-use std::fmt;
 use std::fs;
 use std::io;
 use std::os::unix::fs::MetadataExt;
-use std::path::PathBuf;
-use crate::render_output::{Renderable, DataList as RenderDataList, DataMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::ack::{Acknowledgeable, Acknowledgment, stable_id};
+use crate::audit::context::AuditContext;
+use crate::audit::footprint::Footprint;
+use crate::audit::mounts::MountPolicy;
+use crate::audit::permissions::severity_policy::SeverityPolicy;
+use crate::audit::scan::{CancelToken, ScanControl};
+use crate::audit::symlink::{SymRule, check_symlink};
+use crate::audit::sysroot;
+use crate::error::HaloError;
+use crate::render_output::{Renderable, DataList as RenderDataList, DataMap, Style, paint};
 use indexmap::IndexMap;
+use rayon::prelude::*;
+use tracing::{debug, info, trace};
 
-/// File permission bitmasks for audit severity checks.
-///
-/// - `WORLD_WRITE`: Others write bit (critical risk)
-/// - `GROUP_PERMS`: Group read/write/execute bits
-/// - `OTHER_PERMS`: Others read/write/execute bits
-///
-/// These constants are used to determine the severity of permission mismatches.
-const WORLD_WRITE: u32 = 0o002;
-const GROUP_PERMS: u32 = 0o070;
-const OTHER_PERMS: u32 = 0o007;
 
 /// Severity level of audit failure.
 ///
 /// Used to classify the risk of a permission mismatch when auditing file or directory permissions.
-#[derive(Debug, Clone, Serialize, PartialEq, Deserialize)]
+/// Severities are totally ordered by [`rank`](Self::rank): Critical > High > Medium > Low >
+/// Info > None. Severity thresholds, sorting, exit-code gating and the risk score all use
+/// this order, so `results.sort_by(|a, b| b.severity.cmp(&a.severity))` puts the worst first.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 pub enum Severity {
     /// No issue (exact match)
     None,
@@ -102,10 +118,66 @@ pub enum Severity {
     Low,
 }
 
+impl Ord for Severity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for Severity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Severity {
+    /// Position in the order of severities: 0 for `None` up to 5 for `Critical`.
+    pub fn rank(&self) -> u8 {
+        match self {
+            Severity::None => 0,
+            Severity::Info => 1,
+            Severity::Low => 2,
+            Severity::Medium => 3,
+            Severity::High => 4,
+            Severity::Critical => 5,
+        }
+    }
+
+    /// The most severe of `severities`; `None` if there are none.
+    pub fn max<'a>(severities: impl IntoIterator<Item = &'a Severity>) -> Severity {
+        severities.into_iter().max().cloned().unwrap_or(Severity::None)
+    }
+
+    /// Returns true if this severity is `min` or more severe.
+    pub fn at_least(&self, min: &Severity) -> bool {
+        self >= min
+    }
+
+    /// Pretty output color: Critical red, High yellow, Medium magenta, Low cyan, else dim.
+    pub fn style(&self) -> Style {
+        match self {
+            Severity::Critical => Style::Red,
+            Severity::High => Style::Yellow,
+            Severity::Medium => Style::Magenta,
+            Severity::Low => Style::Cyan,
+            Severity::Info | Severity::None => Style::Dim,
+        }
+    }
+
+    // Fixed-width, colored severity column of pretty output; "-" for `None`
+    pub(crate) fn column(&self) -> String {
+        let name = match self {
+            Severity::None => "-".to_string(),
+            other => format!("{:?}", other),
+        };
+        paint(&format!("{:<8}", name), self.style())
+    }
+}
+
 /// Status of a user-selected path for audit.
 ///
 /// Indicates whether the path is a valid file, directory, or not found.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PathStatus {
     /// Path is a valid file
     ValidFile,
@@ -120,7 +192,7 @@ pub enum PathStatus {
 /// Result status for a permission audit.
 ///
 /// Indicates whether the permissions passed, failed, or are stricter than expected.
-#[derive(Debug, Serialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Status {
     /// Permissions match expected
     Pass,
@@ -130,10 +202,32 @@ pub enum Status {
     Strict,
 }
 
+impl Status {
+    /// Pretty output color: Pass green, Fail red, Strict blue.
+    pub fn style(&self) -> Style {
+        match self {
+            Status::Pass => Style::Green,
+            Status::Fail => Style::Red,
+            Status::Strict => Style::Blue,
+        }
+    }
+
+    // Colored status symbol of pretty output
+    pub(crate) fn symbol(&self) -> String {
+        let symbol = match self {
+            Status::Pass => "✓",
+            Status::Fail => "✗",
+            Status::Strict => "!",
+        };
+        paint(symbol, self.style())
+    }
+}
+
 /// Importance level for an audited file or directory.
 ///
 /// Used to indicate the security relevance of a file or directory in an audit.
-#[derive(Debug, Serialize, PartialEq, clap::ValueEnum, Clone, Deserialize)]
+#[derive(Debug, Serialize, PartialEq, Eq, Hash, Clone, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 pub enum Importance {
     /// High importance (security-critical)
     High,
@@ -146,7 +240,7 @@ pub enum Importance {
 /// Result of a permission audit for a single file or directory.
 ///
 /// Contains the outcome of a permission check, including severity, status, path, expected and found modes, importance, and any error.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
 pub struct PermissionResults {
     /// Severity of the mismatch
     pub severity: Severity,
@@ -155,21 +249,110 @@ pub struct PermissionResults {
     /// Path audited
     pub path: PathBuf,
     /// Expected file mode (octal)
-    #[serde(serialize_with = "as_octal")]
+    #[serde(deserialize_with = "from_octal")]
     pub expected_mode: u32,
     /// Found file mode (octal)
-    #[serde(serialize_with = "as_octal")]
+    #[serde(deserialize_with = "from_octal")]
     pub found_mode: u32,
     /// Importance of the file
     pub importance: Importance,
+    /// Id of the rule that produced the result
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    /// Acknowledgment covering this failure in its current state
+    #[serde(default)]
+    pub acknowledged: Option<Acknowledgment>,
     /// Optional error if audit failed
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
     pub error: Option<AuditError>,
 }
 
+impl PermissionResults {
+    /// Informational failure for a rule whose path does not exist.
+    pub fn not_found(path: PathBuf, expected_mode: u32, importance: Importance, rule_id: Option<String>) -> Self {
+        let error = Some(AuditError::Other(HaloError::NotFound(path.clone()).to_string()));
+        PermissionResults {
+            severity: Severity::Info,
+            status: Status::Fail,
+            path,
+            expected_mode,
+            found_mode: 0o000,
+            importance,
+            rule_id,
+            acknowledged: None,
+            error,
+        }
+    }
+
+    /// Stable id of the result, derived from its path; used to acknowledge a failure.
+    pub fn id(&self) -> String {
+        stable_id(&["permissions", &self.path.to_string_lossy()])
+    }
+}
+
+impl Acknowledgeable for PermissionResults {
+    fn ack_id(&self) -> String {
+        self.id()
+    }
+
+    fn fingerprint(&self) -> String {
+        stable_id(&[
+            &format!("{:?}", self.status),
+            &format!("{:?}", self.severity),
+            &format!("{:o}", self.found_mode),
+            &format!("{:o}", self.expected_mode),
+        ])
+    }
+
+    fn is_failure(&self) -> bool {
+        self.status == Status::Fail
+    }
+
+    fn acknowledgment_mut(&mut self) -> &mut Option<Acknowledgment> {
+        &mut self.acknowledged
+    }
+}
+
+// Written by hand to include the computed id
+impl Serialize for PermissionResults {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Octal(u32);
+        impl Serialize for Octal {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                as_octal(&self.0, serializer)
+            }
+        }
+        let mut result = serializer.serialize_struct("PermissionResults", 10)?;
+        result.serialize_field("id", &self.id())?;
+        result.serialize_field("severity", &self.severity)?;
+        result.serialize_field("status", &self.status)?;
+        result.serialize_field("path", &self.path)?;
+        result.serialize_field("expected_mode", &Octal(self.expected_mode))?;
+        result.serialize_field("found_mode", &Octal(self.found_mode))?;
+        result.serialize_field("importance", &self.importance)?;
+        match &self.rule_id {
+            Some(id) => result.serialize_field("rule_id", id)?,
+            None => result.skip_field("rule_id")?,
+        }
+        match &self.acknowledged {
+            Some(ack) => result.serialize_field("acknowledged", ack)?,
+            None => result.skip_field("acknowledged")?,
+        }
+        match &self.error {
+            Some(error) => result.serialize_field("error", error)?,
+            None => result.skip_field("error")?,
+        }
+        result.end()
+    }
+}
+
 impl Renderable for PermissionResults {
     fn to_datalist(&self) -> RenderDataList {
         let mut map = IndexMap::new();
+        map.insert("id".to_string(), self.id());
+        if let Some(ref id) = self.rule_id {
+            map.insert("rule_id".to_string(), id.clone());
+        }
         map.insert("path".to_string(), self.path.display().to_string());
         map.insert("expected_mode".to_string(), format!("{:o}", self.expected_mode));
         map.insert("found_mode".to_string(), format!("{:o}", self.found_mode));
@@ -179,33 +362,72 @@ impl Renderable for PermissionResults {
         if let Some(ref err) = self.error {
             map.insert("error".to_string(), format!("{:?}", err));
         }
+        if let Some(ref ack) = self.acknowledged {
+            map.insert("acknowledged".to_string(), ack.comment.clone());
+        }
         vec![map]
     }
     
     fn pretty_print(&self) -> String {
-        let status_symbol = match self.status {
-            Status::Pass => "✓",
-            Status::Fail => "✗", 
-            Status::Strict => "!",
+        let status_symbol = match self.acknowledged {
+            Some(_) => paint("~", Style::Dim),
+            None => self.status.symbol(),
         };
-        
         let mut result = format!(
-            "{} {} (found: {:o}, expected: {:o}) - {:?}",
+            "{} {} {} (found: {:o}, expected: {:o}) - {:?}",
             status_symbol,
+            self.severity.column(),
             self.path.display(),
             self.found_mode,
             self.expected_mode,
             self.importance
         );
+        if let Some(ref id) = self.rule_id {
+            result.push_str(&format!(" [{}]", id));
+        }
         
         if let Some(ref err) = self.error {
             result.push_str(&format!(" [Error: {:?}]", err));
         }
+        if self.status == Status::Fail {
+            result.push_str(&format!(" ({})", self.id()));
+        }
+        if let Some(ref ack) = self.acknowledged {
+            result.push_str(&format!("\n      > acknowledged: {}", ack.comment));
+        }
         
         result
     }
 }
 
+/// Adds `result` unless a resumed checkpoint already holds it, recording it to the checkpoint
+/// and stream; passing results are only recorded if the control does not retain them.
+fn emit(results: &mut Vec<PermissionResults>, control: &mut ScanControl, result: PermissionResults) {
+    if control.admit(&result.path) {
+        control.record(&result);
+        let kept = control.retains(&result);
+        control.count(kept);
+        if kept {
+            results.push(result);
+        }
+    }
+}
+
+/// One step of a walk: a result for a path, or a directory to walk next with its metadata.
+enum Step {
+    Found(PermissionResults),
+    Descend(PathBuf, fs::Metadata),
+}
+
+/// A rule's walk with the severity policy and metadata source of the run.
+#[derive(Clone, Copy)]
+struct Walker<'a> {
+    rule: &'a PermissionRules,
+    policy: &'a SeverityPolicy,
+    context: &'a AuditContext,
+    cancel: Option<&'a CancelToken>,
+}
+
 /// Helper to serialize file modes as octal strings for JSON output.
 ///
 /// Used for pretty-printing file modes in audit results.
@@ -216,6 +438,15 @@ where
     serializer.serialize_str(&format!("{:o}", mode))
 }
 
+/// Helper to read file modes written by [`as_octal`].
+pub fn from_octal<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    u32::from_str_radix(&s, 8).map_err(serde::de::Error::custom)
+}
+
 /// Trait for audit rule configuration structs.
 ///
 /// Implement this trait to provide audit rules for a group of files or directories.
@@ -233,6 +464,209 @@ pub trait AuditPermissions {
         }
         results
     }
+
+    /// Runs all audit rules with the given resource profile.
+    ///
+    /// Directories are walked with the profile's [`jobs`](Footprint::jobs). With
+    /// [`Footprint::Low`] passing results are dropped after each rule, so memory use is
+    /// bounded by the number of failures rather than the number of files checked.
+    fn run_audit_perms_with(&self, footprint: &Footprint) -> Vec<PermissionResults> {
+        self.run_audit_perms_controlled(footprint, &mut ScanControl::default().with_jobs(footprint.jobs()))
+    }
+
+    /// Runs all audit rules with the given resource profile, throttled and checkpointed by
+    /// `control`.
+    fn run_audit_perms_controlled(
+        &self,
+        footprint: &Footprint,
+        control: &mut ScanControl,
+    ) -> Vec<PermissionResults> {
+        let mut results = Vec::new();
+        let mut visited = HashSet::new();
+        for rule in self.rules() {
+            let mut rule_results = rule.check_with(&mut visited, control);
+            if !footprint.keep_passing() {
+                rule_results.retain(|r| r.status != Status::Pass);
+            }
+            results.extend(rule_results);
+        }
+        results
+    }
+}
+
+/// Lazy walk of a rule's path, see [`PermissionRules::check_iter`].
+pub struct PermissionWalk {
+    rule: PermissionRules,
+    started: bool,
+    // Steps still to take, the next one last
+    pending: Vec<Step>,
+    // Listing of the directory being read, reused for every directory
+    entries: Vec<PathBuf>,
+    visited: HashSet<(u64, u64)>,
+    policy: SeverityPolicy,
+    context: Arc<AuditContext>,
+}
+
+impl PermissionWalk {
+    /// Map permission mismatches to severities with `policy`.
+    pub fn with_severity_policy(mut self, policy: SeverityPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Read metadata through `context`, e.g. to share its cache with other rules.
+    pub fn with_context(mut self, context: Arc<AuditContext>) -> Self {
+        self.context = context;
+        self
+    }
+}
+
+impl Iterator for PermissionWalk {
+    type Item = PermissionResults;
+
+    fn next(&mut self) -> Option<PermissionResults> {
+        let PermissionWalk { rule, started, pending, entries, visited, policy, context } = self;
+        let walker = Walker { rule, policy, context, cancel: None };
+        if !*started {
+            *started = true;
+            walker.start(pending);
+        }
+        walker.next(pending, entries, visited)
+    }
+}
+
+impl Walker<'_> {
+    // Takes the steps in `pending` until one yields a result, in the order of the sequential
+    // walk
+    fn next(
+        &self,
+        pending: &mut Vec<Step>,
+        entries: &mut Vec<PathBuf>,
+        visited: &mut HashSet<(u64, u64)>,
+    ) -> Option<PermissionResults> {
+        while let Some(step) = pending.pop() {
+            match step {
+                Step::Found(result) => return Some(result),
+                Step::Descend(dir, meta) => {
+                    let first = pending.len();
+                    self.descend(dir, &meta, &mut |dev, ino| visited.insert((dev, ino)), entries, pending);
+                    // The first entry is taken next
+                    pending[first..].reverse();
+                }
+            }
+        }
+        None
+    }
+
+    // Checks the rule's path itself: pushes its result onto `out`, or the directory if it is
+    // walked
+    fn start(&self, out: &mut Vec<Step>) {
+        let rule = self.rule;
+        if let Ok(meta) = self.context.symlink_metadata(&rule.path)
+            && meta.file_type().is_symlink()
+        {
+            out.push(Step::Found(rule.symlink_result(&rule.path)));
+            return;
+        }
+        match self.context.metadata(&rule.path) {
+            Ok(meta) if meta.is_file() => {
+                out.push(Step::Found(rule.mode_result(rule.path.clone(), &meta, rule.expected_mode, self.policy)));
+            }
+            Ok(meta) if meta.is_dir() => {
+                if rule.recursive || rule.expected_dir_mode.is_some() {
+                    out.push(Step::Descend(rule.path.clone(), meta));
+                }
+            }
+            _ => debug!("Skipping {}: not a file or directory", rule.path.display()),
+        }
+    }
+
+    // Walks the directory `dir`: records it with `first_visit`, which returns false if it was
+    // already walked, checks its own mode and, if the rule is recursive, pushes the results of
+    // its files and symlinks and the subdirectories to walk next onto `out`, in sorted order.
+    // Each entry is read with one `lstat`; `entries` holds the listing
+    fn descend(
+        &self,
+        dir: PathBuf,
+        meta: &fs::Metadata,
+        first_visit: &mut impl FnMut(u64, u64) -> bool,
+        entries: &mut Vec<PathBuf>,
+        out: &mut Vec<Step>,
+    ) {
+        let rule = self.rule;
+        if !first_visit(meta.dev(), meta.ino()) {
+            debug!("Skipping {}: directory already visited", dir.display());
+            return;
+        }
+        if let Some(dir_mode) = rule.expected_dir_mode {
+            out.push(Step::Found(rule.mode_result(dir.clone(), meta, dir_mode, self.policy)));
+        }
+        if !rule.recursive {
+            return;
+        }
+
+        entries.clear();
+        if let Err(e) = sysroot::read_dir_into(&dir, entries) {
+            info!("Cannot read directory {}: {}", dir.display(), e);
+            out.push(Step::Found(rule.error_result(dir, format!("Failed to read directory: {}", e))));
+            return;
+        }
+        entries.sort();
+        trace!("Walking {} ({} entries)", dir.display(), entries.len());
+        for path in entries.drain(..) {
+            let entry = match self.context.symlink_metadata(&path) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    debug!("Skipping {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            // Symlinks in directory contents are reported without following them
+            if entry.file_type().is_symlink() {
+                out.push(Step::Found(rule.symlink_result(&path)));
+            } else if entry.is_file() {
+                out.push(Step::Found(rule.mode_result(path, &entry, rule.expected_mode, self.policy)));
+            } else if entry.is_dir() {
+                if entry.dev() != meta.dev() && rule.skips_mount(&path, self.context) {
+                    debug!("Skipping {}: mount point excluded by the mount policy", path.display());
+                    continue;
+                }
+                out.push(Step::Descend(path, entry));
+            } else {
+                debug!("Skipping {}: not a file or directory", path.display());
+            }
+        }
+    }
+
+    // Like the sequential walk of `dir`, with its subdirectories walked on the current rayon pool
+    fn walk_parallel(
+        &self,
+        dir: PathBuf,
+        meta: &fs::Metadata,
+        visited: &Mutex<HashSet<(u64, u64)>>,
+    ) -> Vec<PermissionResults> {
+        if self.cancel.is_some_and(CancelToken::is_cancelled) {
+            return Vec::new();
+        }
+        // Runs on a worker thread, which reads the root of the run only once entered
+        let _root = sysroot::enter(self.context.root().map(Path::to_path_buf));
+        let mut first_visit = |dev, ino| visited.lock().unwrap_or_else(|e| e.into_inner()).insert((dev, ino));
+        let mut steps = Vec::new();
+        self.descend(dir, meta, &mut first_visit, &mut Vec::new(), &mut steps);
+        steps
+            .into_par_iter()
+            .fold(Vec::new, |mut results, step| {
+                match step {
+                    Step::Found(result) => results.push(result),
+                    Step::Descend(dir, meta) => results.extend(self.walk_parallel(dir, &meta, visited)),
+                }
+                results
+            })
+            .reduce(Vec::new, |mut a, mut b| {
+                a.append(&mut b);
+                a
+            })
+    }
 }
 
 /// Audit rule for a single file or directory path.
@@ -248,6 +682,21 @@ pub struct PermissionRules {
     pub recursive: bool,
     /// Importance of the file or directory
     pub importance: Importance,
+    /// Stable rule id (e.g. `HALO-USER-0002`) carried by every result of the rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Expected mode of directories (the path itself and, if recursive, every directory
+    /// below it); directories are not checked if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_dir_mode: Option<u32>,
+    /// Which mounts below the path a recursive walk enters; the run's default, see
+    /// [`ScanControl::with_mount_policy`], if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mounts: Option<MountPolicy>,
+    /// Severity of symlinks found by the rule that fail their check, overriding the
+    /// per-issue default, see [`SymRule::severity_on_mismatch`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symlink_severity: Option<Severity>,
 }
 
 /* Needs more robust error handling */
@@ -264,19 +713,23 @@ impl PermissionRules {
     /// # Returns
     /// Tuple of `PermissionRules` and `PathStatus`.
     pub fn new(path: PathBuf, expected_mode: u32, importance: Importance) -> (Self, PathStatus) {
-        if !path.exists() {
+        if !sysroot::exists(&path) {
             return (
                 PermissionRules {
                     path,
                     expected_mode,
                     importance,
                     recursive: false,
+                    id: None,
+                    expected_dir_mode: None,
+                    mounts: None,
+                    symlink_severity: None,
                 },
                 PathStatus::NotFound,
             );
         }
 
-        match fs::metadata(&path) {
+        match sysroot::metadata(&path) {
             Ok(meta) => {
                 if meta.is_file() {
                     (
@@ -285,6 +738,10 @@ impl PermissionRules {
                             expected_mode,
                             importance,
                             recursive: false,
+                            id: None,
+                            expected_dir_mode: None,
+                            mounts: None,
+                            symlink_severity: None,
                         },
                         PathStatus::ValidFile,
                     )
@@ -295,6 +752,10 @@ impl PermissionRules {
                             expected_mode,
                             importance,
                             recursive: true,
+                            id: None,
+                            expected_dir_mode: None,
+                            mounts: None,
+                            symlink_severity: None,
                         },
                         PathStatus::ValidDirectory,
                     )
@@ -305,6 +766,10 @@ impl PermissionRules {
                             expected_mode,
                             importance,
                             recursive: false,
+                            id: None,
+                            expected_dir_mode: None,
+                            mounts: None,
+                            symlink_severity: None,
                         },
                         PathStatus::NotFound, // fallback for weird cases
                     )
@@ -318,6 +783,10 @@ impl PermissionRules {
                             expected_mode,
                             importance,
                             recursive: false,
+                            id: None,
+                            expected_dir_mode: None,
+                            mounts: None,
+                            symlink_severity: None,
                         },
                         PathStatus::PermissionDenied,
                     )
@@ -328,6 +797,10 @@ impl PermissionRules {
                             expected_mode,
                             importance,
                             recursive: false,
+                            id: None,
+                            expected_dir_mode: None,
+                            mounts: None,
+                            symlink_severity: None,
                         },
                         PathStatus::NotFound,
                     )
@@ -336,9 +809,28 @@ impl PermissionRules {
         }
     }
 
+    /// Set the rule id reported with every result of this rule.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Check directories against `mode`, and files against `expected_mode`.
+    pub fn with_dir_mode(mut self, mode: u32) -> Self {
+        self.expected_dir_mode = Some(mode);
+        self
+    }
+
+    /// Enter the mounts below the path according to `policy`.
+    pub fn with_mount_policy(mut self, policy: MountPolicy) -> Self {
+        self.mounts = Some(policy);
+        self
+    }
+
     /// Determine severity based on mode comparison.
     ///
-    /// Returns a `Severity` value based on the difference between found and expected mode.
+    /// Returns a `Severity` value based on the difference between found and expected mode,
+    /// using the built-in [`SeverityPolicy`].
     ///
     /// # Arguments
     /// * `mode_found` - The actual file mode found
@@ -346,30 +838,13 @@ impl PermissionRules {
     /// # Returns
     /// Severity of the mismatch
     pub fn determine_severity(&self, mode_found: u32) -> Severity {
-        // World-writable is always critical
-        if (mode_found & WORLD_WRITE) != 0 {
-            return Severity::Critical;
-        }
-
-        // Exact match is 'None' severity
-        if mode_found == self.expected_mode {
-            return Severity::None;
-        }
-
-        // More permissive than expected (group/other bits)
-        if (mode_found & GROUP_PERMS) > (self.expected_mode & GROUP_PERMS)
-            || (mode_found & OTHER_PERMS) > (self.expected_mode & OTHER_PERMS)
-        {
-            return Severity::High;
-        }
-
-        // Stricter than expected (fewer bits set)
-        if (mode_found & 0o777) < (self.expected_mode & 0o777) {
-            return Severity::Info;
-        }
+        self.determine_severity_with(mode_found, &SeverityPolicy::default())
+    }
 
-        // Fallback for other mismatches
-        Severity::Low
+    /// Like [`determine_severity`](Self::determine_severity), with the bit classes mapped to
+    /// severities by `policy` instead of the built-in mapping.
+    pub fn determine_severity_with(&self, mode_found: u32, policy: &SeverityPolicy) -> Severity {
+        policy.severity(self.expected_mode, mode_found, &self.importance)
     }
 
     /// Check the permissions of the file or directory against the expected mode.
@@ -382,160 +857,183 @@ impl PermissionRules {
     /// # Returns
     /// Vector of `PermissionResults` for the path and its children (if recursive)
     pub fn check(&self, visited: &mut HashSet<(u64, u64)>) -> Vec<PermissionResults> {
-        let mut results = Vec::new();
+        self.check_with(visited, &mut ScanControl::default())
+    }
 
-        // Symlink handling
-        if let Ok(meta) = fs::symlink_metadata(&self.path) {
-            if meta.file_type().is_symlink() {
-                use crate::audit::symlink::{SymRule, check_symlink};
-                let sym_rule = SymRule {
-                    path: self.path.clone(),
-                    target_link: None, // You may want to pass a specific expected target
-                };
-                let sym_result = check_symlink(&sym_rule);
-                // Map SymResult to PermissionResults for compatibility
-                results.push(PermissionResults {
-                    path: sym_result.path.clone(),
-                    status: if sym_result.pass {
-                        Status::Pass
-                    } else {
-                        Status::Strict
-                    },
-                    expected_mode: self.expected_mode,
-                    found_mode: 0,
-                    severity: if sym_result.pass {
-                        Severity::None
-                    } else {
-                        Severity::Info
-                    },
-                    importance: self.importance.clone(),
-                    error: sym_result.error.map(AuditError::Other),
-                });
-                return results;
-            }
+    /// Like [`check`](Self::check), with throttling, checkpointing and limits from `control`.
+    ///
+    /// Directory entries are visited in sorted order so an interrupted walk can be resumed.
+    /// A walk that reaches a limit returns the results found so far.
+    pub fn check_with(
+        &self,
+        visited: &mut HashSet<(u64, u64)>,
+        control: &mut ScanControl,
+    ) -> Vec<PermissionResults> {
+        if control.is_cancelled() {
+            return Vec::new();
         }
-
-        if self.path.is_file() {
-            match fs::metadata(&self.path) {
-                Ok(meta) => {
-                    let mode = meta.mode() & 0o777;
-                    let status = if mode == self.expected_mode {
-                        Status::Pass
-                    } else if mode < self.expected_mode {
-                        Status::Strict
-                    } else {
-                        Status::Fail
-                    };
-                    let final_severity = self.determine_severity(mode);
-
-                    results.push(PermissionResults {
-                        path: self.path.clone(),
-                        status,
-                        expected_mode: self.expected_mode,
-                        found_mode: mode,
-                        severity: final_severity,
-                        importance: self.importance.clone(),
-                        error: None,
-                    });
-                }
-                Err(e) => {
-                    results.push(PermissionResults {
-                        path: self.path.clone(),
-                        status: Status::Fail,
-                        expected_mode: self.expected_mode,
-                        found_mode: 0,
-                        severity: Severity::Critical,
-                        importance: self.importance.clone(),
-                        error: Some(AuditError::Other(format!("Failed to read metadata: {}", e))),
-                    });
+        control.begin_rule(&self.path);
+        let rule = PermissionRules {
+            mounts: self.mounts.or(Some(control.mount_policy())),
+            ..self.clone()
+        };
+        let Some(pool) = control.pool() else {
+            let mut walk = rule
+                .check_iter()
+                .with_severity_policy(control.severity_policy().clone())
+                .with_context(control.context().clone());
+            walk.visited = std::mem::take(visited);
+            let mut results = Vec::new();
+            for result in walk.by_ref() {
+                emit(&mut results, control, result);
+                if control.truncate(&rule) || control.is_cancelled() {
+                    break;
                 }
             }
-        } else if self.path.is_dir() && self.recursive {
-            match fs::metadata(&self.path) {
-                Ok(meta) => {
-                    let dev = meta.dev();
-                    let ino = meta.ino();
-                    if !visited.insert((dev, ino)) {
-                        return results;
-                    }
-                }
-                Err(e) => {
-                    results.push(PermissionResults {
-                        path: self.path.clone(),
-                        status: Status::Fail,
-                        expected_mode: self.expected_mode,
-                        found_mode: 0,
-                        severity: Severity::Critical,
-                        importance: self.importance.clone(),
-                        error: Some(AuditError::Other(format!(
-                            "Failed to read directory metadata: {}",
-                            e
-                        ))),
-                    });
-                    return results;
-                }
+            *visited = walk.visited;
+            return results;
+        };
+        let shared = Mutex::new(std::mem::take(visited));
+        let walker = Walker {
+            rule: &rule,
+            policy: control.severity_policy(),
+            context: control.context(),
+            cancel: Some(control.cancel_token()),
+        };
+        let mut start = Vec::new();
+        walker.start(&mut start);
+        let mut found = Vec::new();
+        for step in start {
+            match step {
+                Step::Found(result) => found.push(result),
+                Step::Descend(dir, meta) => found.extend(pool.install(|| walker.walk_parallel(dir, &meta, &shared))),
             }
+        }
+        *visited = shared.into_inner().unwrap_or_else(|e| e.into_inner());
+        // Sorting by path restores the order of the sequential walk
+        found.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut results = Vec::with_capacity(found.len());
+        for result in found {
+            emit(&mut results, control, result);
+        }
+        results
+    }
 
-            match fs::read_dir(&self.path) {
-                Ok(entries) => {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        // Symlink handling: skip symlinks in directory contents
-                        if let Ok(meta) = fs::symlink_metadata(&path) {
-                            if meta.file_type().is_symlink() {
-                                use crate::audit::symlink::{SymRule, check_symlink};
-                                let sym_rule = SymRule {
-                                    path: path.clone(),
-                                    target_link: None,
-                                };
-                                let sym_result = check_symlink(&sym_rule);
-                                results.push(PermissionResults {
-                                    path: sym_result.path.clone(),
-                                    status: if sym_result.pass {
-                                        Status::Pass
-                                    } else {
-                                        Status::Strict
-                                    },
-                                    expected_mode: self.expected_mode,
-                                    found_mode: 0,
-                                    severity: if sym_result.pass {
-                                        Severity::None
-                                    } else {
-                                        Severity::Info
-                                    },
-                                    importance: self.importance.clone(),
-                                    error: sym_result.error.map(AuditError::Other),
-                                });
-                                continue;
-                            }
-                        }
-                        let sub_rule = PermissionRules {
-                            path,
-                            expected_mode: self.expected_mode,
-                            importance: self.importance.clone(),
-                            recursive: true,
-                        };
-                        results.extend(sub_rule.check(visited));
-                    }
-                }
-                Err(e) => {
-                    results.push(PermissionResults {
-                        path: self.path.clone(),
-                        status: Status::Fail,
-                        expected_mode: self.expected_mode,
-                        found_mode: 0,
-                        severity: Severity::Critical,
-                        importance: self.importance.clone(),
-                        error: Some(AuditError::Other(format!(
-                            "Failed to read directory: {}",
-                            e
-                        ))),
-                    });
-                }
-            }
+    /// Like [`check`](Self::check), reading metadata through `context` on the current thread,
+    /// so paths another rule of the run has read are not read again.
+    pub fn check_in(&self, context: &AuditContext) -> Vec<PermissionResults> {
+        let policy = SeverityPolicy::default();
+        let walker = Walker { rule: self, policy: &policy, context, cancel: None };
+        let (mut pending, mut entries, mut visited) = (Vec::new(), Vec::new(), HashSet::new());
+        walker.start(&mut pending);
+        std::iter::from_fn(|| walker.next(&mut pending, &mut entries, &mut visited)).collect()
+    }
+
+    /// Like [`check`](Self::check), yielding results one at a time as the walk finds them.
+    ///
+    /// Nothing is read until the first call to `next`, and only the entries of the
+    /// directories on the current path are held, so million-file trees are walked in bounded
+    /// memory. The order is the same as `check`'s; the walk uses one thread.
+    ///
+    /// ```rust
+    /// use alhalo::{Importance, PermissionRules};
+    /// let (rule, _) = PermissionRules::new("/etc".into(), 0o644, Importance::Low);
+    /// let worst = rule.check_iter().filter(|r| r.found_mode & 0o002 != 0).take(10);
+    /// for result in worst {
+    ///     println!("{} is world-writable", result.path.display());
+    /// }
+    /// ```
+    pub fn check_iter(&self) -> PermissionWalk {
+        PermissionWalk {
+            rule: self.clone(),
+            started: false,
+            pending: Vec::new(),
+            entries: Vec::new(),
+            visited: HashSet::new(),
+            policy: SeverityPolicy::default(),
+            context: Arc::new(AuditContext::new()),
         }
+    }
 
-        results
+    /// Whether the walk stays out of the directory `path`, a mount point below the rule's path.
+    fn skips_mount(&self, path: &Path, context: &AuditContext) -> bool {
+        match self.mounts.unwrap_or_default() {
+            MountPolicy::All => false,
+            MountPolicy::OneFileSystem => true,
+            policy => policy.skips(context.mount_table().fs_type(&sysroot::host_path_of(path))),
+        }
+    }
+
+    /// A critical failure for `path`, which could not be checked.
+    fn error_result(&self, path: PathBuf, error: String) -> PermissionResults {
+        PermissionResults {
+            path,
+            status: Status::Fail,
+            expected_mode: self.expected_mode,
+            found_mode: 0,
+            severity: Severity::Critical,
+            importance: self.importance.clone(),
+            rule_id: self.id.clone(),
+            acknowledged: None,
+            error: Some(AuditError::Other(error)),
+        }
+    }
+
+    /// Compare the mode of `path` in `meta` with `expected`.
+    fn mode_result(
+        &self,
+        path: PathBuf,
+        meta: &fs::Metadata,
+        expected: u32,
+        policy: &SeverityPolicy,
+    ) -> PermissionResults {
+        let mode = meta.mode() & 0o777;
+        let status = if mode == expected {
+            Status::Pass
+        } else if mode < expected {
+            Status::Strict
+        } else {
+            Status::Fail
+        };
+        PermissionResults {
+            path,
+            status,
+            expected_mode: expected,
+            found_mode: mode,
+            severity: policy.severity(expected, mode, &self.importance),
+            importance: self.importance.clone(),
+            rule_id: self.id.clone(),
+            acknowledged: None,
+            error: None,
+        }
+    }
+
+    /// Audit a symlink encountered by this rule and map the outcome to `PermissionResults`.
+    ///
+    /// The severity comes from the symlink issue found (broken, unexpected target, not a symlink),
+    /// or from `symlink_severity` if set.
+    fn symlink_result(&self, path: &Path) -> PermissionResults {
+        let sym_rule = SymRule {
+            path: path.to_path_buf(),
+            target_link: None,
+            severity_on_mismatch: self.symlink_severity.clone(),
+        };
+        let sym_result = check_symlink(&sym_rule);
+        PermissionResults {
+            path: sym_result.path,
+            status: if sym_result.pass {
+                Status::Pass
+            } else {
+                Status::Strict
+            },
+            expected_mode: self.expected_mode,
+            found_mode: 0,
+            severity: sym_result.severity,
+            importance: self.importance.clone(),
+            rule_id: self.id.clone(),
+            acknowledged: None,
+            error: sym_result.error.map(AuditError::Other),
+        }
     }
 
     /// Run a custom audit for a user-specified path, expected mode, and importance.
@@ -565,18 +1063,7 @@ impl PermissionRules {
                 results.extend(audit_rule.check(&mut visited));
             }
             PathStatus::NotFound => {
-                results.push(PermissionResults {
-                    severity: Severity::Info,
-                    expected_mode,
-                    found_mode: 0o000,
-                    path,
-                    status: Status::Fail,
-                    importance: Importance::Low,
-                    error: Some(AuditError::Other(format!(
-                        "Path not found: {}",
-                        audit_rule.path.display()
-                    ))),
-                });
+                results.push(PermissionResults::not_found(path, expected_mode, Importance::Low, None));
             }
             PathStatus::PermissionDenied => {
                 results.push(PermissionResults {
@@ -586,10 +1073,9 @@ impl PermissionRules {
                     path,
                     status: Status::Fail,
                     importance: Importance::High,
-                    error: Some(AuditError::Other(format!(
-                        "Permission denied: {}",
-                        audit_rule.path.display()
-                    ))),
+                    rule_id: None,
+                    acknowledged: None,
+                    error: Some(AuditError::Other(HaloError::PermissionDenied(audit_rule.path).to_string())),
                 });
             }
         }
@@ -639,7 +1125,7 @@ pub fn parse_mode(input: &str) -> Result<u32, AuditError> {
         if part.is_empty() {
             continue;
         }
-        let (who, rest) = match part.find(|c: char| c == '=' || c == '+' || c == '-') {
+        let (who, rest) = match part.find(['=', '+', '-']) {
             Some(idx) => part.split_at(idx),
             None => return Err(AuditError::InvalidShortSymbolicFormat),
         };
@@ -712,43 +1198,34 @@ pub fn perm_to_datalist(results: &[PermissionResults]) -> RenderDataList {
 
 /// Error type for permission audit failures and parsing errors.
 ///
-/// Used to represent errors encountered during permission parsing or audit checks.
-#[derive(Debug, PartialEq, Serialize)]
+/// Used to represent errors encountered during permission parsing or audit checks. Results
+/// carry it as report data; functions that load rules fail with a
+/// [`HaloError`], e.g. [`HaloError::InvalidMode`] wrapping this error.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, thiserror::Error)]
 pub enum AuditError {
     /// Invalid octal mode string
+    #[error("Invalid octal mode")]
     InvalidOctalMode,
     /// Invalid symbolic mode string
+    #[error("Invalid symbolic mode")]
     InvalidSymbolicMode,
     /// Invalid short symbolic mode format
+    #[error("Invalid short symbolic mode format")]
     InvalidShortSymbolicFormat,
     /// Invalid permission character
+    #[error("Invalid permission char: {0}")]
     InvalidPermissionChar(char),
     /// Invalid class (user/group/other)
+    #[error("Invalid class: {0}")]
     InvalidClass(char),
     /// Invalid operator (=, +, -)
+    #[error("Invalid operator: {0}")]
     InvalidOperator(char),
     /// Other error with message
+    #[error("{0}")]
     Other(String),
 }
 
-impl fmt::Display for AuditError {
-    /// Formats the error for display purposes.
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            AuditError::InvalidOctalMode => write!(f, "Invalid octal mode"),
-            AuditError::InvalidSymbolicMode => write!(f, "Invalid symbolic mode"),
-            AuditError::InvalidShortSymbolicFormat => {
-                write!(f, "Invalid short symbolic mode format")
-            }
-            AuditError::InvalidPermissionChar(c) => write!(f, "Invalid permission char: {}", c),
-            AuditError::InvalidClass(c) => write!(f, "Invalid class: {}", c),
-            AuditError::InvalidOperator(c) => write!(f, "Invalid operator: {}", c),
-            AuditError::Other(msg) => write!(f, "{}", msg),
-        }
-    }
-}
-impl std::error::Error for AuditError {}
-
 /* -------- Unit tests for permission parsing ---------- */
 /// Unit tests for permission parsing and severity logic.
 #[cfg(test)]
@@ -798,6 +1275,10 @@ mod tests {
             expected_mode: 0o640,
             recursive: false,
             importance: Importance::Medium,
+            id: None,
+            expected_dir_mode: None,
+            mounts: None,
+            symlink_severity: None,
         };
         // Others have read, which is more permissive than expected
         assert_eq!(rule.determine_severity(0o644), Severity::High);
@@ -810,6 +1291,10 @@ mod tests {
             expected_mode: 0o640,
             recursive: false,
             importance: Importance::Medium,
+            id: None,
+            expected_dir_mode: None,
+            mounts: None,
+            symlink_severity: None,
         };
         // Not stricter, not more permissive, not world-writable, not exact match
         assert_eq!(rule.determine_severity(0o641), Severity::High);
@@ -824,6 +1309,10 @@ mod tests {
             expected_mode: 0o640,
             recursive: false,
             importance: Importance::Medium,
+            id: None,
+            expected_dir_mode: None,
+            mounts: None,
+            symlink_severity: None,
         };
         assert_eq!(rule.determine_severity(0o640), Severity::None);
     }
@@ -835,6 +1324,10 @@ mod tests {
             expected_mode: 0o640,
             recursive: false,
             importance: Importance::Medium,
+            id: None,
+            expected_dir_mode: None,
+            mounts: None,
+            symlink_severity: None,
         };
         assert_eq!(rule.determine_severity(0o666), Severity::Critical);
     }
@@ -846,6 +1339,10 @@ mod tests {
             expected_mode: 0o640,
             recursive: false,
             importance: Importance::Medium,
+            id: None,
+            expected_dir_mode: None,
+            mounts: None,
+            symlink_severity: None,
         };
         // Group has write, which is more permissive than expected
         assert_eq!(rule.determine_severity(0o660), Severity::High);
@@ -858,8 +1355,88 @@ mod tests {
             expected_mode: 0o644,
             recursive: false,
             importance: Importance::Medium,
+            id: None,
+            expected_dir_mode: None,
+            mounts: None,
+            symlink_severity: None,
         };
         // Only owner can read/write
         assert_eq!(rule.determine_severity(0o600), Severity::Info);
     }
+
+    #[test]
+    fn test_severity_order_and_result_equality() {
+        let mut severities = vec![Severity::Low, Severity::None, Severity::Critical, Severity::Info, Severity::High];
+        severities.sort();
+        assert_eq!(severities, [Severity::None, Severity::Info, Severity::Low, Severity::High, Severity::Critical]);
+        assert!(Severity::Medium > Severity::Low);
+        assert_eq!(Severity::Critical.rank(), 5);
+        assert_eq!(Severity::max(&[Severity::Info, Severity::High, Severity::Low]), Severity::High);
+        assert_eq!(Severity::max(&[]), Severity::None);
+        assert!(Severity::Info.at_least(&Severity::Info));
+        assert!(!Severity::None.at_least(&Severity::Info));
+
+        let result = PermissionResults::not_found(PathBuf::from("/etc/shadow"), 0o640, Importance::High, None);
+        let mut results = HashSet::new();
+        results.insert(result.clone());
+        results.insert(result.clone());
+        assert_eq!(results.len(), 1);
+        let mut other = result.clone();
+        other.found_mode = 0o644;
+        assert_ne!(other, result);
+    }
+
+    #[test]
+    fn test_expected_dir_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join("file"), "x").unwrap();
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::set_permissions(&sub, fs::Permissions::from_mode(0o775)).unwrap();
+        fs::set_permissions(sub.join("file"), fs::Permissions::from_mode(0o644)).unwrap();
+
+        let (rule, _) = PermissionRules::new(dir.path().to_path_buf(), 0o644, Importance::High);
+        assert!(rule.check(&mut HashSet::new()).iter().all(|r| r.path.is_file()));
+
+        let results = rule.with_dir_mode(0o755).check(&mut HashSet::new());
+        let status: Vec<_> = results.iter().map(|r| (r.path.clone(), &r.status, r.expected_mode)).collect();
+        assert_eq!(
+            status,
+            vec![
+                (dir.path().to_path_buf(), &Status::Pass, 0o755),
+                (sub.clone(), &Status::Fail, 0o755),
+                (sub.join("file"), &Status::Pass, 0o644),
+            ]
+        );
+        assert_eq!(results[1].severity, Severity::High);
+    }
+
+    #[test]
+    fn test_walk_order_and_entry_types() {
+        let dir = tempfile::tempdir().unwrap();
+        for path in ["a", "b/c", "b/d/e", "f"] {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        std::os::unix::fs::symlink("a", dir.path().join("b/link")).unwrap();
+        let _socket = std::os::unix::net::UnixListener::bind(dir.path().join("sock")).unwrap();
+
+        let (rule, _) = PermissionRules::new(dir.path().to_path_buf(), 0o644, Importance::Low);
+        let paths: Vec<_> = rule
+            .check(&mut HashSet::new())
+            .into_iter()
+            .map(|r| r.path.strip_prefix(dir.path()).unwrap().to_path_buf())
+            .collect();
+        // Depth first in sorted order; symlinks are reported, sockets skipped
+        let expected: Vec<PathBuf> = ["a", "b/c", "b/d/e", "b/link", "f"].iter().map(PathBuf::from).collect();
+        assert_eq!(paths, expected);
+
+        let (file, _) = PermissionRules::new(dir.path().join("a"), 0o600, Importance::Low);
+        let results = file.check(&mut HashSet::new());
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].status, Status::Fail);
+    }
 }
@@ -9,7 +9,8 @@
 
 use crate::impl_audit;
 use crate::{AuditPermissions, Importance, PermissionRules};
-use std::path::PathBuf;
+use crate::audit::sysroot;
+use std::path::{Path, PathBuf};
 
 /// Audit rules for user and authentication files.
 ///
@@ -44,15 +45,14 @@ impl_audit! {
     UserConfig,
     self,
     [
-        {path: &self.passwd, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
-        {path: &self.shadow, expected_mode: 0o600, importance: Importance::High, recursive: false},
-        {path: &self.group, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
-        {path: &self.gshadow, expected_mode: 0o600, importance: Importance::High, recursive: false},
-        {path: &self.sudoers, expected_mode: 0o440, importance: Importance::High, recursive: false},
-        // The directory itself should be 755, note recursive is false here
-        {path: &self.pamd, expected_mode: 0o755, importance: Importance::High, recursive: false},
-        // Files within pam.d should be 644
-        {path: &self.pamd, expected_mode: 0o644, importance: Importance::High, recursive: true}
+        {id: "HALO-USER-0001", path: &self.passwd, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
+        {id: "HALO-USER-0002", path: &self.shadow, expected_mode: 0o600, importance: Importance::High, recursive: false},
+        {id: "HALO-USER-0003", path: &self.group, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
+        {id: "HALO-USER-0004", path: &self.gshadow, expected_mode: 0o600, importance: Importance::High, recursive: false},
+        {id: "HALO-USER-0005", path: &self.sudoers, expected_mode: 0o440, importance: Importance::High, recursive: false},
+        // HALO-USER-0006 (the pam.d directory itself) is covered by expected_dir_mode below
+        // Directories in pam.d should be 755, files 644
+        {id: "HALO-USER-0007", path: &self.pamd, expected_mode: 0o644, importance: Importance::High, recursive: true, expected_dir_mode: 0o755}
     ]
 }
 
@@ -83,10 +83,10 @@ impl_audit! {
     SysConfig,
     self,
     [
-        {path: &self.grubcfg, expected_mode: 0o640, importance: Importance::High, recursive: false},
-        {path: &self.fstab, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
-        {path: &self.sysctl, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
-        {path: &self.systemd, expected_mode: 0o644, importance: Importance::High, recursive: true}
+        {id: "HALO-SYS-0001", path: &self.grubcfg, expected_mode: 0o640, importance: Importance::High, recursive: false},
+        {id: "HALO-SYS-0002", path: &self.fstab, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
+        {id: "HALO-SYS-0003", path: &self.sysctl, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
+        {id: "HALO-SYS-0004", path: &self.systemd, expected_mode: 0o644, importance: Importance::High, recursive: true}
     ]
 }
 
@@ -115,18 +115,21 @@ impl_audit! {
     NetConf,
     self,
     [
-        {path: &self.hosts, expected_mode: 0o644, importance: Importance::Low, recursive: false},
-        {path: &self.resolv_cfg, expected_mode: 0o644, importance: Importance::Low, recursive: false},
-        {path: &self.interface, expected_mode: 0o644, importance: Importance::Medium, recursive: false}
+        {id: "HALO-NET-0001", path: &self.hosts, expected_mode: 0o644, importance: Importance::Low, recursive: false},
+        {id: "HALO-NET-0002", path: &self.resolv_cfg, expected_mode: 0o644, importance: Importance::Low, recursive: false},
+        {id: "HALO-NET-0003", path: &self.interface, expected_mode: 0o644, importance: Importance::Medium, recursive: false}
     ]
 }
 
 /// Audit rules for log files.
 ///
-/// Includes `/var/log/wtmp` and `/var/log/btmp`.
+/// Includes `/var/log/wtmp`, `/var/log/btmp` and the logrotate configuration in
+/// `/etc/logrotate.conf` and `/etc/logrotate.d`.
 pub struct Log {
     wtmp: PathBuf,
     btmp: PathBuf,
+    logrotate_conf: PathBuf,
+    logrotate_d: PathBuf,
 }
 
 /// Provides default paths for log files.
@@ -135,6 +138,8 @@ impl Default for Log {
         Self {
             wtmp: "/var/log/wtmp".into(),
             btmp: "/var/log/btmp".into(),
+            logrotate_conf: "/etc/logrotate.conf".into(),
+            logrotate_d: "/etc/logrotate.d".into(),
         }
     }
 }
@@ -144,7 +149,150 @@ impl_audit! {
     Log,
     self,
     [
-        {path: &self.wtmp, expected_mode: 0o664, importance: Importance::High, recursive: false},
-        {path: &self.btmp, expected_mode: 0o664, importance: Importance::High, recursive: false}
+        {id: "HALO-LOG-0001", path: &self.wtmp, expected_mode: 0o664, importance: Importance::High, recursive: false},
+        {id: "HALO-LOG-0002", path: &self.btmp, expected_mode: 0o664, importance: Importance::High, recursive: false},
+        {id: "HALO-LOG-0003", path: &self.logrotate_conf, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
+        {id: "HALO-LOG-0004", path: &self.logrotate_d, expected_mode: 0o644, importance: Importance::Medium, recursive: true}
     ]
 }
+
+/// Audit rules for Raspberry Pi and other single-board computers.
+///
+/// Includes the firmware `config.txt` (both `/boot` and `/boot/firmware` layouts), dhcpcd
+/// configuration and hooks, Wi-Fi credentials in `wpa_supplicant.conf`, and the default
+/// `010_pi-nopasswd` sudoers drop-in.
+pub struct EmbeddedConfig {
+    boot_config: PathBuf,
+    firmware_config: PathBuf,
+    dhcpcd: PathBuf,
+    dhcpcd_hooks: PathBuf,
+    wpa_supplicant: PathBuf,
+    pi_sudoers: PathBuf,
+}
+
+/// Provides default paths for Raspberry Pi OS and similar embedded distributions.
+impl Default for EmbeddedConfig {
+    fn default() -> Self {
+        Self {
+            boot_config: "/boot/config.txt".into(),
+            firmware_config: "/boot/firmware/config.txt".into(),
+            dhcpcd: "/etc/dhcpcd.conf".into(),
+            dhcpcd_hooks: "/lib/dhcpcd/dhcpcd-hooks".into(),
+            wpa_supplicant: "/etc/wpa_supplicant/wpa_supplicant.conf".into(),
+            pi_sudoers: "/etc/sudoers.d/010_pi-nopasswd".into(),
+        }
+    }
+}
+
+// Implements audit rules for EmbeddedConfig
+impl_audit! {
+    EmbeddedConfig,
+    self,
+    [
+        {id: "HALO-EMBEDDED-0001", path: &self.boot_config, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
+        {id: "HALO-EMBEDDED-0002", path: &self.firmware_config, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
+        {id: "HALO-EMBEDDED-0003", path: &self.dhcpcd, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
+        // Hooks run as root whenever an interface changes state
+        {id: "HALO-EMBEDDED-0004", path: &self.dhcpcd_hooks, expected_mode: 0o644, importance: Importance::High, recursive: true},
+        {id: "HALO-EMBEDDED-0005", path: &self.wpa_supplicant, expected_mode: 0o600, importance: Importance::High, recursive: false},
+        {id: "HALO-EMBEDDED-0006", path: &self.pi_sudoers, expected_mode: 0o440, importance: Importance::High, recursive: false}
+    ]
+}
+
+/// Audit rules for the Linux audit daemon configuration.
+///
+/// Includes `/etc/audit/auditd.conf`, `/etc/audit/audit.rules`, and `/etc/audit/rules.d`.
+pub struct AuditdConfig {
+    auditd_conf: PathBuf,
+    audit_rules: PathBuf,
+    rules_d: PathBuf,
+}
+
+/// Provides default paths for auditd configuration.
+impl Default for AuditdConfig {
+    fn default() -> Self {
+        Self {
+            auditd_conf: "/etc/audit/auditd.conf".into(),
+            audit_rules: "/etc/audit/audit.rules".into(),
+            rules_d: "/etc/audit/rules.d".into(),
+        }
+    }
+}
+
+// Implements audit rules for AuditdConfig
+impl_audit! {
+    AuditdConfig,
+    self,
+    [
+        {id: "HALO-AUDITD-0001", path: &self.auditd_conf, expected_mode: 0o640, importance: Importance::High, recursive: false},
+        {id: "HALO-AUDITD-0002", path: &self.audit_rules, expected_mode: 0o640, importance: Importance::High, recursive: false},
+        // Anyone able to edit the rules can blind the audit trail
+        {id: "HALO-AUDITD-0003", path: &self.rules_d, expected_mode: 0o640, importance: Importance::High, recursive: true}
+    ]
+}
+
+/// Audit rules for Kubernetes node files, following the CIS Kubernetes Benchmark.
+///
+/// Includes the control plane manifests and kubeconfig files in `/etc/kubernetes`, the
+/// cluster PKI, the kubelet configuration, service drop-ins and PKI, and the etcd data directory.
+/// Files missing on worker nodes are skipped.
+pub struct K8sNodeConfig {
+    manifests: PathBuf,
+    admin_conf: PathBuf,
+    scheduler_conf: PathBuf,
+    controller_manager_conf: PathBuf,
+    kubelet_conf: PathBuf,
+    pki: PathBuf,
+    kubelet_config: PathBuf,
+    kubelet_service_d: PathBuf,
+    kubelet_pki: PathBuf,
+    etcd_data: PathBuf,
+}
+
+/// Provides the default kubeadm paths.
+impl Default for K8sNodeConfig {
+    fn default() -> Self {
+        Self {
+            manifests: "/etc/kubernetes/manifests".into(),
+            admin_conf: "/etc/kubernetes/admin.conf".into(),
+            scheduler_conf: "/etc/kubernetes/scheduler.conf".into(),
+            controller_manager_conf: "/etc/kubernetes/controller-manager.conf".into(),
+            kubelet_conf: "/etc/kubernetes/kubelet.conf".into(),
+            pki: "/etc/kubernetes/pki".into(),
+            kubelet_config: "/var/lib/kubelet/config.yaml".into(),
+            kubelet_service_d: "/etc/systemd/system/kubelet.service.d".into(),
+            kubelet_pki: "/var/lib/kubelet/pki".into(),
+            etcd_data: "/var/lib/etcd".into(),
+        }
+    }
+}
+
+// Implements audit rules for K8sNodeConfig
+impl_audit! {
+    K8sNodeConfig,
+    self,
+    [
+        // CIS 1.1.1-1.1.8: control plane static pod manifests
+        {id: "HALO-K8S-0001", path: &self.manifests, expected_mode: 0o600, importance: Importance::High, recursive: true},
+        // CIS 1.1.13-1.1.18: kubeconfig files grant cluster credentials
+        {id: "HALO-K8S-0002", path: &self.admin_conf, expected_mode: 0o600, importance: Importance::High, recursive: false},
+        {id: "HALO-K8S-0003", path: &self.scheduler_conf, expected_mode: 0o600, importance: Importance::High, recursive: false},
+        {id: "HALO-K8S-0004", path: &self.controller_manager_conf, expected_mode: 0o600, importance: Importance::High, recursive: false},
+        {id: "HALO-K8S-0005", path: &self.kubelet_conf, expected_mode: 0o600, importance: Importance::High, recursive: false},
+        // CIS 1.1.20-1.1.21: cluster certificates and keys
+        {id: "HALO-K8S-0006", path: &self.pki, expected_mode: 0o600, importance: Importance::High, recursive: true},
+        // CIS 4.1.1, 4.1.9: kubelet service and configuration
+        {id: "HALO-K8S-0007", path: &self.kubelet_service_d, expected_mode: 0o600, importance: Importance::Medium, recursive: true},
+        {id: "HALO-K8S-0008", path: &self.kubelet_config, expected_mode: 0o600, importance: Importance::Medium, recursive: false},
+        {id: "HALO-K8S-0009", path: &self.kubelet_pki, expected_mode: 0o600, importance: Importance::High, recursive: true},
+        // CIS 1.1.11: etcd data holds every cluster secret
+        {id: "HALO-K8S-0010", path: &self.etcd_data, expected_mode: 0o600, importance: Importance::High, recursive: true}
+    ]
+}
+
+impl K8sNodeConfig {
+    /// Returns true if the host looks like a Kubernetes node (kubeadm or kubelet paths exist).
+    pub fn detect() -> bool {
+        sysroot::exists(Path::new("/etc/kubernetes")) || sysroot::exists(Path::new("/var/lib/kubelet"))
+    }
+}
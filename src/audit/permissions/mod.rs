@@ -1,2 +1,3 @@
 pub mod audit_permissions;
 pub mod default_permissions;
+pub mod severity_policy;
@@ -0,0 +1,187 @@
+//! Configurable mapping from permission mismatches to severities.
+//!
+//! [`PermissionRules::determine_severity`](super::audit_permissions::PermissionRules::determine_severity)
+//! classifies a found mode against the expected one into a [`BitClass`] (world-writable,
+//! extra bits for others, added group write, other extra group bits, stricter, or another
+//! mismatch) and a [`SeverityPolicy`] maps that class to a severity. The default policy
+//! reproduces HALO's built-in mapping; overrides can raise or lower the severity of a class,
+//! optionally only for rules of a given importance.
+//!
+//! # Example TOML
+//! ```toml
+//! [severity_policy]
+//! stricter = "None"
+//!
+//! # Group write on security-critical files is as bad as world write
+//! [[severity_policy.overrides]]
+//! class = "group-write"
+//! importance = "High"
+//! severity = "Critical"
+//! ```
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{BitClass, Importance, Severity, SeverityPolicy};
+//! let policy = SeverityPolicy::default().with_override(BitClass::GroupWrite, Some(Importance::High), Severity::Critical);
+//! assert_eq!(policy.severity(0o600, 0o620, &Importance::High), Severity::Critical);
+//! assert_eq!(policy.severity(0o600, 0o620, &Importance::Low), Severity::High);
+//! ```
+use super::audit_permissions::{Importance, Severity};
+use serde::{Deserialize, Serialize};
+
+const WORLD_WRITE: u32 = 0o002;
+const GROUP_WRITE: u32 = 0o020;
+const GROUP_PERMS: u32 = 0o070;
+const OTHER_PERMS: u32 = 0o007;
+
+/// Kind of difference between an expected and a found mode, most severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BitClass {
+    /// Others can write
+    WorldWrite,
+    /// Others have more permissions than expected
+    Other,
+    /// Group write was added
+    GroupWrite,
+    /// Group has more read/execute permissions than expected
+    Group,
+    /// Fewer bits than expected
+    Stricter,
+    /// Any other difference, e.g. owner bits
+    Mismatch,
+}
+
+impl BitClass {
+    /// Classifies `found` against `expected`; `None` if they match.
+    pub fn classify(expected: u32, found: u32) -> Option<BitClass> {
+        // World-writable counts even where the rule expects it
+        if found & WORLD_WRITE != 0 {
+            return Some(BitClass::WorldWrite);
+        }
+        if found == expected {
+            return None;
+        }
+        if (found & OTHER_PERMS) > (expected & OTHER_PERMS) {
+            return Some(BitClass::Other);
+        }
+        if (found & GROUP_PERMS) > (expected & GROUP_PERMS) {
+            return Some(if found & GROUP_WRITE != 0 && expected & GROUP_WRITE == 0 {
+                BitClass::GroupWrite
+            } else {
+                BitClass::Group
+            });
+        }
+        if (found & 0o777) < (expected & 0o777) {
+            return Some(BitClass::Stricter);
+        }
+        Some(BitClass::Mismatch)
+    }
+}
+
+/// Severity of a bit class for rules of one importance (or any importance).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeverityOverride {
+    pub class: BitClass,
+    /// Only applies to rules of this importance; all rules if unset
+    #[serde(default)]
+    pub importance: Option<Importance>,
+    pub severity: Severity,
+}
+
+/// Severities assigned to each bit class.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SeverityPolicy {
+    pub world_write: Severity,
+    pub other: Severity,
+    pub group_write: Severity,
+    pub group: Severity,
+    pub stricter: Severity,
+    pub mismatch: Severity,
+    /// Checked in order before the per-class defaults above
+    pub overrides: Vec<SeverityOverride>,
+}
+
+/// HALO's built-in mapping.
+impl Default for SeverityPolicy {
+    fn default() -> Self {
+        Self {
+            world_write: Severity::Critical,
+            other: Severity::High,
+            group_write: Severity::High,
+            group: Severity::High,
+            stricter: Severity::Info,
+            mismatch: Severity::Low,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl SeverityPolicy {
+    /// Adds an override, checked after the ones already present.
+    pub fn with_override(mut self, class: BitClass, importance: Option<Importance>, severity: Severity) -> Self {
+        self.overrides.push(SeverityOverride {
+            class,
+            importance,
+            severity,
+        });
+        self
+    }
+
+    /// Severity of finding `found` where `expected` was expected, on a rule of `importance`.
+    pub fn severity(&self, expected: u32, found: u32, importance: &Importance) -> Severity {
+        let Some(class) = BitClass::classify(expected, found) else {
+            return Severity::None;
+        };
+        let matching = self
+            .overrides
+            .iter()
+            .find(|o| o.class == class && o.importance.as_ref().is_none_or(|i| i == importance));
+        if let Some(rule) = matching {
+            return rule.severity.clone();
+        }
+        match class {
+            BitClass::WorldWrite => self.world_write.clone(),
+            BitClass::Other => self.other.clone(),
+            BitClass::GroupWrite => self.group_write.clone(),
+            BitClass::Group => self.group.clone(),
+            BitClass::Stricter => self.stricter.clone(),
+            BitClass::Mismatch => self.mismatch.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(BitClass::classify(0o640, 0o640), None);
+        assert_eq!(BitClass::classify(0o666, 0o666), Some(BitClass::WorldWrite));
+        assert_eq!(BitClass::classify(0o640, 0o644), Some(BitClass::Other));
+        assert_eq!(BitClass::classify(0o640, 0o660), Some(BitClass::GroupWrite));
+        assert_eq!(BitClass::classify(0o600, 0o640), Some(BitClass::Group));
+        assert_eq!(BitClass::classify(0o644, 0o600), Some(BitClass::Stricter));
+        assert_eq!(BitClass::classify(0o640, 0o740), Some(BitClass::Mismatch));
+    }
+
+    #[test]
+    fn test_overrides_by_importance() {
+        let policy: SeverityPolicy = toml::from_str(
+            r#"
+            stricter = "None"
+            [[overrides]]
+            class = "group-write"
+            importance = "High"
+            severity = "Critical"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(policy.severity(0o640, 0o660, &Importance::High), Severity::Critical);
+        assert_eq!(policy.severity(0o640, 0o660, &Importance::Medium), Severity::High);
+        assert_eq!(policy.severity(0o644, 0o600, &Importance::Low), Severity::None);
+        assert_eq!(policy.world_write, Severity::Critical);
+    }
+}
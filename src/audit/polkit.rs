@@ -0,0 +1,415 @@
+//! polkit rules and D-Bus system policy audit for HALO.
+//!
+//! polkit rules and D-Bus service policies decide which local users may perform privileged
+//! actions, and are an often overlooked local privilege escalation surface on desktops.
+//!
+//! # Checks
+//! - `polkit-world-writable` / `dbus-world-writable`: world-writable rule files or directories
+//! - `polkit-admin-group`: `addAdminRule` granting administrator rights to a non-admin group
+//! - `polkit-grant-non-admin-group`: rules returning `polkit.Result.YES` for a non-admin group
+//! - `polkit-unconditional-yes`: rules returning `polkit.Result.YES` without checking the subject
+//! - `dbus-send-destination-any`: `<allow send_destination=...>` for every user without an
+//!   interface, member or path restriction
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, PolkitAudit};
+//! let findings = PolkitAudit::default().run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{}: {}", f.path.display(), f.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::Severity;
+use crate::audit::sysroot;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// polkit rule directories and D-Bus policy directories audited for risky grants.
+pub struct PolkitAudit {
+    pub polkit_rules: Vec<PathBuf>,
+    pub dbus_policies: Vec<PathBuf>,
+    /// Groups expected to hold administrator rights
+    pub admin_groups: Vec<String>,
+}
+
+/// Provides the standard polkit and D-Bus system policy directories.
+impl Default for PolkitAudit {
+    fn default() -> Self {
+        Self {
+            polkit_rules: vec![
+                "/etc/polkit-1/rules.d".into(),
+                "/usr/share/polkit-1/rules.d".into(),
+            ],
+            dbus_policies: vec![
+                "/usr/share/dbus-1/system.d".into(),
+                "/etc/dbus-1/system.d".into(),
+            ],
+            admin_groups: vec!["wheel".into(), "sudo".into(), "admin".into()],
+        }
+    }
+}
+
+impl AuditFindings for PolkitAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for dir in &self.polkit_rules {
+            audit_dir(dir, "rules", "polkit", &mut findings, |path, content| {
+                check_polkit_rules(path, content, &self.admin_groups)
+            });
+        }
+        for dir in &self.dbus_policies {
+            audit_dir(dir, "conf", "dbus", &mut findings, check_dbus_policy);
+        }
+        findings
+    }
+}
+
+/// Audits every file with the given extension in `dir`.
+///
+/// Missing directories are skipped; `prefix` names the world-writable and pass checks.
+fn audit_dir(
+    dir: &Path,
+    extension: &str,
+    prefix: &str,
+    findings: &mut Vec<Finding>,
+    check: impl Fn(&Path, &str) -> Vec<Finding>,
+) {
+    let Ok(entries) = sysroot::read_dir(dir) else {
+        return;
+    };
+    let writable_check = format!("{}-world-writable", prefix);
+    if let Some(finding) = world_writable(dir, &writable_check) {
+        findings.push(finding);
+    }
+    let mut files: Vec<PathBuf> = entries
+        .into_iter()
+        .filter(|p| p.extension().is_some_and(|e| e == extension))
+        .collect();
+    files.sort();
+    for path in files {
+        let mut file_findings: Vec<Finding> = world_writable(&path, &writable_check)
+            .into_iter()
+            .collect();
+        if let Ok(content) = sysroot::read_to_string(&path) {
+            file_findings.extend(check(&path, &content));
+        }
+        if file_findings.is_empty() {
+            findings.push(Finding::pass(
+                &format!("{}-policy", prefix),
+                &path,
+                "No risky grants found",
+            ));
+        }
+        findings.extend(file_findings);
+    }
+}
+
+fn world_writable(path: &Path, check: &str) -> Option<Finding> {
+    let meta = sysroot::metadata(path).ok()?;
+    if meta.mode() & 0o002 == 0 {
+        return None;
+    }
+    Some(Finding::fail(
+        check,
+        path,
+        Severity::Critical,
+        "World-writable: any local user can change privilege policy",
+    ))
+}
+
+/// Checks a polkit JavaScript rules file for grants to non-admin groups.
+///
+/// # Arguments
+/// * `path` - Path reported in the findings
+/// * `content` - Contents of the `.rules` file
+/// * `admin_groups` - Groups expected to hold administrator rights
+///
+/// # Returns
+/// Vector of failing `Finding`s, empty if no risky grants were found.
+pub fn check_polkit_rules(path: &Path, content: &str, admin_groups: &[String]) -> Vec<Finding> {
+    let code = strip_js_comments(content);
+    let is_admin = |group: &str| admin_groups.iter().any(|g| g == group);
+    let mut findings = Vec::new();
+
+    for body in admin_rule_bodies(&code) {
+        for group in quoted_after(body, "unix-group:") {
+            if !is_admin(&group) {
+                findings.push(
+                    Finding::fail(
+                        "polkit-admin-group",
+                        path,
+                        Severity::High,
+                        "Administrator authentication is delegated to a non-admin group",
+                    )
+                    .with_subject(group),
+                );
+            }
+        }
+    }
+
+    if code.contains("polkit.Result.YES") {
+        let groups: Vec<String> = quoted_after(&code, "isInGroup(\"")
+            .into_iter()
+            .chain(quoted_after(&code, "isInGroup('"))
+            .collect();
+        for group in groups.into_iter().filter(|g| !is_admin(g)) {
+            findings.push(
+                Finding::fail(
+                    "polkit-grant-non-admin-group",
+                    path,
+                    Severity::Medium,
+                    "Actions are allowed without authentication for a non-admin group",
+                )
+                .with_subject(group),
+            );
+        }
+        if !code.contains("subject.") {
+            findings.push(Finding::fail(
+                "polkit-unconditional-yes",
+                path,
+                Severity::High,
+                "Rule returns polkit.Result.YES without checking who is asking",
+            ));
+        }
+    }
+    findings
+}
+
+/// Checks a D-Bus system policy for destinations any user may send to without restriction.
+///
+/// Only `<policy context="default">` and `<policy user="*">` blocks apply to every user;
+/// `<allow send_destination=...>` entries there are flagged unless they also restrict the
+/// interface, member or object path.
+///
+/// # Returns
+/// Vector of failing `Finding`s, empty if no unrestricted destinations were found.
+pub fn check_dbus_policy(path: &Path, content: &str) -> Vec<Finding> {
+    let content = strip_xml_comments(content);
+    let mut findings = Vec::new();
+    for block in content.split("<policy").skip(1) {
+        let Some((open_tag, body)) = block.split_once('>') else {
+            continue;
+        };
+        let applies_to_all =
+            open_tag.contains("context=\"default\"") || open_tag.contains("user=\"*\"");
+        if !applies_to_all {
+            continue;
+        }
+        let body = body.split("</policy>").next().unwrap_or_default();
+        for allow in body.split("<allow").skip(1) {
+            let tag = allow.split('>').next().unwrap_or_default();
+            let Some(destination) = attribute(tag, "send_destination") else {
+                continue;
+            };
+            let restricted = ["send_interface", "send_member", "send_path"]
+                .iter()
+                .any(|a| attribute(tag, a).is_some());
+            if !restricted {
+                findings.push(
+                    Finding::fail(
+                        "dbus-send-destination-any",
+                        path,
+                        Severity::Medium,
+                        "Any user may call every method of this service",
+                    )
+                    .with_subject(destination),
+                );
+            }
+        }
+    }
+    findings
+}
+
+/// Removes `//` and `/* */` comments outside string literals, keeping line breaks.
+fn strip_js_comments(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            out.push(c);
+            if c == '\\' {
+                out.extend(chars.next());
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('/', Some('/')) => while chars.next_if(|&n| n != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for n in chars.by_ref() {
+                    if n == '\n' {
+                        out.push(n);
+                    }
+                    if prev == '*' && n == '/' {
+                        break;
+                    }
+                    prev = n;
+                }
+            }
+            ('"' | '\'' | '`', _) => {
+                quote = Some(c);
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Returns the body of each `addAdminRule` callback, from its opening brace to the matching one.
+///
+/// Braces inside string literals are ignored; an unclosed body runs to the end of the file.
+fn admin_rule_bodies(code: &str) -> Vec<&str> {
+    let mut bodies = Vec::new();
+    for (at, _) in code.match_indices("addAdminRule") {
+        let Some(open) = code[at..].find('{') else {
+            continue;
+        };
+        let start = at + open + 1;
+        let mut end = code.len();
+        let mut depth = 1;
+        let mut quote = None;
+        let mut escaped = false;
+        for (i, c) in code[start..].char_indices() {
+            if let Some(q) = quote {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+                continue;
+            }
+            match c {
+                '"' | '\'' | '`' => quote = Some(c),
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = start + i;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        bodies.push(&code[start..end]);
+    }
+    bodies
+}
+
+/// Collects the names following each occurrence of `marker`, up to a closing quote.
+fn quoted_after(code: &str, marker: &str) -> Vec<String> {
+    code.split(marker)
+        .skip(1)
+        .filter_map(|rest| {
+            let name: String = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+                .collect();
+            (!name.is_empty()).then_some(name)
+        })
+        .collect()
+}
+
+/// Returns the value of `name="value"` in an XML tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let rest = tag.split(&format!("{}=\"", name)).nth(1)?;
+    rest.split('"').next().map(str::to_string)
+}
+
+fn strip_xml_comments(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("<!--") {
+        out.push_str(&rest[..start]);
+        rest = match rest[start..].find("-->") {
+            Some(end) => &rest[start + end + 3..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn admin_groups() -> Vec<String> {
+        vec!["wheel".to_string()]
+    }
+
+    #[test]
+    fn test_admin_rule_non_admin_group() {
+        let rules = "polkit.addAdminRule(function(action, subject) {\n\
+                     return [\"unix-group:wheel\", \"unix-group:staff\"];\n});\n";
+        let findings = check_polkit_rules(Path::new("50.rules"), rules, &admin_groups());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].check, "polkit-admin-group");
+        assert_eq!(findings[0].subject.as_deref(), Some("staff"));
+    }
+
+    #[test]
+    fn test_admin_groups_outside_admin_rule() {
+        let rules = "polkit.addRule(function(action, subject) {\n\
+                     if (subject.isInGroup(\"wheel\")) { polkit.log(\"unix-group:staff\"); }\n});\n\
+                     polkit.addAdminRule(function(action, subject) {\n\
+                     if (action.id == \"a\") { return [\"unix-group:wheel\"]; }\n\
+                     return [\"unix-user:0\"];\n});\n\
+                     var groups = [\"unix-group:ops\"];\n";
+        assert!(check_polkit_rules(Path::new("50.rules"), rules, &admin_groups()).is_empty());
+    }
+
+    #[test]
+    fn test_comments_outside_strings() {
+        // A "//" inside a string does not hide the rest of the line
+        let rules = "polkit.addAdminRule(function(action, subject) {\n\
+                     polkit.log(\"see https://example.com\"); return [\"unix-group:staff\"];\n\
+                     /* return [\"unix-group:ops\"]; */ // \"unix-group:dev\"\n});\n";
+        let findings = check_polkit_rules(Path::new("50.rules"), rules, &admin_groups());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].subject.as_deref(), Some("staff"));
+
+        let rules = "polkit.addRule(function(action, subject) {\n\
+                     if (subject.isInGroup('users')) { polkit.log('a // b'); return polkit.Result.YES; }\n});\n";
+        let findings = check_polkit_rules(Path::new("a.rules"), rules, &admin_groups());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].check, "polkit-grant-non-admin-group");
+    }
+
+    #[test]
+    fn test_result_yes_rules() {
+        let rules = "polkit.addRule(function(action, subject) {\n\
+                     if (subject.isInGroup(\"users\")) { return polkit.Result.YES; }\n\
+                     // subject.isInGroup(\"ignored\")\n});\n";
+        let findings = check_polkit_rules(Path::new("a.rules"), rules, &admin_groups());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].subject.as_deref(), Some("users"));
+
+        let open = "polkit.addRule(function(action) { return polkit.Result.YES; });";
+        let findings = check_polkit_rules(Path::new("b.rules"), open, &admin_groups());
+        assert_eq!(findings[0].check, "polkit-unconditional-yes");
+    }
+
+    #[test]
+    fn test_dbus_default_policy() {
+        let policy = r#"<busconfig>
+  <policy user="root"><allow own="org.example.Root"/><allow send_destination="org.example.Root"/></policy>
+  <policy context="default">
+    <allow send_destination="org.example.Open"/>
+    <allow send_destination="org.example.Scoped" send_interface="org.example.Scoped.Read"/>
+    <!-- <allow send_destination="org.example.Commented"/> -->
+  </policy>
+</busconfig>"#;
+        let findings = check_dbus_policy(Path::new("example.conf"), policy);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].subject.as_deref(), Some("org.example.Open"));
+    }
+}
@@ -0,0 +1,182 @@
+//! Common interface of audit rules.
+//!
+//! Permission, ownership and symlink rules each produce their own result type. The
+//! [`AuditRule`] trait evaluates any of them, and rules defined outside HALO, to generic
+//! [`Finding`]s, so configs and engines can hold a heterogeneous `Vec<Box<dyn AuditRule>>`
+//! and custom checks plug into an [`Audit`](crate::Audit) with
+//! [`audit_rule`](crate::Audit::audit_rule). Their findings are reported with those of the
+//! content and policy audits.
+//!
+//! The context is the run's shared [`AuditContext`], so a rule reading metadata through it
+//! does not `stat` a path another rule of the run has already read. The built-in rules all
+//! do. It is shared rather than `&mut` because the run's parallel walks read through it at
+//! the same time.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::audit::context::AuditContext;
+//! use alhalo::audit::rule::AuditRule;
+//! use alhalo::{Audit, Finding, Importance, OwnershipRule, PermissionRules, Severity};
+//! use std::path::Path;
+//!
+//! // A custom rule: /etc/motd must not exist
+//! struct NoMotd;
+//! impl AuditRule for NoMotd {
+//!     fn evaluate(&self, ctx: &AuditContext) -> Vec<Finding> {
+//!         let path = Path::new("/etc/motd");
+//!         vec![match ctx.symlink_metadata(path) {
+//!             Ok(_) => Finding::fail("no-motd", path, Severity::Low, "/etc/motd exists"),
+//!             Err(_) => Finding::pass("no-motd", path, "/etc/motd is absent"),
+//!         }]
+//!     }
+//! }
+//!
+//! let (perms, _) = PermissionRules::new("/etc/passwd".into(), 0o644, Importance::Medium);
+//! let (owner, _) = OwnershipRule::new("/etc/passwd".into(), 0, 0, true);
+//! let rules: Vec<Box<dyn AuditRule>> = vec![Box::new(perms), Box::new(owner), Box::new(NoMotd)];
+//! let context = AuditContext::cached();
+//! let findings: Vec<Finding> = rules.iter().flat_map(|rule| rule.evaluate(&context)).collect();
+//! assert_eq!(findings.len(), 3);
+//!
+//! let report = Audit::new().audit_rule(NoMotd).run().unwrap();
+//! assert_eq!(report.findings.len(), 1);
+//! ```
+use crate::audit::context::AuditContext;
+use crate::audit::finding::Finding;
+use crate::audit::ownership::ownership::{OwnershipResult, OwnershipRule};
+use crate::audit::permissions::audit_permissions::{PermissionResults, PermissionRules, Severity, Status};
+use crate::audit::symlink::{SymResult, SymRule, check_symlink_with};
+use std::fmt;
+
+/// A rule that can be evaluated on its own and reports generic findings.
+///
+/// Implementations must be `Send + Sync`, as audits run on worker threads.
+pub trait AuditRule: Send + Sync {
+    /// Evaluates the rule and returns its findings, passing and failing.
+    fn evaluate(&self, ctx: &AuditContext) -> Vec<Finding>;
+}
+
+impl fmt::Debug for dyn AuditRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuditRule")
+    }
+}
+
+impl<R: AuditRule + ?Sized> AuditRule for Box<R> {
+    fn evaluate(&self, ctx: &AuditContext) -> Vec<Finding> {
+        (**self).evaluate(ctx)
+    }
+}
+
+/// One finding per path walked, with check `permission-mode`.
+///
+/// The walk uses the built-in severity mapping and one thread; run the rule with
+/// [`Audit::rule`](crate::Audit::rule) for policies, limits and parallel walks.
+impl AuditRule for PermissionRules {
+    fn evaluate(&self, ctx: &AuditContext) -> Vec<Finding> {
+        self.check_in(ctx).iter().map(permission_finding).collect()
+    }
+}
+
+/// One finding with check `ownership`.
+impl AuditRule for OwnershipRule {
+    fn evaluate(&self, ctx: &AuditContext) -> Vec<Finding> {
+        vec![ownership_finding(&self.check_ownership_with(ctx))]
+    }
+}
+
+/// One finding with check `symlink`.
+impl AuditRule for SymRule {
+    fn evaluate(&self, ctx: &AuditContext) -> Vec<Finding> {
+        vec![symlink_finding(&check_symlink_with(self, ctx))]
+    }
+}
+
+fn permission_finding(result: &PermissionResults) -> Finding {
+    let message = match &result.error {
+        Some(error) => error.to_string(),
+        None if result.status == Status::Pass => format!("Mode {:o} as expected", result.found_mode),
+        None => format!("Mode {:o}, expected {:o}", result.found_mode, result.expected_mode),
+    };
+    let mut finding = Finding::fail("permission-mode", &result.path, result.severity.clone(), message);
+    finding.status = result.status.clone();
+    finding
+}
+
+fn ownership_finding(result: &OwnershipResult) -> Finding {
+    let owner = |uid: Option<u32>, gid: Option<u32>| match (uid, gid) {
+        (Some(uid), Some(gid)) => format!("{}:{}", uid, gid),
+        _ => "unknown".to_string(),
+    };
+    let expected = owner(result.expected_uid, result.expected_gid);
+    let message = match &result.error {
+        Some(error) => error.clone(),
+        None => format!("Owner {}, expected {}", owner(result.found_uid, result.found_gid), expected),
+    };
+    outcome("ownership", result.pass, &result.path, &result.severity, message)
+}
+
+fn symlink_finding(result: &SymResult) -> Finding {
+    let message = match (&result.error, &result.target) {
+        (Some(error), _) => error.clone(),
+        (None, Some(target)) => format!("Symlink points to {}", target.display()),
+        (None, None) => "Symlink resolves".to_string(),
+    };
+    outcome("symlink", result.pass, &result.path, &result.severity, message)
+}
+
+fn outcome(check: &str, pass: bool, path: &std::path::Path, severity: &Severity, message: String) -> Finding {
+    if pass {
+        Finding::pass(check, path, message)
+    } else {
+        Finding::fail(check, path, severity.clone(), message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Importance;
+    use std::fs;
+    use std::os::unix::fs::{PermissionsExt, symlink};
+
+    #[test]
+    fn test_heterogeneous_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o666)).unwrap();
+        symlink(&file, dir.path().join("link")).unwrap();
+        let uid = fs::metadata(&file).map(|m| std::os::unix::fs::MetadataExt::uid(&m)).unwrap();
+
+        let (perms, _) = PermissionRules::new(file.clone(), 0o644, Importance::High);
+        let (owner, _) = OwnershipRule::new(file.clone(), uid, u32::MAX, true);
+        let link = SymRule {
+            path: dir.path().join("link"),
+            target_link: Some(file.clone()),
+            severity_on_mismatch: None,
+        };
+        let rules: Vec<Box<dyn AuditRule>> = vec![Box::new(perms), Box::new(owner), Box::new(link)];
+        let context = AuditContext::cached();
+        let findings: Vec<Finding> = rules.iter().flat_map(|r| r.evaluate(&context)).collect();
+
+        let checks: Vec<&str> = findings.iter().map(|f| f.check.as_str()).collect();
+        assert_eq!(checks, ["permission-mode", "ownership", "symlink"]);
+        assert!(findings[0].is_failure());
+        assert_eq!(findings[0].message, "Mode 666, expected 644");
+        assert!(findings[1].is_failure());
+        assert!(findings[1].message.starts_with(&format!("Owner {}:", uid)));
+        assert!(!findings[2].is_failure());
+        assert_eq!(findings[2].severity, Severity::None);
+
+        // Every rule reads metadata through the context, so a second run is served by its cache
+        for rule in &rules {
+            let context = AuditContext::cached();
+            rule.evaluate(&context);
+            let misses = context.stats().misses;
+            rule.evaluate(&context);
+            assert!(misses > 0);
+            assert_eq!(context.stats().misses, misses);
+        }
+    }
+}
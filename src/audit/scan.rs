@@ -0,0 +1,770 @@
+//! Rate limiting and checkpointing for long permission scans.
+//!
+//! Recursive rules over production storage can visit millions of files. A [`ScanControl`]
+//! passed to the permission walk can:
+//! - throttle the walk to a maximum number of files per second, so the scan does not compete
+//!   with production I/O
+//! - append every result to a checkpoint file, so an interrupted scan resumes after the last
+//!   recorded file instead of starting over
+//! - map permission mismatches to severities with a [`SeverityPolicy`] instead of the
+//!   built-in mapping
+//! - write every result to a [`ResultStream`] as soon as it is produced
+//! - walk directories on several threads ([`with_jobs`](ScanControl::with_jobs))
+//! - keep only non-passing results in memory
+//! - set which mounts rules without their own [`MountPolicy`] enter
+//! - stop a runaway rule after a number of files, results or a runtime ([`ScanLimits`])
+//!
+//! The walk visits directory entries in sorted order, so the position in the traversal is the
+//! last recorded (rule, path) pair. The checkpoint is a JSON Lines file: a header line with a
+//! key identifying the scan, then one line per result. It is flushed periodically and removed
+//! by [`ScanControl::finish`] once the scan completes. A checkpoint written for a different
+//! scan (different key) is discarded.
+//!
+//! A parallel walk checks the entries of each directory concurrently and sorts a rule's
+//! results by path before they are recorded, which is the order of the sequential walk.
+//! Throttled, checkpointed and capped scans depend on that order while walking and stay
+//! sequential.
+//! A sequential walk is a [`PermissionWalk`](crate::PermissionWalk) that yields one result
+//! at a time; with [`without_passing`](ScanControl::without_passing) passing results are
+//! streamed and then dropped, so a streamed low-footprint scan holds only its failures.
+//!
+//! Limits apply to each rule's walk, so one runaway rule does not keep the others from
+//! running. A walk that reaches a limit stops where it is, keeps the results it has and is
+//! listed as a [`Truncation`] in the report summary.
+//!
+//! A [`CancelToken`] stops the whole scan from another thread, e.g. a Ctrl-C handler or the
+//! stop button of a GUI: walks stop at the next file, and the checkpoint is kept so the scan
+//! can resume later.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{Audit, Importance, PermissionRules};
+//! let (rule, _) = PermissionRules::new("/etc".into(), 0o644, Importance::Medium);
+//! let checkpoint = std::env::temp_dir().join("halo-scan-doc.jsonl");
+//! let report = Audit::new()
+//!     .rule(rule)
+//!     .io_rate(5000)
+//!     .checkpoint(&checkpoint)
+//!     .run()
+//!     .unwrap();
+//! println!("{} results", report.permissions.len());
+//! ```
+use crate::audit::context::AuditContext;
+use crate::audit::mounts::MountPolicy;
+use crate::audit::permissions::audit_permissions::{PermissionResults, PermissionRules, Status};
+use crate::audit::permissions::severity_policy::SeverityPolicy;
+use crate::observer::{AuditObserver, ResultRef};
+use crate::stream::ResultStream;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, trace, warn};
+
+/// Results written between checkpoint flushes.
+const FLUSH_EVERY: usize = 100;
+/// Longest time between checkpoint flushes.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    key: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    rule: usize,
+    result: PermissionResults,
+}
+
+/// Caps on each rule's walk; unset caps do not apply.
+///
+/// In a config file they are the `[limits]` table, with the runtime as a number of seconds
+/// or a string such as `"10m"`:
+/// ```toml
+/// [limits]
+/// max_files = 1000000
+/// max_results = 50000
+/// max_runtime = "15m"
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScanLimits {
+    /// Paths a walk visits
+    #[serde(default)]
+    pub max_files: Option<u64>,
+    /// Results a walk keeps in memory; with [`without_passing`](ScanControl::without_passing)
+    /// only failures count
+    #[serde(default)]
+    pub max_results: Option<usize>,
+    /// Time a walk runs
+    #[serde(default, deserialize_with = "runtime")]
+    pub max_runtime: Option<Duration>,
+}
+
+impl ScanLimits {
+    /// Whether any cap is set.
+    pub fn is_set(&self) -> bool {
+        self.max_files.is_some() || self.max_results.is_some() || self.max_runtime.is_some()
+    }
+}
+
+// A runtime in seconds, or as accepted by `parse_interval`
+fn runtime<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Seconds(u64),
+        Interval(String),
+    }
+    match Value::deserialize(deserializer)? {
+        Value::Seconds(secs) => Ok(Some(Duration::from_secs(secs))),
+        Value::Interval(s) => crate::watch::parse_interval(&s).map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// The cap a walk stopped at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanLimit {
+    MaxFiles,
+    MaxResults,
+    MaxRuntime,
+}
+
+impl fmt::Display for ScanLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScanLimit::MaxFiles => "max files",
+            ScanLimit::MaxResults => "max results",
+            ScanLimit::MaxRuntime => "max runtime",
+        })
+    }
+}
+
+/// A rule's walk stopped by a [`ScanLimits`] cap; its results are incomplete.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Truncation {
+    /// Path of the rule
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    pub limit: ScanLimit,
+    /// Paths visited before the walk stopped
+    pub files: u64,
+    /// Results kept
+    pub results: usize,
+    pub elapsed_ms: u64,
+}
+
+impl fmt::Display for Truncation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(id) = &self.rule_id {
+            write!(f, " ({})", id)?;
+        }
+        write!(
+            f,
+            " stopped at {} after {} files, {} results in {} ms",
+            self.limit, self.files, self.results, self.elapsed_ms
+        )
+    }
+}
+
+// Progress of the current rule's walk, for the limits
+#[derive(Default)]
+struct WalkCount {
+    started: Option<Instant>,
+    files: u64,
+    results: usize,
+}
+
+struct Checkpoint {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    pending: usize,
+    last_flush: Instant,
+}
+
+/// Cancels a running audit from another thread.
+///
+/// Clones share one flag. Once cancelled, walks stop at the next file and the remaining
+/// targets of an [`Audit`](crate::Audit) are skipped; the report holds the results found so
+/// far and its metadata is marked `cancelled`.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// A token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every audit holding a clone of this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`cancel`](Self::cancel) was called, or the flag set.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A token cancelled by setting `flag`, e.g. from a signal handler.
+impl From<Arc<AtomicBool>> for CancelToken {
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        CancelToken(flag)
+    }
+}
+
+/// Throttling, checkpoint state and severity policy for one permission scan.
+///
+/// The default control neither throttles nor checkpoints and uses the built-in severities.
+#[derive(Default)]
+pub struct ScanControl {
+    /// Maximum files per second, if throttled
+    rate: Option<u32>,
+    started: Option<Instant>,
+    admitted: u64,
+    /// Index of the rule being walked, counting from 0
+    rule: Option<usize>,
+    checkpoint: Option<Checkpoint>,
+    /// Last (rule, path) recorded by the interrupted run being resumed
+    resume_after: Option<(usize, PathBuf)>,
+    restored: Vec<Entry>,
+    policy: SeverityPolicy,
+    stream: Option<ResultStream>,
+    observers: Vec<Arc<dyn AuditObserver>>,
+    /// Worker threads of a parallel walk
+    pool: Option<Arc<rayon::ThreadPool>>,
+    /// Whether passing results are returned, or only recorded and streamed
+    drop_passing: bool,
+    /// Metadata lookups, shared with the other rules of the run
+    context: Arc<AuditContext>,
+    /// Mounts entered by rules without a policy of their own
+    mounts: MountPolicy,
+    limits: ScanLimits,
+    walk: WalkCount,
+    /// Results produced by all walks so far
+    files: u64,
+    truncations: Vec<Truncation>,
+    cancel: CancelToken,
+}
+
+impl ScanControl {
+    /// Create a control that neither throttles nor checkpoints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the walk to `files_per_sec` files per second.
+    pub fn with_rate(mut self, files_per_sec: u32) -> Self {
+        self.rate = Some(files_per_sec.max(1));
+        self
+    }
+
+    /// Map permission mismatches to severities with `policy`.
+    pub fn with_severity_policy(mut self, policy: SeverityPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Read metadata through `context`, e.g. the cache of the run.
+    pub fn with_context(mut self, context: Arc<AuditContext>) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// The context metadata is read through.
+    pub(crate) fn context(&self) -> &Arc<AuditContext> {
+        &self.context
+    }
+
+    /// Enter mounts according to `policy` in rules that do not set one, e.g.
+    /// [`MountPolicy::OneFileSystem`] for `--one-file-system`.
+    pub fn with_mount_policy(mut self, policy: MountPolicy) -> Self {
+        self.mounts = policy;
+        self
+    }
+
+    /// The mount policy of rules that do not set one.
+    pub(crate) fn mount_policy(&self) -> MountPolicy {
+        self.mounts
+    }
+
+    /// Return only non-passing results from the walk; passing ones are still recorded to the
+    /// checkpoint and stream. Keeps memory bounded by the failures of a streamed scan.
+    pub fn without_passing(mut self) -> Self {
+        self.drop_passing = true;
+        self
+    }
+
+    /// Whether the walk returns `result`.
+    pub(crate) fn retains(&self, result: &PermissionResults) -> bool {
+        !(self.drop_passing && result.status == Status::Pass)
+    }
+
+    /// Walk directories with `jobs` worker threads; 0 or 1 walks on the calling thread.
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.pool = (jobs > 1)
+            .then(|| rayon::ThreadPoolBuilder::new().num_threads(jobs).build().ok())
+            .flatten()
+            .map(Arc::new);
+        self
+    }
+
+    /// Stop each rule's walk at `limits`.
+    pub fn with_limits(mut self, limits: ScanLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Paths the walks checked so far, whether or not their results were kept.
+    pub fn files_scanned(&self) -> u64 {
+        self.files
+    }
+
+    /// The walks stopped by a limit so far.
+    pub fn truncations(&self) -> &[Truncation] {
+        &self.truncations
+    }
+
+    /// The pool to walk on, unless the walk is sequential: a single job, or a throttled,
+    /// checkpointed or capped scan.
+    pub(crate) fn pool(&self) -> Option<Arc<rayon::ThreadPool>> {
+        if self.rate.is_some() || self.checkpoint.is_some() || self.limits.is_set() {
+            return None;
+        }
+        self.pool.clone()
+    }
+
+    /// Write every result to `stream` as it is produced.
+    pub fn with_stream(mut self, stream: ResultStream) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    /// Stop walking once `token` is cancelled.
+    pub fn with_cancel(mut self, token: CancelToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Whether the scan was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    pub(crate) fn cancel_token(&self) -> &CancelToken {
+        &self.cancel
+    }
+
+    /// Tell `observer` when each walk starts and each result is produced.
+    pub fn with_observer(mut self, observer: Arc<dyn AuditObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// The observers of the run.
+    pub(crate) fn observers(&self) -> &[Arc<dyn AuditObserver>] {
+        &self.observers
+    }
+
+    /// The result stream, if any.
+    pub(crate) fn stream_mut(&mut self) -> Option<&mut ResultStream> {
+        self.stream.as_mut()
+    }
+
+    /// Record results to `path`, resuming from it if it holds a checkpoint for the same scan.
+    ///
+    /// # Arguments
+    /// * `path` - Checkpoint file
+    /// * `key` - Identifies the scan (rules and targets); a checkpoint with another key is discarded
+    ///
+    /// # Returns
+    /// * `Err` if the checkpoint cannot be read or created.
+    pub fn with_checkpoint(mut self, path: &Path, key: &str) -> io::Result<Self> {
+        let mut resumable = false;
+        match File::open(path) {
+            Ok(file) => {
+                let mut lines = BufReader::new(file).lines();
+                let header = lines
+                    .next()
+                    .transpose()?
+                    .and_then(|l| serde_json::from_str::<Header>(&l).ok());
+                if header.is_some_and(|h| h.key == key) {
+                    resumable = true;
+                    // A line cut off by the interruption fails to parse and ends the restore
+                    for line in lines {
+                        let Ok(entry) = serde_json::from_str::<Entry>(&line?) else {
+                            break;
+                        };
+                        self.resume_after = Some((entry.rule, entry.result.path.clone()));
+                        self.restored.push(entry);
+                    }
+                    if let Some((_, last)) = &self.resume_after {
+                        info!("Resuming scan from {} after {}", path.display(), last.display());
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        // Rewrite the valid part, dropping a truncated last line
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", to_line(&Header { key: key.to_string() })?)?;
+        if resumable {
+            for entry in &self.restored {
+                writeln!(writer, "{}", to_line(entry)?)?;
+            }
+        }
+        writer.flush()?;
+        let file = OpenOptions::new().append(true).open(path)?;
+        self.checkpoint = Some(Checkpoint {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+            pending: 0,
+            last_flush: Instant::now(),
+        });
+        Ok(self)
+    }
+
+    /// Returns true if this scan resumes an interrupted one.
+    pub fn is_resuming(&self) -> bool {
+        self.resume_after.is_some()
+    }
+
+    /// Results recorded by the interrupted run; empty after the first call.
+    pub fn take_restored(&mut self) -> Vec<PermissionResults> {
+        std::mem::take(&mut self.restored)
+            .into_iter()
+            .map(|entry| entry.result)
+            .collect()
+    }
+
+    /// Severity policy for the results of this scan.
+    pub(crate) fn severity_policy(&self) -> &SeverityPolicy {
+        &self.policy
+    }
+
+    /// Called by the walk when it starts the next top-level rule.
+    pub(crate) fn begin_rule(&mut self, path: &Path) {
+        self.observers.iter().for_each(|o| o.on_path_start(path));
+        self.rule = Some(self.rule.map_or(0, |r| r + 1));
+        self.walk = WalkCount {
+            started: Some(Instant::now()),
+            ..WalkCount::default()
+        };
+    }
+
+    /// Counts a result produced by the walk, `kept` if it is returned.
+    pub(crate) fn count(&mut self, kept: bool) {
+        self.files += 1;
+        self.walk.files += 1;
+        if kept {
+            self.walk.results += 1;
+        }
+    }
+
+    /// Stops the walk of `rule` if it reached a limit; returns whether it did.
+    pub(crate) fn truncate(&mut self, rule: &PermissionRules) -> bool {
+        if !self.limits.is_set() {
+            return false;
+        }
+        let elapsed = self.walk.started.map(|s| s.elapsed()).unwrap_or_default();
+        let limit = if self.limits.max_files.is_some_and(|max| self.walk.files >= max) {
+            ScanLimit::MaxFiles
+        } else if self.limits.max_results.is_some_and(|max| self.walk.results >= max) {
+            ScanLimit::MaxResults
+        } else if self.limits.max_runtime.is_some_and(|max| elapsed >= max) {
+            ScanLimit::MaxRuntime
+        } else {
+            return false;
+        };
+        let truncation = Truncation {
+            path: rule.path.clone(),
+            rule_id: rule.id.clone(),
+            limit,
+            files: self.walk.files,
+            results: self.walk.results,
+            elapsed_ms: elapsed.as_millis() as u64,
+        };
+        warn!("Walk truncated: {}", truncation);
+        self.truncations.push(truncation);
+        true
+    }
+
+    /// Whether the walk should produce a result for `path`.
+    ///
+    /// Returns false for results the resumed checkpoint already holds. Otherwise waits as
+    /// long as needed to stay within the rate limit and returns true.
+    pub(crate) fn admit(&mut self, path: &Path) -> bool {
+        let rule = self.rule.unwrap_or_default();
+        if let Some((done_rule, done_path)) = &self.resume_after
+            && (rule < *done_rule || (rule == *done_rule && path <= done_path.as_path()))
+        {
+            trace!("Skipping {}: already in the checkpoint", path.display());
+            return false;
+        }
+        if let Some(rate) = self.rate {
+            let started = *self.started.get_or_insert_with(Instant::now);
+            self.admitted += 1;
+            let due = Duration::from_secs_f64(self.admitted as f64 / f64::from(rate));
+            if let Some(wait) = due.checked_sub(started.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+        true
+    }
+
+    /// Writes a result to the stream and tells the observers.
+    pub(crate) fn publish(&mut self, result: ResultRef<'_>) {
+        if let Some(stream) = self.stream.as_mut() {
+            match result {
+                ResultRef::Permission(result) => stream.permission(result),
+                ResultRef::Ownership(result) => stream.ownership(result),
+                ResultRef::Finding(finding) => stream.finding(finding),
+            }
+        }
+        self.observers.iter().for_each(|o| o.on_finding(result));
+    }
+
+    /// Appends a result to the checkpoint, flushing periodically, and publishes it.
+    pub(crate) fn record(&mut self, result: &PermissionResults) {
+        self.publish(ResultRef::Permission(result));
+        let rule = self.rule.unwrap_or_default();
+        let Some(checkpoint) = self.checkpoint.as_mut() else {
+            return;
+        };
+        let written = to_line(&EntryRef { rule, result })
+            .and_then(|line| writeln!(checkpoint.writer, "{}", line));
+        checkpoint.pending += 1;
+        if written.is_err()
+            || checkpoint.pending >= FLUSH_EVERY
+            || checkpoint.last_flush.elapsed() >= FLUSH_INTERVAL
+        {
+            checkpoint.writer.flush().ok();
+            checkpoint.pending = 0;
+            checkpoint.last_flush = Instant::now();
+        }
+    }
+
+    /// Marks the scan complete, flushes the stream and removes the checkpoint file; a
+    /// cancelled scan keeps its checkpoint to resume from.
+    pub fn finish(self) -> io::Result<()> {
+        if let Some(stream) = self.stream {
+            stream.finish()?;
+        }
+        match self.checkpoint {
+            Some(mut checkpoint) if self.cancel.is_cancelled() => checkpoint.writer.flush(),
+            Some(checkpoint) => {
+                drop(checkpoint.writer);
+                fs::remove_file(checkpoint.path)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct EntryRef<'a> {
+    rule: usize,
+    result: &'a PermissionResults,
+}
+
+fn to_line<T: Serialize>(value: &T) -> io::Result<String> {
+    serde_json::to_string(value).map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::permissions::audit_permissions::{Importance, PermissionRules};
+    use std::collections::HashSet;
+
+    fn tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a", "b", "c", "d"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_resume_skips_recorded_results() {
+        let dir = tree();
+        let checkpoint = dir.path().join("scan.jsonl");
+        let rule = PermissionRules {
+            path: dir.path().to_path_buf(),
+            expected_mode: 0o644,
+            recursive: true,
+            importance: Importance::Low,
+            id: None,
+            expected_dir_mode: None,
+            mounts: None,
+            symlink_severity: None,
+        };
+
+        // Simulate an interrupted run that got through "a" and "b" (plus a cut-off line)
+        let first = rule.check(&mut HashSet::new());
+        let mut lines = vec![to_line(&Header { key: "k".into() }).unwrap()];
+        for result in first.iter().filter(|r| r.path.ends_with("a") || r.path.ends_with("b")) {
+            lines.push(to_line(&EntryRef { rule: 0, result }).unwrap());
+        }
+        lines.push("{\"rule\":0,\"res".to_string());
+        fs::write(&checkpoint, lines.join("\n")).unwrap();
+
+        let mut control = ScanControl::new().with_checkpoint(&checkpoint, "k").unwrap();
+        assert!(control.is_resuming());
+        let restored = control.take_restored();
+        assert_eq!(restored.len(), 2);
+        let resumed = rule.check_with(&mut HashSet::new(), &mut control);
+        let names: Vec<_> = resumed.iter().filter_map(|r| r.path.file_name()).collect();
+        // The checkpoint file itself sorts after "d" and is picked up by the walk
+        assert_eq!(names[..2], ["c", "d"]);
+        control.finish().unwrap();
+        assert!(!checkpoint.exists());
+    }
+
+    #[test]
+    fn test_other_scan_key_starts_over() {
+        let dir = tree();
+        let checkpoint = dir.path().join("scan.jsonl");
+        fs::write(&checkpoint, "{\"key\":\"old\"}\n").unwrap();
+        let control = ScanControl::new().with_checkpoint(&checkpoint, "new").unwrap();
+        assert!(!control.is_resuming());
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let dir = tree();
+        let rule = PermissionRules {
+            path: dir.path().to_path_buf(),
+            expected_mode: 0o644,
+            recursive: true,
+            importance: Importance::Low,
+            id: None,
+            expected_dir_mode: None,
+            mounts: None,
+            symlink_severity: None,
+        };
+        let mut control = ScanControl::new().with_rate(100);
+        let started = Instant::now();
+        let results = rule.check_with(&mut HashSet::new(), &mut control);
+        assert_eq!(results.len(), 4);
+        assert!(started.elapsed() >= Duration::from_millis(35));
+    }
+
+    #[test]
+    fn test_parallel_walk_matches_sequential() {
+        let dir = tree();
+        for sub in ["x/y", "x/z", "w"] {
+            fs::create_dir_all(dir.path().join(sub)).unwrap();
+            fs::write(dir.path().join(sub).join("f"), "").unwrap();
+        }
+        std::os::unix::fs::symlink("a", dir.path().join("x/link")).unwrap();
+        let rule = PermissionRules {
+            path: dir.path().to_path_buf(),
+            expected_mode: 0o644,
+            recursive: true,
+            importance: Importance::Low,
+            id: None,
+            expected_dir_mode: Some(0o755),
+            mounts: None,
+            symlink_severity: None,
+        };
+        let sequential = rule.check_with(&mut HashSet::new(), &mut ScanControl::new());
+        let mut visited = HashSet::new();
+        let parallel = rule.check_with(&mut visited, &mut ScanControl::new().with_jobs(4));
+        let paths = |results: &[PermissionResults]| results.iter().map(|r| r.path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths(&parallel), paths(&sequential));
+        assert_eq!(visited.len(), 5);
+        // Throttled walks stay sequential
+        assert!(ScanControl::new().with_jobs(4).with_rate(10).pool().is_none());
+    }
+
+    #[test]
+    fn test_iterator_and_dropped_passing_results() {
+        let dir = tree();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/e"), "").unwrap();
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir.path().join("b"), fs::Permissions::from_mode(0o666)).unwrap();
+        let rule = PermissionRules {
+            path: dir.path().to_path_buf(),
+            expected_mode: 0o644,
+            recursive: true,
+            importance: Importance::Low,
+            id: None,
+            expected_dir_mode: None,
+            mounts: None,
+            symlink_severity: None,
+        };
+        let walked: Vec<_> = rule.check_iter().map(|r| r.path).collect();
+        let checked: Vec<_> = rule.check(&mut HashSet::new()).into_iter().map(|r| r.path).collect();
+        assert_eq!(walked, checked);
+        assert_eq!(walked.len(), 5);
+        assert_eq!(rule.check_iter().take(1).count(), 1);
+
+        // Passing results are streamed but not returned
+        let out = tempfile::NamedTempFile::new().unwrap();
+        let stream = ResultStream::new(out.reopen().unwrap());
+        let mut control = ScanControl::new().with_stream(stream).without_passing();
+        let kept = rule.check_with(&mut HashSet::new(), &mut control);
+        assert_eq!(kept.len(), 1);
+        assert!(kept[0].path.ends_with("b"));
+        control.finish().unwrap();
+        assert_eq!(fs::read_to_string(out.path()).unwrap().lines().count(), 5);
+    }
+
+    #[test]
+    fn test_limits_truncate_the_walk() {
+        let dir = tree();
+        let rule = PermissionRules {
+            path: dir.path().to_path_buf(),
+            expected_mode: 0o644,
+            recursive: true,
+            importance: Importance::Low,
+            id: Some("R1".into()),
+            expected_dir_mode: None,
+            mounts: None,
+            symlink_severity: None,
+        };
+        let limits = ScanLimits {
+            max_files: Some(2),
+            ..ScanLimits::default()
+        };
+        let mut control = ScanControl::new().with_jobs(4).with_limits(limits);
+        assert!(control.pool().is_none());
+        assert_eq!(rule.check_with(&mut HashSet::new(), &mut control).len(), 2);
+        // Each rule gets the full limit
+        assert_eq!(rule.check_with(&mut HashSet::new(), &mut control).len(), 2);
+        let truncation = &control.truncations()[0];
+        assert_eq!((truncation.limit, truncation.files), (ScanLimit::MaxFiles, 2));
+        assert_eq!(control.truncations().len(), 2);
+        assert!(truncation.to_string().contains("(R1) stopped at max files"));
+
+        // Only kept results count towards max_results
+        let limits = ScanLimits {
+            max_results: Some(1),
+            ..ScanLimits::default()
+        };
+        let mut control = ScanControl::new().without_passing().with_limits(limits);
+        assert!(rule.check_with(&mut HashSet::new(), &mut control).is_empty());
+        assert!(control.truncations().is_empty());
+
+        let limits: ScanLimits = toml::from_str("max_runtime = \"2m\"\nmax_files = 10").unwrap();
+        assert_eq!(limits.max_runtime, Some(Duration::from_secs(120)));
+        assert!(toml::from_str::<ScanLimits>("max_runtime = \"2 weeks\"").is_err());
+    }
+}
@@ -0,0 +1,579 @@
+//! Sudoers content policy audit for HALO.
+//!
+//! The built-in `UserConfig` target only checks that `/etc/sudoers` is mode 0440. This module
+//! parses `/etc/sudoers`, its include directives and `/etc/sudoers.d/*` to flag risky policy.
+//!
+//! # Checks
+//! - `sudoers-nopasswd-all`: `NOPASSWD` tag applied to the `ALL` command, directly or through a
+//!   `Cmnd_Alias` that expands to it
+//! - `sudoers-no-authenticate`: `Defaults !authenticate`
+//! - `sudoers-wildcard-command`: command specs or `Cmnd_Alias` entries containing wildcards
+//! - `sudoers-world-writable`: world-writable sudoers files, included files or include directories
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, SudoersAudit};
+//! let findings = SudoersAudit::default().run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{}: {}", f.path.display(), f.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::Severity;
+use crate::audit::sysroot;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Tags that may prefix a command in a sudoers user specification.
+const COMMAND_TAGS: &[&str] = &[
+    "NOPASSWD", "PASSWD", "NOEXEC", "EXEC", "SETENV", "NOSETENV", "LOG_INPUT", "NOLOG_INPUT",
+    "LOG_OUTPUT", "NOLOG_OUTPUT", "MAIL", "NOMAIL", "FOLLOW", "NOFOLLOW", "INTERCEPT",
+    "NOINTERCEPT",
+];
+
+/// Sudoers files audited for risky policy.
+///
+/// Defaults to `/etc/sudoers` and the files in `/etc/sudoers.d`.
+pub struct SudoersAudit {
+    pub sudoers: PathBuf,
+    pub sudoers_d: PathBuf,
+}
+
+/// Provides default paths for the sudoers policy files.
+impl Default for SudoersAudit {
+    fn default() -> Self {
+        Self {
+            sudoers: "/etc/sudoers".into(),
+            sudoers_d: "/etc/sudoers.d".into(),
+        }
+    }
+}
+
+impl AuditFindings for SudoersAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut visited = HashSet::new();
+        // Aliases defined in one file apply to the files read after it
+        let mut aliases = HashMap::new();
+        audit_file(&self.sudoers, None, &mut visited, &mut aliases, &mut findings);
+        // Files in sudoers.d are audited even if the main file does not include them
+        for file in include_dir_files(&self.sudoers_d) {
+            audit_file(&file, None, &mut visited, &mut aliases, &mut findings);
+        }
+        findings
+    }
+}
+
+/// An `#include`/`@include` or `#includedir`/`@includedir` directive found in a sudoers file.
+struct Include {
+    path: PathBuf,
+    dir: bool,
+    line: String,
+}
+
+/// Checks sudoers content for risky entries without following include directives.
+///
+/// # Arguments
+/// * `path` - Path reported in the findings
+/// * `content` - Contents of the sudoers file
+///
+/// # Returns
+/// Vector of failing `Finding`s, empty if no risky entries were found.
+pub fn check_sudoers_content(path: &Path, content: &str) -> Vec<Finding> {
+    scan(path, content, &mut HashMap::new()).0
+}
+
+/// `Cmnd_Alias` names mapped to the commands they list.
+type Aliases = HashMap<String, Vec<String>>;
+
+fn audit_file(
+    path: &Path,
+    included_by: Option<&str>,
+    visited: &mut HashSet<PathBuf>,
+    aliases: &mut Aliases,
+    findings: &mut Vec<Finding>,
+) {
+    if !visited.insert(path.to_path_buf()) {
+        return;
+    }
+    if let Some(finding) = world_writable(path, included_by) {
+        findings.push(finding);
+    }
+    let content = match sysroot::read_to_string(path) {
+        Ok(content) => content,
+        // No sudo installed, or a dangling include: nothing grants privileges
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            findings.push(Finding::pass("sudoers-policy", path, "Not present; nothing to audit"));
+            return;
+        }
+        Err(e) => {
+            findings.push(Finding::fail(
+                "sudoers-unreadable",
+                path,
+                Severity::Info,
+                format!("Cannot read sudoers file: {}", e),
+            ));
+            return;
+        }
+    };
+    let (file_findings, includes) = scan(path, &content, aliases);
+    if file_findings.is_empty() {
+        findings.push(Finding::pass(
+            "sudoers-policy",
+            path,
+            "No risky sudoers entries found",
+        ));
+    }
+    findings.extend(file_findings);
+
+    for include in includes {
+        if include.dir {
+            if let Some(finding) = world_writable(&include.path, Some(&include.line)) {
+                findings.push(finding);
+            }
+            for file in include_dir_files(&include.path) {
+                audit_file(&file, Some(&include.line), visited, aliases, findings);
+            }
+        } else {
+            audit_file(&include.path, Some(&include.line), visited, aliases, findings);
+        }
+    }
+}
+
+/// Reports a path as world-writable, if it is.
+fn world_writable(path: &Path, included_by: Option<&str>) -> Option<Finding> {
+    let meta = sysroot::metadata(path).ok()?;
+    if meta.mode() & 0o002 == 0 {
+        return None;
+    }
+    let finding = Finding::fail(
+        "sudoers-world-writable",
+        path,
+        Severity::Critical,
+        "World-writable sudoers policy lets any user grant themselves root",
+    );
+    Some(match included_by {
+        Some(line) => finding.with_subject(line),
+        None => finding,
+    })
+}
+
+/// Lists the files sudo would read from an include directory.
+///
+/// Like sudo, skips names ending in `~` or containing a `.`, and returns them in sorted order.
+fn include_dir_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match sysroot::read_dir(dir) {
+        Ok(entries) => entries
+            .into_iter()
+            .filter(|p| sysroot::is_file(p))
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| !n.ends_with('~') && !n.contains('.'))
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    files.sort();
+    files
+}
+
+/// Scans sudoers content and returns the failing findings plus any include directives.
+///
+/// `Cmnd_Alias` definitions are added to `aliases` before the user specifications are checked.
+fn scan(path: &Path, content: &str, aliases: &mut Aliases) -> (Vec<Finding>, Vec<Include>) {
+    let mut findings = Vec::new();
+    let mut includes = Vec::new();
+    let mut entries = Vec::new();
+
+    for line in logical_lines(content) {
+        if let Some(include) = parse_include(path, &line) {
+            includes.push(include);
+            continue;
+        }
+        let entry = strip_comment(&line).trim().to_string();
+        if entry.is_empty() {
+            continue;
+        }
+        if let Some(definitions) = entry.strip_prefix("Cmnd_Alias") {
+            for definition in split_top_level(definitions, ':') {
+                if let Some((name, commands)) = split_once_top_level(definition, '=') {
+                    let commands = split_top_level(commands, ',');
+                    aliases.insert(
+                        name.trim().to_string(),
+                        commands.iter().map(|c| c.trim().to_string()).collect(),
+                    );
+                }
+            }
+        }
+        entries.push(entry);
+    }
+
+    for entry in &entries {
+        let entry = entry.as_str();
+        if entry.starts_with("Defaults") {
+            let options = entry.split_once(char::is_whitespace).map_or("", |(_, o)| o);
+            if options.split(',').any(|o| o.trim() == "!authenticate") {
+                findings.push(
+                    Finding::fail(
+                        "sudoers-no-authenticate",
+                        path,
+                        Severity::High,
+                        "Defaults !authenticate disables password prompts for sudo",
+                    )
+                    .with_subject(entry),
+                );
+            }
+        } else if entry.starts_with("Cmnd_Alias") {
+            // Cmnd_Alias NAME = cmd, cmd : NAME2 = cmd
+            let definitions = entry.trim_start_matches("Cmnd_Alias");
+            for definition in split_top_level(definitions, ':') {
+                if let Some((_, commands)) = split_once_top_level(definition, '=')
+                    && split_top_level(commands, ',').iter().any(|c| is_wildcard(c.trim()))
+                {
+                    findings.push(wildcard_finding(path, entry));
+                    break;
+                }
+            }
+        } else if entry.starts_with("User_Alias")
+            || entry.starts_with("Runas_Alias")
+            || entry.starts_with("Host_Alias")
+        {
+            continue;
+        } else {
+            let mut wildcard = false;
+            let mut nopasswd_all = false;
+            for commands in command_lists(entry) {
+                // Tags do not carry over from one host group to the next
+                let mut nopasswd = false;
+                for command in split_top_level(&commands, ',') {
+                    let command = parse_command(command.trim(), &mut nopasswd);
+                    if nopasswd && grants_all(command, aliases, &mut HashSet::new()) {
+                        nopasswd_all = true;
+                    } else if !command.starts_with('!') && is_wildcard(command) {
+                        wildcard = true;
+                    }
+                }
+            }
+            if nopasswd_all {
+                findings.push(
+                    Finding::fail(
+                        "sudoers-nopasswd-all",
+                        path,
+                        Severity::High,
+                        "NOPASSWD grants password-less sudo for all commands",
+                    )
+                    .with_subject(entry),
+                );
+            }
+            if wildcard {
+                findings.push(wildcard_finding(path, entry));
+            }
+        }
+    }
+    (findings, includes)
+}
+
+fn wildcard_finding(path: &Path, entry: &str) -> Finding {
+    Finding::fail(
+        "sudoers-wildcard-command",
+        path,
+        Severity::Medium,
+        "Wildcard in command allows arguments or binaries beyond the intended ones",
+    )
+    .with_subject(entry)
+}
+
+/// Strips a leading runas spec and command tags, tracking whether `NOPASSWD` is in effect.
+///
+/// Tags carry over to the following commands in the same list until overridden.
+fn parse_command<'a>(mut command: &'a str, nopasswd: &mut bool) -> &'a str {
+    if command.starts_with('(')
+        && let Some(end) = command.find(')')
+    {
+        command = command[end + 1..].trim_start();
+    }
+    while let Some((tag, rest)) = command.split_once(':') {
+        let tag = tag.trim();
+        if !COMMAND_TAGS.contains(&tag) {
+            break;
+        }
+        match tag {
+            "NOPASSWD" => *nopasswd = true,
+            "PASSWD" => *nopasswd = false,
+            _ => {}
+        }
+        command = rest.trim_start();
+    }
+    command
+}
+
+/// Returns the command list of each `Host_List = Cmnd_Spec_List` group in a user specification.
+///
+/// Groups are separated by `:`, which also ends command tags, so a segment only starts a new
+/// group if it contains the `=` after a host list.
+fn command_lists(entry: &str) -> Vec<String> {
+    let mut lists: Vec<String> = Vec::new();
+    for segment in split_top_level(entry, ':') {
+        match split_once_top_level(segment, '=') {
+            Some((_, commands)) => lists.push(commands.to_string()),
+            None => {
+                if let Some(list) = lists.last_mut() {
+                    list.push(':');
+                    list.push_str(segment);
+                }
+            }
+        }
+    }
+    lists
+}
+
+/// Whether a command is `ALL` or a `Cmnd_Alias` that expands to `ALL`, following nested aliases.
+fn grants_all(command: &str, aliases: &Aliases, seen: &mut HashSet<String>) -> bool {
+    if command == "ALL" {
+        return true;
+    }
+    if !seen.insert(command.to_string()) {
+        return false;
+    }
+    aliases.get(command).is_some_and(|commands| {
+        commands.iter().any(|c| grants_all(c, aliases, seen))
+    })
+}
+
+/// Splits at each `sep` that is outside a runas spec's parentheses and not escaped.
+fn split_top_level(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut escaped = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if c == sep && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Splits at the first `sep` found by [`split_top_level`].
+fn split_once_top_level(s: &str, sep: char) -> Option<(&str, &str)> {
+    let head = *split_top_level(s, sep).first()?;
+    let rest = s.get(head.len() + sep.len_utf8()..)?;
+    Some((head, rest))
+}
+
+fn is_wildcard(command: &str) -> bool {
+    command != "ALL" && (command.contains('*') || command.contains('?'))
+}
+
+/// Parses an include directive, resolving relative paths against the including file's directory.
+fn parse_include(path: &Path, line: &str) -> Option<Include> {
+    let line = line.trim();
+    let (directive, target) = line.split_once(char::is_whitespace)?;
+    let dir = match directive {
+        "#include" | "@include" => false,
+        "#includedir" | "@includedir" => true,
+        _ => return None,
+    };
+    let target = PathBuf::from(target.trim().trim_matches('"'));
+    let target = if target.is_relative() {
+        path.parent().unwrap_or(Path::new("/")).join(target)
+    } else {
+        target
+    };
+    Some(Include {
+        path: target,
+        dir,
+        line: line.to_string(),
+    })
+}
+
+/// Removes a trailing comment, keeping `#` followed by a digit (a numeric uid/gid).
+fn strip_comment(line: &str) -> &str {
+    let bytes = line.as_bytes();
+    for (i, b) in bytes.iter().enumerate() {
+        if *b == b'#' && !bytes.get(i + 1).is_some_and(|n| n.is_ascii_digit()) {
+            return &line[..i];
+        }
+    }
+    line
+}
+
+/// Joins lines ending in a backslash with the line that follows, separated by a single space.
+///
+/// Shared with the udev rules parser, which uses the same continuation syntax.
+pub(crate) fn logical_lines(content: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for line in content.lines() {
+        let line = if current.is_empty() { line } else { line.trim_start() };
+        if let Some(stripped) = line.strip_suffix('\\') {
+            current.push_str(stripped.trim_end());
+            current.push(' ');
+        } else {
+            current.push_str(line);
+            lines.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checks(content: &str) -> Vec<String> {
+        check_sudoers_content(Path::new("/etc/sudoers"), content)
+            .into_iter()
+            .map(|f| f.check)
+            .collect()
+    }
+
+    #[test]
+    fn test_nopasswd_all() {
+        assert_eq!(
+            checks("alice ALL=(ALL:ALL) NOPASSWD: ALL\n"),
+            vec!["sudoers-nopasswd-all"]
+        );
+        // Tag carries over to the following command in the list
+        assert_eq!(
+            checks("bob ALL = NOPASSWD: /usr/bin/apt, ALL\n"),
+            vec!["sudoers-nopasswd-all"]
+        );
+        // PASSWD overrides the earlier tag
+        assert!(checks("bob ALL = NOPASSWD: /usr/bin/apt, PASSWD: ALL\n").is_empty());
+    }
+
+    #[test]
+    fn test_nopasswd_all_in_later_host_group() {
+        assert_eq!(
+            checks("alice ALL = (root) /bin/ls : db01 = (ALL:ALL) NOPASSWD: ALL\n"),
+            vec!["sudoers-nopasswd-all"]
+        );
+        // The tag does not carry over into the next group
+        assert!(checks("alice ALL = NOPASSWD: /bin/ls : db01 = ALL\n").is_empty());
+        assert_eq!(
+            checks("carol web = /bin/ls : db01 = /usr/bin/less /var/log/*\n"),
+            vec!["sudoers-wildcard-command"]
+        );
+    }
+
+    #[test]
+    fn test_nopasswd_alias_expanding_to_all() {
+        assert_eq!(
+            checks("Cmnd_Alias EVERYTHING = ALL\nbob ALL = NOPASSWD: EVERYTHING\n"),
+            vec!["sudoers-nopasswd-all"]
+        );
+        // Nested aliases, one of several definitions on the line
+        assert_eq!(
+            checks(
+                "Cmnd_Alias PKG = /usr/bin/apt : ANY = /bin/ls, EVERYTHING\n\
+                 Cmnd_Alias EVERYTHING = ALL\n\
+                 bob ALL = NOPASSWD: ANY\n"
+            ),
+            vec!["sudoers-nopasswd-all"]
+        );
+        assert!(checks("Cmnd_Alias PKG = /usr/bin/apt\nbob ALL = NOPASSWD: PKG\n").is_empty());
+        // Self-referencing aliases do not recurse forever
+        assert!(checks("Cmnd_Alias LOOP = LOOP\nbob ALL = NOPASSWD: LOOP\n").is_empty());
+        // Without NOPASSWD the alias is an ordinary admin rule
+        assert!(checks("Cmnd_Alias EVERYTHING = ALL\nbob ALL = EVERYTHING\n").is_empty());
+    }
+
+    #[test]
+    fn test_aliases_apply_to_later_files() {
+        let mut aliases = HashMap::new();
+        let (findings, _) = scan(
+            Path::new("/etc/sudoers"),
+            "Cmnd_Alias EVERYTHING = ALL\n",
+            &mut aliases,
+        );
+        assert!(findings.is_empty());
+        let (findings, _) = scan(
+            Path::new("/etc/sudoers.d/bob"),
+            "bob ALL = NOPASSWD: EVERYTHING\n",
+            &mut aliases,
+        );
+        assert_eq!(findings[0].check, "sudoers-nopasswd-all");
+    }
+
+    #[test]
+    fn test_plain_admin_rule_passes() {
+        assert!(checks("root ALL=(ALL:ALL) ALL\n%sudo ALL=(ALL:ALL) ALL\n").is_empty());
+    }
+
+    #[test]
+    fn test_no_authenticate() {
+        assert_eq!(
+            checks("Defaults:alice !authenticate\n"),
+            vec!["sudoers-no-authenticate"]
+        );
+        assert!(checks("Defaults env_reset, secure_path=\"/usr/bin\"\n").is_empty());
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert_eq!(
+            checks("carol ALL = /usr/bin/less /var/log/*\n"),
+            vec!["sudoers-wildcard-command"]
+        );
+        assert_eq!(
+            checks("Cmnd_Alias EDIT = /usr/bin/vi /etc/*\n"),
+            vec!["sudoers-wildcard-command"]
+        );
+    }
+
+    #[test]
+    fn test_comments_and_continuations() {
+        assert!(checks("# alice ALL=(ALL) NOPASSWD: ALL\n").is_empty());
+        assert_eq!(
+            checks("alice ALL=(ALL) \\\n    NOPASSWD: ALL # trailing comment\n"),
+            vec!["sudoers-nopasswd-all"]
+        );
+        // A '#' followed by digits is a uid, not a comment
+        assert_eq!(
+            checks("#1000 ALL=(ALL) NOPASSWD: ALL\n"),
+            vec!["sudoers-nopasswd-all"]
+        );
+    }
+
+    #[test]
+    fn test_missing_sudoers_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = SudoersAudit {
+            sudoers: dir.path().join("sudoers"),
+            sudoers_d: dir.path().join("sudoers.d"),
+        };
+        let findings = audit.run_audit();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].check, "sudoers-policy");
+        assert!(!findings[0].is_failure());
+    }
+
+    #[test]
+    fn test_includes_are_not_rules() {
+        let (findings, includes) = scan(
+            Path::new("/etc/sudoers"),
+            "@includedir /etc/sudoers.d\n#include extra\n",
+            &mut HashMap::new(),
+        );
+        assert!(findings.is_empty());
+        assert_eq!(includes.len(), 2);
+        assert!(includes[0].dir);
+        assert_eq!(includes[1].path, PathBuf::from("/etc/extra"));
+    }
+}
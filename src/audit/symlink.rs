@@ -5,7 +5,8 @@
 //! # Features
 //! - Check if a path is a symlink
 //! - Compare symlink target to an expected value
-//! - Report broken symlinks and errors
+//! - Report missing paths, broken symlinks, unexpected targets and non-symlinks as distinct issues
+//! - Per-rule severity override for failed checks
 //! - Return detailed results for use in CLI and library
 //!
 //! # Example Usage
@@ -15,96 +16,231 @@
 //! let rule = SymRule {
 //!     path: "/etc/ssl/certs/ca-certificates.crt".into(),
 //!     target_link: Some("/usr/lib/ssl/certs/ca-certificates.crt".into()),
+//!     severity_on_mismatch: None,
 //! };
 //! let result = check_symlink(&rule);
 //! println!("Target: {:?}, Pass: {}", result.target, result.pass);
 //! ```
+use crate::Severity;
+use crate::audit::context::AuditContext;
+use crate::audit::sysroot;
 use serde::{Deserialize, Serialize};
-use std::fs;
 use std::path::PathBuf;
 
 /// Represents a symlink audit rule for a file or directory.
 ///
 /// Used to specify the path of the symlink and the expected target (if any).
 /// If `target_link` is `None`, only existence and type are checked.
+/// If `severity_on_mismatch` is `None`, each [`SymlinkIssue`] uses its default severity.
 #[derive(Debug, Clone)]
 pub struct SymRule {
     pub path: PathBuf,
     pub target_link: Option<PathBuf>,
+    /// Severity to report for any failed check, overriding the per-issue default
+    pub severity_on_mismatch: Option<Severity>,
+}
+
+/// Kind of problem found by a symlink audit.
+///
+/// Each kind is reported as a separate finding with its own default severity.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SymlinkIssue {
+    /// Nothing exists at the audited path
+    Missing,
+    /// The symlink exists but its target does not resolve
+    Broken,
+    /// The symlink resolves to a different target than expected
+    UnexpectedTarget,
+    /// The path exists but is not a symlink
+    NotSymlink,
+}
+
+impl SymlinkIssue {
+    /// Default severity used when the rule does not set `severity_on_mismatch`.
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            SymlinkIssue::Missing => Severity::Low,
+            SymlinkIssue::Broken => Severity::Medium,
+            SymlinkIssue::UnexpectedTarget => Severity::High,
+            SymlinkIssue::NotSymlink => Severity::Medium,
+        }
+    }
 }
 
 /// Result of a symlink audit.
 ///
-/// Contains the actual and expected target, pass/fail status, and error info.
+/// Contains the actual and expected target, pass/fail status, the kind of issue found, and error info.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SymResult {
     pub path: PathBuf,
     pub target: Option<PathBuf>,
     pub target_link: Option<PathBuf>,
     pub pass: bool,
+    pub severity: Severity,
+    pub issue: Option<SymlinkIssue>,
     pub error: Option<String>,
 }
 
+impl SymRule {
+    /// Severity for the given issue, honoring the rule's `severity_on_mismatch` override.
+    pub fn severity_for(&self, issue: SymlinkIssue) -> Severity {
+        self.severity_on_mismatch
+            .clone()
+            .unwrap_or_else(|| issue.default_severity())
+    }
+
+    fn failed(&self, issue: SymlinkIssue, target: Option<PathBuf>, error: String) -> SymResult {
+        SymResult {
+            path: self.path.clone(),
+            target,
+            target_link: self.target_link.clone(),
+            pass: false,
+            severity: self.severity_for(issue),
+            issue: Some(issue),
+            error: Some(error),
+        }
+    }
+}
+
 /// Checks a symlink for existence and target match.
 ///
-/// Returns a `SymResult` with the actual target, expected target, pass/fail, and error details.
+/// Returns a `SymResult` with the actual target, expected target, pass/fail, the issue found, and error details.
+/// A missing path, a broken link, an unexpected target, and a non-symlink path are reported as distinct issues.
 ///
 /// # Arguments
-/// * `rule` - SymRule specifying path and expected target
+/// * `rule` - SymRule specifying path, expected target and optional severity override
 ///
 /// # Returns
 /// * `SymResult` - Result of the symlink audit
 pub fn check_symlink(rule: &SymRule) -> SymResult {
-    if !rule.path.exists() {
-        return SymResult {
-            path: rule.path.clone(),
-            target: None,
-            target_link: rule.target_link.clone(),
-            pass: false,
-            error: Some("Symlink not found".to_string()),
-        };
+    check_symlink_with(rule, &AuditContext::new())
+}
+
+/// Like [`check_symlink`], reading metadata through `context`, e.g. to share its cache with
+/// other rules.
+pub fn check_symlink_with(rule: &SymRule, context: &AuditContext) -> SymResult {
+    let meta = match context.symlink_metadata(&rule.path) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return rule.failed(SymlinkIssue::Missing, None, "Symlink not found".to_string());
+        }
+        Err(e) => {
+            return rule.failed(
+                SymlinkIssue::Missing,
+                None,
+                format!("Failed to get metadata: {}", e),
+            );
+        }
+    };
+    if !meta.file_type().is_symlink() {
+        return rule.failed(
+            SymlinkIssue::NotSymlink,
+            None,
+            "Path is not a symlink".to_string(),
+        );
     }
-    match fs::symlink_metadata(&rule.path) {
-        Ok(meta) => {
-            if meta.file_type().is_symlink() {
-                match fs::read_link(&rule.path) {
-                    Ok(target) => {
-                        let pass = match &rule.target_link {
-                            Some(expected) => &target == expected,
-                            None => true,
-                        };
-                        SymResult {
-                            path: rule.path.clone(),
-                            target: Some(target),
-                            target_link: rule.target_link.clone(),
-                            pass,
-                            error: None,
-                        }
-                    }
-                    Err(e) => SymResult {
-                        path: rule.path.clone(),
-                        target: None,
-                        target_link: rule.target_link.clone(),
-                        pass: false,
-                        error: Some(format!("Failed to read symlink target: {}", e)),
-                    },
-                }
-            } else {
-                SymResult {
-                    path: rule.path.clone(),
-                    target: None,
-                    target_link: rule.target_link.clone(),
-                    pass: false,
-                    error: Some("Path is not a symlink".to_string()),
-                }
-            }
+    let target = match sysroot::read_link(&rule.path) {
+        Ok(target) => target,
+        Err(e) => {
+            return rule.failed(
+                SymlinkIssue::Broken,
+                None,
+                format!("Failed to read symlink target: {}", e),
+            );
         }
-        Err(e) => SymResult {
-            path: rule.path.clone(),
-            target: None,
-            target_link: rule.target_link.clone(),
-            pass: false,
-            error: Some(format!("Failed to get metadata: {}", e)),
-        },
+    };
+    if let Some(expected) = &rule.target_link
+        && &target != expected
+    {
+        return rule.failed(
+            SymlinkIssue::UnexpectedTarget,
+            Some(target.clone()),
+            format!(
+                "Symlink points to {}, expected {}",
+                target.display(),
+                expected.display()
+            ),
+        );
+    }
+    // `exists` follows the link, so a dangling target shows up here
+    if context.metadata(&rule.path).is_err() {
+        return rule.failed(
+            SymlinkIssue::Broken,
+            Some(target.clone()),
+            format!("Symlink target {} does not exist", target.display()),
+        );
+    }
+    SymResult {
+        path: rule.path.clone(),
+        target: Some(target),
+        target_link: rule.target_link.clone(),
+        pass: true,
+        severity: Severity::None,
+        issue: None,
+        error: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::os::unix::fs::symlink;
+    use tempfile::tempdir;
+
+    fn rule(path: PathBuf, target_link: Option<PathBuf>) -> SymRule {
+        SymRule {
+            path,
+            target_link,
+            severity_on_mismatch: None,
+        }
+    }
+
+    #[test]
+    fn test_broken_symlink() {
+        let dir = tempdir().unwrap();
+        let link = dir.path().join("link");
+        symlink(dir.path().join("missing"), &link).unwrap();
+        let result = check_symlink(&rule(link, None));
+        assert!(!result.pass);
+        assert_eq!(result.issue, Some(SymlinkIssue::Broken));
+        assert_eq!(result.severity, Severity::Medium);
+    }
+
+    #[test]
+    fn test_unexpected_target() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target");
+        File::create(&target).unwrap();
+        let link = dir.path().join("link");
+        symlink(&target, &link).unwrap();
+        let result = check_symlink(&rule(link, Some("/somewhere/else".into())));
+        assert_eq!(result.issue, Some(SymlinkIssue::UnexpectedTarget));
+        assert_eq!(result.severity, Severity::High);
+    }
+
+    #[test]
+    fn test_not_symlink_with_override() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("file");
+        File::create(&file).unwrap();
+        let mut sym_rule = rule(file, None);
+        sym_rule.severity_on_mismatch = Some(Severity::Critical);
+        let result = check_symlink(&sym_rule);
+        assert_eq!(result.issue, Some(SymlinkIssue::NotSymlink));
+        assert_eq!(result.severity, Severity::Critical);
+    }
+
+    #[test]
+    fn test_valid_symlink_passes() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target");
+        File::create(&target).unwrap();
+        let link = dir.path().join("link");
+        symlink(&target, &link).unwrap();
+        let result = check_symlink(&rule(link, Some(target)));
+        assert!(result.pass);
+        assert_eq!(result.issue, None);
+        assert_eq!(result.severity, Severity::None);
     }
 }
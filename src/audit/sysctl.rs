@@ -0,0 +1,215 @@
+//! Sysctl kernel parameter audit for HALO.
+//!
+//! Reads kernel parameters from `/proc/sys` and compares them to a hardening profile.
+//! The built-in profile covers common kernel, filesystem and network hardening settings;
+//! individual keys can be overridden or added with `[[sysctl_rules]]` in a TOML config.
+//!
+//! Expected values are compared after normalizing whitespace, so multi-value parameters like
+//! `kernel.printk` can be written as `"4 4 1 7"`. A value prefixed with `>=` is compared
+//! numerically and passes for any value at least that large (e.g. `kernel.kptr_restrict`
+//! may be 1 or 2).
+//!
+//! # Example TOML
+//! ```toml
+//! [[sysctl_rules]]
+//! key = "net.ipv4.ip_forward"
+//! expected = "1" # this host is a router
+//! severity = "Low"
+//! ```
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, SysctlAudit};
+//! let findings = SysctlAudit::default().run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{}: {}", f.subject.as_deref().unwrap_or_default(), f.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::Severity;
+use crate::audit::sysroot;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Expected value of a single kernel parameter.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SysctlRule {
+    /// Parameter name in dotted form, e.g. `kernel.kptr_restrict`
+    pub key: String,
+    /// Expected value, or `>=N` for a numeric minimum
+    pub expected: String,
+    /// Severity reported when the value does not match
+    pub severity: Severity,
+}
+
+impl SysctlRule {
+    pub fn new(key: &str, expected: &str, severity: Severity) -> Self {
+        Self {
+            key: key.to_string(),
+            expected: expected.to_string(),
+            severity,
+        }
+    }
+
+    /// Returns true if `actual` satisfies the expected value.
+    pub fn matches(&self, actual: &str) -> bool {
+        if let Some(min) = self.expected.trim().strip_prefix(">=") {
+            return match (min.trim().parse::<i64>(), actual.trim().parse::<i64>()) {
+                (Ok(min), Ok(value)) => value >= min,
+                _ => false,
+            };
+        }
+        let normalize = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+        normalize(&self.expected) == normalize(actual)
+    }
+}
+
+/// The built-in kernel hardening profile.
+pub fn hardening_profile() -> Vec<SysctlRule> {
+    vec![
+        SysctlRule::new("kernel.kptr_restrict", ">=1", Severity::Medium),
+        SysctlRule::new("kernel.dmesg_restrict", "1", Severity::Low),
+        SysctlRule::new("kernel.randomize_va_space", "2", Severity::High),
+        SysctlRule::new("kernel.yama.ptrace_scope", ">=1", Severity::Medium),
+        SysctlRule::new("kernel.unprivileged_bpf_disabled", ">=1", Severity::Medium),
+        SysctlRule::new("kernel.sysrq", "0", Severity::Low),
+        SysctlRule::new("fs.protected_symlinks", "1", Severity::High),
+        SysctlRule::new("fs.protected_hardlinks", "1", Severity::High),
+        SysctlRule::new("fs.protected_fifos", ">=1", Severity::Low),
+        SysctlRule::new("fs.protected_regular", ">=1", Severity::Low),
+        SysctlRule::new("fs.suid_dumpable", "0", Severity::Medium),
+        SysctlRule::new("net.ipv4.ip_forward", "0", Severity::Medium),
+        SysctlRule::new("net.ipv4.conf.all.accept_redirects", "0", Severity::Medium),
+        SysctlRule::new("net.ipv4.conf.all.send_redirects", "0", Severity::Medium),
+        SysctlRule::new("net.ipv4.conf.all.accept_source_route", "0", Severity::Medium),
+        SysctlRule::new("net.ipv4.conf.all.rp_filter", ">=1", Severity::Low),
+        SysctlRule::new("net.ipv4.tcp_syncookies", "1", Severity::Medium),
+        SysctlRule::new("net.ipv4.icmp_echo_ignore_broadcasts", "1", Severity::Low),
+        SysctlRule::new("net.ipv6.conf.all.accept_redirects", "0", Severity::Medium),
+    ]
+}
+
+/// Kernel parameters checked against a hardening profile.
+pub struct SysctlAudit {
+    /// Root of the sysctl tree, normally `/proc/sys`
+    pub root: PathBuf,
+    pub rules: Vec<SysctlRule>,
+}
+
+/// Uses `/proc/sys` and the built-in hardening profile.
+impl Default for SysctlAudit {
+    fn default() -> Self {
+        Self {
+            root: "/proc/sys".into(),
+            rules: hardening_profile(),
+        }
+    }
+}
+
+impl AuditFindings for SysctlAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        self.rules
+            .iter()
+            .map(|rule| {
+                let path = self.key_path(&rule.key);
+                match sysroot::read_to_string(&path) {
+                    Ok(actual) => check_sysctl_value(&path, rule, &actual),
+                    Err(e) => Finding::fail(
+                        "sysctl-unavailable",
+                        &path,
+                        Severity::Info,
+                        format!("Cannot read kernel parameter: {}", e),
+                    )
+                    .with_subject(&rule.key),
+                }
+            })
+            .collect()
+    }
+}
+
+impl SysctlAudit {
+    /// Replaces profile rules with the same key and appends new ones.
+    pub fn with_overrides(mut self, overrides: Vec<SysctlRule>) -> Self {
+        for rule in overrides {
+            match self.rules.iter_mut().find(|r| r.key == rule.key) {
+                Some(existing) => *existing = rule,
+                None => self.rules.push(rule),
+            }
+        }
+        self
+    }
+
+    /// Path of a dotted parameter name below `root`.
+    pub fn key_path(&self, key: &str) -> PathBuf {
+        self.root.join(key.replace('.', "/"))
+    }
+}
+
+/// Compares a parameter's current value with its rule.
+///
+/// # Arguments
+/// * `path` - Path reported in the finding
+/// * `rule` - Expected value and severity
+/// * `actual` - Current value as read from `/proc/sys`
+pub fn check_sysctl_value(path: &Path, rule: &SysctlRule, actual: &str) -> Finding {
+    let actual = actual.trim();
+    let finding = if rule.matches(actual) {
+        Finding::pass("sysctl", path, format!("{} = {}", rule.key, actual))
+    } else {
+        Finding::fail(
+            "sysctl",
+            path,
+            rule.severity.clone(),
+            format!("{} = {} (expected: {})", rule.key, actual, rule.expected),
+        )
+    };
+    finding.with_subject(&rule.key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::Status;
+
+    #[test]
+    fn test_exact_and_minimum_values() {
+        let exact = SysctlRule::new("kernel.printk", "4 4 1 7", Severity::Low);
+        assert!(exact.matches("4\t4\t1\t7\n"));
+        assert!(!exact.matches("7 4 1 7"));
+
+        let minimum = SysctlRule::new("kernel.kptr_restrict", ">=1", Severity::Medium);
+        assert!(minimum.matches("2"));
+        assert!(!minimum.matches("0"));
+        assert!(!minimum.matches("not a number"));
+    }
+
+    #[test]
+    fn test_overrides_replace_and_extend() {
+        let audit = SysctlAudit::default().with_overrides(vec![
+            SysctlRule::new("net.ipv4.ip_forward", "1", Severity::Low),
+            SysctlRule::new("vm.mmap_min_addr", ">=65536", Severity::Medium),
+        ]);
+        assert_eq!(audit.rules.len(), hardening_profile().len() + 1);
+        let forward = audit.rules.iter().find(|r| r.key == "net.ipv4.ip_forward").unwrap();
+        assert_eq!(forward.expected, "1");
+    }
+
+    #[test]
+    fn test_audit_reads_root() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("fs")).unwrap();
+        fs::write(dir.path().join("fs/protected_symlinks"), "0\n").unwrap();
+        let audit = SysctlAudit {
+            root: dir.path().to_path_buf(),
+            rules: vec![
+                SysctlRule::new("fs.protected_symlinks", "1", Severity::High),
+                SysctlRule::new("fs.protected_hardlinks", "1", Severity::High),
+            ],
+        };
+        let findings = audit.run_audit();
+        assert_eq!(findings[0].status, Status::Fail);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[1].check, "sysctl-unavailable");
+    }
+}
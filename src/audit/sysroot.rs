@@ -0,0 +1,312 @@
+//! Auditing an alternate root filesystem.
+//!
+//! Golden-image pipelines and incident response audit filesystems that are not the running
+//! system: a mounted container image, a chroot, a rescued disk. With an alternate root set
+//! (`halo --root /mnt/image`, or [`Audit::root`](crate::Audit::root)), every audit reads
+//! `/etc/shadow` as `/mnt/image/etc/shadow` and reports it as `/etc/shadow`.
+//!
+//! The functions here mirror [`std::fs`] for such logical paths and are what audits use for
+//! all file access. Symlinks are resolved as they would be inside the root: an absolute link
+//! target starts again at the root and `..` never climbs above it, so a link in the image
+//! pointing at `/etc/passwd` is read from the image, never from the host. Without a root the
+//! functions are plain [`std::fs`] calls.
+//!
+//! [`set_root`] sets the root for the whole process, like the working directory. [`enter`]
+//! sets it for the current thread only, until the returned guard is dropped, so audits of
+//! different roots can run concurrently on separate threads: [`Audit::run`](crate::Audit::run)
+//! enters its root on the calling thread, and the [`AuditContext`](crate::audit::context::AuditContext)
+//! of the run carries it to the threads of a parallel walk.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::audit::sysroot;
+//! use std::path::Path;
+//! let image = tempfile::tempdir().unwrap();
+//! std::fs::create_dir(image.path().join("etc")).unwrap();
+//! std::fs::write(image.path().join("etc/hostname"), "golden\n").unwrap();
+//! let _root = sysroot::enter(Some(image.path().to_path_buf()));
+//! assert_eq!(sysroot::read_to_string(Path::new("/etc/hostname")).unwrap(), "golden\n");
+//! ```
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs::{self, Metadata};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+
+// Symlinks followed while resolving one path before giving up, as in Linux (ELOOP)
+const MAX_SYMLINKS: usize = 40;
+
+static ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+thread_local! {
+    // Root entered on this thread; `None` when no scope is entered and `ROOT` applies
+    static ENTERED: RefCell<Option<Option<PathBuf>>> = const { RefCell::new(None) };
+}
+
+/// Sets the alternate root for all threads of this process that have not [entered](enter)
+/// one; `None` audits the running system.
+pub fn set_root(root: Option<PathBuf>) {
+    *ROOT.write().unwrap_or_else(|e| e.into_inner()) = root;
+}
+
+/// The alternate root of the current thread, if one is set.
+pub fn root() -> Option<PathBuf> {
+    ENTERED
+        .with_borrow(Clone::clone)
+        .unwrap_or_else(|| ROOT.read().unwrap_or_else(|e| e.into_inner()).clone())
+}
+
+/// Restores the previous root of the thread when dropped, see [`enter`].
+#[must_use = "the root is restored when the guard is dropped"]
+pub struct RootGuard {
+    previous: Option<Option<PathBuf>>,
+    // Restores the thread it was created on
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for RootGuard {
+    fn drop(&mut self) {
+        ENTERED.set(self.previous.take());
+    }
+}
+
+/// Sets the alternate root of the current thread until the returned guard is dropped; `None`
+/// audits the running system whatever [`set_root`] set.
+pub fn enter(root: Option<PathBuf>) -> RootGuard {
+    RootGuard {
+        previous: ENTERED.replace(Some(root)),
+        _thread: PhantomData,
+    }
+}
+
+// Resolves `path` inside `root` to a logical absolute path without symlinks (except the last
+// component unless `follow`)
+pub(crate) fn resolve_in(root: &Path, path: &Path, follow: bool) -> io::Result<PathBuf> {
+    let mut pending: VecDeque<OsString> = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_os_string()),
+            Component::ParentDir => Some("..".into()),
+            _ => None,
+        })
+        .collect();
+    let mut resolved = PathBuf::from("/");
+    let mut links = 0;
+    while let Some(name) = pending.pop_front() {
+        if name == ".." {
+            resolved.pop();
+            continue;
+        }
+        let candidate = resolved.join(&name);
+        let is_last = pending.is_empty();
+        let target = if is_last && !follow {
+            None
+        } else {
+            fs::read_link(host(root, &candidate)).ok()
+        };
+        let Some(target) = target else {
+            resolved = candidate;
+            continue;
+        };
+        links += 1;
+        if links > MAX_SYMLINKS {
+            return Err(io::Error::other(format!(
+                "Too many levels of symbolic links: {}",
+                path.display()
+            )));
+        }
+        if target.is_absolute() {
+            resolved = PathBuf::from("/");
+        }
+        for component in target.components().rev() {
+            match component {
+                Component::Normal(part) => pending.push_front(part.to_os_string()),
+                Component::ParentDir => pending.push_front("..".into()),
+                _ => {}
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+// Host path of a logical path without symlinks
+pub(crate) fn host(root: &Path, logical: &Path) -> PathBuf {
+    root.join(logical.strip_prefix("/").unwrap_or(logical))
+}
+
+// Host path to access `path` at, following a final symlink if `follow`
+fn host_path(path: &Path, follow: bool) -> io::Result<PathBuf> {
+    match root() {
+        Some(root) => Ok(host(&root, &resolve_in(&root, path, follow)?)),
+        None => Ok(path.to_path_buf()),
+    }
+}
+
+/// Where `path` is on this machine: below the root if one is set, symlinks resolved inside
+/// it; `path` itself otherwise.
+pub fn host_path_of(path: &Path) -> PathBuf {
+    host_path(path, true).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Like [`fs::read_to_string`].
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    fs::read_to_string(host_path(path, true)?)
+}
+
+/// Like [`fs::read`].
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    fs::read(host_path(path, true)?)
+}
+
+/// Like [`fs::metadata`]: follows symlinks, inside the root.
+pub fn metadata(path: &Path) -> io::Result<Metadata> {
+    fs::metadata(host_path(path, true)?)
+}
+
+/// Like [`fs::symlink_metadata`].
+pub fn symlink_metadata(path: &Path) -> io::Result<Metadata> {
+    fs::symlink_metadata(host_path(path, false)?)
+}
+
+/// Like [`fs::read_link`]; the target is returned as written in the link.
+pub fn read_link(path: &Path) -> io::Result<PathBuf> {
+    fs::read_link(host_path(path, false)?)
+}
+
+/// Like [`fs::canonicalize`], as a logical path inside the root.
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    match root() {
+        Some(root) => {
+            let resolved = resolve_in(&root, path, true)?;
+            fs::symlink_metadata(host(&root, &resolved))?;
+            Ok(resolved)
+        }
+        None => fs::canonicalize(path),
+    }
+}
+
+/// Like [`fs::set_permissions`].
+pub fn set_permissions(path: &Path, permissions: fs::Permissions) -> io::Result<()> {
+    fs::set_permissions(host_path(path, true)?, permissions)
+}
+
+/// Like [`std::os::unix::fs::chown`].
+pub fn chown(path: &Path, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+    std::os::unix::fs::chown(host_path(path, true)?, uid, gid)
+}
+
+/// A path opened without following it, see [`open_nofollow`].
+#[derive(Debug)]
+pub struct PathHandle {
+    file: fs::File,
+}
+
+/// Opens `path` itself with `O_PATH | O_NOFOLLOW`, without reading it.
+///
+/// A symlink is opened as the link, not its target. Stat'ing and changing the handle acts on
+/// the inode that was opened, so a symlink swapped in for the path afterwards cannot redirect
+/// a `chmod` or `chown` to another file.
+pub fn open_nofollow(path: &Path) -> io::Result<PathHandle> {
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_PATH | libc::O_NOFOLLOW | libc::O_CLOEXEC)
+        .open(host_path(path, false)?)?;
+    Ok(PathHandle { file })
+}
+
+impl PathHandle {
+    /// Metadata of the opened inode; a symlink's own.
+    pub fn metadata(&self) -> io::Result<Metadata> {
+        self.file.metadata()
+    }
+
+    /// Sets the permission bits of the opened inode.
+    pub fn set_mode(&self, mode: u32) -> io::Result<()> {
+        // fchmod(2) rejects O_PATH descriptors; the /proc link resolves to the opened inode
+        let fd = format!("/proc/self/fd/{}", self.file.as_raw_fd());
+        fs::set_permissions(fd, fs::Permissions::from_mode(mode))
+    }
+
+    /// Changes the owner and/or group of the opened inode; `None` leaves it as it is.
+    pub fn chown(&self, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+        let (uid, gid) = (uid.unwrap_or(u32::MAX), gid.unwrap_or(u32::MAX));
+        // SAFETY: the descriptor is open and the path is an empty NUL-terminated string
+        let ret = unsafe { libc::fchownat(self.file.as_raw_fd(), c"".as_ptr(), uid, gid, libc::AT_EMPTY_PATH) };
+        if ret == 0 { Ok(()) } else { Err(io::Error::last_os_error()) }
+    }
+}
+
+/// Like [`Path::exists`].
+pub fn exists(path: &Path) -> bool {
+    metadata(path).is_ok()
+}
+
+/// Like [`Path::is_file`].
+pub fn is_file(path: &Path) -> bool {
+    metadata(path).is_ok_and(|m| m.is_file())
+}
+
+/// Like [`Path::is_dir`].
+pub fn is_dir(path: &Path) -> bool {
+    metadata(path).is_ok_and(|m| m.is_dir())
+}
+
+/// Paths of the entries of the directory `path`, as `path` joined with each name; entries
+/// that cannot be read are skipped.
+pub fn read_dir(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    read_dir_into(path, &mut entries)?;
+    Ok(entries)
+}
+
+/// Like [`read_dir`], appending the entries to `entries` so a walk can reuse one buffer.
+pub fn read_dir_into(path: &Path, entries: &mut Vec<PathBuf>) -> io::Result<()> {
+    entries.extend(fs::read_dir(host_path(path, true)?)?.flatten().map(|entry| path.join(entry.file_name())));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn test_resolve_inside_root() {
+        let image = tempfile::tempdir().unwrap();
+        let root = image.path();
+        fs::create_dir_all(root.join("etc/alternatives")).unwrap();
+        fs::create_dir_all(root.join("usr/etc")).unwrap();
+        fs::write(root.join("etc/passwd"), "root:x:0:0::/root:/bin/sh\n").unwrap();
+        // Absolute and escaping links stay inside the image
+        symlink("/etc/passwd", root.join("etc/alternatives/passwd")).unwrap();
+        symlink("../../../../etc/passwd", root.join("usr/etc/passwd")).unwrap();
+        symlink("/usr/etc", root.join("etc/linked")).unwrap();
+        symlink("loop", root.join("etc/loop")).unwrap();
+
+        assert_eq!(
+            resolve_in(root, Path::new("/etc/alternatives/passwd"), true).unwrap(),
+            Path::new("/etc/passwd")
+        );
+        assert_eq!(resolve_in(root, Path::new("/usr/etc/passwd"), true).unwrap(), Path::new("/etc/passwd"));
+        assert_eq!(
+            resolve_in(root, Path::new("/etc/linked/passwd"), false).unwrap(),
+            Path::new("/usr/etc/passwd")
+        );
+        assert_eq!(resolve_in(root, Path::new("/../etc/./passwd"), true).unwrap(), Path::new("/etc/passwd"));
+        assert!(resolve_in(root, Path::new("/etc/loop"), true).is_err());
+        assert_eq!(host(root, Path::new("/etc/passwd")), root.join("etc/passwd"));
+        assert!(super::root().is_none());
+        assert!(is_file(&root.join("etc/passwd")));
+        // An entered root applies to this thread only
+        let guard = enter(Some(root.to_path_buf()));
+        assert!(is_file(Path::new("/usr/etc/passwd")));
+        assert!(std::thread::spawn(super::root).join().unwrap().is_none());
+        drop(guard);
+        assert!(super::root().is_none());
+    }
+}
@@ -0,0 +1,395 @@
+//! TLS certificate and private key audit for HALO.
+//!
+//! Walks certificate directories such as `/etc/ssl` and `/etc/letsencrypt`, parses every PEM
+//! certificate and reports those expiring within `warn_days`, with a severity that rises as
+//! the expiry date approaches. PEM private keys must be mode 600 (or stricter) and owned by
+//! root.
+//!
+//! CA certificates (`basicConstraints CA:TRUE`) are skipped: the system trust store is
+//! maintained by the distribution and an expiring root is not something the host's
+//! administrator renews. `/etc/letsencrypt/archive` is skipped as well, since it keeps every
+//! superseded certificate; the current ones are reached through the `live` symlinks.
+//!
+//! # Checks
+//! - `tls-cert-expiry`: one finding per certificate; Critical once expired, High within 7
+//!   days, Medium within 14 days, Low within `warn_days`
+//! - `tls-key-mode`: private key readable by group (Medium) or others (High)
+//! - `tls-key-owner`: private key not owned by root
+//! - `tls-cert-unparsable`: a PEM certificate block could not be decoded
+//!
+//! # Example TOML
+//! ```toml
+//! [tls]
+//! dirs = ["/etc/ssl", "/etc/letsencrypt", "/srv/app/certs"]
+//! warn_days = 21
+//! ```
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, TlsAudit};
+//! let findings = TlsAudit::default().run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{}: {}", f.path.display(), f.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::Severity;
+use crate::audit::sysroot;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Files larger than this are not read.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+const DAY: i64 = 86_400;
+
+/// Certificate directories and the expiry warning window.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TlsAudit {
+    /// Directories searched recursively for PEM certificates and keys
+    pub dirs: Vec<PathBuf>,
+    /// Directories below `dirs` that are not searched
+    pub skip_dirs: Vec<PathBuf>,
+    /// Certificates expiring within this many days are reported
+    pub warn_days: u32,
+}
+
+/// Searches `/etc/ssl`, `/etc/pki/tls` and `/etc/letsencrypt` with a 30 day window.
+impl Default for TlsAudit {
+    fn default() -> Self {
+        Self {
+            dirs: vec!["/etc/ssl".into(), "/etc/pki/tls".into(), "/etc/letsencrypt".into()],
+            skip_dirs: vec!["/etc/letsencrypt/archive".into()],
+            warn_days: 30,
+        }
+    }
+}
+
+impl AuditFindings for TlsAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let mut files = Vec::new();
+        let mut seen = HashSet::new();
+        for dir in &self.dirs {
+            self.collect(dir, &mut seen, &mut files);
+        }
+        files.sort();
+
+        let mut findings = Vec::new();
+        for path in files {
+            let Ok(content) = sysroot::read_to_string(&path) else {
+                continue;
+            };
+            if content.contains("PRIVATE KEY-----")
+                && let Ok(meta) = sysroot::metadata(&path)
+            {
+                findings.extend(check_key(&path, meta.mode(), meta.uid()));
+            }
+            for block in pem_blocks(&content, "CERTIFICATE") {
+                findings.push(match decode_base64(&block).and_then(|der| parse_certificate(&der)) {
+                    Some(cert) if cert.is_ca => continue,
+                    Some(cert) => check_certificate(&path, &cert, now, self.warn_days),
+                    None => Finding::fail(
+                        "tls-cert-unparsable",
+                        &path,
+                        Severity::Info,
+                        "Certificate could not be decoded",
+                    ),
+                });
+            }
+        }
+        findings
+    }
+}
+
+impl TlsAudit {
+    /// Collects regular files below `dir`, following file symlinks but not directory ones.
+    fn collect(&self, dir: &Path, seen: &mut HashSet<PathBuf>, files: &mut Vec<PathBuf>) {
+        if self.skip_dirs.iter().any(|s| s == dir) {
+            return;
+        }
+        let Ok(entries) = sysroot::read_dir(dir) else {
+            return;
+        };
+        for path in entries {
+            let Ok(link_meta) = sysroot::symlink_metadata(&path) else {
+                continue;
+            };
+            if link_meta.is_dir() {
+                self.collect(&path, seen, files);
+                continue;
+            }
+            // Certificates are often linked from several places; check each file once
+            let Ok(meta) = sysroot::metadata(&path) else {
+                continue;
+            };
+            let canonical = sysroot::canonicalize(&path).unwrap_or_else(|_| path.clone());
+            if meta.is_file() && meta.len() <= MAX_FILE_SIZE && seen.insert(canonical) {
+                files.push(path);
+            }
+        }
+    }
+}
+
+/// Fields of an X.509 certificate used by the audit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CertificateInfo {
+    /// Common name of the subject, if present
+    pub subject: Option<String>,
+    /// Expiry as seconds since the Unix epoch
+    pub not_after: i64,
+    /// Whether the certificate is a CA (`basicConstraints CA:TRUE`)
+    pub is_ca: bool,
+}
+
+/// Checks a certificate's expiry date.
+///
+/// # Arguments
+/// * `path` - File holding the certificate
+/// * `cert` - Parsed certificate
+/// * `now` - Current time as seconds since the Unix epoch
+/// * `warn_days` - Certificates expiring within this many days fail
+pub fn check_certificate(path: &Path, cert: &CertificateInfo, now: i64, warn_days: u32) -> Finding {
+    let name = cert.subject.clone().unwrap_or_else(|| "certificate".to_string());
+    let days_left = (cert.not_after - now).div_euclid(DAY);
+    let severity = match days_left {
+        d if d < 0 => Severity::Critical,
+        d if d < 7 => Severity::High,
+        d if d < 14 => Severity::Medium,
+        d if d < i64::from(warn_days) => Severity::Low,
+        _ => {
+            return Finding::pass(
+                "tls-cert-expiry",
+                path,
+                format!("{} is valid for {} more days", name, days_left),
+            )
+            .with_subject(name);
+        }
+    };
+    let message = if days_left < 0 {
+        format!("{} expired {} days ago", name, -days_left)
+    } else {
+        format!("{} expires in {} days", name, days_left)
+    };
+    Finding::fail("tls-cert-expiry", path, severity, message).with_subject(name)
+}
+
+/// Checks the mode and owner of a private key file.
+pub fn check_key(path: &Path, mode: u32, uid: u32) -> Vec<Finding> {
+    let mode = mode & 0o7777;
+    let mode_finding = if mode & 0o077 == 0 {
+        Finding::pass("tls-key-mode", path, format!("Private key is mode {:o}", mode))
+    } else {
+        let severity = if mode & 0o007 != 0 { Severity::High } else { Severity::Medium };
+        Finding::fail(
+            "tls-key-mode",
+            path,
+            severity,
+            format!("Private key is mode {:o}; expected 600", mode),
+        )
+    };
+    let owner_finding = if uid == 0 {
+        Finding::pass("tls-key-owner", path, "Private key is owned by root")
+    } else {
+        Finding::fail(
+            "tls-key-owner",
+            path,
+            Severity::Medium,
+            format!("Private key is owned by UID {}; expected root", uid),
+        )
+    };
+    vec![mode_finding, owner_finding]
+}
+
+/// Base64 bodies of the `-----BEGIN <label>-----` blocks in `content`.
+fn pem_blocks(content: &str, label: &str) -> Vec<String> {
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    let mut blocks = Vec::new();
+    let mut current: Option<String> = None;
+    for line in content.lines().map(str::trim) {
+        if line == begin {
+            current = Some(String::new());
+        } else if line == end {
+            blocks.extend(current.take());
+        } else if let Some(body) = current.as_mut() {
+            body.push_str(line);
+        }
+    }
+    blocks
+}
+
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in input.bytes().filter(|c| !c.is_ascii_whitespace() && *c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// Splits one DER element off `data`: (tag, content, rest).
+fn der_element(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *data.first()?;
+    let first = *data.get(1)?;
+    let (len, header) = if first & 0x80 == 0 {
+        (usize::from(first), 2)
+    } else {
+        let count = usize::from(first & 0x7f);
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let len = data.get(2..2 + count)?.iter().fold(0usize, |acc, b| (acc << 8) | usize::from(*b));
+        (len, 2 + count)
+    };
+    let content = data.get(header..header + len)?;
+    Some((tag, content, &data[header + len..]))
+}
+
+/// Extracts subject common name, expiry and CA flag from a DER certificate.
+pub fn parse_certificate(der: &[u8]) -> Option<CertificateInfo> {
+    let (_, certificate, _) = der_element(der)?;
+    let (_, tbs, _) = der_element(certificate)?;
+    let mut fields = Vec::new();
+    let mut rest = tbs;
+    while !rest.is_empty() {
+        let (tag, content, next) = der_element(rest)?;
+        fields.push((tag, content));
+        rest = next;
+    }
+    // Skip the optional explicit version: serial, signature, issuer, validity, subject
+    let offset = usize::from(fields.first()?.0 == 0xa0);
+    let (_, validity) = *fields.get(offset + 3)?;
+    let (_, subject) = *fields.get(offset + 4)?;
+    let (_, _, after) = der_element(validity)?;
+    let (tag, not_after, _) = der_element(after)?;
+    let extensions = fields.iter().find(|(tag, _)| *tag == 0xa3).map(|(_, c)| *c);
+    Some(CertificateInfo {
+        subject: common_name(subject),
+        not_after: parse_time(tag, not_after)?,
+        is_ca: extensions.is_some_and(is_ca),
+    })
+}
+
+/// Value of the first commonName attribute (OID 2.5.4.3) in a Name.
+fn common_name(name: &[u8]) -> Option<String> {
+    const CN: &[u8] = &[0x06, 0x03, 0x55, 0x04, 0x03];
+    let start = name.windows(CN.len()).position(|w| w == CN)? + CN.len();
+    let (_, value, _) = der_element(&name[start..])?;
+    Some(String::from_utf8_lossy(value).into_owned())
+}
+
+/// Whether the extensions contain `basicConstraints` with `cA` set.
+fn is_ca(extensions: &[u8]) -> bool {
+    const BASIC_CONSTRAINTS: &[u8] = &[0x06, 0x03, 0x55, 0x1d, 0x13];
+    let Some(start) = extensions.windows(BASIC_CONSTRAINTS.len()).position(|w| w == BASIC_CONSTRAINTS) else {
+        return false;
+    };
+    let mut rest = &extensions[start + BASIC_CONSTRAINTS.len()..];
+    // Optional critical flag, then the OCTET STRING wrapping the constraints
+    while let Some((tag, content, next)) = der_element(rest) {
+        match tag {
+            0x01 => rest = next,
+            0x04 => {
+                return der_element(content)
+                    .and_then(|(_, constraints, _)| der_element(constraints))
+                    .is_some_and(|(tag, value, _)| tag == 0x01 && value.first().is_some_and(|v| *v != 0));
+            }
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Parses a UTCTime (`YYMMDDHHMMSSZ`) or GeneralizedTime (`YYYYMMDDHHMMSSZ`).
+fn parse_time(tag: u8, value: &[u8]) -> Option<i64> {
+    let text = std::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        0x17 => {
+            let yy: i64 = text.get(..2)?.parse().ok()?;
+            (if yy >= 50 { 1900 + yy } else { 2000 + yy }, text.get(2..)?)
+        }
+        0x18 => (text.get(..4)?.parse().ok()?, text.get(4..)?),
+        _ => return None,
+    };
+    let field = |i: usize| -> Option<i64> { rest.get(i..i + 2)?.parse().ok() };
+    let days = days_from_civil(year, field(0)?, field(2)?);
+    Some(days * DAY + field(4)? * 3600 + field(6)? * 60 + field(8)?)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Self-signed leaf certificate for CN=halo.test, valid until 2026-11-15 11:51:08 UTC.
+    const CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIBejCCASCgAwIBAgIUWqldHkMYoQA0yEXxiV12FCPiA0wwCgYIKoZIzj0EAwIw
+FDESMBAGA1UEAwwJaGFsby50ZXN0MB4XDTI2MTAxNjExNTEwOFoXDTI2MTExNTEx
+NTEwOFowFDESMBAGA1UEAwwJaGFsby50ZXN0MFkwEwYHKoZIzj0CAQYIKoZIzj0D
+AQcDQgAESRsQltjSqAzYcT+Mm5z22Jds9Av5d8BR9GHRTZZaKoCai5m/Kejr/p4A
+HEu2BJ8tW7E7Zrkx2gdRjRo4b3gIjKNQME4wHQYDVR0OBBYEFOWipfhzb0VyBtqE
+Pr6gc5wgykW8MB8GA1UdIwQYMBaAFOWipfhzb0VyBtqEPr6gc5wgykW8MAwGA1Ud
+EwEB/wQCMAAwCgYIKoZIzj0EAwIDSAAwRQIhAJVtgzHdToAAxhjZnvCv6SKhUtC/
+gk/4BrYtBpwC1bftAiAwNpGb4CbNool53cPUf9UAS0PNYY9Q/ObB4cHcn7jWmw==
+-----END CERTIFICATE-----
+";
+
+    #[test]
+    fn test_parse_certificate_and_expiry() {
+        let der = decode_base64(&pem_blocks(CERT, "CERTIFICATE")[0]).unwrap();
+        let cert = parse_certificate(&der).unwrap();
+        assert_eq!(cert.subject.as_deref(), Some("halo.test"));
+        assert!(!cert.is_ca);
+        let expected = days_from_civil(2026, 11, 15) * DAY + 11 * 3600 + 51 * 60 + 8;
+        assert_eq!(cert.not_after, expected);
+
+        let path = Path::new("/etc/ssl/halo.pem");
+        let at = |days: i64| check_certificate(path, &cert, cert.not_after - days * DAY, 30);
+        assert!(!at(60).is_failure());
+        assert_eq!(at(20).severity, Severity::Low);
+        assert_eq!(at(10).severity, Severity::Medium);
+        assert_eq!(at(3).severity, Severity::High);
+        let expired = at(-2);
+        assert_eq!(expired.severity, Severity::Critical);
+        assert!(expired.message.contains("expired 2 days ago"));
+    }
+
+    #[test]
+    fn test_key_permissions() {
+        let path = Path::new("/etc/ssl/private/halo.key");
+        assert!(check_key(path, 0o100600, 0).iter().all(|f| !f.is_failure()));
+        let findings = check_key(path, 0o100644, 1000);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert!(findings[1].is_failure());
+        assert_eq!(check_key(path, 0o100640, 0)[0].severity, Severity::Medium);
+    }
+}
@@ -1,9 +1,11 @@
 //! TOML configuration loader for HALO audit rules.
 //!
 //! This module provides types and functions for parsing audit rules from TOML files for both permission and ownership audits.
+//! The same schema can be written in YAML or JSON, e.g. when configs are generated by other
+//! tools; [`AuditConfig::from_file`] picks the format from the file extension.
 //!
 //! Features:
-//! - Deserialize permission and ownership rule configs from TOML
+//! - Deserialize permission and ownership rule configs from TOML, YAML or JSON
 //! - Validate and convert permission formats (octal, symbolic)
 //! - Integrate with HALO's permission and ownership audit systems
 //! - Supports custom audit configurations via config files
@@ -11,47 +13,210 @@
 //! # Example TOML
 //! ```toml
 //! [[perm_rules]]
+//! id = "SITE-0001" # optional, derived from the path if unset
 //! path = "/etc/passwd"
 //! expected_mode = 600 # or "0o600" or "u=rw,g=r,o="
 //! importance = "Medium"
 //! recursive = false
 //!
+//! [[perm_rules]]
+//! path = "/home/*/.ssh" # wildcards expand to one result per matching path
+//! expected_mode = 700 # matched directories are checked themselves, not walked
+//! importance = "High"
+//!
+//! [[perm_rules]]
+//! path = "/etc/pam.d"
+//! expected_mode = 644 # files
+//! expected_dir_mode = 755 # the directory itself and subdirectories
+//! importance = "High"
+//! recursive = true
+//!
+//! [[perm_rules]]
+//! path = "/srv"
+//! expected_mode = 640
+//! importance = "Medium"
+//! recursive = true
+//! mounts = "one-file-system" # or "skip-virtual" (default), "all"
+//!
 //! [[owner_rules]]
 //! path = "/etc/passwd"
 //! expected_uid = 0
 //! expected_gid = 0
+//!
+//! [[sysctl_rules]]
+//! key = "net.ipv4.ip_forward"
+//! expected = "1"
+//! severity = "Low"
+//!
+//! [[content_rules]]
+//! path = "/etc/ssh/sshd_config"
+//! must_match = '^\s*PermitRootLogin\s+no\b'
+//! severity = "High"
+//!
+//! [[absent_rules]]
+//! path = "/etc/ssh/ssh_host_dsa_key" # wildcards allowed, e.g. "/home/*/.rhosts"
+//! severity = "High"
+//!
+//! [firewall]
+//! ssh_port = 2222
+//!
+//! [tls]
+//! dirs = ["/etc/ssl", "/srv/app/certs"]
+//! warn_days = 21
+//!
+//! [logrotate]
+//! max_log_size = 524288000
+//!
+//! [severity_policy]
+//! group_write = "Critical"
+//!
+//! [limits] # per recursive rule
+//! max_files = 1000000
+//! max_runtime = "15m"
+//!
+//! [[waivers]]
+//! path = "/srv/shared"
+//! rule = "permissions"
+//! reason = "Group-writable upload area"
+//! expires = "2026-12-31"
+//! ```
+//!
+//! One policy file can serve a heterogeneous fleet with named profiles, each selecting the
+//! rules whose id matches one of `rules` (wildcards allowed; sysctl rules match by key) or
+//! whose path lies below one of `paths`, plus the `tables` it names. `check --config
+//! fleet.toml --profile webserver` then runs only that subset:
+//! ```toml
+//! [profiles.webserver]
+//! description = "nginx hosts"
+//! rules = ["WEB-*", "net.ipv4.*"]
+//! paths = ["/etc/nginx", "/etc/ssl"]
+//! tables = ["firewall", "tls"]
+//!
+//! [profiles.workstation]
+//! paths = ["/home"]
+//! ```
+//!
+//! Large policies can be split across files and shared between hosts; included files are
+//! resolved relative to the including one and merged before it:
+//! ```toml
+//! includes = ["base.toml", "webserver.yaml"]
+//! ```
+//!
+//! # Example YAML
+//! ```yaml
+//! perm_rules:
+//!   - path: /etc/passwd
+//!     expected_mode: 644
+//!     importance: Medium
+//! owner_rules:
+//!   - path: /etc/passwd
+//!     expected_uid: 0
+//!     expected_gid: 0
 //! ```
 use crate::audit::{
+    absence::{AbsenceAudit, AbsenceRule},
+    content::{ContentAudit, ContentRule},
+    finding::{AuditFindings, Finding},
+    glob,
+    firewall::{FirewallAudit, FirewallPolicy},
+    logrotate::LogrotateAudit,
+    scan::{ScanControl, ScanLimits},
+    sysctl::{SysctlAudit, SysctlRule},
+    tls::TlsAudit,
     permissions::{
         audit_permissions::{
             PermissionResults, PermissionRules, parse_mode,
-            Importance, 
+            Importance, Severity,
         },
+        severity_policy::SeverityPolicy,
     },
     ownership::ownership::{OwnershipResult, OwnershipRule},
 };
-use serde::Deserialize;
-use std::path::PathBuf;
+use crate::ack::stable_id;
+use crate::error::HaloError;
+use crate::observer::ResultRef;
+use crate::waiver::Waiver;
+use crate::audit::context::AuditContext;
+use crate::audit::mounts::MountPolicy;
+use crate::audit::sysroot;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// File format of an audit config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Format of a config file by extension: `.yaml` and `.yml` are YAML, `.json` is JSON,
+    /// anything else TOML.
+    pub fn from_path(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            Some("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Toml,
+        }
+    }
+
+    /// Name used in error messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Yaml => "YAML",
+            ConfigFormat::Json => "JSON",
+        }
+    }
+}
 
 /// Represents a single permission audit rule loaded from a TOML config file.
 ///
 /// Fields:
-/// - `path`: Path to the file or directory to audit permissions.
+/// - `path`: Path to the file or directory to audit permissions; may contain `*` and `?` wildcards.
 /// - `expected_mode`: Expected file mode (permissions) in octal, symbolic, or integer format.
 /// - `importance`: Importance level for the permission rule.
-/// - `recursive`: If true, audit directories recursively. Optional; defaults to false.
-#[derive(Debug, Deserialize)]
+/// - `recursive`: If true, audit directories recursively. Optional; if unset, a directory named
+///   by `path` is audited recursively, while a directory matched by a wildcard is checked itself
+///   against `expected_dir_mode`, or `expected_mode` if that is unset.
+/// - `id`: Rule id carried by the results. Optional; derived from the path if unset.
+/// - `optional`: If true, a missing path is skipped instead of reported. Optional; defaults to false.
+/// - `expected_dir_mode`: Mode expected of directories, in the same formats as `expected_mode`.
+///   Optional; directories are not checked if unset.
+/// - `mounts`: Which mounts below the path a recursive rule enters: `skip-virtual`,
+///   `one-file-system` or `all`. Optional; the run's default (`--one-file-system`) if unset.
+/// - `symlink_severity`: Severity of symlinks that fail their check. Optional; each symlink
+///   issue (broken, unexpected target, not a symlink) has its own default if unset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermissionConfig {
     pub path: String,
     /// Accepts either decimal (e.g. 644), octal string (e.g. "0o644"), or integer (e.g. 644)
     pub expected_mode: ModeValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_dir_mode: Option<ModeValue>,
     pub importance: Importance,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub recursive: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mounts: Option<MountPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symlink_severity: Option<Severity>,
 }
 // ...existing code...
 // ...existing code...
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ModeValue {
     Int(u32),
@@ -62,18 +227,32 @@ pub enum ModeValue {
 /// Represents a single ownership audit rule loaded from a TOML config file.
 ///
 /// Fields:
-/// - `path`: Path to the file or directory to audit ownership.
+/// - `path`: Path to the file or directory to audit ownership; may contain `*` and `?` wildcards.
 /// - `expected_uid`: Optional expected UID for ownership audit.
 /// - `expected_gid`: Optional expected GID for ownership audit.
 /// - `follow_symlinks`: If true, follow symlinks (optional, default false)
 /// - `recursive`: If true, audit directories recursively (optional, default false)
-#[derive(Debug, Deserialize)]
+/// - `id`: Rule id carried by the result (optional, derived from the path if unset)
+/// - `optional`: If true, a missing path is skipped instead of reported (optional, default false)
+/// - `symlink_severity`: Severity if the path is a symlink that fails its check (optional, the
+///   default of each symlink issue if unset)
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OwnerConfig {
     pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub expected_uid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub expected_gid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub follow_symlinks: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub recursive: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symlink_severity: Option<Severity>,
 }
 
 /// Represents the top-level TOML config structure for audit rules.
@@ -81,12 +260,550 @@ pub struct OwnerConfig {
 /// Fields:
 /// - `perm_rules`: List of permission audit rules to apply.
 /// - `owner_rules`: List of ownership audit rules to apply (optional).
-#[derive(Debug, Deserialize)]
+/// - `sysctl_rules`: Overrides for the sysctl hardening profile (optional).
+/// - `content_rules`: Regexes files must or must not match (optional).
+/// - `absent_rules`: Paths that must not exist (optional).
+/// - `firewall`: Expected firewall policy (optional).
+/// - `tls`: Certificate directories and expiry window (optional).
+/// - `logrotate`: Key logs and maximum log size (optional).
+/// - `severity_policy`: Severities for permission mismatches (optional).
+/// - `limits`: Caps on each permission rule's walk (optional).
+/// - `includes`: Config files merged before this one, relative to this file (optional).
+/// - `profiles`: Named subsets of the rules, selected with [`AuditConfig::profile`] (optional).
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct AuditConfig {
+    #[serde(default)]
+    pub includes: Vec<PathBuf>,
     #[serde(default)]
     pub perm_rules: Vec<PermissionConfig>,
     #[serde(default)]
     pub owner_rules: Vec<OwnerConfig>,
+    #[serde(default)]
+    pub sysctl_rules: Vec<SysctlRule>,
+    #[serde(default)]
+    pub content_rules: Vec<ContentRule>,
+    #[serde(default)]
+    pub absent_rules: Vec<AbsenceRule>,
+    #[serde(default)]
+    pub firewall: Option<FirewallPolicy>,
+    #[serde(default)]
+    pub tls: Option<TlsAudit>,
+    #[serde(default)]
+    pub logrotate: Option<LogrotateAudit>,
+    #[serde(default)]
+    pub severity_policy: Option<SeverityPolicy>,
+    #[serde(default)]
+    pub limits: Option<ScanLimits>,
+    #[serde(default)]
+    pub waivers: Vec<Waiver>,
+    #[serde(default)]
+    pub profiles: IndexMap<String, ProfileConfig>,
+}
+
+/// Tables a profile can select with `tables`.
+pub const PROFILE_TABLES: [&str; 3] = ["firewall", "tls", "logrotate"];
+
+/// A named subset of the rules of a config.
+///
+/// Fields:
+/// - `description`: Shown by `halo list profiles` (optional).
+/// - `rules`: Rule ids to select, with `*` and `?` wildcards; sysctl rules match by key (optional).
+/// - `paths`: Selects rules whose path lies below one of these (optional).
+/// - `tables`: Tables to keep, out of `firewall`, `tls` and `logrotate` (optional).
+///
+/// Waivers, the severity policy and the limits apply to every profile.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProfileConfig {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<String>,
+    #[serde(default)]
+    pub paths: Vec<PathBuf>,
+    #[serde(default)]
+    pub tables: Vec<String>,
+}
+
+impl ProfileConfig {
+    /// Returns true if a rule with `id` (if any) and `path` belongs to the profile.
+    pub fn selects(&self, id: Option<&str>, path: &str) -> bool {
+        id.is_some_and(|id| self.rules.iter().any(|pattern| glob::glob_match(pattern, id)))
+            || self.paths.iter().any(|prefix| Path::new(path).starts_with(prefix))
+    }
+}
+
+impl AuditConfig {
+    /// Reads, parses and validates a TOML, YAML or JSON configuration file.
+    ///
+    /// The format is chosen by extension, see [`ConfigFormat::from_path`].
+    ///
+    /// # Arguments
+    /// * `path` - Path to the file containing rules.
+    ///
+    /// # Returns
+    /// * `Ok(AuditConfig)` if the file parses and every rule is well-formed.
+    /// * `Err` with a user-friendly error message otherwise.
+    pub fn from_file(path: &str) -> Result<Self, HaloError> {
+        load_config(Path::new(path))
+    }
+
+    /// Parses and validates a configuration in `format`.
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self, HaloError> {
+        let parsed = match format {
+            ConfigFormat::Toml => toml::from_str::<AuditConfig>(content).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml_ng::from_str::<AuditConfig>(content).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::from_str::<AuditConfig>(content).map_err(|e| e.to_string()),
+        };
+        let config = parsed.map_err(|e| HaloError::parse(format!("{} config", format.name()), e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Appends the rules and waivers of `other`; its tables replace the ones set here.
+    ///
+    /// `includes` are left unchanged.
+    pub fn merge(&mut self, other: AuditConfig) {
+        self.perm_rules.extend(other.perm_rules);
+        self.owner_rules.extend(other.owner_rules);
+        self.sysctl_rules.extend(other.sysctl_rules);
+        self.content_rules.extend(other.content_rules);
+        self.absent_rules.extend(other.absent_rules);
+        self.waivers.extend(other.waivers);
+        self.firewall = other.firewall.or(self.firewall.take());
+        self.tls = other.tls.or(self.tls.take());
+        self.logrotate = other.logrotate.or(self.logrotate.take());
+        self.severity_policy = other.severity_policy.or(self.severity_policy.take());
+        self.limits = other.limits.or(self.limits.take());
+        self.profiles.extend(other.profiles);
+    }
+
+    /// The rules and tables selected by profile `name`.
+    ///
+    /// # Returns
+    /// * `Ok(AuditConfig)` with the selected rules, all waivers and the severity policy.
+    /// * `Err` if the config defines no profile `name`.
+    pub fn profile(&self, name: &str) -> Result<AuditConfig, HaloError> {
+        let profile = self.profiles.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            HaloError::InvalidConfig(format!(
+                "Config defines no profile '{}' (profiles: {})",
+                name,
+                known.join(", ")
+            ))
+        })?;
+        let table = |name: &str| profile.tables.iter().any(|t| t == name);
+        Ok(AuditConfig {
+            includes: Vec::new(),
+            perm_rules: self
+                .perm_rules
+                .iter()
+                .filter(|r| profile.selects(Some(&rule_id(&r.id, "PERM", &r.path)), &r.path))
+                .cloned()
+                .collect(),
+            owner_rules: self
+                .owner_rules
+                .iter()
+                .filter(|r| profile.selects(Some(&rule_id(&r.id, "OWNER", &r.path)), &r.path))
+                .cloned()
+                .collect(),
+            sysctl_rules: self
+                .sysctl_rules
+                .iter()
+                .filter(|r| profile.selects(Some(&r.key), ""))
+                .cloned()
+                .collect(),
+            content_rules: self
+                .content_rules
+                .iter()
+                .filter(|r| profile.selects(r.id.as_deref(), &r.path))
+                .cloned()
+                .collect(),
+            absent_rules: self
+                .absent_rules
+                .iter()
+                .filter(|r| profile.selects(r.id.as_deref(), &r.path))
+                .cloned()
+                .collect(),
+            firewall: self.firewall.clone().filter(|_| table("firewall")),
+            tls: self.tls.clone().filter(|_| table("tls")),
+            logrotate: self.logrotate.clone().filter(|_| table("logrotate")),
+            severity_policy: self.severity_policy.clone(),
+            limits: self.limits,
+            waivers: self.waivers.clone(),
+            profiles: IndexMap::new(),
+        })
+    }
+
+    /// Checks the rules for errors that do not depend on the audited system:
+    /// empty paths, invalid modes, invalid sysctl keys, malformed waivers and empty profiles.
+    pub fn validate(&self) -> Result<(), HaloError> {
+        for rule in &self.perm_rules {
+            // Validate path is non-empty and not just whitespace
+            if rule.path.trim().is_empty() {
+                return Err(HaloError::InvalidConfig("Audit rule has empty or invalid path.".to_string()));
+            }
+            rule.mode()?;
+            rule.dir_mode()?;
+        }
+        for owner in &self.owner_rules {
+            if owner.path.trim().is_empty() {
+                return Err(HaloError::InvalidConfig("Ownership rule has empty or invalid path.".to_string()));
+            }
+        }
+        for rule in &self.sysctl_rules {
+            if rule.key.trim().is_empty() || rule.key.contains('/') {
+                return Err(HaloError::InvalidConfig(format!("Invalid sysctl key '{}'.", rule.key)));
+            }
+        }
+        for rule in &self.content_rules {
+            rule.validate()?;
+        }
+        for rule in &self.absent_rules {
+            rule.validate()?;
+        }
+        for waiver in &self.waivers {
+            waiver.validate()?;
+        }
+        for (name, profile) in &self.profiles {
+            if let Some(table) = profile.tables.iter().find(|t| !PROFILE_TABLES.contains(&t.as_str())) {
+                return Err(HaloError::InvalidConfig(format!(
+                    "Profile '{}' names unknown table '{}', expected one of: {}.",
+                    name,
+                    table,
+                    PROFILE_TABLES.join(", ")
+                )));
+            }
+            if profile.rules.is_empty() && profile.paths.is_empty() && profile.tables.is_empty() {
+                return Err(HaloError::InvalidConfig(format!(
+                    "Profile '{}' selects no rules, paths or tables.",
+                    name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the permission rules, with severities from `severity_policy` and walks capped by
+    /// `limits` if set.
+    ///
+    /// A rule whose path does not exist, or whose pattern matches nothing, yields one
+    /// informational "Path not found" failure unless it is `optional`.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<PermissionResults>)` with the results of every rule.
+    /// * `Err` if a rule has an invalid mode.
+    pub fn permission_results(&self) -> Result<Vec<PermissionResults>, HaloError> {
+        let mut control = ScanControl::default();
+        if let Some(policy) = &self.severity_policy {
+            control = control.with_severity_policy(policy.clone());
+        }
+        if let Some(limits) = self.limits {
+            control = control.with_limits(limits);
+        }
+        self.permission_results_with(&mut control)
+    }
+
+    /// Runs the permission rules, throttled and checkpointed by `control`.
+    ///
+    /// Severities and limits follow `control`, not `severity_policy` and `limits`.
+    pub fn permission_results_with(
+        &self,
+        control: &mut ScanControl,
+    ) -> Result<Vec<PermissionResults>, HaloError> {
+        let mut results = Vec::new();
+        for rule in &self.perm_rules {
+            let audit_rules = rule.rules()?;
+            // A missing path is reported, not fatal, so one decommissioned file does not stop the audit
+            if audit_rules.is_empty() && !rule.optional {
+                let path = PathBuf::from(&rule.path);
+                let id = rule_id(&rule.id, "PERM", &rule.path);
+                let result = PermissionResults::not_found(path, rule.mode()?, rule.importance.clone(), Some(id));
+                control.publish(ResultRef::Permission(&result));
+                results.push(result);
+                continue;
+            }
+            for audit_rule in audit_rules {
+                let mut visited = std::collections::HashSet::new();
+                results.extend(audit_rule.check_with(&mut visited, control));
+            }
+        }
+        Ok(results)
+    }
+
+    /// The permission rules of this config, one per existing path or pattern match.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<PermissionRules>)` for the paths that exist now.
+    /// * `Err` if a rule has an invalid mode.
+    pub fn permission_rules(&self) -> Result<Vec<PermissionRules>, HaloError> {
+        let mut rules = Vec::new();
+        for rule in &self.perm_rules {
+            rules.extend(rule.rules()?);
+        }
+        Ok(rules)
+    }
+
+    /// The ownership rules of this config, one per existing path or pattern match.
+    pub fn ownership_rules(&self) -> Vec<OwnershipRule> {
+        self.owner_rules.iter().flat_map(OwnerConfig::rules).collect()
+    }
+
+    /// Runs the ownership rules.
+    ///
+    /// Missing paths are reported as for [`permission_results`](Self::permission_results).
+    ///
+    /// # Returns
+    /// * `Ok(Vec<OwnershipResult>)` with one result per matched path.
+    pub fn ownership_results(&self) -> Result<Vec<OwnershipResult>, HaloError> {
+        self.ownership_results_with(&AuditContext::new())
+    }
+
+    /// Runs the ownership rules, reading metadata through `context`.
+    pub fn ownership_results_with(
+        &self,
+        context: &AuditContext,
+    ) -> Result<Vec<OwnershipResult>, HaloError> {
+        let mut results = Vec::new();
+        for owner in &self.owner_rules {
+            let ownership_rules = owner.rules();
+            if ownership_rules.is_empty() && !owner.optional {
+                let path = PathBuf::from(&owner.path);
+                let id = rule_id(&owner.id, "OWNER", &owner.path);
+                results.push(OwnershipResult::not_found(
+                    path,
+                    owner.expected_uid.unwrap_or(0),
+                    owner.expected_gid.unwrap_or(0),
+                    Some(id),
+                ));
+                continue;
+            }
+            results.extend(ownership_rules.iter().map(|rule| rule.check_ownership_with(context)));
+        }
+        Ok(results)
+    }
+
+    /// Runs the sysctl audit with this config's overrides.
+    ///
+    /// # Returns
+    /// One finding per parameter, or an empty vector if the config has no `[[sysctl_rules]]`.
+    pub fn sysctl_findings(&self) -> Vec<Finding> {
+        if self.sysctl_rules.is_empty() {
+            return Vec::new();
+        }
+        SysctlAudit::default()
+            .with_overrides(self.sysctl_rules.clone())
+            .run_audit()
+    }
+
+    /// Runs the content rules.
+    ///
+    /// # Returns
+    /// The content findings, or an empty vector if the config has no `[[content_rules]]`.
+    pub fn content_findings(&self) -> Vec<Finding> {
+        ContentAudit::new(self.content_rules.clone()).run_audit()
+    }
+
+    /// Runs the absence rules.
+    ///
+    /// # Returns
+    /// The absence findings, or an empty vector if the config has no `[[absent_rules]]`.
+    pub fn absence_findings(&self) -> Vec<Finding> {
+        AbsenceAudit::new(self.absent_rules.clone()).run_audit()
+    }
+
+    /// Runs the firewall audit with this config's policy.
+    ///
+    /// # Returns
+    /// The firewall findings, or an empty vector if the config has no `[firewall]` table.
+    pub fn firewall_findings(&self) -> Vec<Finding> {
+        match &self.firewall {
+            Some(policy) => FirewallAudit { policy: policy.clone() }.run_audit(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Runs the TLS audit with this config's directories and expiry window.
+    ///
+    /// # Returns
+    /// The TLS findings, or an empty vector if the config has no `[tls]` table.
+    pub fn tls_findings(&self) -> Vec<Finding> {
+        self.tls.as_ref().map(|tls| tls.run_audit()).unwrap_or_default()
+    }
+
+    /// Runs the logrotate audit with this config's key logs and size limit.
+    ///
+    /// # Returns
+    /// The logrotate findings, or an empty vector if the config has no `[logrotate]` table.
+    pub fn logrotate_findings(&self) -> Vec<Finding> {
+        self.logrotate.as_ref().map(|l| l.run_audit()).unwrap_or_default()
+    }
+}
+
+/// Paths a rule applies to: the matches of a glob pattern, or the path itself if it exists.
+fn rule_paths(path: &str) -> Vec<PathBuf> {
+    if glob::is_pattern(path) {
+        return glob::expand(path);
+    }
+    let path_obj = PathBuf::from(path);
+    if sysroot::exists(&path_obj) { vec![path_obj] } else { Vec::new() }
+}
+
+/// Explicit id of a config rule, or `CFG-<KIND>-<hash of path>` so the id stays the same
+/// when rules are reordered.
+fn rule_id(id: &Option<String>, kind: &str, path: &str) -> String {
+    id.clone()
+        .unwrap_or_else(|| format!("CFG-{}-{}", kind, &stable_id(&[path])[..8]).to_uppercase())
+}
+
+impl PermissionConfig {
+    /// Parses and validates the expected mode of this rule.
+    pub fn mode(&self) -> Result<u32, HaloError> {
+        self.parse_field("expected_mode", &self.expected_mode)
+    }
+
+    /// Parses and validates the expected directory mode of this rule, if set.
+    pub fn dir_mode(&self) -> Result<Option<u32>, HaloError> {
+        self.expected_dir_mode
+            .as_ref()
+            .map(|value| self.parse_field("expected_dir_mode", value))
+            .transpose()
+    }
+
+    /// Builds the audit rule for each path this rule matches now.
+    pub fn rules(&self) -> Result<Vec<PermissionRules>, HaloError> {
+        let mode = self.mode()?;
+        let dir_mode = self.dir_mode()?;
+        let id = rule_id(&self.id, "PERM", &self.path);
+        let mut rules = Vec::new();
+        for path_obj in rule_paths(&self.path) {
+            // Clone importance to avoid lifetime shennanigans
+            let importance = self.importance.clone();
+            let (audit_rule, _path_status) = PermissionRules::new(path_obj, mode, importance);
+            let mut audit_rule = audit_rule.with_id(id.clone());
+            audit_rule.expected_dir_mode = dir_mode;
+            audit_rule.mounts = self.mounts;
+            audit_rule.symlink_severity = self.symlink_severity.clone();
+            match self.recursive {
+                Some(rec) => audit_rule.recursive = rec,
+                // A pattern matches the paths to check, not trees to walk
+                None if glob::is_pattern(&self.path) => {
+                    audit_rule.recursive = false;
+                    audit_rule.expected_dir_mode = dir_mode.or(Some(mode));
+                }
+                None => {}
+            }
+            rules.push(audit_rule);
+        }
+        Ok(rules)
+    }
+
+    fn parse_field(&self, field: &str, value: &ModeValue) -> Result<u32, HaloError> {
+        let (input, parsed) = match value {
+            ModeValue::Int(i) => (i.to_string(), parse_mode(&i.to_string())),
+            ModeValue::Str(s) => (s.clone(), parse_mode(s)),
+        };
+        let mode = parsed.map_err(|source| HaloError::InvalidMode {
+            field: field.to_string(),
+            value: input,
+            path: self.path.clone(),
+            source,
+        })?;
+        if mode > 0o777 {
+            return Err(HaloError::InvalidConfig(format!(
+                "Invalid {} {:o} for path '{}'. Must be <= 777.",
+                field, mode, self.path
+            )));
+        }
+        Ok(mode)
+    }
+}
+
+impl OwnerConfig {
+    /// Builds the ownership rule for each path this rule matches now.
+    ///
+    /// An unset `expected_uid` or `expected_gid` means root (0).
+    pub fn rules(&self) -> Vec<OwnershipRule> {
+        let expected_uid = self.expected_uid.unwrap_or(0);
+        let expected_gid = self.expected_gid.unwrap_or(0);
+        let follow_symlinks = self.follow_symlinks.unwrap_or(false);
+        let id = rule_id(&self.id, "OWNER", &self.path);
+        rule_paths(&self.path)
+            .into_iter()
+            .map(|path_obj| {
+                let (ownership_rule, _path_status) =
+                    OwnershipRule::new(path_obj, expected_uid, expected_gid, follow_symlinks);
+                let mut ownership_rule = ownership_rule.with_id(id.clone());
+                ownership_rule.symlink_severity = self.symlink_severity.clone();
+                if let Some(rec) = self.recursive {
+                    ownership_rule.recursive = rec;
+                }
+                ownership_rule
+            })
+            .collect()
+    }
+}
+
+/// Reads, parses and validates a TOML, YAML or JSON configuration file, choosing the format
+/// by extension.
+///
+/// Files listed in `includes` are loaded first, recursively, and merged in order with
+/// [`AuditConfig::merge`], so the including file's rules come last and its tables win.
+/// Included files may use a different format. An include cycle or a missing include is an
+/// error naming the files involved.
+///
+/// # Example
+/// ```rust,no_run
+/// use alhalo::audit::toml_config::load_config;
+/// let config = load_config("rules.yaml".as_ref()).unwrap();
+/// let results = config.permission_results().unwrap();
+/// ```
+pub fn load_config(path: &Path) -> Result<AuditConfig, HaloError> {
+    load_with_includes(path, &mut Vec::new())
+}
+
+// Loads `path` and its includes; `chain` holds the canonical paths of the files including it
+fn load_with_includes(
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<AuditConfig, HaloError> {
+    let format = ConfigFormat::from_path(path);
+    let content = std::fs::read_to_string(path).map_err(|e| HaloError::io(path, e))?;
+    let config = AuditConfig::parse(&content, format)?;
+    debug!("Loaded {} config {}", format.name(), path.display());
+    if config.includes.is_empty() {
+        return Ok(config);
+    }
+
+    let canonical = std::fs::canonicalize(path).map_err(|e| HaloError::io(path, e))?;
+    chain.push(canonical);
+    let base = path.parent().unwrap_or(Path::new("."));
+    let mut merged = AuditConfig::default();
+    for include in &config.includes {
+        let include_path = base.join(include);
+        if !include_path.exists() {
+            return Err(HaloError::InvalidConfig(format!(
+                "Config '{}' includes '{}', which does not exist.",
+                path.display(),
+                include_path.display()
+            )));
+        }
+        let include_canonical = std::fs::canonicalize(&include_path).map_err(|e| HaloError::io(&include_path, e))?;
+        if let Some(start) = chain.iter().position(|p| *p == include_canonical) {
+            let cycle: Vec<String> = chain[start..]
+                .iter()
+                .chain(std::iter::once(&include_canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(HaloError::InvalidConfig(format!("Config include cycle: {}", cycle.join(" -> "))));
+        }
+        merged.merge(load_with_includes(&include_path, chain)?);
+    }
+    chain.pop();
+
+    let includes = config.includes.clone();
+    merged.merge(config);
+    merged.includes = includes;
+    Ok(merged)
 }
 
 /// Loads rules for permission audits from a TOML configuration file.
@@ -106,67 +823,8 @@ pub struct AuditConfig {
 /// importance = "Medium"
 /// recursive = false
 /// ```
-pub fn toml_permissions(path: &str) -> Result<Vec<PermissionResults>, Box<dyn std::error::Error>> {
-    let content = std::fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read TOML file '{}': {}", path, e))?;
-    let config: AuditConfig =
-        toml::from_str(&content).map_err(|e| format!("Failed to parse TOML config: {}", e))?;
-    let mut results = Vec::new();
-
-    // Process permission rules
-    for rule in &config.perm_rules {
-        // Validate path is non-empty and not just whitespace
-        if rule.path.trim().is_empty() {
-            return Err(format!("Audit rule has empty or invalid path.").into());
-        }
-        // Check if path exists
-        let path_obj = PathBuf::from(&rule.path);
-        if !path_obj.exists() {
-            return Err(format!("Audit rule path '{}' does not exist.", rule.path).into());
-        }
-        let mode = match &rule.expected_mode {
-            ModeValue::Int(i) => {
-                let mode_str = i.to_string();
-                match parse_mode(&mode_str) {
-                    Ok(m) => m,
-                    Err(e) => {
-                        return Err(format!(
-                            "Invalid expected_mode '{}' for path '{}': {}",
-                            i, rule.path, e
-                        )
-                        .into());
-                    }
-                }
-            }
-            ModeValue::Str(s) => match parse_mode(&s) {
-                Ok(m) => m,
-                Err(e) => {
-                    return Err(format!(
-                        "Invalid expected_mode '{}' for path '{}': {}",
-                        s, rule.path, e
-                    )
-                    .into());
-                }
-            },
-        };
-        if mode > 0o777 {
-            return Err(format!(
-                "Invalid expected_mode {:o} for path '{}'. Must be <= 777.",
-                mode, rule.path
-            )
-            .into());
-        }
-        // Clone importance to avoid lifetime shennanigans
-        let importance = rule.importance.clone();
-        let (mut audit_rule, _path_status) =
-            PermissionRules::new(path_obj.clone(), mode, importance.clone());
-        if let Some(rec) = rule.recursive {
-            audit_rule.recursive = rec;
-        }
-        let mut visited = std::collections::HashSet::new();
-        results.extend(audit_rule.check(&mut visited));
-    }
-    Ok(results)
+pub fn toml_permissions(path: &str) -> Result<Vec<PermissionResults>, HaloError> {
+    AuditConfig::from_file(path)?.permission_results()
 }
 
 /// Loads rules for ownership audits from a TOML configuration file.
@@ -185,42 +843,33 @@ pub fn toml_permissions(path: &str) -> Result<Vec<PermissionResults>, Box<dyn st
 /// expected_uid = 0
 /// expected_gid = 0
 /// ```
-pub fn toml_ownership(path: &str) -> Result<Vec<OwnershipResult>, Box<dyn std::error::Error>> {
-    let content = std::fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read TOML file '{}': {}", path, e))?;
-    let config: AuditConfig =
-        toml::from_str(&content).map_err(|e| format!("Failed to parse TOML config: {}", e))?;
-    let mut results = Vec::new();
-
-    for owner in &config.owner_rules {
-        // Validate path
-        if owner.path.trim().is_empty() {
-            return Err(format!("Ownership rule has empty or invalid path.").into());
-        }
-        let path_obj = PathBuf::from(&owner.path);
-        if !path_obj.exists() {
-            return Err(format!("Ownership rule path '{}' does not exist.", owner.path).into());
-        }
-        // Use 0 (root) as default if not specified, or skip if you prefer
-        let expected_uid = owner.expected_uid.unwrap_or(0);
-        let expected_gid = owner.expected_gid.unwrap_or(0);
-        let follow_symlinks = owner.follow_symlinks.unwrap_or(false);
-        let (mut ownership_rule, _path_status) =
-            OwnershipRule::new(path_obj, expected_uid, expected_gid, follow_symlinks);
-        if let Some(rec) = owner.recursive {
-            ownership_rule.recursive = rec;
-        }
-        let ownership_result = ownership_rule.check_ownership();
-        results.push(ownership_result);
-    }
-    Ok(results)
+pub fn toml_ownership(path: &str) -> Result<Vec<OwnershipResult>, HaloError> {
+    AuditConfig::from_file(path)?.ownership_results()
 }
 
-/*
-* I would like to add YAML support in the future, but for now TOML is sufficient.
-* The deprecation of serde_yaml is concerning and I would prefer to avoid adding
-* dependencies that are unmaintained, maybe rust-yaml2 could be an alternative.
-*/
+/// Runs the sysctl audit with the overrides from a TOML configuration file.
+///
+/// The built-in hardening profile is used as the base; `[[sysctl_rules]]` entries replace
+/// profile rules with the same key or add new ones.
+///
+/// # Arguments
+/// * `path` - Path to the TOML file containing rules.
+///
+/// # Returns
+/// * `Ok(Vec<Finding>)` with one finding per parameter, or an empty vector if the file has no
+///   `[[sysctl_rules]]`.
+/// * `Err` with a user-friendly error message if reading or parsing fails, or if a rule is invalid.
+///
+/// # Example TOML
+/// ```toml
+/// [[sysctl_rules]]
+/// key = "kernel.sysrq"
+/// expected = "176"
+/// severity = "Low"
+/// ```
+pub fn toml_sysctl(path: &str) -> Result<Vec<Finding>, HaloError> {
+    Ok(AuditConfig::from_file(path)?.sysctl_findings())
+}
 
 #[cfg(test)]
 mod tests {
@@ -311,8 +960,35 @@ mod tests {
         );
         let toml_path = dir.path().join("config.toml");
         write_toml(&toml_path, &toml);
-        let rules = toml_permissions(toml_path.to_str().unwrap());
-        assert!(rules.is_err());
+        let results = toml_permissions(toml_path.to_str().unwrap()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].status, crate::Status::Fail);
+        assert_eq!(results[0].severity, crate::Severity::Info);
+        assert!(results[0].error.as_ref().unwrap().to_string().starts_with("Path not found"));
+
+        // Optional rules are skipped, and the remaining rules still run
+        let other = dir.path().join("present");
+        File::create(&other).unwrap();
+        let toml = format!(
+            r#"
+            [[perm_rules]]
+            path = "{}"
+            expected_mode = 600
+            importance = "Medium"
+            optional = true
+
+            [[perm_rules]]
+            path = "{}"
+            expected_mode = 644
+            importance = "Medium"
+        "#,
+            file_path.display(),
+            other.display()
+        );
+        write_toml(&toml_path, &toml);
+        let results = toml_permissions(toml_path.to_str().unwrap()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, other);
     }
 
     #[test]
@@ -329,5 +1005,422 @@ mod tests {
         let rules = toml_permissions(toml_path.to_str().unwrap());
         assert!(rules.is_err());
     }
+
+    #[test]
+    fn test_sysctl_overrides() {
+        let dir = tempdir().unwrap();
+        let toml = r#"
+            [[sysctl_rules]]
+            key = "net.ipv4.ip_forward"
+            expected = "1"
+            severity = "Low"
+
+            [[sysctl_rules]]
+            key = "kernel.ostype"
+            expected = "Linux"
+            severity = "Info"
+        "#;
+        let toml_path = dir.path().join("config.toml");
+        write_toml(&toml_path, toml);
+        let findings = toml_sysctl(toml_path.to_str().unwrap()).unwrap();
+        let ostype = findings
+            .iter()
+            .find(|f| f.subject.as_deref() == Some("kernel.ostype"))
+            .unwrap();
+        assert!(!ostype.is_failure());
+        let forward = findings
+            .iter()
+            .filter(|f| f.subject.as_deref() == Some("net.ipv4.ip_forward"))
+            .count();
+        assert_eq!(forward, 1);
+    }
+
+    #[test]
+    fn test_sysctl_invalid_key() {
+        let dir = tempdir().unwrap();
+        let toml = r#"
+            [[sysctl_rules]]
+            key = "../etc/shadow"
+            expected = "1"
+            severity = "Low"
+        "#;
+        let toml_path = dir.path().join("config.toml");
+        write_toml(&toml_path, toml);
+        assert!(toml_sysctl(toml_path.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_severity_policy() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("testfile");
+        File::create(&file_path).unwrap();
+        std::fs::set_permissions(&file_path, std::fs::Permissions::from_mode(0o660)).unwrap();
+        let toml = format!(
+            r#"
+            [[perm_rules]]
+            path = "{}"
+            expected_mode = 640
+            importance = "High"
+
+            [[severity_policy.overrides]]
+            class = "group-write"
+            importance = "High"
+            severity = "Critical"
+        "#,
+            file_path.display()
+        );
+        let toml_path = dir.path().join("config.toml");
+        write_toml(&toml_path, &toml);
+        let results = toml_permissions(toml_path.to_str().unwrap()).unwrap();
+        assert_eq!(results[0].severity, crate::Severity::Critical);
+    }
+
+    #[test]
+    fn test_yaml_config() {
+        assert_eq!(ConfigFormat::from_path(Path::new("rules.YML")), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path(Path::new("rules.toml")), ConfigFormat::Toml);
+        let config = AuditConfig::parse(
+            "perm_rules:\n  - path: /etc/passwd\n    expected_mode: \"u=rw,g=r,o=r\"\n    importance: Medium\n\
+             waivers:\n  - path: /etc\n    reason: lab host\n",
+            ConfigFormat::Yaml,
+        )
+        .unwrap();
+        assert_eq!(config.perm_rules[0].mode().unwrap(), 0o644);
+        assert_eq!(config.waivers.len(), 1);
+        let err = AuditConfig::parse("perm_rules: [{path: ''}]", ConfigFormat::Yaml).unwrap_err();
+        assert!(err.to_string().starts_with("Failed to parse YAML config"), "{}", err);
+    }
+
+    #[test]
+    fn test_json_config() {
+        assert_eq!(ConfigFormat::from_path(Path::new("generated.json")), ConfigFormat::Json);
+        let config = AuditConfig::parse(
+            r#"{
+                "perm_rules": [{"path": "/etc/shadow", "expected_mode": 600, "importance": "High", "id": "GEN-1"}],
+                "sysctl_rules": [{"key": "kernel.sysrq", "expected": "0", "severity": "Low"}]
+            }"#,
+            ConfigFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(config.perm_rules[0].mode().unwrap(), 0o600);
+        assert_eq!(config.perm_rules[0].id.as_deref(), Some("GEN-1"));
+        assert_eq!(config.sysctl_rules[0].key, "kernel.sysrq");
+        // Invalid rules are rejected like in TOML
+        let err = AuditConfig::parse(
+            r#"{"sysctl_rules": [{"key": "bad/key", "expected": "0", "severity": "Low"}]}"#,
+            ConfigFormat::Json,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Invalid sysctl key"), "{}", err);
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        let dir = tempdir().unwrap();
+        let missing = dir.path().join("missing.toml");
+        assert!(matches!(load_config(&missing), Err(HaloError::NotFound(path)) if path == missing));
+
+        let toml = "[[perm_rules]]\npath = \"/etc\"\nexpected_mode = \"rwz\"\nimportance = \"Low\"\n";
+        let err = AuditConfig::parse(toml, ConfigFormat::Toml).unwrap_err();
+        match err {
+            HaloError::InvalidMode { field, value, source, .. } => {
+                assert_eq!((field.as_str(), value.as_str()), ("expected_mode", "rwz"));
+                assert_eq!(source, crate::AuditError::InvalidShortSymbolicFormat);
+            }
+            other => panic!("unexpected error {}", other),
+        }
+        assert!(matches!(
+            AuditConfig::parse("perm_rules = 1", ConfigFormat::Toml),
+            Err(HaloError::Parse { .. })
+        ));
+        assert!(matches!(AuditConfig::default().profile("web"), Err(HaloError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_glob_paths() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("cron.d")).unwrap();
+        for name in ["backup", "logrotate"] {
+            File::create(dir.path().join("cron.d").join(name)).unwrap();
+        }
+        let toml = format!(
+            r#"
+            [[perm_rules]]
+            path = "{0}/cron.d/*"
+            expected_mode = 644
+            importance = "Medium"
+
+            [[owner_rules]]
+            path = "{0}/cron.d/b*"
+        "#,
+            dir.path().display()
+        );
+        let config: AuditConfig = toml::from_str(&toml).unwrap();
+        let results = config.permission_results().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].path, dir.path().join("cron.d/logrotate"));
+        assert_eq!(results[0].rule_id, results[1].rule_id);
+        assert_eq!(config.ownership_results().unwrap().len(), 1);
+
+        let empty: AuditConfig = toml::from_str(&format!(
+            "[[perm_rules]]\npath = \"{}/cron.d/*.conf\"\nexpected_mode = 644\nimportance = \"Low\"\n",
+            dir.path().display()
+        ))
+        .unwrap();
+        let results = empty.permission_results().unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].error.is_some());
+    }
+
+    #[test]
+    fn test_glob_matched_directories() {
+        use crate::audit::permissions::audit_permissions::Status;
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempdir().unwrap();
+        let (alice, bob) = (dir.path().join("home/alice/.ssh"), dir.path().join("home/bob/.ssh"));
+        std::fs::create_dir_all(&alice).unwrap();
+        std::fs::create_dir_all(&bob).unwrap();
+        File::create(alice.join("id.pub")).unwrap();
+        std::fs::set_permissions(alice.join("id.pub"), std::fs::Permissions::from_mode(0o644)).unwrap();
+        std::fs::set_permissions(&alice, std::fs::Permissions::from_mode(0o700)).unwrap();
+        std::fs::set_permissions(&bob, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let rule = format!(
+            "[[perm_rules]]\npath = \"{}/home/*/.ssh\"\nexpected_mode = 700\nimportance = \"High\"\n",
+            dir.path().display()
+        );
+
+        // The directories themselves, including the empty one, not the files inside
+        let config: AuditConfig = toml::from_str(&rule).unwrap();
+        let results = config.permission_results().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!((&results[0].path, results[0].status.clone()), (&alice, Status::Pass));
+        assert_eq!((&results[1].path, results[1].status.clone()), (&bob, Status::Fail));
+
+        // An explicit `recursive` still walks them
+        let config: AuditConfig = toml::from_str(&format!("{}recursive = true\n", rule)).unwrap();
+        let results = config.permission_results().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, alice.join("id.pub"));
+    }
+
+    #[test]
+    fn test_includes() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("testfile");
+        File::create(&target).unwrap();
+        std::fs::create_dir(dir.path().join("shared")).unwrap();
+        write_toml(
+            &dir.path().join("shared/base.toml"),
+            &format!(
+                "[[perm_rules]]\npath = \"{}\"\nexpected_mode = 644\nimportance = \"Low\"\n[tls]\nwarn_days = 10\n",
+                target.display()
+            ),
+        );
+        write_toml(
+            &dir.path().join("shared/web.yaml"),
+            "sysctl_rules:\n  - key: kernel.sysrq\n    expected: \"0\"\n    severity: Low\n",
+        );
+        let host = dir.path().join("host.toml");
+        write_toml(
+            &host,
+            &format!(
+                "includes = [\"shared/base.toml\", \"shared/web.yaml\"]\n[[perm_rules]]\npath = \"{}\"\nexpected_mode = 600\nimportance = \"High\"\n",
+                target.display()
+            ),
+        );
+        let config = load_config(&host).unwrap();
+        assert_eq!(config.perm_rules.len(), 2);
+        assert_eq!(config.perm_rules[1].mode().unwrap(), 0o600);
+        assert_eq!(config.sysctl_rules.len(), 1);
+        assert_eq!(config.tls.unwrap().warn_days, 10);
+
+        write_toml(&dir.path().join("a.toml"), "includes = [\"b.toml\"]\n");
+        write_toml(&dir.path().join("b.toml"), "includes = [\"a.toml\"]\n");
+        let err = load_config(&dir.path().join("a.toml")).unwrap_err().to_string();
+        assert!(err.starts_with("Config include cycle:"), "{}", err);
+        assert!(err.contains("a.toml -> ") && err.ends_with("a.toml"), "{}", err);
+
+        write_toml(&dir.path().join("c.toml"), "includes = [\"missing.toml\"]\n");
+        let err = load_config(&dir.path().join("c.toml")).unwrap_err().to_string();
+        assert!(err.contains("includes") && err.contains("missing.toml"), "{}", err);
+    }
+
+    #[test]
+    fn test_expected_dir_mode() {
+        let config: AuditConfig = toml::from_str(
+            r#"
+            [[perm_rules]]
+            path = "/etc/pam.d"
+            expected_mode = 644
+            expected_dir_mode = "rwxr-xr-x"
+            importance = "High"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.perm_rules[0].dir_mode().unwrap(), Some(0o755));
+        let config: AuditConfig = toml::from_str(
+            "[[perm_rules]]\npath = \"/etc\"\nexpected_mode = 644\nexpected_dir_mode = 7777\nimportance = \"Low\"\n",
+        )
+        .unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("expected_dir_mode"), "{}", err);
+    }
+
+    #[test]
+    fn test_mount_policy() {
+        let config: AuditConfig = toml::from_str(
+            r#"
+            [[perm_rules]]
+            path = "/srv"
+            expected_mode = 640
+            importance = "Medium"
+            recursive = true
+            mounts = "one-file-system"
+
+            [[perm_rules]]
+            path = "/var"
+            expected_mode = 640
+            importance = "Medium"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.perm_rules[0].rules().unwrap()[0].mounts, Some(MountPolicy::OneFileSystem));
+        assert_eq!(config.perm_rules[1].rules().unwrap()[0].mounts, None);
+        assert!(toml::from_str::<AuditConfig>(
+            "[[perm_rules]]\npath = \"/srv\"\nexpected_mode = 640\nimportance = \"Low\"\nmounts = \"xdev\"\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_symlink_severity() {
+        let dir = tempdir().unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(dir.path().join("missing"), &link).unwrap();
+        let config: AuditConfig = toml::from_str(&format!(
+            r#"
+            [[perm_rules]]
+            path = "{dir}"
+            expected_mode = 644
+            importance = "Low"
+            recursive = true
+            symlink_severity = "Critical"
+
+            [[perm_rules]]
+            path = "{dir}"
+            expected_mode = 644
+            importance = "Low"
+            recursive = true
+
+            [[owner_rules]]
+            path = "{dir}/li*"
+            symlink_severity = "Low"
+        "#,
+            dir = dir.path().display()
+        ))
+        .unwrap();
+        let severity = |rule: &PermissionConfig| {
+            let results = rule.rules().unwrap()[0].check(&mut Default::default());
+            results.iter().find(|r| r.path == link).unwrap().severity.clone()
+        };
+        // A broken link is Medium unless the rule says otherwise
+        assert_eq!(severity(&config.perm_rules[0]), Severity::Critical);
+        assert_eq!(severity(&config.perm_rules[1]), Severity::Medium);
+        // Globs match broken links too
+        let owner = config.owner_rules[0].rules()[0].check_ownership();
+        assert!(!owner.pass);
+        assert_eq!(owner.severity, Severity::Low);
+    }
+
+    #[test]
+    fn test_rule_ids() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("testfile");
+        File::create(&file_path).unwrap();
+        let toml = format!(
+            r#"
+            [[perm_rules]]
+            id = "SITE-0001"
+            path = "{0}"
+            expected_mode = 644
+            importance = "Low"
+
+            [[perm_rules]]
+            path = "{0}"
+            expected_mode = 600
+            importance = "Low"
+        "#,
+            file_path.display()
+        );
+        let toml_path = dir.path().join("config.toml");
+        write_toml(&toml_path, &toml);
+        let results = toml_permissions(toml_path.to_str().unwrap()).unwrap();
+        assert_eq!(results[0].rule_id.as_deref(), Some("SITE-0001"));
+        let generated = results[1].rule_id.clone().unwrap();
+        assert!(generated.starts_with("CFG-PERM-"), "{}", generated);
+        // Generated ids depend only on the path
+        let again = toml_permissions(toml_path.to_str().unwrap()).unwrap();
+        assert_eq!(again[1].rule_id, Some(generated));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let toml = r#"
+            [[perm_rules]]
+            id = "WEB-0001"
+            path = "/etc/nginx/nginx.conf"
+            expected_mode = 644
+            importance = "Medium"
+
+            [[perm_rules]]
+            path = "/home/alice/.ssh"
+            expected_mode = 700
+            importance = "High"
+
+            [[owner_rules]]
+            path = "/etc/ssl/private"
+
+            [[sysctl_rules]]
+            key = "net.ipv4.ip_forward"
+            expected = "1"
+            severity = "Low"
+
+            [tls]
+            warn_days = 10
+
+            [logrotate]
+            max_log_size = 1024
+
+            [profiles.webserver]
+            description = "nginx hosts"
+            rules = ["WEB-*", "net.ipv4.*"]
+            paths = ["/etc/ssl"]
+            tables = ["tls"]
+
+            [profiles.workstation]
+            paths = ["/home"]
+        "#;
+        let config = AuditConfig::parse(toml, ConfigFormat::Toml).unwrap();
+        assert_eq!(config.profiles.keys().collect::<Vec<_>>(), ["webserver", "workstation"]);
+
+        let web = config.profile("webserver").unwrap();
+        assert_eq!(web.perm_rules.len(), 1);
+        assert_eq!(web.perm_rules[0].id.as_deref(), Some("WEB-0001"));
+        assert_eq!(web.owner_rules.len(), 1);
+        assert_eq!(web.sysctl_rules.len(), 1);
+        assert!(web.tls.is_some() && web.logrotate.is_none());
+
+        let workstation = config.profile("workstation").unwrap();
+        assert_eq!(workstation.perm_rules[0].path, "/home/alice/.ssh");
+        assert!(workstation.owner_rules.is_empty() && workstation.sysctl_rules.is_empty());
+
+        let err = config.profile("database").unwrap_err().to_string();
+        assert!(err.contains("'database'") && err.contains("webserver, workstation"), "{}", err);
+
+        let err = AuditConfig::parse("[profiles.x]\ntables = [\"dns\"]\n", ConfigFormat::Toml).unwrap_err();
+        assert!(err.to_string().contains("unknown table 'dns'"), "{}", err);
+        assert!(AuditConfig::parse("[profiles.x]\n", ConfigFormat::Toml).is_err());
+    }
     // ...existing code...
 }
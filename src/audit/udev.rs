@@ -0,0 +1,301 @@
+//! Udev rules audit for HALO.
+//!
+//! Udev rules decide the permissions of device nodes and may run programs as root when a
+//! device appears. A single permissive rule can expose raw disks, memory or input devices to
+//! every local user.
+//!
+//! # Checks
+//! - `udev-permissive-mode`: `MODE` grants access to other users on a sensitive device class
+//!   (block, memory, input, hidraw, tty, kvm, tpm...), or is world-writable on any device
+//! - `udev-group-override`: `GROUP` hands a sensitive device to a non-standard group
+//! - `udev-run-writable`: `RUN+=` program in a user-writable location
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{AuditFindings, UdevAudit};
+//! let findings = UdevAudit::default().run_audit();
+//! for f in findings.iter().filter(|f| f.is_failure()) {
+//!     println!("{}: {}", f.path.display(), f.message);
+//! }
+//! ```
+use crate::audit::finding::{AuditFindings, Finding};
+use crate::Severity;
+use crate::audit::sysroot;
+use crate::audit::sudoers::logical_lines;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Subsystems whose devices give access to disks, memory, input or hardware security.
+const SENSITIVE_SUBSYSTEMS: &[&str] = &[
+    "block", "mem", "input", "hidraw", "tty", "nvme", "scsi_generic", "kvm", "vfio", "tpm",
+    "tpmrm", "i2c-dev",
+];
+
+/// Kernel device name prefixes of sensitive device nodes.
+const SENSITIVE_KERNEL_PREFIXES: &[&str] = &[
+    "mem", "kmem", "port", "sd", "nvme", "hd", "vd", "mmcblk", "sg", "dm-", "loop", "hidraw",
+    "event", "uinput", "kvm", "tpm", "tty", "i2c-",
+];
+
+/// Groups distributions use for device access.
+const DEVICE_GROUPS: &[&str] = &[
+    "root", "disk", "kmem", "input", "tty", "dialout", "uucp", "kvm", "tss", "video", "render",
+    "audio", "plugdev", "cdrom", "tape", "i2c", "lp",
+];
+
+/// Locations any local user can write to.
+const USER_WRITABLE_PREFIXES: &[&str] = &["/home", "/tmp", "/var/tmp", "/dev/shm", "/run/user"];
+
+/// Udev rule directories audited for permissive device rules.
+pub struct UdevAudit {
+    pub rules_dirs: Vec<PathBuf>,
+}
+
+/// Provides the local udev rules directory.
+impl Default for UdevAudit {
+    fn default() -> Self {
+        Self {
+            rules_dirs: vec!["/etc/udev/rules.d".into()],
+        }
+    }
+}
+
+impl AuditFindings for UdevAudit {
+    fn run_audit(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for dir in &self.rules_dirs {
+            let Ok(entries) = sysroot::read_dir(dir) else {
+                continue;
+            };
+            let mut files: Vec<PathBuf> = entries
+                .into_iter()
+                .filter(|p| p.extension().is_some_and(|e| e == "rules"))
+                .collect();
+            files.sort();
+            for path in files {
+                let content = match sysroot::read_to_string(&path) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        findings.push(Finding::fail(
+                            "udev-unreadable",
+                            &path,
+                            Severity::Info,
+                            format!("Cannot read udev rules: {}", e),
+                        ));
+                        continue;
+                    }
+                };
+                let file_findings = check_udev_rules(&path, &content);
+                if file_findings.is_empty() {
+                    findings.push(Finding::pass(
+                        "udev-rules",
+                        &path,
+                        "No permissive device rules found",
+                    ));
+                }
+                findings.extend(file_findings);
+            }
+        }
+        findings
+    }
+}
+
+/// Checks the rules in a udev rules file.
+///
+/// # Arguments
+/// * `path` - Path reported in the findings
+/// * `content` - Contents of the `.rules` file
+///
+/// # Returns
+/// Vector of failing `Finding`s, each with the offending rule as its subject.
+pub fn check_udev_rules(path: &Path, content: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let rules = logical_lines(content)
+        .into_iter()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    for rule in rules {
+        let pairs = parse_pairs(&rule);
+        let sensitive = pairs.iter().any(|(key, op, value)| {
+            *op == "=="
+                && ((key == "SUBSYSTEM" && SENSITIVE_SUBSYSTEMS.contains(&value.as_str()))
+                    || (key == "KERNEL"
+                        && SENSITIVE_KERNEL_PREFIXES.iter().any(|p| value.starts_with(p))))
+        });
+
+        for (key, op, value) in &pairs {
+            if *op == "==" || *op == "!=" {
+                continue;
+            }
+            let finding = match key.as_str() {
+                "MODE" => {
+                    let Ok(mode) = u32::from_str_radix(value, 8) else {
+                        continue;
+                    };
+                    if sensitive && mode & 0o006 != 0 {
+                        Finding::fail(
+                            "udev-permissive-mode",
+                            path,
+                            Severity::High,
+                            format!(
+                                "MODE={} lets every local user access a sensitive device (raw disks, memory, keyboards...)",
+                                value
+                            ),
+                        )
+                    } else if mode & 0o002 != 0 {
+                        Finding::fail(
+                            "udev-permissive-mode",
+                            path,
+                            Severity::Low,
+                            format!("MODE={} makes the device world-writable", value),
+                        )
+                    } else {
+                        continue;
+                    }
+                }
+                "GROUP" if sensitive && !DEVICE_GROUPS.contains(&value.as_str()) => {
+                    Finding::fail(
+                        "udev-group-override",
+                        path,
+                        Severity::Medium,
+                        format!(
+                            "GROUP={} grants members of a non-standard group access to a sensitive device",
+                            value
+                        ),
+                    )
+                }
+                "RUN" => {
+                    let program = value.split_whitespace().next().unwrap_or_default();
+                    if !user_writable(Path::new(program)) {
+                        continue;
+                    }
+                    Finding::fail(
+                        "udev-run-writable",
+                        path,
+                        Severity::High,
+                        format!(
+                            "RUN program {} is in a user-writable location and runs as root when the device appears",
+                            program
+                        ),
+                    )
+                }
+                _ => continue,
+            };
+            findings.push(finding.with_subject(rule.as_str()));
+        }
+    }
+    findings
+}
+
+/// Returns true if a program path can be replaced by an unprivileged user.
+fn user_writable(program: &Path) -> bool {
+    // Relative names are resolved from /usr/lib/udev
+    if !program.is_absolute() {
+        return false;
+    }
+    if USER_WRITABLE_PREFIXES.iter().any(|p| program.starts_with(p)) {
+        return true;
+    }
+    let writable = |p: &Path| sysroot::metadata(p).is_ok_and(|m| m.mode() & 0o002 != 0);
+    // A world-writable parent without the sticky bit lets anyone replace the program
+    let parent_writable = program.parent().is_some_and(|dir| {
+        sysroot::metadata(dir).is_ok_and(|m| m.mode() & 0o002 != 0 && m.mode() & 0o1000 == 0)
+    });
+    writable(program) || parent_writable
+}
+
+/// Parses `KEY{attr}=="value", KEY+="value"` pairs from a rule.
+fn parse_pairs(rule: &str) -> Vec<(String, &'static str, String)> {
+    const OPERATORS: &[&str] = &["==", "!=", "+=", "-=", ":=", "="];
+    let mut pairs = Vec::new();
+    let mut rest = rule;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        // Attribute names in braces may contain operator characters, e.g. ENV{ID_FS-TYPE}
+        let mut depth = 0;
+        let Some(op_start) = rest.char_indices().find_map(|(i, c)| match c {
+            '{' => {
+                depth += 1;
+                None
+            }
+            '}' => {
+                depth -= 1;
+                None
+            }
+            '=' | '!' | '+' | '-' | ':' if depth == 0 => Some(i),
+            _ => None,
+        }) else {
+            break;
+        };
+        let key = rest[..op_start].trim();
+        let key = key.split('{').next().unwrap_or(key).to_string();
+        let Some(op) = OPERATORS
+            .iter()
+            .find(|op| rest[op_start..].starts_with(*op))
+        else {
+            break;
+        };
+        rest = rest[op_start + op.len()..].trim_start();
+        let Some(quoted) = rest.strip_prefix('"') else {
+            break;
+        };
+        let Some(end) = quoted.find('"') else {
+            break;
+        };
+        pairs.push((key, *op, quoted[..end].to_string()));
+        rest = &quoted[end + 1..];
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checks(content: &str) -> Vec<(String, Severity)> {
+        check_udev_rules(Path::new("99-test.rules"), content)
+            .into_iter()
+            .map(|f| (f.check, f.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_permissive_mode_on_sensitive_device() {
+        let rules = "SUBSYSTEM==\"block\", KERNEL==\"sd*\", MODE=\"0666\"\n";
+        assert_eq!(
+            checks(rules),
+            vec![("udev-permissive-mode".to_string(), Severity::High)]
+        );
+        // World-writable but not sensitive
+        let rules = "SUBSYSTEM==\"usb\", ATTRS{idVendor}==\"1234\", MODE=\"0666\"\n";
+        assert_eq!(
+            checks(rules),
+            vec![("udev-permissive-mode".to_string(), Severity::Low)]
+        );
+        // Group access only
+        assert!(checks("KERNEL==\"hidraw*\", MODE=\"0660\", GROUP=\"plugdev\"\n").is_empty());
+    }
+
+    #[test]
+    fn test_group_override() {
+        let rules = "# comment\nKERNEL==\"event*\", \\\n  GROUP=\"games\"\n";
+        let findings = check_udev_rules(Path::new("99-test.rules"), rules);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].check, "udev-group-override");
+        assert_eq!(
+            findings[0].subject.as_deref(),
+            Some("KERNEL==\"event*\", GROUP=\"games\"")
+        );
+    }
+
+    #[test]
+    fn test_run_in_user_writable_location() {
+        let rules = "ACTION==\"add\", RUN+=\"/home/alice/mount.sh %k\"\n\
+                     ACTION==\"add\", RUN+=\"/usr/bin/logger added\"\n\
+                     ACTION==\"add\", RUN+=\"helper\"\n";
+        assert_eq!(
+            checks(rules),
+            vec![("udev-run-writable".to_string(), Severity::High)]
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Catalog of HALO's capabilities for discovery.
+//!
+//! `halo list targets|profiles|formats|checks` prints what HALO can do with a one-line
+//! description each, so users do not have to read the module docs to find a target name or
+//! look up what a check id in a report means.
+//!
+//! Targets come from [`Target`] and the registered and installed [plugins](crate::plugin),
+//! formats from [`OutputFormat`], so new variants show up without further changes. Check ids are kept in [`CHECKS`]; an audit that adds a check adds
+//! it there as well. Profiles defined in a config file are listed with [`config_profiles`].
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::catalog::{CatalogKind, entries};
+//! use alhalo::Renderable;
+//! entries(CatalogKind::Checks).render_and_print(Some("text"));
+//! ```
+use crate::audit::builder::Target;
+use crate::audit::cis::CisLevel;
+use crate::audit::footprint::Footprint;
+use crate::audit::toml_config::AuditConfig;
+use crate::render_output::{DataList, OutputFormat, Renderable};
+use indexmap::IndexMap;
+use serde::Serialize;
+
+/// Pseudo-target of checks run by config rules, e.g. `[[content_rules]]`.
+pub const CONFIG: &str = "config";
+
+/// Pseudo-target of checks reported for any [plugin](crate::plugin).
+pub const PLUGIN: &str = "plugin";
+
+/// What to list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CatalogKind {
+    /// Built-in audit targets and plugins for `check --target`
+    Targets,
+    /// Resource profiles and benchmark profiles for `check --profile`
+    Profiles,
+    /// Output formats for `--format`
+    Formats,
+    /// Check ids reported by content and policy audits
+    Checks,
+}
+
+/// One listed item.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CatalogEntry {
+    pub name: String,
+    /// Target the entry belongs to, for checks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub description: String,
+}
+
+impl Renderable for CatalogEntry {
+    fn to_datalist(&self) -> DataList {
+        let mut map = IndexMap::new();
+        map.insert("name".to_string(), self.name.clone());
+        if let Some(target) = &self.target {
+            map.insert("target".to_string(), target.clone());
+        }
+        map.insert("description".to_string(), self.description.clone());
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        match &self.target {
+            Some(target) => format!("{:<28} [{}] {}", self.name, target, self.description),
+            None => format!("{:<16} {}", self.name, self.description),
+        }
+    }
+}
+
+/// Check ids of the content and policy audits: (target or profile, check id, description).
+///
+/// Checks that only run from config rules are listed under [`CONFIG`].
+pub const CHECKS: &[(&str, &str, &str)] = &[
+    ("user", "passwd-duplicate-uid0", "Accounts other than root with UID 0"),
+    ("user", "passwd-system-login-shell", "System accounts with an interactive login shell"),
+    ("user", "passwd-missing-group", "Primary GIDs with no entry in /etc/group"),
+    ("user", "passwd-orphaned-uid", "Files under /home owned by UIDs without an account"),
+    ("user", "passwd-unreadable", "/etc/passwd could not be read"),
+    ("user", "group-unreadable", "/etc/group could not be read"),
+    ("log", "logrotate-config", "/etc/logrotate.conf exists"),
+    ("log", "logrotate-coverage", "Key log is covered by a logrotate entry"),
+    ("log", "logrotate-oversized-log", "Log file larger than the limit; rotation may be broken"),
+    ("sysctl", "sysctl", "Kernel parameter differs from the hardening profile"),
+    ("sysctl", "sysctl-unavailable", "Kernel parameter could not be read"),
+    ("config", "content-must-match", "File has no match for a required regex"),
+    ("config", "content-must-not-match", "File has a match for a forbidden regex"),
+    ("config", "content-unreadable", "File named by a content rule could not be read"),
+    ("config", "path-must-not-exist", "Path forbidden by an absence rule exists"),
+    ("config", "rule-conflict", "Two rules expect different modes or owners for the same path"),
+    ("sudoers", "sudoers-nopasswd-all", "NOPASSWD applied to the ALL command"),
+    ("sudoers", "sudoers-no-authenticate", "Defaults !authenticate"),
+    ("sudoers", "sudoers-wildcard-command", "Command specs or aliases containing wildcards"),
+    ("sudoers", "sudoers-world-writable", "World-writable sudoers files or include directories"),
+    ("sudoers", "sudoers-policy", "Sudoers policy passed all checks"),
+    ("sudoers", "sudoers-unreadable", "Sudoers files could not be read"),
+    ("accounts", "login-defs-pass-max-days", "PASS_MAX_DAYS missing or above the maximum"),
+    ("accounts", "login-defs-pass-min-days", "PASS_MIN_DAYS missing or below the minimum"),
+    ("accounts", "login-defs-pass-warn-age", "PASS_WARN_AGE missing or below the minimum"),
+    ("accounts", "login-defs-unreadable", "/etc/login.defs could not be read"),
+    ("accounts", "account-empty-password", "Account with an empty password"),
+    ("accounts", "account-locked", "Account is locked"),
+    ("accounts", "account-password-aging", "Password never expires or exceeds the maximum age"),
+    ("accounts", "shadow-unreadable", "/etc/shadow could not be read"),
+    ("auditd", "auditd-installed", "auditd is installed"),
+    ("auditd", "auditd-running", "auditd is running"),
+    ("auditd", "auditd-missing-watch", "Required file has no write watch"),
+    ("polkit", "polkit-world-writable", "World-writable polkit rule files or directories"),
+    ("polkit", "polkit-admin-group", "addAdminRule grants admin rights to a non-admin group"),
+    ("polkit", "polkit-grant-non-admin-group", "Rule returns YES for a non-admin group"),
+    ("polkit", "polkit-unconditional-yes", "Rule returns YES without checking the subject"),
+    ("polkit", "dbus-world-writable", "World-writable D-Bus policy files or directories"),
+    ("polkit", "dbus-send-destination-any", "D-Bus service open to every user without restriction"),
+    ("udev", "udev-permissive-mode", "Permissive MODE on a sensitive device class"),
+    ("udev", "udev-group-override", "GROUP hands a sensitive device to a non-standard group"),
+    ("udev", "udev-run-writable", "RUN+= program in a user-writable location"),
+    ("udev", "udev-rules", "Udev rules passed all checks"),
+    ("udev", "udev-unreadable", "Udev rules could not be read"),
+    ("docker", "docker", "Docker is not installed"),
+    ("docker", "docker-socket-owner", "Docker socket owned by root and group root or docker"),
+    ("docker", "docker-socket-mode", "Docker socket not accessible to other users"),
+    ("docker", "docker-daemon-json", "daemon.json could not be parsed"),
+    ("docker", "docker-userns-remap", "User namespace remapping enabled"),
+    ("docker", "docker-live-restore", "Live restore enabled"),
+    ("docker", "docker-tcp-without-tls", "Daemon listens on TCP without tlsverify"),
+    ("docker", "docker-group-member", "Member of the docker group (root equivalent)"),
+    ("docker", "docker-container", "Privileged, host-namespace or sensitive-mount container"),
+    ("docker", "docker-api", "Docker API could not be queried"),
+    ("environ", "environ-secret", "Secret-looking variables in a process environment"),
+    ("environ", "environ-unreadable", "Process environments could not be read"),
+    ("firewall", "firewall-active", "A firewall is loaded"),
+    ("firewall", "firewall-backend", "The active firewall is the required one"),
+    ("firewall", "firewall-default-deny", "Inbound traffic is dropped unless allowed"),
+    ("firewall", "firewall-ssh-rate-limit", "New SSH connections are rate limited"),
+    ("firewall", "firewall-capture", "Captured ruleset could not be saved"),
+    ("firewall", "firewall-unavailable", "An installed firewall tool could not be run"),
+    ("tls", "tls-cert-expiry", "Certificate expired or expiring within the warning window"),
+    ("tls", "tls-key-mode", "Private key readable by group or others"),
+    ("tls", "tls-key-owner", "Private key not owned by root"),
+    ("tls", "tls-cert-unparsable", "PEM certificate could not be decoded"),
+    ("hardening", "hardening-core-pattern", "Core dumps written to a shared or unknown directory"),
+    ("hardening", "hardening-suid-dumpable", "setuid programs dump core"),
+    ("hardening", "hardening-core-limit", "No hard core 0 limit for all users"),
+    ("hardening", "hardening-proc-hidepid", "/proc mounted without hidepid"),
+    ("cis-l1", "cis-file-permissions", "File has bits beyond the CIS mode or is not owned by root"),
+    ("embedded", "embedded-default-pi-user", "Default pi account exists and is not locked"),
+    ("immutable", "immutable-distro", "No immutable distribution detected"),
+    ("immutable", "nixos-etc-static", "/etc/static points into /nix/store"),
+    ("immutable", "nixos-etc-symlink-target", "/etc symlinks point into the Nix store"),
+    ("immutable", "nixos-store-writable", "/nix/store is not world-writable"),
+    ("immutable", "ostree-gpg-verify", "ostree remotes verify GPG signatures"),
+    ("image", "image-setuid", "Setuid program in a container image"),
+    ("image", "image-setgid", "Setgid program in a container image"),
+    ("image", "image-ownership", "Path under a system directory of an image not owned by root"),
+    ("plugin", "plugin-error", "Plugin executable could not run, failed or printed invalid findings"),
+];
+
+/// Everything of one kind, in a stable order.
+pub fn entries(kind: CatalogKind) -> Vec<CatalogEntry> {
+    match kind {
+        CatalogKind::Targets => Target::ALL
+            .iter()
+            .map(|t| entry(t.name(), t.description()))
+            .chain(crate::plugin::plugins().iter().map(|p| entry(p.name(), p.description())))
+            .collect(),
+        CatalogKind::Profiles => [Footprint::Standard, Footprint::Low]
+            .iter()
+            .map(|f| entry(f.name(), f.description()))
+            .chain(CisLevel::ALL.iter().map(|l| entry(l.name(), l.description())))
+            .collect(),
+        CatalogKind::Formats => OutputFormat::ALL
+            .iter()
+            .map(|f| entry(f.name(), f.description()))
+            .collect(),
+        CatalogKind::Checks => CHECKS
+            .iter()
+            .map(|(target, id, description)| CatalogEntry {
+                target: Some(target.to_string()),
+                ..entry(id, *description)
+            })
+            .collect(),
+    }
+}
+
+/// The `[profiles.<name>]` of a config, in file order, with [`CONFIG`] as target.
+pub fn config_profiles(config: &AuditConfig) -> Vec<CatalogEntry> {
+    config
+        .profiles
+        .iter()
+        .map(|(name, profile)| CatalogEntry {
+            target: Some(CONFIG.to_string()),
+            ..entry(name, profile.description.clone().unwrap_or_else(|| "Config profile".to_string()))
+        })
+        .collect()
+}
+
+fn entry(name: &str, description: impl Into<String>) -> CatalogEntry {
+    CatalogEntry {
+        name: name.to_string(),
+        target: None,
+        description: description.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_entries_are_described_and_unique() {
+        for kind in [CatalogKind::Targets, CatalogKind::Profiles, CatalogKind::Formats, CatalogKind::Checks] {
+            let entries = entries(kind);
+            assert!(!entries.is_empty());
+            assert!(entries.iter().all(|e| !e.description.is_empty()), "{:?}", kind);
+            let names: HashSet<_> = entries.iter().map(|e| &e.name).collect();
+            assert_eq!(names.len(), entries.len(), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn test_checks_belong_to_targets() {
+        let targets: HashSet<_> = entries(CatalogKind::Targets)
+            .into_iter()
+            .chain(entries(CatalogKind::Profiles))
+            .map(|e| e.name)
+            .chain([CONFIG, PLUGIN, "image"].map(String::from))
+            .collect();
+        for (target, id, _) in CHECKS {
+            assert!(targets.contains(*target), "{} has unknown target {}", id, target);
+        }
+    }
+}
@@ -0,0 +1,438 @@
+// Output of `halo check`: rendering of the report returned by `handle_check` in the selected
+// format, summaries, suggested fixes and side outputs (stored files, history, webhook)
+use alhalo::{AuditReport, Footprint, ImmutableDistro, Renderable, Severity, StatusCounts};
+use alhalo::heatmap::Heatmap;
+use alhalo::history::History;
+use alhalo::render_output::{OutputFormat, RenderOptions};
+use alhalo::state::StateStore;
+use alhalo::stream::ResultStream;
+use alhalo::teams::TeamMap;
+use alhalo::template::ReportTemplate;
+use alhalo::webhook::Webhook;
+use crate::handlers::check::CheckRequest;
+use serde::Serialize;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{error, info};
+
+// Prints human text for `format`: to stdout, or to stderr when stdout carries a
+// machine-readable format, so `check -f json | jq` gets nothing but the data
+macro_rules! say {
+    ($format:expr) => {
+        say!($format, "")
+    };
+    ($format:expr, $($arg:tt)*) => {
+        if machine_readable($format) {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+// Formats read by programs rather than people
+fn machine_readable(format: Option<&str>) -> bool {
+    matches!(OutputFormat::from_str(format), OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::Csv)
+}
+
+// Prints a section after the results: in `format` on stdout, or as pretty text on stderr for
+// machine-readable formats
+fn print_section<T: Renderable + Serialize>(item: &T, format: Option<&str>, options: &RenderOptions) {
+    if machine_readable(format) {
+        eprint!("{}", item.pretty_print_with(options));
+    } else {
+        item.render_and_print_with(format, options);
+    }
+}
+
+// Prints the report of `request` and returns the number of open failures that were
+// reported, i.e. at or above `--min-severity` if given, and at or above `--fail-on` if
+// given. `streaming` means the results were already written as JSON Lines.
+#[allow(clippy::too_many_arguments)]
+pub fn print_check(
+    request: &CheckRequest,
+    mut report: AuditReport,
+    view: &ReportView,
+    format: Option<&str>,
+    store: Option<&PathBuf>,
+    record: bool,
+    streaming: bool,
+) -> usize {
+    view.apply(&mut report);
+    view.post(&report);
+    if request.is_config() {
+        return print_config_report(&report, view, format, store, streaming);
+    }
+    let (failures, whole_report) = if request.checks_permissions() {
+        print_permission_report(&report, view, format, store, record, request.footprint, streaming)
+    } else {
+        (view.failures(&report), false)
+    };
+    // The whole report already lists the ownership results
+    if request.checks_ownership() && !streaming && !whole_report {
+        report.ownership.render_and_print_with(format, &view.render);
+    }
+    failures
+}
+
+// Prints the report of a config file or benchmark profile
+fn print_config_report(
+    report: &AuditReport,
+    view: &ReportView,
+    format: Option<&str>,
+    store: Option<&PathBuf>,
+    streaming: bool,
+) -> usize {
+    if streaming {
+        return view.failures(report);
+    }
+    if view.render_summary(report, format)
+        || view.render_html(report, format, store)
+        || view.render_template(report, store)
+    {
+        return view.failures(report);
+    }
+    let heatmap = view.render_heatmap(report, format);
+    let whole_report = !heatmap && view.render_report(report, format, store);
+    if !heatmap && !whole_report {
+        report.permissions.render_and_print_with(format, &view.render);
+        if !report.ownership.is_empty() {
+            report.ownership.render_and_print_with(format, &view.render);
+        }
+        if !report.findings.is_empty() {
+            report.findings.render_and_print_with(format, &view.render);
+        }
+    }
+    view.print_summary(report, format, whole_report);
+    view.failures(report)
+}
+
+// Prints the permission results of a target or path with the failed paths and suggested
+// fixes, and records the run with `--record`. Returns the failures and whether the whole
+// report was printed
+fn print_permission_report(
+    report: &AuditReport,
+    view: &ReportView,
+    format: Option<&str>,
+    store: Option<&PathBuf>,
+    record: bool,
+    footprint: Footprint,
+    streaming: bool,
+) -> (usize, bool) {
+    // The stream or template output already holds every result; keep stdout free of anything
+    // else
+    if streaming || view.render_template(report, store) {
+        if record {
+            match record_run(report) {
+                Ok(id) => eprintln!("Run recorded as {}", id),
+                Err(e) => eprintln!("Failed to record run: {}", e),
+            }
+        }
+        return (view.failures(report), false);
+    }
+
+    if !footprint.keep_passing() {
+        say!(format, "Low-footprint mode: passing results are not retained");
+    }
+    let results = &report.permissions;
+    let findings = &report.findings;
+
+    // Handle output rendering; the summary and the HTML page carry the summary sections
+    // themselves
+    let summarized = view.render_summary(report, format)
+        || view.render_html(report, format, store);
+    let mut whole_report = false;
+    if !summarized && format.is_some() && !view.render_heatmap(report, format) {
+        whole_report = view.render_report(report, format, store);
+        if !whole_report {
+            // Use trait-based rendering for the per-result formats
+            if !results.is_empty() || findings.is_empty() {
+                results.render_and_print_with(format, &view.render);
+            }
+            if !findings.is_empty() {
+                findings.render_and_print_with(format, &view.render);
+            }
+        }
+    }
+
+    if !summarized {
+        view.print_summary(report, format, whole_report);
+    }
+
+    if record {
+        match record_run(report) {
+            Ok(id) => say!(format, "Run recorded as {}", id),
+            Err(e) => eprintln!("Failed to record run: {}", e),
+        }
+    }
+    if view.summary_only {
+        return (view.failures(report), whole_report);
+    }
+
+    // Print summary and suggested fixes
+    let total = results.len();
+    let counts: StatusCounts = results.iter().map(|r| &r.status).collect();
+    let failed: Vec<_> = report.failed_permissions().collect();
+    if total > 0 || findings.is_empty() {
+        say!(
+            format,
+            "\nSummary: {} checked, {} passed, {} strict, {} failed",
+            total, counts.passed, counts.strict, failed.len()
+        );
+    }
+    // On NixOS/ostree, paths in the read-only store cannot be fixed with chmod
+    let distro = ImmutableDistro::detect();
+    let read_only = |p: &std::path::Path| distro.as_ref().is_some_and(|d| d.is_read_only(p));
+    for r in &failed {
+        say!(
+            format,
+            "[!] FAIL: {} (found: {:o}, expected: {:o}) ({})",
+            r.path.display(),
+            r.found_mode,
+            r.expected_mode,
+            r.id()
+        );
+        if let Some(d) = distro.as_ref().filter(|_| read_only(&r.path)) {
+            say!(format, "    No chmod suggested: {} ({})", d.fix_hint(), d.name);
+        } else if r.found_mode != r.expected_mode && r.path.is_file() && r.expected_mode != 0 {
+            say!(
+                format,
+                "    Suggested fix: # chmod {:o} {}",
+                r.expected_mode,
+                r.path.display()
+            );
+        }
+        if let Some(err) = &r.error {
+            say!(format, "    Error: {}", err);
+        }
+    }
+    if !findings.is_empty() {
+        let failed_findings: Vec<_> = findings.iter().filter(|f| f.is_open_failure()).collect();
+        say!(
+            format,
+            "\nPolicy findings: {} checked, {} failed, {} acknowledged",
+            findings.len(),
+            failed_findings.len(),
+            report.acknowledged_count()
+        );
+        for f in failed_findings {
+            say!(
+                format,
+                "[!] {:?}: {} - {} ({})",
+                f.severity,
+                f.path.display(),
+                f.message,
+                f.id
+            );
+        }
+        for f in findings.iter().filter(|f| f.acknowledged.is_some()) {
+            let comment = f.acknowledged.as_ref().map(|a| a.comment.as_str()).unwrap_or_default();
+            say!(format, "[ack] {}: {} - {} ({})", f.id, f.path.display(), f.message, comment);
+        }
+    }
+    // Fixes are applied by `halo fix`, never from here, so `check` stays non-interactive
+    if failed.iter().any(|r| !read_only(&r.path)) {
+        let stored = store.filter(|_| format == Some(OutputFormat::Json.name()));
+        match stored {
+            Some(path) => say!(format, "\nTo apply the suggested fixes: halo fix --from {}", path.display()),
+            None => say!(
+                format,
+                "\nTo apply the suggested fixes, store the report with --format json --store report.json, then: halo fix --from report.json"
+            ),
+        }
+    }
+    (view.failures(report), whole_report)
+}
+
+
+// Opens the JSON Lines stream for `--format jsonl`: to `store` if given, stdout otherwise.
+// Returns `None` for other formats.
+pub fn open_stream(format: Option<&str>, store: Option<&PathBuf>, view: &ReportView) -> io::Result<Option<ResultStream>> {
+    if view.summary_only || !matches!(OutputFormat::from_str(format), OutputFormat::Jsonl) {
+        return Ok(None);
+    }
+    let mut stream = match store {
+        Some(path) => ResultStream::new(io::BufWriter::new(std::fs::File::create(path)?)),
+        None => ResultStream::new(io::stdout()),
+    };
+    if let Some(min) = view.min_severity {
+        stream = stream.min_severity(min.clone());
+    }
+    if view.only_failures {
+        stream = stream.only_failures();
+    }
+    let acks = StateStore::new(StateStore::default_dir()).acknowledgments().unwrap_or_default();
+    Ok(Some(stream.acknowledgments(acks)))
+}
+
+// Records the report in the default state directory; returns the run id
+fn record_run(report: &AuditReport) -> io::Result<String> {
+    let run = serde_json::to_string(report).unwrap_or_default();
+    StateStore::new(StateStore::default_dir())
+        .record_run(&run)
+        .map(|stored| stored.id)
+}
+
+// How results are presented: team map and filter from `--owners` / `--owner`, the
+// `--heatmap` depth, the `--min-severity` and `--fail-on` thresholds, the `--template`, the
+// `--sort` and `--group-by` options, the `--only-failures` and `--summary-only` flags, the
+// `--post-url` webhook and the `--history` database
+pub struct ReportView<'a> {
+    pub teams: Option<&'a TeamMap>,
+    pub template: Option<&'a ReportTemplate>,
+    pub render: RenderOptions,
+    pub only_failures: bool,
+    pub summary_only: bool,
+    pub owner: Option<&'a str>,
+    pub heatmap: Option<usize>,
+    pub min_severity: Option<&'a Severity>,
+    pub fail_on: Option<&'a Severity>,
+    pub webhook: Option<&'a Webhook>,
+    pub history: Option<&'a Path>,
+}
+
+impl ReportView<'_> {
+    // Restricts the report to the selected team and severity threshold, if any, and orders it
+    fn apply(&self, report: &mut AuditReport) {
+        if let Some(key) = self.render.sort {
+            report.sort(key);
+        }
+        if let (Some(teams), Some(owner)) = (self.teams, self.owner) {
+            report.retain_team(teams, owner);
+        }
+        if let Some(min) = self.min_severity {
+            report.retain_min_severity(min);
+        }
+        if self.only_failures {
+            report.retain_failures();
+        }
+    }
+
+    // Open failures that count for the exit status: all, or those at or above `--fail-on`
+    fn failures(&self, report: &AuditReport) -> usize {
+        match self.fail_on {
+            Some(min) => report.failure_count_at_least(min),
+            None => report.failure_count(),
+        }
+    }
+
+    // Records the report in the `--history` database and delivers it to the `--post-url`
+    // webhook, if set; errors go to stderr so the rendered output stays intact
+    fn post(&self, report: &AuditReport) {
+        if let Some(db) = self.history {
+            match History::open(db).and_then(|mut history| history.record(report)) {
+                Ok(id) => info!("Run recorded in {} as {}", db.display(), id),
+                Err(e) => error!("Failed to record run in history: {}", e),
+            }
+        }
+        let Some(webhook) = self.webhook else {
+            return;
+        };
+        match webhook.post(report) {
+            Ok(status) => info!("Report posted to {} (HTTP {})", webhook.url(), status),
+            Err(e) => error!("Failed to post report: {}", e),
+        }
+    }
+
+    // The CSV dialect without sorting or grouping, for counts and scores
+    fn csv_only(&self) -> RenderOptions {
+        RenderOptions::default().csv(self.render.csv)
+    }
+
+    // Prints only the result counts; false without `--summary-only`
+    fn render_summary(&self, report: &AuditReport, format: Option<&str>) -> bool {
+        if !self.summary_only {
+            return false;
+        }
+        report.summary().render_and_print_with(format, &self.csv_only());
+        true
+    }
+
+    // Renders the directory heatmap instead of the flat result list; false if not requested
+    fn render_heatmap(&self, report: &AuditReport, format: Option<&str>) -> bool {
+        let Some(depth) = self.heatmap else {
+            return false;
+        };
+        Heatmap::from_report(report, depth).render_and_print(format);
+        true
+    }
+
+    // Prints the whole report with its run metadata and summary instead of the flat result
+    // lists; JSON goes to `store` if given. False for the per-result formats (CSV, JSON Lines)
+    // unless `--group-by` is set
+    fn render_report(&self, report: &AuditReport, format: Option<&str>, store: Option<&PathBuf>) -> bool {
+        let whole = [OutputFormat::Pretty, OutputFormat::Json, OutputFormat::Text, OutputFormat::Markdown];
+        if !whole.iter().any(|f| format == Some(f.name())) && self.render.group_by.is_none() {
+            return false;
+        }
+        if format == Some(OutputFormat::Json.name())
+            && let Some(path) = store
+        {
+            let stored = std::fs::File::create(path).and_then(|file| {
+                report.render_to_with(OutputFormat::Json, &self.render, io::BufWriter::new(file))
+            });
+            match stored {
+                Ok(()) => say!(format, "JSON report stored to {}", path.display()),
+                Err(e) => eprintln!("Failed to store output: {}", e),
+            }
+            return true;
+        }
+        report.render_and_print_with(format, &self.render);
+        true
+    }
+
+    // Renders the whole report as one HTML page, to `store` if given; returns false for other
+    // formats
+    fn render_html(&self, report: &AuditReport, format: Option<&str>, store: Option<&PathBuf>) -> bool {
+        if format != Some(OutputFormat::Html.name()) {
+            return false;
+        }
+        let page = report.html();
+        match store {
+            Some(path) => match std::fs::write(path, &page) {
+                Ok(()) => println!("HTML report stored to {}", path.display()),
+                Err(e) => eprintln!("Failed to store output: {}", e),
+            },
+            None => print!("{}", page),
+        }
+        true
+    }
+
+    // Renders the report with the user's template, to `store` if given; returns false without
+    // a template
+    fn render_template(&self, report: &AuditReport, store: Option<&PathBuf>) -> bool {
+        let Some(template) = self.template else {
+            return false;
+        };
+        match template.render(report) {
+            Ok(text) => match store {
+                Some(path) => match std::fs::write(path, &text) {
+                    Ok(()) => println!("Template output stored to {}", path.display()),
+                    Err(e) => eprintln!("Failed to store output: {}", e),
+                },
+                None => print!("{}", text),
+            },
+            Err(e) => eprintln!("Error rendering template: {}", e),
+        }
+        true
+    }
+
+    // Prints waived failures, per-team result counts when a team map is loaded, and the host
+    // risk score; `whole_report` output already lists waived failures, and as JSON the score
+    fn print_summary(&self, report: &AuditReport, format: Option<&str>, whole_report: bool) {
+        if !report.waived.is_empty() && !whole_report {
+            say!(format, "\nWaived:");
+            print_section(&report.waived, format, &self.render);
+        }
+        if let Some(teams) = self.teams {
+            say!(format, "\nPer-team summary:");
+            print_section(&report.team_summaries(teams), format, &self.csv_only());
+        }
+        // The JSON report carries the score in its summary
+        let in_report = whole_report && format == Some(OutputFormat::Json.name());
+        if !report.is_empty() && !in_report {
+            say!(format);
+            print_section(&report.risk_score(), format, &self.csv_only());
+        }
+    }
+}
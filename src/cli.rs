@@ -1,9 +1,31 @@
-use crate::handlers::{handle_bash, handle_net, handle_parse, handle_check};
-use crate::handlers::check::AuditTarget;
-use alhalo::Importance;
-use clap::{ArgGroup, Parser, Subcommand};
+use crate::handlers::{handle_ack, handle_completions, handle_daemon, handle_diff, handle_fix, handle_history, handle_list, handle_monitor, handle_net, handle_parse, handle_check, handle_prune, handle_schema, handle_snapshot, handle_trend, handle_watch, handle_keys, handle_verify, handle_fleet, handle_image};
+use crate::handlers::keys::sign_report;
+use crate::check_output::{ReportView, open_stream, print_check};
+use crate::handlers::check::{CheckRequest, ScanOptions};
+use crate::handlers::fix::FixSource;
+use alhalo::Target;
+use alhalo::audit::scan::{CancelToken, ScanLimits};
+use alhalo::audit::sysroot;
+use alhalo::catalog::CatalogKind;
+use alhalo::parsers::FileParser;
+use alhalo::history::History;
+use alhalo::state::StateStore;
+use alhalo::teams::TeamMap;
+use alhalo::template::ReportTemplate;
+use alhalo::{Footprint, HaloError, Importance, MatchOn, Severity};
+use alhalo::render_output::{CsvOptions, GroupBy, RenderOptions, SortKey};
+use alhalo::watch::parse_interval;
+use alhalo::webhook::Webhook;
+use clap::builder::{PossibleValue, TypedValueParser};
+use clap::error::ErrorKind;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use std::ffi::OsStr;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
 /// Command-line interface for HALO
 #[derive(Parser, Debug)]
@@ -16,9 +38,41 @@ help_template = "\
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    #[arg(long, global = true, help = "Disable colors in pretty output; NO_COLOR in the environment does the same")]
+    pub no_color: bool,
+
+    #[arg(
+        short = 'v',
+        long,
+        global = true,
+        action = clap::ArgAction::Count,
+        help = "Show diagnostics on stderr: -v for skipped paths, unreadable directories and timing, -vv for details"
+    )]
+    pub verbose: u8,
+
+    #[arg(
+        short = 'q',
+        long,
+        global = true,
+        conflicts_with = "verbose",
+        help = "Only show errors on stderr, no warnings"
+    )]
+    pub quiet: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        value_parser = parse_root,
+        help = "Audit the filesystem mounted at DIR (container image, chroot, rescued disk) instead of the running system; paths are reported as inside it: Example - check -t all --root /mnt/image"
+    )]
+    pub root: Option<PathBuf>,
 }
 
 /// CLI commands for HALO
+// Parsed once per command line, so `Check` carrying most of the options is fine
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Parse a file and render output in the selected format
@@ -41,8 +95,33 @@ pub enum Commands {
         )]
         line: Option<Vec<String>>,
 
+        #[arg(
+            long,
+            value_name = "PATTERN",
+            help = "Select lines whose key matches a regex, repeatable: Example - parse -F /proc/cpuinfo --line-regex '^model name'"
+        )]
+        line_regex: Vec<String>,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value = "keys",
+            requires = "line_regex",
+            help = "Match --line-regex against keys, values or both: Example - parse -F /proc/self/mountinfo -p mountinfo --line-regex nosuid --match-on values"
+        )]
+        match_on: MatchOn,
+
         #[arg(short = 's', long, help = "Store output to file")]
         store: Option<PathBuf>,
+
+        #[arg(
+            short = 'p',
+            long,
+            value_enum,
+            default_value = "colon",
+            help = "Select how lines are split: colon (key: value), keyvalue (KEY=value, os-release and sysctl.conf), fstab (/etc/fstab, /proc/mounts) or mountinfo (/proc/self/mountinfo): Example - parse -F /etc/os-release --parser keyvalue"
+        )]
+        parser: FileParser,
     },
 
     /// Check file permissions and/or ownership
@@ -50,7 +129,7 @@ pub enum Commands {
         group(
             ArgGroup::new("audit")
                 .required(false)
-                .args(&["target", "path"])
+                .args(&["target", "path", "profile"])
         ),
         group(
             ArgGroup::new("config")
@@ -60,13 +139,13 @@ pub enum Commands {
     )]
     Check {
         #[arg(
-            value_enum,
+            value_parser = TargetArgParser,
             short = 't',
             long,
             group = "audit",
-            help = "Select target files to check permissions: Example - check --target user"
+            help = "Select target files to check permissions, or a plugin (see list targets): Example - check --target user"
         )]
-        target: Option<AuditTarget>,
+        target: Option<TargetArg>,
         #[arg(
             short = 'p',
             long,
@@ -74,6 +153,13 @@ pub enum Commands {
             help = "Specify a path to check permissions, a file, or directory: Example - check --path /etc/shadow <expected_permissions, importance>"
         )]
         path: Option<PathBuf>,
+        #[arg(
+            long,
+            group = "audit",
+            value_name = "PROFILE",
+            help = "Run a benchmark profile, or a [profiles.<name>] subset of the --config file (see list profiles): Example - check --profile cis-l1, check --config fleet.toml --profile webserver"
+        )]
+        profile: Option<String>,
         #[arg(
             short = 'f',
             long,
@@ -114,11 +200,296 @@ pub enum Commands {
         #[arg(
             short = 'T',
             long,
-            help = "Select toml config file to load audit rules from: Example - check --toml config.toml"
+            visible_alias = "config",
+            help = "Select TOML, YAML or JSON config file (by extension) to load audit rules from: Example - check --config rules.yaml"
         )]
         toml: Option<PathBuf>,
-        #[arg(short = 's', long, help = "Store JSON output to file")]
+        #[arg(short = 's', long, help = "Store JSON output, the HTML report with --format html, or the --template output to file: Example - check --target all -f html --store report.html")]
         store: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["format", "heatmap"],
+            help = "Render the report with a Handlebars template instead of --format: Example - check -t all --template nagios.hbs"
+        )]
+        template: Option<PathBuf>,
+        #[arg(
+            value_enum,
+            long,
+            help = "Order results by severity (most severe first), path or status (failures first): Example - check -t all --sort severity -f csv"
+        )]
+        sort: Option<SortKey>,
+        #[arg(
+            value_enum,
+            long,
+            help = "Group results by the target that produced them or by severity: Example - check -t all --group-by severity -f markdown"
+        )]
+        group_by: Option<GroupBy>,
+        #[arg(
+            long,
+            action = clap::ArgAction::SetTrue,
+            help = "Only report failures and stricter-than-expected permissions: Example - check -t all --only-failures"
+        )]
+        only_failures: bool,
+        #[arg(
+            long,
+            value_name = "CHAR",
+            value_parser = parse_delimiter,
+            help = "Field delimiter for CSV output, a single character or 'tab': Example - check -t all -f csv --delimiter ';'"
+        )]
+        delimiter: Option<u8>,
+        #[arg(
+            long,
+            action = clap::ArgAction::SetTrue,
+            help = "Leave out the CSV header row: Example - check -t all -f csv --no-header"
+        )]
+        no_header: bool,
+        #[arg(
+            long,
+            action = clap::ArgAction::SetTrue,
+            conflicts_with_all = ["template", "heatmap", "group_by"],
+            help = "Only print result counts per status and severity and the risk score: Example - check -t all --summary-only"
+        )]
+        summary_only: bool,
+        #[arg(
+            short = 'r',
+            long,
+            action = clap::ArgAction::SetTrue,
+            help = "Record this run in the HALO state directory ($HALO_STATE_DIR): Example - check -t all --record"
+        )]
+        record: bool,
+        #[arg(
+            long,
+            value_name = "DB",
+            num_args = 0..=1,
+            help = "Record this run in a SQLite history database, by default ~/.local/share/halo/history.db ($HALO_HISTORY): Example - check -t all --history"
+        )]
+        history: Option<Option<PathBuf>>,
+        #[arg(
+            long,
+            value_enum,
+            num_args = 0..=1,
+            default_missing_value = "detached",
+            requires = "store",
+            help = "Sign the --store file with the key from `halo keys generate`, as <file>.sig or <file>.jws: Example - check -t all -f json --store report.json --sign"
+        )]
+        sign: Option<SignFormat>,
+        #[arg(
+            long,
+            action = clap::ArgAction::SetTrue,
+            help = "Minimize memory and CPU use on small devices; passing results are not retained: Example - check -t embedded --low-footprint"
+        )]
+        low_footprint: bool,
+        #[arg(
+            long,
+            help = "Team map (TOML) assigning path prefixes to teams; prints a per-team summary: Example - check -t all --owners teams.toml"
+        )]
+        owners: Option<PathBuf>,
+        #[arg(
+            long,
+            requires = "owners",
+            help = "Only report paths owned by this team in the --owners map: Example - check -t all --owners teams.toml --owner web-team"
+        )]
+        owner: Option<String>,
+        #[arg(
+            long,
+            num_args = 0..=1,
+            default_missing_value = "3",
+            value_name = "DEPTH",
+            help = "Show results as a directory tree of failure counts and worst severity, DEPTH levels deep (default 3): Example - check -t all --heatmap 2 -f pretty"
+        )]
+        heatmap: Option<usize>,
+        #[arg(
+            long,
+            value_name = "FILES_PER_SEC",
+            help = "Throttle permission walks to this many files per second: Example - check -p /srv -e 640 --io-rate 500"
+        )]
+        io_rate: Option<u32>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Checkpoint progress to FILE and resume from it if a previous run was interrupted: Example - check -T big.toml --checkpoint /var/lib/halo/scan.jsonl"
+        )]
+        checkpoint: Option<PathBuf>,
+        #[arg(
+            short = 'j',
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(usize),
+            help = "Walk directories with N threads; defaults to the number of CPUs, 1 with --low-footprint: Example - check -p /usr -e 755 --jobs 8"
+        )]
+        jobs: Option<usize>,
+        #[arg(
+            long,
+            help = "Do not descend into other filesystems during recursive walks; pseudo and network filesystems are always skipped unless a rule sets mounts = \"all\": Example - check -p / -e 755 --one-file-system"
+        )]
+        one_file_system: bool,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Stop a rule's walk after N files and report it as truncated: Example - check -p /srv -e 640 --max-files 1000000"
+        )]
+        max_files: Option<u64>,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Stop a rule's walk once it holds N results: Example - check -p /srv -e 640 --low-footprint --max-results 10000"
+        )]
+        max_results: Option<usize>,
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = parse_interval,
+            help = "Stop a rule's walk after DURATION (e.g. 90s, 15m): Example - check -T big.toml --max-runtime 15m"
+        )]
+        max_runtime: Option<Duration>,
+        #[arg(
+            value_enum,
+            long,
+            value_name = "SEVERITY",
+            help = "Only report failures at least this severe: Example - check -t all --min-severity high"
+        )]
+        min_severity: Option<Severity>,
+        #[arg(
+            value_enum,
+            long,
+            value_name = "SEVERITY",
+            default_value_t = FailOn::Any,
+            help = "Exit with status 1 if a reported failure is at least this severe, or never: Example - check -t all --fail-on critical"
+        )]
+        fail_on: FailOn,
+        #[arg(
+            long,
+            value_name = "URL",
+            help = "POST the JSON report to this HTTP endpoint: Example - check -t all --post-url https://soar.example.com/intake"
+        )]
+        post_url: Option<String>,
+        #[arg(
+            long,
+            value_name = "NAME: VALUE",
+            value_parser = parse_header,
+            requires = "post_url",
+            help = "Extra header for --post-url, repeatable: Example - check -t all --post-url https://... --post-header 'Authorization: Bearer TOKEN'"
+        )]
+        post_header: Vec<(String, String)>,
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 3,
+            requires = "post_url",
+            help = "Retry a failed --post-url delivery up to N times with exponential backoff: Example - check -t all --post-url https://... --post-retries 5"
+        )]
+        post_retries: u32,
+    },
+
+    /// Remove recorded runs outside the retention policy
+    Prune {
+        #[arg(
+            short = 'n',
+            long,
+            help = "Keep at most this many of the newest runs (default 100): Example - prune --keep-runs 20"
+        )]
+        keep_runs: Option<usize>,
+        #[arg(
+            short = 'd',
+            long,
+            help = "Remove runs older than this many days (default 90): Example - prune --keep-days 30"
+        )]
+        keep_days: Option<u64>,
+        #[arg(
+            long,
+            help = "Directory holding HALO state, overrides $HALO_STATE_DIR: Example - prune --state-dir /var/lib/halo"
+        )]
+        state_dir: Option<PathBuf>,
+        #[arg(
+            long,
+            action = clap::ArgAction::SetTrue,
+            help = "List the runs that would be removed without deleting them"
+        )]
+        dry_run: bool,
+    },
+
+    /// Record current modes and owners, optionally as a config to audit against later
+    Snapshot {
+        #[arg(required = true, help = "Files or directories to record, recursively: Example - snapshot /etc /var/log")]
+        paths: Vec<PathBuf>,
+        #[arg(
+            short = 'e',
+            long,
+            action = clap::ArgAction::SetTrue,
+            help = "Emit a TOML config with one permission and one ownership rule per path: Example - snapshot --emit-config /etc"
+        )]
+        emit_config: bool,
+        #[arg(
+            short = 'f',
+            long,
+            conflicts_with = "emit_config",
+            help = "Specify format output of the listing: Example - snapshot /etc --format json"
+        )]
+        format: Option<String>,
+        #[arg(
+            short = 's',
+            long,
+            help = "Write the output to a file: Example - snapshot --emit-config /etc --store baseline.toml"
+        )]
+        store: Option<PathBuf>,
+    },
+
+    /// Acknowledge a finding as an accepted exception, or list acknowledgments
+    Ack {
+        #[arg(help = "Id of the finding, permission or ownership failure to acknowledge, as shown in check output; omit to list acknowledgments")]
+        id: Option<String>,
+        #[arg(
+            short = 'c',
+            long,
+            help = "Why the finding is accepted: Example - ack 3f2a9c1b7d40 --comment \"approved exception, ticket #123\""
+        )]
+        comment: Option<String>,
+        #[arg(
+            long,
+            action = clap::ArgAction::SetTrue,
+            requires = "id",
+            help = "Remove the acknowledgment for the finding"
+        )]
+        remove: bool,
+        #[arg(
+            long,
+            help = "Directory holding HALO state, overrides $HALO_STATE_DIR: Example - ack --state-dir /var/lib/halo"
+        )]
+        state_dir: Option<PathBuf>,
+        #[arg(
+            value_enum,
+            short = 't',
+            long,
+            help = "Target that reports the finding; defaults to the one that reported it in the newest recorded run, else every target: Example - ack <id> -c <comment> --target sys"
+        )]
+        target: Option<Target>,
+        #[arg(
+            short = 'T',
+            long,
+            visible_alias = "config",
+            help = "Also look up the finding in the rules of this TOML, YAML or JSON config file: Example - ack <id> -c <comment> --config config.toml"
+        )]
+        toml: Option<PathBuf>,
+    },
+
+    /// List targets, profiles, output formats or check ids with descriptions
+    List {
+        #[arg(value_enum, help = "What to list: Example - list checks")]
+        kind: CatalogKind,
+        #[arg(
+            short = 'f',
+            long,
+            help = "Specify format output: Example - list targets --format json"
+        )]
+        format: Option<String>,
+        #[arg(
+            short = 'T',
+            long,
+            visible_alias = "config",
+            help = "Also list the profiles defined in this TOML, YAML or JSON config file: Example - list profiles --config fleet.toml"
+        )]
+        toml: Option<PathBuf>,
     },
 
     /// Network discovery and analysis tools
@@ -137,19 +508,340 @@ pub enum Commands {
             help = "Scan your network for devices: Example - net --devices"
         )]
         devices: bool,
+
+        #[arg(
+            short = 's',
+            long,
+            action = clap::ArgAction::SetTrue,
+            help = "List listening sockets with owning process and user: Example - net --sockets"
+        )]
+        sockets: bool,
+
+        #[arg(
+            short = 'a',
+            long,
+            value_delimiter = ',',
+            requires = "sockets",
+            help = "Expected listening ports; others are flagged and exit with status 1: Example - net --sockets --allow-ports 22,443"
+        )]
+        allow_ports: Vec<u16>,
+    },
+
+    /// Compare two stored JSON reports: new failures, resolved failures and changed modes
+    Diff {
+        #[arg(help = "Earlier report, e.g. from check --format json --store: Example - diff last-week.json today.json")]
+        old: PathBuf,
+        #[arg(help = "Later report to compare with the earlier one")]
+        new: PathBuf,
+        #[arg(
+            short = 'f',
+            long,
+            help = "Specify format output: Example - diff old.json new.json --format markdown"
+        )]
+        format: Option<String>,
+    },
+
+    /// Plan fixes for the failures in a stored JSON report and apply them, or roll them back
+    #[command(alias = "remediate")]
+    #[clap(
+        group(
+            ArgGroup::new("source")
+                .required(true)
+                .args(&["from", "rollback"])
+        ),
+    )]
+    Fix {
+        #[arg(
+            long,
+            help = "Report to fix, e.g. from check --format json --store: Example - fix --from report.json"
+        )]
+        from: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "JOURNAL",
+            help = "Restore the modes and owners recorded in an undo journal of an earlier fix: Example - fix --rollback ~/.local/state/halo/journals/<id>.jsonl"
+        )]
+        rollback: Option<PathBuf>,
+        #[arg(
+            long,
+            conflicts_with = "rollback",
+            help = "Undo journal to write before changing anything (default: a new file under journals/ in the HALO state directory): Example - fix --from report.json --yes --journal undo.jsonl"
+        )]
+        journal: Option<PathBuf>,
+        #[arg(
+            short = 'n',
+            long,
+            action = clap::ArgAction::SetTrue,
+            help = "Only print the plan, change nothing: Example - fix --from report.json --dry-run"
+        )]
+        dry_run: bool,
+        #[arg(
+            long,
+            action = clap::ArgAction::SetTrue,
+            conflicts_with = "dry_run",
+            help = "Print the plan as a shell script instead of applying it, e.g. for review or another host: Example - fix --from report.json --script > fix.sh"
+        )]
+        script: bool,
+        #[arg(
+            short = 'y',
+            long,
+            action = clap::ArgAction::SetTrue,
+            conflicts_with_all = ["dry_run", "script"],
+            help = "Apply the plan without asking; required when stdin is not a terminal: Example - fix --from report.json --yes"
+        )]
+        yes: bool,
+        #[arg(
+            short = 'f',
+            long,
+            help = "Specify format of the printed plan, or 'ansible' for a playbook of file tasks instead of applying it: Example - fix --from report.json --format ansible > halo-fix.yml"
+        )]
+        format: Option<String>,
+        #[arg(
+            long,
+            help = "Ansible host pattern of the playbook (default: the host named in the report): Example - fix --from report.json --format ansible --hosts webservers"
+        )]
+        hosts: Option<String>,
+    },
+
+    /// Re-run audits on a schedule and print only new, resolved or changed failures
+    #[clap(
+        group(
+            ArgGroup::new("audit")
+                .required(true)
+                .multiple(true)
+                .args(&["target", "profile", "toml"])
+        ),
+    )]
+    Watch {
+        #[arg(value_enum, short = 't', long, help = "Target to audit: Example - watch --target all")]
+        target: Option<Target>,
+        #[arg(
+            long,
+            value_name = "PROFILE",
+            help = "Benchmark profile, or a [profiles.<name>] subset of the --config file: Example - watch --profile cis-l1"
+        )]
+        profile: Option<String>,
+        #[arg(
+            short = 'T',
+            long,
+            visible_alias = "config",
+            help = "TOML, YAML or JSON config file with audit rules, reloaded when it changes: Example - watch --config rules.toml"
+        )]
+        toml: Option<PathBuf>,
+        #[arg(
+            short = 'i',
+            long,
+            default_value = "10m",
+            value_parser = parse_interval,
+            help = "Time between the starts of two runs, in s, m, h or d: Example - watch --target all --interval 30s"
+        )]
+        interval: Duration,
+        #[arg(
+            value_enum,
+            long,
+            value_name = "SEVERITY",
+            help = "Only report failures at least this severe: Example - watch --target all --min-severity high"
+        )]
+        min_severity: Option<Severity>,
+        #[arg(long, help = "Stop after this many runs instead of running until interrupted: Example - watch --target all --runs 6")]
+        runs: Option<usize>,
+        #[arg(
+            short = 'f',
+            long,
+            help = "Specify format of the printed changes: Example - watch --target all --format jsonl"
+        )]
+        format: Option<String>,
+    },
+
+    /// Run audits on cron-style schedules in the foreground, recording reports and logging failures to syslog and webhooks
+    Daemon {
+        #[arg(
+            short = 'c',
+            long,
+            value_name = "FILE",
+            help = "TOML daemon config with [[jobs]] and their schedules: Example - daemon --config /etc/halo/daemon.toml"
+        )]
+        config: PathBuf,
+    },
+
+    /// Watch rule paths and re-audit each one as soon as its mode or owner changes
+    #[clap(
+        group(
+            ArgGroup::new("rules")
+                .required(true)
+                .multiple(true)
+                .args(&["target", "toml"])
+        ),
+    )]
+    Monitor {
+        #[arg(value_enum, short = 't', long, help = "Target whose permission rules to monitor: Example - monitor --target user")]
+        target: Option<Target>,
+        #[arg(
+            short = 'T',
+            long,
+            visible_alias = "config",
+            help = "TOML, YAML or JSON config file whose permission and ownership rules to monitor: Example - monitor --config rules.toml"
+        )]
+        toml: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "PROFILE",
+            requires = "toml",
+            help = "Only monitor a [profiles.<name>] subset of the --config file: Example - monitor --config fleet.toml --profile webserver"
+        )]
+        profile: Option<String>,
+        #[arg(
+            value_enum,
+            long,
+            value_name = "SEVERITY",
+            help = "Only report failures at least this severe: Example - monitor --target user --min-severity high"
+        )]
+        min_severity: Option<Severity>,
+        #[arg(
+            short = 'f',
+            long,
+            help = "Specify format of the printed results: Example - monitor --target user --format jsonl"
+        )]
+        format: Option<String>,
     },
 
-    /// Generate a Bash completion script for the CLI
-    Bash {
-        #[arg(short, long, default_value = "halo.bash")]
-        out: String,
+    /// List runs recorded with `check --history`, print one again, or show the results of a path over time
+    History {
+        #[arg(
+            long,
+            global = true,
+            value_name = "DB",
+            help = "History database; defaults to ~/.local/share/halo/history.db ($HALO_HISTORY): Example - history list --db /var/lib/halo/history.db"
+        )]
+        db: Option<PathBuf>,
+        #[arg(
+            short = 'f',
+            long,
+            global = true,
+            help = "Specify format output: Example - history list --format json"
+        )]
+        format: Option<String>,
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+
+    /// Show failure counts, new and resolved failures and the risk score of the runs in the history over time
+    Trend {
+        #[arg(
+            short = 's',
+            long,
+            default_value = "30d",
+            value_parser = parse_interval,
+            help = "Include runs from this long ago, in s, m, h or d: Example - trend --since 7d"
+        )]
+        since: Duration,
+        #[arg(
+            long,
+            value_name = "DB",
+            help = "History database; defaults to ~/.local/share/halo/history.db ($HALO_HISTORY): Example - trend --db /var/lib/halo/history.db"
+        )]
+        db: Option<PathBuf>,
+        #[arg(
+            short = 'f',
+            long,
+            help = "Specify format output, e.g. html for a chart: Example - trend --since 90d --format html > trend.html"
+        )]
+        format: Option<String>,
+    },
+
+    /// Generate or show the key pair that `check --sign` signs stored reports with
+    Keys {
+        #[arg(
+            long,
+            global = true,
+            value_name = "DIR",
+            help = "Key directory; defaults to keys/ in the state directory ($HALO_STATE_DIR): Example - keys generate --dir /etc/halo/keys"
+        )]
+        dir: Option<PathBuf>,
+        #[command(subcommand)]
+        action: KeysAction,
+    },
+
+    /// Verify the signature of a report stored with `check --sign`
+    Verify {
+        #[arg(help = "Signed report, or a .jws file: Example - verify report.json")]
+        file: PathBuf,
+        #[arg(long, value_name = "FILE", help = "Detached signature; defaults to <file>.sig: Example - verify report.json --sig report.sig")]
+        sig: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "PUB",
+            help = "Trusted public key (halo.pub) or key directory; defaults to the local key: Example - verify report.json --key halo.pub"
+        )]
+        key: Option<PathBuf>,
+    },
+
+    /// Audit the hosts of an inventory and print a fleet report ranking the worst offenders
+    Fleet {
+        #[arg(
+            short = 'i',
+            long,
+            help = "TOML, YAML or JSON inventory of hosts (by extension): Example - fleet --inventory hosts.toml"
+        )]
+        inventory: PathBuf,
+        #[arg(long, help = "Only audit hosts with this tag: Example - fleet -i hosts.toml --tag prod")]
+        tag: Option<String>,
+        #[arg(
+            short = 'j',
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(usize),
+            help = "Audit at most N hosts at a time; defaults to 8: Example - fleet -i hosts.toml --jobs 16"
+        )]
+        jobs: Option<usize>,
+        #[arg(
+            short = 'f',
+            long,
+            help = "Specify format output: Example - fleet -i hosts.toml --format html > fleet.html"
+        )]
+        format: Option<String>,
+    },
+
+    /// Audit container images without running them
+    Image {
+        #[command(subcommand)]
+        action: ImageAction,
+    },
+
+    /// Print the JSON schema of reports written by `check --format json`
+    Schema {
+        #[arg(
+            short = 's',
+            long,
+            help = "Write the schema to a file instead of stdout: Example - schema --store report.schema.json"
+        )]
+        store: Option<PathBuf>,
+    },
+
+    /// Generate a shell completion script for the CLI
+    #[command(alias = "bash")]
+    Completions {
+        #[arg(
+            short = 's',
+            long,
+            value_enum,
+            default_value_t = Shell::Bash,
+            help = "Shell to complete in: Example - completions --shell zsh"
+        )]
+        shell: Shell,
+        #[arg(
+            short,
+            long,
+            help = "Write the script to a file instead of stdout: Example - completions --shell fish --out halo.fish"
+        )]
+        out: Option<PathBuf>,
     },
 }
 
 // Core CLI loop - Interactive CLI loop for HALO
 //
 // Presents a `halo>` prompt and parses user commands interactively.
-// Supports `parse`, `check`, `net`, `bash`, `exit`, and `help` commands.
+// Supports `parse`, `check`, `prune`, `snapshot`, `ack`, `list`, `diff`, `fix`, `watch`, `net`, `schema`, `completions`, `exit`, and `help` commands.
 //
 pub fn cli() {
     loop {
@@ -178,7 +870,7 @@ pub fn cli() {
             break;
         }
         if input == "help" {
-            println!("Available commands: parse, check, net, bash, exit, help");
+            println!("Available commands: parse, check, prune, snapshot, ack, list, diff, fix, watch, net, schema, completions, exit, help");
             continue;
         }
 
@@ -188,51 +880,565 @@ pub fn cli() {
             .collect::<Vec<_>>();
 
         match Cli::try_parse_from(args) {
-            Ok(cli) => run_command(&cli.command),
+            Ok(cli) => {
+                if cli.no_color {
+                    alhalo::set_color(false);
+                }
+                crate::logging::set_verbosity(cli.verbose, cli.quiet);
+                sysroot::set_root(cli.root.clone());
+                run_command(&cli.command);
+            }
             Err(e) => eprintln!("{}", e),
         }
     }
 }
 
+/// Queries of `halo history`
+#[derive(Subcommand, Debug)]
+pub enum HistoryAction {
+    /// List recorded runs, newest first
+    List {
+        #[arg(short = 'n', long, help = "Only list the N most recent runs: Example - history list -n 10")]
+        limit: Option<usize>,
+    },
+    /// Print the report of a recorded run
+    Show {
+        #[arg(help = "Run id from `history list`: Example - history show 42")]
+        id: i64,
+    },
+    /// List the results recorded for a path, newest first
+    Path {
+        #[arg(help = "Path as reported: Example - history path /etc/shadow")]
+        path: PathBuf,
+        #[arg(
+            long,
+            action = clap::ArgAction::SetTrue,
+            help = "Only list failures, e.g. when it last failed: Example - history path /etc/shadow --failures -n 1"
+        )]
+        failures: bool,
+        #[arg(short = 'n', long, help = "Only list the N most recent results: Example - history path /etc/shadow -n 5")]
+        limit: Option<usize>,
+    },
+}
+
+/// Actions of `halo keys`
+#[derive(Subcommand, Debug)]
+pub enum KeysAction {
+    /// Generate a new ed25519 key pair
+    Generate {
+        #[arg(long, action = clap::ArgAction::SetTrue, help = "Replace an existing key pair: Example - keys generate --force")]
+        force: bool,
+    },
+    /// Print the id and public key
+    Show,
+}
+
+/// Actions of `halo image`
+#[derive(Subcommand, Debug)]
+pub enum ImageAction {
+    /// Unpack a `docker save` or OCI image and audit its filesystem, setuid programs and ownership
+    Audit {
+        #[arg(help = "Image tarball (docker save, OCI layout or root filesystem export) or OCI layout directory: Example - image audit ./image.tar")]
+        image: PathBuf,
+        #[arg(
+            value_enum,
+            short = 't',
+            long,
+            default_values = ["user", "sys", "net", "log"],
+            help = "Targets to audit inside the image, repeatable: Example - image audit ./image.tar -t sys -t sudoers"
+        )]
+        target: Vec<Target>,
+        #[arg(
+            short = 'f',
+            long,
+            default_value = "pretty",
+            help = "Specify format output: Example - image audit ./image.tar --format json"
+        )]
+        format: Option<String>,
+        #[arg(short = 's', long, help = "Store the JSON report to file: Example - image audit ./image.tar --store image-report.json")]
+        store: Option<PathBuf>,
+        #[arg(
+            value_enum,
+            long,
+            default_value_t = FailOn::Any,
+            help = "Exit with status 1 if a failure is at least this severe, or never: Example - image audit ./image.tar --fail-on high"
+        )]
+        fail_on: FailOn,
+    },
+}
+
+/// How `check --sign` signs the stored report
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SignFormat {
+    /// Detached signature in <file>.sig
+    Detached,
+    /// The report as a compact JWS in <file>.jws
+    Jws,
+}
+
+/// Failures that make `check` exit with status 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FailOn {
+    /// Any open failure
+    Any,
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+    /// Always exit with status 0
+    Never,
+}
+
+impl FailOn {
+    // Least severe failure that counts; `None` for `Any` and `Never`
+    pub(crate) fn threshold(self) -> Option<Severity> {
+        match self {
+            FailOn::Info => Some(Severity::Info),
+            FailOn::Low => Some(Severity::Low),
+            FailOn::Medium => Some(Severity::Medium),
+            FailOn::High => Some(Severity::High),
+            FailOn::Critical => Some(Severity::Critical),
+            FailOn::Any | FailOn::Never => None,
+        }
+    }
+}
+
+// Parses a CSV delimiter: a single ASCII character, or `tab` / `\t`
+fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s {
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        _ if s.len() == 1 && s.is_ascii() && s != "\"" && s != "\n" => Ok(s.as_bytes()[0]),
+        _ => Err(format!("expected a single ASCII character other than a quote or newline, or 'tab', got '{}'", s)),
+    }
+}
+
+// Parses an alternate root, which must be an existing directory
+fn parse_root(s: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(s);
+    if !path.is_dir() {
+        return Err(format!("'{}' is not a directory", s));
+    }
+    Ok(path)
+}
+
+// Parses an HTTP header given as `Name: value`
+fn parse_header(s: &str) -> Result<(String, String), String> {
+    match s.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.trim().to_string())),
+        _ => Err(format!("expected 'Name: value', got '{}'", s)),
+    }
+}
+
+// Prints the error of a command with its code, as JSON on stdout for the JSON formats so
+// parsers of the output get a document, and returns the code's exit status
+fn report_error(error: &HaloError, format: Option<&str>) -> i32 {
+    match format {
+        Some("json" | "jsonl" | "ndjson") => println!("{}", error.to_json()),
+        _ => eprintln!("Error [{}]: {}", error.code(), error),
+    }
+    error.code().exit_status()
+}
+
+// Cancels the returned token on Ctrl-C, so the audit stops and its partial results are
+// printed; a second Ctrl-C terminates as usual
+fn cancel_on_interrupt() -> CancelToken {
+    static INTERRUPTED: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+    extern "C" fn interrupted(_: libc::c_int) {
+        if let Some(flag) = INTERRUPTED.get() {
+            flag.store(true, Ordering::Relaxed);
+        }
+        // SAFETY: signal() is async-signal-safe
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+        }
+    }
+    let flag = INTERRUPTED.get_or_init(Default::default);
+    flag.store(false, Ordering::Relaxed);
+    let handler: extern "C" fn(libc::c_int) = interrupted;
+    // SAFETY: the handler only stores to an atomic and resets the disposition
+    unsafe {
+        libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+    }
+    CancelToken::from(flag.clone())
+}
+
+// Restores the default Ctrl-C behaviour after an audit
+fn restore_interrupt() {
+    // SAFETY: resets the disposition of SIGINT, no handler is involved
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_DFL);
+    }
+}
+
+// A built-in target or a plugin, as accepted by `check --target`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TargetArg {
+    Builtin(Target),
+    Plugin(String),
+}
+
+// Parses `check --target`: built-in targets are listed in the help and completions, plugin
+// names are looked up when parsed
+#[derive(Clone)]
+struct TargetArgParser;
+
+impl TypedValueParser for TargetArgParser {
+    type Value = TargetArg;
+
+    fn parse_ref(&self, cmd: &clap::Command, arg: Option<&clap::Arg>, value: &OsStr) -> Result<TargetArg, clap::Error> {
+        let value = value
+            .to_str()
+            .ok_or_else(|| clap::Error::new(ErrorKind::InvalidUtf8).with_cmd(cmd))?;
+        if let Ok(target) = Target::from_str(value, true) {
+            return Ok(TargetArg::Builtin(target));
+        }
+        if alhalo::plugin::find(value).is_some() {
+            return Ok(TargetArg::Plugin(value.to_string()));
+        }
+        let arg = arg.map(|a| a.to_string()).unwrap_or_else(|| "--target".to_string());
+        let message = format!("invalid value '{}' for '{}': not a target or plugin, see `list targets`\n", value, arg);
+        Err(clap::Error::raw(ErrorKind::InvalidValue, message).with_cmd(cmd))
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(Target::value_variants().iter().filter_map(|t| t.to_possible_value())))
+    }
+}
+
 // Run a CLI command (for direct execution or from the interactive loop)
 //
 // Delegates each subcommand to a specialized handler function:
 // - `Parse`: Calls `handle_parse` to parse and render a file
 // - `Check`: Calls `handle_check` to audit permissions and/or ownership
+// - `Prune`: Calls `handle_prune` to enforce retention on recorded runs
+// - `Snapshot`: Calls `handle_snapshot` to record modes and owners as a listing or config
+// - `Ack`: Calls `handle_ack` to acknowledge findings
+// - `List`: Calls `handle_list` to enumerate targets, profiles, formats and checks
+// - `Diff`: Calls `handle_diff` to compare two stored reports
+// - `Fix`: Calls `handle_fix` to plan and apply fixes for a stored report
+// - `Watch`: Calls `handle_watch` to re-run audits on a schedule and print the changes
+// - `Daemon`: Calls `handle_daemon` to run scheduled audits until stopped
+// - `Monitor`: Calls `handle_monitor` to re-audit rule paths as they change
+// - `History`: Calls `handle_history` to query recorded runs
+// - `Trend`: Calls `handle_trend` to show failure and risk trends
+// - `Keys`: Calls `handle_keys` to manage the report signing key pair
+// - `Verify`: Calls `handle_verify` to check the signature of a stored report
+// - `Fleet`: Calls `handle_fleet` to audit the hosts of an inventory
+// - `Image`: Calls `handle_image` to audit a container image
 // - `Net`: Calls `handle_net` to perform network discovery
-// - `Bash`: Calls `handle_bash` to generate bash completion script
+// - `Schema`: Calls `handle_schema` to print the JSON report schema
+// - `Completions`: Calls `handle_completions` to generate a shell completion script
 //
-// This modular approach keeps CLI logic clean and maintainable.
-pub fn run_command(command: &Commands) {
+// This modular approach keeps CLI logic clean and maintainable. Returns the process exit
+// status for direct execution (clap exits with 2 on usage errors itself; the interactive
+// loop ignores it):
+// - the exit status of the [`ErrorCode`](alhalo::ErrorCode) if `parse`, `check`, `prune`,
+//   `snapshot`, `ack`, `list`, `net`, `diff`, `schema` or `completions` failed
+// - 1 if `check` or `image audit` reported a failure at or above `--fail-on`, or `net
+//   --sockets` found listeners on ports outside `--allow-ports`
+// - 1 if `fix` did not apply its plan, or `watch`, `daemon` or `monitor` could not start
+// - 1 if `history`, `trend`, `keys` or `image` failed, `verify` found no valid signature or
+//   `fleet` could not audit a host
+// - 0 otherwise
+pub fn run_command(command: &Commands) -> i32 {
     match command {
         Commands::Parse {
             format,
             line,
             store,
             file,
+            parser,
+            line_regex,
+            match_on,
         } => {
-            handle_parse(file, format, line, store);
+            if let Err(e) = handle_parse(file, format, line, store, *parser, line_regex, *match_on) {
+                return report_error(&e, format.as_deref());
+            }
         }
         Commands::Check {
             target,
             path,
+            profile,
             format,
             expect,
             importance,
             expect_uid,
             expect_gid,
             store,
+            template,
+            sort,
+            group_by,
+            only_failures,
+            summary_only,
+            delimiter,
+            no_header,
+            toml,
+            record,
+            history,
+            sign,
+            low_footprint,
+            owners,
+            owner,
+            heatmap,
+            io_rate,
+            checkpoint,
+            jobs,
+            one_file_system,
+            max_files,
+            max_results,
+            max_runtime,
+            min_severity,
+            fail_on,
+            post_url,
+            post_header,
+            post_retries,
+        } => {
+            let webhook = post_url.as_ref().map(|url| {
+                post_header
+                    .iter()
+                    .fold(Webhook::new(url.as_str()), |hook, (name, value)| hook.header(name.as_str(), value.as_str()))
+                    .retries(*post_retries)
+            });
+            let history = history.as_ref().map(|db| db.clone().unwrap_or_else(History::default_path));
+            let footprint = if *low_footprint {
+                Footprint::Low
+            } else {
+                Footprint::Standard
+            };
+            let (target, plugin) = match target {
+                Some(TargetArg::Builtin(t)) => (Some(*t), None),
+                Some(TargetArg::Plugin(name)) => (None, Some(name.clone())),
+                None => (None, None),
+            };
+            let request = CheckRequest {
+                target,
+                plugin,
+                path: path.clone(),
+                profile: profile.clone(),
+                toml: toml.clone(),
+                expect: expect.clone(),
+                importance: importance.clone(),
+                expect_uid: *expect_uid,
+                expect_gid: *expect_gid,
+                footprint,
+                scan: ScanOptions {
+                    io_rate: *io_rate,
+                    checkpoint: checkpoint.clone(),
+                    jobs: *jobs,
+                    one_file_system: *one_file_system,
+                    limits: ScanLimits {
+                        max_files: *max_files,
+                        max_results: *max_results,
+                        max_runtime: *max_runtime,
+                    },
+                    cancel: Some(cancel_on_interrupt()),
+                },
+            };
+            let teams = match owners.as_deref().map(TeamMap::from_file).transpose() {
+                Ok(teams) => teams,
+                Err(e) => {
+                    eprintln!("Error loading team map: {}", e);
+                    return 1;
+                }
+            };
+            let template = match template.as_deref().map(ReportTemplate::from_file).transpose() {
+                Ok(template) => template,
+                Err(e) => {
+                    eprintln!("Error loading template: {}", e);
+                    return 1;
+                }
+            };
+            let fail_on_threshold = fail_on.threshold();
+            let view = ReportView {
+                teams: teams.as_ref(),
+                template: template.as_ref(),
+                owner: owner.as_deref(),
+                heatmap: *heatmap,
+                min_severity: min_severity.as_ref(),
+                fail_on: fail_on_threshold.as_ref(),
+                render: RenderOptions {
+                    sort: *sort,
+                    group_by: *group_by,
+                    csv: CsvOptions {
+                        delimiter: delimiter.unwrap_or(b','),
+                        headers: !*no_header,
+                    },
+                },
+                only_failures: *only_failures,
+                summary_only: *summary_only,
+                webhook: webhook.as_ref(),
+                history: history.as_deref(),
+            };
+            // Config files and profiles print the JSON report unless a format is given
+            let format = format.as_deref().or(Some("json").filter(|_| request.is_config()));
+            let stream = match open_stream(format, store.as_ref(), &view) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Failed to open output: {}", e);
+                    return 1;
+                }
+            };
+            let streaming = stream.is_some();
+            if let Some(path) = checkpoint.as_ref().filter(|path| path.exists()) {
+                eprintln!("Resuming from checkpoint {}", path.display());
+            }
+            let result = handle_check(&request, stream);
+            restore_interrupt();
+            let failures = match result {
+                Ok(report) => print_check(&request, report, &view, format, store.as_ref(), *record, streaming),
+                Err(e) => return report_error(&e, format),
+            };
+            if request.scan.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+                eprintln!("Interrupted: the results are partial");
+                return 130;
+            }
+            if let (Some(format), Some(file)) = (sign, store) {
+                match sign_report(file, &StateStore::new(StateStore::default_dir()).keys_dir(), *format) {
+                    Ok(path) => eprintln!("Signed {} as {}", file.display(), path.display()),
+                    Err(e) => {
+                        eprintln!("Error signing {}: {}", file.display(), e);
+                        return 1;
+                    }
+                }
+            }
+            // Gate CI and cron jobs: fail if a reported failure at or above `--fail-on` remains
+            if *fail_on != FailOn::Never && failures > 0 {
+                return 1;
+            }
+        }
+        Commands::Prune {
+            keep_runs,
+            keep_days,
+            state_dir,
+            dry_run,
+        } => {
+            if let Err(e) = handle_prune(*keep_runs, *keep_days, state_dir, *dry_run) {
+                return report_error(&e, None);
+            }
+        }
+        Commands::Snapshot {
+            paths,
+            emit_config,
+            format,
+            store,
+        } => {
+            if let Err(e) = handle_snapshot(paths, *emit_config, format, store) {
+                return report_error(&e, format.as_deref());
+            }
+        }
+        Commands::Ack {
+            id,
+            comment,
+            remove,
+            state_dir,
+            target,
             toml,
         } => {
-            handle_check(
-                target, path, format, expect, importance, expect_uid, expect_gid, store, toml,
-            );
+            if let Err(e) = handle_ack(id, comment, *remove, state_dir, *target, toml) {
+                return report_error(&e, None);
+            }
         }
-        Commands::Net { format, devices } => {
-            handle_net(format, *devices);
+        Commands::List { kind, format, toml } => {
+            if let Err(e) = handle_list(*kind, format, toml) {
+                return report_error(&e, format.as_deref());
+            }
         }
-        Commands::Bash { out } => {
-            handle_bash(out);
+        Commands::Net {
+            format,
+            devices,
+            sockets,
+            allow_ports,
+        } => {
+            match handle_net(format, *devices, *sockets, allow_ports) {
+                Ok(0) => {}
+                // Gate scripts on unexpected listeners, like `check` on failures
+                Ok(_) => return 1,
+                Err(e) => return report_error(&e, format.as_deref()),
+            }
+        }
+        Commands::Diff { old, new, format } => {
+            if let Err(e) = handle_diff(old, new, format) {
+                return report_error(&e, format.as_deref());
+            }
+        }
+        Commands::Fix {
+            from,
+            rollback,
+            journal,
+            dry_run,
+            script,
+            yes,
+            format,
+            hosts,
+        } => {
+            let source = match (from, rollback) {
+                (_, Some(journal)) => FixSource::Rollback(journal),
+                (Some(report), None) => FixSource::Report(report, journal.as_deref()),
+                (None, None) => unreachable!("clap requires --from or --rollback"),
+            };
+            return handle_fix(source, *dry_run, *script, *yes, format, hosts.as_deref());
+        }
+        Commands::Watch {
+            target,
+            profile,
+            toml,
+            interval,
+            min_severity,
+            runs,
+            format,
+        } => {
+            return handle_watch(target, toml, profile, min_severity, *interval, *runs, format);
+        }
+        Commands::Daemon { config } => {
+            return handle_daemon(config);
+        }
+        Commands::Monitor {
+            target,
+            toml,
+            profile,
+            min_severity,
+            format,
+        } => {
+            return handle_monitor(target, toml, profile, min_severity, format);
+        }
+        Commands::History { db, format, action } => {
+            let db = db.clone().unwrap_or_else(History::default_path);
+            return handle_history(&db, action, format);
+        }
+        Commands::Trend { since, db, format } => {
+            let db = db.clone().unwrap_or_else(History::default_path);
+            return handle_trend(&db, *since, format);
+        }
+        Commands::Keys { dir, action } => {
+            let dir = dir.clone().unwrap_or_else(|| StateStore::new(StateStore::default_dir()).keys_dir());
+            return handle_keys(&dir, action);
+        }
+        Commands::Verify { file, sig, key } => {
+            let key = key.clone().unwrap_or_else(|| StateStore::new(StateStore::default_dir()).keys_dir());
+            return handle_verify(file, sig, &key);
+        }
+        Commands::Fleet {
+            inventory,
+            tag,
+            jobs,
+            format,
+        } => {
+            return handle_fleet(inventory, tag, *jobs, format);
+        }
+        Commands::Image { action } => {
+            return handle_image(action);
+        }
+        Commands::Schema { store } => {
+            if let Err(e) = handle_schema(store) {
+                return report_error(&e, None);
+            }
+        }
+        Commands::Completions { shell, out } => {
+            if let Err(e) = handle_completions(*shell, out) {
+                return report_error(&e, None);
+            }
         }
     }
+    0
 }
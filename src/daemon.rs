@@ -0,0 +1,547 @@
+//! Scheduled audits for long-running HALO agents.
+//!
+//! `halo daemon --config /etc/halo/daemon.toml` runs audit jobs on cron-style schedules (see
+//! [`CronSchedule`]) without further input: each run is recorded in the state directory
+//! and pruned to the retention limits, failures are logged to syslog, and the JSON report
+//! can be posted to a webhook. The daemon stays in the foreground, so systemd, OpenRC or a
+//! container runtime supervises it. A changed daemon config is reloaded at the next minute,
+//! keeping the last good one if it fails to load. The rules `config` of each job is watched the
+//! same way: a job whose rules file has a bad edit keeps running the last good rules.
+//!
+//! Syslog gets a summary line per run and one line per failure that is new since the
+//! previous run of the same job, so a failure is not reported again every night.
+//!
+//! # Example daemon config
+//! ```toml
+//! state_dir = "/var/lib/halo" # default: the HALO state directory
+//! syslog = true
+//! keep_runs = 100
+//! keep_days = 90
+//! history = "/var/lib/halo/history.db" # optional SQLite history
+//!
+//! [webhook]
+//! url = "https://soar.example.com/intake/halo"
+//! headers = { Authorization = "Bearer token" }
+//!
+//! [[jobs]]
+//! name = "nightly"
+//! schedule = "30 2 * * *" # UTC
+//! targets = ["all"]
+//!
+//! [[jobs]]
+//! name = "web"
+//! schedule = "*/15 * * * *"
+//! config = "/etc/halo/webserver.toml"
+//! profile = "webserver"
+//! min_severity = "High"
+//! ```
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::daemon::DaemonConfig;
+//! let config = DaemonConfig::parse(r#"
+//!     [[jobs]]
+//!     name = "hourly"
+//!     schedule = "@hourly"
+//!     targets = ["user", "sys"]
+//! "#).unwrap();
+//! assert_eq!(config.next_due(0), Some((3600, vec![0])));
+//! ```
+use crate::audit::builder::{Audit, Target};
+use crate::audit::cis::CisLevel;
+use crate::audit::permissions::audit_permissions::Severity;
+use crate::audit::toml_config::AuditConfig;
+use crate::diff::{ChangeKind, ReportDiff};
+#[cfg(feature = "history")]
+use crate::history::History;
+use crate::reload::{ConfigWatcher, ReloadEvent};
+use crate::report::AuditReport;
+use crate::schedule::CronSchedule;
+use crate::state::{RetentionPolicy, StateStore};
+#[cfg(feature = "webhook")]
+use crate::webhook::Webhook;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+
+/// Settings and jobs of `halo daemon`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DaemonConfig {
+    /// Where runs are recorded; the default HALO state directory if unset
+    #[serde(default)]
+    pub state_dir: Option<PathBuf>,
+    /// Log summaries and new failures to the local syslog (`/dev/log`)
+    #[serde(default = "default_syslog")]
+    pub syslog: bool,
+    /// Recorded runs to keep; see [`RetentionPolicy`]
+    #[serde(default = "default_keep_runs")]
+    pub keep_runs: Option<usize>,
+    /// Days to keep recorded runs
+    #[serde(default = "default_keep_days")]
+    pub keep_days: Option<u64>,
+    /// SQLite [history](crate::history) database that also records every run
+    #[serde(default)]
+    pub history: Option<PathBuf>,
+    /// Endpoint that receives every report
+    #[serde(default)]
+    pub webhook: Option<WebhookConfig>,
+    pub jobs: Vec<Job>,
+}
+
+fn default_syslog() -> bool {
+    true
+}
+
+fn default_keep_runs() -> Option<usize> {
+    RetentionPolicy::default().keep_runs
+}
+
+fn default_keep_days() -> Option<u64> {
+    RetentionPolicy::default().keep_days
+}
+
+/// Webhook settings of the daemon config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub headers: IndexMap<String, String>,
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+/// An audit the daemon runs on a schedule.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Job {
+    /// Name used in logs
+    pub name: String,
+    /// When to run, in UTC
+    pub schedule: CronSchedule,
+    #[serde(default)]
+    pub targets: Vec<Target>,
+    /// Benchmark profile such as `cis-l1`, or a `[profiles.<name>]` of `config`
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Config file with audit rules
+    #[serde(default)]
+    pub config: Option<PathBuf>,
+    /// Only report failures at least this severe
+    #[serde(default)]
+    pub min_severity: Option<Severity>,
+}
+
+impl Job {
+    /// Builds the audit this job runs, reading `config` when the audit runs.
+    pub fn audit(&self) -> Result<Audit, Box<dyn std::error::Error>> {
+        self.build(None)
+    }
+
+    /// Builds the audit this job runs with `rules` already loaded from `config`, e.g. the last
+    /// good config of a [`ConfigWatcher`].
+    pub fn audit_with(&self, rules: &AuditConfig) -> Result<Audit, Box<dyn std::error::Error>> {
+        self.build(Some(rules))
+    }
+
+    fn build(&self, rules: Option<&AuditConfig>) -> Result<Audit, Box<dyn std::error::Error>> {
+        let mut audit = Audit::new();
+        for target in &self.targets {
+            audit = audit.target(*target);
+        }
+        let cis = self.profile.as_deref().and_then(CisLevel::from_name);
+        match (rules, &self.config) {
+            (Some(rules), _) => {
+                audit = audit.config(match self.profile.as_deref().filter(|_| cis.is_none()) {
+                    Some(name) => rules.profile(name)?,
+                    None => rules.clone(),
+                });
+            }
+            (None, Some(path)) => audit = audit.config_file(path),
+            (None, None) => {}
+        }
+        if let Some(name) = &self.profile {
+            match cis {
+                Some(level) => audit = audit.cis(level),
+                None if rules.is_some() => {}
+                None if self.config.is_some() => audit = audit.profile(name),
+                None => return Err(format!("job {}: unknown profile '{}'", self.name, name).into()),
+            }
+        }
+        if let Some(min) = &self.min_severity {
+            audit = audit.min_severity(min.clone());
+        }
+        Ok(audit)
+    }
+}
+
+impl DaemonConfig {
+    /// Parses and validates a daemon config in TOML.
+    pub fn parse(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let config: DaemonConfig =
+            toml::from_str(content).map_err(|e| format!("Failed to parse daemon config: {}", e))?;
+        if config.jobs.is_empty() {
+            return Err("Daemon config defines no [[jobs]]".into());
+        }
+        for job in &config.jobs {
+            if job.targets.is_empty() && job.profile.is_none() && job.config.is_none() {
+                return Err(format!("job {}: set targets, profile or config", job.name).into());
+            }
+            job.audit()?;
+        }
+        Ok(config)
+    }
+
+    /// Reads and validates a daemon config file.
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&content)
+    }
+
+    /// Earliest time after `time` at which a job is due, with the indices of the jobs due
+    /// then; `None` if no job will ever run.
+    pub fn next_due(&self, time: u64) -> Option<(u64, Vec<usize>)> {
+        let due: Vec<(usize, u64)> = self
+            .jobs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, job)| job.schedule.next_after(time).map(|t| (i, t)))
+            .collect();
+        let next = due.iter().map(|(_, t)| *t).min()?;
+        Some((next, due.iter().filter(|(_, t)| *t == next).map(|(i, _)| *i).collect()))
+    }
+
+    fn store(&self) -> StateStore {
+        StateStore::new(self.state_dir.clone().unwrap_or_else(StateStore::default_dir))
+    }
+
+    #[cfg(feature = "webhook")]
+    fn webhook(&self) -> Option<Webhook> {
+        let config = self.webhook.as_ref()?;
+        let hook = config
+            .headers
+            .iter()
+            .fold(Webhook::new(config.url.as_str()), |hook, (name, value)| hook.header(name.as_str(), value.as_str()));
+        Some(hook.retries(config.retries))
+    }
+}
+
+/// Runs the jobs of a daemon config on their schedules.
+pub struct Daemon {
+    config: ConfigWatcher<DaemonConfig>,
+    // Rules config files of the jobs, by path
+    rules: HashMap<PathBuf, ConfigWatcher<AuditConfig>>,
+    // Last report of each job, by name, for reporting only new failures
+    previous: HashMap<String, AuditReport>,
+}
+
+// Syslog priority (facility daemon) of a failure
+fn priority(severity: &Severity) -> u8 {
+    let level = match severity {
+        Severity::Critical => 2,
+        Severity::High => 3,
+        Severity::Medium => 4,
+        Severity::Low => 5,
+        _ => 6,
+    };
+    3 * 8 + level
+}
+
+// Sends one message to the local syslog socket; errors are only logged
+fn syslog(priority: u8, message: &str) {
+    let line = format!("<{}>halo[{}]: {}", priority, std::process::id(), message);
+    let sent = UnixDatagram::unbound().and_then(|socket| socket.send_to(line.as_bytes(), "/dev/log"));
+    if let Err(e) = sent {
+        warn!("Failed to write to syslog: {}", e);
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl Daemon {
+    /// Loads the daemon config and watches it for changes.
+    pub fn from_file(path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let config = ConfigWatcher::new(vec![path], |paths| DaemonConfig::from_file(&paths[0]))?;
+        Ok(Self {
+            config,
+            rules: HashMap::new(),
+            previous: HashMap::new(),
+        })
+    }
+
+    // Last good rules of the config file at `path`, reloaded if it changed. The file is watched
+    // from the first run of a job using it, which fails if it does not load then
+    fn rules(&mut self, path: &Path) -> Result<&AuditConfig, Box<dyn std::error::Error>> {
+        if !self.rules.contains_key(path) {
+            let watcher = ConfigWatcher::audit_config(path.to_path_buf())?;
+            self.rules.insert(path.to_path_buf(), watcher);
+            return Ok(self.rules[path].current());
+        }
+        let watcher = self.rules.get_mut(path).expect("watched above");
+        match watcher.poll() {
+            ReloadEvent::Reloaded => info!("Rules {} reloaded", path.display()),
+            ReloadEvent::Rejected(e) => error!("Keeping last good rules {}: {}", path.display(), e),
+            ReloadEvent::Unchanged => {}
+        }
+        Ok(watcher.current())
+    }
+
+    /// Config in effect.
+    pub fn config(&self) -> &DaemonConfig {
+        self.config.current()
+    }
+
+    /// Runs jobs as they become due, until the process is stopped.
+    pub fn run(&mut self) -> ! {
+        for job in &self.config().jobs {
+            info!("Job {} scheduled: {}", job.name, job.schedule);
+        }
+        let mut last = now();
+        loop {
+            match self.config.poll() {
+                ReloadEvent::Reloaded => {
+                    info!("Daemon config reloaded");
+                    // Stop watching rules files no job uses anymore
+                    let jobs = &self.config.current().jobs;
+                    self.rules.retain(|path, _| jobs.iter().any(|job| job.config.as_deref() == Some(path)));
+                }
+                ReloadEvent::Rejected(e) => error!("Keeping last good daemon config: {}", e),
+                ReloadEvent::Unchanged => {}
+            }
+            let current = now();
+            let due = self.config().next_due(last).filter(|(time, _)| *time <= current);
+            match due {
+                Some((time, jobs)) => {
+                    last = time;
+                    for i in jobs {
+                        let job = self.config().jobs[i].clone();
+                        self.run_job(&job);
+                    }
+                }
+                None => {
+                    last = current;
+                    // Wake at the next due time, but at least every minute to pick up config
+                    // changes
+                    let next = self.config().next_due(current).map(|(t, _)| t).unwrap_or(current + 60);
+                    thread::sleep(Duration::from_secs(next.min(current + 60).saturating_sub(current).max(1)));
+                }
+            }
+        }
+    }
+
+    /// Runs `job` once: records the report, prunes old runs, and reports to syslog and the
+    /// webhook as configured.
+    pub fn run_job(&mut self, job: &Job) -> Option<AuditReport> {
+        info!("Running job {}", job.name);
+        let audit = match &job.config {
+            Some(path) => self.rules(path).and_then(|rules| job.audit_with(rules)),
+            None => job.audit(),
+        };
+        let report = match audit.and_then(|audit| Ok(audit.run()?)) {
+            Ok(report) => report,
+            Err(e) => {
+                error!("Job {} failed: {}", job.name, e);
+                if self.config().syslog {
+                    syslog(3 * 8 + 3, &format!("job {} failed: {}", job.name, e));
+                }
+                return None;
+            }
+        };
+        let config = self.config().clone();
+        let store = config.store();
+        match serde_json::to_string(&report) {
+            Ok(json) => match store.record_run(&json) {
+                Ok(run) => info!("Job {} recorded as run {}", job.name, run.id),
+                Err(e) => error!("Failed to record run of job {}: {}", job.name, e),
+            },
+            Err(e) => error!("Failed to serialize report of job {}: {}", job.name, e),
+        }
+        let policy = RetentionPolicy {
+            keep_runs: config.keep_runs,
+            keep_days: config.keep_days,
+        };
+        match store.prune(&policy) {
+            Ok(removed) if !removed.is_empty() => info!("Pruned {} old runs", removed.len()),
+            Ok(_) => {}
+            Err(e) => warn!("Failed to prune runs: {}", e),
+        }
+        #[cfg(feature = "history")]
+        if let Some(db) = &config.history {
+            match History::open(db).and_then(|mut history| history.record(&report)) {
+                Ok(id) => info!("Job {} recorded in {} as {}", job.name, db.display(), id),
+                Err(e) => error!("Failed to record job {} in history: {}", job.name, e),
+            }
+        }
+        #[cfg(not(feature = "history"))]
+        if config.history.is_some() {
+            warn!("Not recording job {} in history: built without the history feature", job.name);
+        }
+        if config.syslog {
+            let previous = self.previous.get(&job.name).cloned().unwrap_or_default();
+            for line in Self::syslog_lines(job, &previous, &report) {
+                syslog(line.0, &line.1);
+            }
+        }
+        #[cfg(feature = "webhook")]
+        if let Some(webhook) = config.webhook() {
+            match webhook.post(&report) {
+                Ok(status) => info!("Report of job {} posted to {} (HTTP {})", job.name, webhook.url(), status),
+                Err(e) => error!("Failed to post report of job {}: {}", job.name, e),
+            }
+        }
+        #[cfg(not(feature = "webhook"))]
+        if config.webhook.is_some() {
+            warn!("Not posting report of job {}: built without the webhook feature", job.name);
+        }
+        self.previous.insert(job.name.clone(), report.clone());
+        Some(report)
+    }
+
+    // Syslog priority and message of the run summary and each failure new since `previous`
+    fn syslog_lines(job: &Job, previous: &AuditReport, report: &AuditReport) -> Vec<(u8, String)> {
+        let diff = ReportDiff::new(previous, report);
+        let mut lines = vec![(
+            3 * 8 + 6,
+            format!(
+                "job {}: {} failures, {} new, {} resolved",
+                job.name,
+                report.failure_count(),
+                diff.new_failures,
+                diff.resolved
+            ),
+        )];
+        for change in diff.changes.iter().filter(|c| c.change == ChangeKind::NewFailure) {
+            let check = change.check.as_ref().map(|c| format!(" [{}]", c)).unwrap_or_default();
+            let state = change.new.as_ref().map(|s| format!(": {}", s)).unwrap_or_default();
+            lines.push((
+                priority(&change.severity),
+                format!(
+                    "job {}: {:?} {} {}{}{}",
+                    job.name,
+                    change.severity,
+                    change.kind,
+                    change.path.display(),
+                    check,
+                    state
+                ),
+            ));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::finding::Finding;
+
+    #[test]
+    fn test_parse_and_schedule() {
+        let config = DaemonConfig::parse(
+            r#"
+            syslog = false
+            keep_runs = 10
+
+            [webhook]
+            url = "http://127.0.0.1:9/hook"
+            headers = { "X-Token" = "abc" }
+
+            [[jobs]]
+            name = "quarter"
+            schedule = "*/15 * * * *"
+            targets = ["user"]
+
+            [[jobs]]
+            name = "hourly"
+            schedule = "@hourly"
+            profile = "cis-l1"
+            min_severity = "High"
+            "#,
+        )
+        .unwrap();
+        assert!(!config.syslog);
+        assert_eq!((config.keep_runs, config.keep_days), (Some(10), Some(90)));
+        assert_eq!(config.webhook.as_ref().unwrap().retries, 3);
+        assert_eq!(config.next_due(0), Some((900, vec![0])));
+        assert_eq!(config.next_due(3000), Some((3600, vec![0, 1])));
+
+        assert!(DaemonConfig::parse("jobs = []").is_err());
+        let missing = "[[jobs]]\nname = \"x\"\nschedule = \"@daily\"\n";
+        assert!(DaemonConfig::parse(missing).is_err());
+        let unknown = "[[jobs]]\nname = \"x\"\nschedule = \"@daily\"\nprofile = \"nope\"\n";
+        assert!(DaemonConfig::parse(unknown).is_err());
+        let bad_schedule = "[[jobs]]\nname = \"x\"\nschedule = \"daily\"\ntargets = [\"user\"]\n";
+        assert!(DaemonConfig::parse(bad_schedule).is_err());
+    }
+
+    #[test]
+    fn test_run_job_records_and_reports_new_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("daemon.toml");
+        let rules = dir.path().join("rules.toml");
+        let file = dir.path().join("secret");
+        fs::write(&file, "").unwrap();
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+        fs::write(
+            &rules,
+            format!("[[perm_rules]]\npath = \"{}\"\nexpected_mode = 600\nimportance = \"High\"\n", file.display()),
+        )
+        .unwrap();
+        fs::write(
+            &config,
+            format!(
+                "state_dir = \"{}\"\nhistory = \"{}\"\nsyslog = false\n\n[[jobs]]\nname = \"rules\"\nschedule = \"@daily\"\nconfig = \"{}\"\n",
+                dir.path().join("state").display(),
+                dir.path().join("history.db").display(),
+                rules.display()
+            ),
+        )
+        .unwrap();
+        let mut daemon = Daemon::from_file(config).unwrap();
+        let job = daemon.config().jobs[0].clone();
+        let report = daemon.run_job(&job).unwrap();
+        assert_eq!(report.failure_count(), 1);
+        assert_eq!(StateStore::new(dir.path().join("state")).list_runs().unwrap().len(), 1);
+        #[cfg(feature = "history")]
+        {
+            let history = History::open(&dir.path().join("history.db")).unwrap();
+            assert_eq!(history.runs(None).unwrap()[0].failed, 1);
+        }
+
+        let lines = Daemon::syslog_lines(&job, &AuditReport::default(), &report);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].1, "job rules: 1 failures, 1 new, 0 resolved");
+        assert_eq!(lines[1].0, 27);
+        assert!(Daemon::syslog_lines(&job, &report, &report).len() == 1);
+
+        // A bad edit of the rules keeps the last good ones in effect
+        fs::write(&rules, "[[perm_rules]]\npath = 1\n").unwrap();
+        let report = daemon.run_job(&job).unwrap();
+        assert_eq!(report.failure_count(), 1);
+        assert_eq!(report.permissions[0].path, file);
+
+        let mut other = AuditReport::default();
+        other.findings.push(Finding::fail("a", "/etc/sudoers", Severity::Critical, "NOPASSWD: ALL"));
+        let lines = Daemon::syslog_lines(&job, &AuditReport::default(), &other);
+        assert_eq!(lines[1], (26, "job rules: Critical finding /etc/sudoers [a]: NOPASSWD: ALL".to_string()));
+    }
+}
@@ -0,0 +1,328 @@
+//! Differences between two HALO reports.
+//!
+//! `halo diff old.json new.json` answers "what changed since last week": a [`ReportDiff`]
+//! lists the failures that are new in the second report, the ones that were resolved, and
+//! results whose mode or owner changed while they kept failing or passing.
+//!
+//! Results are matched by kind and path; findings by their stable id. A failure that is
+//! missing from the new report counts as resolved, e.g. because the file was removed or
+//! low-footprint mode dropped it after it passed. Acknowledged findings and waived results
+//! are not failures. Like other results, a diff renders in every output format.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::diff::ReportDiff;
+//! use alhalo::{AuditReport, Finding, Renderable, Severity};
+//! let old = AuditReport::default();
+//! let mut new = AuditReport::default();
+//! new.findings.push(Finding::fail("sysctl", "/proc/sys/kernel/kptr_restrict", Severity::Medium, "0"));
+//! let diff = ReportDiff::new(&old, &new);
+//! assert_eq!(diff.new_failures, 1);
+//! diff.render_and_print(Some("pretty"));
+//! ```
+use crate::audit::permissions::audit_permissions::{Severity, Status};
+use crate::render_output::{DataList, DataMap, Renderable, Style, paint};
+use crate::report::{AuditReport, RunMetadata};
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// How a result changed between two reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeKind {
+    /// Failing in the new report, passing or absent in the old one
+    NewFailure,
+    /// Failing in the old report, passing or absent in the new one
+    Resolved,
+    /// Same outcome, but a different mode or owner
+    Changed,
+}
+
+impl ChangeKind {
+    fn name(&self) -> &'static str {
+        match self {
+            ChangeKind::NewFailure => "new-failure",
+            ChangeKind::Resolved => "resolved",
+            ChangeKind::Changed => "changed",
+        }
+    }
+}
+
+/// One result that differs between two reports.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub change: ChangeKind,
+    /// `permission`, `ownership` or `finding`
+    pub kind: &'static str,
+    pub path: PathBuf,
+    /// Check id of a finding, rule id of a permission or ownership result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check: Option<String>,
+    /// Severity of the failure, from the new report unless it was resolved
+    pub severity: Severity,
+    /// Found mode, owner or finding message in the old report
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    /// Found mode, owner or finding message in the new report
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+}
+
+/// Changes from one report to another, most severe first within each kind of change.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportDiff {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_run: Option<RunMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_run: Option<RunMetadata>,
+    pub new_failures: usize,
+    pub resolved: usize,
+    pub changed: usize,
+    pub changes: Vec<Change>,
+}
+
+// A result reduced to what the diff compares; also what the history stores per result
+pub(crate) struct Entry {
+    pub(crate) kind: &'static str,
+    pub(crate) path: PathBuf,
+    pub(crate) check: Option<String>,
+    pub(crate) severity: Severity,
+    pub(crate) failing: bool,
+    pub(crate) state: String,
+}
+
+pub(crate) fn entries(report: &AuditReport) -> IndexMap<String, Entry> {
+    let mut entries = IndexMap::new();
+    for r in &report.permissions {
+        let entry = Entry {
+            kind: "permission",
+            path: r.path.clone(),
+            check: r.rule_id.clone(),
+            severity: r.severity.clone(),
+            failing: r.status == Status::Fail,
+            state: format!("{:o}", r.found_mode),
+        };
+        entries.insert(format!("permission:{}", r.path.display()), entry);
+    }
+    for r in &report.ownership {
+        let id = |v: Option<u32>| v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+        let entry = Entry {
+            kind: "ownership",
+            path: r.path.clone(),
+            check: r.rule_id.clone(),
+            severity: r.severity.clone(),
+            failing: !r.pass,
+            state: format!("{}:{}", id(r.found_uid), id(r.found_gid)),
+        };
+        entries.insert(format!("ownership:{}", r.path.display()), entry);
+    }
+    for f in &report.findings {
+        let entry = Entry {
+            kind: "finding",
+            path: f.path.clone(),
+            check: Some(f.check.clone()),
+            severity: f.severity.clone(),
+            failing: f.is_open_failure(),
+            state: f.message.clone(),
+        };
+        entries.insert(format!("finding:{}", f.id), entry);
+    }
+    entries
+}
+
+impl ReportDiff {
+    /// Compares `old` with `new`.
+    pub fn new(old: &AuditReport, new: &AuditReport) -> Self {
+        let before = entries(old);
+        let after = entries(new);
+        let mut changes = Vec::new();
+        for (key, entry) in &after {
+            let previous = before.get(key);
+            let change = match previous {
+                _ if entry.failing && !previous.is_some_and(|p| p.failing) => ChangeKind::NewFailure,
+                Some(p) if p.failing && !entry.failing => ChangeKind::Resolved,
+                // A finding's message reflects its state only when it fails
+                Some(p) if p.state != entry.state && (entry.kind != "finding" || entry.failing) => {
+                    ChangeKind::Changed
+                }
+                _ => continue,
+            };
+            let severity = match change {
+                ChangeKind::Resolved => previous.map(|p| p.severity.clone()).unwrap_or(Severity::None),
+                _ => entry.severity.clone(),
+            };
+            changes.push(Change {
+                change,
+                kind: entry.kind,
+                path: entry.path.clone(),
+                check: entry.check.clone(),
+                severity,
+                old: previous.map(|p| p.state.clone()),
+                new: Some(entry.state.clone()),
+            });
+        }
+        for (key, entry) in &before {
+            if entry.failing && !after.contains_key(key) {
+                changes.push(Change {
+                    change: ChangeKind::Resolved,
+                    kind: entry.kind,
+                    path: entry.path.clone(),
+                    check: entry.check.clone(),
+                    severity: entry.severity.clone(),
+                    old: Some(entry.state.clone()),
+                    new: None,
+                });
+            }
+        }
+        let order = |c: &ChangeKind| [ChangeKind::NewFailure, ChangeKind::Resolved, ChangeKind::Changed].iter().position(|k| k == c);
+        changes.sort_by(|a, b| {
+            order(&a.change)
+                .cmp(&order(&b.change))
+                .then(b.severity.cmp(&a.severity))
+                .then(a.path.cmp(&b.path))
+        });
+        let count = |kind: ChangeKind| changes.iter().filter(|c| c.change == kind).count();
+        Self {
+            old_run: old.metadata.clone(),
+            new_run: new.metadata.clone(),
+            new_failures: count(ChangeKind::NewFailure),
+            resolved: count(ChangeKind::Resolved),
+            changed: count(ChangeKind::Changed),
+            changes,
+        }
+    }
+
+    /// True if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Renderable for Change {
+    fn to_datalist(&self) -> DataList {
+        let mut map = DataMap::new();
+        map.insert("change".to_string(), self.change.name().to_string());
+        map.insert("kind".to_string(), self.kind.to_string());
+        map.insert("path".to_string(), self.path.display().to_string());
+        map.insert("check".to_string(), self.check.clone().unwrap_or_default());
+        map.insert("severity".to_string(), format!("{:?}", self.severity));
+        map.insert("old".to_string(), self.old.clone().unwrap_or_default());
+        map.insert("new".to_string(), self.new.clone().unwrap_or_default());
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        let check = self.check.as_ref().map(|c| format!(" [{}]", c)).unwrap_or_default();
+        let detail = match (&self.old, &self.new) {
+            (Some(old), Some(new)) if old != new => format!(": {} -> {}", old, new),
+            (_, Some(state)) | (Some(state), None) => format!(": {}", state),
+            _ => String::new(),
+        };
+        format!(
+            "  {} {} {}{}{}\n",
+            paint(&self.severity.column(), self.severity.style()),
+            self.kind,
+            self.path.display(),
+            check,
+            detail
+        )
+    }
+}
+
+impl Renderable for ReportDiff {
+    fn to_datalist(&self) -> DataList {
+        self.changes.iter().flat_map(|c| c.to_datalist()).collect()
+    }
+
+    fn pretty_print(&self) -> String {
+        let mut output = String::new();
+        for (label, run) in [("Old", &self.old_run), ("New", &self.new_run)] {
+            if let Some(run) = run {
+                output.push_str(&format!("{}: {}", label, run.pretty_print()));
+            }
+        }
+        if self.is_empty() {
+            output.push_str("No changes\n");
+            return output;
+        }
+        let sections = [
+            (ChangeKind::NewFailure, "New failures", Style::Red),
+            (ChangeKind::Resolved, "Resolved", Style::Green),
+            (ChangeKind::Changed, "Changed", Style::Yellow),
+        ];
+        for (kind, title, style) in sections {
+            let changes: Vec<&Change> = self.changes.iter().filter(|c| c.change == kind).collect();
+            if changes.is_empty() {
+                continue;
+            }
+            output.push_str(&paint(&format!("{} ({}):", title, changes.len()), style));
+            output.push('\n');
+            changes.iter().for_each(|c| output.push_str(&c.pretty_print()));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Importance;
+    use crate::audit::finding::Finding;
+    use crate::audit::permissions::audit_permissions::PermissionResults;
+
+    fn permission(path: &str, found: u32, status: Status) -> PermissionResults {
+        PermissionResults {
+            severity: if status == Status::Fail { Severity::High } else { Severity::None },
+            status,
+            path: PathBuf::from(path),
+            expected_mode: 0o600,
+            found_mode: found,
+            importance: Importance::High,
+            rule_id: None,
+            acknowledged: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_new_resolved_and_changed() {
+        let old = AuditReport {
+            permissions: vec![
+                permission("/etc/shadow", 0o600, Status::Pass),
+                permission("/etc/gshadow", 0o644, Status::Fail),
+                permission("/etc/ssh/key", 0o644, Status::Fail),
+                permission("/root/.ssh/id", 0o644, Status::Fail),
+            ],
+            findings: vec![Finding::fail("sysctl", "/proc/sys/a", Severity::Low, "a = 0")],
+            ..Default::default()
+        };
+        let new = AuditReport {
+            permissions: vec![
+                permission("/etc/shadow", 0o644, Status::Fail),
+                permission("/etc/gshadow", 0o600, Status::Pass),
+                permission("/etc/ssh/key", 0o666, Status::Fail),
+            ],
+            findings: vec![Finding::fail("sysctl", "/proc/sys/a", Severity::Low, "a = 0")],
+            ..Default::default()
+        };
+        let diff = ReportDiff::new(&old, &new);
+        let summary: Vec<(ChangeKind, &str, Option<&str>, Option<&str>)> = diff
+            .changes
+            .iter()
+            .map(|c| (c.change, c.path.to_str().unwrap(), c.old.as_deref(), c.new.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (ChangeKind::NewFailure, "/etc/shadow", Some("600"), Some("644")),
+                (ChangeKind::Resolved, "/etc/gshadow", Some("644"), Some("600")),
+                (ChangeKind::Resolved, "/root/.ssh/id", Some("644"), None),
+                (ChangeKind::Changed, "/etc/ssh/key", Some("644"), Some("666")),
+            ]
+        );
+        assert_eq!((diff.new_failures, diff.resolved, diff.changed), (1, 2, 1));
+        assert_eq!(diff.to_datalist()[0]["change"], "new-failure");
+        assert!(ReportDiff::new(&new, &new).is_empty());
+    }
+}
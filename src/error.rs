@@ -0,0 +1,234 @@
+//! Error type of the library.
+//!
+//! Running audits, loading and validating configs and discovering network state fail with a
+//! [`HaloError`], so callers can tell a missing file from an unreadable one or a bad rule:
+//!
+//! ```rust
+//! use alhalo::HaloError;
+//! use alhalo::audit::toml_config::AuditConfig;
+//! match AuditConfig::from_file("/nonexistent/rules.toml") {
+//!     Err(HaloError::NotFound(path)) => eprintln!("no config at {}", path.display()),
+//!     Err(e) => eprintln!("{}", e),
+//!     Ok(config) => println!("{} permission rules", config.perm_rules.len()),
+//! }
+//! ```
+//!
+//! `HaloError` is `Send + Sync`, so `?` converts it into a `Box<dyn Error>`. The `error`
+//! fields of audit results are not affected: they are report data and stay messages.
+//!
+//! Wrappers that cannot match on the enum branch on its [`ErrorCode`] instead: a stable string
+//! such as `E_PATH_NOT_FOUND`, printed with `halo check` errors, in the `code` of JSON error
+//! output, and mapped to a distinct exit status.
+//!
+//! ```rust
+//! use alhalo::HaloError;
+//! use alhalo::audit::toml_config::AuditConfig;
+//! let error = AuditConfig::from_file("/nonexistent/rules.toml").unwrap_err();
+//! assert_eq!(error.code().as_str(), "E_PATH_NOT_FOUND");
+//! assert_eq!(error.to_json()["code"], "E_PATH_NOT_FOUND");
+//! ```
+use crate::audit::permissions::audit_permissions::AuditError;
+use serde::{Serialize, Serializer};
+use serde_json::{Value, json};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Errors of audit runs, config loading, rule validation and discovery.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum HaloError {
+    /// A file or directory does not exist
+    #[error("Path not found: {}", .0.display())]
+    NotFound(PathBuf),
+    /// A file or directory cannot be read
+    #[error("Permission denied: {}", .0.display())]
+    PermissionDenied(PathBuf),
+    /// Any other I/O failure on a path
+    #[error("Failed to read '{}': {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// Malformed input, e.g. a config file or command output, described by `what`
+    #[error("Failed to parse {what}: {message}")]
+    Parse { what: String, message: String },
+    /// A mode in a rule that is not octal or symbolic, or above 777
+    #[error("Invalid {field} '{value}' for path '{path}': {source}")]
+    InvalidMode {
+        field: String,
+        value: String,
+        path: String,
+        #[source]
+        source: AuditError,
+    },
+    /// A well-formed config with an invalid rule, waiver, profile or include
+    #[error("{0}")]
+    InvalidConfig(String),
+    /// Options that do not describe a valid audit, e.g. a root that is not a directory
+    #[error("{0}")]
+    InvalidArgument(String),
+    /// Writing results to a stream or checkpoint failed
+    #[error("Failed to write results: {0}")]
+    Output(#[source] io::Error),
+    /// An external command that could not run or failed
+    #[error("Failed to run {command}: {message}")]
+    Command { command: String, message: String },
+}
+
+impl HaloError {
+    /// The error of an I/O operation on `path`: [`NotFound`](Self::NotFound) and
+    /// [`PermissionDenied`](Self::PermissionDenied) by kind, [`Io`](Self::Io) otherwise.
+    pub fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        let path = path.as_ref().to_path_buf();
+        match source.kind() {
+            io::ErrorKind::NotFound => HaloError::NotFound(path),
+            io::ErrorKind::PermissionDenied => HaloError::PermissionDenied(path),
+            _ => HaloError::Io { path, source },
+        }
+    }
+
+    /// A [`Parse`](Self::Parse) error of `what`.
+    pub fn parse(what: impl Into<String>, message: impl ToString) -> Self {
+        HaloError::Parse {
+            what: what.into(),
+            message: message.to_string(),
+        }
+    }
+
+    /// The stable code of the failure.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            HaloError::NotFound(_) => ErrorCode::PathNotFound,
+            HaloError::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            HaloError::Io { .. } => ErrorCode::Io,
+            HaloError::Parse { what, .. } if what.starts_with("TOML") => ErrorCode::TomlParse,
+            HaloError::Parse { what, .. } if what.starts_with("YAML") => ErrorCode::YamlParse,
+            HaloError::Parse { what, .. } if what.starts_with("JSON") => ErrorCode::JsonParse,
+            HaloError::Parse { .. } => ErrorCode::Parse,
+            HaloError::InvalidMode { .. } => ErrorCode::InvalidMode,
+            HaloError::InvalidConfig(_) => ErrorCode::InvalidConfig,
+            HaloError::InvalidArgument(_) => ErrorCode::InvalidArgument,
+            HaloError::Output(_) => ErrorCode::Output,
+            HaloError::Command { .. } => ErrorCode::Command,
+        }
+    }
+
+    /// JSON error output: `{"error": "<message>", "code": "<code>"}`.
+    pub fn to_json(&self) -> Value {
+        json!({ "error": self.to_string(), "code": self.code() })
+    }
+}
+
+/// Machine-readable cause of a [`HaloError`].
+///
+/// The strings and exit statuses are stable: new codes may be added, existing ones are not
+/// renamed. Exit statuses follow `sysexits.h`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// `E_PATH_NOT_FOUND`: a file or directory does not exist (exit status 66)
+    PathNotFound,
+    /// `E_PERM_DENIED`: a file or directory cannot be read (77)
+    PermissionDenied,
+    /// `E_IO`: any other I/O failure (74)
+    Io,
+    /// `E_TOML_PARSE`: a TOML config is malformed (65)
+    TomlParse,
+    /// `E_YAML_PARSE`: a YAML config is malformed (65)
+    YamlParse,
+    /// `E_JSON_PARSE`: a JSON config is malformed (65)
+    JsonParse,
+    /// `E_PARSE`: other malformed input, e.g. command output (65)
+    Parse,
+    /// `E_INVALID_MODE`: a rule's mode is not a valid mode (78)
+    InvalidMode,
+    /// `E_INVALID_CONFIG`: a config holds an invalid rule, waiver, profile or include (78)
+    InvalidConfig,
+    /// `E_INVALID_ARGUMENT`: options that do not describe a valid audit (64)
+    InvalidArgument,
+    /// `E_OUTPUT`: results could not be written (73)
+    Output,
+    /// `E_COMMAND`: an external command could not run or failed (69)
+    Command,
+}
+
+impl ErrorCode {
+    /// The code's string, e.g. `E_PATH_NOT_FOUND`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::PathNotFound => "E_PATH_NOT_FOUND",
+            ErrorCode::PermissionDenied => "E_PERM_DENIED",
+            ErrorCode::Io => "E_IO",
+            ErrorCode::TomlParse => "E_TOML_PARSE",
+            ErrorCode::YamlParse => "E_YAML_PARSE",
+            ErrorCode::JsonParse => "E_JSON_PARSE",
+            ErrorCode::Parse => "E_PARSE",
+            ErrorCode::InvalidMode => "E_INVALID_MODE",
+            ErrorCode::InvalidConfig => "E_INVALID_CONFIG",
+            ErrorCode::InvalidArgument => "E_INVALID_ARGUMENT",
+            ErrorCode::Output => "E_OUTPUT",
+            ErrorCode::Command => "E_COMMAND",
+        }
+    }
+
+    /// Exit status of `halo` when a command fails with this code.
+    pub fn exit_status(&self) -> i32 {
+        match self {
+            ErrorCode::InvalidArgument => 64,
+            ErrorCode::TomlParse | ErrorCode::YamlParse | ErrorCode::JsonParse | ErrorCode::Parse => 65,
+            ErrorCode::PathNotFound => 66,
+            ErrorCode::Command => 69,
+            ErrorCode::Output => 73,
+            ErrorCode::Io => 74,
+            ErrorCode::PermissionDenied => 77,
+            ErrorCode::InvalidMode | ErrorCode::InvalidConfig => 78,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_errors_by_kind() {
+        let missing = HaloError::io("/etc/x", io::Error::from(io::ErrorKind::NotFound));
+        assert!(matches!(&missing, HaloError::NotFound(path) if path == Path::new("/etc/x")));
+        assert_eq!(missing.to_string(), "Path not found: /etc/x");
+        let denied = HaloError::io("/etc/shadow", io::Error::from(io::ErrorKind::PermissionDenied));
+        assert!(matches!(denied, HaloError::PermissionDenied(_)));
+        let other = HaloError::io("/etc", io::Error::other("boom"));
+        assert_eq!(other.to_string(), "Failed to read '/etc': boom");
+        assert!(std::error::Error::source(&other).is_some());
+
+        let boxed: Box<dyn std::error::Error + Send + Sync> = HaloError::parse("TOML config", "bad key").into();
+        assert_eq!(boxed.to_string(), "Failed to parse TOML config: bad key");
+    }
+
+    #[test]
+    fn test_codes_and_exit_statuses() {
+        let toml = HaloError::parse("TOML config", "bad key");
+        assert_eq!(toml.code(), ErrorCode::TomlParse);
+        assert_eq!(toml.code().to_string(), "E_TOML_PARSE");
+        assert_eq!(toml.code().exit_status(), 65);
+        assert_eq!(HaloError::parse("ARP table", "short line").code().as_str(), "E_PARSE");
+        let denied = HaloError::io("/etc/shadow", io::Error::from(io::ErrorKind::PermissionDenied));
+        assert_eq!((denied.code().as_str(), denied.code().exit_status()), ("E_PERM_DENIED", 77));
+        let json = HaloError::InvalidArgument("no target".into()).to_json();
+        assert_eq!(json, serde_json::json!({ "error": "no target", "code": "E_INVALID_ARGUMENT" }));
+    }
+}
@@ -0,0 +1,184 @@
+//! C ABI for embedding HALO audits (feature `ffi`).
+//!
+//! Tools written in other languages can call audits in-process instead of running `halo check`
+//! and parsing its output. Each function takes NUL-terminated UTF-8 strings and returns the
+//! JSON report, as written by `check --format json`, in a string the caller releases with
+//! [`halo_string_free`]. Failures return a JSON object `{"error": "<message>", "code":
+//! "<code>"}` with the [`ErrorCode`](crate::ErrorCode) instead, so the result is never NULL.
+//! Panics are caught and reported with code `E_INTERNAL`.
+//!
+//! Build the shared library with
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib`; `bindings/c/halo.h`
+//! declares the functions. Python tooling uses the `pyhalo` module of the `python` feature,
+//! see [`crate::python`].
+//!
+//! # Example Usage
+//! ```c
+//! #include "halo.h"
+//! char *report = halo_audit_path("/etc/shadow", "640", "high");
+//! puts(report);
+//! halo_string_free(report);
+//! ```
+use crate::audit::builder::{Audit, Target};
+use crate::audit::cis::CisLevel;
+use crate::audit::permissions::audit_permissions::{Importance, PathStatus, PermissionRules, parse_mode};
+use crate::error::HaloError;
+use crate::report::AuditReport;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+
+/// Audits the permissions of `path`, recursively for a directory, against `expected_mode`
+/// (octal such as `"640"` or symbolic such as `"rw-r-----"`) with `importance` `"high"`,
+/// `"medium"` or `"low"`. A path that does not exist or cannot be read is an error with code
+/// `E_PATH_NOT_FOUND` or `E_PERM_DENIED`.
+///
+/// # Safety
+/// Each argument must be NULL or point to a NUL-terminated string valid for the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn halo_audit_path(
+    path: *const c_char,
+    expected_mode: *const c_char,
+    importance: *const c_char,
+) -> *mut c_char {
+    // SAFETY: the caller passes NULL or valid NUL-terminated strings
+    let args = unsafe { (arg(path, "path"), arg(expected_mode, "expected_mode"), arg(importance, "importance")) };
+    respond(|| audit_path(args.0?, args.1?, args.2?))
+}
+
+// Shared with the Python module
+pub(crate) fn audit_path(path: &str, mode: &str, importance: &str) -> Result<AuditReport, HaloError> {
+    let mode = parse_mode(mode).map_err(|e| HaloError::InvalidArgument(format!("Invalid mode '{}': {}", mode, e)))?;
+    let (rule, status) = PermissionRules::new(PathBuf::from(path), mode, parse_importance(importance)?);
+    match status {
+        PathStatus::NotFound => Err(HaloError::NotFound(rule.path)),
+        PathStatus::PermissionDenied => Err(HaloError::PermissionDenied(rule.path)),
+        PathStatus::ValidFile | PathStatus::ValidDirectory => Audit::new().rule(rule).run(),
+    }
+}
+
+/// Runs a built-in target such as `"user"` or `"all"`, or a benchmark profile such as
+/// `"cis-l1"`, as listed by `halo list targets` and `halo list profiles`.
+///
+/// # Safety
+/// `profile` must be NULL or point to a NUL-terminated string valid for the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn halo_audit_profile(profile: *const c_char) -> *mut c_char {
+    // SAFETY: the caller passes NULL or a valid NUL-terminated string
+    let profile = unsafe { arg(profile, "profile") };
+    respond(|| audit_profile(profile?))
+}
+
+// Shared with the Python module
+pub(crate) fn audit_profile(name: &str) -> Result<AuditReport, HaloError> {
+    let audit = match (Target::from_name(name), CisLevel::from_name(name)) {
+        (Some(target), _) => Audit::new().target(target),
+        (None, Some(level)) => Audit::new().cis(level),
+        (None, None) => {
+            return Err(HaloError::InvalidArgument(format!(
+                "Unknown target or profile '{}'; see `halo list targets` and `halo list profiles`",
+                name
+            )));
+        }
+    };
+    audit.run()
+}
+
+/// Releases a string returned by a `halo_` function; NULL is ignored.
+///
+/// # Safety
+/// `s` must be NULL or a string returned by this library, released only once.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn halo_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: `s` came from `CString::into_raw` in `respond`
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+// Borrows a C string argument as UTF-8
+unsafe fn arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, HaloError> {
+    if ptr.is_null() {
+        return Err(HaloError::InvalidArgument(format!("{} is NULL", name)));
+    }
+    // SAFETY: non-NULL and NUL-terminated per the caller's contract
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|_| HaloError::InvalidArgument(format!("{} is not UTF-8", name)))
+}
+
+fn parse_importance(name: &str) -> Result<Importance, HaloError> {
+    match name.to_ascii_lowercase().as_str() {
+        "high" => Ok(Importance::High),
+        "medium" => Ok(Importance::Medium),
+        "low" => Ok(Importance::Low),
+        _ => Err(HaloError::InvalidArgument(format!(
+            "Invalid importance '{}': use high, medium or low",
+            name
+        ))),
+    }
+}
+
+// Runs `audit` and returns its report, or the error, as an owned JSON string
+fn respond(audit: impl FnOnce() -> Result<AuditReport, HaloError>) -> *mut c_char {
+    let json = match panic::catch_unwind(AssertUnwindSafe(audit)) {
+        Ok(Ok(report)) => serde_json::to_string(&report).unwrap_or_else(|e| internal_error(&e.to_string())),
+        Ok(Err(e)) => e.to_json().to_string(),
+        Err(_) => internal_error("HALO panicked during the audit"),
+    };
+    // JSON escapes control characters, so the string holds no NUL
+    CString::new(json).unwrap_or_default().into_raw()
+}
+
+fn internal_error(message: &str) -> String {
+    serde_json::json!({ "error": message, "code": "E_INTERNAL" }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    // Calls `f` and takes ownership of the returned JSON
+    fn call(f: impl FnOnce() -> *mut c_char) -> Value {
+        let ptr = f();
+        let json = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        unsafe { halo_string_free(ptr) };
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_audit_path_returns_report_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("secret");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+        let path = CString::new(file.to_str().unwrap()).unwrap();
+        let (mode, importance) = (CString::new("600").unwrap(), CString::new("High").unwrap());
+
+        let report = call(|| unsafe { halo_audit_path(path.as_ptr(), mode.as_ptr(), importance.as_ptr()) });
+        assert_eq!(report["summary"]["failed"], 1);
+        assert_eq!(report["permissions"][0]["expected_mode"], "600");
+
+        let bad = CString::new("rwz").unwrap();
+        let error = call(|| unsafe { halo_audit_path(path.as_ptr(), bad.as_ptr(), importance.as_ptr()) });
+        assert!(error["error"].as_str().unwrap().starts_with("Invalid mode 'rwz'"));
+        assert_eq!(error["code"], "E_INVALID_ARGUMENT");
+        let error = call(|| unsafe { halo_audit_path(std::ptr::null(), mode.as_ptr(), importance.as_ptr()) });
+        assert_eq!(error["error"], "path is NULL");
+
+        let missing = CString::new(dir.path().join("missing").to_str().unwrap()).unwrap();
+        let error = call(|| unsafe { halo_audit_path(missing.as_ptr(), mode.as_ptr(), importance.as_ptr()) });
+        assert_eq!(error["code"], "E_PATH_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_audit_profile_rejects_unknown_names() {
+        let name = CString::new("nope").unwrap();
+        let error = call(|| unsafe { halo_audit_profile(name.as_ptr()) });
+        assert!(error["error"].as_str().unwrap().starts_with("Unknown target or profile 'nope'"));
+        unsafe { halo_string_free(std::ptr::null_mut()) };
+    }
+}
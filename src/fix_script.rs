@@ -1,17 +1,55 @@
-use alhalo::PermissionResults;
+use alhalo::audit::passwd::{parse_group_names, parse_passwd};
+use alhalo::remediate::{FixAction, FixPlan, shell_quote};
+use std::collections::HashMap;
+use std::fs;
 
-pub fn generate_fix_script(results: &[PermissionResults]) -> String {
-    let mut script = String::from("#!/bin/bash\n# HALO Permission Fix Script\n\n");
-    for res in results {
-        if res.status == alhalo::Status::Fail {
-            script.push_str(&format!(
-                "chmod {:o} {}\n",
-                res.expected_mode,
-                res.path.display()
-            ));
+// Owner and group names from /etc/passwd and /etc/group, numeric ids where there is no name
+struct Names {
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+impl Names {
+    fn load() -> Self {
+        Self {
+            users: parse_passwd(&fs::read_to_string("/etc/passwd").unwrap_or_default())
+                .into_iter()
+                .map(|e| (e.uid, e.name))
+                .collect(),
+            groups: parse_group_names(&fs::read_to_string("/etc/group").unwrap_or_default()),
         }
     }
+
+    fn user(&self, uid: u32) -> String {
+        self.users.get(&uid).cloned().unwrap_or_else(|| uid.to_string())
+    }
+
+    fn group(&self, gid: u32) -> String {
+        self.groups.get(&gid).cloned().unwrap_or_else(|| gid.to_string())
+    }
+}
+
+// Writes the applicable fixes of `plan` as a bash script: `chmod` for permission failures,
+// `chown user:group` for ownership failures. Owners are named from /etc/passwd and
+// /etc/group where possible, numeric otherwise; paths are quoted for the shell
+pub fn generate_fix_script(plan: &FixPlan) -> String {
+    let names = Names::load();
+    let mut script = String::from("#!/bin/bash\n# HALO Permission Fix Script\n\n");
+    for fix in plan.applicable() {
+        let command = match &fix.action {
+            FixAction::Chmod { to, .. } => format!("chmod {:o}", to),
+            FixAction::Chown { uid: Some(uid), gid: Some(gid), .. } => {
+                format!("chown {}:{}", names.user(*uid), names.group(*gid))
+            }
+            FixAction::Chown { uid: Some(uid), gid: None, .. } => format!("chown {}", names.user(*uid)),
+            FixAction::Chown { uid: None, gid: Some(gid), .. } => format!("chgrp {}", names.group(*gid)),
+            FixAction::Chown { uid: None, gid: None, .. } => continue,
+        };
+        // Keep paths starting with a dash from being read as options
+        let end = if fix.path.as_os_str().as_encoded_bytes().starts_with(b"-") { " --" } else { "" };
+        script.push_str(&format!("{}{} {}\n", command, end, shell_quote(&fix.path)));
+    }
     script
 }
 
-// Future: Add support for ownership fixes, symlink handling, etc.
+// Future: Add support for symlink handling, etc.
@@ -0,0 +1,577 @@
+//! Multi-host inventories and fleet reports.
+//!
+//! An [`Inventory`] lists the hosts of a fleet in TOML, YAML or JSON (by extension), each
+//! with tags and the targets, profile or config to audit it with. `halo fleet --inventory
+//! hosts.toml` collects one report per host and consolidates them into a [`FleetReport`]:
+//! fleet-wide counts, the hosts ranked worst first by risk score, and each host's failures.
+//!
+//! A host's report comes from, in order of preference:
+//! - `report`: a JSON report already collected from the host, e.g. written by an agent
+//!   running `halo daemon` or `halo check --store` and copied back;
+//! - `address`: an SSH destination; `ssh -o BatchMode=yes <address> halo check --format json`
+//!   runs the audit there (`command` overrides `halo`);
+//! - neither: the audit runs on this machine.
+//!
+//! ```toml
+//! [[hosts]]
+//! name = "web-1"
+//! address = "ops@web-1.example.com"
+//! tags = ["web", "prod"]
+//! profile = "cis-l1"
+//!
+//! [[hosts]]
+//! name = "db-1"
+//! tags = ["db", "prod"]
+//! report = "collected/db-1.json"
+//! ```
+//!
+//! Relative `report` paths, and the `config` of hosts audited on this machine, are resolved
+//! against the directory of the inventory file, like config includes. The `config` of a host
+//! with an `address` is a path on that host and is passed on as is.
+//!
+//! Hosts are audited in parallel, at most [`MAX_PARALLEL_HOSTS`] at a time unless
+//! [`Inventory::collect_with`] says otherwise. A host that cannot be audited is listed with
+//! its error and counted as unreachable instead of failing the whole report.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::fleet::Inventory;
+//! let inventory = Inventory::parse(
+//!     "[[hosts]]\nname = \"web-1\"\naddress = \"web-1\"\ntags = [\"web\"]\n",
+//!     alhalo::ConfigFormat::Toml,
+//! ).unwrap();
+//! assert_eq!(inventory.tagged(Some("web")).len(), 1);
+//! assert!(inventory.tagged(Some("db")).is_empty());
+//! ```
+use crate::audit::cis::CisLevel;
+use crate::audit::toml_config::ConfigFormat;
+use crate::remediate::shell_quote;
+use crate::render_output::{DataList, DataMap, Renderable, Style, html_escape, html_page, html_table, paint};
+use crate::report::{AuditReport, ReportSummary};
+use crate::{Audit, Target};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Hosts [`Inventory::collect`] audits at the same time.
+pub const MAX_PARALLEL_HOSTS: usize = 8;
+
+/// A host of an inventory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Host {
+    /// Name shown in the fleet report
+    pub name: String,
+    /// SSH destination, e.g. `ops@web-1.example.com`
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub targets: Vec<Target>,
+    /// Benchmark profile such as `cis-l1`, or a `[profiles.<name>]` of `config`
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Config file with audit rules, on the host that runs the audit; relative to the
+    /// inventory file for hosts audited on this machine
+    #[serde(default)]
+    pub config: Option<PathBuf>,
+    /// JSON report already collected from the host, relative to the inventory file
+    #[serde(default)]
+    pub report: Option<PathBuf>,
+    /// Command that runs HALO on the remote host; defaults to `halo`
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+impl Host {
+    // Arguments of `check` selecting this host's targets, profile and config
+    fn check_args(&self) -> Vec<String> {
+        let mut args = vec!["check".to_string(), "--format".to_string(), "json".to_string()];
+        for target in &self.targets {
+            args.extend(["--target".to_string(), target.name().to_string()]);
+        }
+        if let Some(config) = &self.config {
+            args.extend(["--config".to_string(), config.display().to_string()]);
+        }
+        if let Some(profile) = &self.profile {
+            args.extend(["--profile".to_string(), profile.clone()]);
+        }
+        args
+    }
+
+    // Arguments of `ssh` running the audit at `address`. The remote shell joins them into one
+    // command line, so the `check` arguments are quoted; `command` is a command line itself,
+    // e.g. `sudo halo`
+    pub(crate) fn ssh_args(&self, address: &str) -> Vec<String> {
+        let mut args: Vec<String> = ["-o", "BatchMode=yes", address, "--"].map(String::from).into();
+        args.push(self.command.clone().unwrap_or_else(|| "halo".to_string()));
+        args.extend(self.check_args().iter().map(|arg| shell_quote(Path::new(arg))));
+        args
+    }
+
+    // Report printed by a remote `check`; it exits with 1 when it reports failures, so only
+    // the output tells. Text after the report, as printed by older versions, is ignored
+    pub(crate) fn read_ssh_output(output: &Output) -> Result<AuditReport, Box<dyn std::error::Error>> {
+        let mut reports = serde_json::Deserializer::from_slice(&output.stdout).into_iter::<AuditReport>();
+        match reports.next() {
+            Some(Ok(report)) => Ok(report),
+            _ => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let reason = stderr.lines().last().unwrap_or("no JSON report in the output");
+                Err(format!("{} ({})", reason.trim(), output.status).into())
+            }
+        }
+    }
+
+    // Audit run on this machine
+    fn local_audit(&self) -> Result<Audit, Box<dyn std::error::Error>> {
+        let mut audit = Audit::new();
+        for target in &self.targets {
+            audit = audit.target(*target);
+        }
+        if let Some(path) = &self.config {
+            audit = audit.config_file(path);
+        }
+        match (CisLevel::resolve(self.profile.as_deref(), self.config.is_some())?, &self.profile) {
+            (Some(level), _) => audit = audit.cis(level),
+            (None, Some(name)) => audit = audit.profile(name),
+            (None, None) => {}
+        }
+        Ok(audit)
+    }
+
+    /// Reads, fetches over SSH or runs the report of this host, see the [module
+    /// docs](self).
+    pub fn collect(&self) -> Result<AuditReport, Box<dyn std::error::Error>> {
+        if let Some(path) = &self.report {
+            return Ok(AuditReport::from_file(path)?);
+        }
+        let Some(address) = &self.address else {
+            return Ok(self.local_audit()?.run()?);
+        };
+        let output = Command::new("ssh")
+            .args(self.ssh_args(address))
+            .output()
+            .map_err(|e| format!("Failed to run ssh: {}", e))?;
+        Self::read_ssh_output(&output)
+    }
+}
+
+/// Hosts of a fleet.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    #[serde(default)]
+    pub hosts: Vec<Host>,
+}
+
+impl Inventory {
+    /// Parses and validates an inventory in `format`.
+    ///
+    /// # Returns
+    /// * `Err` if it fails to parse, lists no hosts, or names a host twice or not at all.
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self, Box<dyn std::error::Error>> {
+        let parsed = match format {
+            ConfigFormat::Toml => toml::from_str::<Inventory>(content).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml_ng::from_str::<Inventory>(content).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::from_str::<Inventory>(content).map_err(|e| e.to_string()),
+        };
+        let inventory = parsed.map_err(|e| format!("Failed to parse {} inventory: {}", format.name(), e))?;
+        if inventory.hosts.is_empty() {
+            return Err("Inventory lists no [[hosts]]".into());
+        }
+        for (i, host) in inventory.hosts.iter().enumerate() {
+            if host.name.trim().is_empty() {
+                return Err(format!("Inventory host {} has no name", i + 1).into());
+            }
+            if inventory.hosts[..i].iter().any(|h| h.name == host.name) {
+                return Err(format!("Inventory lists host '{}' twice", host.name).into());
+            }
+        }
+        Ok(inventory)
+    }
+
+    /// Reads an inventory file; the format is chosen by extension.
+    ///
+    /// Relative paths of the hosts are resolved against the file's directory, see the
+    /// [module docs](self).
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let mut inventory = Self::parse(&content, ConfigFormat::from_path(path))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for host in &mut inventory.hosts {
+            if let Some(report) = host.report.as_mut().filter(|p| p.is_relative()) {
+                *report = dir.join(&*report);
+            }
+            if host.address.is_none()
+                && let Some(config) = host.config.as_mut().filter(|p| p.is_relative())
+            {
+                *config = dir.join(&*config);
+            }
+        }
+        Ok(inventory)
+    }
+
+    /// Hosts carrying `tag`, or all hosts for `None`.
+    pub fn tagged(&self, tag: Option<&str>) -> Vec<&Host> {
+        self.hosts
+            .iter()
+            .filter(|host| tag.is_none_or(|tag| host.tags.iter().any(|t| t == tag)))
+            .collect()
+    }
+
+    /// Collects the reports of `hosts`, [`MAX_PARALLEL_HOSTS`] at a time.
+    pub fn collect(hosts: &[&Host]) -> FleetReport {
+        Self::collect_with(hosts, MAX_PARALLEL_HOSTS)
+    }
+
+    /// Collects the reports of `hosts`, at most `parallel` (at least one) at a time.
+    pub fn collect_with(hosts: &[&Host], parallel: usize) -> FleetReport {
+        let results: Vec<Mutex<Option<Result<AuditReport, String>>>> = hosts.iter().map(|_| Mutex::new(None)).collect();
+        let next = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..parallel.clamp(1, hosts.len().max(1)) {
+                scope.spawn(|| {
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(host) = hosts.get(i) else { break };
+                        let result = host.collect().map_err(|e| e.to_string());
+                        *results[i].lock().unwrap_or_else(|e| e.into_inner()) = Some(result);
+                    }
+                });
+            }
+        });
+        FleetReport::new(
+            hosts
+                .iter()
+                .zip(results)
+                .map(|(host, result)| {
+                    let result = result.into_inner().unwrap_or_else(|e| e.into_inner());
+                    (*host, result.unwrap_or_else(|| Err("audit panicked".to_string())))
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Report of one host in a fleet report.
+#[derive(Debug, Clone, Serialize)]
+pub struct HostReport {
+    pub name: String,
+    pub tags: Vec<String>,
+    /// Why the host could not be audited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub summary: ReportSummary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<AuditReport>,
+}
+
+/// Counts across all hosts of a fleet report.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FleetSummary {
+    pub hosts: usize,
+    /// Hosts that could not be audited
+    pub unreachable: usize,
+    /// Hosts with at least one open failure
+    pub failing_hosts: usize,
+    pub checked: usize,
+    pub failed: usize,
+    pub critical: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    pub info: usize,
+    /// Sum of the hosts' risk scores
+    pub risk_score: u32,
+}
+
+/// Reports of the hosts of a fleet, worst offender first.
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetReport {
+    pub summary: FleetSummary,
+    pub hosts: Vec<HostReport>,
+}
+
+impl FleetReport {
+    /// Consolidates the report, or the error, of each host. Hosts are ranked by risk score,
+    /// then open failures, then name; unreachable hosts come last.
+    pub fn new(results: Vec<(&Host, Result<AuditReport, String>)>) -> Self {
+        let mut hosts: Vec<HostReport> = results
+            .into_iter()
+            .map(|(host, result)| {
+                let (report, error) = match result {
+                    Ok(report) => (Some(report), None),
+                    Err(e) => (None, Some(e)),
+                };
+                HostReport {
+                    name: host.name.clone(),
+                    tags: host.tags.clone(),
+                    error,
+                    summary: report.as_ref().map(AuditReport::summary).unwrap_or_default(),
+                    report,
+                }
+            })
+            .collect();
+        hosts.sort_by(|a, b| {
+            a.error
+                .is_some()
+                .cmp(&b.error.is_some())
+                .then(b.summary.risk_score.cmp(&a.summary.risk_score))
+                .then(b.summary.failed.cmp(&a.summary.failed))
+                .then(a.name.cmp(&b.name))
+        });
+        let mut summary = FleetSummary {
+            hosts: hosts.len(),
+            ..Default::default()
+        };
+        for host in &hosts {
+            let s = &host.summary;
+            summary.unreachable += usize::from(host.error.is_some());
+            summary.failing_hosts += usize::from(s.failed > 0);
+            summary.checked += s.checked;
+            summary.failed += s.failed;
+            summary.critical += s.critical;
+            summary.high += s.high;
+            summary.medium += s.medium;
+            summary.low += s.low;
+            summary.info += s.info;
+            summary.risk_score += s.risk_score;
+        }
+        Self { summary, hosts }
+    }
+
+    /// Whether any host could not be audited.
+    pub fn has_unreachable(&self) -> bool {
+        self.summary.unreachable > 0
+    }
+
+    // Failures of a host, for the per-host sections
+    fn failures(host: &HostReport) -> Option<AuditReport> {
+        let mut report = host.report.clone()?;
+        report.retain_failures();
+        (!report.is_empty()).then_some(report)
+    }
+}
+
+impl Renderable for FleetReport {
+    // One row per host, in ranking order
+    fn to_datalist(&self) -> DataList {
+        self.hosts
+            .iter()
+            .enumerate()
+            .map(|(i, host)| {
+                let s = &host.summary;
+                let mut map = DataMap::new();
+                map.insert("rank".to_string(), (i + 1).to_string());
+                map.insert("host".to_string(), host.name.clone());
+                map.insert("tags".to_string(), host.tags.join(" "));
+                map.insert("checked".to_string(), s.checked.to_string());
+                map.insert("failed".to_string(), s.failed.to_string());
+                map.insert("critical".to_string(), s.critical.to_string());
+                map.insert("high".to_string(), s.high.to_string());
+                map.insert("medium".to_string(), s.medium.to_string());
+                map.insert("low".to_string(), s.low.to_string());
+                map.insert("risk_score".to_string(), s.risk_score.to_string());
+                map.insert("error".to_string(), host.error.clone().unwrap_or_default());
+                map
+            })
+            .collect()
+    }
+
+    fn pretty_print(&self) -> String {
+        let s = &self.summary;
+        let mut output = format!(
+            "Fleet: {} hosts, {} failing, {} unreachable; {} checked, {} failed ({} critical, {} high, {} medium, {} low), risk score {}\n\nWorst offenders:\n",
+            s.hosts,
+            s.failing_hosts,
+            s.unreachable,
+            s.checked,
+            paint(&s.failed.to_string(), Style::Red),
+            s.critical,
+            s.high,
+            s.medium,
+            s.low,
+            s.risk_score
+        );
+        for (i, host) in self.hosts.iter().enumerate() {
+            let line = match &host.error {
+                Some(e) => paint(&format!("unreachable: {}", e), Style::Dim),
+                None => format!("risk {:>5}  {:>4} failed", host.summary.risk_score, host.summary.failed),
+            };
+            output.push_str(&format!("{:>3}. {:<24} {}\n", i + 1, host.name, line));
+        }
+        for host in &self.hosts {
+            if let Some(failures) = Self::failures(host) {
+                output.push_str(&format!("\n== {} ==\n{}", host.name, failures.pretty_print()));
+            }
+        }
+        output
+    }
+
+    fn html(&self) -> String {
+        let s = &self.summary;
+        let mut body = format!(
+            "<p>{} hosts, {} failing, {} unreachable; {} failed ({} critical, {} high), risk score {}</p>\n<h2>Worst offenders</h2>\n{}",
+            s.hosts,
+            s.failing_hosts,
+            s.unreachable,
+            s.failed,
+            s.critical,
+            s.high,
+            s.risk_score,
+            html_table(&self.to_datalist(), &[])
+        );
+        for host in &self.hosts {
+            if let Some(failures) = Self::failures(host) {
+                body.push_str(&format!(
+                    "<h2>{}</h2>\n{}",
+                    html_escape(&host.name),
+                    html_table(&failures.to_datalist(), &[])
+                ));
+            }
+        }
+        html_page("HALO fleet report", &body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Severity;
+    use crate::audit::finding::Finding;
+
+    fn host(name: &str, tags: &[&str]) -> Host {
+        Host {
+            name: name.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn report(failures: &[(&str, Severity)]) -> AuditReport {
+        AuditReport {
+            findings: failures
+                .iter()
+                .map(|(path, severity)| Finding::fail("a", path, severity.clone(), "x"))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_inventory() {
+        let yaml = "hosts:\n  - name: web-1\n    address: ops@web-1\n    tags: [web]\n    targets: [user]\n    profile: cis-l1\n  - name: db-1\n    report: db-1.json\n";
+        let inventory = Inventory::parse(yaml, ConfigFormat::Yaml).unwrap();
+        assert_eq!(inventory.hosts.len(), 2);
+        assert_eq!(inventory.tagged(None).len(), 2);
+        assert_eq!(inventory.tagged(Some("web"))[0].name, "web-1");
+        assert_eq!(
+            inventory.hosts[0].check_args(),
+            ["check", "--format", "json", "--target", "user", "--profile", "cis-l1"]
+        );
+
+        assert!(Inventory::parse("", ConfigFormat::Toml).is_err());
+        let twice = "[[hosts]]\nname = \"a\"\n[[hosts]]\nname = \"a\"\n";
+        assert!(Inventory::parse(twice, ConfigFormat::Toml).unwrap_err().to_string().contains("twice"));
+    }
+
+    #[test]
+    fn test_ssh_command_line() {
+        use std::os::unix::process::ExitStatusExt;
+        let web = Host {
+            config: Some(PathBuf::from("/srv/my rules.toml")),
+            profile: Some("it's; rm -rf /".to_string()),
+            command: Some("sudo halo".to_string()),
+            ..host("web", &[])
+        };
+        assert_eq!(
+            web.ssh_args("ops@web")[4..],
+            [
+                "sudo halo",
+                "check",
+                "--format",
+                "json",
+                "--config",
+                "'/srv/my rules.toml'",
+                "--profile",
+                r"'it'\''s; rm -rf /'"
+            ]
+        );
+
+        let json = serde_json::to_string_pretty(&report(&[("/etc/a", Severity::High)])).unwrap();
+        let output = |stdout: String, stderr: &str| Output {
+            status: std::process::ExitStatus::from_raw(1 << 8),
+            stdout: stdout.into_bytes(),
+            stderr: stderr.as_bytes().to_vec(),
+        };
+        let trailing = format!("{}\n\nPolicy findings: 1 checked, 1 failed, 0 acknowledged\n[!] High: /etc/a\n", json);
+        assert_eq!(Host::read_ssh_output(&output(trailing, "")).unwrap().failure_count(), 1);
+        let error = Host::read_ssh_output(&output(String::new(), "halo: command not found\n")).unwrap_err();
+        assert!(error.to_string().starts_with("halo: command not found"));
+    }
+
+    #[test]
+    fn test_example_inventory() {
+        let inventory = Inventory::from_file(Path::new("examples/toml_configs/inventory.toml")).unwrap();
+        assert_eq!(inventory.tagged(Some("prod")).len(), 3);
+        assert_eq!(inventory.hosts[1].check_args()[3..], ["--target", "sys", "--target", "net"]);
+    }
+
+    #[test]
+    fn test_fleet_report_ranking() {
+        let (web, db, edge, lab) = (host("web", &["prod"]), host("db", &[]), host("edge", &[]), host("lab", &[]));
+        let fleet = FleetReport::new(vec![
+            (&web, Ok(report(&[("/etc/a", Severity::Low)]))),
+            (&lab, Err("connection refused".to_string())),
+            (&db, Ok(report(&[("/etc/a", Severity::Critical), ("/etc/b", Severity::High)]))),
+            (&edge, Ok(report(&[]))),
+        ]);
+        let names: Vec<&str> = fleet.hosts.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, ["db", "web", "edge", "lab"]);
+        assert_eq!(fleet.summary.hosts, 4);
+        assert_eq!((fleet.summary.failing_hosts, fleet.summary.unreachable), (2, 1));
+        assert_eq!((fleet.summary.failed, fleet.summary.critical), (3, 1));
+        assert!(fleet.has_unreachable());
+
+        let rows = fleet.to_datalist();
+        assert_eq!((rows[0]["rank"].as_str(), rows[0]["host"].as_str()), ("1", "db"));
+        assert_eq!(rows[3]["error"], "connection refused");
+        assert!(fleet.pretty_print().contains("== db =="));
+        let json = serde_json::to_value(&fleet).unwrap();
+        assert_eq!(json["hosts"][0]["report"]["summary"]["failed"], 2);
+    }
+
+    #[test]
+    fn test_collect_stored_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("web.json");
+        fs::write(&path, serde_json::to_string(&report(&[("/etc/a", Severity::High)])).unwrap()).unwrap();
+        let stored = Host {
+            report: Some(path),
+            ..host("web", &[])
+        };
+        let missing = Host {
+            report: Some(dir.path().join("missing.json")),
+            ..host("db", &[])
+        };
+        let fleet = Inventory::collect_with(&[&stored, &missing, &stored], 1);
+        assert_eq!(fleet.hosts.len(), 3);
+        assert_eq!(fleet.hosts[0].summary.failed, 1);
+        assert!(fleet.hosts[2].error.as_ref().unwrap().contains("Path not found"));
+    }
+
+    #[test]
+    fn test_relative_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hosts.toml");
+        let toml = "[[hosts]]\nname = \"db\"\nreport = \"db.json\"\n\n[[hosts]]\nname = \"local\"\nconfig = \"rules.toml\"\n\n[[hosts]]\nname = \"web\"\naddress = \"web-1\"\nconfig = \"rules.toml\"\n";
+        fs::write(&path, toml).unwrap();
+        let inventory = Inventory::from_file(&path).unwrap();
+        assert_eq!(inventory.hosts[0].report.as_deref(), Some(dir.path().join("db.json").as_path()));
+        assert_eq!(inventory.hosts[1].config.as_deref(), Some(dir.path().join("rules.toml").as_path()));
+        assert_eq!(inventory.hosts[2].config.as_deref(), Some(Path::new("rules.toml")));
+    }
+}
@@ -0,0 +1,89 @@
+use alhalo::catalog::CONFIG;
+use alhalo::observer::ResultRef;
+use alhalo::report::AuditReport;
+use alhalo::state::StateStore;
+use alhalo::{Audit, HaloError, Target};
+use std::io;
+use std::path::PathBuf;
+
+// Target that produced the failure `id` in the newest recorded run, if any
+fn recorded_target(store: &StateStore, id: &str) -> Option<String> {
+    let run = store.list_runs().ok()?.pop()?;
+    let report = AuditReport::from_file(&run.path).ok()?;
+    let failure = report.failure_by_id(id)?;
+    Some(report.target_of(failure).to_string())
+}
+
+// Handler for the `ack` command
+// Acknowledges a finding, permission or ownership failure by id, removes an acknowledgment,
+// or lists all acknowledgments. Only the target that owns the failure is re-run: `target`,
+// else the one that reported it in the newest recorded run, else every target.
+// Fails if the id matches no failing result or acknowledgment, or the state store fails
+pub fn handle_ack(
+    id: &Option<String>,
+    comment: &Option<String>,
+    remove: bool,
+    state_dir: &Option<PathBuf>,
+    target: Option<Target>,
+    toml: &Option<PathBuf>,
+) -> Result<(), HaloError> {
+    let store = StateStore::new(state_dir.clone().unwrap_or_else(StateStore::default_dir));
+    let store_error = |e: io::Error| HaloError::io(store.acks_path(), e);
+
+    let Some(id) = id else {
+        let acks = store.acknowledgments().map_err(store_error)?;
+        if acks.is_empty() {
+            println!("No acknowledged findings");
+        }
+        for ack in acks {
+            println!("{} - {}", ack.id, ack.comment);
+        }
+        return Ok(());
+    };
+
+    if remove {
+        if !store.unacknowledge(id).map_err(store_error)? {
+            return Err(HaloError::InvalidArgument(format!("No acknowledgment found for {}", id)));
+        }
+        println!("Removed acknowledgment for {}", id);
+        return Ok(());
+    }
+
+    let Some(comment) = comment else {
+        return Err(HaloError::InvalidArgument(
+            "--comment is required to acknowledge a finding".to_string(),
+        ));
+    };
+
+    // Re-run the owning audit to capture the failure's current state
+    let owner = target.map(|t| t.name().to_string()).or_else(|| recorded_target(&store, id));
+    let owning_target = owner.as_deref().and_then(|name| Target::ALL.into_iter().find(|t| t.name() == name));
+    let mut audit = Audit::new();
+    match (owning_target, owner.as_deref()) {
+        (Some(target), _) => audit = audit.target(target),
+        (None, Some(CONFIG)) if toml.is_some() => {}
+        _ => {
+            for target in &Target::ALL {
+                audit = audit.target(*target);
+            }
+        }
+    }
+    if let Some(path) = toml {
+        audit = audit.config_file(path);
+    }
+    let report = audit.run()?;
+    let Some(failure) = report.failure_by_id(id) else {
+        return Err(HaloError::InvalidArgument(format!("No failing result with id {}", id)));
+    };
+    store.acknowledge(failure.acknowledge(comment)).map_err(store_error)?;
+    let detail = match failure {
+        ResultRef::Permission(r) => format!("mode {:o}, expected {:o}", r.found_mode, r.expected_mode),
+        ResultRef::Ownership(r) => {
+            let id = |id: Option<u32>| id.map_or("?".to_string(), |id| id.to_string());
+            format!("owner {}:{}, expected {}:{}", id(r.found_uid), id(r.found_gid), id(r.expected_uid), id(r.expected_gid))
+        }
+        ResultRef::Finding(f) => f.message.clone(),
+    };
+    println!("Acknowledged {}: {} - {}", id, failure.path().display(), detail);
+    Ok(())
+}
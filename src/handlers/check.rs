@@ -1,291 +1,231 @@
-use crate::fix_script::generate_fix_script;
 use alhalo::{
-    AuditPermissions, Importance, Log, NetConf, PermissionRules, SysConfig, UserConfig,
-    toml_ownership, toml_permissions, Renderable, parse_mode,
+    Audit, AuditReport, CisLevel, Footprint, HaloError, Importance, PathStatus, PermissionRules, Target,
+    parse_mode,
 };
-use std::env;
-use std::io::{self, Write};
-use std::path::PathBuf;
+use alhalo::audit::mounts::MountPolicy;
+use alhalo::audit::scan::{CancelToken, ScanLimits};
+use alhalo::state::StateStore;
+use alhalo::stream::ResultStream;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
 
-/// Audit targets for permissions check.
-///
-/// Used to select which group of files to audit for permissions.
-#[derive(Debug, Clone, clap::ValueEnum)]
-pub enum AuditTarget {
-    User,
-    Sys,
-    Net,
-    Log,
-    All,
+// The audits `halo check` runs: a target, a path with expected mode or owner, or a config
+// file and/or benchmark profile, with the walk options
+#[derive(Default)]
+pub struct CheckRequest {
+    pub target: Option<Target>,
+    pub plugin: Option<String>,
+    pub path: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub toml: Option<PathBuf>,
+    pub expect: Option<String>,
+    pub importance: Option<Importance>,
+    pub expect_uid: Option<u32>,
+    pub expect_gid: Option<u32>,
+    pub footprint: Footprint,
+    pub scan: ScanOptions,
 }
 
-// Audits file permissions and/or ownership based on CLI arguments.
-// Supports permission checks, ownership checks, and TOML config loading.
-// Results are rendered and printed in the selected format.
-pub fn handle_check(
-    target: &Option<AuditTarget>,
-    path: &Option<PathBuf>,
-    format: &Option<String>,
-    expect: &Option<String>,
-    importance: &Option<Importance>,
-    expect_uid: &Option<u32>,
-    expect_gid: &Option<u32>,
-    store: &Option<PathBuf>,
-    toml: &Option<PathBuf>,
-) {
-    if toml.is_some() {
-        handle_toml();
-        return;
+impl CheckRequest {
+    // Whether the rules come from `--toml` and/or `--profile`
+    pub fn is_config(&self) -> bool {
+        self.toml.is_some() || self.profile.is_some()
     }
-    let permission_args = target.is_some() || (expect.is_some() && importance.is_some());
-    let ownership_args = expect_uid.is_some() || expect_gid.is_some();
 
-    if permission_args && ownership_args {
-        let parsed_mode = expect.as_ref().map(|s| parse_mode(s)).transpose();
-        match parsed_mode {
-            Ok(mode_opt) => {
-                handle_permissions(
-                    target.as_ref().map(|t| t.to_owned()),
-                    path.as_ref().map(|p| p.to_owned()),
-                    mode_opt,
-                    importance.as_ref().map(|i| i.to_owned()),
-                    store.as_ref().map(|s| s.to_owned()),
-                    format,
-                );
-            }
-            Err(e) => eprintln!("Error parsing expected mode: {}", e),
-        }
-        handle_ownership(
-            path.as_ref().map(|p| p.to_owned()),
-            *expect_uid,
-            *expect_gid,
-            format,
-        );
-    } else if permission_args {
-        let parsed_mode = expect.as_ref().map(|s| parse_mode(s)).transpose();
-        match parsed_mode {
-            Ok(mode_opt) => {
-                handle_permissions(
-                    target.as_ref().map(|t| t.to_owned()),
-                    path.as_ref().map(|p| p.to_owned()),
-                    mode_opt,
-                    importance.as_ref().map(|i| i.to_owned()),
-                    store.as_ref().map(|s| s.to_owned()),
-                    format,
-                );
-            }
-            Err(e) => eprintln!("Error parsing expected mode: {}", e),
-        }
-    } else if ownership_args {
-        handle_ownership(
-            path.as_ref().map(|p| p.to_owned()),
-            *expect_uid,
-            *expect_gid,
-            format,
-        );
-    } else {
-        println!("No valid permission or ownership audit arguments provided.\n");
+    // Whether permissions are audited: a target or plugin, or a path with expected mode and
+    // importance
+    pub fn checks_permissions(&self) -> bool {
+        !self.is_config()
+            && (self.target.is_some() || self.plugin.is_some() || (self.expect.is_some() && self.importance.is_some()))
+    }
+
+    // Whether the ownership of `path` is audited
+    pub fn checks_ownership(&self) -> bool {
+        !self.is_config() && (self.expect_uid.is_some() || self.expect_gid.is_some())
+    }
+}
+
+// Runs the audits of `request` and returns their report, writing each result to `stream` as
+// it is found if given. Nothing is printed; see `check_output` for rendering.
+pub fn handle_check(request: &CheckRequest, stream: Option<ResultStream>) -> Result<AuditReport, HaloError> {
+    if request.is_config() {
+        return handle_toml(request.toml.as_deref(), request.profile.as_deref(), &request.scan, stream);
+    }
+    if !request.checks_permissions() && !request.checks_ownership() {
+        return Err(HaloError::InvalidArgument(
+            "No valid permission or ownership audit arguments provided.".to_string(),
+        ));
+    }
+    if let Some(name) = &request.plugin {
+        let audit = Audit::new().plugin(name).footprint(request.footprint);
+        let mut report = run_audit(request.scan.apply(audit), stream)?;
+        apply_stored_acknowledgments(&mut report);
+        return Ok(report);
+    }
+    let mut report = AuditReport::default();
+    if request.checks_permissions() {
+        let mode = request
+            .expect
+            .as_deref()
+            .map(parse_mode)
+            .transpose()
+            .map_err(|e| HaloError::InvalidArgument(format!("Error parsing expected mode: {}", e)))?;
+        report = handle_permissions(
+            request.target,
+            request.path.clone(),
+            mode,
+            request.importance.clone(),
+            request.footprint,
+            &request.scan,
+            stream,
+        )?;
+    }
+    if request.checks_ownership() {
+        let ownership = handle_ownership(request.path.clone(), request.expect_uid, request.expect_gid)?;
+        report.ownership.extend(ownership.ownership);
     }
+    Ok(report)
 }
 
 // Audits file permissions based on target type or custom path/mode
 pub fn handle_permissions(
-    target: Option<AuditTarget>,
+    target: Option<Target>,
     path: Option<PathBuf>,
     expected_mode: Option<u32>,
     importance: Option<Importance>,
-    store: Option<PathBuf>,
-    format: &Option<String>,
-) {
-    let mut results = Vec::new();
-
+    footprint: Footprint,
+    scan: &ScanOptions,
+    mut stream: Option<ResultStream>,
+) -> Result<AuditReport, HaloError> {
+    let streaming = stream.is_some();
+    let mut report = AuditReport::default();
     if let Some(t) = target {
-        match t {
-            AuditTarget::User => {
-                let user = UserConfig::default();
-                results.extend(user.run_audit_perms());
-            }
-            AuditTarget::Sys => {
-                let sys = SysConfig::default();
-                results.extend(sys.run_audit_perms());
-            }
-            AuditTarget::Net => {
-                let net = NetConf::default();
-                results.extend(net.run_audit_perms());
-            }
-            AuditTarget::Log => {
-                let logs = Log::default();
-                results.extend(logs.run_audit_perms());
-            }
-            AuditTarget::All => {
-                results.extend(UserConfig::default().run_audit_perms());
-                results.extend(SysConfig::default().run_audit_perms());
-                results.extend(NetConf::default().run_audit_perms());
-                results.extend(Log::default().run_audit_perms());
-            }
-        }
-    } else if let Some(p) = path {
-        if let (Some(mode), Some(imp)) = (expected_mode, importance) {
-            results.extend(PermissionRules::custom_audit(p, mode, imp));
+        report = run_audit(scan.apply(Audit::new().target(t).footprint(footprint)), stream.take())?;
+        apply_stored_acknowledgments(&mut report);
+    } else if let (Some(p), Some(mode), Some(imp)) = (path, expected_mode, importance) {
+        let (rule, status) = PermissionRules::new(p.clone(), mode, imp.clone());
+        let walkable = matches!(status, PathStatus::ValidFile | PathStatus::ValidDirectory);
+        // Directories are walked by an audit run, in parallel unless --low-footprint
+        if (scan.is_set() || streaming || status == PathStatus::ValidDirectory) && walkable {
+            report = run_audit(scan.apply(Audit::new().rule(rule).footprint(footprint)), stream.take())?;
         } else {
-            eprintln!("Error: Both --expect and --importance are required with --path.");
-        }
-    }
-
-    // Handle output rendering
-    if format.is_some() {
-        // Use trait-based rendering for specified formats
-        results.render_and_print(format.as_deref());
-        
-        // Handle file storage for JSON format
-        if format.as_deref() == Some("json") {
-            if let Some(ref path) = store {
-                if let Ok(output) = results.render(alhalo::render_output::OutputFormat::Json) {
-                    if let Err(e) = std::fs::write(&path, &output) {
-                        eprintln!("Failed to store output: {}", e);
-                    } else {
-                        println!("JSON output stored to {}", path.display());
-                    }
-                }
-            }
-        }
-    }
-
-    // Print summary and suggested fixes
-    let total = results.len();
-    let failed: Vec<_> = results
-        .iter()
-        .filter(|r| r.status == alhalo::Status::Fail)
-        .collect();
-    let passed = results
-        .iter()
-        .filter(|r| r.status == alhalo::Status::Pass)
-        .count();
-    let strict = results
-        .iter()
-        .filter(|r| r.status == alhalo::Status::Strict)
-        .count();
-    println!(
-        "\nSummary: {} checked, {} passed, {} strict, {} failed",
-        total,
-        passed,
-        strict,
-        failed.len()
-    );
-    for r in &failed {
-        println!(
-            "[!] FAIL: {} (found: {:o}, expected: {:o})",
-            r.path.display(),
-            r.found_mode,
-            r.expected_mode
-        );
-        if r.found_mode != r.expected_mode && r.path.is_file() && r.expected_mode != 0 {
-            println!(
-                "    Suggested fix: # chmod {:o} {}",
-                r.expected_mode,
-                r.path.display()
-            );
-        }
-        if let Some(err) = &r.error {
-            println!("    Error: {}", err);
+            report.permissions.extend(PermissionRules::custom_audit(p, mode, imp));
         }
+    } else {
+        return Err(HaloError::InvalidArgument(
+            "Both --expect and --importance are required with --path.".to_string(),
+        ));
     }
-    // If any permissions failed, generate script to fix permissions
-    if !failed.is_empty() {
-        print!("Would you like to apply the suggested fixes? [y/N]: ");
-        io::stdout().flush().ok();
-        let mut answer = String::new();
-        if io::stdin().read_line(&mut answer).is_ok() {
-            if answer.trim().eq_ignore_ascii_case("y") {
-                let script = generate_fix_script(&results);
-                println!("\n --- Permission Fix Generated --- \n{}\n", script);
-                print!("Run suggested fixes? [y/N]: ");
-                io::stdout().flush().ok();
-                let mut run_answer = String::new();
-                if io::stdin().read_line(&mut run_answer).is_ok() {
-                    if run_answer.trim().eq_ignore_ascii_case("y") {
-                        let tmp_path = "/tmp/fix_permissions.sh";
-                        if let Err(e) = std::fs::write(tmp_path, &script) {
-                            eprintln!("Failed to write script: {}", e);
-                        } else {
-                            println!("Running fix script as root (requires sudo)...");
-                            let status = std::process::Command::new("sudo")
-                                .arg("bash")
-                                .arg(tmp_path)
-                                .status();
-                            match status {
-                                Ok(s) if s.success() => println!("Permissions fixed"),
-                                Ok(s) => eprintln!("Script exited with: {}", s),
-                                Err(e) => eprintln!("Failed to run script: {}", e),
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    // Results that did not come from an audit run, e.g. a path that does not exist
+    if let Some(mut stream) = stream {
+        report.permissions.iter().for_each(|r| stream.permission(r));
+        stream.finish().map_err(HaloError::Output)?;
     }
+    Ok(report)
 }
 
-// Handler for ownership auditing
-//
-// Checks the ownership of a given path against expected UID and GID.
+// Checks the ownership of a given path against expected UID and GID
 pub fn handle_ownership(
     path: Option<PathBuf>,
     expect_uid: Option<u32>,
     expect_gid: Option<u32>,
-    format: &Option<String>,
-) {
-    if let Some(path_val) = path {
-        if expect_uid.is_some() || expect_gid.is_some() {
-            let (rule, _status) = alhalo::OwnershipRule::new(
-                path_val,
-                expect_uid.unwrap_or(0),
-                expect_gid.unwrap_or(0),
-                true,
-            );
-            let result = rule.check_ownership();
-            result.render_and_print(format.as_deref());
-            // Optionally, print summary or suggested fixes here if desired
-            return;
-        }
-    }
-    println!("Ownership check could not be performed.");
+) -> Result<AuditReport, HaloError> {
+    let Some(path) = path.filter(|_| expect_uid.is_some() || expect_gid.is_some()) else {
+        return Err(HaloError::InvalidArgument(
+            "Ownership check could not be performed: --path and --expect-uid or --expect-gid are required."
+                .to_string(),
+        ));
+    };
+    let (rule, _status) = alhalo::OwnershipRule::new(path, expect_uid.unwrap_or(0), expect_gid.unwrap_or(0), true);
+    let mut report = AuditReport::default();
+    report.ownership.push(rule.check_ownership());
+    Ok(report)
 }
 
-// Handler for TOML configuration loading
+// Handler for TOML configuration loading and benchmark profiles
 //
-// Loads and processes TOML configuration files for permissions and ownership audits
-pub fn handle_toml() {
-    // Get TOML file path and format from CLI args (simple version: env vars or prompt)
-    let args: Vec<String> = env::args().collect();
-    let toml_path = args.iter().find(|a| a.ends_with(".toml"));
-    let format = args.iter().find(|a| a == &"--format").and_then(|_| {
-        let idx = args.iter().position(|a| a == "--format");
-        idx.and_then(|i| args.get(i + 1))
-    });
+// Loads permission, ownership, sysctl and firewall rules from a TOML configuration file and/or
+// runs a benchmark profile such as `cis-l1`, and returns the results; rendering is left to the
+// caller. Other profile names select a `[profiles.<name>]` subset of the config file.
+pub fn handle_toml(
+    path: Option<&Path>,
+    profile: Option<&str>,
+    scan: &ScanOptions,
+    stream: Option<ResultStream>,
+) -> Result<AuditReport, HaloError> {
+    let mut audit = Audit::new();
+    if let Some(path) = path {
+        audit = audit.config_file(path);
+    }
+    match (CisLevel::resolve(profile, path.is_some())?, profile) {
+        (Some(level), _) => audit = audit.cis(level),
+        (None, Some(name)) => audit = audit.profile(name),
+        (None, None) => {}
+    }
+    let mut report = run_audit(scan.apply(audit), stream)?;
+    apply_stored_acknowledgments(&mut report);
+    Ok(report)
+}
+
+// Runs `audit`, streaming results as they are produced if `stream` is given
+fn run_audit(audit: Audit, stream: Option<ResultStream>) -> Result<AuditReport, HaloError> {
+    match stream {
+        Some(stream) => audit.run_streaming(stream),
+        None => audit.run(),
+    }
+}
+
+// Throttling, checkpointing, worker threads, mount boundaries and limits of permission walks
+// from `--io-rate` / `--checkpoint` / `--jobs` / `--one-file-system` / `--max-*`
+#[derive(Default)]
+pub struct ScanOptions {
+    pub io_rate: Option<u32>,
+    pub checkpoint: Option<PathBuf>,
+    pub jobs: Option<usize>,
+    pub one_file_system: bool,
+    pub limits: ScanLimits,
+    pub cancel: Option<CancelToken>,
+}
 
-    let format = format.map(|s| s.to_string()).or(Some("json".to_string()));
+impl ScanOptions {
+    fn is_set(&self) -> bool {
+        self.io_rate.is_some()
+            || self.checkpoint.is_some()
+            || self.jobs.is_some()
+            || self.one_file_system
+            || self.limits.is_set()
+    }
 
-    if let Some(path_str) = toml_path {
-        // Permissions
-        match toml_permissions(path_str) {
-            Ok(toml_permission_results) => {
-                toml_permission_results.render_and_print(format.as_deref());
-            },
-            Err(e) => eprintln!("Error loading TOML permission rules: {}", e),
+    fn apply(&self, mut audit: Audit) -> Audit {
+        if let Some(jobs) = self.jobs {
+            audit = audit.jobs(jobs);
         }
-        // Ownership
-        match toml_ownership(path_str) {
-            Ok(toml_owner_results) => {
-                if !toml_owner_results.is_empty() {
-                    toml_owner_results.render_and_print(format.as_deref());
-                }
-            },
-            Err(e) => eprintln!("Error loading TOML ownership rules: {}", e),
+        if self.one_file_system {
+            audit = audit.mount_policy(MountPolicy::OneFileSystem);
         }
-    } else {
-        eprintln!(
-            "No TOML file path provided. Usage: halo check --toml config.toml [--format json|csv|text]"
-        );
+        if self.limits.is_set() {
+            audit = audit.limits(self.limits);
+        }
+        if let Some(rate) = self.io_rate {
+            audit = audit.io_rate(rate);
+        }
+        if let Some(path) = &self.checkpoint {
+            audit = audit.checkpoint(path);
+        }
+        if let Some(token) = &self.cancel {
+            audit = audit.cancel_token(token.clone());
+        }
+        audit
     }
-}
\ No newline at end of file
+}
+
+// Marks findings acknowledged with `halo ack` in the default state directory
+pub fn apply_stored_acknowledgments(report: &mut AuditReport) {
+    match StateStore::new(StateStore::default_dir()).acknowledgments() {
+        Ok(acks) => {
+            let applied = report.apply_acknowledgments(&acks);
+            debug!("Applied {} of {} stored acknowledgments", applied, acks.len());
+        }
+        Err(e) => warn!("Failed to load acknowledgments: {}", e),
+    }
+}
@@ -0,0 +1,26 @@
+use crate::cli::Cli;
+use alhalo::HaloError;
+use clap::CommandFactory;
+use clap_complete::{Shell, generate};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+// Handler for the `completions` command
+//
+// Generates the completion script for `shell`, to stdout or to the file `out`. The script is
+// built in memory first: clap_complete panics on write errors such as a closed pipe
+pub fn handle_completions(shell: Shell, out: &Option<PathBuf>) -> Result<(), HaloError> {
+    let mut script = Vec::new();
+    generate(shell, &mut Cli::command(), "halo", &mut script);
+    match out {
+        Some(path) => {
+            std::fs::write(path, &script).map_err(|e| HaloError::io(path, e))?;
+            println!("{} completion script generated at: {}", shell, path.display());
+        }
+        None => match io::stdout().lock().write_all(&script) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(HaloError::Output(e)),
+            _ => {}
+        },
+    }
+    Ok(())
+}
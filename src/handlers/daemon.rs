@@ -0,0 +1,16 @@
+use alhalo::daemon::Daemon;
+use std::path::Path;
+
+// Handler for the `daemon` command
+//
+// Loads the daemon config and runs its jobs in the foreground until the process is stopped.
+// Returns 1 if the config cannot be loaded; otherwise it does not return
+pub fn handle_daemon(config: &Path) -> i32 {
+    match Daemon::from_file(config.to_path_buf()) {
+        Ok(mut daemon) => daemon.run(),
+        Err(e) => {
+            eprintln!("Failed to load daemon config {}: {}", config.display(), e);
+            1
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use alhalo::diff::ReportDiff;
+use alhalo::{AuditReport, HaloError, Renderable};
+use std::path::Path;
+
+// Handler for the `diff` command
+//
+// Loads two stored JSON reports and prints the failures that are new in `new`, the ones that
+// were resolved and the results whose mode or owner changed. Fails if a report cannot be read
+pub fn handle_diff(old: &Path, new: &Path, format: &Option<String>) -> Result<(), HaloError> {
+    let old = AuditReport::from_file(old)?;
+    let new = AuditReport::from_file(new)?;
+    ReportDiff::new(&old, &new).render_and_print(format.as_deref());
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use crate::fix_script::generate_fix_script;
+use alhalo::remediate::{FixPlan, FixStatus, Remediator};
+use alhalo::state::StateStore;
+use alhalo::{AuditReport, Renderable};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+
+// What `fix` works from: a stored report and an optional undo journal for `--from`, or the
+// journal to restore for `--rollback`
+pub enum FixSource<'a> {
+    Report(&'a Path, Option<&'a Path>),
+    Rollback(&'a Path),
+}
+
+// Handler for the `fix` command
+//
+// Plans fixes for the failures in a stored JSON report, or the changes that restore an undo
+// journal. With `--dry-run` prints the plan, with `--script` the equivalent shell script, with
+// `--format ansible` an Ansible playbook for `hosts`, by default the audited host, and changes nothing. With `--yes` applies the plan and prints one result per fix; otherwise
+// prints the plan and applies it after a single confirmation when stdin is a terminal.
+// Without a terminal and without `--yes` nothing is changed, so scripts never block on a
+// prompt. Fixes from a report record the prior state of each path in an undo journal first.
+// Returns the exit status: 1 if the report or journal could not be loaded, the fixes were
+// declined or any of them failed
+pub fn handle_fix(
+    source: FixSource,
+    dry_run: bool,
+    script: bool,
+    yes: bool,
+    format: &Option<String>,
+    hosts: Option<&str>,
+) -> i32 {
+    // A journal was written on this machine
+    let mut audited_host = "localhost".to_string();
+    let plan = match source {
+        FixSource::Report(path, _) => AuditReport::from_file(path).map_err(Box::<dyn std::error::Error>::from).map(|r| {
+            if let Some(metadata) = r.metadata.as_ref().filter(|m| !m.hostname.is_empty()) {
+                audited_host = metadata.hostname.clone();
+            }
+            FixPlan::from_report(&r)
+        }),
+        FixSource::Rollback(path) => FixPlan::from_journal(path)
+            .map_err(|e| format!("Failed to read journal {}: {}", path.display(), e).into()),
+    };
+    let plan = match plan {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return 1;
+        }
+    };
+    if script {
+        print!("{}", generate_fix_script(&plan));
+        return 0;
+    }
+    if format.as_deref() == Some("ansible") {
+        print!("{}", plan.playbook(hosts.unwrap_or(&audited_host)));
+        return 0;
+    }
+    if !yes || plan.is_empty() {
+        plan.render_and_print(format.as_deref());
+    }
+    if dry_run || plan.is_empty() {
+        return 0;
+    }
+    if !yes {
+        if !io::stdin().is_terminal() {
+            eprintln!("Not applying fixes without confirmation; pass --yes to apply them");
+            return 1;
+        }
+        print!("Apply {} fixes? [y/N]: ", plan.applicable().count());
+        io::stdout().flush().ok();
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer).is_err() || !answer.trim().eq_ignore_ascii_case("y") {
+            return 1;
+        }
+    }
+    let remediator = match source {
+        FixSource::Report(_, Some(journal)) => Remediator::new().journal(journal),
+        FixSource::Report(_, None) => match StateStore::new(StateStore::default_dir()).new_journal() {
+            Ok(journal) => Remediator::new().journal(journal),
+            Err(e) => {
+                eprintln!("Failed to create undo journal: {}", e);
+                return 1;
+            }
+        },
+        FixSource::Rollback(_) => Remediator::new(),
+    };
+    let results = remediator.apply(&plan);
+    results.render_and_print(format.as_deref());
+    let count = |status: FixStatus| results.iter().filter(|r| r.status == status).count();
+    let failed = count(FixStatus::Failed);
+    eprintln!(
+        "{} applied, {} skipped, {} failed",
+        count(FixStatus::Applied),
+        count(FixStatus::Skipped),
+        failed
+    );
+    if let Some(journal) = remediator.journal_path()
+        && count(FixStatus::Applied) > 0
+    {
+        eprintln!("Undo with: halo fix --rollback {}", journal.display());
+    }
+    if failed > 0 { 1 } else { 0 }
+}
@@ -0,0 +1,26 @@
+use alhalo::Renderable;
+use alhalo::fleet::{Inventory, MAX_PARALLEL_HOSTS};
+use std::path::Path;
+
+// Handler for the `fleet` command
+//
+// Loads the inventory, collects the report of each host (optionally only those tagged `tag`)
+// at most `jobs` at a time, and prints the consolidated fleet report. Returns 1 if the inventory cannot be loaded, no
+// host matches, or a host could not be audited
+pub fn handle_fleet(inventory: &Path, tag: &Option<String>, jobs: Option<usize>, format: &Option<String>) -> i32 {
+    let inventory = match Inventory::from_file(inventory) {
+        Ok(inventory) => inventory,
+        Err(e) => {
+            eprintln!("Error loading inventory: {}", e);
+            return 1;
+        }
+    };
+    let hosts = inventory.tagged(tag.as_deref());
+    if hosts.is_empty() {
+        eprintln!("No hosts tagged '{}' in the inventory", tag.as_deref().unwrap_or_default());
+        return 1;
+    }
+    let report = Inventory::collect_with(&hosts, jobs.unwrap_or(MAX_PARALLEL_HOSTS));
+    report.render_and_print(format.as_deref());
+    i32::from(report.has_unreachable())
+}
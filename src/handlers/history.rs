@@ -0,0 +1,40 @@
+use crate::cli::HistoryAction;
+use alhalo::Renderable;
+use alhalo::history::History;
+use std::path::Path;
+
+// Handler for the `history` command
+//
+// Opens the history database and lists its runs, prints the report of one run, or lists the
+// results recorded for one path. Returns 1 if the database cannot be read or the run does
+// not exist
+pub fn handle_history(db: &Path, action: &HistoryAction, format: &Option<String>) -> i32 {
+    let history = match History::open(db) {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!("Error opening history: {}", e);
+            return 1;
+        }
+    };
+    let result = match action {
+        HistoryAction::List { limit } => history.runs(*limit).map(|runs| runs.render_and_print(format.as_deref())),
+        HistoryAction::Show { id } => match history.report(*id) {
+            Ok(Some(report)) => {
+                report.render_and_print(format.as_deref());
+                Ok(())
+            }
+            Ok(None) => Err(format!("No run {} in {}", id, db.display()).into()),
+            Err(e) => Err(e),
+        },
+        HistoryAction::Path { path, failures, limit } => history
+            .path_history(path, *failures, *limit)
+            .map(|records| records.render_and_print(format.as_deref())),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error reading history: {}", e);
+            1
+        }
+    }
+}
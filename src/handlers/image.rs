@@ -0,0 +1,45 @@
+use crate::cli::{FailOn, ImageAction};
+use alhalo::Renderable;
+use alhalo::image::ContainerImage;
+use std::fs;
+
+// Handler for the `image` command
+//
+// Unpacks the image into a temporary directory, audits it with the selected targets plus the
+// setuid and ownership checks of its layers, prints the report and optionally stores it as
+// JSON. Returns 1 if the image cannot be read or audited, or if a failure is at or above
+// `--fail-on`
+pub fn handle_image(action: &ImageAction) -> i32 {
+    match action {
+        ImageAction::Audit {
+            image,
+            target,
+            format,
+            store,
+            fail_on,
+        } => {
+            let report = match ContainerImage::open(image).and_then(|image| image.audit(target)) {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!("Error auditing image {}: {}", image.display(), e);
+                    return 1;
+                }
+            };
+            report.render_and_print(format.as_deref());
+            if let Some(file) = store {
+                let written = serde_json::to_string_pretty(&report)
+                    .map_err(|e| e.to_string())
+                    .and_then(|json| fs::write(file, json).map_err(|e| e.to_string()));
+                if let Err(e) = written {
+                    eprintln!("Error writing report to {}: {}", file.display(), e);
+                    return 1;
+                }
+            }
+            let failures = match fail_on.threshold() {
+                Some(min) => report.failure_count_at_least(&min),
+                None => report.failure_count(),
+            };
+            i32::from(*fail_on != FailOn::Never && failures > 0)
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::cli::{KeysAction, SignFormat};
+use alhalo::signing::{DetachedSignature, PublicKey, SigningKeys};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Handler for the `keys` command
+//
+// Generates the report signing key pair in `dir`, refusing to replace an existing one unless
+// forced, or prints the id and public key of the pair. Returns 1 on failure
+pub fn handle_keys(dir: &Path, action: &KeysAction) -> i32 {
+    match action {
+        KeysAction::Generate { force } => {
+            let keys = match SigningKeys::generate() {
+                Ok(keys) => keys,
+                Err(e) => {
+                    eprintln!("Error generating key: {}", e);
+                    return 1;
+                }
+            };
+            if let Err(e) = keys.save(dir, *force) {
+                if e.kind() == std::io::ErrorKind::AlreadyExists {
+                    eprintln!("A key already exists in {}; pass --force to replace it", dir.display());
+                } else {
+                    eprintln!("Error writing key to {}: {}", dir.display(), e);
+                }
+                return 1;
+            }
+            println!("Generated key {} in {}", keys.public_key().id(), dir.display());
+            println!("Public key: {}", keys.public_key().to_base64());
+            0
+        }
+        KeysAction::Show => match PublicKey::load(dir) {
+            Ok(key) => {
+                println!("Key id:     {}", key.id());
+                println!("Public key: {}", key.to_base64());
+                0
+            }
+            Err(e) => {
+                eprintln!("Error loading key: {} (run `halo keys generate` first)", e);
+                1
+            }
+        },
+    }
+}
+
+// Signs a stored report with the key pair in `dir`
+//
+// Writes a detached signature to `<file>.sig` or the report as a compact JWS to `<file>.jws`
+// and returns the path written
+pub fn sign_report(file: &Path, dir: &Path, format: SignFormat) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let keys = SigningKeys::load(dir).map_err(|e| format!("{} (run `halo keys generate` first)", e))?;
+    let data = fs::read(file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    let (path, content) = match format {
+        SignFormat::Detached => (
+            DetachedSignature::path_for(file),
+            serde_json::to_string_pretty(&keys.sign(&data))?,
+        ),
+        SignFormat::Jws => {
+            let mut path = file.as_os_str().to_owned();
+            path.push(".jws");
+            (PathBuf::from(path), keys.jws(&data))
+        }
+    };
+    fs::write(&path, content + "\n")?;
+    Ok(path)
+}
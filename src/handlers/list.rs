@@ -0,0 +1,18 @@
+use alhalo::catalog::{CatalogKind, config_profiles, entries};
+use alhalo::{AuditConfig, HaloError, Renderable};
+use std::path::PathBuf;
+
+// Handler for the `list` command
+// Prints the targets, profiles, formats or check ids HALO knows about; profiles also include
+// the ones defined in the config file, if given. Fails if that config cannot be loaded
+pub fn handle_list(kind: CatalogKind, format: &Option<String>, toml: &Option<PathBuf>) -> Result<(), HaloError> {
+    let mut listed = entries(kind);
+    if kind == CatalogKind::Profiles
+        && let Some(path) = toml
+    {
+        let config = AuditConfig::from_file(&path.to_string_lossy())?;
+        listed.extend(config_profiles(&config));
+    }
+    listed.render_and_print(format.as_deref());
+    Ok(())
+}
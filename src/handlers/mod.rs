@@ -2,19 +2,67 @@
 //!
 //! This module contains individual handlers for each CLI command:
 //! - `parse`: File parsing and rendering
-//! - `check`: Permission and ownership auditing
+//! - `check`: Permission and ownership auditing, returning the report to the CLI for output
+//! - `prune`: Retention management for recorded runs
+//! - `snapshot`: Policy generation from the current filesystem state
+//! - `ack`: Acknowledgment of accepted findings
+//! - `list`: Listing of targets, profiles, formats and checks
+//! - `diff`: Comparison of two stored reports
+//! - `fix`: Planning and applying fixes for a stored report
+//! - `watch`: Scheduled re-runs that print what changed
+//! - `daemon`: Background audits on cron-style schedules
+//! - `monitor`: Real-time re-audits of changed rule paths
+//! - `history`: Queries of the SQLite run history
+//! - `trend`: Failure and risk trends across recorded runs
+//! - `keys`: Report signing keys and signing of stored reports
+//! - `verify`: Verification of signed reports
+//! - `fleet`: Consolidated reports across the hosts of an inventory
+//! - `image`: Audits of container images
 //! - `net`: Network discovery
-//! - `bash`: Shell completion generation
+//! - `schema`: JSON report schema
+//! - `completions`: Shell completion generation
 //! - `file`: File reading and parsing utilities
 
 pub mod parse;
 pub mod check;
+pub mod prune;
+pub mod snapshot;
+pub mod ack;
+pub mod list;
+pub mod diff;
+pub mod fix;
+pub mod watch;
+pub mod daemon;
+pub mod monitor;
+pub mod history;
+pub mod trend;
+pub mod keys;
+pub mod verify;
+pub mod fleet;
+pub mod image;
 pub mod net;
-pub mod bash;
+pub mod schema;
+pub mod completions;
 pub mod file;
 
 // Re-export handler functions used by CLI
 pub use parse::handle_parse;
 pub use check::handle_check;
+pub use prune::handle_prune;
+pub use snapshot::handle_snapshot;
+pub use ack::handle_ack;
+pub use list::handle_list;
+pub use diff::handle_diff;
+pub use fix::handle_fix;
+pub use watch::handle_watch;
+pub use daemon::handle_daemon;
+pub use monitor::handle_monitor;
+pub use history::handle_history;
+pub use trend::handle_trend;
+pub use keys::handle_keys;
+pub use verify::handle_verify;
+pub use fleet::handle_fleet;
+pub use image::handle_image;
 pub use net::handle_net;
-pub use bash::handle_bash;
\ No newline at end of file
+pub use schema::handle_schema;
+pub use completions::handle_completions;
\ No newline at end of file
@@ -0,0 +1,95 @@
+use crate::handlers::check::apply_stored_acknowledgments;
+use alhalo::audit::monitor::Monitor;
+use alhalo::audit::toml_config::load_config;
+use alhalo::reload::{ConfigWatcher, ReloadEvent};
+use alhalo::{AuditConfig, HaloError, Renderable, Severity, Target};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use tracing::{error, info};
+
+// How often the monitor checks for events and config changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// Handler for the `monitor` command
+//
+// Watches the permission and ownership rule paths of the target and/or config and prints
+// the re-audit of each path as soon as its mode or owner changes, until interrupted. A changed
+// config file is reloaded and its rule paths watched instead; if it fails to load, the last
+// good config stays in effect
+pub fn handle_monitor(
+    target: &Option<Target>,
+    toml: &Option<PathBuf>,
+    profile: &Option<String>,
+    min_severity: &Option<Severity>,
+    format: &Option<String>,
+) -> i32 {
+    let profile = profile.clone();
+    let config = toml.clone().map(|path| {
+        ConfigWatcher::new(vec![path], move |paths| {
+            let config = load_config(&paths[0])?;
+            let config = match &profile {
+                Some(name) => config.profile(name)?,
+                None => config,
+            };
+            // Reject invalid modes here, so they never replace the last good config
+            config.permission_rules()?;
+            Ok(config)
+        })
+    });
+    let mut config = match config.transpose() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error loading config: {}", e);
+            return 1;
+        }
+    };
+    let build = |config: Option<&AuditConfig>| -> Result<Monitor, HaloError> {
+        let mut monitor = Monitor::new();
+        if let Some(t) = target {
+            monitor = monitor.target(*t);
+        }
+        match config {
+            Some(config) => monitor.config(config),
+            None => Ok(monitor),
+        }
+    };
+    let mut monitor = match build(config.as_ref().map(ConfigWatcher::current)) {
+        Ok(monitor) => monitor,
+        Err(e) => {
+            eprintln!("Error loading config: {}", e);
+            return 1;
+        }
+    };
+    loop {
+        if let Some(watcher) = config.as_mut() {
+            match watcher.poll() {
+                ReloadEvent::Reloaded => match build(Some(watcher.current())) {
+                    Ok(reloaded) => {
+                        info!("Reloaded {}", watcher.paths()[0].display());
+                        monitor = reloaded;
+                    }
+                    Err(e) => error!("Keeping last good config: {}", e),
+                },
+                ReloadEvent::Rejected(e) => error!("Keeping last good config: {}", e),
+                ReloadEvent::Unchanged => {}
+            }
+        }
+        match monitor.poll() {
+            Ok(Some(mut report)) => {
+                apply_stored_acknowledgments(&mut report);
+                if let Some(min) = min_severity {
+                    report.retain_min_severity(min);
+                }
+                if !report.is_empty() {
+                    report.render_and_print(format.as_deref());
+                }
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                eprintln!("Monitor failed: {}", e);
+                return 1;
+            }
+        }
+    }
+}
@@ -1,17 +1,30 @@
 use alhalo::audit::networking::discovery::get_arp_devices;
-use alhalo::Renderable;
+use alhalo::audit::networking::sockets::{apply_allowlist, get_listening_sockets};
+use alhalo::{HaloError, Renderable};
 
 // Handler for the `net` command
-// Performs network discovery and renders results in the specified format
-pub fn handle_net(format: &Option<String>, devices: bool) {
+// Performs network discovery and renders results in the specified format. Returns the number
+// of listening sockets on ports outside the allowlist
+pub fn handle_net(format: &Option<String>, devices: bool, sockets: bool, allow_ports: &[u16]) -> Result<usize, HaloError> {
+    if !devices && !sockets {
+        return Err(HaloError::InvalidArgument(
+            "Network discovery requires the --devices or --sockets flag".to_string(),
+        ));
+    }
     if devices {
-        match get_arp_devices() {
-            Ok(results) => {
-                results.render_and_print(format.as_deref());
-            },
-            Err(e) => eprintln!("Error discovering network devices: {}", e),
+        get_arp_devices()?.render_and_print(format.as_deref());
+    }
+    let mut unexpected = 0;
+    if sockets {
+        let mut results = get_listening_sockets()?;
+        if !allow_ports.is_empty() {
+            apply_allowlist(&mut results, allow_ports);
+        }
+        results.render_and_print(format.as_deref());
+        unexpected = results.iter().filter(|s| s.expected == Some(false)).count();
+        if unexpected > 0 {
+            eprintln!("{} listening socket(s) on ports outside the allowlist", unexpected);
         }
-    } else {
-        eprintln!("Network discovery requires the --devices flag");
     }
-}
\ No newline at end of file
+    Ok(unexpected)
+}
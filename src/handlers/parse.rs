@@ -1,32 +1,34 @@
 use crate::handlers::file::handle_file;
-use alhalo::{ParsedData, Renderable};
+use alhalo::{HaloError, LineRegex, MatchOn, ParsedData, Renderable};
+use alhalo::parsers::FileParser;
 use alhalo::render_output::OutputFormat;
 use std::path::PathBuf;
 
 // Handler for the `parse` command
 // Parses the specified file and renders output in the selected format
+// Fails if a `--line-regex` pattern is invalid or the output cannot be rendered or stored
 pub fn handle_parse(
     file: &Option<PathBuf>,
     format: &Option<String>,
     line: &Option<Vec<String>>,
     store: &Option<PathBuf>,
-) {
-    let data = handle_file(file.as_ref().map(|p| p.to_owned()));
+    parser: FileParser,
+    line_regex: &[String],
+    match_on: MatchOn,
+) -> Result<(), HaloError> {
+    let data = handle_file(file.as_ref().map(|p| p.to_owned()), parser);
     let filter_keys = line.as_ref().cloned().unwrap_or_default();
-    let parsed_data = ParsedData::with_filter(data, filter_keys);
+    let mut parsed_data = ParsedData::with_filter(data, filter_keys);
+    if !line_regex.is_empty() {
+        parsed_data = parsed_data.with_regex(LineRegex::new(line_regex, match_on)?);
+    }
     
     let output_format = OutputFormat::from_str(format.as_deref());
-    match parsed_data.render(output_format) {
-        Ok(output) => {
-            print!("{}", output);
-            if let Some(path) = store {
-                if let Err(e) = std::fs::write(path, &output) {
-                    eprintln!("Failed to store output: {}", e);
-                } else {
-                    println!("Output stored to {}", path.display());
-                }
-            }
-        }
-        Err(e) => eprintln!("Error rendering output: {}", e),
+    let output = parsed_data.render(output_format).map_err(HaloError::Output)?;
+    print!("{}", output);
+    if let Some(path) = store {
+        std::fs::write(path, &output).map_err(|e| HaloError::io(path, e))?;
+        println!("Output stored to {}", path.display());
     }
+    Ok(())
 }
\ No newline at end of file
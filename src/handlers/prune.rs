@@ -0,0 +1,37 @@
+use alhalo::HaloError;
+use alhalo::state::{RetentionPolicy, StateStore};
+use std::path::PathBuf;
+
+// Handler for the `prune` command
+// Removes recorded runs that fall outside the retention policy
+pub fn handle_prune(
+    keep_runs: Option<usize>,
+    keep_days: Option<u64>,
+    state_dir: &Option<PathBuf>,
+    dry_run: bool,
+) -> Result<(), HaloError> {
+    let defaults = RetentionPolicy::default();
+    let policy = RetentionPolicy {
+        keep_runs: keep_runs.or(defaults.keep_runs),
+        keep_days: keep_days.or(defaults.keep_days),
+    };
+    let store = StateStore::new(state_dir.clone().unwrap_or_else(StateStore::default_dir));
+
+    let result = if dry_run {
+        store.expired_runs(&policy)
+    } else {
+        store.prune(&policy)
+    };
+    let runs = result.map_err(|e| HaloError::io(store.runs_dir(), e))?;
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for run in &runs {
+        println!("{} run {}", verb, run.id);
+    }
+    println!(
+        "{} {} run(s) from {}",
+        verb,
+        runs.len(),
+        store.runs_dir().display()
+    );
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use alhalo::HaloError;
+use alhalo::schema::REPORT_SCHEMA;
+use std::path::PathBuf;
+
+// Handler for the `schema` command
+//
+// Prints the JSON schema of `check --format json` reports, or writes it to `store`
+pub fn handle_schema(store: &Option<PathBuf>) -> Result<(), HaloError> {
+    match store {
+        Some(path) => {
+            std::fs::write(path, REPORT_SCHEMA).map_err(|e| HaloError::io(path, e))?;
+            println!("Report schema stored to {}", path.display());
+        }
+        None => print!("{}", REPORT_SCHEMA),
+    }
+    Ok(())
+}
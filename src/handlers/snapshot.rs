@@ -0,0 +1,38 @@
+use alhalo::{HaloError, Renderable};
+use alhalo::render_output::OutputFormat;
+use alhalo::snapshot::Snapshot;
+use std::io;
+use std::path::PathBuf;
+
+// Handler for the `snapshot` command
+// Records the current modes and owners below the given paths, either as a listing or, with
+// `--emit-config`, as a TOML policy that audits against them
+pub fn handle_snapshot(
+    paths: &[PathBuf],
+    emit_config: bool,
+    format: &Option<String>,
+    store: &Option<PathBuf>,
+) -> Result<(), HaloError> {
+    let snapshot = Snapshot::capture(paths);
+    if snapshot.entries.is_empty() {
+        return Err(HaloError::InvalidArgument("Nothing found below the given paths".to_string()));
+    }
+    let output = if emit_config {
+        snapshot.to_toml().map_err(io::Error::other)
+    } else {
+        snapshot.render(OutputFormat::from_str(format.as_deref()))
+    };
+    let output = output.map_err(HaloError::Output)?;
+    match store {
+        Some(path) => {
+            std::fs::write(path, &output).map_err(|e| HaloError::io(path, e))?;
+            println!(
+                "Recorded {} paths to {}",
+                snapshot.entries.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", output),
+    }
+    Ok(())
+}
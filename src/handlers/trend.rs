@@ -0,0 +1,27 @@
+use alhalo::Renderable;
+use alhalo::history::History;
+use alhalo::trend::Trend;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Handler for the `trend` command
+//
+// Prints failure counts, new and resolved failures and the risk score of each run recorded
+// in the history within the last `since`. Returns 1 if the history cannot be read
+pub fn handle_trend(db: &Path, since: Duration, format: &Option<String>) -> i32 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let trend = History::open(db).and_then(|history| Trend::from_history(&history, now.saturating_sub(since.as_secs())));
+    match trend {
+        Ok(trend) => {
+            trend.render_and_print(format.as_deref());
+            0
+        }
+        Err(e) => {
+            eprintln!("Error reading history: {}", e);
+            1
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use alhalo::signing::{DetachedSignature, PublicKey};
+use std::fs;
+use std::path::Path;
+
+// Handler for the `verify` command
+//
+// Checks a report against its detached signature (`<file>.sig` unless `sig` is given), or a
+// `.jws` file by itself, with the public key `key`. Prints the outcome and returns 1 if the
+// signature is missing, malformed or does not match
+pub fn handle_verify(file: &Path, sig: &Option<std::path::PathBuf>, key: &Path) -> i32 {
+    let result = PublicKey::load(key).and_then(|key| {
+        let data = fs::read(file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        if file.extension().is_some_and(|ext| ext == "jws") && sig.is_none() {
+            let content = String::from_utf8(data).map_err(|_| "not a compact JWS")?;
+            key.verify_jws(&content).map(|_| key)
+        } else {
+            let sig = sig.clone().unwrap_or_else(|| DetachedSignature::path_for(file));
+            key.verify(&data, &DetachedSignature::from_file(&sig)?).map(|_| key)
+        }
+    });
+    match result {
+        Ok(key) => {
+            println!("OK: {} is signed by key {}", file.display(), key.id());
+            0
+        }
+        Err(e) => {
+            eprintln!("FAILED: {}: {}", file.display(), e);
+            1
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::handlers::check::apply_stored_acknowledgments;
+use alhalo::diff::ReportDiff;
+use alhalo::reload::{ConfigWatcher, ReloadEvent};
+use alhalo::watch::Watch;
+use alhalo::{Audit, AuditConfig, AuditReport, CisLevel, Renderable, Severity, Target};
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{error, info};
+
+// Handler for the `watch` command
+//
+// Re-runs the target, profile and/or config audit every `interval` until interrupted, or
+// `runs` times, and prints only the failures that are new, resolved or changed since the
+// previous run. A changed config file is reloaded before the next run; if it fails to load,
+// the last good config stays in effect. Returns 1 if the watch could not start
+#[allow(clippy::too_many_arguments)]
+pub fn handle_watch(
+    target: &Option<Target>,
+    toml: &Option<PathBuf>,
+    profile: &Option<String>,
+    min_severity: &Option<Severity>,
+    interval: Duration,
+    runs: Option<usize>,
+    format: &Option<String>,
+) -> i32 {
+    let cis = match CisLevel::resolve(profile.as_deref(), toml.is_some()) {
+        Ok(cis) => cis,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    let mut config = match toml.clone().map(ConfigWatcher::audit_config).transpose() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error loading config: {}", e);
+            return 1;
+        }
+    };
+    let mut audit = || -> Result<AuditReport, Box<dyn std::error::Error>> {
+        let mut audit = Audit::new();
+        if let Some(t) = target {
+            audit = audit.target(*t);
+        }
+        if let Some(level) = cis {
+            audit = audit.cis(level);
+        }
+        if let Some(watcher) = config.as_mut() {
+            match watcher.poll() {
+                ReloadEvent::Reloaded => info!("Reloaded {}", watcher.paths()[0].display()),
+                ReloadEvent::Rejected(e) => error!("Keeping last good config: {}", e),
+                ReloadEvent::Unchanged => {}
+            }
+            let current: &AuditConfig = watcher.current();
+            audit = audit.config(match profile.as_deref().filter(|_| cis.is_none()) {
+                Some(name) => current.profile(name)?,
+                None => current.clone(),
+            });
+        }
+        if let Some(min) = min_severity {
+            audit = audit.min_severity(min.clone());
+        }
+        let mut report = audit.run()?;
+        apply_stored_acknowledgments(&mut report);
+        Ok(report)
+    };
+    let mut watch = Watch::new(interval);
+    if let Some(runs) = runs {
+        watch = watch.max_runs(runs);
+    }
+    let result = watch.run(&mut audit, |diff| {
+        // The previous run is the one printed before
+        let diff = ReportDiff { old_run: None, ..diff.clone() };
+        diff.render_and_print(format.as_deref());
+        ControlFlow::Continue(())
+    });
+    if let Err(e) = result {
+        eprintln!("Watch failed: {}", e);
+        return 1;
+    }
+    0
+}
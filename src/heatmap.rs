@@ -0,0 +1,216 @@
+//! Severity heatmap of the filesystem.
+//!
+//! A host-wide audit can produce thousands of results. A [`Heatmap`] aggregates them by
+//! directory, up to a maximum depth, so the problem areas of a host stand out: each directory
+//! node carries the number of results below it, how many failed and the worst failing
+//! severity.
+//!
+//! JSON output keeps the tree nested so viewers can collapse directories; CSV, text and
+//! Markdown output list one row per directory.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::heatmap::Heatmap;
+//! use alhalo::{Audit, Renderable, Target};
+//! let report = Audit::new().target(Target::Sys).run().unwrap();
+//! let heatmap = Heatmap::from_report(&report, 3);
+//! heatmap.render_and_print(Some("pretty"));
+//! ```
+use crate::audit::permissions::audit_permissions::{Severity, Status};
+use crate::render_output::{DataList, Renderable};
+use crate::report::AuditReport;
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+
+/// Aggregated results for one directory.
+#[derive(Debug, Serialize)]
+pub struct HeatNode {
+    pub path: PathBuf,
+    /// Results at or below this directory
+    pub checked: usize,
+    /// Failing results at or below this directory
+    pub failed: usize,
+    /// Worst severity among the failures, `None` if nothing failed
+    pub worst: Severity,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<HeatNode>,
+}
+
+impl HeatNode {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            checked: 0,
+            failed: 0,
+            worst: Severity::None,
+            children: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, failure: Option<&Severity>) {
+        self.checked += 1;
+        if let Some(severity) = failure {
+            self.failed += 1;
+            if *severity > self.worst {
+                self.worst = severity.clone();
+            }
+        }
+    }
+
+    fn child(&mut self, path: PathBuf) -> &mut HeatNode {
+        let index = match self.children.iter().position(|c| c.path == path) {
+            Some(index) => index,
+            None => {
+                self.children.push(HeatNode::new(path));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[index]
+    }
+
+    /// Most failures first, then worst severity, then path.
+    fn sort(&mut self) {
+        self.children.sort_by(|a, b| {
+            b.failed
+                .cmp(&a.failed)
+                .then(b.worst.cmp(&a.worst))
+                .then(a.path.cmp(&b.path))
+        });
+        for child in &mut self.children {
+            child.sort();
+        }
+    }
+
+    fn rows(&self, depth: usize, data: &mut DataList) {
+        let mut map = IndexMap::new();
+        map.insert("path".to_string(), self.path.display().to_string());
+        map.insert("depth".to_string(), depth.to_string());
+        map.insert("checked".to_string(), self.checked.to_string());
+        map.insert("failed".to_string(), self.failed.to_string());
+        map.insert("worst".to_string(), format!("{:?}", self.worst));
+        data.push(map);
+        for child in &self.children {
+            child.rows(depth + 1, data);
+        }
+    }
+
+    fn tree(&self, prefix: &str, output: &mut String) {
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == self.children.len();
+            let name = child.path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+            output.push_str(&format!(
+                "{}{} {}/  {} failed / {} checked{}\n",
+                prefix,
+                if last { "└─" } else { "├─" },
+                name,
+                child.failed,
+                child.checked,
+                if child.failed > 0 { format!(" (worst: {:?})", child.worst) } else { String::new() }
+            ));
+            child.tree(&format!("{}{}", prefix, if last { "   " } else { "│  " }), output);
+        }
+    }
+}
+
+/// Directory tree of result counts and worst severities.
+#[derive(Debug, Serialize)]
+pub struct Heatmap {
+    /// Deepest directory level aggregated; deeper results count towards their ancestor
+    pub max_depth: usize,
+    pub root: HeatNode,
+}
+
+impl Heatmap {
+    /// Aggregate every permission, ownership and policy result of `report` by directory.
+    ///
+    /// # Arguments
+    /// * `report` - Results to aggregate; acknowledged findings count as passing
+    /// * `max_depth` - Number of directory levels below `/` to show
+    pub fn from_report(report: &AuditReport, max_depth: usize) -> Self {
+        let mut heatmap = Heatmap {
+            max_depth,
+            root: HeatNode::new(PathBuf::from("/")),
+        };
+        for r in &report.permissions {
+            let open = r.status == Status::Fail && r.acknowledged.is_none();
+            heatmap.add(&r.path, open.then_some(&r.severity));
+        }
+        for r in &report.ownership {
+            heatmap.add(&r.path, (!r.pass && r.acknowledged.is_none()).then_some(&r.severity));
+        }
+        for f in &report.findings {
+            heatmap.add(&f.path, f.is_open_failure().then_some(&f.severity));
+        }
+        heatmap.root.sort();
+        heatmap
+    }
+
+    /// Count a result for every directory above `path`, down to `max_depth`.
+    fn add(&mut self, path: &Path, failure: Option<&Severity>) {
+        self.root.add(failure);
+        let dirs: Vec<_> = path
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .take(self.max_depth)
+            .collect();
+        let mut node = &mut self.root;
+        let mut current = PathBuf::from("/");
+        for dir in dirs {
+            current.push(dir);
+            node = node.child(current.clone());
+            node.add(failure);
+        }
+    }
+}
+
+impl Renderable for Heatmap {
+    fn to_datalist(&self) -> DataList {
+        let mut data = Vec::new();
+        self.root.rows(0, &mut data);
+        data
+    }
+
+    fn pretty_print(&self) -> String {
+        let mut output = format!(
+            "/  {} failed / {} checked (worst: {:?})\n",
+            self.root.failed, self.root.checked, self.root.worst
+        );
+        self.root.tree("", &mut output);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Finding;
+
+    #[test]
+    fn test_aggregates_by_directory() {
+        let report = AuditReport {
+            findings: vec![
+                Finding::fail("a", "/etc/ssh/sshd_config", Severity::Medium, "x"),
+                Finding::fail("b", "/etc/ssh/ssh_host_rsa_key", Severity::Critical, "x"),
+                Finding::pass("c", "/etc/passwd", "ok"),
+                Finding::fail("d", "/var/log/deep/nested/file.log", Severity::Low, "x"),
+            ],
+            ..Default::default()
+        };
+        let heatmap = Heatmap::from_report(&report, 2);
+        assert_eq!((heatmap.root.checked, heatmap.root.failed), (4, 3));
+
+        let etc = &heatmap.root.children[0];
+        assert_eq!(etc.path, PathBuf::from("/etc"));
+        assert_eq!((etc.checked, etc.failed, &etc.worst), (3, 2, &Severity::Critical));
+        assert_eq!(etc.children[0].path, PathBuf::from("/etc/ssh"));
+
+        // Depth is capped: /var/log/deep/nested is counted at /var/log
+        let var = &heatmap.root.children[1];
+        assert_eq!(var.children[0].path, PathBuf::from("/var/log"));
+        assert!(var.children[0].children.is_empty());
+        assert_eq!(heatmap.to_datalist().len(), 5);
+    }
+}
@@ -0,0 +1,414 @@
+//! SQLite history of audit runs.
+//!
+//! The [state directory](crate::state) keeps whole reports as files, which is enough to diff
+//! two runs but painful for questions across many, such as "when did `/etc/shadow` last
+//! fail". A [`History`] database records every run with its metadata and summary, the full
+//! report, and one row per result (kind, path, check id, severity, whether it failed and the
+//! mode, owner or message found), so such questions are a single query.
+//!
+//! From the CLI: `halo check --target all --history` records a run in
+//! [`History::default_path`], `halo history list` lists the runs, `halo history show <id>`
+//! prints one again and `halo history path /etc/shadow --failures` answers the question above.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::history::History;
+//! use alhalo::{AuditReport, Finding, Severity};
+//! let dir = tempfile::tempdir().unwrap();
+//! let mut history = History::open(&dir.path().join("history.db")).unwrap();
+//! let mut report = AuditReport::default();
+//! report.findings.push(Finding::fail("sudoers-nopasswd", "/etc/sudoers", Severity::High, "NOPASSWD: ALL"));
+//! let id = history.record(&report).unwrap();
+//! let last = history.last_failure("/etc/sudoers".as_ref()).unwrap().unwrap();
+//! assert_eq!(last.run_id, id);
+//! ```
+use crate::audit::permissions::audit_permissions::Severity;
+use crate::diff::entries;
+use crate::render_output::{DataList, DataMap, Renderable, Style, paint};
+use crate::report::AuditReport;
+use crate::schedule::civil_from_days;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp INTEGER NOT NULL,
+    hostname TEXT NOT NULL,
+    halo_version TEXT NOT NULL,
+    targets TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    checked INTEGER NOT NULL,
+    failed INTEGER NOT NULL,
+    risk_score INTEGER NOT NULL,
+    report TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS results (
+    run_id INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    path TEXT NOT NULL,
+    check_id TEXT,
+    severity TEXT NOT NULL,
+    failing INTEGER NOT NULL,
+    state TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS results_path ON results(path, run_id);
+CREATE INDEX IF NOT EXISTS runs_timestamp ON runs(timestamp);
+";
+
+/// Formats seconds since the Unix epoch as `YYYY-MM-DD HH:MM UTC`.
+pub(crate) fn format_utc(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days((timestamp / 86_400) as i64);
+    let minutes = timestamp % 86_400 / 60;
+    format!("{:04}-{:02}-{:02} {:02}:{:02} UTC", year, month, day, minutes / 60, minutes % 60)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// Severities are stored by name, as in reports
+fn severity_from_name(name: &str) -> Severity {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).unwrap_or(Severity::None)
+}
+
+/// A recorded run, without its results.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryRun {
+    pub id: i64,
+    /// Start of the run in seconds since the Unix epoch
+    pub timestamp: u64,
+    pub hostname: String,
+    pub halo_version: String,
+    pub targets: Vec<String>,
+    pub duration_ms: u64,
+    /// Results checked
+    pub checked: usize,
+    /// Open failures
+    pub failed: usize,
+    pub risk_score: u32,
+}
+
+impl Renderable for HistoryRun {
+    fn to_datalist(&self) -> DataList {
+        let mut map = DataMap::new();
+        map.insert("id".to_string(), self.id.to_string());
+        map.insert("timestamp".to_string(), self.timestamp.to_string());
+        map.insert("hostname".to_string(), self.hostname.clone());
+        map.insert("targets".to_string(), self.targets.join(" "));
+        map.insert("checked".to_string(), self.checked.to_string());
+        map.insert("failed".to_string(), self.failed.to_string());
+        map.insert("risk_score".to_string(), self.risk_score.to_string());
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        format!(
+            "#{:<5} {}  {:<16} {:>4} failed of {:<5} risk {:<4} {}",
+            self.id,
+            format_utc(self.timestamp),
+            self.hostname,
+            self.failed,
+            self.checked,
+            self.risk_score,
+            self.targets.join(", ")
+        )
+    }
+}
+
+/// One recorded result for a path.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathRecord {
+    pub run_id: i64,
+    /// Start of the run in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// `permission`, `ownership` or `finding`
+    pub kind: String,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check: Option<String>,
+    pub severity: Severity,
+    /// Whether the result was an open failure
+    pub failing: bool,
+    /// Mode, `uid:gid` or finding message found
+    pub state: String,
+}
+
+impl Renderable for PathRecord {
+    fn to_datalist(&self) -> DataList {
+        let mut map = DataMap::new();
+        map.insert("run_id".to_string(), self.run_id.to_string());
+        map.insert("timestamp".to_string(), self.timestamp.to_string());
+        map.insert("kind".to_string(), self.kind.clone());
+        map.insert("path".to_string(), self.path.display().to_string());
+        map.insert("check".to_string(), self.check.clone().unwrap_or_default());
+        map.insert("severity".to_string(), format!("{:?}", self.severity));
+        map.insert("failing".to_string(), self.failing.to_string());
+        map.insert("state".to_string(), self.state.clone());
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        let status = if self.failing {
+            paint(&format!("[{:?}]", self.severity), self.severity.style())
+        } else {
+            paint("[ok]", Style::Green)
+        };
+        let check = self.check.as_ref().map(|c| format!(" {}", c)).unwrap_or_default();
+        format!(
+            "#{:<5} {}  {} {}{}: {}",
+            self.run_id,
+            format_utc(self.timestamp),
+            status,
+            self.kind,
+            check,
+            self.state
+        )
+    }
+}
+
+/// A SQLite database of audit runs and their results.
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    /// Opens the database at `path`, creating it and its directory if needed.
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// `$HALO_HISTORY`, else `history.db` in `$XDG_DATA_HOME/halo` or `~/.local/share/halo`,
+    /// else `/var/lib/halo/history.db`.
+    pub fn default_path() -> PathBuf {
+        if let Ok(path) = std::env::var("HALO_HISTORY") {
+            return PathBuf::from(path);
+        }
+        if let Ok(dir) = std::env::var("XDG_DATA_HOME") {
+            return PathBuf::from(dir).join("halo/history.db");
+        }
+        if let Ok(home) = std::env::var("HOME") {
+            return PathBuf::from(home).join(".local/share/halo/history.db");
+        }
+        PathBuf::from("/var/lib/halo/history.db")
+    }
+
+    /// Records `report` with one row per result; returns the run id.
+    ///
+    /// The run time is the report's start time, or now for reports without metadata.
+    pub fn record(&mut self, report: &AuditReport) -> Result<i64, Box<dyn std::error::Error>> {
+        let metadata = report.metadata.clone().unwrap_or_default();
+        let timestamp = if metadata.timestamp > 0 { metadata.timestamp } else { now() };
+        let summary = report.summary();
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (timestamp, hostname, halo_version, targets, duration_ms, checked, failed, risk_score, report)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                timestamp as i64,
+                metadata.hostname,
+                metadata.halo_version,
+                metadata.targets.join(","),
+                metadata.duration_ms as i64,
+                summary.checked as i64,
+                summary.failed as i64,
+                summary.risk_score,
+                serde_json::to_string(report)?,
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO results (run_id, kind, path, check_id, severity, failing, state)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for entry in entries(report).values() {
+                insert.execute(params![
+                    id,
+                    entry.kind,
+                    entry.path.to_string_lossy(),
+                    entry.check,
+                    format!("{:?}", entry.severity),
+                    entry.failing,
+                    entry.state,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Recorded runs, newest first; at most `limit` if set.
+    pub fn runs(&self, limit: Option<usize>) -> Result<Vec<HistoryRun>, Box<dyn std::error::Error>> {
+        self.query_runs(0, limit)
+    }
+
+    /// Runs started at or after `since` (seconds since the Unix epoch), oldest first.
+    pub fn runs_since(&self, since: u64) -> Result<Vec<HistoryRun>, Box<dyn std::error::Error>> {
+        let mut runs = self.query_runs(since, None)?;
+        runs.reverse();
+        Ok(runs)
+    }
+
+    // Runs started at or after `since`, newest first
+    fn query_runs(&self, since: u64, limit: Option<usize>) -> Result<Vec<HistoryRun>, Box<dyn std::error::Error>> {
+        let mut statement = self.conn.prepare(
+            "SELECT id, timestamp, hostname, halo_version, targets, duration_ms, checked, failed, risk_score
+             FROM runs WHERE timestamp >= ?1 ORDER BY timestamp DESC, id DESC LIMIT ?2",
+        )?;
+        let limit = limit.map(|l| l as i64).unwrap_or(-1);
+        let runs = statement
+            .query_map(params![since as i64, limit], |row| {
+                let targets: String = row.get(4)?;
+                Ok(HistoryRun {
+                    id: row.get(0)?,
+                    timestamp: row.get::<_, i64>(1)? as u64,
+                    hostname: row.get(2)?,
+                    halo_version: row.get(3)?,
+                    targets: targets.split(',').filter(|t| !t.is_empty()).map(String::from).collect(),
+                    duration_ms: row.get::<_, i64>(5)? as u64,
+                    checked: row.get::<_, i64>(6)? as usize,
+                    failed: row.get::<_, i64>(7)? as usize,
+                    risk_score: row.get(8)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(runs)
+    }
+
+    /// The full report of run `id`, if recorded.
+    pub fn report(&self, id: i64) -> Result<Option<AuditReport>, Box<dyn std::error::Error>> {
+        let json: Option<String> = self
+            .conn
+            .query_row("SELECT report FROM runs WHERE id = ?1", [id], |row| row.get(0))
+            .optional()?;
+        Ok(match json {
+            Some(json) => Some(serde_json::from_str(&json)?),
+            None => None,
+        })
+    }
+
+    /// Recorded results for `path`, newest first; only failures if `failures_only`, and at
+    /// most `limit` if set.
+    pub fn path_history(
+        &self,
+        path: &Path,
+        failures_only: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<PathRecord>, Box<dyn std::error::Error>> {
+        let mut statement = self.conn.prepare(
+            "SELECT r.run_id, runs.timestamp, r.kind, r.path, r.check_id, r.severity, r.failing, r.state
+             FROM results r JOIN runs ON runs.id = r.run_id
+             WHERE r.path = ?1 AND (?2 = 0 OR r.failing = 1)
+             ORDER BY runs.timestamp DESC, r.run_id DESC
+             LIMIT ?3",
+        )?;
+        let limit = limit.map(|l| l as i64).unwrap_or(-1);
+        let records = statement
+            .query_map(params![path.to_string_lossy(), failures_only, limit], |row| {
+                let path: String = row.get(3)?;
+                let severity: String = row.get(5)?;
+                Ok(PathRecord {
+                    run_id: row.get(0)?,
+                    timestamp: row.get::<_, i64>(1)? as u64,
+                    kind: row.get(2)?,
+                    path: PathBuf::from(path),
+                    check: row.get(4)?,
+                    severity: severity_from_name(&severity),
+                    failing: row.get(6)?,
+                    state: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(records)
+    }
+
+    /// The most recent failure recorded for `path`.
+    pub fn last_failure(&self, path: &Path) -> Result<Option<PathRecord>, Box<dyn std::error::Error>> {
+        Ok(self.path_history(path, true, Some(1))?.pop())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::finding::Finding;
+    use crate::audit::permissions::audit_permissions::{Importance, PermissionResults, Status};
+    use crate::report::RunMetadata;
+
+    fn report(timestamp: u64, mode: u32) -> AuditReport {
+        let mut report = AuditReport {
+            metadata: Some(RunMetadata {
+                hostname: "web1".to_string(),
+                timestamp,
+                targets: vec!["user".to_string(), "sys".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        report.permissions.push(PermissionResults {
+            severity: if mode == 0o640 { Severity::None } else { Severity::Critical },
+            status: if mode == 0o640 { Status::Pass } else { Status::Fail },
+            path: "/etc/shadow".into(),
+            expected_mode: 0o640,
+            found_mode: mode,
+            importance: Importance::High,
+            rule_id: Some("HALO-USER-0002".to_string()),
+            acknowledged: None,
+            error: None,
+        });
+        report.findings.push(Finding::fail("a", "/etc/sudoers", Severity::High, "NOPASSWD: ALL"));
+        report
+    }
+
+    #[test]
+    fn test_record_and_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/history.db");
+        let mut history = History::open(&path).unwrap();
+        let first = history.record(&report(1000, 0o644)).unwrap();
+        let second = history.record(&report(2000, 0o640)).unwrap();
+
+        let runs = history.runs(None).unwrap();
+        assert_eq!(runs.iter().map(|r| r.id).collect::<Vec<_>>(), vec![second, first]);
+        assert_eq!(runs[1].failed, 2);
+        assert_eq!(runs[0].targets, vec!["user", "sys"]);
+        assert_eq!(history.runs(Some(1)).unwrap().len(), 1);
+        assert_eq!(history.runs_since(1500).unwrap()[0].id, second);
+
+        let shadow = Path::new("/etc/shadow");
+        let records = history.path_history(shadow, false, None).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(!records[0].failing);
+        let last = history.last_failure(shadow).unwrap().unwrap();
+        assert_eq!((last.run_id, last.timestamp), (first, 1000));
+        assert_eq!((last.severity, last.state.as_str()), (Severity::Critical, "644"));
+        assert!(history.last_failure(Path::new("/etc/passwd")).unwrap().is_none());
+
+        let stored = history.report(first).unwrap().unwrap();
+        assert_eq!(stored.permissions[0].found_mode, 0o644);
+        assert!(history.report(99).unwrap().is_none());
+
+        // Reopening keeps the runs
+        drop(history);
+        assert_eq!(History::open(&path).unwrap().runs(None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_utc(1_772_360_100), "2026-03-01 10:15 UTC");
+    }
+}
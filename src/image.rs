@@ -0,0 +1,609 @@
+//! Auditing container images without running them.
+//!
+//! `halo image audit ./image.tar` reads a container image, applies its filesystem layers into
+//! a temporary directory and audits that as an [alternate root](crate::audit::sysroot):
+//! the permission targets check the image's files as they would on a host, and the layer
+//! headers are checked for setuid and setgid programs and for system paths not owned by
+//! root. A [`ContainerImage`] is one of:
+//! - a `docker save` tarball (`manifest.json` with the layer list), optionally gzipped;
+//! - an OCI image layout, as a directory or a tarball of one (`index.json`, `blobs/sha256/`);
+//!   multi-platform indexes use the manifest for this machine's architecture;
+//! - a flat root filesystem tarball, such as `docker export` writes.
+//!
+//! Layers are applied in order with their whiteouts (`.wh.<name>` deletes a path of an
+//! earlier layer, `.wh..wh..opq` empties a directory). Symlinks in the image are resolved
+//! inside it while unpacking, so a layer cannot write outside the temporary directory.
+//! Modes are kept, including setuid bits; ownership on disk is only kept when running as
+//! root, which is why owner checks use the uids and gids recorded in the layers. Device
+//! nodes and FIFOs are not created.
+//!
+//! Gzip-compressed and uncompressed layers are supported, zstd layers are not.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::image::ContainerImage;
+//! use alhalo::Target;
+//! let dir = tempfile::tempdir().unwrap();
+//! let path = dir.path().join("rootfs.tar");
+//! let mut archive = tar::Builder::new(std::fs::File::create(&path).unwrap());
+//! let mut header = tar::Header::new_gnu();
+//! header.set_size(0);
+//! header.set_mode(0o4755);
+//! header.set_uid(0);
+//! header.set_gid(0);
+//! archive.append_data(&mut header, "usr/bin/tool", std::io::empty()).unwrap();
+//! archive.finish().unwrap();
+//! drop(archive);
+//!
+//! let report = ContainerImage::open(&path).unwrap().audit(&[Target::Sys]).unwrap();
+//! assert!(report.findings.iter().any(|f| f.check == "image-setuid" && f.is_failure()));
+//! ```
+use crate::audit::builder::{Audit, Target};
+use crate::audit::finding::Finding;
+use crate::audit::permissions::audit_permissions::Severity;
+use crate::audit::sysroot::{host, resolve_in};
+use crate::report::AuditReport;
+use flate2::read::GzDecoder;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::os::unix::fs::{MetadataExt, PermissionsExt, lchown};
+use std::path::{Component, Path, PathBuf};
+use tempfile::TempDir;
+use tracing::{debug, warn};
+
+/// Target name of the checks on the image layers in reports.
+pub const IMAGE_TARGET: &str = "image";
+
+const WHITEOUT_PREFIX: &str = ".wh.";
+const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+
+// Directories whose contents should be owned by root
+const SYSTEM_DIRS: &[&str] = &["/bin", "/boot", "/etc", "/lib", "/lib64", "/root", "/sbin", "/usr"];
+
+// Decompresses `reader` if it is gzip; zstd is recognized to give a clear error
+fn decompress<'a>(reader: impl Read + 'a) -> io::Result<Box<dyn Read + 'a>> {
+    let mut reader = BufReader::new(reader);
+    let magic = reader.fill_buf()?;
+    if magic.starts_with(&[0x1f, 0x8b]) {
+        Ok(Box::new(GzDecoder::new(reader)))
+    } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "zstd compressed layers are not supported"))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+// Archive member name without a leading `./` or `/`
+fn member_name(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Where the image is read from.
+#[derive(Debug, Clone)]
+enum Source {
+    /// An OCI image layout directory
+    Dir(PathBuf),
+    /// A tarball, possibly gzipped
+    Tar(PathBuf),
+}
+
+impl Source {
+    // Calls `f` with the member `name`, or with the whole tarball for an empty name
+    fn with_member<T>(&self, name: &str, f: impl FnOnce(&mut dyn Read) -> io::Result<T>) -> io::Result<T> {
+        match self {
+            Source::Dir(dir) => f(&mut File::open(dir.join(name))?),
+            Source::Tar(path) if name.is_empty() => f(&mut decompress(File::open(path)?)?),
+            Source::Tar(path) => {
+                let mut archive = tar::Archive::new(decompress(File::open(path)?)?);
+                for entry in archive.entries()? {
+                    let mut entry = entry?;
+                    if member_name(&entry.path()?) == name {
+                        return f(&mut entry);
+                    }
+                }
+                Err(io::Error::new(io::ErrorKind::NotFound, format!("{} not found in the image", name)))
+            }
+        }
+    }
+
+    // A JSON member, or `None` if there is no such member
+    fn json(&self, name: &str) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+        match self.with_member(name, |reader| {
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content)?;
+            Ok(content)
+        }) {
+            Ok(content) => Ok(Some(serde_json::from_slice(&content).map_err(|e| format!("Invalid {}: {}", name, e))?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+// Blob path of a `sha256:<hex>` digest in an OCI layout; both parts must be lowercase
+// alphanumeric so a crafted digest cannot name a path outside `blobs/`
+fn blob(digest: &Value) -> Result<String, Box<dyn std::error::Error>> {
+    let digest = digest.as_str().ok_or("descriptor without a digest")?;
+    let valid = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit());
+    match digest.split_once(':') {
+        Some((algorithm, hex)) if valid(algorithm) && valid(hex) => Ok(format!("blobs/{}/{}", algorithm, hex)),
+        _ => Err(format!("invalid digest '{}'", digest).into()),
+    }
+}
+
+// OCI name of this machine's architecture
+fn oci_architecture() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "arm" => "arm",
+        other => other,
+    }
+}
+
+/// A container image on disk.
+#[derive(Debug, Clone)]
+pub struct ContainerImage {
+    path: PathBuf,
+    source: Source,
+    /// Repository tag or OCI reference name, if the image records one
+    pub reference: Option<String>,
+    /// Layer members, bottom layer first; a single empty name for a flat root filesystem
+    /// tarball
+    pub layers: Vec<String>,
+}
+
+impl ContainerImage {
+    /// Reads the layer list of the image at `path`.
+    ///
+    /// # Returns
+    /// * `Err` if the file cannot be read, or a directory is not an OCI image layout.
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let source = if path.is_dir() {
+            Source::Dir(path.to_path_buf())
+        } else {
+            Source::Tar(path.to_path_buf())
+        };
+        let image = |reference, layers| Self {
+            path: path.to_path_buf(),
+            source: source.clone(),
+            reference,
+            layers,
+        };
+        if let Some(manifest) = source.json("manifest.json")? {
+            let entry = manifest.get(0).ok_or("manifest.json lists no image")?;
+            let layers = entry["Layers"]
+                .as_array()
+                .ok_or("manifest.json has no Layers")?
+                .iter()
+                .filter_map(|layer| layer.as_str().map(String::from))
+                .collect();
+            let reference = entry["RepoTags"].get(0).and_then(Value::as_str).map(String::from);
+            return Ok(image(reference, layers));
+        }
+        if let Some(index) = source.json("index.json")? {
+            let reference = index["manifests"][0]["annotations"]["org.opencontainers.image.ref.name"]
+                .as_str()
+                .map(String::from);
+            let mut manifest = index;
+            // Nested indexes select the manifest for this platform, or the first one
+            while let Some(manifests) = manifest["manifests"].as_array() {
+                let descriptor = manifests
+                    .iter()
+                    .find(|m| m["platform"]["os"] == "linux" && m["platform"]["architecture"] == oci_architecture())
+                    .or(manifests.first())
+                    .ok_or("image index lists no manifest")?;
+                let name = blob(&descriptor["digest"])?;
+                manifest = source.json(&name)?.ok_or_else(|| format!("{} not found in the image", name))?;
+            }
+            let layers = manifest["layers"]
+                .as_array()
+                .ok_or("image manifest has no layers")?
+                .iter()
+                .map(|layer| blob(&layer["digest"]))
+                .collect::<Result<_, _>>()?;
+            return Ok(image(reference, layers));
+        }
+        match &source {
+            Source::Dir(_) => Err(format!("{} is not an OCI image layout (no index.json)", path.display()).into()),
+            Source::Tar(_) => Ok(image(None, vec![String::new()])),
+        }
+    }
+
+    /// Applies the layers into `dest`, an empty directory.
+    pub fn unpack(&self, dest: &Path) -> Result<ImageFilesystem, Box<dyn std::error::Error>> {
+        let mut filesystem = ImageFilesystem {
+            root: dest.to_path_buf(),
+            entries: BTreeMap::new(),
+            skipped: 0,
+            temp: None,
+        };
+        let as_root = fs::metadata("/proc/self").is_ok_and(|m| m.uid() == 0);
+        for layer in &self.layers {
+            debug!("Applying layer {}", if layer.is_empty() { "(root filesystem)" } else { layer });
+            self.source
+                .with_member(layer, |reader| {
+                    let mut archive = tar::Archive::new(decompress(reader)?);
+                    for entry in archive.entries()? {
+                        filesystem.apply(&mut entry?, as_root)?;
+                    }
+                    Ok(())
+                })
+                .map_err(|e| format!("Failed to apply layer {}: {}", layer, e))?;
+        }
+        filesystem.restore_dir_modes();
+        if filesystem.skipped > 0 {
+            warn!("{} entries of {} could not be unpacked", filesystem.skipped, self.path.display());
+        }
+        Ok(filesystem)
+    }
+
+    /// Unpacks the image into a temporary directory and audits it: `targets` run against it
+    /// as an alternate root, followed by the setuid, setgid and ownership checks of
+    /// [`ImageFilesystem::findings`] (target [`IMAGE_TARGET`]). The report's `metadata.root`
+    /// is the image path, and its host name the image reference if the image has no
+    /// `/etc/hostname`.
+    pub fn audit(&self, targets: &[Target]) -> Result<AuditReport, Box<dyn std::error::Error>> {
+        let temp = tempfile::Builder::new().prefix("halo-image-").tempdir()?;
+        let mut filesystem = self.unpack(temp.path())?;
+        let audit = targets
+            .iter()
+            .fold(Audit::new().root(temp.path()), |audit, target| audit.target(*target));
+        filesystem.temp = Some(temp);
+        let mut report = audit.run()?;
+        let mark = report.mark();
+        report.findings.extend(filesystem.findings());
+        report.attribute(mark, IMAGE_TARGET);
+        if let Some(metadata) = report.metadata.as_mut() {
+            metadata.targets.push(IMAGE_TARGET.to_string());
+            metadata.root = Some(self.path.clone());
+            if metadata.hostname.is_empty()
+                && let Some(reference) = &self.reference
+            {
+                metadata.hostname = reference.clone();
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Kind of a path in an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    Symlink,
+    /// Device node, FIFO or other special file
+    Other,
+}
+
+/// Mode and owner of a path as recorded in the image layers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageEntry {
+    pub kind: EntryKind,
+    /// Permission bits including setuid, setgid and sticky
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+}
+
+/// The merged filesystem of an unpacked image.
+#[derive(Debug)]
+pub struct ImageFilesystem {
+    /// Directory the layers were applied into
+    pub root: PathBuf,
+    /// Every path of the merged image, as inside it
+    pub entries: BTreeMap<PathBuf, ImageEntry>,
+    /// Entries that could not be unpacked, e.g. hard links to missing files
+    pub skipped: usize,
+    // Removed, after making its directories writable again, when the filesystem is dropped
+    temp: Option<TempDir>,
+}
+
+impl ImageFilesystem {
+    // Applies one layer entry; failures of single entries are counted, not returned
+    fn apply<R: Read>(&mut self, entry: &mut tar::Entry<R>, as_root: bool) -> io::Result<()> {
+        let path = entry.path()?.into_owned();
+        if path.components().any(|c| c == Component::ParentDir) {
+            self.skipped += 1;
+            return Ok(());
+        }
+        let Some(name) = path.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+            return Ok(());
+        };
+        let parent = Path::new("/").join(path.parent().unwrap_or(Path::new("")));
+        let Ok(parent) = resolve_in(&self.root, &parent, true) else {
+            self.skipped += 1;
+            return Ok(());
+        };
+        fs::create_dir_all(host(&self.root, &parent))?;
+
+        if name == OPAQUE_WHITEOUT {
+            for child in fs::read_dir(host(&self.root, &parent))? {
+                remove(&child?.path())?;
+            }
+            self.forget(&parent, false);
+            return Ok(());
+        }
+        if let Some(hidden) = name.strip_prefix(WHITEOUT_PREFIX) {
+            let logical = parent.join(hidden);
+            remove(&host(&self.root, &logical))?;
+            self.forget(&logical, true);
+            return Ok(());
+        }
+
+        let logical = parent.join(&name);
+        let target = host(&self.root, &logical);
+        let header = entry.header();
+        let (mode, uid, gid) = (header.mode()? & 0o7777, header.uid()? as u32, header.gid()? as u32);
+        let entry_type = header.entry_type();
+        let existing = fs::symlink_metadata(&target).ok();
+        if !(entry_type.is_dir() && existing.as_ref().is_some_and(|m| m.is_dir())) {
+            remove(&target)?;
+        }
+        let record = if entry_type.is_dir() {
+            fs::create_dir_all(&target)?;
+            ImageEntry { kind: EntryKind::Directory, mode, uid, gid }
+        } else if entry_type.is_hard_link() {
+            let Some(link) = entry.link_name()? else {
+                self.skipped += 1;
+                return Ok(());
+            };
+            let linked = Path::new("/").join(link);
+            let Some(source) = resolve_in(&self.root, &linked, false)
+                .ok()
+                .filter(|source| fs::hard_link(host(&self.root, source), &target).is_ok())
+            else {
+                self.skipped += 1;
+                return Ok(());
+            };
+            match self.entries.get(&source) {
+                Some(linked) => *linked,
+                None => ImageEntry { kind: EntryKind::File, mode, uid, gid },
+            }
+        } else if entry_type.is_file() || entry_type.is_symlink() {
+            entry.set_preserve_permissions(true);
+            entry.set_preserve_mtime(true);
+            entry.unpack(&target)?;
+            let kind = if entry_type.is_file() { EntryKind::File } else { EntryKind::Symlink };
+            ImageEntry { kind, mode, uid, gid }
+        } else if entry_type.is_character_special() || entry_type.is_block_special() || entry_type.is_fifo() {
+            ImageEntry { kind: EntryKind::Other, mode, uid, gid }
+        } else {
+            return Ok(());
+        };
+        if as_root && record.kind != EntryKind::Other {
+            lchown(&target, Some(record.uid), Some(record.gid))?;
+        }
+        self.entries.insert(logical, record);
+        Ok(())
+    }
+
+    // Drops `path` (if `itself`) and everything below it from the entries
+    fn forget(&mut self, path: &Path, itself: bool) {
+        self.entries
+            .retain(|entry, _| !(entry.starts_with(path) && (itself || entry != path)));
+    }
+
+    // Directories stay writable while layers are applied; give them their final modes,
+    // deepest first so that a closed parent does not block its children
+    fn restore_dir_modes(&self) {
+        let mut dirs: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.kind == EntryKind::Directory)
+            .collect();
+        dirs.sort_by_key(|(path, _)| std::cmp::Reverse(path.components().count()));
+        for (path, entry) in dirs {
+            let _ = fs::set_permissions(host(&self.root, path), fs::Permissions::from_mode(entry.mode));
+        }
+    }
+
+    /// Setuid and setgid programs, and files and directories under the system directories
+    /// (`/bin`, `/etc`, `/usr`, ...) not owned by root, from the modes and owners recorded in
+    /// the layers. One passing finding per check when nothing is found.
+    pub fn findings(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut special = false;
+        let mut owned = true;
+        for (path, entry) in &self.entries {
+            if entry.kind == EntryKind::File && entry.mode & 0o4000 != 0 {
+                special = true;
+                let owner = if entry.uid == 0 { "root".to_string() } else { format!("uid {}", entry.uid) };
+                findings.push(Finding::fail(
+                    "image-setuid",
+                    path,
+                    Severity::Medium,
+                    format!("setuid program owned by {} (mode {:o}); remove the bit unless it is required", owner, entry.mode),
+                ));
+            }
+            if entry.kind == EntryKind::File && entry.mode & 0o2000 != 0 {
+                special = true;
+                findings.push(Finding::fail(
+                    "image-setgid",
+                    path,
+                    Severity::Low,
+                    format!("setgid program of gid {} (mode {:o})", entry.gid, entry.mode),
+                ));
+            }
+            let system = SYSTEM_DIRS.iter().any(|dir| path.starts_with(dir));
+            if system && entry.kind != EntryKind::Symlink && entry.uid != 0 {
+                owned = false;
+                findings.push(Finding::fail(
+                    "image-ownership",
+                    path,
+                    Severity::Medium,
+                    format!("owned by uid {}, not root", entry.uid),
+                ));
+            }
+        }
+        if !special {
+            findings.push(Finding::pass("image-setuid", "/", "no setuid or setgid programs"));
+        }
+        if owned {
+            findings.push(Finding::pass("image-ownership", "/", "system directories are owned by root"));
+        }
+        findings
+    }
+}
+
+impl Drop for ImageFilesystem {
+    fn drop(&mut self) {
+        if self.temp.is_some() {
+            for (path, entry) in &self.entries {
+                if entry.kind == EntryKind::Directory {
+                    let _ = fs::set_permissions(host(&self.root, path), fs::Permissions::from_mode(0o700));
+                }
+            }
+        }
+    }
+}
+
+// Removes whatever is at `path`, if anything
+fn remove(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    // A layer tarball of (path, mode, uid, content) files, symlinks as "->target" content
+    fn layer(files: &[(&str, u32, u64, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, mode, uid, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_mode(*mode);
+            header.set_uid(*uid);
+            header.set_gid(0);
+            if let Some(target) = content.strip_prefix("->") {
+                header.set_entry_type(tar::EntryType::Symlink);
+                header.set_size(0);
+                builder.append_link(&mut header, path, target).unwrap();
+            } else if path.ends_with('/') {
+                header.set_entry_type(tar::EntryType::Directory);
+                header.set_size(0);
+                builder.append_data(&mut header, path, io::empty()).unwrap();
+            } else {
+                header.set_size(content.len() as u64);
+                builder.append_data(&mut header, path, content.as_bytes()).unwrap();
+            }
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn append(builder: &mut tar::Builder<File>, name: &str, data: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_uid(0);
+        header.set_gid(0);
+        builder.append_data(&mut header, name, data).unwrap();
+    }
+
+    #[test]
+    fn test_docker_save_layers_and_whiteouts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("image.tar");
+        let mut builder = tar::Builder::new(File::create(&path).unwrap());
+        let base = layer(&[
+            ("etc/", 0o755, 0, ""),
+            ("etc/shadow", 0o640, 0, "root:*:1::::::\n"),
+            ("etc/old.conf", 0o644, 0, "x"),
+            ("usr/bin/su", 0o4755, 0, "elf"),
+            ("usr/lib/app/", 0o755, 0, ""),
+            ("usr/lib/app/stale", 0o644, 0, "x"),
+            ("etc/escape", 0o777, 0, "->/tmp"),
+        ]);
+        let mut gzipped = GzEncoder::new(Vec::new(), Compression::fast());
+        io::Write::write_all(&mut gzipped, &layer(&[
+            ("etc/.wh.old.conf", 0o644, 0, ""),
+            ("usr/lib/app/.wh..wh..opq", 0o644, 0, ""),
+            ("usr/lib/app/new", 0o644, 1000, "x"),
+            ("etc/shadow", 0o644, 0, "root:*:1::::::\n"),
+            ("etc/escape/owned", 0o644, 0, "x"),
+        ]))
+        .unwrap();
+        append(&mut builder, "base/layer.tar", &base);
+        append(&mut builder, "top/layer.tar", &gzipped.finish().unwrap());
+        append(
+            &mut builder,
+            "manifest.json",
+            br#"[{"Config":"c.json","RepoTags":["app:1.0"],"Layers":["base/layer.tar","top/layer.tar"]}]"#,
+        );
+        builder.finish().unwrap();
+        drop(builder);
+
+        let image = ContainerImage::open(&path).unwrap();
+        assert_eq!(image.reference.as_deref(), Some("app:1.0"));
+        assert_eq!(image.layers.len(), 2);
+        let out = tempfile::tempdir().unwrap();
+        let filesystem = image.unpack(out.path()).unwrap();
+        assert!(!out.path().join("etc/old.conf").exists());
+        assert!(!out.path().join("usr/lib/app/stale").exists());
+        assert!(!filesystem.entries.contains_key(Path::new("/usr/lib/app/stale")));
+        let mode = fs::metadata(out.path().join("etc/shadow")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o7777, 0o644);
+        // The absolute symlink resolves inside the image, not to the host's /tmp
+        assert!(out.path().join("tmp/owned").exists());
+
+        let findings = filesystem.findings();
+        let failed = |check: &str| findings.iter().filter(|f| f.check == check && f.is_failure()).count();
+        assert_eq!(failed("image-setuid"), 1);
+        assert_eq!(failed("image-ownership"), 1);
+        assert_eq!(failed("image-setgid"), 0);
+    }
+
+    #[test]
+    fn test_oci_layout_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = dir.path().join("blobs/sha256");
+        fs::create_dir_all(&blobs).unwrap();
+        fs::write(blobs.join("aaa"), layer(&[("bin/ping", 0o2755, 0, "elf")])).unwrap();
+        fs::write(blobs.join("mmm"), r#"{"layers":[{"digest":"sha256:aaa"}]}"#).unwrap();
+        fs::write(
+            blobs.join("iii"),
+            r#"{"manifests":[{"digest":"sha256:zzz","platform":{"os":"windows","architecture":"amd64"}},
+                {"digest":"sha256:mmm","platform":{"os":"linux","architecture":"ARCH"}}]}"#
+                .replace("ARCH", oci_architecture()),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("index.json"),
+            r#"{"manifests":[{"digest":"sha256:iii","annotations":{"org.opencontainers.image.ref.name":"1.0"}}]}"#,
+        )
+        .unwrap();
+
+        let image = ContainerImage::open(dir.path()).unwrap();
+        assert_eq!(image.layers, vec!["blobs/sha256/aaa"]);
+        assert_eq!(image.reference.as_deref(), Some("1.0"));
+        let out = tempfile::tempdir().unwrap();
+        let findings = image.unpack(out.path()).unwrap().findings();
+        assert!(findings.iter().any(|f| f.check == "image-setgid" && f.path == Path::new("/bin/ping")));
+        assert!(ContainerImage::open(out.path()).is_err());
+
+        // A digest naming a path outside blobs/ is rejected, not read
+        fs::write(dir.path().join("index.json"), r#"{"manifests":[{"digest":"sha256:../../../etc/passwd"}]}"#).unwrap();
+        let error = ContainerImage::open(dir.path()).unwrap_err();
+        assert!(error.to_string().contains("invalid digest"), "{}", error);
+        for digest in ["sha256:", ":abc", "SHA256:abc", "sha256/x:abc", "sha256:ab/c", "sha256"] {
+            assert!(blob(&Value::from(digest)).is_err(), "{}", digest);
+        }
+        assert_eq!(blob(&Value::from("sha512:0af9")).unwrap(), "blobs/sha512/0af9");
+    }
+}
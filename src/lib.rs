@@ -1,4 +1,5 @@
 #![doc(html_root_url = "https://docs.rs/alhalo")]
+#![allow(clippy::needless_doctest_main)]
 //! # Aletha Labs: HALO — Host Armor for Linux Operations
 //!
 //! **Audit, parse, and render Linux system configuration and permissions.**
@@ -11,11 +12,84 @@
 //! ## Features
 //! - Audit system, user, network, and log files for best-practice permissions
 //! - Configurable audit rules via TOML
+//! - Configurable severity policy: which permission bit classes map to which severities, per importance
 //! - Built-in and custom audit targets
-//! - Output in pretty text, JSON, or CSV
+//! - Output in pretty text, JSON, CSV, Markdown or a standalone HTML report
+//! - Streaming JSON Lines output (`--format jsonl`): one result per line as the audit runs, for `jq` or log shippers
+//! - Colored pretty output grouped by target, with aligned severity columns (`--no-color` or `NO_COLOR` to disable)
+//! - User-defined Handlebars output templates (`--template`), e.g. monitoring plugin output or wiki markup
+//! - Deterministic ordering and grouping in every format (`--sort severity|path|status`, `--group-by target|severity`)
+//! - Failures-only and summary-only output (`--only-failures`, `--summary-only`) for runs with many passing files
+//! - Self-describing reports: JSON, text, Markdown, HTML and pretty output carry the host, kernel, HALO version, time, targets, duration and summary counts of the run
+//! - Versioned JSON report format: a `schema_version` in every report and the matching JSON Schema from `halo schema`
+//! - Shell completion scripts for bash, zsh, fish and PowerShell (`halo completions --shell zsh`)
+//! - Diagnostics through `tracing`: skipped paths, unreadable directories and per-target timing on stderr with `-v`/`-vv`, errors only with `-q`
+//! - Exit status 1 when `check` reports failures, with a `--fail-on critical|high|...|never` threshold, for CI and cron gating
+//! - Report diffs: new failures, resolved failures and changed modes between two stored reports (`halo diff old.json new.json`)
+//! - Non-interactive remediation: `halo fix --from report.json [--dry-run] [--yes]` plans the `chmod` and `chown` fixes for a stored report, prints them and applies them in-process when confirmed, with a result per fix (`remediate::Remediator`), and records an undo journal for `halo fix --rollback`; or exports the plan as a shell script or Ansible playbook (`--format ansible`)
+//! - Watch mode: re-run audits on a schedule and print only new, resolved or changed failures (`halo watch --interval 10m --target all`)
+//! - Daemon mode: scheduled audits from a config of cron-style jobs, recorded and logged to syslog and webhooks (`halo daemon --config daemon.toml`)
+//! - Real-time monitoring: re-audit rule paths on `chmod`/`chown` events via inotify (`halo monitor --target user`)
+//! - Run history in SQLite: `check --history`, then `halo history list|show <id>|path <path>` for questions across runs
+//! - Trends: failure counts, new/resolved failures and risk score over the recorded runs (`halo trend --since 30d --format html`)
+//! - Signed reports: ed25519 detached signatures or JWS for stored reports (`halo keys generate`, `check --store r.json --sign`, `halo verify r.json`)
+//! - Fleet reports: audit the hosts of a TOML/YAML inventory over SSH or from collected reports, ranked worst first (`halo fleet -i hosts.toml`)
+//! - Offline images: audit a mounted container image, chroot or rescued disk with `--root /mnt/image`; symlinks resolve inside it
+//! - Container images: `halo image audit ./image.tar` unpacks a `docker save` or OCI image and audits its files, setuid programs and ownership
+//! - Parallel walks: recursive permission checks walk directories on all CPUs (`check --jobs N`), in the same output order as a single thread
+//! - Streaming walks: `PermissionRules::check_iter` yields results as they are found, in bounded memory
+//! - Metadata cache: permission and ownership rules share one `stat` per path within a run
+//! - Mount boundaries: recursive walks skip pseudo and network filesystems, `--one-file-system` stays on one
+//! - Safety caps: `--max-files`, `--max-results` and `--max-runtime` stop a runaway walk, reported as truncated
+//! - Timing: wall time, files scanned and files per second of each target in the report summary
+//! - Async API: `async_audit::AsyncAuditor` awaits audits and fleet collection from tokio code (feature `async`)
+//! - Errors: [`HaloError`] tells missing and unreadable files, parse failures and invalid rules apart
+//! - Rule trait: [`AuditRule`] evaluates permission, ownership, symlink and custom rules to findings
+//! - Plugins: register named sets of custom rules, or install `halo-plugin-<name>` executables, and run them with `check --target <name>`
+//! - Observers: progress and result callbacks of a running audit (`observer::AuditObserver`)
+//! - Cancellation: stop a running audit from another thread and keep its partial results (`audit::scan::CancelToken`)
+//! - Feature flags: `cli` (default) builds the `alhalo` binary and derives `clap::ValueEnum` for the public enums; disable default features for a library without clap
+//! - C and Python: `halo_audit_path` and `halo_audit_profile` return JSON reports over a C ABI (feature `ffi`), and as `dict`s from the `pyhalo` Python module (feature `python`)
+//! - Error codes: every [`HaloError`] has a stable [`ErrorCode`] such as `E_PATH_NOT_FOUND`, shown by `check` errors and mapped to exit statuses
+//! - File parsers: `key: value` records, `KEY=value` files such as os-release and sysctl.conf (`parse --parser keyvalue`, `parsers::FileParser`), and fstab and mountinfo mount tables (`--parser fstab|mountinfo`, `audit::mounts::parse_fstab`)
+//! - Regex line filters: `parse --line-regex '^model name'` keeps pairs whose key, value or either matches (`--match-on`), in every format (`filter_lines`, `LineRegex`)
+//! - Webhook delivery: POST the JSON report to Slack/Teams relays or SOAR intakes with custom headers and retries (`--post-url`)
 //! - CLI and library APIs
 //! - Ownership audit for files and directories
 //! - Symlink audit: check symlink existence and target
+//! - Account database consistency: extra UID 0 accounts, system accounts with login shells, missing groups, orphaned file owners
+//! - Account audit: password aging in `/etc/login.defs`, empty and locked passwords in `/etc/shadow`
+//! - Sudoers policy audit: flag `NOPASSWD: ALL`, `!authenticate`, wildcard commands and world-writable includes
+//! - polkit and D-Bus policy audit: admin rights for non-admin groups, unconditional grants, services open to every user
+//! - Sysctl kernel parameter audit against a hardening profile, with TOML overrides
+//! - Content rules in the config: regexes a file must or must not match (`[[content_rules]]`)
+//! - Named profiles in the config (`[profiles.webserver]`) selecting rules by id or path, run with `check --config fleet.toml --profile webserver`
+//! - Absence rules in the config: paths such as DSA host keys or `.rhosts` files that must not exist (`[[absent_rules]]`)
+//! - Udev rules audit: permissive device modes, group overrides and `RUN+=` programs in user-writable locations
+//! - auditd audit: daemon installed and running, configuration permissions, write watches on identity and sudoers files
+//! - CIS Distribution Independent Linux Level 1/Level 2 profiles (`check --profile cis-l1`), each finding tagged with its benchmark control id
+//! - Kubernetes node preset (`check --target k8s-node`): kubeconfig files, manifests, PKI, kubelet config and etcd data per the CIS Kubernetes Benchmark
+//! - Process environment audit: secret-looking variables (`*_PASSWORD`, `AWS_SECRET_ACCESS_KEY`, ...) in running services, names only
+//! - Firewall audit: active ufw/nftables/iptables ruleset, default-deny inbound and SSH rate limiting, configurable with `[firewall]` in TOML
+//! - TLS audit: certificates expiring soon (severity by days left) and private keys that are not 600/root
+//! - Log rotation audit: logrotate configured and covering key logs, oversized logs that suggest broken rotation
+//! - Hardening audit: `kernel.core_pattern`, `fs.suid_dumpable`, core limits in `limits.conf` and `hidepid` on `/proc`
+//! - Listening socket discovery from `/proc/net` with owning process and user, checked against an allowlist of ports (`net --sockets`)
+//! - Docker host audit: socket ownership and mode, `daemon.json` hardening, `docker` group members, privileged and host-namespace containers
+//! - Config hot-reload with validation and rollback to the last good config for long-running modes
+//! - Waivers for accepted risks in TOML (`[[waivers]]` with path, rule, reason and expiry), listed in a separate waived section until they expire
+//! - Acknowledgment of accepted findings (`halo ack`), valid until the finding's state changes
+//! - Severity threshold for CI gating: `check --min-severity high` filters output and sets the exit status
+//! - Host risk score: open failures weighted by severity and importance, with per-target subtotals in the `check` summary
+//! - Severity heatmap: results aggregated per directory with failure counts and worst severity (`check --heatmap`)
+//! - Team ownership map: split or filter reports by the team owning each path (`check --owners teams.toml --owner web-team`)
+//! - Rate-limited (`check --io-rate`) and resumable (`check --checkpoint`) scans for large trees
+//! - Recorded run history with retention limits (`halo prune`)
+//! - Policy snapshots: current modes and owners emitted as a TOML config to audit against later (`halo snapshot --emit-config`)
+//! - Raspberry Pi / embedded preset and a low-footprint mode for small devices
+//! - NixOS and ostree awareness: no `chmod` suggestions for read-only store paths
+//! - Builder API combining targets, rules and config files into one report
+//! - Capability listing: targets, profiles, formats and check ids with descriptions (`halo list checks`)
 //! - Easy integration into scripts and automation
 //! - Open source under the MIT License
 //! - Actively maintained by Aletha Labs
@@ -52,6 +126,20 @@
 //! }
 //! ```
 //! 
+//! ### Composing a Complete Audit
+//! The [`Audit`] builder runs targets, rules and TOML config files together and returns one
+//! [`AuditReport`]:
+//! ```rust
+//! use alhalo::prelude::*;
+//!
+//! let report = Audit::new()
+//!     .target(Target::Sys)
+//!     .min_severity(Severity::High)
+//!     .run()
+//!     .expect("audit failed");
+//! println!("{} of {} results failed", report.failure_count(), report.len());
+//! ```
+//!
 //! ### Alternative Usage (Explicit Imports)
 //! ```rust
 //! use alhalo::{PermissionRules, Importance, PermissionResults, check_symlink, SymRule};
@@ -65,6 +153,7 @@
 //!     let sym_rule = SymRule {
 //!         path: "/etc/ssl/certs/ca-certificates.crt".into(),
 //!         target_link: None, // Optionally set expected target
+//!         severity_on_mismatch: None, // Or override the per-issue default severity
 //!     };
 //!     let sym_result = check_symlink(&sym_rule);
 //!     println!("Symlink target: {:?}, Pass: {}", sym_result.target, sym_result.pass);
@@ -106,6 +195,8 @@
 //! Summary: 29 checked, 27 passed, 0 strict, 2 failed
 //! [!] FAIL: /etc/shadow (found: 640, expected: 600)
 //!     Suggested fix: chmod 600 /etc/shadow
+//!
+//! To apply the suggested fixes, store the report with --format json --store report.json, then: halo fix --from report.json
 //! .....
 //! ```
 //! Or run a single command directly:
@@ -126,28 +217,105 @@
 //! 
 //! - **Quick Start**: Use [`prelude`] for easy imports
 //! - **Core Types**: [`PermissionRules`], [`PermissionResults`], [`Importance`] 
+//! - **Builder**: [`Audit`] composes targets, rules and config files into an [`AuditReport`]
 //! - **Rendering**: [`Renderable`] trait for output formatting
 //! - **Advanced**: Full API available through submodules ([`audit`], [`render_output`])
 
+#[cfg(feature = "async")]
+pub mod async_audit;
 pub mod audit;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod ack;
+pub mod catalog;
+pub mod daemon;
+pub mod diff;
+pub mod fleet;
+pub mod heatmap;
+#[cfg(feature = "history")]
+pub mod history;
+#[cfg(feature = "image")]
+pub mod image;
 pub mod macros;
+pub mod observer;
+pub mod parsers;
+pub mod plugin;
 pub mod render_output;
 pub mod prelude;
+pub mod reload;
+pub mod remediate;
+pub mod report;
+pub mod risk;
+pub mod schedule;
+pub mod schema;
+#[cfg(feature = "signing")]
+pub mod signing;
+pub mod snapshot;
+pub mod state;
+pub mod stream;
+pub mod teams;
+#[cfg(feature = "template")]
+pub mod template;
+#[cfg(feature = "history")]
+pub mod trend;
+pub mod waiver;
+pub mod watch;
+#[cfg(feature = "webhook")]
+pub mod webhook;
 
 #[doc(hidden)]
 pub use audit::{
     permissions::{
         audit_permissions::{
             AuditPermissions, Severity, PathStatus, Status, AuditError,
-            parse_mode, perm_to_datalist, PermissionResults, PermissionRules, Importance,
+            parse_mode, perm_to_datalist, PermissionResults, PermissionRules, PermissionWalk, Importance,
         },
-        default_permissions::{Log, NetConf, SysConfig, UserConfig},
+        severity_policy::{BitClass, SeverityOverride, SeverityPolicy},
+        default_permissions::{AuditdConfig, EmbeddedConfig, K8sNodeConfig, Log, NetConf, SysConfig, UserConfig},
     },
+    absence::{AbsenceAudit, AbsenceRule},
+    accounts::AccountsAudit,
+    auditd::{AuditdAudit, check_watch_rules, watched_paths},
+    builder::{Audit, Target},
+    cis::{CisAudit, CisCheck, CisControl, CisLevel, check_file},
+    content::{ContentAudit, ContentRule},
+    docker::{DockerAudit, check_container, check_daemon_json, check_group_members, check_socket, docker_group_members},
+    embedded::EmbeddedAudit,
+    environ::EnvironAudit,
+    finding::{AuditFindings, Finding},
+    firewall::{FirewallAudit, FirewallBackend, FirewallPolicy, FirewallRuleset, check_ruleset},
+    footprint::Footprint,
+    scan::ScanControl,
+    hardening::{HardeningAudit, check_core_limit, check_core_pattern, check_proc_hidepid, check_suid_dumpable},
+    immutable::{ImmutableAudit, ImmutableDistro, ImmutableKind},
     ownership::ownership::{OwnershipResult, OwnershipRule, ownership_to_datalist},
-    symlink::{SymResult, SymRule, check_symlink},
-    toml_config::{AuditConfig, OwnerConfig, PermissionConfig, toml_ownership, toml_permissions},
+    logrotate::{LogrotateAudit, check_coverage, rotated_patterns},
+    passwd::{PasswdAudit, PasswdEntry, parse_passwd},
+    polkit::{PolkitAudit, check_dbus_policy, check_polkit_rules},
+    rule::AuditRule,
+    sudoers::{SudoersAudit, check_sudoers_content},
+    symlink::{SymResult, SymRule, SymlinkIssue, check_symlink},
+    sysctl::{SysctlAudit, SysctlRule, check_sysctl_value, hardening_profile},
+    tls::{CertificateInfo, TlsAudit, check_certificate, check_key, parse_certificate},
+    toml_config::{
+        AuditConfig, ConfigFormat, OwnerConfig, PermissionConfig, ProfileConfig, load_config, toml_ownership,
+        toml_permissions, toml_sysctl,
+    },
+    udev::{UdevAudit, check_udev_rules},
     networking::discovery,
 };
 
 #[doc(hidden)]
-pub use render_output::{Renderable, OutputFormat, DataList, DataMap, filter, render_csv, render_json, render_text, ParsedData};
+pub use error::{ErrorCode, HaloError};
+
+#[doc(hidden)]
+pub use report::{AuditReport, ReportSummary, RunMetadata, SeverityCount, StatusCounts, TargetResults};
+
+#[doc(hidden)]
+pub use risk::{RiskScore, RiskSubtotal};
+
+#[doc(hidden)]
+pub use render_output::{Renderable, OutputFormat, DataList, DataMap, filter, filter_lines, LineRegex, MatchOn, render_csv, render_json, render_markdown, render_text, render_html, render_jsonl, render_rows, render_csv_with, write_csv, ParsedData, Style, paint, set_color, RenderOptions, SortKey, GroupBy, CsvOptions};
@@ -0,0 +1,41 @@
+//! Diagnostic logging for the CLI.
+//!
+//! Audit results go to stdout; diagnostics from `tracing` events in the library and handlers
+//! (skipped paths, unreadable directories, timing) go to stderr. The verbosity flags pick how
+//! much of that is shown and can change between commands of the interactive loop.
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tracing::Level;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::prelude::*;
+
+// Most detailed level shown: 0 error, 1 warn, 2 info, 3 debug, 4 trace
+static VERBOSITY: AtomicU8 = AtomicU8::new(1);
+
+// Installs the stderr logger; call once at startup
+pub fn init() {
+    let ansi = std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_ansi(ansi)
+        .with_target(false)
+        .without_time()
+        .with_filter(filter_fn(|meta| level_rank(meta.level()) <= VERBOSITY.load(Ordering::Relaxed)));
+    tracing_subscriber::registry().with(layer).init();
+}
+
+// Shows warnings by default, one more level per `-v`, and only errors with `-q`
+pub fn set_verbosity(verbose: u8, quiet: bool) {
+    let level = if quiet { 0 } else { 1 + verbose.min(3) };
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+fn level_rank(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 0,
+        Level::WARN => 1,
+        Level::INFO => 2,
+        Level::DEBUG => 3,
+        Level::TRACE => 4,
+    }
+}
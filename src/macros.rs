@@ -65,15 +65,20 @@ macro_rules! render {
 ///     MyConfig,
 ///     self,
 ///     [
-///         {path: &self.file, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
+///         {id: "HALO-MY-0001", path: &self.file, expected_mode: 0o644, importance: Importance::Medium, recursive: false},
+///         // Directories 755, files 644
+///         {id: "HALO-MY-0002", path: &self.dir, expected_mode: 0o644, importance: Importance::Medium, recursive: true, expected_dir_mode: 0o755},
 ///         // ...
 ///     ]
 /// }
 /// ```
+///
+/// Rule ids are stable: never renumber or reuse them, append new rules with the next number.
+/// `expected_dir_mode` is optional.
 #[macro_export]
 macro_rules! impl_audit {
     ($struct_name:ident, $s:ident, [
-        $( { path: $path:expr, expected_mode: $expected_mode:expr, importance: $importance:expr, recursive: $recursive:expr } ),*
+        $( { id: $id:expr, path: $path:expr, expected_mode: $expected_mode:expr, importance: $importance:expr, recursive: $recursive:expr $(, expected_dir_mode: $dir_mode:expr)? } ),*
     ]) => {
         impl AuditPermissions for $struct_name {
             fn rules(&$s) -> Vec<PermissionRules> {
@@ -84,6 +89,10 @@ macro_rules! impl_audit {
                             expected_mode: $expected_mode,
                             importance: $importance,
                             recursive: $recursive,
+                            id: Some($id.to_string()),
+                            expected_dir_mode: None $(.or(Some($dir_mode)))?,
+                            mounts: None,
+                            symlink_severity: None,
                         },
                     )*
                 ]
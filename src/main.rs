@@ -1,16 +1,24 @@
+mod check_output;
 mod cli;
 mod fix_script;
 mod types;
 mod handlers;
+mod logging;
 use crate::cli::{Cli, cli, run_command};
 use clap::Parser;
 
 fn main() {
+    logging::init();
     let args: Vec<String> = std::env::args().collect();
     if args.len() > 1 {
         // Run command directly, then exit
         let cli_args = Cli::parse();
-        run_command(&cli_args.command);
+        if cli_args.no_color {
+            alhalo::set_color(false);
+        }
+        logging::set_verbosity(cli_args.verbose, cli_args.quiet);
+        alhalo::audit::sysroot::set_root(cli_args.root.clone());
+        std::process::exit(run_command(&cli_args.command));
     } else {
         println!(
             "Welcome to Aletha Labs: HALO - Host Armor for Linux Operations\n\n Please enter your commands, or type 'help' for further information"
@@ -0,0 +1,182 @@
+//! Progress and result events of a running audit.
+//!
+//! A [`ResultStream`](crate::stream::ResultStream) writes results as JSON Lines. Progress
+//! bars, live UIs and exporters to other formats need the events themselves: an
+//! [`AuditObserver`] added with [`Audit::observer`](crate::Audit::observer) is told when a
+//! permission walk starts at a path, when each result is produced and when each target
+//! completes, with its timing. The audit itself does not change.
+//!
+//! Events are delivered on the thread running the audit, in the order of the report. A
+//! parallel walk reports the results of each rule once the rule's walk is done. Results are
+//! observed as produced, before waivers, acknowledgments, the severity threshold and
+//! low-footprint mode are applied; the report holds the filtered results.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::observer::{AuditObserver, ResultRef};
+//! use alhalo::report::TargetTiming;
+//! use alhalo::{Audit, Target};
+//! use std::sync::Arc;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! #[derive(Default)]
+//! struct Progress {
+//!     results: AtomicUsize,
+//! }
+//!
+//! impl AuditObserver for Progress {
+//!     fn on_finding(&self, _result: ResultRef<'_>) {
+//!         self.results.fetch_add(1, Ordering::Relaxed);
+//!     }
+//!
+//!     fn on_target_complete(&self, timing: &TargetTiming) {
+//!         eprintln!("{} done: {} results", timing.target, timing.results);
+//!     }
+//! }
+//!
+//! let progress = Arc::new(Progress::default());
+//! let report = Audit::new().target(Target::User).observer(progress.clone()).run().unwrap();
+//! assert_eq!(progress.results.load(Ordering::Relaxed), report.len());
+//! ```
+use crate::ack::{Acknowledgeable, Acknowledgment};
+use crate::audit::finding::Finding;
+use crate::audit::ownership::ownership::OwnershipResult;
+use crate::audit::permissions::audit_permissions::{PermissionResults, Severity};
+use crate::report::TargetTiming;
+use std::fmt;
+use std::path::Path;
+
+/// A result produced by an audit.
+#[derive(Debug, Clone, Copy)]
+pub enum ResultRef<'a> {
+    Permission(&'a PermissionResults),
+    Ownership(&'a OwnershipResult),
+    Finding(&'a Finding),
+}
+
+impl<'a> ResultRef<'a> {
+    /// Path the result is about.
+    pub fn path(&self) -> &'a Path {
+        match *self {
+            ResultRef::Permission(result) => &result.path,
+            ResultRef::Ownership(result) => &result.path,
+            ResultRef::Finding(finding) => &finding.path,
+        }
+    }
+
+    /// Severity of the result; `None` for passing results.
+    pub fn severity(&self) -> &'a Severity {
+        match *self {
+            ResultRef::Permission(result) => &result.severity,
+            ResultRef::Ownership(result) => &result.severity,
+            ResultRef::Finding(finding) => &finding.severity,
+        }
+    }
+
+    /// Stable id of the result, as passed to `halo ack`.
+    pub fn id(&self) -> String {
+        match *self {
+            ResultRef::Permission(result) => result.ack_id(),
+            ResultRef::Ownership(result) => result.ack_id(),
+            ResultRef::Finding(finding) => finding.ack_id(),
+        }
+    }
+
+    /// Acknowledges the result in its current state.
+    pub fn acknowledge(&self, comment: &str) -> Acknowledgment {
+        match *self {
+            ResultRef::Permission(result) => Acknowledgment::new(result, comment),
+            ResultRef::Ownership(result) => Acknowledgment::new(result, comment),
+            ResultRef::Finding(finding) => Acknowledgment::new(finding, comment),
+        }
+    }
+}
+
+/// Receives the events of an audit run; every method does nothing by default.
+///
+/// Observers are shared with the audit, so methods take `&self`; keep counters in atomics
+/// or a `Mutex`.
+pub trait AuditObserver: Send + Sync {
+    /// A permission rule starts walking `path`.
+    fn on_path_start(&self, _path: &Path) {}
+
+    /// A result was produced.
+    fn on_finding(&self, _result: ResultRef<'_>) {}
+
+    /// A target, plugin, benchmark profile, or the rules or configs of the audit completed.
+    fn on_target_complete(&self, _timing: &TargetTiming) {}
+}
+
+impl fmt::Debug for dyn AuditObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuditObserver")
+    }
+}
+
+impl<O: AuditObserver + ?Sized> AuditObserver for std::sync::Arc<O> {
+    fn on_path_start(&self, path: &Path) {
+        (**self).on_path_start(path)
+    }
+
+    fn on_finding(&self, result: ResultRef<'_>) {
+        (**self).on_finding(result)
+    }
+
+    fn on_target_complete(&self, timing: &TargetTiming) {
+        (**self).on_target_complete(timing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Audit, Importance, OwnershipRule, PermissionRules};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl AuditObserver for Recorder {
+        fn on_path_start(&self, path: &Path) {
+            self.events.lock().unwrap().push(format!("start {}", path.display()));
+        }
+
+        fn on_finding(&self, result: ResultRef<'_>) {
+            let name = result.path().file_name().unwrap().to_string_lossy().into_owned();
+            self.events.lock().unwrap().push(name);
+        }
+
+        fn on_target_complete(&self, timing: &TargetTiming) {
+            self.events.lock().unwrap().push(format!("done {} {}", timing.target, timing.results));
+        }
+    }
+
+    #[test]
+    fn test_events_in_report_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let tree: PathBuf = dir.path().join("tree");
+        fs::create_dir(&tree).unwrap();
+        fs::write(tree.join("a"), "").unwrap();
+        fs::write(dir.path().join("owned"), "").unwrap();
+        let (rule, _) = PermissionRules::new(tree.clone(), 0o777, Importance::Low);
+        let (owner, _) = OwnershipRule::new(dir.path().join("owned"), 0, 0, true);
+
+        let recorder = Arc::new(Recorder::default());
+        let report = Audit::new()
+            .rule(rule)
+            .ownership_rule(owner)
+            .observer(recorder.clone())
+            .run()
+            .unwrap();
+        let events = recorder.events.lock().unwrap();
+        assert_eq!(
+            *events,
+            [format!("start {}", tree.display()), "a".into(), "owned".into(), "done rules 2".into()]
+        );
+        assert_eq!(report.len(), 2);
+    }
+}
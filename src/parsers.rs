@@ -0,0 +1,173 @@
+//! Parsers of plain-text system files for `halo parse`.
+//!
+//! [`FileParser::Colon`] reads `key: value` records separated by blank lines, as in
+//! `/proc/cpuinfo` and `/proc/meminfo`. [`FileParser::KeyValue`] reads `KEY=value` files:
+//! `/etc/os-release`, `/etc/default/*` and `sysctl.conf`, with quotes stripped and `#` and `;`
+//! comments skipped. [`FileParser::Fstab`] and [`FileParser::Mountinfo`] read `/etc/fstab`,
+//! `/proc/mounts` and `/proc/self/mountinfo` with [`parse_fstab`] and [`parse_mountinfo`],
+//! one record per mount with the options comma-separated. All return a [`DataList`] ready for
+//! any output format.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::parsers::{FileParser, parse_key_values};
+//! let os_release = "NAME=\"Fedora Linux\"\n# comment\nID=fedora\n";
+//! assert_eq!(parse_key_values(os_release)["NAME"], "Fedora Linux");
+//!
+//! let sysctl = "; hardening\nnet.ipv4.ip_forward = 0\n";
+//! let data = FileParser::KeyValue.parse(sysctl);
+//! assert_eq!(data[0]["net.ipv4.ip_forward"], "0");
+//! ```
+use crate::audit::mounts::{MountEntry, parse_fstab, parse_mountinfo};
+use crate::render_output::{DataList, DataMap};
+
+/// How `halo parse` splits a file into records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum FileParser {
+    /// `key: value` lines, records separated by blank lines (`/proc/cpuinfo`)
+    #[default]
+    Colon,
+    /// `KEY=value` lines with quotes stripped and comments skipped (`/etc/os-release`,
+    /// `sysctl.conf`)
+    #[cfg_attr(feature = "cli", value(name = "keyvalue"))]
+    KeyValue,
+    /// Mount tables in fstab format (`/etc/fstab`, `/proc/mounts`)
+    Fstab,
+    /// The kernel's `/proc/self/mountinfo`
+    Mountinfo,
+}
+
+impl FileParser {
+    /// Parses `content`; a key=value file is one record.
+    pub fn parse(&self, content: &str) -> DataList {
+        match self {
+            FileParser::Colon => parse_colon(content),
+            FileParser::KeyValue => {
+                let map = parse_key_values(content);
+                if map.is_empty() { Vec::new() } else { vec![map] }
+            }
+            FileParser::Fstab => parse_fstab(content).iter().map(mount_record).collect(),
+            FileParser::Mountinfo => parse_mountinfo(content).iter().map(mount_record).collect(),
+        }
+    }
+}
+
+fn mount_record(entry: &MountEntry) -> DataMap {
+    DataMap::from([
+        ("device".to_string(), entry.device.clone()),
+        ("mountpoint".to_string(), entry.mount_point.display().to_string()),
+        ("fstype".to_string(), entry.fs_type.clone()),
+        ("options".to_string(), entry.options.join(",")),
+    ])
+}
+
+/// Parses `key: value` lines into one record per blank-line separated block.
+pub fn parse_colon(content: &str) -> DataList {
+    let mut data: DataList = Vec::new();
+    let mut current_map = DataMap::new();
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            if !current_map.is_empty() {
+                data.push(std::mem::take(&mut current_map));
+            }
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            current_map.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    if !current_map.is_empty() {
+        data.push(current_map);
+    }
+    data
+}
+
+/// Parses `KEY=value` lines in file order; a key set twice keeps its last value, as the shell
+/// and `sysctl --system` do.
+///
+/// Blank lines and lines starting with `#` or `;` are skipped, as are lines without `=`.
+/// Whitespace around keys and values is trimmed, a leading `export ` or, for sysctl, `-` is
+/// dropped from the key, and a value in matching single or double quotes is unquoted.
+pub fn parse_key_values(content: &str) -> DataMap {
+    let mut map = DataMap::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let key = key.strip_prefix("export ").map_or(key, str::trim_start);
+        let key = key.strip_prefix('-').unwrap_or(key);
+        if key.is_empty() {
+            continue;
+        }
+        map.insert(key.to_string(), unquote(value.trim()).to_string());
+    }
+    map
+}
+
+// The value inside matching outer quotes, or `value` itself
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_values() {
+        let content = "# /etc/default/grub\n\
+            GRUB_TIMEOUT=5\n\
+            GRUB_CMDLINE_LINUX=\"quiet splash\"\n\
+            export LANG='C.UTF-8'\n\
+            ; sysctl comment\n\
+            -net.ipv4.ip_forward = 1\n\
+            net.ipv4.ip_forward = 0\n\
+            EMPTY=\n\
+            not a pair\n\
+            QUOTE=\"unbalanced\n";
+        let map = parse_key_values(content);
+        let pairs: Vec<(&str, &str)> = map.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(
+            pairs,
+            [
+                ("GRUB_TIMEOUT", "5"),
+                ("GRUB_CMDLINE_LINUX", "quiet splash"),
+                ("LANG", "C.UTF-8"),
+                ("net.ipv4.ip_forward", "0"),
+                ("EMPTY", ""),
+                ("QUOTE", "\"unbalanced"),
+            ]
+        );
+        assert!(FileParser::KeyValue.parse("# only comments\n").is_empty());
+    }
+
+    #[test]
+    fn test_colon_records() {
+        let data = FileParser::Colon.parse("processor : 0\nmodel name : x\n\nprocessor : 1\n");
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0]["model name"], "x");
+        assert_eq!(data[1]["processor"], "1");
+    }
+
+    #[test]
+    fn test_mount_records() {
+        let data = FileParser::Fstab.parse("# <fs> <mount> <type>\nUUID=ab12 / ext4 errors=remount-ro 0 1\n");
+        let record: Vec<(&str, &str)> = data[0].iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(
+            record,
+            [("device", "UUID=ab12"), ("mountpoint", "/"), ("fstype", "ext4"), ("options", "errors=remount-ro")]
+        );
+        let data = FileParser::Mountinfo.parse("22 1 0:21 / /proc rw,nosuid shared:12 - proc proc rw,hidepid=2\n");
+        assert_eq!(data[0]["options"], "rw,nosuid,hidepid=2");
+    }
+}
@@ -0,0 +1,360 @@
+//! Third-party audit modules.
+//!
+//! Organisation-specific checks do not have to live in HALO. A [`Plugin`] is a named set of
+//! [`AuditRule`]s; once [registered](register) it runs like a built-in target with
+//! [`Audit::plugin`](crate::Audit::plugin), and its name is listed by `halo list targets`
+//! and accepted by `halo check --target`.
+//!
+//! The `halo` binary cannot link code it was not built with, so it also discovers plugins
+//! as executables named `halo-plugin-<name>` on `PATH`, like git and cargo subcommands. Such
+//! an executable prints the JSON array of its [`Finding`]s on stdout; a downstream crate
+//! builds one from its plugin with [`Plugin::serve`]. Audits of an alternate root pass it
+//! in the `HALO_ROOT` environment variable, which `serve` applies.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::audit::context::AuditContext;
+//! use alhalo::plugin::{self, Plugin};
+//! use alhalo::{Audit, AuditRule, Finding};
+//!
+//! struct BannerRule;
+//! impl AuditRule for BannerRule {
+//!     fn evaluate(&self, _ctx: &AuditContext) -> Vec<Finding> {
+//!         vec![Finding::pass("acme-banner", "/etc/issue.net", "Login banner present")]
+//!     }
+//! }
+//!
+//! plugin::register(Plugin::new("acme", "ACME corporate baseline").rule(BannerRule)).unwrap();
+//! let report = Audit::new().plugin("acme").run().unwrap();
+//! assert_eq!(report.findings[0].check, "acme-banner");
+//! ```
+//!
+//! A plugin executable, e.g. `src/bin/halo-plugin-acme.rs` of the downstream crate:
+//! ```rust,no_run
+//! # use alhalo::plugin::Plugin;
+//! fn main() {
+//!     let plugin = Plugin::new("acme", "ACME corporate baseline");
+//!     std::process::exit(plugin.serve());
+//! }
+//! ```
+use crate::audit::builder::Target;
+use crate::audit::context::AuditContext;
+use crate::audit::finding::Finding;
+use crate::audit::permissions::audit_permissions::Severity;
+use crate::audit::rule::AuditRule;
+use crate::audit::sysroot;
+use crate::error::HaloError;
+use std::env;
+use std::ffi::OsStr;
+use std::io::{self, Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Prefix of the executables discovered as plugins.
+pub const EXECUTABLE_PREFIX: &str = "halo-plugin-";
+
+/// Time a plugin executable may run before it is killed, see [`ExternalRule::with_timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+// Interval at which a running plugin executable is checked for exit
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+static REGISTRY: RwLock<Vec<Arc<Plugin>>> = RwLock::new(Vec::new());
+
+/// A named set of rules run as one target.
+#[derive(Debug)]
+pub struct Plugin {
+    name: String,
+    description: String,
+    rules: Vec<Box<dyn AuditRule>>,
+}
+
+impl Plugin {
+    /// An empty plugin; `name` is what `check --target` accepts.
+    pub fn new(name: &str, description: &str) -> Self {
+        Plugin {
+            name: name.to_string(),
+            description: description.to_string(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Add a rule.
+    pub fn rule(mut self, rule: impl AuditRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// The plugin's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The one-line description listed by `halo list targets`.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Entry point of a `halo-plugin-<name>` executable: evaluates the rules, below
+    /// `HALO_ROOT` if set, and prints the findings as a JSON array.
+    ///
+    /// # Returns
+    /// The process exit code: 0, or 1 if the findings could not be written.
+    pub fn serve(&self) -> i32 {
+        let _root = sysroot::enter(env::var_os("HALO_ROOT").map(PathBuf::from));
+        let findings = self.evaluate(&AuditContext::cached());
+        let written = serde_json::to_writer(io::stdout().lock(), &findings)
+            .map_err(io::Error::from)
+            .and_then(|()| io::stdout().flush());
+        match written {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("Failed to write findings: {}", e);
+                1
+            }
+        }
+    }
+}
+
+impl AuditRule for Plugin {
+    fn evaluate(&self, ctx: &AuditContext) -> Vec<Finding> {
+        self.rules.iter().flat_map(|rule| rule.evaluate(ctx)).collect()
+    }
+}
+
+/// Runs a `halo-plugin-<name>` executable and reads its findings.
+///
+/// A plugin that cannot be run, fails, runs longer than its timeout or prints anything but a
+/// JSON array of findings is reported as one high-severity `plugin-error` finding: the audit
+/// goes on, but its checks did not run, so the report fails.
+#[derive(Debug, Clone)]
+pub struct ExternalRule {
+    program: PathBuf,
+    timeout: Duration,
+}
+
+impl ExternalRule {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        ExternalRule {
+            program: program.into(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Kill the executable if it has not exited after `timeout`; [`DEFAULT_TIMEOUT`] if unset.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn run(&self) -> Result<Vec<Finding>, String> {
+        let mut command = Command::new(&self.program);
+        if let Some(root) = sysroot::root() {
+            command.env("HALO_ROOT", root);
+        }
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        // Drained while waiting, so a plugin printing more than a pipe holds does not block
+        let read = |pipe: Option<Box<dyn Read + Send>>| {
+            thread::spawn(move || {
+                let mut buf = Vec::new();
+                if let Some(mut pipe) = pipe {
+                    let _ = pipe.read_to_end(&mut buf);
+                }
+                buf
+            })
+        };
+        let stdout = read(child.stdout.take().map(|p| Box::new(p) as _));
+        let stderr = read(child.stderr.take().map(|p| Box::new(p) as _));
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                // The readers are left to finish when processes the plugin started exit
+                return Err(format!("timed out after {} s", self.timeout.as_secs_f64()));
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        if !status.success() {
+            let stderr = String::from_utf8_lossy(&stderr);
+            return Err(format!("exited with {}: {}", status, stderr.trim()));
+        }
+        serde_json::from_slice(&stdout).map_err(|e| format!("invalid findings: {}", e))
+    }
+}
+
+impl AuditRule for ExternalRule {
+    fn evaluate(&self, _ctx: &AuditContext) -> Vec<Finding> {
+        self.run().unwrap_or_else(|e| {
+            let message = format!("Plugin {} failed: {}", self.program.display(), e);
+            vec![Finding::fail("plugin-error", &self.program, Severity::High, message)]
+        })
+    }
+}
+
+/// Register `plugin` for this process.
+///
+/// # Returns
+/// * `Ok(())` once the plugin can be run by name.
+/// * `Err(HaloError::InvalidArgument)` if the name is not lowercase letters, digits and
+///   dashes, or is taken by a built-in target or another registered plugin.
+pub fn register(plugin: Plugin) -> Result<(), HaloError> {
+    let name = plugin.name();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        return Err(HaloError::InvalidArgument(format!(
+            "Invalid plugin name '{}': use lowercase letters, digits and dashes",
+            name
+        )));
+    }
+    if Target::from_name(name).is_some() {
+        return Err(HaloError::InvalidArgument(format!("Plugin name '{}' is a built-in target", name)));
+    }
+    let mut registry = REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+    if registry.iter().any(|p| p.name() == name) {
+        return Err(HaloError::InvalidArgument(format!("Plugin '{}' is already registered", name)));
+    }
+    registry.push(Arc::new(plugin));
+    Ok(())
+}
+
+/// The plugin called `name`: a registered one, or else a `halo-plugin-<name>` executable on
+/// `PATH`.
+pub fn find(name: &str) -> Option<Arc<Plugin>> {
+    let registry = REGISTRY.read().unwrap_or_else(|e| e.into_inner());
+    if let Some(plugin) = registry.iter().find(|p| p.name() == name) {
+        return Some(plugin.clone());
+    }
+    drop(registry);
+    discover(&env::var_os("PATH").unwrap_or_default())
+        .into_iter()
+        .find(|p| p.name() == name)
+        .map(Arc::new)
+}
+
+/// Registered plugins in registration order, then the executables on `PATH` not shadowed by
+/// one of them.
+pub fn plugins() -> Vec<Arc<Plugin>> {
+    let mut plugins = REGISTRY.read().unwrap_or_else(|e| e.into_inner()).clone();
+    for plugin in discover(&env::var_os("PATH").unwrap_or_default()) {
+        if !plugins.iter().any(|p| p.name() == plugin.name()) {
+            plugins.push(Arc::new(plugin));
+        }
+    }
+    plugins
+}
+
+// The plugin executables in the directories of `path`; the first of a name wins, as for
+// the shell
+fn discover(path: &OsStr) -> Vec<Plugin> {
+    let mut found: Vec<Plugin> = Vec::new();
+    for dir in env::split_paths(path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut programs: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        programs.sort();
+        for program in programs {
+            let Some(name) = program
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix(EXECUTABLE_PREFIX))
+            else {
+                continue;
+            };
+            if name.is_empty() || !is_executable(&program) || found.iter().any(|p| p.name() == name) {
+                continue;
+            }
+            let description = format!("External plugin {}", program.display());
+            found.push(Plugin::new(name, &description).rule(ExternalRule::new(&program)));
+        }
+    }
+    found
+}
+
+fn is_executable(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    struct Fixed(&'static str);
+
+    impl AuditRule for Fixed {
+        fn evaluate(&self, _ctx: &AuditContext) -> Vec<Finding> {
+            vec![Finding::fail(self.0, "/etc/x", Severity::Low, "bad")]
+        }
+    }
+
+    #[test]
+    fn test_register_and_find() {
+        let plugin = Plugin::new("unit-registry", "Test").rule(Fixed("a")).rule(Fixed("b"));
+        register(plugin).unwrap();
+        let found = find("unit-registry").unwrap();
+        assert_eq!(found.description(), "Test");
+        assert_eq!(found.evaluate(&AuditContext::new()).len(), 2);
+        assert!(plugins().iter().any(|p| p.name() == "unit-registry"));
+
+        assert!(matches!(register(Plugin::new("unit-registry", "")), Err(HaloError::InvalidArgument(_))));
+        assert!(register(Plugin::new("user", "")).is_err());
+        assert!(register(Plugin::new("Bad Name", "")).is_err());
+        assert!(find("unit-missing").is_none());
+    }
+
+    #[test]
+    fn test_external_plugins() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = |name: &str, body: &str| {
+            let path = dir.path().join(name);
+            fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+            path
+        };
+        let finding = serde_json::to_string(&[Finding::pass("ext-check", "/etc/y", "ok")]).unwrap();
+        script("halo-plugin-good", &format!("echo '{}'", finding));
+        script("halo-plugin-broken", "echo oops >&2; exit 3");
+        fs::write(dir.path().join("halo-plugin-plain"), "").unwrap();
+
+        let found = discover(dir.path().as_os_str());
+        let names: Vec<&str> = found.iter().map(|p| p.name()).collect();
+        assert_eq!(names, ["broken", "good"]);
+
+        let context = AuditContext::new();
+        let broken = found[0].evaluate(&context);
+        assert_eq!(broken[0].check, "plugin-error");
+        assert_eq!(broken[0].severity, Severity::High);
+        assert!(broken[0].message.contains("oops"));
+        let good = found[1].evaluate(&context);
+        assert_eq!(good[0].check, "ext-check");
+        assert!(!good[0].is_failure());
+
+        // A hung plugin is killed at its timeout
+        let hung = ExternalRule::new(script("halo-plugin-hung", "exec sleep 30")).with_timeout(Duration::from_millis(200));
+        let started = Instant::now();
+        let failed = hung.evaluate(&context);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(failed[0].is_failure());
+        assert!(failed[0].message.contains("timed out"), "{}", failed[0].message);
+        // Output larger than a pipe buffer is read while the plugin runs
+        let many = vec![Finding::pass("ext-check", "/etc/y", "ok"); 5000];
+        fs::write(dir.path().join("many.json"), serde_json::to_string(&many).unwrap()).unwrap();
+        let large = ExternalRule::new(script("halo-plugin-large", &format!("cat '{}'", dir.path().join("many.json").display())));
+        assert_eq!(large.evaluate(&context).len(), 5000);
+    }
+}
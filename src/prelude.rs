@@ -13,18 +13,47 @@ pub use crate::audit::permissions::audit_permissions::{
     Importance, PathStatus, Status, Severity
 };
 
+// Builder API and unified report
+pub use crate::audit::builder::{Audit, Target};
+pub use crate::report::AuditReport;
+pub use crate::risk::RiskScore;
+
 // Configuration types
 pub use crate::audit::permissions::default_permissions::{
-    UserConfig, SysConfig, NetConf, Log
+    UserConfig, SysConfig, NetConf, Log, EmbeddedConfig, AuditdConfig, K8sNodeConfig
 };
+pub use crate::audit::footprint::Footprint;
+pub use crate::audit::permissions::severity_policy::{BitClass, SeverityPolicy};
 
 // Ownership types
 pub use crate::audit::ownership::ownership::{
     OwnershipResult, OwnershipRule
 };
 
+// Content and policy audit types
+pub use crate::audit::finding::{AuditFindings, Finding};
+pub use crate::audit::rule::AuditRule;
+pub use crate::audit::absence::{AbsenceAudit, AbsenceRule};
+pub use crate::audit::accounts::AccountsAudit;
+pub use crate::audit::auditd::AuditdAudit;
+pub use crate::audit::cis::{CisAudit, CisLevel};
+pub use crate::audit::content::{ContentAudit, ContentRule};
+pub use crate::audit::docker::DockerAudit;
+pub use crate::audit::embedded::EmbeddedAudit;
+pub use crate::audit::environ::EnvironAudit;
+pub use crate::audit::firewall::{FirewallAudit, FirewallPolicy};
+pub use crate::audit::hardening::HardeningAudit;
+pub use crate::audit::immutable::{ImmutableAudit, ImmutableDistro};
+pub use crate::audit::logrotate::LogrotateAudit;
+pub use crate::audit::passwd::PasswdAudit;
+pub use crate::audit::polkit::PolkitAudit;
+pub use crate::audit::sudoers::SudoersAudit;
+pub use crate::audit::sysctl::{SysctlAudit, SysctlRule};
+pub use crate::audit::tls::TlsAudit;
+pub use crate::audit::udev::UdevAudit;
+
 // Symlink types
-pub use crate::audit::symlink::{SymResult, SymRule, check_symlink};
+pub use crate::audit::symlink::{SymResult, SymRule, SymlinkIssue, check_symlink};
 
 // Rendering traits and types
 pub use crate::render_output::{
@@ -33,5 +62,5 @@ pub use crate::render_output::{
 
 // TOML configuration
 pub use crate::audit::toml_config::{
-    AuditConfig, PermissionConfig, OwnerConfig
+    AuditConfig, PermissionConfig, OwnerConfig, ProfileConfig
 };
\ No newline at end of file
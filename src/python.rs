@@ -0,0 +1,72 @@
+//! Python module `pyhalo` (feature `python`).
+//!
+//! Security teams can embed HALO audits in Python tooling instead of running `halo check` in
+//! a subprocess and parsing its output. The functions return the JSON report, as written by
+//! `check --format json`, as a `dict`. An audit that cannot run raises `pyhalo.HaloError`,
+//! whose `code` attribute holds the [`ErrorCode`](crate::ErrorCode), e.g. `"E_PATH_NOT_FOUND"`.
+//! Audits run without holding the GIL, so other Python threads keep running.
+//!
+//! Build and install the module into the active virtualenv with
+//! `maturin develop -m bindings/python/pyproject.toml`.
+//!
+//! # Example Usage
+//! ```python
+//! import pyhalo
+//! report = pyhalo.audit_path("/etc/shadow", "640", "high")
+//! print(report["summary"]["failed"])
+//! try:
+//!     pyhalo.audit_profile("nope")
+//! except pyhalo.HaloError as e:
+//!     print(e.code)  # E_INVALID_ARGUMENT
+//! ```
+use crate::ffi;
+use crate::report::AuditReport;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+pyo3::create_exception!(
+    pyhalo,
+    HaloError,
+    PyException,
+    "An audit that could not run, e.g. an invalid mode or unknown profile; `code` is the stable error code."
+);
+
+/// Audits the permissions of `path`, recursively for a directory, against `expected_mode`
+/// (octal such as `"640"` or symbolic such as `"rw-r-----"`) with `importance` `"high"`,
+/// `"medium"` or `"low"`, and returns the report as a dict.
+#[pyfunction]
+#[pyo3(signature = (path, expected_mode, importance = "medium"))]
+fn audit_path(py: Python<'_>, path: std::path::PathBuf, expected_mode: &str, importance: &str) -> PyResult<Py<PyAny>> {
+    let path = path.to_string_lossy().into_owned();
+    let report = py.detach(|| ffi::audit_path(&path, expected_mode, importance));
+    to_dict(py, report)
+}
+
+/// Runs a target such as `"user"` or a benchmark profile such as `"cis-l1"`, as listed by
+/// `halo list targets` and `halo list profiles`, and returns the report as a dict.
+#[pyfunction]
+fn audit_profile(py: Python<'_>, profile: &str) -> PyResult<Py<PyAny>> {
+    let report = py.detach(|| ffi::audit_profile(profile));
+    to_dict(py, report)
+}
+
+// Converts the report to a dict through its JSON form, or the error to a `HaloError`
+fn to_dict(py: Python<'_>, report: Result<AuditReport, crate::HaloError>) -> PyResult<Py<PyAny>> {
+    let report = report.map_err(|e| {
+        let error = HaloError::new_err(e.to_string());
+        match error.value(py).setattr("code", e.code().as_str()) {
+            Ok(()) => error,
+            Err(e) => e,
+        }
+    })?;
+    let json = serde_json::to_string(&report).map_err(|e| HaloError::new_err(e.to_string()))?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+#[pymodule]
+fn pyhalo(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("HaloError", m.py().get_type::<HaloError>())?;
+    m.add_function(wrap_pyfunction!(audit_path, m)?)?;
+    m.add_function(wrap_pyfunction!(audit_profile, m)?)?;
+    Ok(())
+}
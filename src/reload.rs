@@ -0,0 +1,157 @@
+//! Config hot-reload for long-running HALO processes.
+//!
+//! Daemon and monitor modes keep running while fleet policy changes. A [`ConfigWatcher`]
+//! watches the config and profile files, reloads them when they change, and validates the
+//! result before using it. If the new config fails to load, the last good config stays in
+//! effect and the error is reported, so a typo in a pushed policy file never leaves an agent
+//! without rules.
+//!
+//! Changes are detected by polling file modification times and sizes, which works on every
+//! filesystem and needs no extra dependencies.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::reload::{ConfigWatcher, ReloadEvent};
+//! use alhalo::{Audit, AuditConfig};
+//! let path = std::env::temp_dir().join("halo-reload-doc.toml");
+//! std::fs::write(&path, "").unwrap();
+//! let mut watcher = ConfigWatcher::audit_config(path.clone()).unwrap();
+//! // In the daemon loop:
+//! match watcher.poll() {
+//!     ReloadEvent::Reloaded => println!("Policy reloaded"),
+//!     ReloadEvent::Rejected(e) => eprintln!("Keeping last good policy: {}", e),
+//!     ReloadEvent::Unchanged => {}
+//! }
+//! let report = Audit::new().config(watcher.current().clone()).run().unwrap();
+//! # std::fs::remove_file(path).ok();
+//! ```
+use crate::audit::toml_config::AuditConfig;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+type Loader<T> = Box<dyn Fn(&[PathBuf]) -> Result<T, Box<dyn std::error::Error>> + Send>;
+
+/// Outcome of checking watched files for changes.
+#[derive(Debug, PartialEq)]
+pub enum ReloadEvent {
+    /// No watched file changed
+    Unchanged,
+    /// Files changed and the new config is now current
+    Reloaded,
+    /// Files changed but failed to load; the last good config is still current
+    Rejected(String),
+}
+
+/// Watches config files and keeps the last config that loaded successfully.
+pub struct ConfigWatcher<T> {
+    paths: Vec<PathBuf>,
+    loader: Loader<T>,
+    current: T,
+    /// (modification time, size) of each path when last checked
+    stamps: Vec<Option<(SystemTime, u64)>>,
+}
+
+impl<T> ConfigWatcher<T> {
+    /// Load the config once and start watching its files.
+    ///
+    /// # Arguments
+    /// * `paths` - Files whose changes trigger a reload
+    /// * `loader` - Loads and validates the config from `paths`
+    ///
+    /// # Returns
+    /// * `Ok(ConfigWatcher)` if the initial load succeeds.
+    /// * `Err` from the loader otherwise; there is no last good config to fall back to.
+    pub fn new<F>(paths: Vec<PathBuf>, loader: F) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        F: Fn(&[PathBuf]) -> Result<T, Box<dyn std::error::Error>> + Send + 'static,
+    {
+        let current = loader(&paths)?;
+        let stamps = paths.iter().map(|p| stamp(p)).collect();
+        Ok(Self {
+            paths,
+            loader: Box::new(loader),
+            current,
+            stamps,
+        })
+    }
+
+    /// The last config that loaded successfully.
+    pub fn current(&self) -> &T {
+        &self.current
+    }
+
+    /// Files being watched.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Reload the config if any watched file changed since the last poll.
+    pub fn poll(&mut self) -> ReloadEvent {
+        let stamps: Vec<_> = self.paths.iter().map(|p| stamp(p)).collect();
+        if stamps == self.stamps {
+            return ReloadEvent::Unchanged;
+        }
+        // Remember the new state either way, so a broken file is reported once, not on every poll
+        self.stamps = stamps;
+        match (self.loader)(&self.paths) {
+            Ok(config) => {
+                self.current = config;
+                ReloadEvent::Reloaded
+            }
+            Err(e) => ReloadEvent::Rejected(e.to_string()),
+        }
+    }
+}
+
+impl ConfigWatcher<AuditConfig> {
+    /// Watch a single HALO TOML config file.
+    ///
+    /// The file is parsed and validated with [`AuditConfig::from_file`] on every change.
+    pub fn audit_config(path: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::new(vec![path], |paths| {
+            let path = paths[0]
+                .to_str()
+                .ok_or_else(|| format!("Config path is not valid UTF-8: {}", paths[0].display()))?;
+            Ok(AuditConfig::from_file(path)?)
+        })
+    }
+}
+
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = "[[sysctl_rules]]\nkey = \"kernel.sysrq\"\nexpected = \"0\"\nseverity = \"Low\"\n";
+
+    #[test]
+    fn test_reload_and_rollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("halo.toml");
+        fs::write(&path, "").unwrap();
+        let mut watcher = ConfigWatcher::audit_config(path.clone()).unwrap();
+        assert!(watcher.current().sysctl_rules.is_empty());
+        assert_eq!(watcher.poll(), ReloadEvent::Unchanged);
+
+        fs::write(&path, VALID).unwrap();
+        assert_eq!(watcher.poll(), ReloadEvent::Reloaded);
+        assert_eq!(watcher.current().sysctl_rules.len(), 1);
+
+        // Invalid key: rejected, last good config stays current
+        fs::write(&path, VALID.replace("kernel.sysrq", "../x")).unwrap();
+        assert!(matches!(watcher.poll(), ReloadEvent::Rejected(_)));
+        assert_eq!(watcher.current().sysctl_rules[0].key, "kernel.sysrq");
+        assert_eq!(watcher.poll(), ReloadEvent::Unchanged);
+    }
+
+    #[test]
+    fn test_initial_load_must_succeed() {
+        let result = ConfigWatcher::audit_config("/nonexistent/halo.toml".into());
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,871 @@
+//! Remediation plans for failed audit results.
+//!
+//! A [`FixPlan`] lists the changes that would bring the failing results of a report back to
+//! their expected state, e.g. `chmod 600 /etc/shadow` or `chown 0:0 /etc/passwd`. `halo fix --from report.json` prints the
+//! plan and applies it when confirmed, so fixing no longer needs an interactive prompt inside
+//! `check`. Results that cannot or should not be fixed in place, such as paths in a read-only
+//! Nix or ostree store, are listed with the reason they are skipped.
+//!
+//! A [`Remediator`] applies a plan in-process with `chmod(2)` and `chown(2)`, without shell
+//! scripts or `sudo`, and returns a [`FixResult`] per fix so partial failures are visible.
+//! Paths whose mode or owner changed between the audit and the fix are skipped. With an
+//! [alternate root](crate::audit::sysroot) set, the paths are changed inside it.
+//!
+//! [`FixPlan::playbook`] writes a plan as an Ansible playbook for the audited host (`halo fix
+//! --from report.json --format ansible`).
+//!
+//! # Rollback
+//! With [`Remediator::journal`], the mode, owner and group of each path are appended to an
+//! undo journal (JSON Lines) before the path is changed. [`FixPlan::from_journal`] plans the
+//! changes that restore them, so `halo fix --rollback <journal>` undoes a fix run.
+//!
+//! # Example Usage
+//! ```rust,no_run
+//! use alhalo::remediate::{FixPlan, Remediator};
+//! use alhalo::{Audit, Renderable, Target};
+//! let report = Audit::new().target(Target::User).run().unwrap();
+//! let plan = FixPlan::from_report(&report);
+//! plan.render_and_print(Some("text"));
+//! let results = Remediator::new().apply(&plan);
+//! results.render_and_print(Some("text"));
+//! ```
+use crate::audit::immutable::ImmutableDistro;
+use crate::audit::sysroot;
+use crate::render_output::{DataList, DataMap, Renderable, Style, paint};
+use crate::report::AuditReport;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// A change that fixes one failed result.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum FixAction {
+    /// Set the permission bits of the path
+    Chmod {
+        #[serde(serialize_with = "crate::audit::permissions::audit_permissions::as_octal")]
+        from: u32,
+        #[serde(serialize_with = "crate::audit::permissions::audit_permissions::as_octal")]
+        to: u32,
+    },
+    /// Change the owner and/or group of the path; `None` leaves it as it is
+    Chown {
+        from_uid: Option<u32>,
+        from_gid: Option<u32>,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    },
+}
+
+// Shows an optional id as the `chown` command line would, `-` if unknown
+fn id(v: Option<u32>) -> String {
+    v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+impl FixAction {
+    /// Shell command equivalent to the action, e.g. `chmod 600`, without the path.
+    pub fn command(&self) -> String {
+        match self {
+            FixAction::Chmod { to, .. } => format!("chmod {:o}", to),
+            FixAction::Chown { uid, gid: None, .. } => format!("chown {}", id(*uid)),
+            FixAction::Chown { uid: None, gid, .. } => format!("chgrp {}", id(*gid)),
+            FixAction::Chown { uid, gid, .. } => format!("chown {}:{}", id(*uid), id(*gid)),
+        }
+    }
+
+    /// State the audit found, e.g. `644` or `1000:1000`.
+    pub fn found(&self) -> String {
+        match self {
+            FixAction::Chmod { from, .. } => format!("{:o}", from),
+            FixAction::Chown { from_uid, from_gid, .. } => format!("{}:{}", id(*from_uid), id(*from_gid)),
+        }
+    }
+
+    /// State the fix sets, e.g. `600` or `0:0`.
+    pub fn target(&self) -> String {
+        match self {
+            FixAction::Chmod { to, .. } => format!("{:o}", to),
+            FixAction::Chown { uid, gid, .. } => format!("{}:{}", id(*uid), id(*gid)),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            FixAction::Chmod { .. } => "chmod",
+            FixAction::Chown { .. } => "chown",
+        }
+    }
+}
+
+/// A planned fix for one path.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Fix {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub action: FixAction,
+    /// Why the fix will not be applied, if it will not
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+/// The fixes for the failed results of a report.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FixPlan {
+    pub fixes: Vec<Fix>,
+}
+
+impl FixPlan {
+    /// Plans a fix for every failing permission and ownership result of `report`.
+    ///
+    /// Ownership fixes come first, as `chown(2)` may clear setuid and setgid bits. Fixes for
+    /// paths that no longer exist, symlinks, results that could not be read and paths in a
+    /// read-only store are kept in the plan but skipped.
+    pub fn from_report(report: &AuditReport) -> Self {
+        let distro = ImmutableDistro::detect();
+        let skip = |path: &Path, unreadable: bool| match sysroot::symlink_metadata(path) {
+            _ if unreadable => Some("the audit could not read the path".to_string()),
+            Err(_) => Some("path does not exist".to_string()),
+            Ok(meta) if meta.file_type().is_symlink() => Some("symlink".to_string()),
+            Ok(_) => distro
+                .as_ref()
+                .filter(|d| d.is_read_only(path))
+                .map(|d| format!("{} ({})", d.fix_hint(), d.name)),
+        };
+        let mut fixes: Vec<Fix> = Vec::new();
+        for r in report.ownership.iter().filter(|r| !r.pass && r.acknowledged.is_none()) {
+            // Only change what differs from the expectation
+            let changed = |expected: Option<u32>, found: Option<u32>| expected.filter(|e| Some(*e) != found);
+            let (uid, gid) = match (changed(r.expected_uid, r.found_uid), changed(r.expected_gid, r.found_gid)) {
+                (None, None) => (r.expected_uid, r.expected_gid),
+                ids => ids,
+            };
+            let planned = fixes
+                .iter()
+                .any(|f| f.path == r.path && matches!(f.action, FixAction::Chown { .. }));
+            if planned || (uid.is_none() && gid.is_none()) {
+                continue;
+            }
+            fixes.push(Fix {
+                path: r.path.clone(),
+                action: FixAction::Chown {
+                    from_uid: r.found_uid,
+                    from_gid: r.found_gid,
+                    uid,
+                    gid,
+                },
+                skipped: skip(&r.path, r.error.is_some()),
+            });
+        }
+        for r in report.failed_permissions() {
+            let planned = fixes
+                .iter()
+                .any(|f| f.path == r.path && matches!(f.action, FixAction::Chmod { .. }));
+            if planned {
+                continue;
+            }
+            fixes.push(Fix {
+                path: r.path.clone(),
+                action: FixAction::Chmod {
+                    from: r.found_mode,
+                    to: r.expected_mode,
+                },
+                skipped: skip(&r.path, r.error.is_some()),
+            });
+        }
+        Self { fixes }
+    }
+
+    /// Plans the changes that restore the paths recorded in an undo journal.
+    ///
+    /// A path recorded more than once is restored to its first, i.e. original, state. Only
+    /// what differs from the recorded state is changed; paths that no longer exist are
+    /// skipped.
+    pub fn from_journal(path: &Path) -> io::Result<Self> {
+        let file = fs::File::open(path)?;
+        let mut entries: Vec<JournalEntry> = Vec::new();
+        for (n, line) in io::BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: JournalEntry = serde_json::from_str(&line)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", n + 1, e)))?;
+            if !entries.iter().any(|e| e.path == entry.path) {
+                entries.push(entry);
+            }
+        }
+        let mut fixes = Vec::new();
+        for entry in entries {
+            let Ok(meta) = sysroot::symlink_metadata(&entry.path) else {
+                fixes.push(Fix {
+                    path: entry.path,
+                    action: FixAction::Chmod { from: 0, to: entry.mode },
+                    skipped: Some("path does not exist".to_string()),
+                });
+                continue;
+            };
+            // Owner first: chown(2) may clear setuid and setgid bits
+            if (meta.uid(), meta.gid()) != (entry.uid, entry.gid) {
+                let changed = |current: u32, recorded: u32| (current != recorded).then_some(recorded);
+                fixes.push(Fix {
+                    path: entry.path.clone(),
+                    action: FixAction::Chown {
+                        from_uid: Some(meta.uid()),
+                        from_gid: Some(meta.gid()),
+                        uid: changed(meta.uid(), entry.uid),
+                        gid: changed(meta.gid(), entry.gid),
+                    },
+                    skipped: None,
+                });
+            }
+            if meta.mode() & 0o7777 != entry.mode {
+                fixes.push(Fix {
+                    path: entry.path,
+                    action: FixAction::Chmod {
+                        from: meta.mode() & 0o7777,
+                        to: entry.mode,
+                    },
+                    skipped: None,
+                });
+            }
+        }
+        Ok(Self { fixes })
+    }
+
+    /// Fixes that will be applied.
+    pub fn applicable(&self) -> impl Iterator<Item = &Fix> {
+        self.fixes.iter().filter(|f| f.skipped.is_none())
+    }
+
+    /// Returns true if no fix will be applied.
+    pub fn is_empty(&self) -> bool {
+        self.applicable().next().is_none()
+    }
+
+    /// The applicable fixes as an Ansible playbook run on `hosts`, one `ansible.builtin.file`
+    /// task per path setting its mode, owner and group.
+    ///
+    /// Owners and groups are numeric, as in the report: names on this machine need not match
+    /// those on the audited host.
+    pub fn playbook(&self, hosts: &str) -> String {
+        use serde_yaml_ng::{Mapping, Value};
+        let entry = |key: &str, value: Value| (Value::from(key), value);
+        let mut tasks: IndexMap<String, Mapping> = IndexMap::new();
+        for fix in self.applicable() {
+            let path = fix.path.display().to_string();
+            let file = tasks
+                .entry(path.clone())
+                .or_insert_with(|| Mapping::from_iter([entry("path", Value::from(path))]));
+            match &fix.action {
+                FixAction::Chmod { to, .. } => {
+                    file.insert("mode".into(), Value::from(format!("{:04o}", to)));
+                }
+                FixAction::Chown { uid, gid, .. } => {
+                    if let Some(uid) = uid {
+                        file.insert("owner".into(), Value::from(uid.to_string()));
+                    }
+                    if let Some(gid) = gid {
+                        file.insert("group".into(), Value::from(gid.to_string()));
+                    }
+                }
+            }
+        }
+        let tasks: Vec<Value> = tasks
+            .into_iter()
+            .map(|(path, file)| {
+                Value::Mapping(Mapping::from_iter([
+                    entry("name", Value::from(format!("Fix {}", path))),
+                    entry("ansible.builtin.file", Value::Mapping(file)),
+                ]))
+            })
+            .collect();
+        let play = Mapping::from_iter([
+            entry("name", Value::from("HALO remediation")),
+            entry("hosts", Value::from(hosts)),
+            entry("become", Value::from(true)),
+            entry("tasks", Value::Sequence(tasks)),
+        ]);
+        let yaml = serde_yaml_ng::to_string(&[play]).unwrap_or_default();
+        format!("---\n# HALO remediation playbook\n{}", yaml)
+    }
+}
+
+impl Renderable for FixPlan {
+    fn to_datalist(&self) -> DataList {
+        self.fixes
+            .iter()
+            .map(|fix| {
+                let mut map = DataMap::new();
+                map.insert("path".to_string(), fix.path.display().to_string());
+                map.insert("action".to_string(), fix.action.name().to_string());
+                map.insert("from".to_string(), fix.action.found());
+                map.insert("to".to_string(), fix.action.target());
+                map.insert("skipped".to_string(), fix.skipped.clone().unwrap_or_default());
+                map
+            })
+            .collect()
+    }
+
+    fn pretty_print(&self) -> String {
+        if self.fixes.is_empty() {
+            return "Nothing to fix\n".to_string();
+        }
+        let mut output = String::new();
+        for fix in &self.fixes {
+            match &fix.skipped {
+                None => output.push_str(&format!(
+                    "  {} {}  (now {})\n",
+                    fix.action.command(),
+                    fix.path.display(),
+                    fix.action.found()
+                )),
+                Some(reason) => output.push_str(&format!(
+                    "  skip {}: {}\n",
+                    fix.path.display(),
+                    reason
+                )),
+            }
+        }
+        output
+    }
+}
+
+/// Quotes `path` for a POSIX shell: unchanged if it only holds safe characters, in single
+/// quotes if it is printable UTF-8, otherwise as a `$'...'` string with `\xNN` escapes.
+pub fn shell_quote(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    let bytes = path.as_os_str().as_bytes();
+    let safe = |b: &u8| b.is_ascii_alphanumeric() || b"_@%+=:,./-".contains(b);
+    if !bytes.is_empty() && bytes.iter().all(safe) {
+        return path.display().to_string();
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(s) if !s.chars().any(char::is_control) => format!("'{}'", s.replace('\'', r"'\''")),
+        _ => {
+            let mut quoted = String::from("$'");
+            for &b in bytes {
+                match b {
+                    b'\\' | b'\'' => quoted.push_str(&format!("\\{}", b as char)),
+                    0x20..=0x7e => quoted.push(b as char),
+                    _ => quoted.push_str(&format!("\\x{:02x}", b)),
+                }
+            }
+            quoted.push('\'');
+            quoted
+        }
+    }
+}
+
+/// Outcome of one fix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixStatus {
+    Applied,
+    Skipped,
+    Failed,
+}
+
+/// What happened when a fix was applied.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FixResult {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub action: FixAction,
+    pub status: FixStatus,
+    /// Why the fix was skipped or failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl Renderable for FixResult {
+    fn to_datalist(&self) -> DataList {
+        let mut map = DataMap::new();
+        map.insert("path".to_string(), self.path.display().to_string());
+        map.insert("action".to_string(), self.action.name().to_string());
+        map.insert("from".to_string(), self.action.found());
+        map.insert("to".to_string(), self.action.target());
+        map.insert("status".to_string(), format!("{:?}", self.status));
+        map.insert("message".to_string(), self.message.clone().unwrap_or_default());
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        let (label, style) = match self.status {
+            FixStatus::Applied => ("[ok]", Style::Green),
+            FixStatus::Skipped => ("[skip]", Style::Yellow),
+            FixStatus::Failed => ("[FAIL]", Style::Red),
+        };
+        let message = self.message.as_ref().map(|m| format!(": {}", m)).unwrap_or_default();
+        format!("{} {} {}{}", paint(label, style), self.action.command(), self.path.display(), message)
+    }
+}
+
+/// State of a path before a fix changed it, one line of an undo journal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub path: PathBuf,
+    #[serde(
+        serialize_with = "crate::audit::permissions::audit_permissions::as_octal",
+        deserialize_with = "crate::audit::permissions::audit_permissions::from_octal"
+    )]
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+}
+
+impl JournalEntry {
+    /// Current mode, owner and group of `path`, without following symlinks.
+    pub fn capture(path: &Path) -> io::Result<Self> {
+        Ok(Self::from_metadata(path, &sysroot::symlink_metadata(path)?))
+    }
+
+    fn from_metadata(path: &Path, meta: &fs::Metadata) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            mode: meta.mode() & 0o7777,
+            uid: meta.uid(),
+            gid: meta.gid(),
+        }
+    }
+}
+
+/// Applies fix plans directly to the filesystem.
+///
+/// Each fix is a single `chmod(2)` or `chown(2)` call on the path; a failing fix, e.g. for
+/// lack of privileges, does not stop the others. Right before the change the path is opened
+/// without following symlinks and stat'ed, and the fix is skipped if its mode or owner is no
+/// longer what the audit found, so a fix never overrides a change made after the audit. The
+/// change is made through the same descriptor, so a symlink swapped in after the check
+/// cannot redirect it.
+///
+/// `chown(2)` clears the setuid and setgid bits. When [`apply`](Self::apply) changed the
+/// owner of a path earlier in the plan, the mode check of its `chmod` ignores those bits, and
+/// the `chmod` restores them.
+#[derive(Debug, Clone, Default)]
+pub struct Remediator {
+    journal: Option<PathBuf>,
+}
+
+impl Remediator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the prior state of every path to this undo journal before changing it. A fix
+    /// whose state cannot be recorded is not applied.
+    pub fn journal(mut self, path: impl Into<PathBuf>) -> Self {
+        self.journal = Some(path.into());
+        self
+    }
+
+    /// Undo journal the prior states are appended to, if any.
+    pub fn journal_path(&self) -> Option<&Path> {
+        self.journal.as_deref()
+    }
+
+    /// Applies every fix of `plan` that is not skipped; returns one result per fix, in plan
+    /// order.
+    pub fn apply(&self, plan: &FixPlan) -> Vec<FixResult> {
+        let mut journal = self.journal.as_ref().map(|path| {
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("Failed to open undo journal {}: {}", path.display(), e))
+        });
+        let mut chowned: Vec<&Path> = Vec::new();
+        let mut results = Vec::new();
+        for fix in &plan.fixes {
+            let result = Self::run(fix, journal.as_mut(), chowned.contains(&fix.path.as_path()));
+            if result.status == FixStatus::Applied && matches!(fix.action, FixAction::Chown { .. }) {
+                chowned.push(&fix.path);
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    // Appends the state of `path` to the journal, flushed to disk before the change
+    fn record(journal: &mut fs::File, path: &Path, meta: &fs::Metadata) -> io::Result<()> {
+        let entry = JournalEntry::from_metadata(path, meta);
+        let mut line = serde_json::to_string(&entry).map_err(io::Error::other)?;
+        line.push('\n');
+        journal.write_all(line.as_bytes())?;
+        journal.sync_data()
+    }
+
+    /// Applies a single fix, without journaling it.
+    pub fn apply_fix(&self, fix: &Fix) -> FixResult {
+        Self::run(fix, None, false)
+    }
+
+    // Skips the fix if planned so or if the path changed since the audit, records the prior
+    // state in `journal` if given, then changes the path through the handle it checked.
+    // `chowned` says this run changed the owner of the path, clearing setuid and setgid
+    fn run(fix: &Fix, journal: Option<&mut Result<fs::File, String>>, chowned: bool) -> FixResult {
+        if let Some(reason) = &fix.skipped {
+            return Self::result(fix, FixStatus::Skipped, Some(reason.clone()));
+        }
+        let checked = sysroot::open_nofollow(&fix.path)
+            .and_then(|handle| Ok((handle.metadata()?, handle)))
+            .map_err(|e| format!("changed since the audit: {}", e))
+            .and_then(|(meta, handle)| Self::verify(fix, &meta, chowned).map(|()| (meta, handle)));
+        let (meta, handle) = match checked {
+            Ok(checked) => checked,
+            Err(reason) => {
+                warn!("Not fixing {}: {}", fix.path.display(), reason);
+                return Self::result(fix, FixStatus::Skipped, Some(reason));
+            }
+        };
+        let recorded = match journal {
+            Some(Err(e)) => Err(e.clone()),
+            Some(Ok(file)) => {
+                Self::record(file, &fix.path, &meta).map_err(|e| format!("Failed to record undo state: {}", e))
+            }
+            None => Ok(()),
+        };
+        match recorded.and_then(|()| Self::change(fix, &handle).map_err(|e| e.to_string())) {
+            Ok(()) => Self::result(fix, FixStatus::Applied, None),
+            Err(e) => Self::result(fix, FixStatus::Failed, Some(e)),
+        }
+    }
+
+    // Fails if the path, as just opened, no longer is in the state the plan was made from,
+    // e.g. because an administrator or package update changed it after the audit. The
+    // permission bits are compared as the audit saw them: with the setuid, setgid and sticky
+    // bits only if the plan recorded them, and without setuid and setgid once `chowned`
+    fn verify(fix: &Fix, meta: &fs::Metadata, chowned: bool) -> Result<(), String> {
+        if meta.file_type().is_symlink() {
+            return Err("changed since the audit: now a symlink".to_string());
+        }
+        match fix.action {
+            FixAction::Chmod { from, .. } => {
+                let mut mask = if from > 0o777 { 0o7777 } else { 0o777 };
+                if chowned {
+                    mask &= !0o6000;
+                }
+                if meta.mode() & mask != from & mask {
+                    return Err(format!(
+                        "changed since the audit: mode is {:o}, the audit found {:o}",
+                        meta.mode() & mask,
+                        from
+                    ));
+                }
+            }
+            FixAction::Chown { from_uid, from_gid, .. } => {
+                let differs = |found: Option<u32>, now: u32| found.is_some_and(|f| f != now);
+                if differs(from_uid, meta.uid()) || differs(from_gid, meta.gid()) {
+                    return Err(format!(
+                        "changed since the audit: owner is {}:{}, the audit found {}",
+                        meta.uid(),
+                        meta.gid(),
+                        fix.action.found()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn result(fix: &Fix, status: FixStatus, message: Option<String>) -> FixResult {
+        FixResult {
+            path: fix.path.clone(),
+            action: fix.action.clone(),
+            status,
+            message,
+        }
+    }
+
+    fn change(fix: &Fix, handle: &sysroot::PathHandle) -> std::io::Result<()> {
+        match &fix.action {
+            FixAction::Chmod { to, .. } => handle.set_mode(*to),
+            FixAction::Chown { uid, gid, .. } => handle.chown(*uid, *gid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::permissions::audit_permissions::{Importance, PermissionResults, Severity, Status};
+    use std::os::unix::fs::{PermissionsExt, symlink};
+
+    #[test]
+    fn test_plan_chmods_existing_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("secret");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+        let result = |path: PathBuf, status: Status| PermissionResults {
+            severity: Severity::High,
+            status,
+            path,
+            expected_mode: 0o600,
+            found_mode: 0o644,
+            importance: Importance::High,
+            rule_id: None,
+            acknowledged: None,
+            error: None,
+        };
+        let report = AuditReport {
+            permissions: vec![
+                result(file.clone(), Status::Fail),
+                result(dir.path().join("gone"), Status::Fail),
+                result(dir.path().to_path_buf(), Status::Pass),
+            ],
+            ..Default::default()
+        };
+        let plan = FixPlan::from_report(&report);
+        assert_eq!(plan.fixes.len(), 2);
+        assert_eq!(plan.fixes[0].action, FixAction::Chmod { from: 0o644, to: 0o600 });
+        assert_eq!(plan.fixes[0].action.command(), "chmod 600");
+        assert_eq!(plan.fixes[1].skipped.as_deref(), Some("path does not exist"));
+        assert_eq!(plan.applicable().count(), 1);
+
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["fixes"][0]["action"], "chmod");
+        assert_eq!(json["fixes"][0]["to"], "600");
+    }
+
+    #[test]
+    fn test_remediator_applies_and_reports_each_fix() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("key");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+        let meta = fs::metadata(&file).unwrap();
+        let (uid, gid, path) = (Some(meta.uid()), Some(meta.gid()), file.clone());
+        let plan = FixPlan {
+            fixes: vec![
+                Fix {
+                    path: path.clone(),
+                    action: FixAction::Chmod { from: 0o644, to: 0o600 },
+                    skipped: None,
+                },
+                // Changing to the current owner needs no privileges
+                Fix {
+                    path: path.clone(),
+                    action: FixAction::Chown { from_uid: uid, from_gid: gid, uid, gid },
+                    skipped: None,
+                },
+                Fix {
+                    path: dir.path().join("gone"),
+                    action: FixAction::Chmod { from: 0o644, to: 0o600 },
+                    skipped: None,
+                },
+                Fix {
+                    path: path.clone(),
+                    action: FixAction::Chmod { from: 0o644, to: 0o000 },
+                    skipped: Some("read-only".to_string()),
+                },
+                // Planned from the mode before the first fix; changed since
+                Fix {
+                    path: path.clone(),
+                    action: FixAction::Chmod { from: 0o644, to: 0o640 },
+                    skipped: None,
+                },
+            ],
+        };
+        let results = Remediator::new().apply(&plan);
+        let statuses: Vec<FixStatus> = results.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            [FixStatus::Applied, FixStatus::Applied, FixStatus::Skipped, FixStatus::Skipped, FixStatus::Skipped]
+        );
+        assert!(results[2].message.as_ref().unwrap().starts_with("changed since the audit"));
+        assert_eq!(
+            results[4].message.as_deref(),
+            Some("changed since the audit: mode is 600, the audit found 644")
+        );
+        assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o7777, 0o600);
+        assert_eq!(FixAction::Chown { from_uid: None, from_gid: None, uid: Some(0), gid: None }.command(), "chown 0");
+        assert_eq!(serde_json::to_value(&results[0]).unwrap()["status"], "applied");
+    }
+
+    #[test]
+    fn test_journal_rolls_back_applied_fixes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("key");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+        let chmod = |from, to| Fix {
+            path: file.clone(),
+            action: FixAction::Chmod { from, to },
+            skipped: None,
+        };
+        let journal = dir.path().join("journal.jsonl");
+        let remediator = Remediator::new().journal(&journal);
+        remediator.apply(&FixPlan { fixes: vec![chmod(0o644, 0o600)] });
+        remediator.apply(&FixPlan { fixes: vec![chmod(0o600, 0o400)] });
+        assert_eq!(fs::read_to_string(&journal).unwrap().lines().count(), 2);
+
+        let rollback = FixPlan::from_journal(&journal).unwrap();
+        assert_eq!(rollback.fixes, [chmod(0o400, 0o644)]);
+        let results = Remediator::new().apply(&rollback);
+        assert_eq!(results[0].status, FixStatus::Applied);
+        assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o7777, 0o644);
+        assert!(FixPlan::from_journal(&journal).unwrap().fixes.is_empty());
+
+        let unwritable = Remediator::new().journal(dir.path().join("missing/journal.jsonl"));
+        let results = unwritable.apply(&FixPlan { fixes: vec![chmod(0o644, 0o600)] });
+        assert_eq!(results[0].status, FixStatus::Failed);
+        assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o7777, 0o644);
+    }
+
+    #[test]
+    fn test_symlink_swapped_in_is_not_followed() {
+        let dir = tempfile::tempdir().unwrap();
+        let (file, victim) = (dir.path().join("key"), dir.path().join("shadow"));
+        for path in [&file, &victim] {
+            fs::write(path, "").unwrap();
+            fs::set_permissions(path, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+        let fix = Fix {
+            path: file.clone(),
+            action: FixAction::Chmod { from: 0o644, to: 0o666 },
+            skipped: None,
+        };
+
+        // Swapped before the check: skipped
+        fs::rename(&file, dir.path().join("moved")).unwrap();
+        symlink(&victim, &file).unwrap();
+        let result = Remediator::new().apply_fix(&fix);
+        assert_eq!(result.status, FixStatus::Skipped);
+        assert_eq!(result.message.as_deref(), Some("changed since the audit: now a symlink"));
+        assert_eq!(mode(&victim), 0o644);
+
+        // Swapped between the check and the change: the checked inode is changed
+        fs::remove_file(&file).unwrap();
+        fs::rename(dir.path().join("moved"), &file).unwrap();
+        let handle = sysroot::open_nofollow(&file).unwrap();
+        assert!(handle.metadata().unwrap().is_file());
+        fs::rename(&file, dir.path().join("moved")).unwrap();
+        symlink(&victim, &file).unwrap();
+        handle.set_mode(0o600).unwrap();
+        handle.chown(None, None).unwrap();
+        assert_eq!(mode(&victim), 0o644);
+        assert_eq!(mode(&dir.path().join("moved")), 0o600);
+    }
+
+    #[test]
+    fn test_chmod_restores_setuid_cleared_by_chown() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("helper");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o4755)).unwrap();
+        let meta = fs::metadata(&file).unwrap();
+        let (uid, gid) = (Some(meta.uid()), Some(meta.gid()));
+        // chown(2) clears setuid even when the owner does not change
+        let plan = FixPlan {
+            fixes: vec![
+                Fix {
+                    path: file.clone(),
+                    action: FixAction::Chown { from_uid: uid, from_gid: gid, uid, gid },
+                    skipped: None,
+                },
+                Fix {
+                    path: file.clone(),
+                    action: FixAction::Chmod { from: 0o4755, to: 0o4750 },
+                    skipped: None,
+                },
+            ],
+        };
+        let results = Remediator::new().apply(&plan);
+        assert_eq!(results[1].status, FixStatus::Applied, "{:?}", results[1].message);
+        assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o7777, 0o4750);
+
+        // Without a chown in the same run the setuid bit is still compared
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o755)).unwrap();
+        let result = Remediator::new().apply_fix(&plan.fixes[1]);
+        assert_eq!(result.status, FixStatus::Skipped);
+    }
+
+    #[test]
+    fn test_plan_chowns_before_chmod() {
+        use crate::audit::ownership::ownership::OwnershipResult;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("passwd");
+        fs::write(&file, "").unwrap();
+        let report = AuditReport {
+            permissions: vec![PermissionResults {
+                severity: Severity::High,
+                status: Status::Fail,
+                path: file.clone(),
+                expected_mode: 0o644,
+                found_mode: 0o666,
+                importance: Importance::High,
+                rule_id: None,
+                acknowledged: None,
+                error: None,
+            }],
+            ownership: vec![OwnershipResult {
+                path: file.clone(),
+                expected_uid: Some(0),
+                expected_gid: Some(0),
+                found_uid: Some(0),
+                found_gid: Some(1000),
+                pass: false,
+                severity: Severity::High,
+                rule_id: None,
+                acknowledged: None,
+                error: None,
+            }],
+            ..Default::default()
+        };
+        let plan = FixPlan::from_report(&report);
+        let commands: Vec<String> = plan.fixes.iter().map(|f| f.action.command()).collect();
+        assert_eq!(commands, ["chgrp 0", "chmod 644"]);
+        assert_eq!(plan.fixes[0].action.found(), "0:1000");
+    }
+
+    #[test]
+    fn test_playbook_sets_mode_and_owner_per_path() {
+        let path = PathBuf::from("/etc/passwd");
+        let plan = FixPlan {
+            fixes: vec![
+                Fix {
+                    path: path.clone(),
+                    action: FixAction::Chown { from_uid: Some(1000), from_gid: Some(1000), uid: Some(0), gid: Some(0) },
+                    skipped: None,
+                },
+                Fix {
+                    path: path.clone(),
+                    action: FixAction::Chmod { from: 0o666, to: 0o644 },
+                    skipped: None,
+                },
+                Fix {
+                    path: PathBuf::from("/nix/store/x"),
+                    action: FixAction::Chmod { from: 0o666, to: 0o644 },
+                    skipped: Some("read-only".to_string()),
+                },
+            ],
+        };
+        let yaml = plan.playbook("web-1");
+        assert!(yaml.starts_with("---\n# HALO remediation playbook\n"));
+        let plays: serde_yaml_ng::Value = serde_yaml_ng::from_str(&yaml).unwrap();
+        assert_eq!(plays[0]["hosts"], "web-1");
+        let tasks = plays[0]["tasks"].as_sequence().unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0]["name"], "Fix /etc/passwd");
+        let file = &tasks[0]["ansible.builtin.file"];
+        assert_eq!(file["path"], "/etc/passwd");
+        assert_eq!(file["mode"], "0644");
+        assert_eq!((&file["owner"], &file["group"]), (&"0".into(), &"0".into()));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        assert_eq!(shell_quote(Path::new("/etc/ssh/sshd_config")), "/etc/ssh/sshd_config");
+        assert_eq!(shell_quote(Path::new("/srv/my files/a;b")), "'/srv/my files/a;b'");
+        assert_eq!(shell_quote(Path::new("/srv/it's $HOME")), r"'/srv/it'\''s $HOME'");
+        assert_eq!(shell_quote(Path::new("/srv/a\nb")), r"$'/srv/a\x0ab'");
+        assert_eq!(shell_quote(Path::new(OsStr::from_bytes(b"/srv/\xff'"))), r"$'/srv/\xff\''");
+    }
+}
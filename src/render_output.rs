@@ -2,16 +2,35 @@
 //!
 //! This module provides functions to render audit and parsed data in multiple formats:
 //! - Pretty-printed JSON
-//! - CSV (with optional column filtering)
+//! - JSON Lines, one object per line; see [`crate::stream`] for streaming audit results
+//! - CSV (with optional column filtering), quoted as needed, with a choice of delimiter
 //! - Human-readable text blocks
+//! - Markdown tables
+//! - Standalone HTML pages with sortable, severity color-coded tables
 //! - Unified trait-based rendering for consistent output handling
 //!
+//! Parsed data is narrowed with [`filter`] to exact keys, or with [`filter_lines`] to pairs
+//! whose key or value matches a [`LineRegex`], before it reaches any format.
+//!
+//! [`RenderOptions`] sort results by severity, path or status and group them by target or
+//! severity in every format; see [`Renderable::render_with`].
+//!
+//! Pretty output is colored by severity and status when stdout is a terminal. Colors are off
+//! when the `NO_COLOR` environment variable is set, and can be forced with [`set_color`], e.g.
+//! for `--no-color`.
+//!
 //! Used by the CLI and macro system to display results in a user-friendly way.
 
+use crate::audit::permissions::audit_permissions::Severity;
+use crate::error::HaloError;
+use crate::risk::UNATTRIBUTED;
 use indexmap::IndexMap;
+use regex::Regex;
 use serde::Serialize;
-use serde_json;
-use std::io;
+use serde_json::{self, Value};
+use std::cmp::Reverse;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
 
 /// A deterministic map of key-value pairs parsed from a file.
 ///
@@ -26,6 +45,8 @@ pub type DataList = Vec<DataMap>;
 pub struct ParsedData {
     pub data: DataList,
     pub filter_keys: Vec<String>,
+    /// Patterns selecting pairs in addition to `filter_keys`
+    pub line_regex: Option<LineRegex>,
 }
 
 impl ParsedData {
@@ -33,16 +54,23 @@ impl ParsedData {
         Self {
             data,
             filter_keys: Vec::new(),
+            line_regex: None,
         }
     }
 
     pub fn with_filter(data: DataList, filter_keys: Vec<String>) -> Self {
-        Self { data, filter_keys }
+        Self { data, filter_keys, line_regex: None }
+    }
+
+    /// Also keep the pairs matching `regex`.
+    pub fn with_regex(mut self, regex: LineRegex) -> Self {
+        self.line_regex = Some(regex);
+        self
     }
 
     /// Get the filtered data for serialization
     pub fn filtered_data(&self) -> DataList {
-        filter(&self.data, &self.filter_keys)
+        filter_lines(&self.data, &self.filter_keys, self.line_regex.as_ref())
     }
 }
 
@@ -59,23 +87,364 @@ impl Serialize for ParsedData {
 #[derive(Debug, Clone)]
 pub enum OutputFormat {
     Json,
+    Jsonl,
     Csv,
     Text,
+    Markdown,
+    Html,
     Pretty,
 }
 
 impl OutputFormat {
+    /// Every output format.
+    pub const ALL: [OutputFormat; 7] = [
+        Self::Pretty,
+        Self::Json,
+        Self::Jsonl,
+        Self::Csv,
+        Self::Text,
+        Self::Markdown,
+        Self::Html,
+    ];
+
+    /// Name accepted by `--format`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Jsonl => "jsonl",
+            Self::Csv => "csv",
+            Self::Text => "text",
+            Self::Markdown => "markdown",
+            Self::Html => "html",
+            Self::Pretty => "pretty",
+        }
+    }
+
+    /// One-line description of the format.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Json => "JSON document, for scripts and other tools",
+            Self::Jsonl => "JSON Lines, one result per line as results are produced (alias: ndjson)",
+            Self::Csv => "Comma-separated values with a header row",
+            Self::Text => "Plain key: value lines",
+            Self::Markdown => "Markdown table (alias: md)",
+            Self::Html => "Standalone HTML page with sortable tables, e.g. for --store report.html",
+            Self::Pretty => "Human-readable summary (default)",
+        }
+    }
+
     /// Parse format string into OutputFormat enum
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: Option<&str>) -> Self {
         match s {
             Some("json") => Self::Json,
+            Some("jsonl") | Some("ndjson") => Self::Jsonl,
             Some("csv") => Self::Csv,
             Some("text") => Self::Text,
+            Some("markdown") | Some("md") => Self::Markdown,
+            Some("html") => Self::Html,
             _ => Self::Pretty,
         }
     }
 }
 
+/// Terminal text styles used by pretty output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Red,
+    Yellow,
+    Green,
+    Blue,
+    Magenta,
+    Cyan,
+    Dim,
+    Bold,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Self::Red => "31",
+            Self::Yellow => "33",
+            Self::Green => "32",
+            Self::Blue => "34",
+            Self::Magenta => "35",
+            Self::Cyan => "36",
+            Self::Dim => "2",
+            Self::Bold => "1",
+        }
+    }
+}
+
+// 0: decide from the environment, 1: never, 2: always
+static COLOR: AtomicU8 = AtomicU8::new(0);
+
+/// Force colors on or off for the rest of the process.
+pub fn set_color(enabled: bool) {
+    COLOR.store(if enabled { 2 } else { 1 }, Ordering::Relaxed);
+}
+
+/// Returns true if pretty output should be colored: as set by [`set_color`], otherwise if
+/// stdout is a terminal and `NO_COLOR` is unset or empty.
+pub fn color_enabled() -> bool {
+    match COLOR.load(Ordering::Relaxed) {
+        1 => false,
+        2 => true,
+        _ => {
+            std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && io::stdout().is_terminal()
+        }
+    }
+}
+
+/// `text` in `style` if colors are enabled, unchanged otherwise.
+///
+/// Pad before painting; escape codes count towards `format!` widths.
+pub fn paint(text: &str, style: Style) -> String {
+    if color_enabled() {
+        format!("\x1b[{}m{}\x1b[0m", style.code(), text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Order of rendered results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SortKey {
+    /// Most severe first
+    Severity,
+    /// Alphabetical by path
+    Path,
+    /// Failures first, then stricter than expected, then passes
+    Status,
+}
+
+/// Grouping of rendered results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum GroupBy {
+    /// Target that produced each result, in order of its first result
+    Target,
+    /// Severity, most severe first
+    Severity,
+}
+
+/// CSV dialect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Field delimiter, e.g. `b'\t'` for TSV
+    pub delimiter: u8,
+    /// Write the header row
+    pub headers: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            headers: true,
+        }
+    }
+}
+
+/// Ordering, grouping and CSV dialect applied by [`Renderable::render_with`].
+///
+/// Sorting is stable: results that compare equal keep the order the audit produced them in, so
+/// the same options always give the same output for the same results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub sort: Option<SortKey>,
+    pub group_by: Option<GroupBy>,
+    pub csv: CsvOptions,
+}
+
+impl RenderOptions {
+    /// Sort results by `key`.
+    pub fn sort(mut self, key: SortKey) -> Self {
+        self.sort = Some(key);
+        self
+    }
+
+    /// Group results by `key`.
+    pub fn group_by(mut self, key: GroupBy) -> Self {
+        self.group_by = Some(key);
+        self
+    }
+
+    /// Write CSV as `csv` says.
+    pub fn csv(mut self, csv: CsvOptions) -> Self {
+        self.csv = csv;
+        self
+    }
+
+    /// Returns true if neither sorting, grouping nor a CSV dialect is requested.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Sorts `rows` and splits them into named groups in group order; a single unnamed group
+    /// without `group_by`.
+    pub fn arrange(&self, rows: DataList) -> Vec<(Option<String>, DataList)> {
+        self.arrange_by(rows, |row, field| row.get(field).cloned())
+    }
+
+    /// Like [`arrange`](Self::arrange) for any items; `get` returns a field of an item by its
+    /// column name (`severity`, `path`, `status` or `pass`, `target`).
+    pub(crate) fn arrange_by<T>(
+        &self,
+        mut items: Vec<T>,
+        get: impl Fn(&T, &str) -> Option<String>,
+    ) -> Vec<(Option<String>, Vec<T>)> {
+        if let Some(key) = self.sort {
+            items.sort_by_cached_key(|item| sort_key(key, |field| get(item, field)));
+        }
+        let Some(group_by) = self.group_by else {
+            return vec![(None, items)];
+        };
+        let mut groups: Vec<(String, Vec<T>)> = Vec::new();
+        for item in items {
+            let name = match group_by {
+                GroupBy::Target => get(&item, "target").unwrap_or_else(|| UNATTRIBUTED.to_string()),
+                GroupBy::Severity => get(&item, "severity").unwrap_or_else(|| "None".to_string()),
+            };
+            match groups.iter_mut().find(|(group, _)| *group == name) {
+                Some((_, members)) => members.push(item),
+                None => groups.push((name, vec![item])),
+            }
+        }
+        if group_by == GroupBy::Severity {
+            groups.sort_by_key(|(name, _)| Reverse(severity_rank_of(name)));
+        }
+        groups.into_iter().map(|(name, members)| (Some(name), members)).collect()
+    }
+
+    /// Sorts and groups the arrays in `value`: a top-level array, or the arrays of a top-level
+    /// object such as a report. Groups become an object keyed by group name, or with `flat` a
+    /// `group` field on each element, as JSON Lines need.
+    pub fn arrange_value(&self, value: Value, flat: bool) -> Value {
+        match value {
+            Value::Array(items) => {
+                let groups = self.arrange_by(items, |item, field| match item.get(field) {
+                    Some(Value::String(s)) => Some(s.clone()),
+                    Some(Value::Null) | None => None,
+                    Some(other) => Some(other.to_string()),
+                });
+                let mut flattened = Vec::new();
+                let mut keyed = serde_json::Map::new();
+                for (name, members) in groups {
+                    match name {
+                        None => flattened.extend(members),
+                        Some(name) if flat => flattened.extend(members.into_iter().map(|mut item| {
+                            if let Value::Object(map) = &mut item {
+                                map.insert("group".to_string(), Value::from(name.as_str()));
+                            }
+                            item
+                        })),
+                        Some(name) => {
+                            keyed.insert(name, Value::Array(members));
+                        }
+                    }
+                }
+                if self.group_by.is_some() && !flat {
+                    Value::Object(keyed)
+                } else {
+                    Value::Array(flattened)
+                }
+            }
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| match v {
+                        Value::Array(_) => (k, self.arrange_value(v, false)),
+                        other => (k, other),
+                    })
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+}
+
+// Severity rank of a severity name; unknown names rank with `None`
+fn severity_rank_of(name: &str) -> u8 {
+    serde_json::from_value::<Severity>(Value::from(name)).map_or(0, |s| s.rank())
+}
+
+// Ascending sort key of one result
+fn sort_key(key: SortKey, get: impl Fn(&str) -> Option<String>) -> (u8, String) {
+    match key {
+        SortKey::Severity => (Severity::Critical.rank() - get("severity").map_or(0, |s| severity_rank_of(&s)), String::new()),
+        SortKey::Path => (0, get("path").unwrap_or_default()),
+        SortKey::Status => {
+            let rank = match (get("status").as_deref(), get("pass").as_deref()) {
+                (Some("Fail"), _) | (None, Some("false")) => 0,
+                (Some("Strict"), _) => 1,
+                (Some("Pass"), _) | (None, Some("true")) => 2,
+                _ => 3,
+            };
+            (rank, String::new())
+        }
+    }
+}
+
+/// Renders `rows` in `format` after sorting and grouping them as `options` say.
+///
+/// Groups are headed sections in text, pretty, Markdown and HTML output, a leading `group`
+/// column in CSV and keyed objects in JSON.
+pub fn render_rows(rows: &DataList, format: OutputFormat, options: &RenderOptions) -> io::Result<String> {
+    let groups = options.arrange(rows.clone());
+    match format {
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let value = serde_json::to_value(rows).map_err(io::Error::other)?;
+            let flat = matches!(format, OutputFormat::Jsonl);
+            let value = options.arrange_value(value, flat);
+            if flat { render_jsonl(&value) } else { render_json(&value) }
+        }
+        OutputFormat::Csv => {
+            let flattened: DataList = groups
+                .into_iter()
+                .flat_map(|(name, members)| {
+                    members.into_iter().map(move |row| match &name {
+                        Some(name) => std::iter::once(("group".to_string(), name.clone())).chain(row).collect(),
+                        None => row,
+                    })
+                })
+                .collect();
+            render_csv_with(&flattened, &[], &options.csv)
+        }
+        OutputFormat::Html => {
+            let mut body = String::new();
+            for (name, members) in &groups {
+                if let Some(name) = name {
+                    body.push_str(&format!("<h2>{}</h2>\n", html_escape(name)));
+                }
+                body.push_str(&html_table(members, &[]));
+            }
+            Ok(html_page("HALO results", &body))
+        }
+        OutputFormat::Markdown => {
+            let mut out = String::new();
+            for (name, members) in &groups {
+                if let Some(name) = name {
+                    out.push_str(&format!("## {}\n\n", name));
+                }
+                out.push_str(&render_markdown(members, &[])?);
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        OutputFormat::Text | OutputFormat::Pretty => {
+            let mut out = String::new();
+            for (name, members) in &groups {
+                if let Some(name) = name {
+                    out.push_str(&format!("== {} ==\n", name));
+                }
+                out.push_str(&render_text(members, &[])?);
+            }
+            Ok(out)
+        }
+    }
+}
+
 /// Trait for types that can be rendered in multiple output formats
 pub trait Renderable {
     /// Convert to DataList for CSV/text rendering
@@ -85,7 +454,19 @@ pub trait Renderable {
     fn pretty_print(&self) -> String {
         "Output available in JSON, CSV, or text format.".to_string()
     }
+
+    /// Standalone HTML page (optional override)
+    fn html(&self) -> String {
+        html_page("HALO results", &html_table(&self.to_datalist(), &[]))
+    }
     
+    /// Pretty output ordered and grouped as `options` say; the default ignores them, which
+    /// suits single results.
+    fn pretty_print_with(&self, options: &RenderOptions) -> String {
+        let _ = options;
+        self.pretty_print()
+    }
+
     /// Render in the specified format
     fn render(&self, format: OutputFormat) -> io::Result<String>
     where
@@ -93,25 +474,97 @@ pub trait Renderable {
     {
         match format {
             OutputFormat::Json => render_json(&self),
+            OutputFormat::Jsonl => render_jsonl(&self),
             OutputFormat::Csv => render_csv(&self.to_datalist(), &[]),
             OutputFormat::Text => render_text(&self.to_datalist(), &[]),
+            OutputFormat::Markdown => render_markdown(&self.to_datalist(), &[]),
+            OutputFormat::Html => Ok(self.html()),
             OutputFormat::Pretty => Ok(self.pretty_print()),
         }
     }
     
+    /// Render in the specified format, sorted and grouped as `options` say
+    fn render_with(&self, format: OutputFormat, options: &RenderOptions) -> io::Result<String>
+    where
+        Self: Serialize,
+    {
+        if options.is_default() {
+            return self.render(format);
+        }
+        match format {
+            OutputFormat::Json | OutputFormat::Jsonl => {
+                let flat = matches!(format, OutputFormat::Jsonl);
+                let value = serde_json::to_value(self).map_err(io::Error::other)?;
+                let value = options.arrange_value(value, flat);
+                if flat { render_jsonl(&value) } else { render_json(&value) }
+            }
+            OutputFormat::Pretty => Ok(self.pretty_print_with(options)),
+            _ => render_rows(&self.to_datalist(), format, options),
+        }
+    }
+
+    /// Render in the specified format straight to `writer`, without building the whole output
+    /// as a `String` first where the format allows (JSON, JSON Lines of a `Vec`, CSV)
+    fn render_to<W: Write>(&self, format: OutputFormat, writer: W) -> io::Result<()>
+    where
+        Self: Serialize,
+    {
+        self.render_to_with(format, &RenderOptions::default(), writer)
+    }
+
+    /// Like [`render_to`](Self::render_to), sorted and grouped as `options` say
+    fn render_to_with<W: Write>(&self, format: OutputFormat, options: &RenderOptions, writer: W) -> io::Result<()>
+    where
+        Self: Serialize,
+    {
+        write_rendered(self, format, options, writer)
+    }
+
     /// Render and print to stdout with error handling
     fn render_and_print(&self, format: Option<&str>)
+    where
+        Self: Serialize,
+    {
+        self.render_and_print_with(format, &RenderOptions::default());
+    }
+
+    /// Render and print to stdout, sorted and grouped as `options` say
+    fn render_and_print_with(&self, format: Option<&str>, options: &RenderOptions)
     where
         Self: Serialize,
     {
         let output_format = OutputFormat::from_str(format);
-        match self.render(output_format) {
-            Ok(output) => print!("{}", output),
+        match self.render_to_with(output_format, options, io::stdout().lock()) {
+            Ok(()) => {}
+            // The reader went away, e.g. `| head`
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
             Err(e) => eprintln!("Error rendering output: {}", e),
         }
     }
 }
 
+// Default of `Renderable::render_to_with`, shared with implementations that override it for
+// some formats
+fn write_rendered<R, W>(item: &R, format: OutputFormat, options: &RenderOptions, mut writer: W) -> io::Result<()>
+where
+    R: Renderable + Serialize + ?Sized,
+    W: Write,
+{
+    if !options.is_default() {
+        writer.write_all(item.render_with(format, options)?.as_bytes())?;
+        return writer.flush();
+    }
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, item).map_err(io::Error::from)?;
+            writer.write_all(b"\n")?;
+        }
+        OutputFormat::Csv => write_csv(&mut writer, &item.to_datalist(), &[], &CsvOptions::default())?,
+        _ => writer.write_all(item.render(format)?.as_bytes())?,
+    }
+    writer.flush()
+}
+
 impl Renderable for ParsedData {
     fn to_datalist(&self) -> DataList {
         self.filtered_data()
@@ -124,6 +577,13 @@ impl Renderable for ParsedData {
             Err(_) => "Error rendering data".to_string(),
         }
     }
+
+    fn pretty_print_with(&self, options: &RenderOptions) -> String {
+        match render_rows(&self.filtered_data(), OutputFormat::Pretty, options) {
+            Ok(output) => output,
+            Err(_) => "Error rendering data".to_string(),
+        }
+    }
 }
 
 /// Implement Renderable for Vec<T> where T: Renderable
@@ -143,13 +603,50 @@ where
         }
         
         let mut output = String::new();
-        output.push_str(&format!("Results Found:\n"));
+        output.push_str("Results Found:\n");
         for item in self {
             output.push_str(&format!("  {}\n", item.pretty_print()));
         }
         output.push_str(&format!("\nTotal results: {}\n", self.len()));
         output
     }
+
+    /// JSON Lines are written one element at a time.
+    fn render_to_with<W: Write>(&self, format: OutputFormat, options: &RenderOptions, mut writer: W) -> io::Result<()>
+    where
+        Self: Serialize,
+    {
+        if !matches!(format, OutputFormat::Jsonl) || !options.is_default() {
+            return write_rendered(self, format, options, writer);
+        }
+        for item in self {
+            serde_json::to_writer(&mut writer, item).map_err(io::Error::from)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
+    }
+
+    fn pretty_print_with(&self, options: &RenderOptions) -> String {
+        if options.is_default() || self.is_empty() {
+            return self.pretty_print();
+        }
+        let items: Vec<(DataMap, &T)> = self
+            .iter()
+            .map(|item| (item.to_datalist().into_iter().next().unwrap_or_default(), item))
+            .collect();
+        let mut output = String::from("Results Found:\n");
+        for (name, members) in options.arrange_by(items, |(row, _), field| row.get(field).cloned()) {
+            if let Some(name) = name {
+                let header = format!("== {} ({}) ==", name, members.len());
+                output.push_str(&format!("{}\n", paint(&header, Style::Bold)));
+            }
+            for (_, item) in members {
+                output.push_str(&format!("  {}\n", item.pretty_print()));
+            }
+        }
+        output.push_str(&format!("\nTotal results: {}\n", self.len()));
+        output
+    }
 }
 /// Renders any serializable data as pretty-printed JSON.
 ///
@@ -160,12 +657,32 @@ where
 /// * `io::Result<String>` containing the pretty-printed JSON string or an error.
 pub fn render_json<T: Serialize>(data: &T) -> io::Result<String> {
     let s =
-        serde_json::to_string_pretty(data).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        serde_json::to_string_pretty(data).map_err(io::Error::other)?;
     Ok(s + "\n")
 }
 
+/// Renders serializable data as JSON Lines: one compact object per element of a sequence, or
+/// a single line for anything else.
+///
+/// # Arguments
+/// * `data` - Reference to a serializable data structure.
+///
+/// # Returns
+/// * `io::Result<String>` containing the lines or an error.
+pub fn render_jsonl<T: Serialize>(data: &T) -> io::Result<String> {
+    let value = serde_json::to_value(data).map_err(io::Error::other)?;
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        other => vec![other],
+    };
+    Ok(items.iter().map(|item| format!("{}\n", item)).collect())
+}
+
 /// Renders a list of data maps as CSV.
 ///
+/// Fields containing the delimiter, quotes or line breaks are quoted, so paths and error
+/// messages survive the round trip.
+///
 /// # Arguments
 /// * `data` - List of data maps to render.
 /// * `line` - List of keys to use as CSV headers (column filter). If empty, uses all keys from the first block.
@@ -173,6 +690,26 @@ pub fn render_json<T: Serialize>(data: &T) -> io::Result<String> {
 /// # Returns
 /// * `io::Result<String>` containing the CSV string or an error.
 pub fn render_csv(data: &DataList, line: &[String]) -> io::Result<String> {
+    render_csv_with(data, line, &CsvOptions::default())
+}
+
+/// Renders a list of data maps as CSV with the delimiter and header row of `options`.
+///
+/// # Arguments
+/// * `data` - List of data maps to render.
+/// * `line` - List of keys to use as CSV headers (column filter). If empty, uses all keys from the first block.
+/// * `options` - Delimiter and whether to write the header row.
+///
+/// # Returns
+/// * `io::Result<String>` containing the CSV string or an error.
+pub fn render_csv_with(data: &DataList, line: &[String], options: &CsvOptions) -> io::Result<String> {
+    let mut out = Vec::new();
+    write_csv(&mut out, data, line, options)?;
+    String::from_utf8(out).map_err(io::Error::other)
+}
+
+/// Writes a list of data maps as CSV to `writer`; see [`render_csv_with`].
+pub fn write_csv<W: Write>(writer: W, data: &DataList, line: &[String], options: &CsvOptions) -> io::Result<()> {
     let data = filter(data, line);
 
     let headers: Vec<String> = if !line.is_empty() {
@@ -180,23 +717,20 @@ pub fn render_csv(data: &DataList, line: &[String]) -> io::Result<String> {
     } else if let Some(first) = data.first() {
         first.keys().cloned().collect()
     } else {
-        Vec::new()
+        return Ok(());
     };
 
-    let mut out = String::new();
-    if !headers.is_empty() {
-        out.push_str(&headers.join(","));
-        out.push('\n');
-        for row in &data {
-            let row_line: Vec<String> = headers
-                .iter()
-                .map(|h| row.get(h).cloned().unwrap_or_default())
-                .collect();
-            out.push_str(&row_line.join(","));
-            out.push('\n');
-        }
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .from_writer(writer);
+    if options.headers {
+        writer.write_record(&headers).map_err(io::Error::from)?;
     }
-    Ok(out)
+    for row in &data {
+        let fields = headers.iter().map(|h| row.get(h).map(String::as_str).unwrap_or_default());
+        writer.write_record(fields).map_err(io::Error::from)?;
+    }
+    writer.flush()
 }
 
 /// Renders a list of data maps as pretty text blocks.
@@ -219,6 +753,138 @@ pub fn render_text(data: &DataList, line: &[String]) -> io::Result<String> {
     Ok(out)
 }
 
+/// Renders a list of data maps as a Markdown table.
+///
+/// # Arguments
+/// * `data` - List of data maps to render.
+/// * `line` - List of keys to use as columns. If empty, uses all keys from the first block.
+///
+/// # Returns
+/// * `io::Result<String>` containing the table, or an empty string if there is no data.
+pub fn render_markdown(data: &DataList, line: &[String]) -> io::Result<String> {
+    let data = filter(data, line);
+    let headers: Vec<String> = if !line.is_empty() {
+        line.to_vec()
+    } else if let Some(first) = data.first() {
+        first.keys().cloned().collect()
+    } else {
+        return Ok(String::new());
+    };
+    let cell = |s: &str| s.replace('|', "\\|").replace('\n', " ");
+
+    let mut out = format!("| {} |\n", headers.iter().map(|h| cell(h)).collect::<Vec<_>>().join(" | "));
+    out.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+    for row in &data {
+        let cells: Vec<String> = headers
+            .iter()
+            .map(|h| cell(row.get(h).map(String::as_str).unwrap_or_default()))
+            .collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    Ok(out)
+}
+
+/// Renders a list of data maps as a standalone HTML page with one sortable table.
+///
+/// # Arguments
+/// * `data` - List of data maps to render.
+/// * `line` - List of keys to use as columns. If empty, uses every key in order of appearance.
+///
+/// # Returns
+/// * `io::Result<String>` containing the page.
+pub fn render_html(data: &DataList, line: &[String]) -> io::Result<String> {
+    Ok(html_page("HALO results", &html_table(data, line)))
+}
+
+// Columns whose values select a color class, e.g. `severity-high` or `pass-false`
+const HTML_COLORED: [&str; 3] = ["severity", "status", "pass"];
+
+const HTML_STYLE: &str = "\
+body{font-family:system-ui,sans-serif;margin:2em;color:#222}
+table{border-collapse:collapse;margin:1em 0;font-size:.9em}
+th,td{border:1px solid #ccc;padding:.3em .6em;text-align:left;vertical-align:top}
+th{background:#f0f0f0;cursor:pointer;user-select:none}
+th:after{content:' \\2195';color:#999}
+.severity-critical{background:#7b1fa2;color:#fff}.severity-high{background:#d32f2f;color:#fff}
+.severity-medium{background:#f57c00;color:#fff}.severity-low{background:#fbc02d}
+.severity-info{background:#e3f2fd}
+.status-fail,.pass-false{color:#c62828;font-weight:bold}.status-pass,.pass-true{color:#2e7d32}
+.status-strict{color:#1565c0}
+";
+
+// Sorts a table by the clicked column, numerically if both cells are numbers
+const HTML_SCRIPT: &str = "\
+document.querySelectorAll('th').forEach(function(th){th.addEventListener('click',function(){
+var table=th.closest('table'),body=table.tBodies[0],i=th.cellIndex,asc=th.dataset.asc!=='1';
+var rows=Array.from(body.rows).sort(function(a,b){var x=a.cells[i].textContent,y=b.cells[i].textContent;
+var n=parseFloat(x)-parseFloat(y);var c=isNaN(n)?x.localeCompare(y):n;return asc?c:-c;});
+table.querySelectorAll('th').forEach(function(h){delete h.dataset.asc;});
+if(asc){th.dataset.asc='1';}rows.forEach(function(r){body.appendChild(r);});});});
+";
+
+/// Wraps `body` in a self-contained HTML page with the table styles and sorting script.
+pub fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>\n{1}</style>\n</head>\n<body>\n<h1>{0}</h1>\n{2}<script>\n{3}</script>\n</body>\n</html>\n",
+        html_escape(title),
+        HTML_STYLE,
+        body,
+        HTML_SCRIPT
+    )
+}
+
+/// Renders a list of data maps as an HTML table; empty if there is no data.
+///
+/// Columns are the keys in `line`, or every key in order of appearance, so rows with optional
+/// keys such as `error` still line up. Severity and status cells carry a color class.
+pub fn html_table(data: &DataList, line: &[String]) -> String {
+    let data = filter(data, line);
+    let mut headers: Vec<String> = line.to_vec();
+    if headers.is_empty() {
+        for key in data.iter().flat_map(|row| row.keys()) {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    if headers.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("<table>\n<thead><tr>");
+    for h in &headers {
+        out.push_str(&format!("<th>{}</th>", html_escape(h)));
+    }
+    out.push_str("</tr></thead>\n<tbody>\n");
+    for row in &data {
+        out.push_str("<tr>");
+        for h in &headers {
+            let value = row.get(h).map(String::as_str).unwrap_or_default();
+            if HTML_COLORED.contains(&h.as_str()) && !value.is_empty() {
+                out.push_str(&format!(
+                    "<td class=\"{}-{}\">{}</td>",
+                    h,
+                    value.to_lowercase(),
+                    html_escape(value)
+                ));
+            } else {
+                out.push_str(&format!("<td>{}</td>", html_escape(value)));
+            }
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</tbody>\n</table>\n");
+    out
+}
+
+/// Escapes `&`, `<`, `>` and quotes for HTML text and attribute values.
+pub fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
 /// Filters a list of data maps by the given keys.
 ///
 /// # Arguments
@@ -228,7 +894,21 @@ pub fn render_text(data: &DataList, line: &[String]) -> io::Result<String> {
 /// # Returns
 /// * `DataList` containing only the filtered key-value pairs.
 pub fn filter(data: &DataList, line: &[String]) -> DataList {
-    if line.is_empty() {
+    filter_lines(data, line, None)
+}
+
+/// Filters a list of data maps by exact keys and by regular expressions.
+///
+/// The keys of `line` come first, in its order, followed by the other pairs matching `regex`
+/// in record order. With a `regex`, records left without any pair are dropped, so
+/// `--line-regex nosuid --match-on values` lists only the mounts using it.
+///
+/// # Arguments
+/// * `data` - List of data maps to filter.
+/// * `line` - Keys to include. If empty and `regex` is `None`, returns all data.
+/// * `regex` - Patterns selecting further pairs by key, value or both.
+pub fn filter_lines(data: &DataList, line: &[String], regex: Option<&LineRegex>) -> DataList {
+    if line.is_empty() && regex.is_none() {
         return data.clone();
     }
     data.iter()
@@ -239,7 +919,57 @@ pub fn filter(data: &DataList, line: &[String]) -> DataList {
                     m.insert(f.clone(), val.clone());
                 }
             }
+            if let Some(regex) = regex {
+                for (key, value) in block {
+                    if !m.contains_key(key) && regex.matches(key, value) {
+                        m.insert(key.clone(), value.clone());
+                    }
+                }
+            }
             m
         })
+        .filter(|m| regex.is_none() || !m.is_empty())
         .collect()
 }
+
+/// Which side of a `key: value` pair [`LineRegex`] patterns are matched against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum MatchOn {
+    /// Match keys, e.g. `^model name`
+    #[default]
+    Keys,
+    /// Match values, e.g. `nosuid`
+    Values,
+    /// Match either
+    Both,
+}
+
+/// Regular expressions selecting pairs of parsed data; a pair is kept if any pattern matches.
+#[derive(Debug, Clone)]
+pub struct LineRegex {
+    patterns: Vec<Regex>,
+    match_on: MatchOn,
+}
+
+impl LineRegex {
+    /// Compiles `patterns`; the error names the first invalid one.
+    pub fn new(patterns: &[String], match_on: MatchOn) -> Result<Self, HaloError> {
+        let patterns = patterns
+            .iter()
+            .map(|p| {
+                Regex::new(p).map_err(|e| HaloError::InvalidArgument(format!("Invalid regex '{}': {}", p, e)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(LineRegex { patterns, match_on })
+    }
+
+    /// Whether a pattern matches `key` or `value`, as selected by [`MatchOn`].
+    pub fn matches(&self, key: &str, value: &str) -> bool {
+        self.patterns.iter().any(|p| match self.match_on {
+            MatchOn::Keys => p.is_match(key),
+            MatchOn::Values => p.is_match(value),
+            MatchOn::Both => p.is_match(key) || p.is_match(value),
+        })
+    }
+}
@@ -0,0 +1,1601 @@
+//! Unified audit report for HALO.
+//!
+//! An [`AuditReport`] collects the results of every audit kind in one run: permission results,
+//! ownership results and content/policy findings. It is returned by the [`Audit`] builder and
+//! can be rendered like any other result with [`Renderable`]. Failures suppressed by a
+//! [waiver](crate::waiver) are kept apart in `waived`. The HTML format renders a standalone
+//! page with a summary and one section per target, e.g. to mail to people who do not read JSON.
+//!
+//! Reports from the builder carry [`RunMetadata`]: host, kernel, HALO version, start time,
+//! targets run and duration. Serialized reports hold the metadata and a [`ReportSummary`] next
+//! to the results, so consumers do not have to reconstruct the context; text, Markdown, pretty
+//! and HTML output show them as well. CSV holds the results only. JSON reports start with a
+//! `schema_version` and follow the [schema](crate::schema).
+//!
+//! [`Audit`]: crate::Audit
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{Audit, Renderable, Target};
+//! let report = Audit::new().target(Target::Sys).run().unwrap();
+//! println!("{} failures", report.failure_count());
+//! report.render_and_print(Some("json"));
+//! ```
+use crate::ack::{Acknowledgment, apply_acknowledgments};
+use crate::audit::finding::Finding;
+use crate::audit::ownership::ownership::OwnershipResult;
+use crate::audit::permissions::audit_permissions::{Importance, PermissionResults, Severity, Status};
+use crate::audit::sysroot;
+use crate::error::HaloError;
+use crate::observer::ResultRef;
+use crate::audit::scan::Truncation;
+use crate::render_output::{
+    DataList, DataMap, GroupBy, OutputFormat, RenderOptions, Renderable, SortKey, Style, html_escape, html_page,
+    html_table, paint, render_csv, render_json, render_jsonl, render_markdown, render_rows, render_text,
+};
+use crate::risk::{RiskScore, UNATTRIBUTED};
+use crate::schema::SCHEMA_VERSION;
+use crate::teams::{TeamMap, TeamSummary, UNASSIGNED};
+use crate::waiver::{OWNERSHIP_RULE, PERMISSIONS_RULE, WaivedResult, Waiver};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Results of all audits in a single run.
+///
+/// Serialized with the [`SCHEMA_VERSION`] first and its [`summary`](Self::summary) after the
+/// metadata. Deserializing a serialized report gives back the results, metadata and target of
+/// each result, so stored reports can be diffed, trended and fixed like fresh ones.
+#[derive(Debug, Clone, Default)]
+pub struct AuditReport {
+    /// Context of the run; set by the [`Audit`](crate::Audit) builder
+    pub metadata: Option<RunMetadata>,
+    /// Permission results from targets, rules and config files
+    pub permissions: Vec<PermissionResults>,
+    /// Ownership results from rules and config files
+    pub ownership: Vec<OwnershipResult>,
+    /// Content and policy findings
+    pub findings: Vec<Finding>,
+    /// Failures suppressed by a waiver; not counted as failures
+    pub waived: Vec<WaivedResult>,
+    /// Target that produced each result, keyed by result kind and path or finding id
+    pub(crate) origins: HashMap<String, String>,
+}
+
+impl Serialize for AuditReport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut report = serializer.serialize_struct("AuditReport", 8)?;
+        report.serialize_field("schema_version", &SCHEMA_VERSION)?;
+        match &self.metadata {
+            Some(metadata) => report.serialize_field("metadata", metadata)?,
+            None => report.skip_field("metadata")?,
+        }
+        report.serialize_field("summary", &self.summary())?;
+        report.serialize_field("permissions", &self.permissions)?;
+        if self.ownership.is_empty() {
+            report.skip_field("ownership")?;
+        } else {
+            report.serialize_field("ownership", &self.ownership)?;
+        }
+        report.serialize_field("findings", &self.findings)?;
+        if self.waived.is_empty() {
+            report.skip_field("waived")?;
+        } else {
+            report.serialize_field("waived", &self.waived)?;
+        }
+        if self.origins.is_empty() {
+            report.skip_field("origins")?;
+        } else {
+            let origins: BTreeMap<_, _> = self.origins.iter().collect();
+            report.serialize_field("origins", &origins)?;
+        }
+        report.end()
+    }
+}
+
+// Fields read back from a serialized report; the summary is derived from the results, except
+// for the timings of the run
+#[derive(Deserialize)]
+struct StoredReport {
+    metadata: Option<RunMetadata>,
+    #[serde(default)]
+    permissions: Vec<PermissionResults>,
+    #[serde(default)]
+    ownership: Vec<OwnershipResult>,
+    #[serde(default)]
+    findings: Vec<Finding>,
+    #[serde(default)]
+    waived: Vec<WaivedResult>,
+    #[serde(default)]
+    origins: HashMap<String, String>,
+    #[serde(default)]
+    summary: StoredSummary,
+}
+
+// The run's timings, kept in the summary rather than the metadata
+#[derive(Deserialize, Default)]
+struct StoredSummary {
+    #[serde(default)]
+    timings: Vec<TargetTiming>,
+}
+
+impl<'de> Deserialize<'de> for AuditReport {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut stored = StoredReport::deserialize(deserializer)?;
+        // Reports written before the timings moved to the summary have them in the metadata
+        if let Some(metadata) = stored.metadata.as_mut()
+            && metadata.timings.is_empty()
+        {
+            metadata.timings = stored.summary.timings;
+        }
+        Ok(AuditReport {
+            metadata: stored.metadata,
+            permissions: stored.permissions,
+            ownership: stored.ownership,
+            findings: stored.findings,
+            waived: stored.waived,
+            origins: stored.origins,
+        })
+    }
+}
+
+/// Context of the run that produced a report.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunMetadata {
+    pub hostname: String,
+    /// Kernel release, e.g. `6.8.0-45-generic`
+    pub kernel: String,
+    pub halo_version: String,
+    /// Start of the run in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Targets run, in order: target names, the CIS level, `rules` and `config`
+    pub targets: Vec<String>,
+    pub duration_ms: u64,
+    /// Alternate root audited instead of the running system, see [`crate::audit::sysroot`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<PathBuf>,
+    /// Walks stopped early by a scan limit, see [`ScanLimits`](crate::audit::scan::ScanLimits)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub truncated: Vec<Truncation>,
+    /// Wall time and files scanned of each target, in run order; serialized in the report
+    /// summary only, see [`ReportSummary::timings`]
+    #[serde(default, skip_serializing)]
+    pub timings: Vec<TargetTiming>,
+    /// The run was cancelled, see [`CancelToken`](crate::audit::scan::CancelToken); its
+    /// results are partial
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+}
+
+/// Wall time and throughput of one target of a run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TargetTiming {
+    /// Target name as in [`RunMetadata::targets`]
+    pub target: String,
+    pub duration_ms: u64,
+    pub results: usize,
+    /// Paths checked by permission walks and ownership rules, including passing ones
+    /// dropped by a low-footprint run
+    pub files: u64,
+    /// Files scanned per second; 0 if the target took no measurable time
+    pub files_per_sec: u64,
+}
+
+impl TargetTiming {
+    /// Timing of `files` scanned by `target` in `elapsed`.
+    pub fn new(target: &str, elapsed: Duration, results: usize, files: u64) -> Self {
+        let secs = elapsed.as_secs_f64();
+        TargetTiming {
+            target: target.to_string(),
+            duration_ms: elapsed.as_millis() as u64,
+            results,
+            files,
+            files_per_sec: if secs > 0.0 { (files as f64 / secs).round() as u64 } else { 0 },
+        }
+    }
+}
+
+impl Renderable for TargetTiming {
+    fn to_datalist(&self) -> DataList {
+        let mut map = DataMap::new();
+        map.insert("target".to_string(), self.target.clone());
+        map.insert("duration_ms".to_string(), self.duration_ms.to_string());
+        map.insert("results".to_string(), self.results.to_string());
+        map.insert("files".to_string(), self.files.to_string());
+        map.insert("files_per_sec".to_string(), self.files_per_sec.to_string());
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        let mut output = format!("{}: {} ms, {} results", self.target, self.duration_ms, self.results);
+        if self.files > 0 {
+            output.push_str(&format!(", {} files ({} files/s)", self.files, self.files_per_sec));
+        }
+        output
+    }
+}
+
+// One indented line per target
+fn pretty_timings(timings: &[TargetTiming]) -> String {
+    timings.iter().map(|t| format!("  {}\n", t.pretty_print())).collect()
+}
+
+impl RunMetadata {
+    /// Host name, kernel release, HALO version and the current time; targets and duration are
+    /// left for the run to fill in. For an alternate root the host name is the one in its
+    /// `/etc/hostname` and the kernel is left empty, as the running one is not the image's.
+    pub fn capture() -> Self {
+        let read = |path: &str| fs::read_to_string(path).map(|s| s.trim().to_string()).unwrap_or_default();
+        let root = sysroot::root();
+        let (hostname, kernel) = match root {
+            Some(_) => (
+                sysroot::read_to_string(Path::new("/etc/hostname"))
+                    .map(|s| s.trim().to_string())
+                    .unwrap_or_default(),
+                String::new(),
+            ),
+            None => (read("/proc/sys/kernel/hostname"), read("/proc/sys/kernel/osrelease")),
+        };
+        Self {
+            hostname,
+            kernel,
+            root,
+            halo_version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Renderable for RunMetadata {
+    fn to_datalist(&self) -> DataList {
+        let mut map = DataMap::new();
+        map.insert("hostname".to_string(), self.hostname.clone());
+        map.insert("kernel".to_string(), self.kernel.clone());
+        map.insert("halo_version".to_string(), self.halo_version.clone());
+        map.insert("timestamp".to_string(), self.timestamp.to_string());
+        map.insert("targets".to_string(), self.targets.join(" "));
+        map.insert("duration_ms".to_string(), self.duration_ms.to_string());
+        if let Some(root) = &self.root {
+            map.insert("root".to_string(), root.display().to_string());
+        }
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        let system = match &self.root {
+            Some(root) => format!("root {}", root.display()),
+            None => format!("kernel {}", self.kernel),
+        };
+        let mut output = format!(
+            "HALO {} on {} ({}), started {}, took {} ms; targets: {}\n",
+            self.halo_version,
+            self.hostname,
+            system,
+            self.timestamp,
+            self.duration_ms,
+            self.targets.join(", ")
+        );
+        output.push_str(&pretty_timings(&self.timings));
+        output
+    }
+}
+
+/// Open failures of one severity, as counted by [`AuditReport::by_severity`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SeverityCount {
+    pub severity: Severity,
+    pub failures: usize,
+}
+
+/// Results by [`Status`], e.g. of the permission results of a report:
+/// `report.permissions.iter().map(|r| &r.status).collect::<StatusCounts>()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct StatusCounts {
+    pub passed: usize,
+    /// Stricter than expected
+    pub strict: usize,
+    pub failed: usize,
+}
+
+impl<'a> FromIterator<&'a Status> for StatusCounts {
+    fn from_iter<I: IntoIterator<Item = &'a Status>>(statuses: I) -> Self {
+        let mut counts = StatusCounts::default();
+        for status in statuses {
+            match status {
+                Status::Pass => counts.passed += 1,
+                Status::Strict => counts.strict += 1,
+                Status::Fail => counts.failed += 1,
+            }
+        }
+        counts
+    }
+}
+
+/// Result counts of a report, e.g. for `check --summary-only`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReportSummary {
+    pub checked: usize,
+    pub passed: usize,
+    /// Permissions or findings stricter than expected
+    pub strict: usize,
+    /// Open failures; acknowledged and waived failures are counted apart
+    pub failed: usize,
+    pub acknowledged: usize,
+    pub waived: usize,
+    /// Open failures per severity
+    pub critical: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    pub info: usize,
+    pub risk_score: u32,
+    /// Walks stopped early by a scan limit; their results are incomplete
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub truncated: Vec<Truncation>,
+    /// Wall time and files scanned of each target
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub timings: Vec<TargetTiming>,
+    /// The run was cancelled before it completed
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+}
+
+/// Result counts at one point of a run, used to attribute the results added afterwards.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ReportMark {
+    pub(crate) permissions: usize,
+    pub(crate) ownership: usize,
+    pub(crate) findings: usize,
+}
+
+/// Check id of findings reporting two rules with different expectations for one path, see
+/// [`AuditReport::dedup`].
+pub const RULE_CONFLICT: &str = "rule-conflict";
+
+// Identifies the directory entry at `path` for deduplication: the parent is canonicalized,
+// once per directory, and the entry itself is not, so a symlink stays apart from its target
+fn entry_key(path: &Path, parents: &mut HashMap<PathBuf, PathBuf>) -> PathBuf {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return path.to_path_buf();
+    };
+    let parent = parents
+        .entry(parent.to_path_buf())
+        .or_insert_with(|| sysroot::canonicalize(parent).unwrap_or_else(|_| parent.to_path_buf()));
+    parent.join(name)
+}
+
+// Identifies a result for target attribution; survives filtering and reordering
+fn origin_key(kind: &str, id: &str) -> String {
+    format!("{}:{}", kind, id)
+}
+
+impl AuditReport {
+    /// Reads a JSON report written by `check --format json --store` or recorded with
+    /// `check --record`. A bare array of permission results, as stored by earlier versions,
+    /// is read as a report holding just those.
+    pub fn from_file(path: &Path) -> Result<Self, HaloError> {
+        let invalid = |e: serde_json::Error| HaloError::parse(format!("JSON report {}", path.display()), e);
+        let text = fs::read_to_string(path).map_err(|e| HaloError::io(path, e))?;
+        let value: Value = serde_json::from_str(&text).map_err(invalid)?;
+        let report = match value {
+            Value::Array(_) => serde_json::from_value(value).map(|permissions| AuditReport {
+                permissions,
+                ..Default::default()
+            }),
+            _ => serde_json::from_value(value),
+        };
+        report.map_err(invalid)
+    }
+
+    /// Permission results with status `Fail` that are not acknowledged.
+    pub fn failed_permissions(&self) -> impl Iterator<Item = &PermissionResults> {
+        self.permissions
+            .iter()
+            .filter(|r| r.status == Status::Fail && r.acknowledged.is_none())
+    }
+
+    /// Number of failing permission, ownership and policy results.
+    ///
+    /// Acknowledged findings are not counted.
+    pub fn failure_count(&self) -> usize {
+        self.failures().count()
+    }
+
+    /// Number of failing results covered by an acknowledgment.
+    pub fn acknowledged_count(&self) -> usize {
+        self.permissions.iter().filter(|r| r.acknowledged.is_some()).count()
+            + self.ownership.iter().filter(|r| r.acknowledged.is_some()).count()
+            + self.findings.iter().filter(|f| f.acknowledged.is_some()).count()
+    }
+
+    /// Marks failing results covered by `acks` as acknowledged.
+    pub fn apply_acknowledgments(&mut self, acks: &[Acknowledgment]) -> usize {
+        apply_acknowledgments(&mut self.permissions, acks)
+            + apply_acknowledgments(&mut self.ownership, acks)
+            + apply_acknowledgments(&mut self.findings, acks)
+    }
+
+    /// Current result counts, to pass to [`attribute`](Self::attribute) later.
+    pub(crate) fn mark(&self) -> ReportMark {
+        ReportMark {
+            permissions: self.permissions.len(),
+            ownership: self.ownership.len(),
+            findings: self.findings.len(),
+        }
+    }
+
+    /// Attributes the results added since `mark` to `target`.
+    ///
+    /// A result already attributed, e.g. a path covered by two targets, keeps its first target.
+    pub(crate) fn attribute(&mut self, mark: ReportMark, target: &str) {
+        let keys = self.permissions[mark.permissions..]
+            .iter()
+            .map(|r| origin_key("permissions", &r.path.to_string_lossy()))
+            .chain(
+                self.ownership[mark.ownership..]
+                    .iter()
+                    .map(|r| origin_key("ownership", &r.path.to_string_lossy())),
+            )
+            .chain(self.findings[mark.findings..].iter().map(|f| origin_key("finding", &f.id)))
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.origins.entry(key).or_insert_with(|| target.to_string());
+        }
+    }
+
+    /// Target that produced a result with `key`, or [`UNATTRIBUTED`].
+    fn origin(&self, key: String) -> &str {
+        self.origins.get(&key).map_or(UNATTRIBUTED, String::as_str)
+    }
+
+    /// Target that produced `result`, or [`UNATTRIBUTED`].
+    pub fn target_of(&self, result: ResultRef<'_>) -> &str {
+        match result {
+            ResultRef::Permission(r) => self.origin(origin_key("permissions", &r.path.to_string_lossy())),
+            ResultRef::Ownership(r) => self.origin(origin_key("ownership", &r.path.to_string_lossy())),
+            ResultRef::Finding(f) => self.origin(origin_key("finding", &f.id)),
+        }
+    }
+
+    /// Failing result with the stable id `id`, acknowledged or not.
+    pub fn failure_by_id(&self, id: &str) -> Option<ResultRef<'_>> {
+        self.permissions
+            .iter()
+            .filter(|r| r.status == Status::Fail)
+            .map(ResultRef::Permission)
+            .chain(self.ownership.iter().filter(|r| !r.pass).map(ResultRef::Ownership))
+            .chain(self.findings.iter().filter(|f| f.is_failure()).map(ResultRef::Finding))
+            .find(|r| r.id() == id)
+    }
+
+    /// Open failures of every kind: unacknowledged failing permissions, then ownership, then
+    /// findings.
+    pub fn failures(&self) -> impl Iterator<Item = ResultRef<'_>> {
+        self.failed_permissions()
+            .map(ResultRef::Permission)
+            .chain(
+                self.ownership
+                    .iter()
+                    .filter(|r| !r.pass && r.acknowledged.is_none())
+                    .map(ResultRef::Ownership),
+            )
+            .chain(self.findings.iter().filter(|f| f.is_open_failure()).map(ResultRef::Finding))
+    }
+
+    /// Number of open failures per severity, most severe first; severities without
+    /// failures are left out.
+    pub fn by_severity(&self) -> Vec<SeverityCount> {
+        let mut counts: Vec<SeverityCount> = Vec::new();
+        for failure in self.failures() {
+            match counts.iter_mut().find(|c| &c.severity == failure.severity()) {
+                Some(count) => count.failures += 1,
+                None => counts.push(SeverityCount {
+                    severity: failure.severity().clone(),
+                    failures: 1,
+                }),
+            }
+        }
+        counts.sort_by(|a, b| b.severity.cmp(&a.severity));
+        counts
+    }
+
+    /// Results grouped by the target that produced them, in order of each target's first
+    /// result.
+    pub fn by_target(&self) -> Vec<TargetResults<'_>> {
+        fn group<'a, 'r>(groups: &'r mut Vec<TargetResults<'a>>, target: &'a str) -> &'r mut TargetResults<'a> {
+            match groups.iter().position(|g| g.target == target) {
+                Some(i) => &mut groups[i],
+                None => {
+                    groups.push(TargetResults { target, ..Default::default() });
+                    groups.last_mut().unwrap()
+                }
+            }
+        }
+        let mut groups: Vec<TargetResults> = Vec::new();
+        for r in &self.permissions {
+            group(&mut groups, self.target_of(ResultRef::Permission(r))).permissions.push(r);
+        }
+        for r in &self.ownership {
+            group(&mut groups, self.target_of(ResultRef::Ownership(r))).ownership.push(r);
+        }
+        for f in &self.findings {
+            group(&mut groups, self.target_of(ResultRef::Finding(f))).findings.push(f);
+        }
+        groups
+    }
+
+    /// Host risk score of the open failures, with subtotals per target.
+    ///
+    /// See [`crate::risk`] for the weights.
+    pub fn risk_score(&self) -> RiskScore {
+        let mut risk = RiskScore::default();
+        for failure in self.failures() {
+            let importance = match failure {
+                ResultRef::Permission(r) => &r.importance,
+                _ => &Importance::Medium,
+            };
+            risk.add(self.target_of(failure), failure.severity(), importance);
+        }
+        risk.sort();
+        risk
+    }
+
+    /// Moves failures covered by an unexpired waiver into `waived`.
+    ///
+    /// # Arguments
+    /// * `waivers` - Waivers from the config
+    /// * `today` - Current day as days since the Unix epoch, see [`crate::waiver::today`]
+    ///
+    /// # Returns
+    /// The number of failures waived.
+    pub fn apply_waivers(&mut self, waivers: &[Waiver], today: i64) -> usize {
+        let active: Vec<&Waiver> = waivers.iter().filter(|w| !w.is_expired(today)).collect();
+        if active.is_empty() {
+            return 0;
+        }
+        // A result matches by its kind or check id, or by the id of the rule that produced it
+        let find = |rule: &str, rule_id: Option<&String>, path: &std::path::Path| {
+            active.iter().copied().find(|w| {
+                w.matches(rule, path) || rule_id.is_some_and(|id| w.matches(id, path))
+            })
+        };
+        let waive = |waiver: &Waiver, rule: &str, path: &std::path::Path, severity: &Severity, message: String| {
+            WaivedResult {
+                rule: rule.to_string(),
+                path: path.to_path_buf(),
+                severity: severity.clone(),
+                message,
+                reason: waiver.reason.clone(),
+                expires: waiver.expires.clone(),
+            }
+        };
+        let before = self.waived.len();
+
+        let mut kept = Vec::with_capacity(self.permissions.len());
+        for r in self.permissions.drain(..) {
+            match find(PERMISSIONS_RULE, r.rule_id.as_ref(), &r.path).filter(|_| r.status == Status::Fail) {
+                Some(w) => {
+                    let message = format!("found {:o}, expected {:o}", r.found_mode, r.expected_mode);
+                    let rule = r.rule_id.as_deref().unwrap_or(PERMISSIONS_RULE);
+                    self.waived.push(waive(w, rule, &r.path, &r.severity, message));
+                }
+                None => kept.push(r),
+            }
+        }
+        self.permissions = kept;
+
+        let mut kept = Vec::with_capacity(self.ownership.len());
+        for r in self.ownership.drain(..) {
+            match find(OWNERSHIP_RULE, r.rule_id.as_ref(), &r.path).filter(|_| !r.pass) {
+                Some(w) => {
+                    let message = format!(
+                        "found {:?}:{:?}, expected {:?}:{:?}",
+                        r.found_uid, r.found_gid, r.expected_uid, r.expected_gid
+                    );
+                    let rule = r.rule_id.as_deref().unwrap_or(OWNERSHIP_RULE);
+                    self.waived.push(waive(w, rule, &r.path, &r.severity, message));
+                }
+                None => kept.push(r),
+            }
+        }
+        self.ownership = kept;
+
+        let mut kept = Vec::with_capacity(self.findings.len());
+        for f in self.findings.drain(..) {
+            match find(&f.check, None, &f.path).filter(|_| f.is_open_failure()) {
+                Some(w) => self.waived.push(waive(w, &f.check, &f.path, &f.severity, f.message.clone())),
+                None => kept.push(f),
+            }
+        }
+        self.findings = kept;
+
+        self.waived.len() - before
+    }
+
+    /// Removes results that several targets, rules or configs produced for the same path,
+    /// e.g. `/etc/passwd` checked by `--target all` and by a config file.
+    ///
+    /// Paths are compared with their parent directory canonicalized, so `/etc/../etc/passwd`
+    /// matches `/etc/passwd`. Of two permission results for a path, the one with the stricter
+    /// expected mode is kept. If neither mode is a subset of the other, the first result is
+    /// kept and a failing [`RULE_CONFLICT`] finding with severity `Info` names both modes;
+    /// ownership results expecting different owners are flagged the same way. An unset uid or
+    /// gid matches any, and of two such ownership results the one expecting more ids is kept.
+    /// Findings with the same id are kept once.
+    ///
+    /// # Returns
+    /// The number of [`RULE_CONFLICT`] findings added at the end of `findings`.
+    pub fn dedup(&mut self) -> usize {
+        let mut parents = HashMap::new();
+        let mut conflicts = Vec::new();
+
+        let mut kept: Vec<PermissionResults> = Vec::with_capacity(self.permissions.len());
+        let mut seen: HashMap<PathBuf, usize> = HashMap::new();
+        for r in self.permissions.drain(..) {
+            let key = entry_key(&r.path, &mut parents);
+            let Some(&i) = seen.get(&key) else {
+                seen.insert(key, kept.len());
+                kept.push(r);
+                continue;
+            };
+            let (first, mode) = (kept[i].expected_mode, r.expected_mode);
+            if mode != first && mode & !first == 0 {
+                kept[i] = r;
+            } else if first & !mode != 0 {
+                let subject = format!("{:o} vs {:o}", first, mode);
+                let message = format!("Rules expect mode {:o} and mode {:o}; checked against {:o}", first, mode, first);
+                conflicts.push(Finding::fail(RULE_CONFLICT, &kept[i].path, Severity::Info, message).with_subject(subject));
+            }
+        }
+        self.permissions = kept;
+
+        let owner = |uid: Option<u32>, gid: Option<u32>| {
+            let id = |id: Option<u32>| id.map_or("-".to_string(), |id| id.to_string());
+            format!("{}:{}", id(uid), id(gid))
+        };
+        let mut kept: Vec<OwnershipResult> = Vec::with_capacity(self.ownership.len());
+        let mut seen: HashMap<PathBuf, usize> = HashMap::new();
+        for r in self.ownership.drain(..) {
+            let key = entry_key(&r.path, &mut parents);
+            let Some(&i) = seen.get(&key) else {
+                seen.insert(key, kept.len());
+                kept.push(r);
+                continue;
+            };
+            // Unset ids match any; the result expecting more ids is the stricter one
+            let (first, other) = ([kept[i].expected_uid, kept[i].expected_gid], [r.expected_uid, r.expected_gid]);
+            let set = |ids: [Option<u32>; 2]| ids.iter().flatten().count();
+            if first.iter().zip(&other).all(|(a, b)| a.is_none() || b.is_none() || a == b) {
+                if set(other) > set(first) {
+                    kept[i] = r;
+                }
+            } else {
+                let first = owner(kept[i].expected_uid, kept[i].expected_gid);
+                let expected = owner(r.expected_uid, r.expected_gid);
+                let subject = format!("{} vs {}", first, expected);
+                let message = format!("Rules expect owner {} and owner {}; checked against {}", first, expected, first);
+                conflicts.push(Finding::fail(RULE_CONFLICT, &kept[i].path, Severity::Info, message).with_subject(subject));
+            }
+        }
+        self.ownership = kept;
+
+        let mut ids = HashSet::new();
+        self.findings.retain(|f| ids.insert(f.id.clone()));
+        conflicts.retain(|f| ids.insert(f.id.clone()));
+        let added = conflicts.len();
+        self.findings.extend(conflicts);
+        added
+    }
+
+    /// Returns true if nothing failed.
+    pub fn is_clean(&self) -> bool {
+        self.failure_count() == 0
+    }
+
+    /// Total number of results in the report.
+    pub fn len(&self) -> usize {
+        self.permissions.len() + self.ownership.len() + self.findings.len()
+    }
+
+    /// Returns true if the report holds no results.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Counts of passing, failing and suppressed results, with open failures per severity.
+    pub fn summary(&self) -> ReportSummary {
+        let mut summary = ReportSummary {
+            checked: self.len(),
+            failed: self.failure_count(),
+            acknowledged: self.acknowledged_count(),
+            waived: self.waived.len(),
+            risk_score: self.risk_score().total,
+            truncated: self.metadata.as_ref().map(|m| m.truncated.clone()).unwrap_or_default(),
+            timings: self.metadata.as_ref().map(|m| m.timings.clone()).unwrap_or_default(),
+            cancelled: self.metadata.as_ref().is_some_and(|m| m.cancelled),
+            ..Default::default()
+        };
+        let statuses: StatusCounts = self
+            .permissions
+            .iter()
+            .map(|r| &r.status)
+            .chain(self.findings.iter().map(|f| &f.status))
+            .collect();
+        summary.passed = statuses.passed + self.ownership.iter().filter(|r| r.pass).count();
+        summary.strict = statuses.strict;
+        for count in self.by_severity() {
+            match count.severity {
+                Severity::Critical => summary.critical += count.failures,
+                Severity::High => summary.high += count.failures,
+                Severity::Medium => summary.medium += count.failures,
+                Severity::Low => summary.low += count.failures,
+                Severity::Info | Severity::None => summary.info += count.failures,
+            }
+        }
+        summary
+    }
+
+    /// Orders each result list by `key`; ties keep their audit order.
+    pub fn sort(&mut self, key: SortKey) {
+        let options = RenderOptions::default().sort(key);
+        self.permissions = sorted(std::mem::take(&mut self.permissions), &options);
+        self.ownership = sorted(std::mem::take(&mut self.ownership), &options);
+        self.findings = sorted(std::mem::take(&mut self.findings), &options);
+        self.waived = sorted(std::mem::take(&mut self.waived), &options);
+    }
+
+    // Every result with its kind and target, for grouped output
+    fn entries(&self) -> Vec<ReportEntry> {
+        let mut entries = Vec::new();
+        for r in &self.permissions {
+            entries.push(ReportEntry::new("permission", self.target_of(ResultRef::Permission(r)), r));
+        }
+        for r in &self.ownership {
+            entries.push(ReportEntry::new("ownership", self.target_of(ResultRef::Ownership(r)), r));
+        }
+        for f in &self.findings {
+            entries.push(ReportEntry::new("finding", self.target_of(ResultRef::Finding(f)), f));
+        }
+        for w in &self.waived {
+            entries.push(ReportEntry::new("waived", UNATTRIBUTED, w));
+        }
+        entries
+    }
+
+    /// Number of open failures at least as severe as `min`, for gating without filtering.
+    pub fn failure_count_at_least(&self, min: &Severity) -> usize {
+        self.failures().filter(|f| f.severity().at_least(min)).count()
+    }
+
+    /// Drops failures less severe than `min`; passing results are kept.
+    pub fn retain_min_severity(&mut self, min: &Severity) {
+        self.permissions
+            .retain(|r| r.status != Status::Fail || r.severity.at_least(min));
+        self.ownership.retain(|r| r.pass || r.severity.at_least(min));
+        self.findings
+            .retain(|f| !f.is_failure() || f.severity.at_least(min));
+    }
+
+    /// Drops all passing results, keeping failures and stricter-than-expected permissions.
+    pub fn retain_failures(&mut self) {
+        self.permissions.retain(|r| r.status != Status::Pass);
+        self.ownership.retain(|r| !r.pass);
+        self.findings.retain(|f| f.is_failure());
+    }
+
+    /// Keeps only results for paths owned by `team` in `teams`.
+    pub fn retain_team(&mut self, teams: &TeamMap, team: &str) {
+        self.permissions.retain(|r| teams.team_for(&r.path) == team);
+        self.ownership.retain(|r| teams.team_for(&r.path) == team);
+        self.findings.retain(|f| teams.team_for(&f.path) == team);
+    }
+
+    /// Result counts per team, in team map order, with unassigned paths last.
+    ///
+    /// Teams without results in this report are omitted.
+    pub fn team_summaries(&self, teams: &TeamMap) -> Vec<TeamSummary> {
+        let mut summaries: Vec<TeamSummary> = teams
+            .teams()
+            .into_iter()
+            .chain([UNASSIGNED])
+            .map(|team| TeamSummary {
+                team: team.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let mut count = |path: &std::path::Path, failed: bool, acknowledged: bool| {
+            let team = teams.team_for(path);
+            if let Some(s) = summaries.iter_mut().find(|s| s.team == team) {
+                s.checked += 1;
+                s.failed += usize::from(failed);
+                s.acknowledged += usize::from(acknowledged);
+            }
+        };
+        for r in &self.permissions {
+            let acknowledged = r.acknowledged.is_some();
+            count(&r.path, r.status == Status::Fail && !acknowledged, acknowledged);
+        }
+        for r in &self.ownership {
+            count(&r.path, !r.pass && r.acknowledged.is_none(), r.acknowledged.is_some());
+        }
+        for f in &self.findings {
+            count(&f.path, f.is_open_failure(), f.acknowledged.is_some());
+        }
+        summaries.retain(|s| s.checked > 0);
+        summaries
+    }
+}
+
+impl Renderable for ReportSummary {
+    fn to_datalist(&self) -> DataList {
+        let mut map = DataMap::new();
+        let counts = [
+            ("checked", self.checked),
+            ("passed", self.passed),
+            ("strict", self.strict),
+            ("failed", self.failed),
+            ("acknowledged", self.acknowledged),
+            ("waived", self.waived),
+            ("critical", self.critical),
+            ("high", self.high),
+            ("medium", self.medium),
+            ("low", self.low),
+            ("info", self.info),
+        ];
+        for (key, count) in counts {
+            map.insert(key.to_string(), count.to_string());
+        }
+        map.insert("risk_score".to_string(), self.risk_score.to_string());
+        if !self.truncated.is_empty() {
+            map.insert("truncated".to_string(), self.truncated.len().to_string());
+        }
+        if self.cancelled {
+            map.insert("cancelled".to_string(), "true".to_string());
+        }
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        let by_severity = [
+            ("critical", self.critical, Severity::Critical),
+            ("high", self.high, Severity::High),
+            ("medium", self.medium, Severity::Medium),
+            ("low", self.low, Severity::Low),
+            ("info", self.info, Severity::Info),
+        ]
+        .iter()
+        .filter(|(_, count, _)| *count > 0)
+        .map(|(name, count, severity)| paint(&format!("{} {}", count, name), severity.style()))
+        .collect::<Vec<_>>();
+        let mut output = format!(
+            "Summary: {} checked, {} passed, {} strict, {} failed",
+            self.checked,
+            paint(&self.passed.to_string(), Status::Pass.style()),
+            self.strict,
+            paint(&self.failed.to_string(), Status::Fail.style())
+        );
+        if !by_severity.is_empty() {
+            output.push_str(&format!(" ({})", by_severity.join(", ")));
+        }
+        output.push_str(&format!(
+            "\n{} acknowledged, {} waived, risk score {}\n",
+            self.acknowledged, self.waived, self.risk_score
+        ));
+        if !self.timings.is_empty() {
+            output.push_str("Timing:\n");
+            output.push_str(&pretty_timings(&self.timings));
+        }
+        for truncation in &self.truncated {
+            output.push_str(&paint(&format!("Truncated: {}", truncation), Severity::Medium.style()));
+            output.push('\n');
+        }
+        if self.cancelled {
+            output.push_str(&paint("Cancelled: the results are partial", Severity::Medium.style()));
+            output.push('\n');
+        }
+        output
+    }
+}
+
+impl AuditReport {
+    // Pretty results, by target if any are attributed
+    fn pretty_results(&self) -> String {
+        let groups = self.by_target();
+        if groups.iter().any(|g| g.target != UNATTRIBUTED) {
+            let mut output = String::new();
+            for group in &groups {
+                output.push_str(&paint(
+                    &format!("== {} ({} results, {} failed) ==", group.target, group.len(), group.failed()),
+                    Style::Bold,
+                ));
+                output.push('\n');
+                let sections: [(&str, Vec<String>); 3] = [
+                    ("Permissions", group.permissions.iter().map(|r| r.pretty_print()).collect()),
+                    ("Ownership", group.ownership.iter().map(|r| r.pretty_print()).collect()),
+                    ("Findings", group.findings.iter().map(|f| f.pretty_print()).collect()),
+                ];
+                for (title, lines) in sections.iter().filter(|(_, lines)| !lines.is_empty()) {
+                    output.push_str(&format!("{}:\n", title));
+                    for line in lines {
+                        output.push_str(&format!("  {}\n", line));
+                    }
+                }
+                output.push('\n');
+            }
+            if !self.waived.is_empty() {
+                output.push_str("Waived:\n");
+                output.push_str(&self.waived.pretty_print());
+            }
+            return output;
+        }
+        let mut output = String::new();
+        if !self.permissions.is_empty() {
+            output.push_str("Permissions:\n");
+            output.push_str(&self.permissions.pretty_print());
+        }
+        if !self.ownership.is_empty() {
+            output.push_str("\nOwnership:\n");
+            output.push_str(&self.ownership.pretty_print());
+        }
+        if !self.findings.is_empty() {
+            output.push_str("\nFindings:\n");
+            output.push_str(&self.findings.pretty_print());
+        }
+        if !self.waived.is_empty() {
+            output.push_str("\nWaived:\n");
+            output.push_str(&self.waived.pretty_print());
+        }
+        if output.is_empty() {
+            output.push_str("No results found.\n");
+        }
+        output
+    }
+}
+
+impl Renderable for AuditReport {
+    fn to_datalist(&self) -> DataList {
+        let mut data = self.permissions.to_datalist();
+        data.extend(self.ownership.to_datalist());
+        data.extend(self.findings.to_datalist());
+        data.extend(self.waived.to_datalist());
+        data
+    }
+
+    fn pretty_print(&self) -> String {
+        let mut output = self.metadata.as_ref().map(|m| m.pretty_print() + "\n").unwrap_or_default();
+        output.push_str(&self.pretty_results());
+        output
+    }
+
+    fn pretty_print_with(&self, options: &RenderOptions) -> String {
+        let mut report = self.clone();
+        if let Some(key) = options.sort {
+            report.sort(key);
+        }
+        if options.group_by.is_none() {
+            return report.pretty_print();
+        }
+        let mut output = String::new();
+        if report.is_empty() {
+            output.push_str("No results found.\n");
+        }
+        for (name, members) in options.arrange_by(report.entries(), |e, field| e.row.get(field).cloned()) {
+            let name = name.unwrap_or_default();
+            output.push_str(&paint(&format!("== {} ({} results) ==", name, members.len()), Style::Bold));
+            output.push('\n');
+            for entry in members {
+                output.push_str(&format!("  {}\n", entry.pretty));
+            }
+            output.push('\n');
+        }
+        match &report.metadata {
+            Some(metadata) => format!("{}\n{}", metadata.pretty_print(), output),
+            None => output,
+        }
+    }
+
+    /// Text and Markdown output start with the run metadata and summary and list each result
+    /// kind separately; other formats as usual.
+    fn render(&self, format: OutputFormat) -> io::Result<String>
+    where
+        Self: Serialize,
+    {
+        let markdown = matches!(format, OutputFormat::Markdown);
+        if !markdown && !matches!(format, OutputFormat::Text) {
+            return match format {
+                OutputFormat::Json => render_json(self),
+                OutputFormat::Jsonl => render_jsonl(self),
+                OutputFormat::Csv => render_csv(&self.to_datalist(), &[]),
+                OutputFormat::Html => Ok(self.html()),
+                _ => Ok(self.pretty_print()),
+            };
+        }
+        let table = |data: &DataList| if markdown { render_markdown(data, &[]) } else { render_text(data, &[]) };
+        let mut sections: Vec<(&str, DataList)> = Vec::new();
+        if let Some(metadata) = &self.metadata {
+            sections.push(("Run", metadata.to_datalist()));
+        }
+        let summary = self.summary();
+        sections.push(("Summary", summary.to_datalist()));
+        sections.push(("Timing", summary.timings.to_datalist()));
+        sections.push(("Permissions", self.permissions.to_datalist()));
+        sections.push(("Ownership", self.ownership.to_datalist()));
+        sections.push(("Findings", self.findings.to_datalist()));
+        sections.push(("Waived", self.waived.to_datalist()));
+        let mut out = String::new();
+        for (title, data) in sections.iter().filter(|(_, data)| !data.is_empty()) {
+            if markdown {
+                out.push_str(&format!("## {}\n\n{}\n", title, table(data)?));
+            } else {
+                out.push_str(&format!("{}:\n{}", title, table(data)?));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Sorted reports keep their layout; grouped reports list every result with its `kind`
+    /// and `target`. HTML reports are already grouped by target.
+    fn render_with(&self, format: OutputFormat, options: &RenderOptions) -> io::Result<String>
+    where
+        Self: Serialize,
+    {
+        let mut report = self.clone();
+        if let Some(key) = options.sort {
+            report.sort(key);
+        }
+        match (options.group_by, &format) {
+            (None, OutputFormat::Csv) => render_rows(&report.to_datalist(), format, options),
+            (None, _) | (Some(GroupBy::Target), OutputFormat::Html) => report.render(format),
+            (_, OutputFormat::Pretty) => Ok(report.pretty_print_with(options)),
+            (_, OutputFormat::Json | OutputFormat::Jsonl) => {
+                let flat = matches!(format, OutputFormat::Jsonl);
+                let values = report.entries().into_iter().map(|e| e.value).collect();
+                let value = options.arrange_value(Value::Array(values), flat);
+                if flat { render_jsonl(&value) } else { render_json(&value) }
+            }
+            _ => {
+                let rows: DataList = report.entries().into_iter().map(|e| e.row).collect();
+                render_rows(&rows, format, options)
+            }
+        }
+    }
+
+    fn html(&self) -> String {
+        if self.is_empty() && self.waived.is_empty() {
+            return html_page("HALO audit report", "<p>No results found.</p>\n");
+        }
+        let row = |pairs: &[(&str, String)]| -> DataMap {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+        };
+        let mut body = String::new();
+        if let Some(metadata) = &self.metadata {
+            body.push_str("<h2>Run</h2>\n");
+            body.push_str(&html_table(&metadata.to_datalist(), &[]));
+        }
+        body.push_str("<h2>Summary</h2>\n");
+        body.push_str(&html_table(
+            &vec![
+                row(&[("result", "checked".into()), ("count", self.len().to_string())]),
+                row(&[("result", "failed".into()), ("count", self.failure_count().to_string())]),
+                row(&[("result", "acknowledged".into()), ("count", self.acknowledged_count().to_string())]),
+                row(&[("result", "waived".into()), ("count", self.waived.len().to_string())]),
+                row(&[("result", "risk score".into()), ("count", self.risk_score().total.to_string())]),
+            ],
+            &[],
+        ));
+        let by_severity: DataList = self
+            .by_severity()
+            .iter()
+            .map(|c| row(&[("severity", format!("{:?}", c.severity)), ("failures", c.failures.to_string())]))
+            .collect();
+        body.push_str(&html_table(&by_severity, &[]));
+
+        for group in self.by_target() {
+            body.push_str(&format!(
+                "<h2>{}</h2>\n<p>{} results, {} failed</p>\n",
+                html_escape(group.target),
+                group.len(),
+                group.failed()
+            ));
+            let TargetResults { permissions, ownership, findings, .. } = group;
+            let sections: [(&str, DataList); 3] = [
+                ("Permissions", permissions.iter().flat_map(|r| r.to_datalist()).collect()),
+                ("Ownership", ownership.iter().flat_map(|r| r.to_datalist()).collect()),
+                ("Findings", findings.iter().flat_map(|f| f.to_datalist()).collect()),
+            ];
+            for (title, data) in sections.iter().filter(|(_, data)| !data.is_empty()) {
+                body.push_str(&format!("<h3>{}</h3>\n", title));
+                body.push_str(&html_table(data, &[]));
+            }
+        }
+        if !self.waived.is_empty() {
+            body.push_str("<h2>Waived</h2>\n");
+            body.push_str(&html_table(&self.waived.to_datalist(), &[]));
+        }
+        html_page("HALO audit report", &body)
+    }
+}
+
+// Stable sort of `items` by the first row of their data lists
+fn sorted<T: Renderable>(items: Vec<T>, options: &RenderOptions) -> Vec<T> {
+    options
+        .arrange_by(items, |item, field| item.to_datalist().first().and_then(|row| row.get(field).cloned()))
+        .into_iter()
+        .flat_map(|(_, members)| members)
+        .collect()
+}
+
+// A result of any kind as a row, a JSON object and a pretty line, each with its kind and
+// target
+struct ReportEntry {
+    row: DataMap,
+    value: Value,
+    pretty: String,
+}
+
+impl ReportEntry {
+    fn new<T: Renderable + Serialize>(kind: &str, target: &str, result: &T) -> Self {
+        let mut row = DataMap::new();
+        row.insert("kind".to_string(), kind.to_string());
+        row.insert("target".to_string(), target.to_string());
+        row.extend(result.to_datalist().into_iter().next().unwrap_or_default());
+        let mut value = serde_json::to_value(result).unwrap_or(Value::Null);
+        if let Value::Object(map) = &mut value {
+            map.insert("kind".to_string(), Value::from(kind));
+            map.insert("target".to_string(), Value::from(target));
+        }
+        Self { row, value, pretty: result.pretty_print() }
+    }
+}
+
+/// Results of one target, as grouped by [`AuditReport::by_target`].
+#[derive(Debug, Default)]
+pub struct TargetResults<'a> {
+    /// Target name, or [`UNATTRIBUTED`]
+    pub target: &'a str,
+    pub permissions: Vec<&'a PermissionResults>,
+    pub ownership: Vec<&'a OwnershipResult>,
+    pub findings: Vec<&'a Finding>,
+}
+
+impl TargetResults<'_> {
+    /// Number of results of the target.
+    pub fn len(&self) -> usize {
+        self.permissions.len() + self.ownership.len() + self.findings.len()
+    }
+
+    /// Returns true if the target has no results.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of open failures of the target.
+    pub fn failed(&self) -> usize {
+        self.permissions.iter().filter(|r| r.status == Status::Fail).count()
+            + self.ownership.iter().filter(|r| !r.pass).count()
+            + self.findings.iter().filter(|f| f.is_open_failure()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::permissions::audit_permissions::AuditError;
+
+    #[test]
+    fn test_min_severity_keeps_passes() {
+        let mut report = AuditReport {
+            findings: vec![
+                Finding::pass("a", "/etc/a", "ok"),
+                Finding::fail("b", "/etc/b", Severity::Low, "low"),
+                Finding::fail("c", "/etc/c", Severity::Critical, "critical"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(report.failure_count(), 2);
+        assert_eq!(report.failure_count_at_least(&Severity::High), 1);
+        report.retain_min_severity(&Severity::High);
+        let checks: Vec<&str> = report.findings.iter().map(|f| f.check.as_str()).collect();
+        assert_eq!(checks, vec!["a", "c"]);
+        report.retain_failures();
+        assert_eq!(report.len(), 1);
+    }
+
+    #[test]
+    fn test_team_split() {
+        let teams: TeamMap =
+            toml::from_str("[[owners]]\npath_prefix = \"/etc/nginx\"\nteam = \"web-team\"\n")
+                .unwrap();
+        let mut report = AuditReport {
+            findings: vec![
+                Finding::fail("a", "/etc/nginx/nginx.conf", Severity::High, "bad"),
+                Finding::pass("b", "/etc/nginx/mime.types", "ok"),
+                Finding::fail("c", "/etc/shadow", Severity::High, "bad"),
+            ],
+            ..Default::default()
+        };
+        let summaries = report.team_summaries(&teams);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!((summaries[0].checked, summaries[0].failed), (2, 1));
+        assert_eq!(summaries[1].team, UNASSIGNED);
+
+        report.retain_team(&teams, "web-team");
+        assert_eq!(report.len(), 2);
+    }
+
+    #[test]
+    fn test_waivers_move_failures() {
+        let mut report = AuditReport {
+            findings: vec![
+                Finding::fail("a", "/srv/data/x", Severity::High, "bad"),
+                Finding::pass("a", "/srv/data/y", "ok"),
+                Finding::fail("b", "/srv/data/z", Severity::High, "bad"),
+                Finding::fail("a", "/etc/a", Severity::High, "bad"),
+            ],
+            ..Default::default()
+        };
+        let waivers = [
+            Waiver::new("/srv/data", "accepted").rule("a").expires("2030-01-01"),
+            Waiver::new("/etc", "expired").expires("2020-01-01"),
+        ];
+        let today = crate::waiver::parse_date("2026-10-16").unwrap();
+        assert_eq!(report.apply_waivers(&waivers, today), 1);
+        assert_eq!(report.waived[0].path, std::path::Path::new("/srv/data/x"));
+        assert_eq!(report.findings.len(), 3);
+        assert_eq!(report.failure_count(), 2);
+
+        let later = crate::waiver::parse_date("2030-01-02").unwrap();
+        let mut report = AuditReport {
+            findings: vec![Finding::fail("a", "/srv/data/x", Severity::High, "bad")],
+            ..Default::default()
+        };
+        assert_eq!(report.apply_waivers(&waivers, later), 0);
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn test_waiver_by_rule_id() {
+        let result = |rule_id: &str, path: &str| PermissionResults {
+            severity: Severity::High,
+            status: Status::Fail,
+            path: path.into(),
+            expected_mode: 0o600,
+            found_mode: 0o640,
+            importance: Importance::High,
+            rule_id: Some(rule_id.to_string()),
+            acknowledged: None,
+            error: None,
+        };
+        let mut report = AuditReport {
+            permissions: vec![result("HALO-USER-0002", "/etc/shadow"), result("HALO-USER-0004", "/etc/gshadow")],
+            ..Default::default()
+        };
+        let waivers = [Waiver::new("/etc", "shadow group read").rule("HALO-USER-0002")];
+        assert_eq!(report.apply_waivers(&waivers, 0), 1);
+        assert_eq!(report.waived[0].rule, "HALO-USER-0002");
+        assert_eq!(report.permissions.len(), 1);
+    }
+
+    #[test]
+    fn test_risk_score_by_target() {
+        let mut report = AuditReport::default();
+        let mark = report.mark();
+        report.findings.push(Finding::fail("a", "/etc/a", Severity::High, "bad"));
+        report.findings.push(Finding::pass("b", "/etc/b", "ok"));
+        report.attribute(mark, "sudoers");
+        report.findings.push(Finding::fail("c", "/etc/c", Severity::Critical, "bad"));
+        report.findings.push(Finding::fail("d", "/etc/d", Severity::Low, "bad"));
+
+        let risk = report.risk_score();
+        assert_eq!(risk.total, 32);
+        assert_eq!(risk.subtotals[0].target, UNATTRIBUTED);
+        assert_eq!((risk.subtotals[1].target.as_str(), risk.subtotals[1].score), ("sudoers", 10));
+
+        report.retain_min_severity(&Severity::High);
+        assert_eq!(report.risk_score().total, 30);
+    }
+
+    #[test]
+    fn test_html_sections() {
+        let mut report = AuditReport::default();
+        let mark = report.mark();
+        report.findings.push(Finding::fail("a", "/etc/sudoers", Severity::High, "NOPASSWD: ALL"));
+        report.attribute(mark, "sudoers");
+        report.findings.push(Finding::pass("b", "/etc/b", "ok"));
+
+        let html = report.html();
+        assert!(html.contains("<title>HALO audit report</title>"));
+        assert!(html.contains("<td>failed</td><td>1</td>"));
+        assert!(html.contains("<td class=\"severity-high\">High</td><td>1</td>"));
+        // One section per target, in order of the first result
+        let sudoers = html.find("<h2>sudoers</h2>\n<p>1 results, 1 failed</p>").unwrap();
+        let other = html.find(&format!("<h2>{}</h2>", UNATTRIBUTED)).unwrap();
+        assert!(sudoers < other);
+        assert!(html.contains("NOPASSWD: ALL"));
+    }
+
+    #[test]
+    fn test_summary_counts() {
+        let mut report = AuditReport {
+            findings: vec![
+                Finding::pass("a", "/etc/a", "ok"),
+                Finding::fail("b", "/etc/b", Severity::High, "high"),
+                Finding::fail("c", "/etc/c", Severity::High, "high"),
+                Finding::fail("d", "/etc/d", Severity::Low, "low"),
+            ],
+            ..Default::default()
+        };
+        let ack = Acknowledgment::new(&report.findings[2], "accepted");
+        report.apply_acknowledgments(&[ack]);
+        let summary = report.summary();
+        assert_eq!((summary.checked, summary.passed, summary.failed), (4, 1, 2));
+        assert_eq!((summary.high, summary.low, summary.acknowledged), (1, 1, 1));
+
+        report.retain_failures();
+        assert_eq!(report.summary().passed, 0);
+        assert_eq!(report.summary().failed, 2);
+    }
+
+    #[test]
+    fn test_acknowledge_permission_and_ownership() {
+        let mut report = AuditReport {
+            permissions: vec![PermissionResults::not_found("/etc/a".into(), 0o600, Importance::High, None)],
+            ownership: vec![OwnershipResult::not_found("/etc/b".into(), 0, 0, None)],
+            ..Default::default()
+        };
+        let permission = report.permissions[0].id();
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["permissions"][0]["id"], permission.as_str());
+        assert_eq!(json["ownership"][0]["id"], report.ownership[0].id().as_str());
+        assert!(report.failure_by_id("missing").is_none());
+
+        let acks: Vec<Acknowledgment> = [permission.as_str(), &report.ownership[0].id()]
+            .iter()
+            .map(|id| report.failure_by_id(id).unwrap().acknowledge("accepted"))
+            .collect();
+        assert_eq!(report.apply_acknowledgments(&acks), 2);
+        assert_eq!((report.summary().failed, report.summary().acknowledged), (0, 2));
+
+        // A different mode is a new state the acknowledgment does not cover
+        report.permissions[0].found_mode = 0o644;
+        assert_eq!(report.apply_acknowledgments(&acks), 1);
+        assert_eq!(report.failures().next().unwrap().id(), permission);
+    }
+
+    #[test]
+    fn test_dedup_keeps_strictest_and_flags_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = |name: &str| dir.path().join(name);
+        let result = |path: PathBuf, mode: u32| PermissionResults::not_found(path, mode, Importance::Low, None);
+        let mut report = AuditReport::default();
+        report.permissions.push(result(file("a"), 0o644));
+        report.permissions.push(result(dir.path().join(".").join("a"), 0o640));
+        report.permissions.push(result(file("a"), 0o644));
+        report.permissions.push(result(file("b"), 0o640));
+        report.permissions.push(result(file("b"), 0o604));
+        report.ownership.push(OwnershipResult::not_found(file("c"), 0, 0, None));
+        report.ownership.push(OwnershipResult::not_found(file("c"), 0, 0, None));
+        report.ownership.push(OwnershipResult::not_found(file("c"), 0, 4, None));
+        // Unset ids match any: uid 0 with any group, then 0:0, then any owner with group 0
+        let owner = |uid: Option<u32>, gid: Option<u32>| {
+            let mut r = OwnershipResult::not_found(file("d"), 0, 0, None);
+            (r.expected_uid, r.expected_gid) = (uid, gid);
+            r
+        };
+        report.ownership.push(owner(Some(0), None));
+        report.ownership.push(owner(Some(0), Some(0)));
+        report.ownership.push(owner(None, Some(0)));
+        report.ownership.push(owner(None, Some(5)));
+        report.findings.push(Finding::fail("x", "/etc/x", Severity::Low, "bad"));
+        report.findings.push(Finding::fail("x", "/etc/x", Severity::Low, "bad"));
+
+        assert_eq!(report.dedup(), 3);
+        let modes: Vec<u32> = report.permissions.iter().map(|r| r.expected_mode).collect();
+        assert_eq!(modes, [0o640, 0o640]);
+        assert_eq!(report.ownership.len(), 2);
+        assert_eq!((report.ownership[1].expected_uid, report.ownership[1].expected_gid), (Some(0), Some(0)));
+        let checks: Vec<&str> = report.findings.iter().map(|f| f.check.as_str()).collect();
+        assert_eq!(checks, ["x", RULE_CONFLICT, RULE_CONFLICT, RULE_CONFLICT]);
+        assert_eq!(report.findings[1].path, file("b"));
+        assert!(report.findings[1].message.contains("640 and mode 604"));
+        assert!(report.findings[2].message.contains("0:0 and owner 0:4"));
+        assert!(report.findings[3].message.contains("0:0 and owner -:5"));
+        assert_eq!(report.dedup(), 0);
+    }
+
+    #[test]
+    fn test_failures_by_severity_and_target() {
+        let mut report = AuditReport::default();
+        let mark = report.mark();
+        let mut pass = PermissionResults::not_found(PathBuf::from("/etc/a"), 0o644, Importance::Low, None);
+        pass.status = Status::Pass;
+        report.permissions.push(pass);
+        report.permissions.push(PermissionResults::not_found(PathBuf::from("/etc/b"), 0o644, Importance::Low, None));
+        report.attribute(mark, "sys");
+        report.findings.push(Finding::fail("c", "/etc/c", Severity::High, "high"));
+        report.findings.push(Finding::fail("d", "/etc/d", Severity::Critical, "critical"));
+        report.findings.push(Finding::pass("e", "/etc/e", "ok"));
+
+        let failed: Vec<&Path> = report.failures().map(|f| f.path()).collect();
+        assert_eq!(failed, [Path::new("/etc/b"), Path::new("/etc/c"), Path::new("/etc/d")]);
+        let counts: Vec<(Severity, usize)> = report.by_severity().into_iter().map(|c| (c.severity, c.failures)).collect();
+        assert_eq!(counts, [(Severity::Critical, 1), (Severity::High, 1), (Severity::Info, 1)]);
+
+        let groups = report.by_target();
+        assert_eq!((groups[0].target, groups[0].len(), groups[0].failed()), ("sys", 2, 1));
+        assert_eq!((groups[1].target, groups[1].len(), groups[1].failed()), (UNATTRIBUTED, 3, 2));
+        assert_eq!(report.target_of(report.failures().next().unwrap()), "sys");
+
+        let statuses: StatusCounts = report.permissions.iter().map(|r| &r.status).collect();
+        assert_eq!(statuses, StatusCounts { passed: 1, strict: 0, failed: 1 });
+    }
+
+    #[test]
+    fn test_sorted_and_grouped_report() {
+        let mut report = AuditReport::default();
+        let mark = report.mark();
+        report.findings.push(Finding::fail("a", "/etc/z", Severity::Low, "low"));
+        report.findings.push(Finding::pass("b", "/etc/y", "ok"));
+        report.attribute(mark, "sudoers");
+        let mark = report.mark();
+        report.findings.push(Finding::fail("c", "/etc/x", Severity::Critical, "critical"));
+        report.attribute(mark, "ssh");
+
+        let mut sorted = report.clone();
+        sorted.sort(SortKey::Path);
+        let paths: Vec<_> = sorted.findings.iter().map(|f| f.path.display().to_string()).collect();
+        assert_eq!(paths, ["/etc/x", "/etc/y", "/etc/z"]);
+
+        let options = RenderOptions::default().sort(SortKey::Severity).group_by(GroupBy::Target);
+        let json: Value = serde_json::from_str(&report.render_with(OutputFormat::Json, &options).unwrap()).unwrap();
+        assert_eq!(json["sudoers"][0]["check"], "a");
+        assert_eq!(json["sudoers"][0]["kind"], "finding");
+        assert_eq!(json["ssh"][0]["target"], "ssh");
+
+        let options = RenderOptions::default().group_by(GroupBy::Severity);
+        let lines = report.render_with(OutputFormat::Jsonl, &options).unwrap();
+        let groups: Vec<Value> = lines.lines().map(|l| serde_json::from_str::<Value>(l).unwrap()["group"].clone()).collect();
+        assert_eq!(groups, ["Critical", "Low", "None"]);
+    }
+
+    #[test]
+    fn test_pretty_target_headers() {
+        crate::render_output::set_color(false);
+        let mut report = AuditReport::default();
+        let mark = report.mark();
+        report.findings.push(Finding::fail("a", "/etc/sudoers", Severity::High, "NOPASSWD: ALL"));
+        report.attribute(mark, "sudoers");
+        report.findings.push(Finding::pass("b", "/etc/b", "ok"));
+
+        let text = report.pretty_print();
+        assert!(text.starts_with("== sudoers (1 results, 1 failed) ==\nFindings:\n  ✗ High     /etc/sudoers [a] NOPASSWD: ALL"));
+        assert!(text.contains(&format!("== {} (1 results, 0 failed) ==", UNATTRIBUTED)));
+        assert!(text.contains("  ✓ -        /etc/b [b] ok"));
+        assert!(!text.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_metadata_in_every_format() {
+        let mut report = AuditReport::default();
+        report.findings.push(Finding::fail("a", "/etc/sudoers", Severity::High, "NOPASSWD: ALL"));
+        let json: Value = serde_json::from_str(&report.render(OutputFormat::Json).unwrap()).unwrap();
+        assert!(json.get("metadata").is_none());
+        assert_eq!(json["summary"]["high"], 1);
+
+        report.metadata = Some(RunMetadata {
+            hostname: "web1".to_string(),
+            targets: vec!["sudoers".to_string()],
+            duration_ms: 42,
+            ..Default::default()
+        });
+        let json: Value = serde_json::from_str(&report.render(OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["metadata"]["hostname"], "web1");
+        assert_eq!(json["metadata"]["targets"][0], "sudoers");
+        assert_eq!(json["findings"][0]["check"], "a");
+        assert!(json.get("ownership").is_none());
+
+        let text = report.render(OutputFormat::Text).unwrap();
+        assert!(text.starts_with("Run:\n  hostname: web1\n"));
+        assert!(text.contains("Summary:\n  checked: 1\n"));
+        let markdown = report.render(OutputFormat::Markdown).unwrap();
+        assert!(markdown.starts_with("## Run\n"));
+        assert!(markdown.contains("## Findings\n"));
+        assert!(report.html().contains("<h2>Run</h2>"));
+        assert!(report.pretty_print().contains("on web1"));
+
+        report.metadata.as_mut().unwrap().timings = vec![
+            TargetTiming::new("sys", Duration::from_millis(500), 3, 1000),
+            TargetTiming::new("cis", Duration::ZERO, 2, 0),
+        ];
+        let lines = "  sys: 500 ms, 3 results, 1000 files (2000 files/s)\n  cis: 0 ms, 2 results\n";
+        let pretty = report.pretty_print();
+        assert!(pretty.contains(&format!("targets: sudoers\n{}", lines)));
+        assert_eq!(pretty.matches("sys: 500 ms").count(), 1);
+        assert!(report.summary().pretty_print().contains(&format!("\nTiming:\n{}", lines)));
+        let text = report.render(OutputFormat::Text).unwrap();
+        assert_eq!(text.matches("target: sys").count(), 1);
+        assert!(text.find("Summary:").unwrap() < text.find("Timing:\n  target: sys\n").unwrap());
+        let json: Value = serde_json::from_str(&report.render(OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["summary"]["timings"][0]["files_per_sec"], 2000);
+        assert!(json["metadata"].get("timings").is_none());
+        let read: AuditReport = serde_json::from_value(json).unwrap();
+        assert_eq!(read.metadata, report.metadata);
+    }
+
+    #[test]
+    fn test_target_timing_rates() {
+        let timing = TargetTiming::new("sys", Duration::from_millis(500), 3, 1000);
+        assert_eq!((timing.duration_ms, timing.files_per_sec), (500, 2000));
+        assert_eq!(TargetTiming::new("cis", Duration::ZERO, 0, 0).files_per_sec, 0);
+        // No measurable time: no rate rather than a division by zero
+        assert_eq!(TargetTiming::new("sys", Duration::ZERO, 1, 10).files_per_sec, 0);
+        // Under a millisecond the duration rounds down to 0 ms but the rate is still measured
+        let fast = TargetTiming::new("user", Duration::from_micros(250), 1, 5);
+        assert_eq!((fast.duration_ms, fast.files_per_sec), (0, 20000));
+        assert_eq!(TargetTiming::new("log", Duration::from_secs(3), 0, 1).files_per_sec, 0);
+        assert_eq!(TargetTiming::new("log", Duration::from_secs(2), 0, 3).files_per_sec, 2);
+    }
+
+    #[test]
+    fn test_from_file_reads_reports_and_arrays() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = AuditReport {
+            metadata: Some(RunMetadata::capture()),
+            findings: vec![Finding::fail("a", "/etc/sudoers", Severity::High, "NOPASSWD: ALL")],
+            ..Default::default()
+        };
+        let path = dir.path().join("report.json");
+        fs::write(&path, serde_json::to_string(&report).unwrap()).unwrap();
+        let read = AuditReport::from_file(&path).unwrap();
+        assert_eq!(read.metadata, report.metadata);
+        assert_eq!(read.findings[0].id, report.findings[0].id);
+        assert_eq!(read.failure_count(), 1);
+
+        let legacy = dir.path().join("legacy.json");
+        fs::write(&legacy, "[]").unwrap();
+        assert!(AuditReport::from_file(&legacy).unwrap().is_empty());
+        fs::write(&legacy, "{\"findings\": 3}").unwrap();
+        let error = AuditReport::from_file(&legacy).unwrap_err();
+        assert_eq!(error.code().as_str(), "E_JSON_PARSE");
+        let missing = AuditReport::from_file(&dir.path().join("missing.json")).unwrap_err();
+        assert_eq!(missing.code().as_str(), "E_PATH_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut report = AuditReport {
+            metadata: Some(RunMetadata::capture()),
+            ..Default::default()
+        };
+        let mark = report.mark();
+        let mut strict = PermissionResults::not_found(PathBuf::from("/etc/shadow"), 0o640, Importance::High, None);
+        strict.status = Status::Strict;
+        strict.found_mode = 0o600;
+        strict.error = Some(AuditError::InvalidPermissionChar('z'));
+        report.permissions.push(strict);
+        report.ownership.push(OwnershipResult::not_found(PathBuf::from("/etc/passwd"), 0, 0, None));
+        report.findings.push(Finding::fail("a", "/etc/sudoers", Severity::High, "NOPASSWD: ALL"));
+        report.attribute(mark, "sys");
+        report.findings.push(Finding::fail("b", "/srv/b", Severity::Low, "bad"));
+
+        let json = serde_json::to_string(&report).unwrap();
+        let read: AuditReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&read).unwrap(), json);
+        let read_status: Vec<Status> = read.permissions.iter().map(|r| r.status.clone()).collect();
+        assert_eq!(read_status, [Status::Strict]);
+        assert_eq!(read.permissions[0].found_mode, 0o600);
+        assert_eq!(read.permissions[0].error, Some(AuditError::InvalidPermissionChar('z')));
+        let targets: Vec<&str> = read.by_target().iter().map(|g| g.target).collect();
+        assert_eq!(targets, ["sys", UNATTRIBUTED]);
+        assert_eq!(read.risk_score().subtotals, report.risk_score().subtotals);
+    }
+}
@@ -0,0 +1,152 @@
+//! Host risk score for HALO.
+//!
+//! Every open failure in an [`AuditReport`] adds its severity weight multiplied by the weight
+//! of its importance; passing, stricter-than-expected and acknowledged results add nothing.
+//! The sum is the host risk score, and the same sum per target shows where the risk comes
+//! from. Scores are comparable between runs of the same targets, so recording them tracks
+//! hardening progress over time.
+//!
+//! | Severity | Weight |   | Importance | Weight |
+//! |----------|--------|---|------------|--------|
+//! | Critical | 10     |   | High       | 3      |
+//! | High     | 5      |   | Medium     | 2      |
+//! | Medium   | 3      |   | Low        | 1      |
+//! | Low      | 1      |   |            |        |
+//! | Info, None | 0    |   |            |        |
+//!
+//! Ownership results and findings carry no importance and are weighted as `Medium`.
+//!
+//! [`AuditReport`]: crate::AuditReport
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{Audit, Renderable, Target};
+//! let report = Audit::new().target(Target::Sys).run().unwrap();
+//! let risk = report.risk_score();
+//! println!("Risk score: {}", risk.total);
+//! risk.render_and_print(Some("json"));
+//! ```
+use crate::audit::permissions::audit_permissions::{Importance, Severity};
+use crate::render_output::{DataList, Renderable};
+use indexmap::IndexMap;
+use serde::Serialize;
+
+/// Target name for results added outside a target, e.g. by custom rules.
+pub const UNATTRIBUTED: &str = "other";
+
+/// Weight of a failure of `severity`; never lower for a higher [`Severity::rank`].
+pub fn severity_weight(severity: &Severity) -> u32 {
+    match severity {
+        Severity::Critical => 10,
+        Severity::High => 5,
+        Severity::Medium => 3,
+        Severity::Low => 1,
+        Severity::Info | Severity::None => 0,
+    }
+}
+
+/// Multiplier for failures on paths of `importance`.
+pub fn importance_weight(importance: &Importance) -> u32 {
+    match importance {
+        Importance::High => 3,
+        Importance::Medium => 2,
+        Importance::Low => 1,
+    }
+}
+
+/// Score of the failures attributed to one target.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RiskSubtotal {
+    pub target: String,
+    pub score: u32,
+    pub failures: usize,
+}
+
+/// Host risk score with per-target subtotals, highest first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RiskScore {
+    pub total: u32,
+    pub subtotals: Vec<RiskSubtotal>,
+}
+
+impl RiskScore {
+    /// Adds a failure of `severity` and `importance` to the subtotal of `target`.
+    pub fn add(&mut self, target: &str, severity: &Severity, importance: &Importance) {
+        let score = severity_weight(severity) * importance_weight(importance);
+        self.total += score;
+        match self.subtotals.iter_mut().find(|s| s.target == target) {
+            Some(subtotal) => {
+                subtotal.score += score;
+                subtotal.failures += 1;
+            }
+            None => self.subtotals.push(RiskSubtotal {
+                target: target.to_string(),
+                score,
+                failures: 1,
+            }),
+        }
+    }
+
+    /// Orders subtotals by score, highest first; ties keep their order.
+    pub fn sort(&mut self) {
+        self.subtotals.sort_by_key(|s| std::cmp::Reverse(s.score));
+    }
+}
+
+impl Renderable for RiskScore {
+    fn to_datalist(&self) -> DataList {
+        let row = |target: &str, score: u32, failures: usize| {
+            let mut map = IndexMap::new();
+            map.insert("target".to_string(), target.to_string());
+            map.insert("score".to_string(), score.to_string());
+            map.insert("failures".to_string(), failures.to_string());
+            map
+        };
+        let mut data: DataList = self
+            .subtotals
+            .iter()
+            .map(|s| row(&s.target, s.score, s.failures))
+            .collect();
+        let failures = self.subtotals.iter().map(|s| s.failures).sum();
+        data.push(row("total", self.total, failures));
+        data
+    }
+
+    fn pretty_print(&self) -> String {
+        let mut output = format!("Risk score: {}\n", self.total);
+        for s in &self.subtotals {
+            output.push_str(&format!(
+                "  {:<12} {:>5}  ({} failures)\n",
+                s.target, s.score, s.failures
+            ));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weights_and_subtotals() {
+        let mut risk = RiskScore::default();
+        risk.add("user", &Severity::Low, &Importance::Low);
+        risk.add("sys", &Severity::Critical, &Importance::High);
+        risk.add("user", &Severity::High, &Importance::Medium);
+        risk.add("sys", &Severity::Info, &Importance::High);
+        risk.sort();
+        assert_eq!(risk.total, 41);
+        assert_eq!(risk.subtotals[0], RiskSubtotal { target: "sys".into(), score: 30, failures: 2 });
+        assert_eq!(risk.subtotals[1].score, 11);
+        assert_eq!(risk.to_datalist().last().unwrap()["failures"], "4");
+    }
+
+    #[test]
+    fn test_weights_follow_severity_order() {
+        let mut severities = [Severity::Critical, Severity::None, Severity::Medium, Severity::Info, Severity::Low, Severity::High];
+        severities.sort();
+        let weights: Vec<u32> = severities.iter().map(severity_weight).collect();
+        assert!(weights.windows(2).all(|w| w[0] <= w[1]), "{:?}", weights);
+    }
+}
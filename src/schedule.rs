@@ -0,0 +1,235 @@
+//! Cron-style schedules.
+//!
+//! A [`CronSchedule`] is a standard five-field cron expression, `minute hour day-of-month
+//! month day-of-week`, evaluated in UTC. Fields accept `*`, numbers, ranges (`1-5`), steps
+//! (`*/15`, `0-30/10`), lists (`1,15`) and three-letter month and weekday names (`jan`,
+//! `mon`); Sunday is `0` or `7`. As in cron, a job whose day-of-month and day-of-week are
+//! both restricted runs when either matches. The shortcuts `@hourly`, `@daily`
+//! (`@midnight`), `@weekly`, `@monthly` and `@yearly` (`@annually`) are accepted too.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::schedule::CronSchedule;
+//! let nightly: CronSchedule = "30 2 * * *".parse().unwrap();
+//! // 1970-01-01 00:00 UTC -> 02:30 the same day
+//! assert_eq!(nightly.next_after(0), Some(2 * 3600 + 30 * 60));
+//! ```
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A parsed five-field cron expression.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct CronSchedule {
+    source: String,
+    // Bit n is set if value n matches
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+// Parses one field into a bit set of the values in `min..=max`; `names` are matched
+// case-insensitively and stand for `min`, `min + 1`, ...
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let lower = s.to_ascii_lowercase();
+        let v = match names.iter().position(|n| *n == lower) {
+            Some(i) => min + i as u32,
+            None => s.parse().map_err(|_| format!("invalid value '{}'", s))?,
+        };
+        if v < min || v > max {
+            return Err(format!("{} is out of range {}-{}", v, min, max));
+        }
+        Ok(v)
+    };
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("invalid step '{}'", step))?;
+                if step == 0 {
+                    return Err("step must be greater than zero".to_string());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (value(a)?, value(b)?),
+                // `5/15` runs from 5 to the end of the range
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let v = value(range)?;
+                    (v, v)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("invalid range '{}'", range));
+        }
+        for v in (start..=end).step_by(step as usize) {
+            bits |= 1 << v;
+        }
+    }
+    Ok(bits)
+}
+
+// (year, month, day) of a day count since 1970-01-01
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+impl CronSchedule {
+    /// The expression as written.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    fn matches_day(&self, days: i64) -> bool {
+        let (_, month, day) = civil_from_days(days);
+        // 1970-01-01 was a Thursday
+        let weekday = (days + 4).rem_euclid(7) as u32;
+        let day_ok = self.days & (1 << day) != 0;
+        let weekday_ok = self.weekdays & (1 << weekday) != 0;
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (false, false) => day_ok || weekday_ok,
+            _ => day_ok && weekday_ok,
+        };
+        self.months & (1 << month) != 0 && day_matches
+    }
+
+    /// First time strictly after `time` (seconds since the Unix epoch) that the schedule
+    /// fires, at the start of a minute; `None` if it never fires, e.g. `0 0 31 2 *`.
+    pub fn next_after(&self, time: u64) -> Option<u64> {
+        let start = (time / 60 + 1) * 60;
+        let first_day = (start / 86_400) as i64;
+        // Every valid combination of day and weekday recurs within 28 years
+        for days in first_day..first_day + 28 * 366 {
+            if !self.matches_day(days) {
+                continue;
+            }
+            let day_start = days as u64 * 86_400;
+            let first_minute = if days == first_day { (start - day_start) / 60 } else { 0 };
+            for minute_of_day in first_minute..24 * 60 {
+                let (hour, minute) = (minute_of_day / 60, minute_of_day % 60);
+                if self.hours & (1 << hour) != 0 && self.minutes & (1 << minute) != 0 {
+                    return Some(day_start + minute_of_day * 60);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expanded = match s.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("invalid schedule '{}': expected 5 fields, got {}", s, fields.len()));
+        };
+        let field = |name: &str, result: Result<u64, String>| {
+            result.map_err(|e| format!("invalid schedule '{}': {} field: {}", s, name, e))
+        };
+        let mut weekdays = field("day-of-week", parse_field(weekday, 0, 7, &WEEKDAYS))?;
+        // Sunday is 0 or 7
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            source: s.trim().to_string(),
+            minutes: field("minute", parse_field(minute, 0, 59, &[]))?,
+            hours: field("hour", parse_field(hour, 0, 23, &[]))?,
+            days: field("day-of-month", parse_field(day, 1, 31, &[]))?,
+            months: field("month", parse_field(month, 1, 12, &MONTHS))?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+}
+
+impl TryFrom<String> for CronSchedule {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::tls::days_from_civil;
+
+    // Seconds since the epoch of a UTC date and time
+    fn at(year: i64, month: i64, day: i64, hour: u64, minute: u64) -> u64 {
+        days_from_civil(year, month, day) as u64 * 86_400 + hour * 3600 + minute * 60
+    }
+
+    #[test]
+    fn test_next_after() {
+        let every_15: CronSchedule = "*/15 * * * *".parse().unwrap();
+        assert_eq!(every_15.next_after(at(2026, 3, 1, 10, 7)), Some(at(2026, 3, 1, 10, 15)));
+        assert_eq!(every_15.next_after(at(2026, 3, 1, 10, 15)), Some(at(2026, 3, 1, 10, 30)));
+
+        // 2026-03-01 is a Sunday
+        let weekdays: CronSchedule = "0 9 * * mon-fri".parse().unwrap();
+        assert_eq!(weekdays.next_after(at(2026, 2, 27, 9, 0)), Some(at(2026, 3, 2, 9, 0)));
+
+        // Either the 13th or a Friday
+        let either: CronSchedule = "0 0 13 * 5".parse().unwrap();
+        assert_eq!(either.next_after(at(2026, 3, 1, 0, 0)), Some(at(2026, 3, 6, 0, 0)));
+        assert_eq!(either.next_after(at(2026, 3, 6, 0, 0)), Some(at(2026, 3, 13, 0, 0)));
+
+        let leap: CronSchedule = "0 0 29 feb *".parse().unwrap();
+        assert_eq!(leap.next_after(at(2026, 1, 1, 0, 0)), Some(at(2028, 2, 29, 0, 0)));
+        let sunday: CronSchedule = "0 0 * * 7".parse().unwrap();
+        assert_eq!(sunday.next_after(at(2026, 3, 2, 0, 0)), Some(at(2026, 3, 8, 0, 0)));
+        assert_eq!("@weekly".parse::<CronSchedule>().unwrap().next_after(at(2026, 3, 2, 0, 0)), Some(at(2026, 3, 8, 0, 0)));
+        assert_eq!("0 0 31 2 *".parse::<CronSchedule>().unwrap().next_after(0), None);
+    }
+
+    #[test]
+    fn test_invalid_schedules() {
+        assert!("* * * *".parse::<CronSchedule>().is_err());
+        assert!("60 * * * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+        assert!("0 0 * * funday".parse::<CronSchedule>().is_err());
+        assert!("5-1 * * * *".parse::<CronSchedule>().is_err());
+    }
+}
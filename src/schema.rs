@@ -0,0 +1,134 @@
+//! Versioned JSON schema of HALO reports.
+//!
+//! Every JSON report starts with a `schema_version`. Within a version fields may be added, so
+//! parsers should ignore fields they do not know; removing, renaming or retyping a field
+//! raises the version. [`REPORT_SCHEMA`] is the [JSON Schema](https://json-schema.org/) of
+//! the current version, also printed by `halo schema`, for validating reports in CI or
+//! generating parsers.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::schema::{SCHEMA_VERSION, report_schema};
+//! assert_eq!(report_schema()["properties"]["schema_version"]["const"], SCHEMA_VERSION);
+//! ```
+use serde_json::Value;
+
+/// Version of the report format described by [`REPORT_SCHEMA`].
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema (draft 2020-12) of a serialized [`AuditReport`](crate::AuditReport).
+pub const REPORT_SCHEMA: &str = include_str!("../schema/report.schema.json");
+
+/// [`REPORT_SCHEMA`] parsed.
+pub fn report_schema() -> Value {
+    serde_json::from_str(REPORT_SCHEMA).expect("bundled report schema is valid JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AuditReport;
+    use crate::ack::Acknowledgment;
+    use crate::audit::finding::Finding;
+    use crate::audit::ownership::ownership::OwnershipResult;
+    use crate::audit::permissions::audit_permissions::{Importance, PermissionResults, Severity};
+    use crate::report::{RunMetadata, TargetTiming};
+    use crate::waiver::Waiver;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    // Checks `value` against the subset of JSON Schema the report schema uses; returns the
+    // JSON pointer of the first mismatch
+    fn check(schema: &Value, root: &Value, value: &Value, at: &str) -> Result<(), String> {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let target = root.pointer(reference.trim_start_matches('#')).ok_or(reference)?;
+            return check(target, root, value, at);
+        }
+        if let Some(expected) = schema.get("const")
+            && expected != value
+        {
+            return Err(format!("{}: expected {}", at, expected));
+        }
+        if let Some(options) = schema["enum"].as_array()
+            && !options.contains(value)
+        {
+            return Err(format!("{}: {} not allowed", at, value));
+        }
+        let type_name = match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(n) if n.is_u64() || n.is_i64() => "integer",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        };
+        let types = match &schema["type"] {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => vec![type_name],
+        };
+        if !types.contains(&type_name) {
+            return Err(format!("{}: {} is not {:?}", at, type_name, types));
+        }
+        if let Value::Array(items) = value {
+            for (i, item) in items.iter().enumerate() {
+                check(&schema["items"], root, item, &format!("{}/{}", at, i))?;
+            }
+        }
+        if let Value::Object(map) = value
+            && schema.get("properties").is_some()
+        {
+            for required in schema["required"].as_array().into_iter().flatten() {
+                if !map.contains_key(required.as_str().unwrap_or_default()) {
+                    return Err(format!("{}: missing {}", at, required));
+                }
+            }
+            for (key, field) in map {
+                match schema["properties"].get(key) {
+                    Some(property) => check(property, root, field, &format!("{}/{}", at, key))?,
+                    None => return Err(format!("{}: unexpected {}", at, key)),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_reports_match_schema() {
+        let schema = report_schema();
+        let mut report = AuditReport::default();
+        let empty = serde_json::to_value(&report).unwrap();
+        assert_eq!(check(&schema, &schema, &empty, ""), Ok(()));
+
+        let mut metadata = RunMetadata::capture();
+        metadata.timings.push(TargetTiming::new("sys", Duration::from_millis(5), 1, 10));
+        report.metadata = Some(metadata);
+        report.permissions.push(PermissionResults::not_found(
+            PathBuf::from("/etc/shadow"),
+            0o640,
+            Importance::High,
+            Some("shadow".to_string()),
+        ));
+        report.ownership.push(OwnershipResult::not_found(PathBuf::from("/etc/passwd"), 0, 0, None));
+        report.ownership[0].acknowledged = Some(Acknowledgment::new(&report.ownership[0], "accepted"));
+        let mut finding = Finding::fail("a", "/etc/sudoers", Severity::High, "NOPASSWD: ALL");
+        finding.subject = Some("line 3".to_string());
+        finding.benchmark = Some("CIS 5.3.4".to_string());
+        finding.acknowledged = Some(Acknowledgment::new(&finding, "accepted"));
+        report.findings.push(finding);
+        let mark = report.mark();
+        report.findings.push(Finding::fail("b", "/srv/b", Severity::Low, "bad"));
+        report.attribute(mark, "sys");
+        report.apply_waivers(&[Waiver::new("/srv", "accepted")], crate::waiver::today());
+        assert!(!report.waived.is_empty());
+
+        let full = serde_json::to_value(&report).unwrap();
+        assert_eq!(full["schema_version"], SCHEMA_VERSION);
+        assert_eq!(check(&schema, &schema, &full, ""), Ok(()));
+
+        let mut renamed = full.clone();
+        renamed["findings"][0]["checks"] = renamed["findings"][0]["check"].take();
+        assert!(check(&schema, &schema, &renamed, "").is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! Signed, tamper-evident reports.
+//!
+//! Compliance evidence is only worth keeping if nobody could have edited it afterwards. HALO
+//! signs stored reports with an ed25519 key pair: a [`SigningKeys`] pair lives in the
+//! `keys/` directory of the [state directory](crate::state) (`halo keys generate`), and a
+//! report file is signed either with a detached [`DetachedSignature`] next to it
+//! (`report.json.sig`) or as a compact JWS (`report.json.jws`, RFC 7515 with `alg` `EdDSA`)
+//! that embeds the report. `halo verify report.json` checks either against a trusted
+//! [`PublicKey`].
+//!
+//! Signatures cover the exact bytes of the file, so any change, even whitespace, breaks
+//! them. The key id in a signature only says which key to use; verification always uses the
+//! public key given, never one taken from the signature.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::signing::SigningKeys;
+//! let keys = SigningKeys::generate().unwrap();
+//! let report = br#"{"schema_version":1}"#;
+//! let signature = keys.sign(report);
+//! assert!(keys.public_key().verify(report, &signature).is_ok());
+//! assert!(keys.public_key().verify(b"{}", &signature).is_err());
+//! ```
+use crate::ack::stable_id;
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+/// File name of the private key in a key directory.
+pub const PRIVATE_KEY_FILE: &str = "halo.key";
+/// File name of the public key in a key directory.
+pub const PUBLIC_KEY_FILE: &str = "halo.pub";
+
+const ALGORITHM: &str = "ed25519";
+
+// Decodes a base64 key file holding exactly N bytes
+fn read_key<const N: usize>(path: &Path) -> Result<[u8; N], Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let bytes = STANDARD
+        .decode(content.trim())
+        .map_err(|e| format!("Invalid key in {}: {}", path.display(), e))?;
+    bytes
+        .try_into()
+        .map_err(|_| format!("Invalid key in {}: expected {} bytes", path.display(), N).into())
+}
+
+/// An ed25519 key pair for signing reports.
+pub struct SigningKeys {
+    key: SigningKey,
+}
+
+impl SigningKeys {
+    /// A new key pair from the system's random number generator (`/dev/urandom`).
+    pub fn generate() -> io::Result<Self> {
+        let mut secret = [0u8; 32];
+        File::open("/dev/urandom")?.read_exact(&mut secret)?;
+        Ok(Self {
+            key: SigningKey::from_bytes(&secret),
+        })
+    }
+
+    /// Loads the key pair from `halo.key` in `dir`.
+    pub fn load(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let secret = read_key::<32>(&dir.join(PRIVATE_KEY_FILE))?;
+        Ok(Self {
+            key: SigningKey::from_bytes(&secret),
+        })
+    }
+
+    /// Writes `halo.key` (mode 600) and `halo.pub` to `dir`, creating it; an existing key is
+    /// only replaced if `overwrite` is set.
+    pub fn save(&self, dir: &Path, overwrite: bool) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let mut options = OpenOptions::new();
+        options.write(true).mode(0o600);
+        if overwrite {
+            options.create(true).truncate(true);
+        } else {
+            options.create_new(true);
+        }
+        let mut file = options.open(dir.join(PRIVATE_KEY_FILE))?;
+        writeln!(file, "{}", STANDARD.encode(self.key.to_bytes()))?;
+        file.sync_all()?;
+        fs::write(dir.join(PUBLIC_KEY_FILE), format!("{}\n", self.public_key().to_base64()))
+    }
+
+    /// The public half, for verification.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey {
+            key: self.key.verifying_key(),
+        }
+    }
+
+    /// Signs `data`, e.g. the bytes of a report file.
+    pub fn sign(&self, data: &[u8]) -> DetachedSignature {
+        DetachedSignature {
+            algorithm: ALGORITHM.to_string(),
+            key_id: self.public_key().id(),
+            signature: STANDARD.encode(self.key.sign(data).to_bytes()),
+        }
+    }
+
+    /// `data` as a compact JWS (`header.payload.signature`) signed with EdDSA.
+    pub fn jws(&self, data: &[u8]) -> String {
+        let header = serde_json::json!({"alg": "EdDSA", "kid": self.public_key().id()});
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(data)
+        );
+        let signature = self.key.sign(signing_input.as_bytes());
+        format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature.to_bytes()))
+    }
+}
+
+/// An ed25519 public key that signatures are verified against.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicKey {
+    key: VerifyingKey,
+}
+
+impl PublicKey {
+    /// Reads a public key file (`halo.pub`), or `halo.pub` in a key directory.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = if path.is_file() { path.to_path_buf() } else { path.join(PUBLIC_KEY_FILE) };
+        let bytes = read_key::<32>(&path)?;
+        let key = VerifyingKey::from_bytes(&bytes).map_err(|e| format!("Invalid key in {}: {}", path.display(), e))?;
+        Ok(Self { key })
+    }
+
+    /// The key as base64, as written to `halo.pub`.
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.key.to_bytes())
+    }
+
+    /// Short id of the key, recorded in signatures.
+    pub fn id(&self) -> String {
+        stable_id(&[ALGORITHM, &self.to_base64()])
+    }
+
+    /// Checks a detached signature of `data`.
+    ///
+    /// # Returns
+    /// * `Err` naming the reason if the signature is malformed, from another key, or does not
+    ///   match `data`.
+    pub fn verify(&self, data: &[u8], signature: &DetachedSignature) -> Result<(), Box<dyn std::error::Error>> {
+        if signature.algorithm != ALGORITHM {
+            return Err(format!("unsupported algorithm '{}'", signature.algorithm).into());
+        }
+        if signature.key_id != self.id() {
+            return Err(format!("signed with key {}, not {}", signature.key_id, self.id()).into());
+        }
+        let bytes: [u8; 64] = STANDARD
+            .decode(&signature.signature)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or("malformed signature")?;
+        self.key
+            .verify_strict(data, &Signature::from_bytes(&bytes))
+            .map_err(|_| "signature does not match: the file was modified".into())
+    }
+
+    /// Checks a compact JWS and returns its payload.
+    pub fn verify_jws(&self, jws: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut parts = jws.trim().split('.');
+        let (Some(header), Some(payload), Some(signature), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err("not a compact JWS".into());
+        };
+        let header: serde_json::Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header)?)?;
+        if header["alg"] != "EdDSA" {
+            return Err(format!("unsupported algorithm {}", header["alg"]).into());
+        }
+        let bytes: [u8; 64] = URL_SAFE_NO_PAD
+            .decode(signature)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or("malformed signature")?;
+        let signing_input = &jws.trim()[..jws.trim().rfind('.').unwrap_or(0)];
+        self.key
+            .verify_strict(signing_input.as_bytes(), &Signature::from_bytes(&bytes))
+            .map_err(|_| "signature does not match: the report was modified or signed with another key")?;
+        Ok(URL_SAFE_NO_PAD.decode(payload)?)
+    }
+}
+
+/// A signature stored next to the signed file, as `<file>.sig`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetachedSignature {
+    /// Always `ed25519`
+    pub algorithm: String,
+    /// [`PublicKey::id`] of the signing key
+    pub key_id: String,
+    /// Base64 signature of the file's bytes
+    pub signature: String,
+}
+
+impl DetachedSignature {
+    /// Reads a `.sig` file.
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Ok(serde_json::from_str(&content).map_err(|e| format!("Invalid signature file {}: {}", path.display(), e))?)
+    }
+
+    /// Path of the detached signature of `file`: `<file>.sig`.
+    pub fn path_for(file: &Path) -> PathBuf {
+        let mut path = file.as_os_str().to_owned();
+        path.push(".sig");
+        PathBuf::from(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = SigningKeys::generate().unwrap();
+        keys.save(dir.path(), false).unwrap();
+        assert!(keys.save(dir.path(), false).is_err());
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(dir.path().join(PRIVATE_KEY_FILE)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let loaded = SigningKeys::load(dir.path()).unwrap();
+        assert_eq!(loaded.public_key(), keys.public_key());
+        assert_eq!(PublicKey::load(dir.path()).unwrap(), keys.public_key());
+        assert_eq!(PublicKey::load(&dir.path().join(PUBLIC_KEY_FILE)).unwrap().id().len(), 12);
+        assert_eq!(
+            DetachedSignature::path_for(Path::new("/tmp/report.json")),
+            PathBuf::from("/tmp/report.json.sig")
+        );
+    }
+
+    #[test]
+    fn test_detached_and_jws() {
+        let keys = SigningKeys::generate().unwrap();
+        let other = SigningKeys::generate().unwrap();
+        let report = br#"{"schema_version":1,"findings":[]}"#;
+
+        let signature = keys.sign(report);
+        let json = serde_json::to_string(&signature).unwrap();
+        let parsed: DetachedSignature = serde_json::from_str(&json).unwrap();
+        assert!(keys.public_key().verify(report, &parsed).is_ok());
+        assert!(keys.public_key().verify(b"{\"schema_version\":2}", &parsed).is_err());
+        let error = other.public_key().verify(report, &parsed).unwrap_err();
+        assert!(error.to_string().starts_with("signed with key"));
+
+        let jws = keys.jws(report);
+        assert_eq!(jws.split('.').count(), 3);
+        assert_eq!(keys.public_key().verify_jws(&jws).unwrap(), report);
+        assert!(other.public_key().verify_jws(&jws).is_err());
+        let mut parts: Vec<&str> = jws.split('.').collect();
+        let tampered = URL_SAFE_NO_PAD.encode(b"{}");
+        parts[1] = &tampered;
+        assert!(keys.public_key().verify_jws(&parts.join(".")).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Policy snapshots of the current filesystem state.
+//!
+//! `halo snapshot --emit-config /etc /var/log` records the mode and owner of every file and
+//! directory below the given paths and writes them as a TOML policy with one `[[perm_rules]]`
+//! and one `[[owner_rules]]` entry per path: "lock in what I have now". Auditing against the
+//! emitted policy later reports every change since the snapshot. The policy is a starting
+//! point to prune and adjust, not a statement that the current state is secure.
+//!
+//! Symlinks are skipped; their mode is meaningless and their targets are audited where they
+//! live. Unreadable directories are skipped as well.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::snapshot::Snapshot;
+//! let snapshot = Snapshot::capture(&["/etc/ssh".into()]);
+//! println!("{}", snapshot.to_toml().unwrap());
+//! ```
+use crate::audit::permissions::audit_permissions::Importance;
+use crate::audit::sysroot;
+use crate::audit::toml_config::{ModeValue, OwnerConfig, PermissionConfig};
+use crate::render_output::{DataList, Renderable};
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Mode and owner of one path.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnapshotEntry {
+    pub path: PathBuf,
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub is_dir: bool,
+}
+
+/// Modes and owners found below a set of paths, in walk order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Snapshot {
+    pub entries: Vec<SnapshotEntry>,
+}
+
+// Shape of the emitted policy; the field names match `AuditConfig`
+#[derive(Serialize)]
+struct EmittedConfig {
+    perm_rules: Vec<PermissionConfig>,
+    owner_rules: Vec<OwnerConfig>,
+}
+
+impl Snapshot {
+    /// Records every file and directory at and below `paths`, sorted within each directory.
+    pub fn capture(paths: &[PathBuf]) -> Self {
+        let mut snapshot = Snapshot::default();
+        for path in paths {
+            snapshot.walk(path);
+        }
+        snapshot
+    }
+
+    fn walk(&mut self, path: &Path) {
+        let Ok(meta) = sysroot::symlink_metadata(path) else {
+            return;
+        };
+        if meta.file_type().is_symlink() {
+            return;
+        }
+        self.entries.push(SnapshotEntry {
+            path: path.to_path_buf(),
+            mode: meta.mode() & 0o777,
+            uid: meta.uid(),
+            gid: meta.gid(),
+            is_dir: meta.is_dir(),
+        });
+        if meta.is_dir()
+            && let Ok(mut children) = sysroot::read_dir(path)
+        {
+            children.sort();
+            for child in children {
+                self.walk(&child);
+            }
+        }
+    }
+
+    /// The snapshot as a TOML policy with one permission and one ownership rule per path.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        let config = EmittedConfig {
+            perm_rules: self.entries.iter().map(SnapshotEntry::permission_rule).collect(),
+            owner_rules: self.entries.iter().map(SnapshotEntry::owner_rule).collect(),
+        };
+        let header = "# Generated by `halo snapshot --emit-config`: the modes and owners found at snapshot time\n\n";
+        Ok(format!("{}{}", header, toml::to_string(&config)?))
+    }
+}
+
+impl SnapshotEntry {
+    /// Rule expecting the recorded mode, without recursion.
+    pub fn permission_rule(&self) -> PermissionConfig {
+        // Octal digits written as a decimal number, e.g. 644, as in hand-written configs
+        let digits = format!("{:o}", self.mode).parse().unwrap_or_default();
+        PermissionConfig {
+            path: self.path.display().to_string(),
+            expected_mode: ModeValue::Int(digits),
+            expected_dir_mode: None,
+            importance: Importance::Medium,
+            recursive: Some(false),
+            id: None,
+            optional: false,
+            mounts: None,
+            symlink_severity: None,
+        }
+    }
+
+    /// Rule expecting the recorded owner and group, without recursion.
+    pub fn owner_rule(&self) -> OwnerConfig {
+        OwnerConfig {
+            path: self.path.display().to_string(),
+            expected_uid: Some(self.uid),
+            expected_gid: Some(self.gid),
+            follow_symlinks: None,
+            recursive: None,
+            id: None,
+            optional: false,
+            symlink_severity: None,
+        }
+    }
+}
+
+impl Renderable for Snapshot {
+    fn to_datalist(&self) -> DataList {
+        self.entries
+            .iter()
+            .map(|e| {
+                let mut map = IndexMap::new();
+                map.insert("path".to_string(), e.path.display().to_string());
+                map.insert("type".to_string(), if e.is_dir { "dir" } else { "file" }.to_string());
+                map.insert("mode".to_string(), format!("{:o}", e.mode));
+                map.insert("uid".to_string(), e.uid.to_string());
+                map.insert("gid".to_string(), e.gid.to_string());
+                map
+            })
+            .collect()
+    }
+
+    fn pretty_print(&self) -> String {
+        let mut output = String::new();
+        for e in &self.entries {
+            output.push_str(&format!(
+                "{:>4o} {:>6}:{:<6} {}{}\n",
+                e.mode,
+                e.uid,
+                e.gid,
+                e.path.display(),
+                if e.is_dir { "/" } else { "" }
+            ));
+        }
+        output.push_str(&format!("\n{} paths\n", self.entries.len()));
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::toml_config::AuditConfig;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_emitted_config_round_trips() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("conf.d")).unwrap();
+        fs::write(dir.path().join("conf.d/app.conf"), "x").unwrap();
+        fs::set_permissions(dir.path().join("conf.d/app.conf"), fs::Permissions::from_mode(0o640)).unwrap();
+        std::os::unix::fs::symlink("conf.d/app.conf", dir.path().join("link")).unwrap();
+
+        let snapshot = Snapshot::capture(&[dir.path().to_path_buf()]);
+        assert_eq!(snapshot.entries.len(), 3);
+        let config: AuditConfig = toml::from_str(&snapshot.to_toml().unwrap()).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.perm_rules[2].mode().unwrap(), 0o640);
+        assert_eq!(config.owner_rules.len(), 3);
+
+        // Auditing the unchanged tree against the snapshot passes
+        let results = config.permission_results().unwrap();
+        assert!(results.iter().all(|r| r.status == crate::Status::Pass), "{:?}", results);
+        assert!(config.ownership_results().unwrap().iter().all(|r| r.pass));
+    }
+}
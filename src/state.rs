@@ -0,0 +1,325 @@
+//! Persistent state storage for HALO.
+//!
+//! Audit runs can be recorded to a state directory so later commands (pruning, history,
+//! acknowledgments) can work with previous results. Each run is stored as a JSON file
+//! under `runs/`, named after the time it was recorded.
+//!
+//! The state directory is `$HALO_STATE_DIR` if set, otherwise `$XDG_STATE_HOME/halo`,
+//! `~/.local/state/halo`, or `/var/lib/halo` as a last resort.
+//!
+//! Acknowledged findings are kept in `acks.json`, see [`StateStore::acknowledge`]. Undo
+//! journals of applied fixes are kept under `journals/`, see [`StateStore::new_journal`].
+//! The key pair for [signing reports](crate::signing) is kept under `keys/`.
+//!
+//! # Retention
+//! [`RetentionPolicy`] bounds how many runs are kept, so the state directory does not grow
+//! without bound on small devices like a Raspberry Pi.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::state::{RetentionPolicy, StateStore};
+//! let dir = std::env::temp_dir().join("halo-state-doc");
+//! let store = StateStore::new(dir.clone());
+//! store.record_run("[]").unwrap();
+//! let policy = RetentionPolicy { keep_runs: Some(10), keep_days: Some(30) };
+//! let removed = store.prune(&policy).unwrap();
+//! println!("Removed {} runs", removed.len());
+//! # std::fs::remove_dir_all(dir).ok();
+//! ```
+use crate::ack::Acknowledgment;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// A directory holding HALO's persistent state.
+#[derive(Debug, Clone)]
+pub struct StateStore {
+    pub dir: PathBuf,
+}
+
+/// A run previously recorded in the state store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredRun {
+    /// Run identifier (`<unix seconds>-<nanoseconds>`)
+    pub id: String,
+    /// Path of the stored report
+    pub path: PathBuf,
+    /// Unix timestamp (seconds) when the run was recorded
+    pub timestamp: u64,
+}
+
+/// Limits on how many recorded runs are kept.
+///
+/// A run is removed when it falls outside *either* limit: it is not among the newest
+/// `keep_runs` runs, or it is older than `keep_days` days. `None` disables a limit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionPolicy {
+    pub keep_runs: Option<usize>,
+    pub keep_days: Option<u64>,
+}
+
+/// Keeps the newest 100 runs from the last 90 days.
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_runs: Some(100),
+            keep_days: Some(90),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Selects the runs this policy would remove.
+    ///
+    /// # Arguments
+    /// * `runs` - Recorded runs, in any order
+    /// * `now` - Current Unix timestamp in seconds
+    ///
+    /// # Returns
+    /// The expired runs, oldest first.
+    pub fn expired<'a>(&self, runs: &'a [StoredRun], now: u64) -> Vec<&'a StoredRun> {
+        let mut newest_first: Vec<&StoredRun> = runs.iter().collect();
+        newest_first.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(b.id.cmp(&a.id)));
+
+        let mut expired: Vec<&StoredRun> = newest_first
+            .into_iter()
+            .enumerate()
+            .filter(|(i, run)| {
+                let over_count = self.keep_runs.is_some_and(|n| *i >= n);
+                let too_old = self
+                    .keep_days
+                    .is_some_and(|d| now.saturating_sub(run.timestamp) > d * SECONDS_PER_DAY);
+                over_count || too_old
+            })
+            .map(|(_, run)| run)
+            .collect();
+        expired.reverse();
+        expired
+    }
+}
+
+impl StateStore {
+    /// Create a store rooted at the given directory.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Resolve the default state directory from the environment.
+    pub fn default_dir() -> PathBuf {
+        if let Ok(dir) = std::env::var("HALO_STATE_DIR") {
+            return PathBuf::from(dir);
+        }
+        if let Ok(dir) = std::env::var("XDG_STATE_HOME") {
+            return PathBuf::from(dir).join("halo");
+        }
+        if let Ok(home) = std::env::var("HOME") {
+            return PathBuf::from(home).join(".local/state/halo");
+        }
+        PathBuf::from("/var/lib/halo")
+    }
+
+    /// Directory containing recorded runs.
+    pub fn runs_dir(&self) -> PathBuf {
+        self.dir.join("runs")
+    }
+
+    /// Record a serialized report as a new run.
+    ///
+    /// # Returns
+    /// The stored run.
+    pub fn record_run(&self, report_json: &str) -> io::Result<StoredRun> {
+        let runs_dir = self.runs_dir();
+        fs::create_dir_all(&runs_dir)?;
+        let (id, timestamp) = Self::new_id()?;
+        let path = runs_dir.join(format!("{}.json", id));
+        fs::write(&path, report_json)?;
+        Ok(StoredRun { id, path, timestamp })
+    }
+
+    // Id of a new run or journal (`<unix seconds>-<nanoseconds>`) and its timestamp
+    fn new_id() -> io::Result<(String, u64)> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(io::Error::other)?;
+        Ok((format!("{}-{:09}", now.as_secs(), now.subsec_nanos()), now.as_secs()))
+    }
+
+    /// Directory containing undo journals of applied fixes.
+    pub fn journals_dir(&self) -> PathBuf {
+        self.dir.join("journals")
+    }
+
+    /// Directory containing the report signing key pair.
+    pub fn keys_dir(&self) -> PathBuf {
+        self.dir.join("keys")
+    }
+
+    /// Path for a new undo journal, `journals/<id>.jsonl`; creates the directory.
+    pub fn new_journal(&self) -> io::Result<PathBuf> {
+        let dir = self.journals_dir();
+        fs::create_dir_all(&dir)?;
+        Ok(dir.join(format!("{}.jsonl", Self::new_id()?.0)))
+    }
+
+    /// List recorded runs, oldest first.
+    ///
+    /// Files in `runs/` that do not follow the run naming scheme are ignored.
+    pub fn list_runs(&self) -> io::Result<Vec<StoredRun>> {
+        let entries = match fs::read_dir(self.runs_dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut runs: Vec<StoredRun> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let id = path.file_name()?.to_str()?.strip_suffix(".json")?.to_string();
+                let timestamp = id.split('-').next()?.parse().ok()?;
+                Some(StoredRun {
+                    id,
+                    path,
+                    timestamp,
+                })
+            })
+            .collect();
+        runs.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)));
+        Ok(runs)
+    }
+
+    /// File holding acknowledged findings.
+    pub fn acks_path(&self) -> PathBuf {
+        self.dir.join("acks.json")
+    }
+
+    /// Load all acknowledgments; an absent file means none.
+    pub fn acknowledgments(&self) -> io::Result<Vec<Acknowledgment>> {
+        match fs::read_to_string(self.acks_path()) {
+            Ok(content) => serde_json::from_str(&content).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Record an acknowledgment, replacing any previous one for the same finding.
+    pub fn acknowledge(&self, ack: Acknowledgment) -> io::Result<()> {
+        let mut acks = self.acknowledgments()?;
+        acks.retain(|a| a.id != ack.id);
+        acks.push(ack);
+        self.write_acks(&acks)
+    }
+
+    /// Remove the acknowledgment for a finding.
+    ///
+    /// # Returns
+    /// `true` if an acknowledgment was removed.
+    pub fn unacknowledge(&self, id: &str) -> io::Result<bool> {
+        let mut acks = self.acknowledgments()?;
+        let before = acks.len();
+        acks.retain(|a| a.id != id);
+        if acks.len() == before {
+            return Ok(false);
+        }
+        self.write_acks(&acks)?;
+        Ok(true)
+    }
+
+    fn write_acks(&self, acks: &[Acknowledgment]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string_pretty(acks).map_err(io::Error::other)?;
+        fs::write(self.acks_path(), json)
+    }
+
+    /// Runs that would be removed by `policy`, without removing them.
+    pub fn expired_runs(&self, policy: &RetentionPolicy) -> io::Result<Vec<StoredRun>> {
+        let runs = self.list_runs()?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(io::Error::other)?
+            .as_secs();
+        Ok(policy.expired(&runs, now).into_iter().cloned().collect())
+    }
+
+    /// Remove runs outside the retention policy.
+    ///
+    /// # Returns
+    /// The removed runs, oldest first.
+    pub fn prune(&self, policy: &RetentionPolicy) -> io::Result<Vec<StoredRun>> {
+        let expired = self.expired_runs(policy)?;
+        for run in &expired {
+            fs::remove_file(&run.path)?;
+        }
+        Ok(expired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(timestamp: u64) -> StoredRun {
+        StoredRun {
+            id: format!("{}-000000000", timestamp),
+            path: PathBuf::from(format!("/tmp/{}.json", timestamp)),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_keep_runs() {
+        let runs: Vec<StoredRun> = (1..=5).map(run).collect();
+        let policy = RetentionPolicy {
+            keep_runs: Some(2),
+            keep_days: None,
+        };
+        let expired: Vec<u64> = policy.expired(&runs, 10).iter().map(|r| r.timestamp).collect();
+        assert_eq!(expired, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_keep_days() {
+        let now = 100 * SECONDS_PER_DAY;
+        let runs = vec![run(now - 40 * SECONDS_PER_DAY), run(now - SECONDS_PER_DAY)];
+        let policy = RetentionPolicy {
+            keep_runs: None,
+            keep_days: Some(30),
+        };
+        let expired = policy.expired(&runs, now);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].timestamp, now - 40 * SECONDS_PER_DAY);
+    }
+
+    #[test]
+    fn test_record_list_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = StateStore::new(dir.path().to_path_buf());
+        for _ in 0..3 {
+            store.record_run("[]").unwrap();
+        }
+        assert_eq!(store.list_runs().unwrap().len(), 3);
+        let policy = RetentionPolicy {
+            keep_runs: Some(1),
+            keep_days: None,
+        };
+        assert_eq!(store.prune(&policy).unwrap().len(), 2);
+        assert_eq!(store.list_runs().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_acknowledgments() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = StateStore::new(dir.path().join("state"));
+        assert!(store.acknowledgments().unwrap().is_empty());
+        let finding = crate::Finding::fail("check", "/etc/x", crate::Severity::Low, "bad");
+        store.acknowledge(Acknowledgment::new(&finding, "first")).unwrap();
+        store.acknowledge(Acknowledgment::new(&finding, "second")).unwrap();
+        let acks = store.acknowledgments().unwrap();
+        assert_eq!(acks.len(), 1);
+        assert_eq!(acks[0].comment, "second");
+        assert!(store.unacknowledge(&finding.id).unwrap());
+        assert!(!store.unacknowledge(&finding.id).unwrap());
+    }
+}
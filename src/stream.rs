@@ -0,0 +1,230 @@
+//! Streaming JSON Lines output for HALO.
+//!
+//! The other formats render a report after the run. A [`ResultStream`] instead writes one JSON
+//! object per line as results are produced: permission results as the walk visits each file,
+//! findings and ownership results as each audit completes. Huge recursive audits can then be
+//! piped into `jq` or a log shipper such as Loki while they run.
+//!
+//! Every line carries a `kind` (`permission`, `ownership`, `finding` or `waived`) and the
+//! `target` that produced it, next to the fields of the result. Waivers, acknowledgments, the
+//! severity threshold and low-footprint mode apply per result, so the stream holds the same
+//! results as the final report.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::{Audit, Target};
+//! use alhalo::stream::ResultStream;
+//! let report = Audit::new()
+//!     .target(Target::Sysctl)
+//!     .run_streaming(ResultStream::new(std::io::stdout()))
+//!     .unwrap();
+//! eprintln!("{} failures", report.failure_count());
+//! ```
+use crate::ack::Acknowledgment;
+use crate::audit::finding::Finding;
+use crate::audit::ownership::ownership::OwnershipResult;
+use crate::audit::permissions::audit_permissions::{PermissionResults, Severity};
+use crate::report::AuditReport;
+use crate::risk::UNATTRIBUTED;
+use crate::waiver::Waiver;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::io::{self, Write};
+
+/// Writes audit results as JSON Lines while the audit runs.
+pub struct ResultStream {
+    writer: Box<dyn Write + Send>,
+    waivers: Vec<Waiver>,
+    today: i64,
+    acknowledgments: Vec<Acknowledgment>,
+    min_severity: Option<Severity>,
+    keep_passing: bool,
+    target: String,
+    written: usize,
+    /// First write error; later results are dropped, e.g. after the reader closed the pipe
+    error: Option<io::Error>,
+}
+
+impl fmt::Debug for ResultStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResultStream")
+            .field("target", &self.target)
+            .field("written", &self.written)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ResultStream {
+    /// Stream every result to `writer`.
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Box::new(writer),
+            waivers: Vec::new(),
+            today: crate::waiver::today(),
+            acknowledgments: Vec::new(),
+            min_severity: None,
+            keep_passing: true,
+            target: UNATTRIBUTED.to_string(),
+            written: 0,
+            error: None,
+        }
+    }
+
+    /// Mark findings covered by `acks` as acknowledged.
+    pub fn acknowledgments(mut self, acks: Vec<Acknowledgment>) -> Self {
+        self.acknowledgments = acks;
+        self
+    }
+
+    /// Only stream failures at least this severe; passing results are kept.
+    pub fn min_severity(mut self, min: Severity) -> Self {
+        self.min_severity = Some(min);
+        self
+    }
+
+    /// Only stream failures and stricter-than-expected permissions, as with
+    /// [`AuditReport::retain_failures`].
+    pub fn only_failures(mut self) -> Self {
+        self.keep_passing = false;
+        self
+    }
+
+    /// Stream failures covered by an unexpired waiver as `waived` lines.
+    pub(crate) fn add_waivers(&mut self, waivers: Vec<Waiver>) {
+        self.waivers.extend(waivers);
+    }
+
+    /// Drop passing results, as in low-footprint mode.
+    pub(crate) fn set_keep_passing(&mut self, keep: bool) {
+        self.keep_passing = keep;
+    }
+
+    /// Target reported for the following results.
+    pub(crate) fn set_target(&mut self, target: &str) {
+        self.target = target.to_string();
+    }
+
+    /// Number of lines written.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Writes a permission result.
+    pub fn permission(&mut self, result: &PermissionResults) {
+        self.write_report(AuditReport {
+            permissions: vec![result.clone()],
+            ..Default::default()
+        });
+    }
+
+    /// Writes an ownership result.
+    pub fn ownership(&mut self, result: &OwnershipResult) {
+        self.write_report(AuditReport {
+            ownership: vec![result.clone()],
+            ..Default::default()
+        });
+    }
+
+    /// Writes a finding.
+    pub fn finding(&mut self, finding: &Finding) {
+        self.write_report(AuditReport {
+            findings: vec![finding.clone()],
+            ..Default::default()
+        });
+    }
+
+    /// Flushes the writer.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` with the number of lines written.
+    /// * `Err` with the first write error.
+    pub fn finish(mut self) -> io::Result<usize> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.writer.flush()?;
+        Ok(self.written)
+    }
+
+    // Filters a single-result report like the final report and writes what remains
+    fn write_report(&mut self, mut report: AuditReport) {
+        report.apply_acknowledgments(&self.acknowledgments);
+        report.apply_waivers(&self.waivers, self.today);
+        if !self.keep_passing {
+            report.retain_failures();
+        }
+        if let Some(min) = &self.min_severity {
+            report.retain_min_severity(min);
+        }
+        for r in &report.permissions {
+            self.write_line("permission", r);
+        }
+        for r in &report.ownership {
+            self.write_line("ownership", r);
+        }
+        for f in &report.findings {
+            self.write_line("finding", f);
+        }
+        for w in &report.waived {
+            self.write_line("waived", w);
+        }
+    }
+
+    fn write_line<T: Serialize>(&mut self, kind: &str, result: &T) {
+        if self.error.is_some() {
+            return;
+        }
+        let mut value = serde_json::to_value(result).unwrap_or(Value::Null);
+        if let Value::Object(map) = &mut value {
+            map.insert("kind".to_string(), Value::from(kind));
+            map.insert("target".to_string(), Value::from(self.target.as_str()));
+        }
+        match writeln!(self.writer, "{}", value) {
+            Ok(()) => self.written += 1,
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    // Writer whose output the test can read back
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_lines_are_filtered_like_the_report() {
+        let out = Shared::default();
+        let mut stream = ResultStream::new(out.clone());
+        stream.add_waivers(vec![Waiver::new("/srv", "accepted")]);
+        stream.set_keep_passing(false);
+        stream.set_target("sudoers");
+        stream.finding(&Finding::pass("a", "/etc/a", "ok"));
+        stream.finding(&Finding::fail("b", "/etc/b", Severity::High, "bad"));
+        stream.finding(&Finding::fail("c", "/srv/c", Severity::High, "bad"));
+        assert_eq!(stream.finish().unwrap(), 2);
+
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["kind"], "finding");
+        assert_eq!(lines[0]["target"], "sudoers");
+        assert_eq!(lines[0]["check"], "b");
+        assert_eq!(lines[1]["kind"], "waived");
+        assert_eq!(lines[1]["reason"], "accepted");
+    }
+}
@@ -0,0 +1,154 @@
+//! Team ownership map for splitting reports by responsible team.
+//!
+//! A fleet report lists every failing file on a host, but different teams own different parts
+//! of the filesystem. A [`TeamMap`] assigns path prefixes to teams so a report can be filtered
+//! to one team ([`AuditReport::retain_team`]) or summarized per team
+//! ([`AuditReport::team_summaries`]).
+//!
+//! The map is a TOML file with one `[[owners]]` entry per prefix. When prefixes overlap, the
+//! longest match wins; paths matching no prefix belong to [`UNASSIGNED`].
+//!
+//! ```toml
+//! [[owners]]
+//! path_prefix = "/etc/nginx"
+//! team = "web-team"
+//!
+//! [[owners]]
+//! path_prefix = "/etc"
+//! team = "platform"
+//! ```
+//!
+//! [`AuditReport::retain_team`]: crate::AuditReport::retain_team
+//! [`AuditReport::team_summaries`]: crate::AuditReport::team_summaries
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::teams::TeamMap;
+//! use std::path::Path;
+//! let map: TeamMap = toml::from_str(
+//!     "[[owners]]\npath_prefix = \"/etc/nginx\"\nteam = \"web-team\"\n",
+//! ).unwrap();
+//! assert_eq!(map.team_for(Path::new("/etc/nginx/nginx.conf")), "web-team");
+//! assert_eq!(map.team_for(Path::new("/etc/shadow")), "unassigned");
+//! ```
+use crate::render_output::{DataList, Renderable};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Team of paths that match no prefix in the map.
+pub const UNASSIGNED: &str = "unassigned";
+
+/// Assigns a path prefix to a team.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OwnerMapping {
+    /// Paths at or below this prefix belong to `team`
+    pub path_prefix: PathBuf,
+    /// Name of the responsible team
+    pub team: String,
+}
+
+/// Path prefix to team mapping, loaded from TOML.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TeamMap {
+    #[serde(default)]
+    pub owners: Vec<OwnerMapping>,
+}
+
+impl TeamMap {
+    /// Load a team map from a TOML file.
+    ///
+    /// # Returns
+    /// * `Ok(TeamMap)` if the file parses and every entry names a team.
+    /// * `Err` if the file cannot be read, fails to parse or has an empty team name.
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read team map {}: {}", path.display(), e))?;
+        let map: TeamMap = toml::from_str(&content)?;
+        if let Some(entry) = map.owners.iter().find(|o| o.team.trim().is_empty()) {
+            return Err(format!(
+                "Team map entry for {} has an empty team name",
+                entry.path_prefix.display()
+            )
+            .into());
+        }
+        Ok(map)
+    }
+
+    /// Team responsible for `path`: the longest matching prefix, or [`UNASSIGNED`].
+    ///
+    /// Prefixes match whole path components, so `/etc/nginx` does not match `/etc/nginx2`.
+    pub fn team_for(&self, path: &Path) -> &str {
+        self.owners
+            .iter()
+            .filter(|o| path.starts_with(&o.path_prefix))
+            .max_by_key(|o| o.path_prefix.components().count())
+            .map(|o| o.team.as_str())
+            .unwrap_or(UNASSIGNED)
+    }
+
+    /// Team names in map order, without duplicates.
+    pub fn teams(&self) -> Vec<&str> {
+        let mut teams: Vec<&str> = Vec::new();
+        for owner in &self.owners {
+            if !teams.contains(&owner.team.as_str()) {
+                teams.push(&owner.team);
+            }
+        }
+        teams
+    }
+}
+
+/// Result counts for one team.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct TeamSummary {
+    pub team: String,
+    pub checked: usize,
+    pub failed: usize,
+    pub acknowledged: usize,
+}
+
+impl Renderable for TeamSummary {
+    fn to_datalist(&self) -> DataList {
+        let mut map = IndexMap::new();
+        map.insert("team".to_string(), self.team.clone());
+        map.insert("checked".to_string(), self.checked.to_string());
+        map.insert("failed".to_string(), self.failed.to_string());
+        map.insert("acknowledged".to_string(), self.acknowledged.to_string());
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        format!(
+            "{}: {} checked, {} failed, {} acknowledged",
+            self.team, self.checked, self.failed, self.acknowledged
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let map: TeamMap = toml::from_str(
+            "[[owners]]\npath_prefix = \"/etc\"\nteam = \"platform\"\n\n\
+             [[owners]]\npath_prefix = \"/etc/nginx\"\nteam = \"web-team\"\n",
+        )
+        .unwrap();
+        assert_eq!(map.team_for(Path::new("/etc/nginx/sites/a.conf")), "web-team");
+        assert_eq!(map.team_for(Path::new("/etc/nginx2")), "platform");
+        assert_eq!(map.team_for(Path::new("/var/log/syslog")), UNASSIGNED);
+        assert_eq!(map.teams(), vec!["platform", "web-team"]);
+    }
+
+    #[test]
+    fn test_empty_team_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("teams.toml");
+        fs::write(&path, "[[owners]]\npath_prefix = \"/etc\"\nteam = \"\"\n").unwrap();
+        assert!(TeamMap::from_file(&path).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! User-defined output templates for HALO reports.
+//!
+//! `halo check --target all --template nagios.hbs` renders the report through a
+//! [Handlebars](https://handlebarsjs.com/) template instead of a built-in format, for
+//! monitoring plugin output, wiki markup or anything else that is plain text. Output is not
+//! HTML-escaped.
+//!
+//! # Template data
+//! - `metadata`: `hostname`, `kernel`, `halo_version`, `timestamp`, `targets`,
+//!   `duration_ms` and, for an alternate root, `root` of the run, or null for a report not
+//!   produced by [`Audit`](crate::Audit)
+//! - `summary`: `checked`, `failed`, `acknowledged`, `waived`, `risk_score` and
+//!   `worst_severity` (the most severe open failure, or null)
+//! - `failures_by_severity`: open failure counts keyed `Critical`, `High`, `Medium`, `Low` and
+//!   `Info`
+//! - `failures`: every open failure, with a `kind` of `permission`, `ownership` or `finding`
+//! - `permissions`, `ownership`, `findings`, `waived`: all results, with the same fields as
+//!   the CSV and Markdown formats
+//! - `targets`: one entry per target with `name`, `results`, `failed`, `permissions`,
+//!   `ownership` and `findings`
+//!
+//! The built-in helpers `if`, `each`, `eq`, `gt`, `len` and friends are available.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::AuditReport;
+//! use alhalo::template::ReportTemplate;
+//! let template = ReportTemplate::from_str(
+//!     "{{#if summary.failed}}WARNING{{else}}OK{{/if}} - {{summary.failed}} failures",
+//! )
+//! .unwrap();
+//! assert_eq!(template.render(&AuditReport::default()).unwrap(), "OK - 0 failures");
+//! ```
+use crate::audit::permissions::audit_permissions::Severity;
+use crate::render_output::{DataMap, Renderable};
+use crate::report::AuditReport;
+use handlebars::Handlebars;
+use serde_json::{Map, Value, json};
+use std::fs;
+use std::path::Path;
+
+const NAME: &str = "report";
+
+/// A Handlebars template that renders an [`AuditReport`].
+pub struct ReportTemplate {
+    registry: Handlebars<'static>,
+}
+
+impl std::fmt::Debug for ReportTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReportTemplate").finish_non_exhaustive()
+    }
+}
+
+impl ReportTemplate {
+    /// Compiles `source`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(source: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut registry = Handlebars::new();
+        registry.register_escape_fn(handlebars::no_escape);
+        registry.register_template_string(NAME, source)?;
+        Ok(Self { registry })
+    }
+
+    /// Reads and compiles the template at `path`.
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read template {}: {}", path.display(), e))?;
+        Self::from_str(&source).map_err(|e| format!("Invalid template {}: {}", path.display(), e).into())
+    }
+
+    /// Renders `report`; see the [module docs](self) for the available data.
+    pub fn render(&self, report: &AuditReport) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(self.registry.render(NAME, &context(report))?)
+    }
+}
+
+// Template data for `report`
+fn context(report: &AuditReport) -> Value {
+    let severities = [Severity::Critical, Severity::High, Severity::Medium, Severity::Low, Severity::Info];
+    let counts = report.by_severity();
+    let mut by_severity = Map::new();
+    for s in &severities {
+        let count = counts.iter().find(|c| &c.severity == s).map_or(0, |c| c.failures);
+        by_severity.insert(format!("{:?}", s), Value::from(count));
+    }
+    let worst = counts.first().map(|c| format!("{:?}", c.severity));
+
+    let mut failures = Vec::new();
+    failures.extend(kind_rows("permission", report.failed_permissions().flat_map(|r| r.to_datalist())));
+    failures.extend(kind_rows(
+        "ownership",
+        report.ownership.iter().filter(|r| !r.pass).flat_map(|r| r.to_datalist()),
+    ));
+    failures.extend(kind_rows(
+        "finding",
+        report.findings.iter().filter(|f| f.is_open_failure()).flat_map(|f| f.to_datalist()),
+    ));
+
+    let targets: Vec<Value> = report
+        .by_target()
+        .iter()
+        .map(|t| {
+            json!({
+                "name": t.target,
+                "results": t.len(),
+                "failed": t.failed(),
+                "permissions": rows(t.permissions.iter().flat_map(|r| r.to_datalist())),
+                "ownership": rows(t.ownership.iter().flat_map(|r| r.to_datalist())),
+                "findings": rows(t.findings.iter().flat_map(|f| f.to_datalist())),
+            })
+        })
+        .collect();
+
+    json!({
+        "metadata": report.metadata,
+        "summary": {
+            "checked": report.len(),
+            "failed": report.failure_count(),
+            "acknowledged": report.acknowledged_count(),
+            "waived": report.waived.len(),
+            "risk_score": report.risk_score().total,
+            "worst_severity": worst,
+        },
+        "failures_by_severity": by_severity,
+        "failures": failures,
+        "permissions": rows(report.permissions.iter().flat_map(|r| r.to_datalist())),
+        "ownership": rows(report.ownership.iter().flat_map(|r| r.to_datalist())),
+        "findings": rows(report.findings.iter().flat_map(|f| f.to_datalist())),
+        "waived": rows(report.waived.to_datalist()),
+        "targets": targets,
+    })
+}
+
+fn rows(data: impl IntoIterator<Item = DataMap>) -> Vec<Value> {
+    data.into_iter()
+        .map(|row| Value::Object(row.into_iter().map(|(k, v)| (k, Value::from(v))).collect()))
+        .collect()
+}
+
+// Rows of one result kind, each tagged with the kind
+fn kind_rows(kind: &str, data: impl IntoIterator<Item = DataMap>) -> Vec<Value> {
+    let mut tagged = rows(data);
+    for row in &mut tagged {
+        if let Value::Object(map) = row {
+            map.insert("kind".to_string(), Value::from(kind));
+        }
+    }
+    tagged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::finding::Finding;
+
+    #[test]
+    fn test_nagios_style_template() {
+        let mut report = AuditReport::default();
+        let mark = report.mark();
+        report.findings.push(Finding::fail("a", "/etc/sudoers", Severity::High, "NOPASSWD: ALL"));
+        report.findings.push(Finding::pass("b", "/etc/b", "ok & fine"));
+        report.attribute(mark, "sudoers");
+
+        let template = ReportTemplate::from_str(concat!(
+            "{{#if failures_by_severity.Critical}}CRITICAL{{else if summary.failed}}WARNING{{else}}OK{{/if}}",
+            " - {{summary.failed}}/{{summary.checked}} failed, worst {{summary.worst_severity}}\n",
+            "{{#each failures}}{{kind}} {{path}}: {{message}}\n{{/each}}",
+            "{{#each targets}}{{name}}={{failed}}{{/each}} {{findings.1.message}}",
+        ))
+        .unwrap();
+        assert_eq!(
+            template.render(&report).unwrap(),
+            "WARNING - 1/2 failed, worst High\nfinding /etc/sudoers: NOPASSWD: ALL\nsudoers=1 ok & fine"
+        );
+        assert!(ReportTemplate::from_str("{{#if}}").is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Trends across the runs in the history.
+//!
+//! `halo trend --since 30d` reads the runs recorded in the [history](crate::history) within
+//! a window and shows how the host evolved: a [`Trend`] has one [`TrendPoint`] per run with
+//! its open failures per severity, the failures that were new or resolved since the run
+//! before (as in [`ReportDiff`]) and its risk score. Text, CSV and Markdown output list the
+//! points, JSON adds the window, and the HTML page draws the risk score and failure count
+//! as a chart above the table.
+//!
+//! The first run in the window has no earlier run to compare with, so its new and resolved
+//! counts are empty.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::history::History;
+//! use alhalo::trend::Trend;
+//! use alhalo::Renderable;
+//! let dir = tempfile::tempdir().unwrap();
+//! let history = History::open(&dir.path().join("history.db")).unwrap();
+//! let trend = Trend::from_history(&history, 0).unwrap();
+//! trend.render_and_print(Some("pretty"));
+//! ```
+use crate::diff::ReportDiff;
+use crate::history::{History, format_utc};
+use crate::render_output::{DataList, DataMap, Renderable, Style, html_page, html_table, paint};
+use crate::report::AuditReport;
+use serde::Serialize;
+
+/// One run of a trend.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TrendPoint {
+    pub run_id: i64,
+    /// Start of the run in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Open failures
+    pub failed: usize,
+    pub critical: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    /// Failures new since the previous run in the trend
+    pub new_failures: Option<usize>,
+    /// Failures resolved since the previous run in the trend
+    pub resolved: Option<usize>,
+    pub risk_score: u32,
+}
+
+/// Failure counts and risk scores of the runs in a window, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Trend {
+    /// Start of the window in seconds since the Unix epoch
+    pub since: u64,
+    pub points: Vec<TrendPoint>,
+}
+
+impl Trend {
+    /// Builds the trend of the runs in `history` that started at or after `since`.
+    pub fn from_history(history: &History, since: u64) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut reports = Vec::new();
+        for run in history.runs_since(since)? {
+            if let Some(report) = history.report(run.id)? {
+                reports.push((run.id, run.timestamp, report));
+            }
+        }
+        Ok(Self::from_reports(since, &reports))
+    }
+
+    /// Builds a trend from `(run id, start time, report)` triples, oldest first.
+    pub fn from_reports(since: u64, reports: &[(i64, u64, AuditReport)]) -> Self {
+        let mut points = Vec::new();
+        let mut previous: Option<&AuditReport> = None;
+        for (run_id, timestamp, report) in reports {
+            let summary = report.summary();
+            let diff = previous.map(|p| ReportDiff::new(p, report));
+            points.push(TrendPoint {
+                run_id: *run_id,
+                timestamp: *timestamp,
+                failed: summary.failed,
+                critical: summary.critical,
+                high: summary.high,
+                medium: summary.medium,
+                low: summary.low,
+                new_failures: diff.as_ref().map(|d| d.new_failures),
+                resolved: diff.as_ref().map(|d| d.resolved),
+                risk_score: summary.risk_score,
+            });
+            previous = Some(report);
+        }
+        Self { since, points }
+    }
+
+    // Inline SVG chart of the risk score (line) and open failures (dashed line)
+    fn chart(&self) -> String {
+        let (width, height, pad) = (720.0, 220.0, 30.0);
+        let max = self
+            .points
+            .iter()
+            .map(|p| p.risk_score.max(p.failed as u32))
+            .max()
+            .unwrap_or(0)
+            .max(1) as f64;
+        let step = if self.points.len() > 1 {
+            (width - 2.0 * pad) / (self.points.len() - 1) as f64
+        } else {
+            0.0
+        };
+        let line = |value: &dyn Fn(&TrendPoint) -> f64| {
+            self.points
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let x = pad + i as f64 * step;
+                    let y = height - pad - value(p) / max * (height - 2.0 * pad);
+                    format!("{:.1},{:.1}", x, y)
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        format!(
+            "<svg viewBox=\"0 0 {w} {h}\" width=\"{w}\" height=\"{h}\" role=\"img\" aria-label=\"Risk score and failures over time\">\n\
+             <line x1=\"{p}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"#999\"/>\n\
+             <text x=\"{p}\" y=\"{t}\" font-size=\"12\">max {max}</text>\n\
+             <polyline fill=\"none\" stroke=\"#c0392b\" stroke-width=\"2\" points=\"{risk}\"><title>risk score</title></polyline>\n\
+             <polyline fill=\"none\" stroke=\"#2c3e50\" stroke-width=\"2\" stroke-dasharray=\"4 3\" points=\"{failed}\"><title>open failures</title></polyline>\n\
+             </svg>\n<p>Solid: risk score. Dashed: open failures.</p>\n",
+            w = width,
+            h = height,
+            p = pad,
+            r = width - pad,
+            b = height - pad,
+            t = pad - 10.0,
+            max = max,
+            risk = line(&|p| p.risk_score as f64),
+            failed = line(&|p| p.failed as f64),
+        )
+    }
+}
+
+// `+n` / `-n`, or `-` for the first run
+fn count(value: Option<usize>, sign: char) -> String {
+    value.map(|v| format!("{}{}", sign, v)).unwrap_or_else(|| "-".to_string())
+}
+
+impl Renderable for Trend {
+    fn to_datalist(&self) -> DataList {
+        self.points
+            .iter()
+            .map(|p| {
+                let mut map = DataMap::new();
+                map.insert("run_id".to_string(), p.run_id.to_string());
+                map.insert("timestamp".to_string(), p.timestamp.to_string());
+                map.insert("failed".to_string(), p.failed.to_string());
+                map.insert("critical".to_string(), p.critical.to_string());
+                map.insert("high".to_string(), p.high.to_string());
+                map.insert("medium".to_string(), p.medium.to_string());
+                map.insert("low".to_string(), p.low.to_string());
+                let optional = |v: Option<usize>| v.map(|v| v.to_string()).unwrap_or_default();
+                map.insert("new_failures".to_string(), optional(p.new_failures));
+                map.insert("resolved".to_string(), optional(p.resolved));
+                map.insert("risk_score".to_string(), p.risk_score.to_string());
+                map
+            })
+            .collect()
+    }
+
+    fn pretty_print(&self) -> String {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return format!("No runs recorded since {}\n", format_utc(self.since));
+        };
+        let mut output = format!(
+            "{} runs since {}: failures {} -> {}, risk score {} -> {}\n",
+            self.points.len(),
+            format_utc(self.since),
+            first.failed,
+            last.failed,
+            first.risk_score,
+            last.risk_score
+        );
+        let max = self.points.iter().map(|p| p.risk_score).max().unwrap_or(0).max(1);
+        for p in &self.points {
+            let bar = "#".repeat((p.risk_score as usize * 30).div_ceil(max as usize));
+            let style = match p.new_failures {
+                Some(n) if n > 0 => Style::Red,
+                _ if p.resolved.is_some_and(|n| n > 0) => Style::Green,
+                _ => Style::Dim,
+            };
+            output.push_str(&format!(
+                "#{:<5} {}  {:>4} failed {:>4} {:>4}  risk {:>4} {}\n",
+                p.run_id,
+                format_utc(p.timestamp),
+                p.failed,
+                paint(&format!("{:>4}", count(p.new_failures, '+')), style),
+                count(p.resolved, '-'),
+                p.risk_score,
+                bar
+            ));
+        }
+        output
+    }
+
+    fn html(&self) -> String {
+        let title = format!("HALO trend since {}", format_utc(self.since));
+        if self.points.is_empty() {
+            return html_page(&title, "<p>No runs recorded.</p>\n");
+        }
+        let mut rows = self.to_datalist();
+        for (row, point) in rows.iter_mut().zip(&self.points) {
+            row.insert("timestamp".to_string(), format_utc(point.timestamp));
+        }
+        let body = format!(
+            "<p>{} runs</p>\n{}{}",
+            self.points.len(),
+            self.chart(),
+            html_table(&rows, &[])
+        );
+        html_page(&title, &body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::finding::Finding;
+    use crate::audit::permissions::audit_permissions::Severity;
+    use crate::report::RunMetadata;
+
+    fn report(timestamp: u64, failures: &[&str]) -> AuditReport {
+        AuditReport {
+            metadata: Some(RunMetadata {
+                timestamp,
+                ..Default::default()
+            }),
+            findings: failures
+                .iter()
+                .map(|path| Finding::fail("a", path, Severity::High, "x"))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_trend_from_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut history = History::open(&dir.path().join("history.db")).unwrap();
+        history.record(&report(1000, &["/etc/a"])).unwrap();
+        history.record(&report(2000, &["/etc/a", "/etc/b", "/etc/c"])).unwrap();
+        history.record(&report(3000, &["/etc/c"])).unwrap();
+
+        let trend = Trend::from_history(&history, 1500).unwrap();
+        assert_eq!(trend.points.len(), 2);
+        assert_eq!((trend.points[0].failed, trend.points[0].high), (3, 3));
+        assert_eq!(trend.points[0].new_failures, None);
+        assert_eq!((trend.points[1].new_failures, trend.points[1].resolved), (Some(0), Some(2)));
+        assert!(trend.points[0].risk_score > trend.points[1].risk_score);
+
+        let all = Trend::from_history(&history, 0).unwrap();
+        assert_eq!(all.points[1].new_failures, Some(2));
+        assert_eq!(all.to_datalist()[0]["new_failures"], "");
+        assert!(all.html().contains("<polyline"));
+        assert!(Trend::from_history(&history, 4000).unwrap().pretty_print().starts_with("No runs"));
+    }
+}
@@ -0,0 +1,220 @@
+//! Waivers for accepted risks.
+//!
+//! Long-lived deployments need exceptions: a data directory that must stay group-writable, a
+//! legacy service account kept until a migration finishes. A waiver in the TOML config names a
+//! path, optionally the rule it applies to, the reason and an expiry date. Failures it matches
+//! are moved out of the results into the report's `waived` section, so they no longer count
+//! as failures but stay visible. Once the expiry date has passed the waiver is ignored and the
+//! results fail again.
+//!
+//! Unlike [acknowledgments](crate::ack), which are recorded per finding with `halo ack`,
+//! waivers live in the policy file next to the rules, cover permission and ownership results
+//! as well as findings, and can cover a whole directory.
+//!
+//! # Example TOML
+//! ```toml
+//! [[waivers]]
+//! path = "/srv/shared"
+//! rule = "permissions"
+//! reason = "Group-writable upload area, ticket OPS-412"
+//! expires = "2026-12-31"
+//! ```
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::waiver::{Waiver, parse_date};
+//! use std::path::Path;
+//! let waiver = Waiver::new("/srv/shared", "legacy uploads").rule("permissions").expires("2026-12-31");
+//! assert!(waiver.matches("permissions", Path::new("/srv/shared/incoming")));
+//! assert!(!waiver.is_expired(parse_date("2026-12-31").unwrap()));
+//! assert!(waiver.is_expired(parse_date("2027-01-01").unwrap()));
+//! ```
+use crate::audit::permissions::audit_permissions::Severity;
+use crate::error::HaloError;
+use crate::render_output::{DataList, Renderable};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Rule id that waives permission results.
+pub const PERMISSIONS_RULE: &str = "permissions";
+/// Rule id that waives ownership results.
+pub const OWNERSHIP_RULE: &str = "ownership";
+
+/// An accepted risk for a path, optionally limited to one rule and a period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Waiver {
+    /// Waived path; also covers everything below it
+    pub path: PathBuf,
+    /// Check id of the waived findings, id of a permission or ownership rule (e.g.
+    /// `HALO-USER-0002`), or [`PERMISSIONS_RULE`] / [`OWNERSHIP_RULE`] for all of them; all rules
+    /// if unset
+    #[serde(default)]
+    pub rule: Option<String>,
+    /// Why the risk is accepted
+    pub reason: String,
+    /// Last day (`YYYY-MM-DD`, UTC) the waiver applies; never expires if unset
+    #[serde(default)]
+    pub expires: Option<String>,
+}
+
+impl Waiver {
+    /// Waive every rule for `path`, without expiry.
+    pub fn new(path: impl Into<PathBuf>, reason: &str) -> Self {
+        Self {
+            path: path.into(),
+            rule: None,
+            reason: reason.to_string(),
+            expires: None,
+        }
+    }
+
+    /// Only waive results of `rule`.
+    pub fn rule(mut self, rule: &str) -> Self {
+        self.rule = Some(rule.to_string());
+        self
+    }
+
+    /// Stop applying after `date` (`YYYY-MM-DD`).
+    pub fn expires(mut self, date: &str) -> Self {
+        self.expires = Some(date.to_string());
+        self
+    }
+
+    /// Checks that the waiver names a path and a reason and that the expiry date is valid.
+    pub fn validate(&self) -> Result<(), HaloError> {
+        if self.path.as_os_str().is_empty() {
+            return Err(HaloError::InvalidConfig("Waiver has an empty path.".to_string()));
+        }
+        if self.reason.trim().is_empty() {
+            return Err(HaloError::InvalidConfig(format!("Waiver for {} has no reason.", self.path.display())));
+        }
+        if let Some(date) = &self.expires
+            && parse_date(date).is_none()
+        {
+            return Err(HaloError::InvalidConfig(format!(
+                "Waiver for {} has invalid expiry '{}', expected YYYY-MM-DD.",
+                self.path.display(),
+                date
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns true if the waiver covers a result of `rule` for `path`.
+    pub fn matches(&self, rule: &str, path: &Path) -> bool {
+        self.rule.as_deref().is_none_or(|r| r == rule) && path.starts_with(&self.path)
+    }
+
+    /// Returns true if `today` (days since the Unix epoch) is after the expiry date.
+    ///
+    /// A waiver with an unparsable expiry date counts as expired.
+    pub fn is_expired(&self, today: i64) -> bool {
+        match &self.expires {
+            Some(date) => parse_date(date).is_none_or(|last| today > last),
+            None => false,
+        }
+    }
+}
+
+/// A failing result suppressed by a waiver.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaivedResult {
+    /// Check id or rule id; [`PERMISSIONS_RULE`] or [`OWNERSHIP_RULE`] for results without one
+    pub rule: String,
+    pub path: PathBuf,
+    pub severity: Severity,
+    /// What failed
+    pub message: String,
+    /// Reason given by the waiver
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+}
+
+impl Renderable for WaivedResult {
+    fn to_datalist(&self) -> DataList {
+        let mut map = IndexMap::new();
+        map.insert("rule".to_string(), self.rule.clone());
+        map.insert("path".to_string(), self.path.display().to_string());
+        map.insert("severity".to_string(), format!("{:?}", self.severity));
+        map.insert("message".to_string(), self.message.clone());
+        map.insert("reason".to_string(), self.reason.clone());
+        map.insert("expires".to_string(), self.expires.clone().unwrap_or_default());
+        vec![map]
+    }
+
+    fn pretty_print(&self) -> String {
+        let until = self
+            .expires
+            .as_deref()
+            .map(|d| format!(" until {}", d))
+            .unwrap_or_default();
+        format!(
+            "[waived{}] {:?} {}: {} - {} ({})",
+            until,
+            self.severity,
+            self.rule,
+            self.path.display(),
+            self.message,
+            self.reason
+        )
+    }
+}
+
+/// Days since 1970-01-01 of a `YYYY-MM-DD` date.
+pub fn parse_date(date: &str) -> Option<i64> {
+    let mut parts = date.trim().splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(crate::audit::tls::days_from_civil(year, month, day))
+}
+
+/// Current UTC day as days since the Unix epoch.
+pub fn today() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64 / 86_400)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2000-03-01"), Some(11_017));
+        assert_eq!(parse_date("2026-13-01"), None);
+        assert_eq!(parse_date("next week"), None);
+    }
+
+    #[test]
+    fn test_matching_and_expiry() {
+        let waiver: Waiver = toml::from_str(
+            r#"
+            path = "/srv/shared"
+            rule = "permissions"
+            reason = "upload area"
+            expires = "2026-12-31"
+            "#,
+        )
+        .unwrap();
+        assert!(waiver.validate().is_ok());
+        assert!(waiver.matches("permissions", Path::new("/srv/shared")));
+        assert!(!waiver.matches("permissions", Path::new("/srv/sharedx")));
+        assert!(!waiver.matches("ownership", Path::new("/srv/shared")));
+        assert!(Waiver::new("/srv", "all").matches("sudoers-nopasswd-all", Path::new("/srv/x")));
+
+        let last = parse_date("2026-12-31").unwrap();
+        assert!(!waiver.is_expired(last));
+        assert!(waiver.is_expired(last + 1));
+        assert!(Waiver::new("/srv", "x").expires("31.12.2026").validate().is_err());
+    }
+}
@@ -0,0 +1,151 @@
+//! Continuous auditing.
+//!
+//! `halo watch --interval 10m --target all` re-runs the selected audits on a schedule in the
+//! foreground and prints only what changed: a [`Watch`] keeps the previous report and
+//! compares each new one with it as a [`ReportDiff`]. The first run is compared with an
+//! empty report, so it lists every current failure once.
+//!
+//! # Example Usage
+//! ```rust
+//! use alhalo::watch::{Watch, parse_interval};
+//! use alhalo::{Audit, Renderable, Target};
+//! use std::ops::ControlFlow;
+//! let mut watch = Watch::new(parse_interval("10m").unwrap()).max_runs(1);
+//! watch
+//!     .run(
+//!         || Ok(Audit::new().target(Target::User).run()?),
+//!         |diff| {
+//!             diff.render_and_print(Some("pretty"));
+//!             ControlFlow::Continue(())
+//!         },
+//!     )
+//!     .unwrap();
+//! ```
+use crate::diff::ReportDiff;
+use crate::report::AuditReport;
+use std::ops::ControlFlow;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+/// Parses an interval such as `90`, `30s`, `10m`, `2h` or `1d`; plain numbers are seconds.
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: u64 = number.parse().map_err(|_| format!("invalid interval '{}'", s))?;
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        _ => return Err(format!("invalid interval '{}': use a number with s, m, h or d", s)),
+    };
+    match number.checked_mul(seconds) {
+        Some(0) => Err("interval must be greater than zero".to_string()),
+        Some(secs) => Ok(Duration::from_secs(secs)),
+        None => Err(format!("interval '{}' is too long", s)),
+    }
+}
+
+/// Re-runs an audit on a schedule and reports the changes between consecutive runs.
+#[derive(Debug)]
+pub struct Watch {
+    interval: Duration,
+    max_runs: Option<usize>,
+    previous: AuditReport,
+}
+
+impl Watch {
+    /// Run every `interval`, measured from the start of one run to the start of the next.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            max_runs: None,
+            previous: AuditReport::default(),
+        }
+    }
+
+    /// Stop after `runs` runs instead of running until stopped.
+    pub fn max_runs(mut self, runs: usize) -> Self {
+        self.max_runs = Some(runs);
+        self
+    }
+
+    /// Compares `report` with the previous one and keeps it for the next comparison.
+    pub fn observe(&mut self, report: AuditReport) -> ReportDiff {
+        let diff = ReportDiff::new(&self.previous, &report);
+        self.previous = report;
+        diff
+    }
+
+    /// Runs `audit` on the schedule and calls `on_change` with every diff that is not empty,
+    /// until `on_change` breaks or the maximum number of runs is reached. A failed run is
+    /// logged and skipped; the next run is compared with the last successful one.
+    pub fn run<A, C>(&mut self, mut audit: A, mut on_change: C) -> Result<(), Box<dyn std::error::Error>>
+    where
+        A: FnMut() -> Result<AuditReport, Box<dyn std::error::Error>>,
+        C: FnMut(&ReportDiff) -> ControlFlow<()>,
+    {
+        let mut runs = 0;
+        loop {
+            let started = Instant::now();
+            runs += 1;
+            match audit() {
+                Ok(report) => {
+                    let diff = self.observe(report);
+                    if diff.is_empty() {
+                        info!("Run {}: no changes", runs);
+                    } else if on_change(&diff).is_break() {
+                        return Ok(());
+                    }
+                }
+                Err(e) => error!("Run {} failed: {}", runs, e),
+            }
+            if self.max_runs.is_some_and(|max| runs >= max) {
+                return Ok(());
+            }
+            thread::sleep(self.interval.saturating_sub(started.elapsed()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::finding::Finding;
+    use crate::audit::permissions::audit_permissions::Severity;
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_interval("10m"), Ok(Duration::from_secs(600)));
+        assert_eq!(parse_interval("2h"), Ok(Duration::from_secs(7200)));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("10 minutes").is_err());
+        assert!(parse_interval("m").is_err());
+    }
+
+    #[test]
+    fn test_run_reports_only_changes() {
+        let failing = || {
+            let mut report = AuditReport::default();
+            report.findings.push(Finding::fail("sysctl", "/proc/sys/a", Severity::High, "a = 0"));
+            report
+        };
+        let reports = [failing(), failing(), AuditReport::default()];
+        let mut next = reports.into_iter();
+        let mut diffs = Vec::new();
+        Watch::new(Duration::from_millis(1))
+            .max_runs(3)
+            .run(
+                || Ok(next.next().unwrap()),
+                |diff| {
+                    diffs.push((diff.new_failures, diff.resolved));
+                    ControlFlow::Continue(())
+                },
+            )
+            .unwrap();
+        assert_eq!(diffs, [(1, 0), (0, 1)]);
+    }
+}
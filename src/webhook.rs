@@ -0,0 +1,196 @@
+//! Webhook delivery of HALO reports.
+//!
+//! A [`Webhook`] POSTs the JSON report (the same object as `check --format json`) to an HTTP
+//! endpoint, e.g. a Slack or Teams relay or a SOAR intake, so integrations need no wrapper
+//! script. Extra headers carry tokens; connection errors, timeouts, `429` and `5xx` responses
+//! are retried with exponential backoff, other responses fail at once.
+//!
+//! From the CLI: `halo check --target all --post-url https://... --post-header "Authorization: Bearer ..."`.
+//!
+//! # Example Usage
+//! ```rust,no_run
+//! use alhalo::{Audit, Target};
+//! use alhalo::webhook::Webhook;
+//! let report = Audit::new().target(Target::Sys).run().unwrap();
+//! Webhook::new("https://soar.example.com/intake/halo")
+//!     .header("Authorization", "Bearer token")
+//!     .retries(5)
+//!     .post(&report)
+//!     .unwrap();
+//! ```
+use crate::report::AuditReport;
+use std::thread;
+use std::time::Duration;
+use tracing::warn;
+
+/// An HTTP endpoint that receives reports as JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Webhook {
+    url: String,
+    headers: Vec<(String, String)>,
+    retries: u32,
+    backoff: Duration,
+    timeout: Duration,
+}
+
+impl Webhook {
+    /// POST to `url` with 3 retries, a 1 second initial backoff and a 30 second timeout.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: Vec::new(),
+            retries: 3,
+            backoff: Duration::from_secs(1),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Send this header with every request.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Retry a failed delivery up to `retries` times.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Wait before the first retry; the wait doubles for each further retry.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Give up on a single request after this long.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Endpoint the reports go to.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// POSTs `report` as JSON.
+    ///
+    /// # Returns
+    /// * `Ok(u16)` with the HTTP status of the accepted delivery.
+    /// * `Err` with the last error once the retries are used up, or at once for a response
+    ///   that is not worth retrying, such as `401` or `404`.
+    pub fn post(&self, report: &AuditReport) -> Result<u16, Box<dyn std::error::Error>> {
+        self.post_body(&serde_json::to_vec(report)?)
+    }
+
+    /// POSTs a JSON `body`, with the same retries as [`post`](Self::post).
+    pub fn post_body(&self, body: &[u8]) -> Result<u16, Box<dyn std::error::Error>> {
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(self.timeout))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        let mut wait = self.backoff;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let mut request = agent.post(&self.url).header("Content-Type", "application/json");
+            for (name, value) in &self.headers {
+                request = request.header(name, value);
+            }
+            let error = match request.send(body) {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    if response.status().is_success() {
+                        return Ok(status);
+                    }
+                    let error = format!("{} returned HTTP {}", self.url, status);
+                    if status != 429 && !response.status().is_server_error() {
+                        return Err(error.into());
+                    }
+                    error
+                }
+                Err(e) => format!("Failed to reach {}: {}", self.url, e),
+            };
+            if attempt > self.retries {
+                return Err(format!("{} (after {} attempts)", error, attempt).into());
+            }
+            warn!("{}; retrying in {} ms", error, wait.as_millis());
+            thread::sleep(wait);
+            wait *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::finding::Finding;
+    use crate::audit::permissions::audit_permissions::Severity;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    // Serves one request per status in `statuses`; returns the URL and a handle yielding the
+    // raw requests
+    fn serve(statuses: &'static [u16]) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                    request.push_str(&line);
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                request.push_str(&String::from_utf8(body).unwrap());
+                let response = format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_post_retries_server_errors() {
+        let (url, server) = serve(&[503, 204]);
+        let mut report = AuditReport::default();
+        report.findings.push(Finding::fail("a", "/etc/sudoers", Severity::High, "NOPASSWD: ALL"));
+        let webhook = Webhook::new(url).header("X-Token", "abc").backoff(Duration::from_millis(1));
+        assert_eq!(webhook.post(&report).unwrap(), 204);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("POST /hook HTTP/1.1\r\n"));
+        assert!(requests[1].to_ascii_lowercase().contains("x-token: abc\r\n"));
+        let body = requests[1].split("\r\n\r\n").nth(1).unwrap();
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(json["findings"][0]["check"], "a");
+    }
+
+    #[test]
+    fn test_post_gives_up_on_client_errors() {
+        let (url, server) = serve(&[404]);
+        let webhook = Webhook::new(url).backoff(Duration::from_millis(1));
+        let error = webhook.post(&AuditReport::default()).unwrap_err();
+        assert!(error.to_string().ends_with("returned HTTP 404"));
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,83 @@
+// Integration test for auditing an alternate root (`--root`)
+use alhalo::{Audit, Importance, PermissionRules, Target};
+use std::fs;
+use std::os::unix::fs::{PermissionsExt, symlink};
+use std::path::Path;
+use std::thread;
+
+#[test]
+fn test_audit_mounted_image() {
+    let image = tempfile::tempdir().expect("Failed to create image dir");
+    let root = image.path();
+    fs::create_dir_all(root.join("etc/ssh")).unwrap();
+    fs::write(root.join("etc/hostname"), "golden\n").unwrap();
+    fs::write(root.join("etc/passwd"), "root:x:0:0:root:/root:/bin/sh\nweb:x:0:0::/srv:/bin/sh\n").unwrap();
+    fs::write(root.join("etc/shadow"), "root:*:19000::::::\n").unwrap();
+    fs::set_permissions(root.join("etc/shadow"), fs::Permissions::from_mode(0o644)).unwrap();
+    // A config directory linked with an absolute target resolves inside the image
+    symlink("/etc/ssh", root.join("etc/ssh-link")).unwrap();
+    fs::write(root.join("etc/ssh/sshd_config"), "").unwrap();
+    fs::set_permissions(root.join("etc/ssh/sshd_config"), fs::Permissions::from_mode(0o666)).unwrap();
+
+    let (rule, _status) = PermissionRules::new("/etc/ssh-link/sshd_config".into(), 0o600, Importance::High);
+    let report = Audit::new()
+        .target(Target::User)
+        .rule(rule)
+        .root(root)
+        .run()
+        .expect("Audit of the image should run");
+
+    let metadata = report.metadata.as_ref().unwrap();
+    assert_eq!(metadata.hostname, "golden");
+    assert_eq!(metadata.root.as_deref(), Some(root));
+    let shadow = report
+        .permissions
+        .iter()
+        .find(|r| r.path.as_os_str() == "/etc/shadow")
+        .expect("/etc/shadow is reported as inside the image");
+    assert_eq!(shadow.found_mode, 0o644);
+    let linked = report
+        .permissions
+        .iter()
+        .find(|r| r.path.as_os_str() == "/etc/ssh-link/sshd_config")
+        .unwrap();
+    assert_eq!(linked.found_mode, 0o666);
+    assert!(report.findings.iter().any(|f| f.check == "passwd-duplicate-uid0" && f.is_failure()));
+    assert!(alhalo::audit::sysroot::root().is_none(), "the root is only set during the run");
+
+    assert!(Audit::new().target(Target::User).root(root.join("missing")).run().is_err());
+}
+
+#[test]
+fn test_concurrent_audits_of_different_roots() {
+    let images: Vec<_> = (0..2).map(|_| tempfile::tempdir().unwrap()).collect();
+    for (i, image) in images.iter().enumerate() {
+        fs::create_dir_all(image.path().join("srv/data")).unwrap();
+        fs::create_dir(image.path().join("etc")).unwrap();
+        fs::write(image.path().join("etc/hostname"), format!("image-{}\n", i)).unwrap();
+        // Each image has a different number of files, walked on several threads
+        for n in 0..(i + 1) * 20 {
+            fs::write(image.path().join(format!("srv/data/{}", n)), "").unwrap();
+        }
+    }
+    let audit = |root: &Path| {
+        let mut rule = PermissionRules::new("/srv".into(), 0o755, Importance::Low).0;
+        rule.recursive = true;
+        Audit::new().rule(rule).jobs(4).root(root).run().unwrap()
+    };
+    thread::scope(|scope| {
+        let runs: Vec<_> = (0..8)
+            .map(|i| {
+                let root = images[i % 2].path();
+                (i % 2, scope.spawn(move || audit(root)))
+            })
+            .collect();
+        for (i, run) in runs {
+            let report = run.join().unwrap();
+            assert_eq!(report.metadata.as_ref().unwrap().hostname, format!("image-{}", i));
+            let files = report.permissions.iter().filter(|r| r.path.starts_with("/srv/data/")).count();
+            assert_eq!(files, (i + 1) * 20, "audit of image {} read only its own root", i);
+        }
+    });
+    assert!(alhalo::audit::sysroot::root().is_none());
+}
@@ -0,0 +1,45 @@
+// Integration test for the async audit API (feature `async`)
+#![cfg(feature = "async")]
+use alhalo::async_audit::AsyncAuditor;
+use alhalo::{Audit, Importance, PermissionRules};
+use std::fs;
+
+#[test]
+fn test_rooted_audits_run_concurrently() {
+    let image = tempfile::tempdir().expect("Failed to create image dir");
+    fs::create_dir(image.path().join("etc")).unwrap();
+    fs::write(image.path().join("etc/hostname"), "golden\n").unwrap();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let auditor = AsyncAuditor::new(4);
+
+    let rule = || PermissionRules::new("/etc/hostname".into(), 0o644, Importance::Low).0;
+    let reports = runtime.block_on(async {
+        let mut tasks = Vec::new();
+        for i in 0..8 {
+            let audit = match i % 2 {
+                0 => Audit::new().rule(rule()).root(image.path()),
+                _ => Audit::new().rule(rule()),
+            };
+            let auditor = auditor.clone();
+            tasks.push(tokio::spawn(async move {
+                auditor.run(audit).await.map_err(|e| e.to_string())
+            }));
+        }
+        let mut reports = Vec::new();
+        for task in tasks {
+            reports.push(task.await.unwrap().expect("audit should run"));
+        }
+        reports
+    });
+
+    for (i, report) in reports.iter().enumerate() {
+        let metadata = report.metadata.as_ref().unwrap();
+        // Every audit saw only its own root
+        assert_eq!(metadata.hostname == "golden", i % 2 == 0, "audit {}", i);
+        assert_eq!(metadata.root.is_some(), i % 2 == 0);
+    }
+    assert!(alhalo::audit::sysroot::root().is_none());
+}
@@ -1,5 +1,5 @@
 // Integration test for TOML config loading in HALO
-use alhalo::toml_permissions;
+use alhalo::{load_config, toml_permissions};
 use std::io::Write;
 
 #[test]
@@ -22,3 +22,35 @@ fn test_valid_toml_config_loading() {
     assert!(!config.is_empty());
     assert_eq!(config[0].expected_mode, 0o644);
 }
+
+#[test]
+fn test_valid_yaml_config_loading() {
+    let target_file = tempfile::NamedTempFile::new().expect("Failed to create target file");
+    let target_path = target_file.path().to_str().unwrap();
+
+    let yaml_content = format!(
+        "perm_rules:\n  - path: \"{}\"\n    expected_mode: \"u=rw,g=r,o=\"\n    importance: High\n",
+        target_path
+    );
+    let mut yaml_file = tempfile::Builder::new()
+        .suffix(".yaml")
+        .tempfile()
+        .expect("Failed to create YAML file");
+    write!(yaml_file, "{}", yaml_content).expect("Failed to write YAML");
+    let config = load_config(yaml_file.path()).expect("Should load YAML config");
+    let results = config.permission_results().expect("Should run YAML rules");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].expected_mode, 0o640);
+}
+
+#[test]
+fn test_example_configs_agree() {
+    let toml = load_config("examples/toml_configs/permissions_config.toml".as_ref()).unwrap();
+    let yaml = load_config("examples/yaml_configs/permissions_config.yaml".as_ref()).unwrap();
+    assert_eq!(toml.perm_rules.len(), yaml.perm_rules.len());
+    for (t, y) in toml.perm_rules.iter().zip(&yaml.perm_rules) {
+        assert_eq!((&t.path, t.mode().unwrap()), (&y.path, y.mode().unwrap()));
+    }
+    assert_eq!(toml.owner_rules.len(), yaml.owner_rules.len());
+    assert_eq!(toml.sysctl_rules, yaml.sysctl_rules);
+}
@@ -0,0 +1,45 @@
+// Integration test for collecting fleet reports over SSH from the real `check --format json`
+#![cfg(feature = "cli")]
+use alhalo::fleet::Host;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+#[test]
+fn test_collect_over_ssh_parses_check_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let bin = dir.path().join("bin");
+    fs::create_dir(&bin).unwrap();
+    // Stands in for ssh: drops the options and destination and runs the rest through the
+    // shell, as the remote sshd does
+    let ssh = bin.join("ssh");
+    fs::write(&ssh, "#!/bin/sh\nwhile [ \"$1\" != \"--\" ]; do shift; done\nshift\nexec sh -c \"$*\"\n").unwrap();
+    fs::set_permissions(&ssh, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let secret = dir.path().join("my secret");
+    fs::write(&secret, "").unwrap();
+    fs::set_permissions(&secret, fs::Permissions::from_mode(0o644)).unwrap();
+    let config = dir.path().join("web rules.toml");
+    fs::write(
+        &config,
+        format!(
+            "[[perm_rules]]\npath = \"{}\"\nexpected_mode = 600\nimportance = \"High\"\nrecursive = false\n",
+            secret.display()
+        ),
+    )
+    .unwrap();
+
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap_or_default());
+    // SAFETY: this test binary runs no other test that reads the environment
+    unsafe { std::env::set_var("PATH", path) };
+    let host = Host {
+        name: "web-1".to_string(),
+        address: Some("ops@web-1".to_string()),
+        config: Some(config),
+        command: Some(env!("CARGO_BIN_EXE_alhalo").to_string()),
+        ..Default::default()
+    };
+    let report = host.collect().unwrap();
+    assert_eq!(report.permissions.len(), 1);
+    assert_eq!(report.permissions[0].path, secret);
+    assert_eq!(report.failure_count(), 1);
+}
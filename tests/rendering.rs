@@ -1,5 +1,9 @@
 // Integration tests for output rendering in HALO
-use alhalo::{DataList, DataMap, filter, render_csv, render_json, render_text};
+use alhalo::{
+    Finding, Renderable, Severity,
+    CsvOptions, DataList, DataMap, GroupBy, LineRegex, MatchOn, OutputFormat, ParsedData, RenderOptions, SortKey, filter, filter_lines, render_csv, render_csv_with,
+    render_html, render_json, render_jsonl, render_markdown, render_rows, render_text,
+};
 
 fn sample_data() -> DataList {
     let mut map = DataMap::new();
@@ -16,6 +20,71 @@ fn test_render_json() {
     assert!(json.contains("value1"));
 }
 
+#[test]
+fn test_render_jsonl() {
+    let mut data = sample_data();
+    data.push(sample_data().remove(0));
+    let jsonl = render_jsonl(&data).expect("Should render JSON Lines");
+    assert_eq!(jsonl, "{\"key1\":\"value1\",\"key2\":\"value2\"}\n".repeat(2));
+}
+
+fn result_rows() -> DataList {
+    [("/etc/b", "Low", "Fail"), ("/etc/a", "Critical", "Fail"), ("/etc/c", "None", "Pass"), ("/etc/d", "Low", "Strict")]
+        .iter()
+        .map(|(path, severity, status)| {
+            let mut map = DataMap::new();
+            map.insert("path".to_string(), path.to_string());
+            map.insert("severity".to_string(), severity.to_string());
+            map.insert("status".to_string(), status.to_string());
+            map
+        })
+        .collect()
+}
+
+#[test]
+fn test_render_rows_sorted_and_grouped() {
+    let paths = |csv: &str| csv.lines().skip(1).map(|l| l.split(',').next().unwrap().to_string()).collect::<Vec<_>>();
+    let by_path = render_rows(&result_rows(), OutputFormat::Csv, &RenderOptions::default().sort(SortKey::Path)).unwrap();
+    assert_eq!(paths(&by_path), ["/etc/a", "/etc/b", "/etc/c", "/etc/d"]);
+
+    let by_status = RenderOptions::default().sort(SortKey::Status);
+    let rows: Vec<String> = by_status.arrange(result_rows()).remove(0).1.iter().map(|r| r["path"].clone()).collect();
+    assert_eq!(rows, ["/etc/b", "/etc/a", "/etc/d", "/etc/c"]);
+
+    // Groups in severity order, sorted by path within each group
+    let options = RenderOptions::default().sort(SortKey::Path).group_by(GroupBy::Severity);
+    let csv = render_rows(&result_rows(), OutputFormat::Csv, &options).unwrap();
+    assert!(csv.starts_with("group,path,severity,status\nCritical,/etc/a"));
+    assert_eq!(paths(&csv), ["Critical", "Low", "Low", "None"]);
+    assert!(csv.contains("Low,/etc/b,Low,Fail\nLow,/etc/d,Low,Strict\n"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&render_rows(&result_rows(), OutputFormat::Json, &options).unwrap()).unwrap();
+    assert_eq!(json["Low"][1]["path"], "/etc/d");
+    let markdown = render_rows(&result_rows(), OutputFormat::Markdown, &options).unwrap();
+    assert!(markdown.starts_with("## Critical\n\n| path |"));
+}
+
+#[test]
+fn test_render_to_matches_render() {
+    let results = vec![
+        Finding::fail("a", "/etc/a,b", Severity::High, "bad"),
+        Finding::pass("b", "/etc/b", "ok"),
+    ];
+    for format in OutputFormat::ALL {
+        let mut out = Vec::new();
+        results.render_to(format.clone(), &mut out).unwrap();
+        let (written, rendered) = (String::from_utf8(out).unwrap(), results.render(format.clone()).unwrap());
+        if matches!(format, OutputFormat::Jsonl) {
+            // Streamed lines keep field order; compare them as JSON
+            let parse = |s: &str| s.lines().map(|l| serde_json::from_str(l).unwrap()).collect::<Vec<serde_json::Value>>();
+            assert_eq!(parse(&written), parse(&rendered));
+        } else {
+            assert_eq!(written, rendered);
+        }
+    }
+}
+
 #[test]
 fn test_render_csv() {
     let data = sample_data();
@@ -25,6 +94,18 @@ fn test_render_csv() {
     assert!(csv.contains("value1,value2"));
 }
 
+#[test]
+fn test_render_csv_quoting() {
+    let mut map = DataMap::new();
+    map.insert("path".to_string(), "/srv/a,b".to_string());
+    map.insert("error".to_string(), "line one\nsaid \"no\"".to_string());
+    let csv = render_csv(&vec![map.clone()], &[]).unwrap();
+    assert_eq!(csv, "path,error\n\"/srv/a,b\",\"line one\nsaid \"\"no\"\"\"\n");
+
+    let tsv = render_csv_with(&vec![map], &[], &CsvOptions { delimiter: b'\t', headers: false }).unwrap();
+    assert_eq!(tsv, "/srv/a,b\t\"line one\nsaid \"\"no\"\"\"\n");
+}
+
 #[test]
 fn test_render_text() {
     let data = sample_data();
@@ -33,6 +114,30 @@ fn test_render_text() {
     assert!(text.contains("key2: value2"));
 }
 
+#[test]
+fn test_render_markdown() {
+    let mut data = sample_data();
+    data[0].insert("key3".to_string(), "a|b".to_string());
+    let md = render_markdown(&data, &[]).expect("Should render Markdown");
+    assert!(md.starts_with("| key1 | key2 | key3 |\n| --- | --- | --- |\n"));
+    assert!(md.contains("| value1 | value2 | a\\|b |"));
+}
+
+#[test]
+fn test_render_html() {
+    let mut data = sample_data();
+    let mut second = DataMap::new();
+    second.insert("key1".to_string(), "<script>".to_string());
+    second.insert("severity".to_string(), "High".to_string());
+    data.push(second);
+    let html = render_html(&data, &[]).expect("Should render HTML");
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    // Columns are the union of keys, so the second row's severity gets a column
+    assert!(html.contains("<th>key1</th><th>key2</th><th>severity</th>"));
+    assert!(html.contains("<td>&lt;script&gt;</td><td></td><td class=\"severity-high\">High</td>"));
+    assert!(!html.contains("<td><script>"));
+}
+
 #[test]
 fn test_filter() {
     let data = sample_data();
@@ -40,3 +145,36 @@ fn test_filter() {
     assert_eq!(filtered[0].len(), 1);
     assert!(filtered[0].contains_key("key1"));
 }
+
+#[test]
+fn test_filter_lines_regex() {
+    let cpu = |n: &str| DataMap::from([
+        ("processor".to_string(), n.to_string()),
+        ("model name".to_string(), "Xeon".to_string()),
+        ("model".to_string(), "85".to_string()),
+    ]);
+    let data = vec![cpu("0"), cpu("1")];
+    let keys = LineRegex::new(&["^model n".to_string()], MatchOn::Keys).unwrap();
+    let filtered = filter_lines(&data, &["processor".to_string()], Some(&keys));
+    let first: Vec<&str> = filtered[0].keys().map(String::as_str).collect();
+    assert_eq!(first, ["processor", "model name"]);
+
+    // Records without a matching pair are dropped
+    let values = LineRegex::new(&["^8".to_string(), "nomatch".to_string()], MatchOn::Values).unwrap();
+    let mut data = data;
+    data.push(DataMap::from([("other".to_string(), "x".to_string())]));
+    let filtered = filter_lines(&data, &[], Some(&values));
+    assert_eq!(filtered.len(), 2);
+    assert_eq!(filtered[0].keys().collect::<Vec<_>>(), ["model"]);
+    let both = LineRegex::new(&["^other$|^x$".to_string()], MatchOn::Both).unwrap();
+    assert_eq!(filter_lines(&data, &[], Some(&both)).len(), 1);
+
+    // Every format renders the filtered pairs
+    let parsed = ParsedData::with_filter(data, Vec::new()).with_regex(keys);
+    let csv = parsed.render(OutputFormat::Csv).unwrap();
+    assert_eq!(csv.lines().next(), Some("model name"));
+    assert!(!parsed.render(OutputFormat::Json).unwrap().contains("processor"));
+
+    let error = LineRegex::new(&["(".to_string()], MatchOn::Keys).unwrap_err();
+    assert!(error.to_string().starts_with("Invalid regex '('"));
+}